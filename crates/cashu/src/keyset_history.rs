@@ -0,0 +1,257 @@
+//! Keyset history
+//!
+//! A mint that rotated its keysets many times would make a restoring wallet ask
+//! for the keys of each keyset separately. The keyset history bundles every
+//! keyset of the mint, active and retired, with its keys and validity range in
+//! one response. A mint with an identity key signs it, so a wallet can cache it
+//! and trust it later without asking the mint again.
+//!
+//! The signed message is the compact JSON object
+//! `{"created":<u64>,"keysets":[<keyset>,...]}`, with keys in this order and no
+//! whitespace. Every keyset is serialized as
+//! `{"id":"<id>","unit":"<unit>","active":<bool>,"input_fee_ppk":<u64>,"valid_from":<u64>,"final_expiry":<u64>,"keys":{"<amount>":"<pubkey>",...}}`,
+//! with the `final_expiry` key left out when the keyset does not expire and the
+//! keys ordered by amount. The signature is a BIP340 Schnorr signature over the
+//! SHA256 of the message, made with the key published as the `pubkey` of the
+//! mint info.
+
+use std::str::FromStr;
+
+use bitcoin::secp256k1::schnorr::Signature;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::nuts::nut01::{Keys, PublicKey, SecretKey};
+use crate::nuts::nut02::{self, Id, KeySet, KeySetInfo};
+use crate::nuts::CurrencyUnit;
+
+/// Keyset history Error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Signature not provided
+    #[error("Keyset history signature not provided")]
+    SignatureMissing,
+    /// Invalid signature
+    #[error("Keyset history signature invalid")]
+    InvalidSignature,
+    /// NUT01 Error
+    #[error(transparent)]
+    NUT01(#[from] crate::nuts::nut01::Error),
+    /// NUT02 Error
+    #[error(transparent)]
+    NUT02(#[from] nut02::Error),
+    /// Json Error
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Keyset of the mint with its keys and validity range
+///
+/// Fields are declared in the order of the canonical serialization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "swagger", derive(utoipa::ToSchema))]
+pub struct KeysetHistoryEntry {
+    /// Keyset [`Id`]
+    #[cfg_attr(feature = "swagger", schema(value_type = String))]
+    pub id: Id,
+    /// Keyset [`CurrencyUnit`]
+    pub unit: CurrencyUnit,
+    /// Whether the mint still signs with the keyset
+    pub active: bool,
+    /// Input Fee PPK
+    #[serde(default)]
+    pub input_fee_ppk: u64,
+    /// Unix time the keyset is valid from
+    pub valid_from: u64,
+    /// Unix time after which the keyset is no longer valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_expiry: Option<u64>,
+    /// Keyset [`Keys`]
+    pub keys: Keys,
+}
+
+impl KeysetHistoryEntry {
+    /// Verify the keyset id matches keys
+    pub fn verify_id(&self) -> Result<(), nut02::Error> {
+        KeySet::from(self.clone()).verify_id()
+    }
+}
+
+impl From<KeysetHistoryEntry> for KeySet {
+    fn from(entry: KeysetHistoryEntry) -> Self {
+        Self {
+            id: entry.id,
+            unit: entry.unit,
+            keys: entry.keys,
+            final_expiry: entry.final_expiry,
+        }
+    }
+}
+
+impl From<KeysetHistoryEntry> for KeySetInfo {
+    fn from(entry: KeysetHistoryEntry) -> Self {
+        Self {
+            id: entry.id,
+            unit: entry.unit,
+            active: entry.active,
+            input_fee_ppk: entry.input_fee_ppk,
+            final_expiry: entry.final_expiry,
+        }
+    }
+}
+
+/// Every keyset of a mint, active and retired
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "swagger", derive(utoipa::ToSchema))]
+pub struct KeysetHistory {
+    /// Unix time the history was made
+    pub created: u64,
+    /// Keysets of the mint, oldest first
+    pub keysets: Vec<KeysetHistoryEntry>,
+    /// Schnorr signature of the mint identity key over the history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// Canonical message signed for [`KeysetHistory`]
+#[derive(Serialize)]
+struct SignedKeysetHistory<'a> {
+    created: u64,
+    keysets: &'a [KeysetHistoryEntry],
+}
+
+impl KeysetHistory {
+    /// Create an unsigned [`KeysetHistory`]
+    ///
+    /// Keysets are ordered by the time they became valid.
+    pub fn new(created: u64, mut keysets: Vec<KeysetHistoryEntry>) -> Self {
+        keysets.sort_by(|a, b| a.valid_from.cmp(&b.valid_from).then(a.id.cmp(&b.id)));
+
+        Self {
+            created,
+            keysets,
+            signature: None,
+        }
+    }
+
+    /// Constructs the message to be signed
+    pub fn msg_to_sign(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(&SignedKeysetHistory {
+            created: self.created,
+            keysets: &self.keysets,
+        })?)
+    }
+
+    /// Sign the history with the mint identity key
+    pub fn sign(&mut self, secret_key: &SecretKey) -> Result<(), Error> {
+        let signature: Signature = secret_key.sign(&self.msg_to_sign()?)?;
+
+        self.signature = Some(signature.to_string());
+
+        Ok(())
+    }
+
+    /// Verify the signature on the history
+    pub fn verify_signature(&self, pubkey: &PublicKey) -> Result<(), Error> {
+        let signature = self.signature.as_ref().ok_or(Error::SignatureMissing)?;
+
+        let signature = Signature::from_str(signature).map_err(|_| Error::InvalidSignature)?;
+
+        pubkey
+            .verify(&self.msg_to_sign()?, &signature)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        Ok(())
+    }
+
+    /// Verify the id of every keyset matches its keys
+    pub fn verify_keyset_ids(&self) -> Result<(), Error> {
+        for keyset in &self.keysets {
+            keyset.verify_id()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::Amount;
+
+    fn entry(valid_from: u64, active: bool) -> KeysetHistoryEntry {
+        let keys = Keys::new(
+            (0..4)
+                .map(|order| {
+                    (
+                        Amount::from(2u64.pow(order)),
+                        SecretKey::generate().public_key(),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>(),
+        );
+
+        KeysetHistoryEntry {
+            id: Id::v1_from_keys(&keys),
+            unit: CurrencyUnit::Sat,
+            active,
+            input_fee_ppk: 100,
+            valid_from,
+            final_expiry: None,
+            keys,
+        }
+    }
+
+    #[test]
+    fn test_keyset_history_signature() {
+        let secret_key = SecretKey::generate();
+
+        let mut history = KeysetHistory::new(
+            1_700_000_600,
+            vec![entry(1_700_000_300, true), entry(1_700_000_000, false)],
+        );
+
+        // Oldest keyset first
+        assert_eq!(history.keysets[0].valid_from, 1_700_000_000);
+        assert!(history.verify_keyset_ids().is_ok());
+
+        history.sign(&secret_key).unwrap();
+        assert!(history.verify_signature(&secret_key.public_key()).is_ok());
+
+        // Signature survives a serialization round trip
+        let json = serde_json::to_string(&history).unwrap();
+        let decoded: KeysetHistory = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify_signature(&secret_key.public_key()).is_ok());
+
+        // Signature does not hold for another key or a changed keyset
+        assert!(matches!(
+            history.verify_signature(&SecretKey::generate().public_key()),
+            Err(Error::InvalidSignature)
+        ));
+        let mut changed = history.clone();
+        changed.keysets[0].active = true;
+        assert!(matches!(
+            changed.verify_signature(&secret_key.public_key()),
+            Err(Error::InvalidSignature)
+        ));
+
+        let mut unsigned = history.clone();
+        unsigned.signature = None;
+        assert!(matches!(
+            unsigned.verify_signature(&secret_key.public_key()),
+            Err(Error::SignatureMissing)
+        ));
+    }
+
+    #[test]
+    fn test_keyset_history_ids() {
+        let mut history = KeysetHistory::new(1_700_000_600, vec![entry(1_700_000_000, true)]);
+        assert!(history.verify_keyset_ids().is_ok());
+
+        // Keys swapped for those of another keyset
+        history.keysets[0].keys = entry(1_700_000_000, true).keys;
+        assert!(history.verify_keyset_ids().is_err());
+    }
+}
@@ -4,16 +4,24 @@
 
 pub mod amount;
 pub mod dhke;
+pub mod keyset_history;
 pub mod mint_url;
 pub mod nuts;
+#[cfg(feature = "paper-backup")]
+pub mod paper_backup;
+pub mod quote_timestamps;
 pub mod secret;
 pub mod util;
 
 pub use lightning_invoice::{self, Bolt11Invoice};
 
 pub use self::amount::Amount;
+pub use self::keyset_history::KeysetHistory;
 pub use self::mint_url::MintUrl;
 pub use self::nuts::*;
+#[cfg(feature = "paper-backup")]
+pub use self::paper_backup::PaperBackup;
+pub use self::quote_timestamps::QuoteTimestamps;
 pub use self::util::SECP256K1;
 
 #[cfg(feature = "mint")]
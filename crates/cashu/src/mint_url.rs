@@ -52,33 +52,18 @@ impl MintUrl {
     fn format_url(url: &str) -> Result<String, Error> {
         ensure_cdk!(!url.is_empty(), Error::InvalidUrl);
 
-        let url = url.trim_end_matches('/');
-        // https://URL.com/path/TO/resource -> https://url.com/path/TO/resource
-        let protocol = url
-            .split("://")
-            .nth(0)
-            .ok_or(Error::InvalidUrl)?
-            .to_lowercase();
-        let host = url
-            .split("://")
-            .nth(1)
-            .ok_or(Error::InvalidUrl)?
-            .split('/')
-            .nth(0)
-            .ok_or(Error::InvalidUrl)?
-            .to_lowercase();
-        let path = url
-            .split("://")
-            .nth(1)
-            .ok_or(Error::InvalidUrl)?
-            .split('/')
-            .skip(1)
-            .collect::<Vec<&str>>()
-            .join("/");
-        let mut formatted_url = format!("{protocol}://{host}");
-        if !path.is_empty() {
-            formatted_url.push_str(&format!("/{path}"));
+        // https://URL.com:443/path/TO/resource/ -> https://url.com/path/TO/resource
+        // Parsing lowercases the scheme and host, normalizes IPv6 literals and
+        // drops the default port of the scheme
+        let url = Url::parse(url.trim())?;
+        let host = url.host_str().ok_or(Error::InvalidUrl)?;
+
+        let mut formatted_url = format!("{}://{host}", url.scheme());
+        if let Some(port) = url.port() {
+            formatted_url.push_str(&format!(":{port}"));
         }
+        formatted_url.push_str(url.path().trim_end_matches('/'));
+
         Ok(formatted_url)
     }
 
@@ -222,6 +207,50 @@ mod tests {
         assert_eq!(result.to_string(), "http://example.com/api/test/url");
     }
 
+    #[test]
+    fn test_ports_and_ipv6() {
+        let url = MintUrl::from_str("https://Mint.example.com:443/").unwrap();
+        assert_eq!(url.to_string(), "https://mint.example.com");
+
+        let url = MintUrl::from_str("http://mint.example.com:3338/").unwrap();
+        assert_eq!(url.to_string(), "http://mint.example.com:3338");
+        assert_eq!(
+            url.join_paths(&["v1", "keys"]).unwrap().to_string(),
+            "http://mint.example.com:3338/v1/keys"
+        );
+
+        let url = MintUrl::from_str("http://[2001:DB8:0:0::1]:3338").unwrap();
+        assert_eq!(url.to_string(), "http://[2001:db8::1]:3338");
+        assert_eq!(
+            url.join_paths(&["v1", "ws"]).unwrap().to_string(),
+            "http://[2001:db8::1]:3338/v1/ws"
+        );
+
+        let url = MintUrl::from_str("http://[::1]:3338/").unwrap();
+        assert_eq!(url, MintUrl::from_str("HTTP://[0:0::1]:3338").unwrap());
+    }
+
+    #[test]
+    fn test_path_prefix() {
+        let url = MintUrl::from_str("https://Example.com:8443/cashu/").unwrap();
+        assert_eq!(url.to_string(), "https://example.com:8443/cashu");
+        assert_eq!(
+            url.join_paths(&["v1", "info"]).unwrap().to_string(),
+            "https://example.com:8443/cashu/v1/info"
+        );
+
+        let mut ws_url = url.join_paths(&["v1", "ws"]).unwrap();
+        ws_url.set_scheme("wss").unwrap();
+        assert_eq!(ws_url.to_string(), "wss://example.com:8443/cashu/v1/ws");
+    }
+
+    #[test]
+    fn test_invalid_urls() {
+        assert!(MintUrl::from_str("").is_err());
+        assert!(MintUrl::from_str("mint.example.com").is_err());
+        assert!(MintUrl::from_str("http://").is_err());
+    }
+
     #[test]
     fn test_mint_url_slash_eqality() {
         let mint_url_with_slash_str = "https://mint.minibits.cash/Bitcoin/";
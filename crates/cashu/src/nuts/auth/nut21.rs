@@ -161,6 +161,12 @@ pub enum RoutePath {
     /// Bolt12 Quote
     #[serde(rename = "/v1/melt/bolt12")]
     MeltBolt12,
+    /// Keysend Melt Quote
+    #[serde(rename = "/v1/melt/quote/keysend")]
+    MeltQuoteKeysend,
+    /// Keysend Melt
+    #[serde(rename = "/v1/melt/keysend")]
+    MeltKeysend,
 }
 
 /// Returns [`RoutePath`]s that match regex
@@ -237,11 +243,12 @@ mod tests {
         let paths = matching_route_paths(".*/quote/.*").unwrap();
 
         // Should match only quote paths
-        assert_eq!(paths.len(), 4);
+        assert_eq!(paths.len(), 5);
         assert!(paths.contains(&RoutePath::MintQuoteBolt11));
         assert!(paths.contains(&RoutePath::MeltQuoteBolt11));
         assert!(paths.contains(&RoutePath::MintQuoteBolt12));
         assert!(paths.contains(&RoutePath::MeltQuoteBolt12));
+        assert!(paths.contains(&RoutePath::MeltQuoteKeysend));
 
         // Should not match non-quote paths
         assert!(!paths.contains(&RoutePath::MintBolt11));
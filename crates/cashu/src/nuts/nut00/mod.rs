@@ -653,6 +653,13 @@ pub enum PaymentMethod {
     Custom(String),
 }
 
+impl PaymentMethod {
+    /// Keysend payments to a lightning node id, a [`PaymentMethod::Custom`] method
+    pub fn keysend() -> Self {
+        Self::Custom("keysend".to_string())
+    }
+}
+
 impl FromStr for PaymentMethod {
     type Err = Error;
     fn from_str(value: &str) -> Result<Self, Self::Err> {
@@ -695,7 +702,7 @@ impl<'de> Deserialize<'de> for PaymentMethod {
 
 /// PreMint
 #[cfg(feature = "wallet")]
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PreMint {
     /// Blinded message
     pub blinded_message: BlindedMessage,
@@ -723,7 +730,7 @@ impl PartialOrd for PreMint {
 
 /// Premint Secrets
 #[cfg(feature = "wallet")]
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PreMintSecrets {
     /// Secrets
     pub secrets: Vec<PreMint>,
@@ -1024,6 +1031,10 @@ mod tests {
             PaymentMethod::from_str("CUSTOM").unwrap(),
             PaymentMethod::Custom("custom".to_string())
         );
+        assert_eq!(
+            PaymentMethod::from_str("keysend").unwrap(),
+            PaymentMethod::keysend()
+        );
 
         // Test serialization/deserialization consistency
         let methods = vec![
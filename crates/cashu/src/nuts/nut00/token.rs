@@ -3,6 +3,8 @@
 //! <https://github.com/cashubtc/nuts/blob/main/00.md>
 
 use std::collections::HashMap;
+#[cfg(feature = "keyset-hints")]
+use std::collections::HashSet;
 use std::fmt;
 use std::str::FromStr;
 
@@ -13,6 +15,8 @@ use serde::{Deserialize, Serialize};
 use super::{Error, Proof, ProofV3, ProofV4, Proofs};
 use crate::mint_url::MintUrl;
 use crate::nut02::ShortKeysetId;
+#[cfg(feature = "keyset-hints")]
+use crate::nuts::Keys;
 use crate::nuts::{CurrencyUnit, Id};
 use crate::{ensure_cdk, Amount, KeySetInfo};
 
@@ -65,6 +69,17 @@ impl Token {
         })
     }
 
+    /// Embed keyset hints for offline verification
+    ///
+    /// Only supported by V4 tokens, V3 tokens are returned unchanged.
+    #[cfg(feature = "keyset-hints")]
+    pub fn with_keyset_hints(self, keys: &HashMap<Id, Keys>) -> Self {
+        match self {
+            Self::TokenV3(token) => Self::TokenV3(token),
+            Self::TokenV4(token) => Self::TokenV4(token.with_keyset_hints(keys)),
+        }
+    }
+
     /// Proofs in [`Token`]
     pub fn proofs(&self, mint_keysets: &[KeySetInfo]) -> Result<Proofs, Error> {
         match self {
@@ -404,6 +419,33 @@ impl TokenV4 {
         &self.unit
     }
 
+    /// Embed keyset hints for offline verification
+    ///
+    /// Adds the public keys of the amounts in the token for every keyset found
+    /// in `keys`, so a receiver can verify the DLEQ proofs without contacting the mint.
+    #[cfg(feature = "keyset-hints")]
+    pub fn with_keyset_hints(mut self, keys: &HashMap<Id, Keys>) -> Self {
+        for t in self.token.iter_mut() {
+            if let Some(keyset_keys) = keys
+                .iter()
+                .find(|(id, _)| ShortKeysetId::from(**id) == t.keyset_id)
+                .map(|(_, keyset_keys)| keyset_keys)
+            {
+                let amounts: HashSet<Amount> = t.proofs.iter().map(|p| p.amount).collect();
+
+                t.keys = Some(Keys::new(
+                    keyset_keys
+                        .iter()
+                        .filter(|(amount, _)| amounts.contains(amount))
+                        .map(|(amount, pubkey)| (*amount, *pubkey))
+                        .collect(),
+                ));
+            }
+        }
+
+        self
+    }
+
     /// Serialize the token to raw binary
     pub fn to_raw_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut prefix = b"crawB".to_vec();
@@ -476,6 +518,8 @@ impl TryFrom<TokenV3> for TokenV4 {
             .map(|(id, proofs)| TokenV4Token {
                 keyset_id: id,
                 proofs,
+                #[cfg(feature = "keyset-hints")]
+                keys: None,
             })
             .collect();
 
@@ -501,6 +545,14 @@ pub struct TokenV4Token {
     /// Proofs
     #[serde(rename = "p")]
     pub proofs: Vec<ProofV4>,
+    /// Keyset public keys for the amounts of the proofs
+    ///
+    /// Optional hint for verifying the DLEQ proofs offline. The keys are not
+    /// trusted until they are confirmed with the mint. The `k` field is not part
+    /// of NUT-00, so it is only read and written with the `keyset-hints` feature.
+    #[cfg(feature = "keyset-hints")]
+    #[serde(rename = "k", default, skip_serializing_if = "Option::is_none")]
+    pub keys: Option<Keys>,
 }
 
 fn serialize_v4_keyset_id<S>(keyset_id: &ShortKeysetId, serializer: S) -> Result<S::Ok, S::Error>
@@ -526,6 +578,8 @@ impl TokenV4Token {
         Self {
             keyset_id: short_id,
             proofs: proofs.into_iter().map(|p| p.into()).collect(),
+            #[cfg(feature = "keyset-hints")]
+            keys: None,
         }
     }
 }
@@ -576,6 +630,32 @@ mod tests {
         assert_eq!(token_data, token);
     }
 
+    #[cfg(feature = "keyset-hints")]
+    #[test]
+    fn test_token_v4_keyset_hints() {
+        let token_str = "cashuBpGF0gaJhaUgArSaMTR9YJmFwgaNhYQFhc3hAOWE2ZGJiODQ3YmQyMzJiYTc2ZGIwZGYxOTcyMTZiMjlkM2I4Y2MxNDU1M2NkMjc4MjdmYzFjYzk0MmZlZGI0ZWFjWCEDhhhUP_trhpXfStS6vN6So0qWvc2X3O4NfM-Y1HISZ5JhZGlUaGFuayB5b3VhbXVodHRwOi8vbG9jYWxob3N0OjMzMzhhdWNzYXQ=";
+        let token = TokenV4::from_str(token_str).unwrap();
+        assert!(token.token[0].keys.is_none());
+
+        let keyset_id = Id::from_str("00ad268c4d1f5826").unwrap();
+        let pubkey = crate::nuts::SecretKey::generate().public_key();
+        let keys = Keys::new(
+            [(Amount::from(1), pubkey), (Amount::from(2), pubkey)]
+                .into_iter()
+                .collect(),
+        );
+
+        let token = token.with_keyset_hints(&HashMap::from([(keyset_id, keys)]));
+
+        // Only the keys for amounts in the token are embedded
+        let hints = token.token[0].keys.clone().unwrap();
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints.amount_key(Amount::from(1)), Some(pubkey));
+
+        let decoded = TokenV4::from_str(&token.to_string()).unwrap();
+        assert_eq!(decoded, token);
+    }
+
     #[test]
     fn test_token_v4_multi_keyset() {
         let token_str_multi_keysets = "cashuBo2F0gqJhaUgA_9SLj17PgGFwgaNhYQFhc3hAYWNjMTI0MzVlN2I4NDg0YzNjZjE4NTAxNDkyMThhZjkwZjcxNmE1MmJmNGE1ZWQzNDdlNDhlY2MxM2Y3NzM4OGFjWCECRFODGd5IXVW-07KaZCvuWHk3WrnnpiDhHki6SCQh88-iYWlIAK0mjE0fWCZhcIKjYWECYXN4QDEzMjNkM2Q0NzA3YTU4YWQyZTIzYWRhNGU5ZjFmNDlmNWE1YjRhYzdiNzA4ZWIwZDYxZjczOGY0ODMwN2U4ZWVhY1ghAjRWqhENhLSsdHrr2Cw7AFrKUL9Ffr1XN6RBT6w659lNo2FhAWFzeEA1NmJjYmNiYjdjYzY0MDZiM2ZhNWQ1N2QyMTc0ZjRlZmY4YjQ0MDJiMTc2OTI2ZDNhNTdkM2MzZGNiYjU5ZDU3YWNYIQJzEpxXGeWZN5qXSmJjY8MzxWyvwObQGr5G1YCCgHicY2FtdWh0dHA6Ly9sb2NhbGhvc3Q6MzMzOGF1Y3NhdA==";
@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::nut00::{BlindedMessage, CurrencyUnit, PaymentMethod, Proofs};
+use super::nut01::PublicKey;
 use super::ProofsMethods;
 #[cfg(feature = "mint")]
 use crate::quote_id::QuoteId;
@@ -31,6 +32,9 @@ pub enum Error {
     /// Invalid quote id
     #[error("Invalid quote id")]
     InvalidQuote,
+    /// Unknown melt failure reason
+    #[error("Unknown melt failure reason")]
+    UnknownFailureReason,
 }
 
 /// Possible states of a quote
@@ -78,6 +82,64 @@ impl FromStr for QuoteState {
     }
 }
 
+/// Reason the last payment attempt of a melt quote failed
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "swagger", derive(utoipa::ToSchema))]
+pub enum MeltFailureReason {
+    /// The payment backend reported the payment as failed
+    PaymentFailed,
+    /// The payment backend returned an error
+    BackendError,
+    /// Paying the request would cost more than the fee reserve
+    FeeExceeded,
+    /// The exchange rate quote of the payment backend expired
+    ExchangeQuoteExpired,
+    /// The request was already paid
+    AlreadyPaid,
+}
+
+impl fmt::Display for MeltFailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::PaymentFailed => write!(f, "payment_failed"),
+            Self::BackendError => write!(f, "backend_error"),
+            Self::FeeExceeded => write!(f, "fee_exceeded"),
+            Self::ExchangeQuoteExpired => write!(f, "exchange_quote_expired"),
+            Self::AlreadyPaid => write!(f, "already_paid"),
+        }
+    }
+}
+
+impl FromStr for MeltFailureReason {
+    type Err = Error;
+
+    fn from_str(reason: &str) -> Result<Self, Self::Err> {
+        match reason {
+            "payment_failed" => Ok(Self::PaymentFailed),
+            "backend_error" => Ok(Self::BackendError),
+            "fee_exceeded" => Ok(Self::FeeExceeded),
+            "exchange_quote_expired" => Ok(Self::ExchangeQuoteExpired),
+            "already_paid" => Ok(Self::AlreadyPaid),
+            _ => Err(Error::UnknownFailureReason),
+        }
+    }
+}
+
+/// Melt quote request for a keysend payment
+///
+/// Keysend pays a lightning node directly by its node id, without an invoice.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "swagger", derive(utoipa::ToSchema))]
+pub struct MeltQuoteKeysendRequest {
+    /// Node id of the lightning node to pay
+    pub pubkey: PublicKey,
+    /// Amount to pay in msat
+    pub amount: Amount,
+    /// Unit wallet would like to pay with
+    pub unit: CurrencyUnit,
+}
+
 /// Melt Bolt11 Request [NUT-05]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "swagger", derive(utoipa::ToSchema))]
@@ -445,4 +507,23 @@ mod tests {
             _ => panic!("Expected Bolt11 options with amountless = true"),
         }
     }
+
+    #[test]
+    fn test_melt_failure_reason_round_trip() {
+        for reason in [
+            MeltFailureReason::PaymentFailed,
+            MeltFailureReason::BackendError,
+            MeltFailureReason::FeeExceeded,
+            MeltFailureReason::ExchangeQuoteExpired,
+            MeltFailureReason::AlreadyPaid,
+        ] {
+            assert_eq!(
+                MeltFailureReason::from_str(&reason.to_string()).unwrap(),
+                reason
+            );
+            assert_eq!(to_string(&reason).unwrap(), format!("\"{reason}\""));
+        }
+
+        assert!(MeltFailureReason::from_str("unknown").is_err());
+    }
 }
@@ -5,10 +5,13 @@
 #[cfg(feature = "auth")]
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::str::FromStr;
 
+use bitcoin::secp256k1::schnorr::Signature;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
 
-use super::nut01::PublicKey;
+use super::nut01::{PublicKey, SecretKey};
 use super::nut17::SupportedMethods;
 use super::nut19::CachedEndpoint;
 use super::{nut04, nut05, nut15, nut19, MppMethodSettings};
@@ -16,6 +19,26 @@ use super::{nut04, nut05, nut15, nut19, MppMethodSettings};
 use super::{AuthRequired, BlindAuthSettings, ClearAuthSettings, ProtectedEndpoint};
 use crate::CurrencyUnit;
 
+/// NUT06 Error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Signature not provided
+    #[error("Mint info signature not provided")]
+    SignatureMissing,
+    /// Pubkey not provided
+    #[error("Mint info pubkey not provided")]
+    PubkeyMissing,
+    /// Invalid signature
+    #[error("Mint info signature invalid")]
+    InvalidSignature,
+    /// NUT01 Error
+    #[error(transparent)]
+    NUT01(#[from] crate::nuts::nut01::Error),
+    /// Json Error
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
 /// Mint Version
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "swagger", derive(utoipa::ToSchema))]
@@ -105,6 +128,9 @@ pub struct MintInfo {
     /// terms of url service of the mint
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tos_url: Option<String>,
+    /// Schnorr signature over the mint info by the key in `pubkey`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 impl MintInfo {
@@ -219,6 +245,53 @@ impl MintInfo {
         }
     }
 
+    /// Constructs the message to be signed
+    ///
+    /// Only the identity of the mint is signed: `name`, `pubkey`, `description`,
+    /// `description_long`, `contact`, `icon_url`, `urls` and `tos_url`, serialized
+    /// as a JSON array in that order. Settings, `motd` and fields added by newer
+    /// mints are not covered, so the signature does not depend on which fields
+    /// the verifier knows about.
+    pub fn msg_to_sign(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(&(
+            &self.name,
+            &self.pubkey,
+            &self.description,
+            &self.description_long,
+            &self.contact,
+            &self.icon_url,
+            &self.urls,
+            &self.tos_url,
+        ))?)
+    }
+
+    /// Sign [`MintInfo`] with the mint identity key
+    ///
+    /// Sets `pubkey` to the public key of `secret_key` before signing.
+    pub fn sign(&mut self, secret_key: &SecretKey) -> Result<(), Error> {
+        self.pubkey = Some(secret_key.public_key());
+
+        let signature: Signature = secret_key.sign(&self.msg_to_sign()?)?;
+
+        self.signature = Some(signature.to_string());
+
+        Ok(())
+    }
+
+    /// Verify the signature on [`MintInfo`] against its `pubkey`
+    pub fn verify_signature(&self) -> Result<(), Error> {
+        let pubkey = self.pubkey.ok_or(Error::PubkeyMissing)?;
+        let signature = self.signature.as_ref().ok_or(Error::SignatureMissing)?;
+
+        let signature = Signature::from_str(signature).map_err(|_| Error::InvalidSignature)?;
+
+        pubkey
+            .verify(&self.msg_to_sign()?, &signature)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        Ok(())
+    }
+
     /// Get protected endpoints
     #[cfg(feature = "auth")]
     pub fn protected_endpoints(&self) -> HashMap<ProtectedEndpoint, AuthRequired> {
@@ -676,4 +749,70 @@ mod tests {
 
         assert_eq!(info, mint_info);
     }
+
+    #[test]
+    fn test_mint_info_signature() {
+        let secret_key = SecretKey::generate();
+
+        let mut mint_info = MintInfo::new()
+            .name("Cashu mint")
+            .description("A mint")
+            .time(1_700_000_000u64);
+        mint_info.sign(&secret_key).unwrap();
+
+        assert_eq!(mint_info.pubkey, Some(secret_key.public_key()));
+        assert!(mint_info.verify_signature().is_ok());
+
+        // Signature survives a serialization round trip
+        let json = serde_json::to_string(&mint_info).unwrap();
+        let decoded: MintInfo = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify_signature().is_ok());
+
+        // Copied info served with a different name is rejected
+        let tampered = mint_info.clone().name("Phishing mint");
+        assert!(matches!(
+            tampered.verify_signature(),
+            Err(Error::InvalidSignature)
+        ));
+
+        // Info re-signed with another key no longer matches the original pubkey
+        let mut resigned = mint_info.clone();
+        resigned.sign(&SecretKey::generate()).unwrap();
+        assert!(resigned.verify_signature().is_ok());
+        assert_ne!(resigned.pubkey, mint_info.pubkey);
+
+        let unsigned = MintInfo::new().pubkey(secret_key.public_key());
+        assert!(matches!(
+            unsigned.verify_signature(),
+            Err(Error::SignatureMissing)
+        ));
+    }
+
+    #[test]
+    fn test_mint_info_signature_ignores_unknown_fields() {
+        let secret_key = SecretKey::generate();
+
+        let mut mint_info = MintInfo::new().name("Cashu mint").time(1_700_000_000u64);
+        mint_info.urls = Some(vec!["https://mint.example.com".to_string()]);
+        mint_info.sign(&secret_key).unwrap();
+
+        // A newer mint may serve fields and settings this version does not know
+        let mut value = serde_json::to_value(&mint_info).unwrap();
+        value["nuts"]["99"] = serde_json::json!({"supported": true});
+        value["new_field"] = serde_json::json!("value");
+
+        let decoded: MintInfo = serde_json::from_value(value).unwrap();
+        assert!(decoded.verify_signature().is_ok());
+
+        // Changing the time or the motd does not require signing again
+        let updated = mint_info.clone().time(1_700_000_100u64).motd("Maintenance");
+        assert!(updated.verify_signature().is_ok());
+
+        let mut moved = mint_info;
+        moved.urls = Some(vec!["https://phishing.example.com".to_string()]);
+        assert!(matches!(
+            moved.verify_signature(),
+            Err(Error::InvalidSignature)
+        ));
+    }
 }
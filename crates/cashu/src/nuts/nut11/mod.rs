@@ -878,7 +878,7 @@ impl From<Tag> for Vec<String> {
 impl SwapRequest {
     /// Generate the message to sign for SIG_ALL validation
     /// Concatenates all input secrets and output blinded messages in order
-    fn sig_all_msg_to_sign(&self) -> String {
+    pub fn sig_all_msg_to_sign(&self) -> String {
         let mut msg_to_sign = String::new();
 
         // Add all input secrets in order
@@ -8,10 +8,11 @@ use bitcoin::secp256k1::{self, Scalar};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use super::nut00::{BlindSignature, Proof};
+use super::nut00::{BlindSignature, Proof, ProofV4};
 use super::nut01::{PublicKey, SecretKey};
 use super::nut02::Id;
 use crate::dhke::{hash_e, hash_to_curve};
+use crate::secret::Secret;
 use crate::{Amount, SECP256K1};
 
 /// NUT12 Error
@@ -144,29 +145,46 @@ fn calculate_dleq(
     Ok(BlindSignatureDleq { e: e_sk, s })
 }
 
+/// Verify the DLEQ proof of an unblinded signature `c` on `secret`
+fn verify_proof_dleq(
+    secret: &Secret,
+    c: PublicKey,
+    dleq: Option<&ProofDleq>,
+    mint_pubkey: PublicKey,
+) -> Result<(), Error> {
+    match dleq {
+        Some(dleq) => {
+            let y = hash_to_curve(secret.as_bytes())?;
+
+            let r: Scalar = dleq.r.as_scalar();
+            let bs1: PublicKey = mint_pubkey.mul_tweak(&SECP256K1, &r)?.into();
+
+            let blinded_signature: PublicKey = c.combine(&bs1)?.into();
+            let blinded_message: PublicKey = y.combine(&dleq.r.public_key())?.into();
+
+            verify_dleq(
+                blinded_message,
+                blinded_signature,
+                &dleq.e,
+                &dleq.s,
+                mint_pubkey,
+            )
+        }
+        None => Err(Error::MissingDleqProof),
+    }
+}
+
 impl Proof {
     /// Verify proof Dleq
     pub fn verify_dleq(&self, mint_pubkey: PublicKey) -> Result<(), Error> {
-        match &self.dleq {
-            Some(dleq) => {
-                let y = hash_to_curve(self.secret.as_bytes())?;
-
-                let r: Scalar = dleq.r.as_scalar();
-                let bs1: PublicKey = mint_pubkey.mul_tweak(&SECP256K1, &r)?.into();
-
-                let blinded_signature: PublicKey = self.c.combine(&bs1)?.into();
-                let blinded_message: PublicKey = y.combine(&dleq.r.public_key())?.into();
-
-                verify_dleq(
-                    blinded_message,
-                    blinded_signature,
-                    &dleq.e,
-                    &dleq.s,
-                    mint_pubkey,
-                )
-            }
-            None => Err(Error::MissingDleqProof),
-        }
+        verify_proof_dleq(&self.secret, self.c, self.dleq.as_ref(), mint_pubkey)
+    }
+}
+
+impl ProofV4 {
+    /// Verify proof Dleq
+    pub fn verify_dleq(&self, mint_pubkey: PublicKey) -> Result<(), Error> {
+        verify_proof_dleq(&self.secret, self.c, self.dleq.as_ref(), mint_pubkey)
     }
 }
 
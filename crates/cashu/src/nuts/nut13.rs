@@ -225,6 +225,23 @@ impl PreMintSecrets {
     }
 }
 
+/// BIP32 path of the secret and blinding factor derived at `counter`
+///
+/// The secret is derived at the child `0` of the returned path and the blinding
+/// factor at the child `1`. Version 01 keysets derive both with HMAC-SHA256 of the
+/// seed, keyset id and counter instead, so they have no path.
+pub fn counter_derivation_path(
+    keyset_id: Id,
+    counter: u32,
+) -> Result<Option<DerivationPath>, Error> {
+    match keyset_id.get_version() {
+        super::nut02::KeySetVersion::Version00 => Ok(Some(
+            derive_path_from_keyset_id(keyset_id)?.child(ChildNumber::from_hardened_idx(counter)?),
+        )),
+        super::nut02::KeySetVersion::Version01 => Ok(None),
+    }
+}
+
 fn derive_path_from_keyset_id(id: Id) -> Result<DerivationPath, Error> {
     let index = u32::from(id);
 
@@ -307,6 +324,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_counter_derivation_path() {
+        let id = Id::from_str("009a1f293253e41e").unwrap();
+        assert_eq!(
+            counter_derivation_path(id, 5).unwrap(),
+            Some(DerivationPath::from_str("m/129372'/0'/864559728'/5'").unwrap())
+        );
+
+        let id = Id::from_str("012e23479a0029432eaad0d2040c09be53bab592d5cbf1d55e0dd26c9495951b30")
+            .unwrap();
+        assert_eq!(counter_derivation_path(id, 5).unwrap(), None);
+    }
+
     #[test]
     fn test_secret_derivation_keyset_v2() {
         let seed =
@@ -14,8 +14,11 @@ use crate::MintQuoteBolt12Response;
 pub mod ws;
 
 /// Subscription Parameter according to the standard
+///
+/// Created with [`Params::new`], as fields may be added.
 #[derive(Debug, Clone, Serialize, Eq, PartialEq, Hash, Deserialize)]
 #[serde(bound = "I: DeserializeOwned + Serialize")]
+#[non_exhaustive]
 pub struct Params<I> {
     /// Kind
     pub kind: Kind,
@@ -24,6 +27,31 @@ pub struct Params<I> {
     /// Subscription Id
     #[serde(rename = "subId")]
     pub id: I,
+    /// Unix time from which missed notifications are replayed
+    ///
+    /// Set when resubscribing after a reconnect, mints keeping recent
+    /// notifications send those matching the subscription before its current
+    /// state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+}
+
+impl<I> Params<I> {
+    /// Create [`Params`] for the notifications of `kind` matching `filters`
+    pub fn new(kind: Kind, filters: Vec<String>, id: I) -> Self {
+        Self {
+            kind,
+            filters,
+            id,
+            since: None,
+        }
+    }
+
+    /// Ask for the notifications missed since the unix time `since`
+    pub fn with_since(mut self, since: Option<u64>) -> Self {
+        self.since = since;
+        self
+    }
 }
 
 /// Check state Settings
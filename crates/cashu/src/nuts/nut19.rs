@@ -61,4 +61,7 @@ pub enum Path {
     /// Bolt12 Melt
     #[serde(rename = "/v1/melt/bolt12")]
     MeltBolt12,
+    /// Keysend Melt
+    #[serde(rename = "/v1/melt/keysend")]
+    MeltKeysend,
 }
@@ -9,10 +9,10 @@ use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
-use super::{BlindSignature, CurrencyUnit, MeltQuoteState, Mpp, PublicKey};
+use super::{BlindSignature, CurrencyUnit, MeltFailureReason, MeltQuoteState, Mpp, PublicKey};
 #[cfg(feature = "mint")]
 use crate::quote_id::QuoteId;
-use crate::Amount;
+use crate::{Amount, QuoteTimestamps};
 
 /// NUT023 Error
 #[derive(Debug, Error)]
@@ -103,6 +103,9 @@ pub struct MintQuoteBolt11Response<Q> {
     /// NUT-19 Pubkey
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pubkey: Option<PublicKey>,
+    /// Timestamps of the quote, signed by mints with an identity key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<QuoteTimestamps>,
 }
 impl<Q: ToString> MintQuoteBolt11Response<Q> {
     /// Convert the MintQuote with a quote type Q to a String
@@ -115,6 +118,7 @@ impl<Q: ToString> MintQuoteBolt11Response<Q> {
             pubkey: self.pubkey,
             amount: self.amount,
             unit: self.unit.clone(),
+            timestamps: self.timestamps.clone(),
         }
     }
 }
@@ -130,6 +134,7 @@ impl From<MintQuoteBolt11Response<QuoteId>> for MintQuoteBolt11Response<String>
             pubkey: value.pubkey,
             amount: value.amount,
             unit: value.unit.clone(),
+            timestamps: value.timestamps,
         }
     }
 }
@@ -271,6 +276,12 @@ pub struct MeltQuoteBolt11Response<Q> {
     // REVIEW: This is now required in the spec, we should remove the option once all mints update
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unit: Option<CurrencyUnit>,
+    /// Timestamps of the quote, signed by mints with an identity key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<QuoteTimestamps>,
+    /// Reason the last payment attempt failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<MeltFailureReason>,
 }
 
 impl<Q: ToString> MeltQuoteBolt11Response<Q> {
@@ -288,6 +299,8 @@ impl<Q: ToString> MeltQuoteBolt11Response<Q> {
             change: self.change,
             request: self.request,
             unit: self.unit,
+            timestamps: self.timestamps,
+            failure_reason: self.failure_reason,
         }
     }
 }
@@ -306,6 +319,8 @@ impl From<MeltQuoteBolt11Response<QuoteId>> for MeltQuoteBolt11Response<String>
             change: value.change,
             request: value.request,
             unit: value.unit,
+            timestamps: value.timestamps,
+            failure_reason: value.failure_reason,
         }
     }
 }
@@ -389,6 +404,15 @@ impl<'de, Q: DeserializeOwned> Deserialize<'de> for MeltQuoteBolt11Response<Q> {
             .get("unit")
             .and_then(|u| serde_json::from_value(u.clone()).ok());
 
+        let timestamps: Option<QuoteTimestamps> = value
+            .get("timestamps")
+            .and_then(|t| serde_json::from_value(t.clone()).ok());
+
+        // Reasons added by newer mints are ignored
+        let failure_reason: Option<MeltFailureReason> = value
+            .get("failure_reason")
+            .and_then(|r| serde_json::from_value(r.clone()).ok());
+
         Ok(Self {
             quote,
             amount,
@@ -400,6 +424,8 @@ impl<'de, Q: DeserializeOwned> Deserialize<'de> for MeltQuoteBolt11Response<Q> {
             change,
             request,
             unit,
+            timestamps,
+            failure_reason,
         })
     }
 }
@@ -5,7 +5,7 @@ use thiserror::Error;
 use super::{CurrencyUnit, MeltOptions, PublicKey};
 #[cfg(feature = "mint")]
 use crate::quote_id::QuoteId;
-use crate::Amount;
+use crate::{Amount, QuoteTimestamps};
 
 /// NUT18 Error
 #[derive(Debug, Error)]
@@ -56,6 +56,9 @@ pub struct MintQuoteBolt12Response<Q> {
     pub amount_paid: Amount,
     /// Amount that has been issued
     pub amount_issued: Amount,
+    /// Timestamps of the quote, signed by mints with an identity key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<QuoteTimestamps>,
 }
 
 #[cfg(feature = "mint")]
@@ -71,6 +74,7 @@ impl<Q: ToString> MintQuoteBolt12Response<Q> {
             pubkey: self.pubkey,
             amount_paid: self.amount_paid,
             amount_issued: self.amount_issued,
+            timestamps: self.timestamps.clone(),
         }
     }
 }
@@ -87,6 +91,7 @@ impl From<MintQuoteBolt12Response<QuoteId>> for MintQuoteBolt12Response<String>
             pubkey: value.pubkey,
             amount: value.amount,
             unit: value.unit,
+            timestamps: value.timestamps,
         }
     }
 }
@@ -0,0 +1,533 @@
+//! Paper backups
+//!
+//! Encodes a token, or any other data, into short lines that can be written on
+//! paper for cold storage, either as BIP39 words or as groups of bech32
+//! characters.
+//!
+//! The data is split into lines of 16 bytes. Every line carries its index and a
+//! checksum, so damaged lines are detected and lines can be entered in any order.
+//! A header line holds the length and a hash of the data, and a parity line, the
+//! XOR of the header and all data lines, recovers any one lost or damaged line.
+//!
+//! A line is 22 bytes: the index as a big endian `u16`, 16 bytes of payload and
+//! the first 4 bytes of the SHA256 of the index and payload. It is written as 16
+//! BIP39 words of 11 bits or 36 bech32 characters of 5 bits, the last 4 bits
+//! being zero. Line `0` is the header with payload
+//! `[version: u8][length: u32 big endian][first 11 bytes of the SHA256 of the data]`,
+//! lines `1..=n` hold the data padded with zeros and line `0xffff` the parity.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use bip39::Language;
+use bitcoin::hashes::{sha256, Hash};
+use thiserror::Error;
+
+use crate::nuts::nut00::token::{Token, TokenV4};
+
+/// Version of the paper backup format
+const VERSION: u8 = 1;
+/// Payload bytes of a line
+const PAYLOAD_BYTES: usize = 16;
+/// Checksum bytes of a line
+const CHECKSUM_BYTES: usize = 4;
+/// Bytes of a line: index, payload and checksum
+const LINE_BYTES: usize = 2 + PAYLOAD_BYTES + CHECKSUM_BYTES;
+/// BIP39 words of a line
+const WORDS_PER_LINE: usize = 16;
+/// Bech32 characters of a line
+const CHARS_PER_LINE: usize = 36;
+/// Bech32 characters written as one group
+const CHARS_PER_GROUP: usize = 4;
+/// Index of the header line
+const HEADER_INDEX: u16 = 0;
+/// Index of the parity line
+const PARITY_INDEX: u16 = u16::MAX;
+/// Bytes of the data hash kept in the header
+const DATA_HASH_BYTES: usize = PAYLOAD_BYTES - 5;
+/// Characters of the bech32 alphabet, without the easily confused `1`, `b`, `i` and `o`
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Paper backup Error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// No lines of a paper backup found
+    #[error("No paper backup lines found")]
+    NotPaperBackup,
+    /// Too much data for a paper backup
+    #[error("Data too large for a paper backup")]
+    DataTooLarge,
+    /// Unsupported format version
+    #[error("Unsupported paper backup version `{0}`")]
+    UnsupportedVersion(u8),
+    /// More lines lost or damaged than can be recovered
+    #[error(
+        "Paper backup cannot be recovered: lines {missing:?} missing, {damaged} lines damaged"
+    )]
+    Unrecoverable {
+        /// Indexes of the lines not found
+        missing: Vec<u16>,
+        /// Lines found with an invalid checksum or word
+        damaged: usize,
+    },
+    /// Decoded data does not match the hash of the header
+    #[error("Paper backup data does not match its hash")]
+    HashMismatch,
+    /// NUT00 Error
+    #[error(transparent)]
+    NUT00(#[from] crate::nuts::nut00::Error),
+}
+
+/// How the lines of a paper backup are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaperFormat {
+    /// 16 BIP39 words per line
+    #[default]
+    Words,
+    /// 36 bech32 characters per line, in groups of 4
+    Alphanumeric,
+}
+
+/// Data encoded into lines for writing on paper
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaperBackup {
+    lines: Vec<[u8; LINE_BYTES]>,
+}
+
+/// Line with its index, payload and checksum
+fn line(index: u16, payload: &[u8; PAYLOAD_BYTES]) -> [u8; LINE_BYTES] {
+    let mut line = [0u8; LINE_BYTES];
+    line[..2].copy_from_slice(&index.to_be_bytes());
+    line[2..2 + PAYLOAD_BYTES].copy_from_slice(payload);
+
+    let checksum = sha256::Hash::hash(&line[..2 + PAYLOAD_BYTES]).to_byte_array();
+    line[2 + PAYLOAD_BYTES..].copy_from_slice(&checksum[..CHECKSUM_BYTES]);
+
+    line
+}
+
+/// Index and payload of a line with a valid checksum
+fn parse_line(line: &[u8; LINE_BYTES]) -> Option<(u16, [u8; PAYLOAD_BYTES])> {
+    let index = u16::from_be_bytes([line[0], line[1]]);
+
+    let mut payload = [0u8; PAYLOAD_BYTES];
+    payload.copy_from_slice(&line[2..2 + PAYLOAD_BYTES]);
+
+    (self::line(index, &payload) == *line).then_some((index, payload))
+}
+
+/// XOR of payloads
+fn xor<'a>(payloads: impl Iterator<Item = &'a [u8; PAYLOAD_BYTES]>) -> [u8; PAYLOAD_BYTES] {
+    payloads.fold([0u8; PAYLOAD_BYTES], |mut parity, payload| {
+        parity
+            .iter_mut()
+            .zip(payload)
+            .for_each(|(parity, byte)| *parity ^= byte);
+        parity
+    })
+}
+
+/// Split bytes into symbols of `bits`, padding the last one with zeros
+fn to_symbols(bytes: &[u8], bits: u32) -> Vec<u16> {
+    let mut symbols = Vec::new();
+    let mut buffer = 0u32;
+    let mut buffered = 0u32;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | u32::from(*byte);
+        buffered += 8;
+
+        while buffered >= bits {
+            buffered -= bits;
+            symbols.push(((buffer >> buffered) & ((1 << bits) - 1)) as u16);
+        }
+        buffer &= (1 << buffered) - 1;
+    }
+
+    if buffered > 0 {
+        symbols.push(((buffer << (bits - buffered)) & ((1 << bits) - 1)) as u16);
+    }
+
+    symbols
+}
+
+/// Join symbols of `bits` into a line, `None` if the padding is not zero
+fn from_symbols(symbols: &[u16], bits: u32) -> Option<[u8; LINE_BYTES]> {
+    let mut bytes = Vec::with_capacity(LINE_BYTES);
+    let mut buffer = 0u32;
+    let mut buffered = 0u32;
+
+    for symbol in symbols {
+        buffer = (buffer << bits) | u32::from(*symbol);
+        buffered += bits;
+
+        while buffered >= 8 {
+            buffered -= 8;
+            bytes.push((buffer >> buffered) as u8);
+        }
+        buffer &= (1 << buffered) - 1;
+    }
+
+    if buffer != 0 {
+        return None;
+    }
+
+    bytes.try_into().ok()
+}
+
+/// Index of a BIP39 word, also matching the first 4 letters of a word
+fn find_word(word: &str) -> Option<u16> {
+    let word = word.to_lowercase();
+
+    Language::English.find_word(&word).or_else(|| {
+        let prefix = word.get(..4)?;
+        let mut matches = Language::English
+            .word_list()
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.starts_with(prefix));
+
+        match (matches.next(), matches.next()) {
+            (Some((index, _)), None) => Some(index as u16),
+            _ => None,
+        }
+    })
+}
+
+/// Line written on paper, `None` if it is not shaped like one
+///
+/// The inner `None` marks a line shaped like a backup line that does not decode.
+fn read_line(text: &str) -> Option<Option<[u8; LINE_BYTES]>> {
+    // Lines of a sheet are labelled with their number, e.g. `07:`
+    let (labelled, text) = match text.split_once(':') {
+        Some((label, rest))
+            if !label.trim().is_empty() && label.trim().chars().all(|c| c.is_ascii_digit()) =>
+        {
+            (true, rest)
+        }
+        _ => (false, text),
+    };
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    if words.len() == WORDS_PER_LINE {
+        let symbols: Option<Vec<u16>> = words.iter().map(|word| find_word(word)).collect();
+
+        return Some(symbols.and_then(|symbols| from_symbols(&symbols, 11)));
+    }
+
+    let chars: Vec<u8> = text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let is_alphanumeric = (chars.len() == CHARS_PER_LINE
+        && chars.iter().all(|c| BECH32_CHARSET.contains(c)))
+        || (labelled && !chars.is_empty() && chars.iter().all(|c| c.is_ascii_alphanumeric()));
+
+    if !is_alphanumeric {
+        return labelled.then_some(None);
+    }
+
+    let symbols: Option<Vec<u16>> = chars
+        .iter()
+        .map(|c| {
+            BECH32_CHARSET
+                .iter()
+                .position(|charset| charset == c)
+                .map(|position| position as u16)
+        })
+        .collect();
+
+    Some(symbols.and_then(|symbols| {
+        (symbols.len() == CHARS_PER_LINE)
+            .then(|| from_symbols(&symbols, 5))
+            .flatten()
+    }))
+}
+
+impl PaperBackup {
+    /// Encode data into a paper backup
+    pub fn encode(data: &[u8]) -> Result<Self, Error> {
+        let length = u32::try_from(data.len()).map_err(|_| Error::DataTooLarge)?;
+
+        let chunks: Vec<[u8; PAYLOAD_BYTES]> = data
+            .chunks(PAYLOAD_BYTES)
+            .map(|chunk| {
+                let mut payload = [0u8; PAYLOAD_BYTES];
+                payload[..chunk.len()].copy_from_slice(chunk);
+                payload
+            })
+            .collect();
+
+        if chunks.len() >= usize::from(PARITY_INDEX) {
+            return Err(Error::DataTooLarge);
+        }
+
+        let mut header = [0u8; PAYLOAD_BYTES];
+        header[0] = VERSION;
+        header[1..5].copy_from_slice(&length.to_be_bytes());
+        header[5..].copy_from_slice(&sha256::Hash::hash(data).to_byte_array()[..DATA_HASH_BYTES]);
+
+        let parity = xor(std::iter::once(&header).chain(chunks.iter()));
+
+        let mut lines = vec![line(HEADER_INDEX, &header)];
+        lines.extend(
+            chunks
+                .iter()
+                .enumerate()
+                .map(|(index, chunk)| line(index as u16 + 1, chunk)),
+        );
+        lines.push(line(PARITY_INDEX, &parity));
+
+        Ok(Self { lines })
+    }
+
+    /// Decode a paper backup from text
+    ///
+    /// Reads lines of words or characters in any order, labelled with their number
+    /// or not, and skips other text such as the instructions of a sheet. Any one
+    /// lost or damaged line is recovered.
+    pub fn decode(text: &str) -> Result<Vec<u8>, Error> {
+        let mut payloads = BTreeMap::new();
+        let mut damaged = 0;
+        let mut found = false;
+
+        for line in text.lines().filter_map(read_line) {
+            found = true;
+
+            match line.as_ref().and_then(parse_line) {
+                Some((index, payload)) => {
+                    payloads.insert(index, payload);
+                }
+                None => damaged += 1,
+            }
+        }
+
+        if !found {
+            return Err(Error::NotPaperBackup);
+        }
+
+        // Without the header the data lines are counted from the highest index found
+        let data_lines = match payloads.get(&HEADER_INDEX) {
+            Some(header) => {
+                let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+                (length as usize).div_ceil(PAYLOAD_BYTES)
+            }
+            None => payloads
+                .keys()
+                .filter(|index| **index != PARITY_INDEX)
+                .max()
+                .map(|index| usize::from(*index))
+                .unwrap_or_default(),
+        };
+
+        if data_lines >= usize::from(PARITY_INDEX) {
+            return Err(Error::DataTooLarge);
+        }
+
+        let missing: Vec<u16> = (0..=data_lines as u16)
+            .filter(|index| !payloads.contains_key(index))
+            .collect();
+
+        match missing.as_slice() {
+            [] => (),
+            [index] if payloads.contains_key(&PARITY_INDEX) => {
+                let recovered = xor(payloads
+                    .iter()
+                    .filter(|(index, _)| {
+                        usize::from(**index) <= data_lines || **index == PARITY_INDEX
+                    })
+                    .map(|(_, payload)| payload));
+                payloads.insert(*index, recovered);
+            }
+            _ => return Err(Error::Unrecoverable { missing, damaged }),
+        }
+
+        let header = payloads[&HEADER_INDEX];
+        if header[0] != VERSION {
+            return Err(Error::UnsupportedVersion(header[0]));
+        }
+        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+
+        let mut data: Vec<u8> = (1..=data_lines as u16)
+            .flat_map(|index| payloads[&index])
+            .collect();
+        data.truncate(length);
+
+        if sha256::Hash::hash(&data).to_byte_array()[..DATA_HASH_BYTES] != header[5..] {
+            return Err(Error::HashMismatch);
+        }
+
+        Ok(data)
+    }
+
+    /// Lines as 16 BIP39 words each
+    pub fn words(&self) -> Vec<String> {
+        let word_list = Language::English.word_list();
+
+        self.lines
+            .iter()
+            .map(|line| {
+                to_symbols(line, 11)
+                    .into_iter()
+                    .map(|symbol| word_list[usize::from(symbol)])
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    /// Lines as 36 bech32 characters each, in groups of 4
+    pub fn alphanumeric(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .map(|line| {
+                let chars: Vec<char> = to_symbols(line, 5)
+                    .into_iter()
+                    .map(|symbol| char::from(BECH32_CHARSET[usize::from(symbol)]))
+                    .collect();
+
+                chars
+                    .chunks(CHARS_PER_GROUP)
+                    .map(|group| group.iter().collect::<String>())
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect()
+    }
+
+    /// Printable sheet with numbered lines and instructions
+    pub fn sheet(&self, format: PaperFormat, description: Option<&str>) -> String {
+        let lines = match format {
+            PaperFormat::Words => self.words(),
+            PaperFormat::Alphanumeric => self.alphanumeric(),
+        };
+
+        let mut sheet = String::from("CASHU PAPER BACKUP\n");
+        if let Some(description) = description {
+            let _ = writeln!(sheet, "{description}");
+        }
+        let _ = writeln!(
+            sheet,
+            "\n{} lines. Keep every line, any single lost line can be recovered.\n",
+            lines.len()
+        );
+
+        for (number, line) in lines.iter().enumerate() {
+            let _ = writeln!(sheet, "{:02}: {line}", number + 1);
+        }
+
+        sheet
+    }
+}
+
+impl Token {
+    /// Encode the token into a paper backup
+    ///
+    /// V3 tokens are converted to V4 first, for a shorter backup.
+    pub fn to_paper_backup(&self) -> Result<PaperBackup, Error> {
+        let bytes = match self {
+            Self::TokenV3(token) => TokenV4::try_from(token.clone())?.to_raw_bytes()?,
+            Self::TokenV4(token) => token.to_raw_bytes()?,
+        };
+
+        PaperBackup::encode(&bytes)
+    }
+
+    /// Decode a token from a paper backup
+    pub fn from_paper_backup(text: &str) -> Result<Self, Error> {
+        Ok(Token::try_from(&PaperBackup::decode(text)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    const TOKEN: &str = "cashuBpGF0gaJhaUgArSaMTR9YJmFwgaNhYQFhc3hAOWE2ZGJiODQ3YmQyMzJiYTc2ZGIwZGYxOTcyMTZiMjlkM2I4Y2MxNDU1M2NkMjc4MjdmYzFjYzk0MmZlZGI0ZWFjWCEDhhhUP_trhpXfStS6vN6So0qWvc2X3O4NfM-Y1HISZ5JhZGlUaGFuayB5b3VhbXVodHRwOi8vbG9jYWxob3N0OjMzMzhhdWNzYXQ=";
+
+    #[test]
+    fn test_paper_backup_round_trip() {
+        for data in [&b""[..], b"cashu", &[7u8; 16], &[0xab; 100]] {
+            let backup = PaperBackup::encode(data).unwrap();
+
+            assert_eq!(backup.words().len(), data.len().div_ceil(PAYLOAD_BYTES) + 2);
+            assert!(backup
+                .words()
+                .iter()
+                .all(|line| line.split(' ').count() == WORDS_PER_LINE));
+
+            assert_eq!(
+                PaperBackup::decode(&backup.words().join("\n")).unwrap(),
+                data
+            );
+            assert_eq!(
+                PaperBackup::decode(&backup.alphanumeric().join("\n")).unwrap(),
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn test_paper_backup_recovery() {
+        let data = [0x42u8; 70];
+        let backup = PaperBackup::encode(&data).unwrap();
+        let words = backup.words();
+
+        // Any one line lost, including the header and parity
+        for lost in 0..words.len() {
+            let mut lines = words.clone();
+            lines.remove(lost);
+            lines.reverse();
+            assert_eq!(PaperBackup::decode(&lines.join("\n")).unwrap(), data);
+        }
+
+        // One damaged word
+        let mut lines = words.clone();
+        lines[2] = lines[2].replacen(lines[2].split(' ').next().unwrap(), "zoo", 1);
+        assert_eq!(PaperBackup::decode(&lines.join("\n")).unwrap(), data);
+
+        // Two lines lost
+        let lines = words[2..].to_vec();
+        assert!(matches!(
+            PaperBackup::decode(&lines.join("\n")),
+            Err(Error::Unrecoverable { .. })
+        ));
+
+        assert!(matches!(
+            PaperBackup::decode("not a backup"),
+            Err(Error::NotPaperBackup)
+        ));
+    }
+
+    #[test]
+    fn test_token_paper_backup_sheet() {
+        let token = Token::from_str(TOKEN).unwrap();
+        let backup = token.to_paper_backup().unwrap();
+
+        for format in [PaperFormat::Words, PaperFormat::Alphanumeric] {
+            let sheet = backup.sheet(format, Some("1 sat at http://localhost:3338"));
+            assert_eq!(Token::from_paper_backup(&sheet).unwrap(), token);
+
+            // Written in upper case and with 4 letter word prefixes
+            let written: String = sheet
+                .lines()
+                .map(|line| {
+                    line.split(' ')
+                        .map(|word| match word.len() > 4 && !word.contains('-') {
+                            true => word[..4].to_uppercase(),
+                            false => word.to_uppercase(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            assert_eq!(Token::from_paper_backup(&written).unwrap(), token);
+        }
+    }
+}
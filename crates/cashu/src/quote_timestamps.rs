@@ -0,0 +1,162 @@
+//! Quote timestamps
+//!
+//! Mint and melt quote responses carry the unix times at which the quote was
+//! created, paid and issued. A mint with an identity key signs them, so wallets
+//! and auditors can prove when a quote settled.
+//!
+//! The signed message is the compact JSON object
+//! `{"created":<u64>,"issued":<u64>,"paid":<u64>,"quote":"<quote id>"}`, with
+//! keys in this order, no whitespace, and the `issued` and `paid` keys left out
+//! when the quote did not reach that state. The signature is a BIP340 Schnorr
+//! signature over the SHA256 of the message, made with the key published as the
+//! `pubkey` of the mint info.
+
+use std::str::FromStr;
+
+use bitcoin::secp256k1::schnorr::Signature;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::nuts::nut01::{PublicKey, SecretKey};
+
+/// Quote timestamps Error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Signature not provided
+    #[error("Quote timestamps signature not provided")]
+    SignatureMissing,
+    /// Invalid signature
+    #[error("Quote timestamps signature invalid")]
+    InvalidSignature,
+    /// NUT01 Error
+    #[error(transparent)]
+    NUT01(#[from] crate::nuts::nut01::Error),
+    /// Json Error
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Unix times of the state transitions of a quote
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "swagger", derive(utoipa::ToSchema))]
+pub struct QuoteTimestamps {
+    /// Unix time the quote was created
+    pub created: u64,
+    /// Unix time the quote was paid
+    ///
+    /// For a mint quote the first payment received, for a melt quote the
+    /// payment made by the mint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paid: Option<u64>,
+    /// Unix time of the last issuance of a mint quote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issued: Option<u64>,
+    /// Schnorr signature of the mint identity key over the timestamps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// Canonical message signed for [`QuoteTimestamps`]
+///
+/// Fields are declared in the order of the canonical serialization.
+#[derive(Serialize)]
+struct SignedTimestamps<'a> {
+    created: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issued: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paid: Option<u64>,
+    quote: &'a str,
+}
+
+impl QuoteTimestamps {
+    /// Create unsigned [`QuoteTimestamps`]
+    pub fn new(created: u64, paid: Option<u64>, issued: Option<u64>) -> Self {
+        Self {
+            created,
+            paid,
+            issued,
+            signature: None,
+        }
+    }
+
+    /// Constructs the message to be signed for the quote `quote_id`
+    pub fn msg_to_sign(&self, quote_id: &str) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(&SignedTimestamps {
+            created: self.created,
+            issued: self.issued,
+            paid: self.paid,
+            quote: quote_id,
+        })?)
+    }
+
+    /// Sign the timestamps of the quote `quote_id` with the mint identity key
+    pub fn sign(&mut self, quote_id: &str, secret_key: &SecretKey) -> Result<(), Error> {
+        let signature: Signature = secret_key.sign(&self.msg_to_sign(quote_id)?)?;
+
+        self.signature = Some(signature.to_string());
+
+        Ok(())
+    }
+
+    /// Verify the signature on the timestamps of the quote `quote_id`
+    pub fn verify_signature(&self, quote_id: &str, pubkey: &PublicKey) -> Result<(), Error> {
+        let signature = self.signature.as_ref().ok_or(Error::SignatureMissing)?;
+
+        let signature = Signature::from_str(signature).map_err(|_| Error::InvalidSignature)?;
+
+        pubkey
+            .verify(&self.msg_to_sign(quote_id)?, &signature)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_timestamps_signature() {
+        let secret_key = SecretKey::generate();
+        let quote_id = "9d745270-1405-46de-b5c5-e2762b4f5e00";
+
+        let mut timestamps = QuoteTimestamps::new(1_700_000_000, Some(1_700_000_060), None);
+
+        assert_eq!(
+            timestamps.msg_to_sign(quote_id).unwrap(),
+            br#"{"created":1700000000,"paid":1700000060,"quote":"9d745270-1405-46de-b5c5-e2762b4f5e00"}"#
+        );
+
+        timestamps.sign(quote_id, &secret_key).unwrap();
+        assert!(timestamps
+            .verify_signature(quote_id, &secret_key.public_key())
+            .is_ok());
+
+        // Signature survives a serialization round trip
+        let json = serde_json::to_string(&timestamps).unwrap();
+        let decoded: QuoteTimestamps = serde_json::from_str(&json).unwrap();
+        assert!(decoded
+            .verify_signature(quote_id, &secret_key.public_key())
+            .is_ok());
+
+        // Signature does not hold for another quote or a moved timestamp
+        assert!(matches!(
+            timestamps.verify_signature("other", &secret_key.public_key()),
+            Err(Error::InvalidSignature)
+        ));
+        let mut moved = timestamps.clone();
+        moved.paid = Some(1_700_000_000);
+        assert!(matches!(
+            moved.verify_signature(quote_id, &secret_key.public_key()),
+            Err(Error::InvalidSignature)
+        ));
+
+        assert!(matches!(
+            QuoteTimestamps::new(1, None, None)
+                .verify_signature(quote_id, &secret_key.public_key()),
+            Err(Error::SignatureMissing)
+        ));
+    }
+}
@@ -0,0 +1,91 @@
+use axum::extract::{Json, State};
+use axum::response::Response;
+#[cfg(feature = "swagger")]
+use cdk::error::ErrorResponse;
+use cdk::mint::QuoteId;
+#[cfg(feature = "auth")]
+use cdk::nuts::nut21::{Method, ProtectedEndpoint, RoutePath};
+use cdk::nuts::{MeltQuoteBolt11Response, MeltQuoteKeysendRequest, MeltRequest};
+use paste::paste;
+use tracing::instrument;
+
+#[cfg(feature = "auth")]
+use crate::auth::AuthHeader;
+use crate::{into_response, post_cache_wrapper, MintState};
+
+post_cache_wrapper!(
+    post_melt_keysend,
+    MeltRequest<QuoteId>,
+    MeltQuoteBolt11Response<QuoteId>
+);
+
+#[cfg_attr(feature = "swagger", utoipa::path(
+    post,
+    context_path = "/v1",
+    path = "/melt/quote/keysend",
+    request_body(content = MeltQuoteKeysendRequest, description = "Quote params", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Successful response", body = MeltQuoteBolt11Response<String>, content_type = "application/json"),
+        (status = 500, description = "Server error", body = ErrorResponse, content_type = "application/json")
+    )
+))]
+/// Request a quote for paying a lightning node by its node id
+#[instrument(skip_all, fields(amount = ?payload.amount))]
+pub async fn post_melt_keysend_quote(
+    #[cfg(feature = "auth")] auth: AuthHeader,
+    State(state): State<MintState>,
+    Json(payload): Json<MeltQuoteKeysendRequest>,
+) -> Result<Json<MeltQuoteBolt11Response<QuoteId>>, Response> {
+    #[cfg(feature = "auth")]
+    {
+        state
+            .mint
+            .verify_auth(
+                auth.into(),
+                &ProtectedEndpoint::new(Method::Post, RoutePath::MeltQuoteKeysend),
+            )
+            .await
+            .map_err(into_response)?;
+    }
+
+    let quote = state
+        .mint
+        .get_melt_quote(payload.into())
+        .await
+        .map_err(into_response)?;
+
+    Ok(Json(quote))
+}
+
+#[cfg_attr(feature = "swagger", utoipa::path(
+    post,
+    context_path = "/v1",
+    path = "/melt/keysend",
+    request_body(content = MeltRequest<String>, description = "Melt params", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Successful response", body = MeltQuoteBolt11Response<String>, content_type = "application/json"),
+        (status = 500, description = "Server error", body = ErrorResponse, content_type = "application/json")
+    )
+))]
+/// Melt tokens for a keysend payment that the mint will make for the user in exchange
+pub async fn post_melt_keysend(
+    #[cfg(feature = "auth")] auth: AuthHeader,
+    State(state): State<MintState>,
+    Json(payload): Json<MeltRequest<QuoteId>>,
+) -> Result<Json<MeltQuoteBolt11Response<QuoteId>>, Response> {
+    #[cfg(feature = "auth")]
+    {
+        state
+            .mint
+            .verify_auth(
+                auth.into(),
+                &ProtectedEndpoint::new(Method::Post, RoutePath::MeltKeysend),
+            )
+            .await
+            .map_err(into_response)?;
+    }
+
+    let res = state.mint.melt(&payload).await.map_err(into_response)?;
+
+    Ok(Json(res))
+}
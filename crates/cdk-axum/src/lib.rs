@@ -15,6 +15,7 @@ use axum::routing::{get, post};
 use axum::Router;
 use cache::HttpCache;
 use cdk::mint::Mint;
+use cdk::nuts::PaymentMethod;
 use router_handlers::*;
 
 mod metrics;
@@ -23,13 +24,18 @@ mod metrics;
 mod auth;
 mod bolt12_router;
 pub mod cache;
+mod keysend_router;
+pub mod recorder;
 mod router_handlers;
 mod ws;
 
+pub use router_handlers::into_response;
+
 #[cfg(feature = "swagger")]
 mod swagger_imports {
     pub use cdk::amount::Amount;
     pub use cdk::error::{ErrorCode, ErrorResponse};
+    pub use cdk::keyset_history::{KeysetHistory, KeysetHistoryEntry};
     pub use cdk::nuts::nut00::{
         BlindSignature, BlindedMessage, CurrencyUnit, PaymentMethod, Proof, Witness,
     };
@@ -51,7 +57,8 @@ mod swagger_imports {
     };
     #[cfg(feature = "auth")]
     pub use cdk::nuts::MintAuthRequest;
-    pub use cdk::nuts::{nut04, nut05, nut15, MeltQuoteState, MintQuoteState};
+    pub use cdk::nuts::{nut04, nut05, nut15, MeltFailureReason, MeltQuoteState, MintQuoteState};
+    pub use cdk::quote_timestamps::QuoteTimestamps;
 }
 
 #[cfg(feature = "swagger")]
@@ -61,6 +68,7 @@ use crate::bolt12_router::{
     cache_post_melt_bolt12, cache_post_mint_bolt12, get_check_mint_bolt12_quote,
     post_melt_bolt12_quote, post_mint_bolt12_quote,
 };
+use crate::keysend_router::{cache_post_melt_keysend, post_melt_keysend_quote};
 
 /// CDK Mint State
 #[derive(Clone)]
@@ -88,6 +96,7 @@ macro_rules! define_api_doc {
                 get_keys,
                 get_keyset_pubkeys,
                 get_keysets,
+                get_keyset_history,
                 get_mint_info,
                 post_mint_bolt11_quote,
                 get_check_mint_bolt11_quote,
@@ -127,10 +136,13 @@ define_api_doc! {
         KeysetResponse,
         KeySet,
         KeySetInfo,
+        KeysetHistory,
+        KeysetHistoryEntry,
         MeltRequest<String>,
         MeltQuoteBolt11Request,
         MeltQuoteBolt11Response<String>,
         MeltQuoteState,
+        MeltFailureReason,
         MeltMethodSettings,
         MintRequest<String>,
         MintResponse,
@@ -149,6 +161,7 @@ define_api_doc! {
         ProofDleq,
         ProofState,
         PublicKey,
+        QuoteTimestamps,
         RestoreRequest,
         RestoreResponse,
         SecretKey,
@@ -183,10 +196,13 @@ define_api_doc! {
         KeysetResponse,
         KeySet,
         KeySetInfo,
+        KeysetHistory,
+        KeysetHistoryEntry,
         MeltRequest<String>,
         MeltQuoteBolt11Request,
         MeltQuoteBolt11Response<String>,
         MeltQuoteState,
+        MeltFailureReason,
         MeltMethodSettings,
         MintRequest<String>,
         MintResponse,
@@ -205,6 +221,7 @@ define_api_doc! {
         ProofDleq,
         ProofState,
         PublicKey,
+        QuoteTimestamps,
         RestoreRequest,
         RestoreResponse,
         SecretKey,
@@ -281,6 +298,15 @@ pub async fn create_mint_router_with_custom_cache(
     cache: HttpCache,
     include_bolt12: bool,
 ) -> Result<Router> {
+    let include_keysend = mint
+        .mint_info()
+        .await?
+        .nuts
+        .nut05
+        .methods
+        .iter()
+        .any(|settings| settings.method == PaymentMethod::keysend());
+
     let state = MintState {
         mint,
         cache: Arc::new(cache),
@@ -289,6 +315,7 @@ pub async fn create_mint_router_with_custom_cache(
     let v1_router = Router::new()
         .route("/keys", get(get_keys))
         .route("/keysets", get(get_keysets))
+        .route("/keysets/history", get(get_keyset_history))
         .route("/keys/{keyset_id}", get(get_keyset_pubkeys))
         .route("/swap", post(cache_post_swap))
         .route("/mint/quote/bolt11", post(post_mint_bolt11_quote))
@@ -324,6 +351,13 @@ pub async fn create_mint_router_with_custom_cache(
         mint_router
     };
 
+    let mint_router = if include_keysend {
+        let keysend_router = create_keysend_router(state.clone());
+        mint_router.nest("/v1", keysend_router)
+    } else {
+        mint_router
+    };
+
     #[cfg(feature = "prometheus")]
     let mint_router = mint_router.layer(axum::middleware::from_fn_with_state(
         state.clone(),
@@ -352,3 +386,14 @@ fn create_bolt12_router(state: MintState) -> Router<MintState> {
         .route("/mint/bolt12", post(cache_post_mint_bolt12))
         .with_state(state)
 }
+
+fn create_keysend_router(state: MintState) -> Router<MintState> {
+    Router::new()
+        .route("/melt/quote/keysend", post(post_melt_keysend_quote))
+        .route(
+            "/melt/quote/keysend/{quote_id}",
+            get(get_check_melt_bolt11_quote),
+        )
+        .route("/melt/keysend", post(cache_post_melt_keysend))
+        .with_state(state)
+}
@@ -0,0 +1,373 @@
+//! Recording and replay of mint request traffic
+//!
+//! The recorder appends every request served by the mint, with its response, to
+//! a JSON lines file. Witnesses and payment preimages are dropped and the secret
+//! and signature of every proof are replaced by a hash, so a recording holds no
+//! spendable ecash. The hashes still tell the same proof apart across requests.
+//! Replaying feeds the recorded requests to another mint router and reports how
+//! each one was answered. That mint has its own keys, so recorded proofs would not
+//! verify there anyway, and requests spending proofs are skipped.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::body::{to_bytes, Body};
+use axum::extract::State;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::middleware::{from_fn_with_state, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use cdk::util::{hex, unix_time};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tower::ServiceExt;
+
+/// Largest body buffered for recording, matching the axum default request body limit
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Fields holding values that can spend or unlock ecash, which are never recorded
+const REDACTED_FIELDS: [&str; 3] = ["witness", "payment_preimage", "preimage"];
+
+/// Proof fields replaced by their hash
+const HASHED_PROOF_FIELDS: [&str; 2] = ["secret", "C"];
+
+/// Prefix of a hashed proof field
+const REDACTED_PREFIX: &str = "redacted:";
+
+/// A request served by the mint and the response it got
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    /// Unix timestamp of the request
+    pub timestamp: u64,
+    /// HTTP method
+    pub method: String,
+    /// Path and query of the request
+    pub path: String,
+    /// Sanitized JSON request body
+    pub request: Option<Value>,
+    /// HTTP status of the response
+    pub status: u16,
+    /// Sanitized JSON response body
+    pub response: Option<Value>,
+}
+
+impl RecordedExchange {
+    /// Whether the request spends proofs whose secrets were redacted
+    pub fn has_redacted_proofs(&self) -> bool {
+        self.request.as_ref().is_some_and(contains_redacted_proof)
+    }
+}
+
+/// Outcome of replaying a [`RecordedExchange`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayOutcome {
+    /// Recorded exchange
+    pub recorded: RecordedExchange,
+    /// HTTP status returned by the replay target, `None` when the request was
+    /// skipped because it spends redacted proofs
+    pub status: Option<u16>,
+    /// Sanitized JSON response body returned by the replay target
+    pub response: Option<Value>,
+}
+
+impl ReplayOutcome {
+    /// Whether the replay target answered with the recorded status
+    pub fn status_matches(&self) -> bool {
+        self.status == Some(self.recorded.status)
+    }
+
+    /// Whether the request was not sent to the replay target
+    pub fn is_skipped(&self) -> bool {
+        self.status.is_none()
+    }
+}
+
+/// Appends the traffic served by a mint router to a recording file
+#[derive(Debug)]
+pub struct RequestRecorder {
+    file: Mutex<File>,
+}
+
+impl RequestRecorder {
+    /// Create a recorder appending to the file at `path`
+    pub async fn new(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Record the traffic served by `router`
+    pub fn layer(self: Arc<Self>, router: Router) -> Router {
+        router.layer(from_fn_with_state(self, record_middleware))
+    }
+
+    async fn record(&self, exchange: &RecordedExchange) -> Result<()> {
+        let mut line = serde_json::to_vec(exchange)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+async fn record_middleware(
+    State(recorder): State<Arc<RequestRecorder>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    // Websocket upgrades are not request/response pairs
+    if req.headers().contains_key(header::UPGRADE) {
+        return next.run(req).await;
+    }
+
+    let method = req.method().to_string();
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|path| path.to_string())
+        .unwrap_or_default();
+
+    let (parts, body) = req.into_parts();
+    let request_body = match to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(request_body.clone())))
+        .await;
+
+    let (parts, body) = response.into_parts();
+    let response_body = match to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::error!("Could not read response body for recording: {}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let exchange = RecordedExchange {
+        timestamp: unix_time(),
+        method,
+        path,
+        request: sanitized_json(&request_body),
+        status: parts.status.as_u16(),
+        response: sanitized_json(&response_body),
+    };
+
+    if let Err(err) = recorder.record(&exchange).await {
+        tracing::error!("Could not record request: {}", err);
+    }
+
+    Response::from_parts(parts, Body::from(response_body))
+}
+
+/// Parse a body as JSON and remove any secrets from it
+fn sanitized_json(body: &[u8]) -> Option<Value> {
+    let mut value = serde_json::from_slice(body).ok()?;
+    sanitize(&mut value);
+    Some(value)
+}
+
+/// Drop witnesses and preimages and hash the secret and signature of proofs
+fn sanitize(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let is_proof = HASHED_PROOF_FIELDS
+                .iter()
+                .all(|field| map.get(*field).is_some_and(Value::is_string));
+
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) {
+                    *value = Value::Null;
+                } else if is_proof && HASHED_PROOF_FIELDS.contains(&key.as_str()) {
+                    if let Some(field) = value.as_str() {
+                        let hash = Sha256::digest(field.as_bytes());
+                        *value = Value::String(format!("{REDACTED_PREFIX}{}", hex::encode(hash)));
+                    }
+                } else {
+                    sanitize(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(sanitize),
+        _ => (),
+    }
+}
+
+/// Whether `value` holds a proof sanitized by [`sanitize`]
+fn contains_redacted_proof(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.iter().any(|(key, value)| match value {
+            Value::String(value) => {
+                HASHED_PROOF_FIELDS.contains(&key.as_str()) && value.starts_with(REDACTED_PREFIX)
+            }
+            value => contains_redacted_proof(value),
+        }),
+        Value::Array(values) => values.iter().any(contains_redacted_proof),
+        _ => false,
+    }
+}
+
+/// Read the exchanges of a recording file
+pub fn read_recording(path: &Path) -> Result<Vec<RecordedExchange>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Send the recorded requests to `router` in order
+///
+/// Requests spending redacted proofs cannot succeed and are skipped.
+pub async fn replay(
+    router: Router,
+    exchanges: Vec<RecordedExchange>,
+) -> Result<Vec<ReplayOutcome>> {
+    let mut outcomes = Vec::with_capacity(exchanges.len());
+
+    for recorded in exchanges {
+        if recorded.has_redacted_proofs() {
+            outcomes.push(ReplayOutcome {
+                recorded,
+                status: None,
+                response: None,
+            });
+            continue;
+        }
+
+        let body = match &recorded.request {
+            Some(request) => Body::from(serde_json::to_vec(request)?),
+            None => Body::empty(),
+        };
+
+        let request = Request::builder()
+            .method(Method::from_bytes(recorded.method.as_bytes())?)
+            .uri(&recorded.path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)?;
+
+        let response = router.clone().oneshot(request).await?;
+        let status = response.status().as_u16();
+        let response_body = to_bytes(response.into_body(), MAX_BODY_SIZE).await?;
+
+        outcomes.push(ReplayOutcome {
+            recorded,
+            status: Some(status),
+            response: sanitized_json(&response_body),
+        });
+    }
+
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_sanitize_redacts_proof_secrets() {
+        let mut request = json!({
+            "inputs": [{
+                "amount": 8,
+                "id": "009a1f293253e41e",
+                "secret": "407915bc212be61a77e3e6d2aeb4c727980bda51cd06a6afc29e2861768a7837",
+                "C": "02bc9097997d81afb2cc7346b5e4345a9346bd2a506eb7958598a72f0cf85163ea",
+                "witness": "{\"signatures\":[\"60f3c9b766770b46caac1d27e1ae6b77c8866ebaeba0b9489fe6a15a837eaa6fcd6eaa825499c72ac342983983fd3ba3a8a41f56677cc99ffd73da68b59e1383\"]}"
+            }],
+            "outputs": [],
+            "payment_preimage": "0000000000000000000000000000000000000000000000000000000000000001"
+        });
+
+        sanitize(&mut request);
+
+        let input = &request["inputs"][0];
+        let secret = input["secret"].as_str().unwrap();
+        assert!(secret.starts_with(REDACTED_PREFIX));
+        assert!(
+            !secret.contains("407915bc212be61a77e3e6d2aeb4c727980bda51cd06a6afc29e2861768a7837")
+        );
+        let c = input["C"].as_str().unwrap();
+        assert!(c.starts_with(REDACTED_PREFIX));
+        assert_ne!(secret, c);
+        assert_eq!(input["amount"], 8);
+        assert_eq!(input["id"], "009a1f293253e41e");
+        assert_eq!(input["witness"], Value::Null);
+        assert_eq!(request["payment_preimage"], Value::Null);
+
+        let exchange = RecordedExchange {
+            timestamp: 0,
+            method: "POST".to_string(),
+            path: "/v1/swap".to_string(),
+            request: Some(request),
+            status: 200,
+            response: None,
+        };
+        assert!(exchange.has_redacted_proofs());
+    }
+
+    #[test]
+    fn test_sanitize_hashes_proofs_consistently() {
+        let proof = json!({
+            "amount": 2,
+            "id": "009a1f293253e41e",
+            "secret": "fe15109314e61d7756b0f8ee0f23a624acaa3f4e042f61433c728c7057b931be",
+            "C": "029e8e5050b890a7d6c0968db16bc1d5d5fa040ea1de284f6ec69d61299f671059"
+        });
+        let mut first = json!({ "inputs": [proof.clone()] });
+        let mut second = json!({ "proofs": [proof] });
+
+        sanitize(&mut first);
+        sanitize(&mut second);
+
+        assert_eq!(first["inputs"][0], second["proofs"][0]);
+    }
+
+    #[test]
+    fn test_blinded_messages_are_not_redacted() {
+        let mut request = json!({
+            "outputs": [{
+                "amount": 8,
+                "id": "009a1f293253e41e",
+                "B_": "02634a2c2b34bec9e8a4aba4361f6bf202d7fa2365379b0840afe249a7a9d71239"
+            }]
+        });
+        let expected = request.clone();
+
+        sanitize(&mut request);
+
+        assert_eq!(request, expected);
+        assert!(!contains_redacted_proof(&request));
+    }
+
+    #[test]
+    fn test_sanitize_redacts_every_redacted_field() {
+        for field in REDACTED_FIELDS {
+            let mut entry = json!({ "kept": "value" });
+            entry[field] = json!("secret value");
+            let mut value = json!({ "nested": [entry] });
+
+            sanitize(&mut value);
+
+            assert_eq!(value["nested"][0][field], Value::Null, "{field}");
+            assert_eq!(value["nested"][0]["kept"], "value", "{field}");
+        }
+    }
+}
@@ -4,6 +4,7 @@ use axum::extract::{Json, Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use cdk::error::{ErrorCode, ErrorResponse};
+use cdk::keyset_history::KeysetHistory;
 use cdk::mint::QuoteId;
 #[cfg(feature = "auth")]
 use cdk::nuts::nut21::{Method, ProtectedEndpoint, RoutePath};
@@ -14,6 +15,7 @@ use cdk::nuts::{
     SwapRequest, SwapResponse,
 };
 use cdk::util::unix_time;
+use cdk::ws::CBOR_SUBPROTOCOL;
 use paste::paste;
 use tracing::instrument;
 
@@ -134,6 +136,30 @@ pub(crate) async fn get_keysets(
     Ok(Json(state.mint.keysets()))
 }
 
+#[cfg_attr(feature = "swagger", utoipa::path(
+    get,
+    context_path = "/v1",
+    path = "/keysets/history",
+    responses(
+        (status = 200, description = "Successful response", body = KeysetHistory, content_type = "application/json"),
+        (status = 500, description = "Server error", body = ErrorResponse, content_type = "application/json")
+    )
+))]
+/// Get every keyset of the mint with its keys
+///
+/// This endpoint returns all keysets of the mint, active and retired, with their keys and validity range, signed by the mint identity key if it has one.
+#[instrument(skip_all)]
+pub(crate) async fn get_keyset_history(
+    State(state): State<MintState>,
+) -> Result<Json<KeysetHistory>, Response> {
+    let history = state.mint.keyset_history().map_err(|err| {
+        tracing::error!("Could not get keyset history: {}", err);
+        into_response(err)
+    })?;
+
+    Ok(Json(history))
+}
+
 #[cfg_attr(feature = "swagger", utoipa::path(
     post,
     context_path = "/v1",
@@ -222,7 +248,8 @@ pub(crate) async fn ws_handler(
     State(state): State<MintState>,
     ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    ws.on_upgrade(|ws| main_websocket(ws, state))
+    ws.protocols([CBOR_SUBPROTOCOL])
+        .on_upgrade(|ws| main_websocket(ws, state))
 }
 
 /// Mint tokens by paying a BOLT11 Lightning invoice.
@@ -441,18 +468,22 @@ pub(crate) async fn post_check(
 pub(crate) async fn get_mint_info(
     State(state): State<MintState>,
 ) -> Result<Json<MintInfo>, Response> {
-    Ok(Json(
-        state
-            .mint
-            .mint_info()
-            .await
-            .map_err(|err| {
-                tracing::error!("Could not get mint info: {}", err);
-                into_response(err)
-            })?
-            .clone()
-            .time(unix_time()),
-    ))
+    let mint_info = state
+        .mint
+        .mint_info()
+        .await
+        .map_err(|err| {
+            tracing::error!("Could not get mint info: {}", err);
+            into_response(err)
+        })?
+        .time(unix_time());
+
+    Ok(Json(state.mint.sign_mint_info(mint_info).map_err(
+        |err| {
+            tracing::error!("Could not sign mint info: {}", err);
+            into_response(err)
+        },
+    )?))
 }
 
 #[cfg_attr(feature = "swagger", utoipa::path(
@@ -537,8 +568,9 @@ pub(crate) async fn post_restore(
     Ok(Json(restore_response))
 }
 
+/// Response of an error with the HTTP status of its error code
 #[instrument(skip_all)]
-pub(crate) fn into_response<T>(error: T) -> Response
+pub fn into_response<T>(error: T) -> Response
 where
     T: Into<ErrorResponse>,
 {
@@ -5,8 +5,8 @@ use cdk::mint::QuoteId;
 use cdk::nuts::nut17::NotificationPayload;
 use cdk::pub_sub::SubId;
 use cdk::ws::{
-    notification_to_ws_message, NotificationInner, WsErrorBody, WsMessageOrResponse,
-    WsMethodRequest, WsRequest,
+    notification_to_ws_message, NotificationInner, WsCodecError, WsEncoding, WsErrorBody, WsFrame,
+    WsMessageOrResponse, WsMethodRequest, WsRequest,
 };
 use futures::StreamExt;
 use tokio::sync::mpsc;
@@ -34,6 +34,13 @@ async fn process(
 
 pub use error::WsError;
 
+fn frame_to_message(frame: WsFrame) -> Message {
+    match frame {
+        WsFrame::Text(text) => Message::Text(text.into()),
+        WsFrame::Binary(bytes) => Message::Binary(bytes.into()),
+    }
+}
+
 pub struct WsContext {
     state: MintState,
     subscriptions: HashMap<SubId, tokio::task::JoinHandle<()>>,
@@ -46,7 +53,15 @@ pub struct WsContext {
 ///
 /// For simplicity sake this function will spawn tasks for each subscription and
 /// keep them in a hashmap, and will have a single subscriber for all of them.
+///
+/// Messages are CBOR binary frames on connections that negotiated
+/// [`cdk::ws::CBOR_SUBPROTOCOL`] and JSON text frames otherwise.
 pub async fn main_websocket(mut socket: WebSocket, state: MintState) {
+    let encoding = WsEncoding::from_subprotocol(
+        socket
+            .protocol()
+            .and_then(|protocol| protocol.to_str().ok()),
+    );
     let (publisher, mut subscriber) = mpsc::channel(100);
     let mut context = WsContext {
         state,
@@ -66,24 +81,24 @@ pub async fn main_websocket(mut socket: WebSocket, state: MintState) {
                     sub_id,
                     payload,
                 });
-                let message = match serde_json::to_string(&notification) {
-                    Ok(message) => message,
+                let message = match encoding.encode(&notification) {
+                    Ok(frame) => frame_to_message(frame),
                     Err(err) => {
                         tracing::error!("Could not serialize notification: {}", err);
                         continue;
                     }
                 };
 
-                if let Err(err)= socket.send(Message::Text(message.into())).await {
+                if let Err(err)= socket.send(message).await {
                     tracing::error!("Could not send websocket message: {}", err);
                     break;
                 }
             }
 
             Some(from_ws) = socket.next() => {
-                let text = match from_ws {
-                    Ok(Message::Text(text)) => text.to_string(),
-                    Ok(Message::Binary(bin)) => String::from_utf8_lossy(&bin).to_string(),
+                let frame = match from_ws {
+                    Ok(Message::Text(text)) => WsFrame::Text(text.to_string()),
+                    Ok(Message::Binary(bin)) => WsFrame::Binary(bin.to_vec()),
                     Ok(Message::Ping(payload)) => {
                         // Reply with Pong with same payload
                         if let Err(e) = socket.send(Message::Pong(payload)).await {
@@ -116,7 +131,7 @@ pub async fn main_websocket(mut socket: WebSocket, state: MintState) {
                 };
 
 
-                let request = match serde_json::from_str::<WsRequest>(&text) {
+                let request = match encoding.decode::<WsRequest>(&frame) {
                     Ok(request) => request,
                     Err(err) => {
                         tracing::error!("Could not parse request: {}", err);
@@ -124,12 +139,13 @@ pub async fn main_websocket(mut socket: WebSocket, state: MintState) {
                     }
                 };
 
-                match process(&mut context, request).await {
-                    Ok(result) => {
-                        if let Err(err) = socket
-                            .send(Message::Text(result.to_string().into()))
-                            .await
-                        {
+                match process(&mut context, request)
+                    .await
+                    .map_err(WsCodecError::from)
+                    .and_then(|result| encoding.encode(&result))
+                {
+                    Ok(frame) => {
+                        if let Err(err) = socket.send(frame_to_message(frame)).await {
                             tracing::error!("Could not send request: {}", err);
                             break;
                         }
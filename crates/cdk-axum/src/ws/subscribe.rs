@@ -15,13 +15,14 @@ pub(crate) async fn handle(
         return Err(WsError::InvalidParams);
     }
 
+    let since = params.since;
     let params: IndexableParams = params.into();
 
     let mut subscription = context
         .state
         .mint
         .pubsub_manager()
-        .try_subscribe(params)
+        .try_subscribe_since(params, since)
         .await
         .map_err(|_| WsError::ParseError)?;
 
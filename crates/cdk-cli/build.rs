@@ -0,0 +1,14 @@
+fn main() {
+    // Target triple selecting the release artifact of `self-update`
+    println!(
+        "cargo:rustc-env=CDK_CLI_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+
+    // Release signing key and metadata location are embedded by release builds
+    println!("cargo:rerun-if-env-changed=CDK_CLI_RELEASE_PUBKEY");
+    println!("cargo:rerun-if-env-changed=CDK_CLI_RELEASE_URL");
+    // Nightly builds are told apart by their build time
+    println!("cargo:rerun-if-env-changed=CDK_CLI_BUILD_METADATA");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -9,7 +9,7 @@ use bip39::Mnemonic;
 use cdk::cdk_database;
 use cdk::cdk_database::WalletDatabase;
 use cdk::nuts::CurrencyUnit;
-use cdk::wallet::MultiMintWallet;
+use cdk::wallet::{account_seed, MultiMintWallet, DEFAULT_ACCOUNT};
 #[cfg(feature = "redb")]
 use cdk_redb::WalletRedbDatabase;
 use cdk_sqlite::WalletSqliteDatabase;
@@ -21,6 +21,7 @@ use url::Url;
 mod nostr_storage;
 mod sub_commands;
 mod token_storage;
+mod updater;
 mod utils;
 
 const DEFAULT_WORK_DIR: &str = ".cdk-cli";
@@ -52,6 +53,9 @@ struct Cli {
     /// Currency unit to use for the wallet
     #[arg(short, long, default_value = "sat")]
     unit: String,
+    /// Account to use, created on first use (sqlite only)
+    #[arg(long)]
+    account: Option<String>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -68,14 +72,26 @@ enum Commands {
     MintPending,
     /// Receive token
     Receive(sub_commands::receive::ReceiveSubCommand),
+    /// Claim tokens sent to a nostr key as they arrive
+    Watch(sub_commands::watch::WatchSubCommand),
     /// Send
     Send(sub_commands::send::SendSubCommand),
+    /// Show or set the spending limits
+    SpendingLimits(sub_commands::spending_limits::SpendingLimitsSubCommand),
+    /// Lock savings until a date, or list and unlock them
+    Savings(sub_commands::savings::SavingsSubCommand),
     /// Transfer tokens between mints
     Transfer(sub_commands::transfer::TransferSubCommand),
+    /// Atomic swap of ecash between mints with another wallet
+    Swap(sub_commands::swap::SwapSubCommand),
+    /// Stream payments on an interval
+    Stream(sub_commands::stream::StreamSubCommand),
     /// Reclaim pending proofs that are no longer pending
     CheckPending,
     /// View mint info
     MintInfo(sub_commands::mint_info::MintInfoSubcommand),
+    /// Discover mints
+    Mints(sub_commands::mints::MintsSubCommand),
     /// Mint proofs via bolt11
     Mint(sub_commands::mint::MintSubCommand),
     /// Burn Spent tokens
@@ -98,6 +114,20 @@ enum Commands {
     CatLogin(sub_commands::cat_login::CatLoginSubCommand),
     /// Cat login with device code flow
     CatDeviceLogin(sub_commands::cat_device_login::CatDeviceLoginSubCommand),
+    /// Inspect wallet internals
+    Debug(sub_commands::debug::DebugSubCommand),
+    /// Inspect and prune the wallet database
+    Db(sub_commands::db::DbSubCommand),
+    /// Report the fees paid per mint and month
+    Fees(sub_commands::fees::FeesSubCommand),
+    /// Export proofs as JSON
+    ProofExport(sub_commands::proof_export::ProofExportSubCommand),
+    /// Import proofs exported as JSON
+    ProofImport(sub_commands::proof_import::ProofImportSubCommand),
+    /// Import the backup of another wallet
+    Import(sub_commands::import_backup::ImportBackupSubCommand),
+    /// Update cdk-cli to the latest signed release
+    SelfUpdate(sub_commands::self_update::SelfUpdateSubCommand),
 }
 
 #[tokio::main]
@@ -112,6 +142,11 @@ async fn main() -> Result<()> {
     // Parse input
     tracing_subscriber::fmt().with_env_filter(env_filter).init();
 
+    // Updating does not need the wallet
+    if let Commands::SelfUpdate(sub_command_args) = &args.command {
+        return sub_commands::self_update::self_update(sub_command_args).await;
+    }
+
     let work_dir = match &args.work_dir {
         Some(work_dir) => work_dir.clone(),
         None => {
@@ -122,35 +157,49 @@ async fn main() -> Result<()> {
 
     fs::create_dir_all(&work_dir)?;
 
-    let localstore: Arc<dyn WalletDatabase<Err = cdk_database::Error> + Send + Sync> =
-        match args.engine.as_str() {
-            "sqlite" => {
-                let sql_path = work_dir.join("cdk-cli.sqlite");
-                #[cfg(not(feature = "sqlcipher"))]
-                let sql = WalletSqliteDatabase::new(&sql_path).await?;
-                #[cfg(feature = "sqlcipher")]
-                let sql = {
-                    match args.password {
-                        Some(pass) => WalletSqliteDatabase::new((sql_path, pass)).await?,
-                        None => bail!("Missing database password"),
-                    }
-                };
+    let (localstore, account): (
+        Arc<dyn WalletDatabase<Err = cdk_database::Error> + Send + Sync>,
+        u32,
+    ) = match args.engine.as_str() {
+        "sqlite" => {
+            let sql_path = work_dir.join("cdk-cli.sqlite");
+            #[cfg(not(feature = "sqlcipher"))]
+            let sql = WalletSqliteDatabase::new(&sql_path).await?;
+            #[cfg(feature = "sqlcipher")]
+            let sql = {
+                match args.password {
+                    Some(pass) => WalletSqliteDatabase::new((sql_path, pass)).await?,
+                    None => bail!("Missing database password"),
+                }
+            };
 
-                Arc::new(sql)
+            let account = match &args.account {
+                Some(name) => utils::account_index(&sql, name).await?,
+                None => DEFAULT_ACCOUNT,
+            };
+
+            (Arc::new(sql.with_account(account)), account)
+        }
+        "redb" => {
+            if args.account.is_some() {
+                bail!("Accounts need the sqlite engine");
             }
-            "redb" => {
-                #[cfg(feature = "redb")]
-                {
-                    let redb_path = work_dir.join("cdk-cli.redb");
-                    Arc::new(WalletRedbDatabase::new(&redb_path)?)
-                }
-                #[cfg(not(feature = "redb"))]
-                {
-                    bail!("redb feature not enabled");
-                }
+
+            #[cfg(feature = "redb")]
+            {
+                let redb_path = work_dir.join("cdk-cli.redb");
+                (
+                    Arc::new(WalletRedbDatabase::new(&redb_path)?),
+                    DEFAULT_ACCOUNT,
+                )
+            }
+            #[cfg(not(feature = "redb"))]
+            {
+                bail!("redb feature not enabled");
             }
-            _ => bail!("Unknown DB engine"),
-        };
+        }
+        _ => bail!("Unknown DB engine"),
+    };
 
     let seed_path = work_dir.join("seed");
 
@@ -171,7 +220,7 @@ async fn main() -> Result<()> {
             mnemonic
         }
     };
-    let seed = mnemonic.to_seed_normalized("");
+    let seed = account_seed(&mnemonic.to_seed_normalized(""), account)?;
 
     // Parse currency unit from args
     let currency_unit = CurrencyUnit::from_str(&args.unit)
@@ -193,6 +242,11 @@ async fn main() -> Result<()> {
         None => MultiMintWallet::new(localstore.clone(), seed, currency_unit.clone()).await?,
     };
 
+    // Spends above the spending limits are confirmed on the terminal
+    multi_mint_wallet
+        .set_confirmation_handler(Some(Arc::new(utils::PromptConfirmationHandler)))
+        .await;
+
     match &args.command {
         Commands::DecodeToken(sub_command_args) => {
             sub_commands::decode_token::decode_token(sub_command_args)
@@ -204,18 +258,37 @@ async fn main() -> Result<()> {
         Commands::Receive(sub_command_args) => {
             sub_commands::receive::receive(&multi_mint_wallet, sub_command_args, &work_dir).await
         }
+        Commands::Watch(sub_command_args) => {
+            sub_commands::watch::watch(&multi_mint_wallet, sub_command_args, &work_dir).await
+        }
         Commands::Send(sub_command_args) => {
             sub_commands::send::send(&multi_mint_wallet, sub_command_args).await
         }
+        Commands::SpendingLimits(sub_command_args) => {
+            sub_commands::spending_limits::spending_limits(&multi_mint_wallet, sub_command_args)
+                .await
+        }
+        Commands::Savings(sub_command_args) => {
+            sub_commands::savings::savings(&multi_mint_wallet, sub_command_args).await
+        }
         Commands::Transfer(sub_command_args) => {
             sub_commands::transfer::transfer(&multi_mint_wallet, sub_command_args).await
         }
+        Commands::Swap(sub_command_args) => {
+            sub_commands::swap::swap(&multi_mint_wallet, sub_command_args).await
+        }
+        Commands::Stream(sub_command_args) => {
+            sub_commands::stream::stream(&multi_mint_wallet, sub_command_args).await
+        }
         Commands::CheckPending => {
             sub_commands::check_pending::check_pending(&multi_mint_wallet).await
         }
         Commands::MintInfo(sub_command_args) => {
             sub_commands::mint_info::mint_info(args.proxy, sub_command_args).await
         }
+        Commands::Mints(sub_command_args) => {
+            sub_commands::mints::mints(&multi_mint_wallet, sub_command_args).await
+        }
         Commands::Mint(sub_command_args) => {
             sub_commands::mint::mint(&multi_mint_wallet, sub_command_args).await
         }
@@ -264,5 +337,24 @@ async fn main() -> Result<()> {
             )
             .await
         }
+        Commands::Debug(sub_command_args) => {
+            sub_commands::debug::debug(&multi_mint_wallet, sub_command_args).await
+        }
+        Commands::Db(sub_command_args) => {
+            sub_commands::db::db(&multi_mint_wallet, sub_command_args).await
+        }
+        Commands::Fees(sub_command_args) => {
+            sub_commands::fees::fees(&multi_mint_wallet, sub_command_args).await
+        }
+        Commands::ProofExport(sub_command_args) => {
+            sub_commands::proof_export::proof_export(&multi_mint_wallet, sub_command_args).await
+        }
+        Commands::ProofImport(sub_command_args) => {
+            sub_commands::proof_import::proof_import(&multi_mint_wallet, sub_command_args).await
+        }
+        Commands::Import(sub_command_args) => {
+            sub_commands::import_backup::import_backup(&multi_mint_wallet, sub_command_args).await
+        }
+        Commands::SelfUpdate(_) => unreachable!("self-update runs before the wallet is opened"),
     }
 }
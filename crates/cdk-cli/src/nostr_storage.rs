@@ -1,4 +1,5 @@
 use std::fs;
+use std::io;
 use std::path::Path;
 
 use anyhow::Result;
@@ -35,3 +36,42 @@ pub async fn get_nostr_last_checked(
         Err(_) => Ok(None),
     }
 }
+
+/// Stores the tokens sent to a nostr key that could not be claimed yet
+pub async fn store_nostr_unclaimed_tokens(
+    work_dir: &Path,
+    verifying_key: &PublicKey,
+    tokens: &[String],
+) -> Result<()> {
+    let key_hex = hex::encode(verifying_key.to_bytes());
+    let file_path = work_dir.join(format!("nostr_unclaimed_{key_hex}"));
+
+    if tokens.is_empty() {
+        return match fs::remove_file(file_path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        };
+    }
+
+    fs::write(file_path, tokens.join("\n"))?;
+
+    Ok(())
+}
+
+/// Gets the tokens sent to a nostr key that could not be claimed yet
+pub async fn get_nostr_unclaimed_tokens(
+    work_dir: &Path,
+    verifying_key: &PublicKey,
+) -> Result<Vec<String>> {
+    let key_hex = hex::encode(verifying_key.to_bytes());
+    let file_path = work_dir.join(format!("nostr_unclaimed_{key_hex}"));
+
+    match fs::read_to_string(file_path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(_) => Ok(Vec::new()),
+    }
+}
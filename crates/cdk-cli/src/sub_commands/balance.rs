@@ -7,6 +7,15 @@ use cdk::wallet::MultiMintWallet;
 use cdk::Amount;
 
 pub async fn balance(multi_mint_wallet: &MultiMintWallet) -> Result<()> {
+    // Matured savings count toward the balance again
+    let unlocked = multi_mint_wallet.unlock_savings().await?;
+    if unlocked > Amount::ZERO {
+        println!(
+            "Unlocked {unlocked} {} of savings",
+            multi_mint_wallet.unit()
+        );
+    }
+
     // Show individual mint balances
     let mint_balances = mint_balances(multi_mint_wallet, multi_mint_wallet.unit()).await?;
 
@@ -21,6 +30,14 @@ pub async fn balance(multi_mint_wallet: &MultiMintWallet) -> Result<()> {
         );
     }
 
+    let mut locked = Amount::ZERO;
+    for wallet in multi_mint_wallet.get_wallets().await {
+        locked += wallet.locked_savings_balance().await?;
+    }
+    if locked > Amount::ZERO {
+        println!("Locked savings: {} {}", locked, multi_mint_wallet.unit());
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use cdk::wallet::MultiMintWallet;
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct DbSubCommand {
+    #[command(subcommand)]
+    command: DbCommands,
+}
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Show what the wallet database holds
+    Stats,
+    /// Show or set the days finalized quotes are kept after they expired
+    Retention(RetentionSubCommand),
+    /// Delete the finalized quotes older than the retention
+    Prune,
+}
+
+#[derive(Args)]
+pub struct RetentionSubCommand {
+    /// Days to keep finalized quotes, 0 to keep them forever
+    #[arg(long)]
+    days: Option<u64>,
+}
+
+pub async fn db(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &DbSubCommand,
+) -> Result<()> {
+    match &sub_command_args.command {
+        DbCommands::Stats => stats(multi_mint_wallet).await,
+        DbCommands::Retention(args) => retention(multi_mint_wallet, args).await,
+        DbCommands::Prune => prune(multi_mint_wallet).await,
+    }
+}
+
+async fn stats(multi_mint_wallet: &MultiMintWallet) -> Result<()> {
+    let quotes = multi_mint_wallet.quote_stats().await?;
+    let transactions = multi_mint_wallet.list_transactions(None).await?;
+
+    println!("Mints: {}", multi_mint_wallet.get_wallets().await.len());
+    println!("Transactions: {}", transactions.len());
+    println!(
+        "Mint quotes: {} ({} finalized)",
+        quotes.mint_quotes, quotes.finalized_mint_quotes
+    );
+    println!(
+        "Melt quotes: {} ({} finalized)",
+        quotes.melt_quotes, quotes.finalized_melt_quotes
+    );
+    print_retention(multi_mint_wallet.quote_retention().await?);
+
+    Ok(())
+}
+
+async fn retention(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &RetentionSubCommand,
+) -> Result<()> {
+    if let Some(days) = sub_command_args.days {
+        multi_mint_wallet
+            .set_quote_retention((days > 0).then_some(days))
+            .await?;
+    }
+
+    print_retention(multi_mint_wallet.quote_retention().await?);
+
+    Ok(())
+}
+
+async fn prune(multi_mint_wallet: &MultiMintWallet) -> Result<()> {
+    let Some(days) = multi_mint_wallet.quote_retention().await? else {
+        println!("No quote retention set, set one with `db retention --days`");
+        return Ok(());
+    };
+
+    let pruned = multi_mint_wallet.prune_quotes().await?;
+
+    println!(
+        "Deleted {} mint and {} melt quotes older than {days} days",
+        pruned.mint_quotes, pruned.melt_quotes
+    );
+
+    Ok(())
+}
+
+fn print_retention(days: Option<u64>) {
+    match days {
+        Some(days) => println!("Quote retention: {days} days"),
+        None => println!("Quote retention: forever"),
+    }
+}
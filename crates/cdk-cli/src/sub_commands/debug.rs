@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Result};
+use cdk::mint_url::MintUrl;
+use cdk::wallet::MultiMintWallet;
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct DebugSubCommand {
+    #[command(subcommand)]
+    command: DebugCommands,
+}
+
+#[derive(Subcommand)]
+pub enum DebugCommands {
+    /// Show the secret derivation counters of a mint's keysets
+    Derivations(DerivationsSubCommand),
+}
+
+#[derive(Args)]
+pub struct DerivationsSubCommand {
+    /// Mint Url
+    mint_url: MintUrl,
+    /// Restore from seed first to find gaps in the used counters
+    #[arg(long, default_value_t = false)]
+    restore: bool,
+}
+
+pub async fn debug(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &DebugSubCommand,
+) -> Result<()> {
+    match &sub_command_args.command {
+        DebugCommands::Derivations(args) => derivations(multi_mint_wallet, args).await,
+    }
+}
+
+async fn derivations(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &DerivationsSubCommand,
+) -> Result<()> {
+    let wallet = multi_mint_wallet
+        .get_wallet(&sub_command_args.mint_url)
+        .await
+        .ok_or(anyhow!("Mint is not in the wallet"))?;
+
+    if sub_command_args.restore {
+        let amount = wallet.restore().await?;
+        println!("Restored {amount}");
+    }
+
+    let report = wallet.derivation_report().await?;
+
+    println!("Mint: {}", report.mint_url);
+
+    for keyset in report.keysets {
+        println!();
+        println!(
+            "Keyset {} ({}, {})",
+            keyset.keyset_id,
+            keyset.unit,
+            if keyset.active { "active" } else { "inactive" }
+        );
+        println!("  Next counter: {}", keyset.next_counter);
+
+        match &keyset.next_path {
+            Some(path) => println!("  Next path: {path}"),
+            None => println!("  Next path: none, derived with HMAC-SHA256"),
+        }
+
+        println!("  Restore high water: {}", keyset.restore_high_water);
+
+        match &keyset.restore {
+            Some(restore) => {
+                println!(
+                    "  Restore: scanned {}..{}, used below {}",
+                    restore.started_at, restore.scanned_until, restore.used_until
+                );
+
+                for gap in &restore.gaps {
+                    println!("  Gap: {}..{}", gap.start, gap.end);
+                }
+
+                if keyset.counter_behind_restore() {
+                    println!("  Warning: next counter is below counters the mint already signed");
+                }
+            }
+            None => println!("  Restore: not run, pass --restore to scan for gaps"),
+        }
+    }
+
+    Ok(())
+}
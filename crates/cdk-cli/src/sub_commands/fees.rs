@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use cdk::util::unix_time;
+use cdk::wallet::types::FeeKind;
+use cdk::wallet::MultiMintWallet;
+use cdk::Amount;
+use clap::Args;
+
+/// Seconds in a day
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Args)]
+pub struct FeesSubCommand {
+    /// Only include fees paid in the last days
+    #[arg(long)]
+    days: Option<u64>,
+}
+
+/// Input and lightning fees paid in a month
+#[derive(Default)]
+struct MonthlyFees {
+    input: Amount,
+    lightning: Amount,
+}
+
+pub async fn fees(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &FeesSubCommand,
+) -> Result<()> {
+    let since = sub_command_args
+        .days
+        .map(|days| unix_time().saturating_sub(days.saturating_mul(DAY_SECS)))
+        .unwrap_or_default();
+    let entries = multi_mint_wallet.fee_entries(since..).await?;
+    let unit = multi_mint_wallet.unit();
+
+    if entries.is_empty() {
+        println!("No fees paid");
+        return Ok(());
+    }
+
+    let mut report: BTreeMap<String, BTreeMap<String, MonthlyFees>> = BTreeMap::new();
+    for entry in entries {
+        let month = report
+            .entry(entry.mint_url.to_string())
+            .or_default()
+            .entry(year_month(entry.timestamp))
+            .or_default();
+
+        match entry.kind {
+            FeeKind::Input => month.input += entry.amount,
+            FeeKind::Lightning => month.lightning += entry.amount,
+        }
+    }
+
+    for (mint_url, months) in report {
+        let mut total = Amount::ZERO;
+        println!("{mint_url}");
+        for (month, fees) in months {
+            println!(
+                "  {month}: {} {unit} input, {} {unit} lightning",
+                fees.input, fees.lightning
+            );
+            total += fees.input + fees.lightning;
+        }
+        println!("  Total: {total} {unit}");
+    }
+
+    Ok(())
+}
+
+/// `YYYY-MM` of a unix timestamp in UTC
+fn year_month(timestamp: u64) -> String {
+    // Civil from days, http://howardhinnant.github.io/date_algorithms.html
+    let days = timestamp / DAY_SECS + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!("{year}-{month:02}")
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use cdk::mint_url::MintUrl;
+use cdk::wallet::{MultiMintWallet, WalletBackup};
+use clap::Args;
+
+#[derive(Args)]
+pub struct ImportBackupSubCommand {
+    /// Backup file of another wallet, with tokens or JSON proofs
+    input: PathBuf,
+    /// Mint of proofs the backup does not store a mint url for
+    #[arg(long)]
+    mint_url: Option<MintUrl>,
+}
+
+pub async fn import_backup(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &ImportBackupSubCommand,
+) -> Result<()> {
+    let backup = WalletBackup::parse(
+        &fs::read_to_string(&sub_command_args.input)?,
+        sub_command_args.mint_url.clone(),
+    )?;
+
+    println!(
+        "Found {} tokens and {} proofs",
+        backup.tokens.len(),
+        backup.proof_count()
+    );
+
+    let imports = multi_mint_wallet.import_backup(backup).await?;
+
+    for (mint_url, import) in imports {
+        println!(
+            "{mint_url}: imported {} proofs worth {}, skipped {} duplicates, {} of unknown keysets and {} spent or pending",
+            import.imported, import.amount, import.duplicates, import.foreign, import.unspendable
+        );
+    }
+
+    Ok(())
+}
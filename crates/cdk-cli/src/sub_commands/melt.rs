@@ -4,6 +4,7 @@ use anyhow::{bail, Result};
 use cdk::amount::{amount_for_offer, Amount, MSAT_IN_SAT};
 use cdk::mint_url::MintUrl;
 use cdk::nuts::{CurrencyUnit, MeltOptions};
+use cdk::types::Melted;
 use cdk::wallet::MultiMintWallet;
 use cdk::Bolt11Invoice;
 use clap::{Args, ValueEnum};
@@ -61,6 +62,16 @@ fn create_melt_options(
     }
 }
 
+/// Helper function to print how the melt fee was spent
+fn print_fee_breakdown(melted: &Melted) {
+    println!(
+        "    Lightning fee: {}, Input fee: {}, Fee reserve returned: {}",
+        melted.fee_breakdown.lightning_fee,
+        melted.fee_breakdown.input_fee,
+        melted.fee_breakdown.fee_reserve_returned
+    );
+}
+
 pub async fn pay(
     multi_mint_wallet: &MultiMintWallet,
     sub_command_args: &MeltSubCommand,
@@ -152,6 +163,7 @@ pub async fn pay(
                 "  {} - Paid: {}, Fee: {}",
                 mint_url, melted.amount, melted.fee_paid
             );
+            print_fee_breakdown(&melted);
             total_paid += melted.amount;
             total_fees += melted.fee_paid;
 
@@ -279,6 +291,7 @@ pub async fn pay(
                     "Payment successful: Paid {} with fee {}",
                     melted.amount, melted.fee_paid
                 );
+                print_fee_breakdown(&melted);
                 if let Some(preimage) = melted.preimage {
                     println!("Payment preimage: {}", preimage);
                 }
@@ -350,6 +363,7 @@ pub async fn pay(
                     "Payment successful: Paid {} with fee {}",
                     melted.amount, melted.fee_paid
                 );
+                print_fee_breakdown(&melted);
                 if let Some(preimage) = melted.preimage {
                     println!("Payment preimage: {}", preimage);
                 }
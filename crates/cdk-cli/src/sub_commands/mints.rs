@@ -0,0 +1,195 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use cdk::mint_url::MintUrl;
+use cdk::nuts::SecretKey;
+use cdk::wallet::{MintAttestation, MultiMintWallet, Wallet};
+use clap::{Args, Subcommand};
+
+use crate::utils::relays_or_default;
+
+#[derive(Args)]
+pub struct MintsSubCommand {
+    #[command(subcommand)]
+    command: MintsCommands,
+}
+
+#[derive(Subcommand)]
+pub enum MintsCommands {
+    /// Discover mints announced and recommended on nostr
+    Discover(DiscoverSubCommand),
+    /// Publish an attestation of your experience with a mint to nostr
+    Attest(AttestSubCommand),
+}
+
+#[derive(Args)]
+pub struct DiscoverSubCommand {
+    /// Nostr relays to query
+    /// Can be specified multiple times for multiple relays
+    /// If not provided, defaults to standard relays
+    #[arg(long, action = clap::ArgAction::Append)]
+    nostr_relay: Option<Vec<String>>,
+    /// Maximum number of mints to show
+    #[arg(long, default_value = "10")]
+    limit: usize,
+    /// Add the discovered mint at this position of the list to the wallet
+    #[arg(long)]
+    add: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct AttestSubCommand {
+    /// Mint to attest
+    mint_url: MintUrl,
+    /// Nostr secret key (nsec or hex) to sign the attestation with
+    #[arg(long)]
+    nostr_key: String,
+    /// Rating out of 5
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=5))]
+    rating: Option<u8>,
+    /// Review text
+    #[arg(long, default_value = "")]
+    review: String,
+    /// Observed uptime in percent
+    #[arg(long)]
+    uptime: Option<f64>,
+    /// Nostr relays to publish to
+    /// Can be specified multiple times for multiple relays
+    /// If not provided, defaults to standard relays
+    #[arg(long, action = clap::ArgAction::Append)]
+    nostr_relay: Option<Vec<String>>,
+}
+
+pub async fn mints(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &MintsSubCommand,
+) -> Result<()> {
+    match &sub_command_args.command {
+        MintsCommands::Discover(discover_args) => discover(multi_mint_wallet, discover_args).await,
+        MintsCommands::Attest(attest_args) => attest(multi_mint_wallet, attest_args).await,
+    }
+}
+
+async fn discover(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &DiscoverSubCommand,
+) -> Result<()> {
+    let relays = relays_or_default(&sub_command_args.nostr_relay);
+
+    println!("Discovering {} mints on nostr...", multi_mint_wallet.unit());
+
+    let mut mints = Wallet::discover_mints(relays, multi_mint_wallet.unit()).await?;
+    mints.truncate(sub_command_args.limit);
+
+    if mints.is_empty() {
+        println!("No mints found");
+        return Ok(());
+    }
+
+    for (i, mint) in mints.iter().enumerate() {
+        let name = mint
+            .mint_info
+            .as_ref()
+            .and_then(|info| info.name.clone())
+            .unwrap_or_default();
+
+        let rating = match mint.average_rating() {
+            Some(rating) => format!("{rating:.1}/5"),
+            None => "unrated".to_string(),
+        };
+
+        println!(
+            "{i}: {} {name} ({} recommendations, {rating})",
+            mint.mint_url,
+            mint.recommendations()
+        );
+
+        if mint.attested_melts() > 0 {
+            let uptime = match mint.average_uptime() {
+                Some(uptime) => format!("{uptime:.1}%"),
+                None => "unknown".to_string(),
+            };
+            let fee = match mint.average_fee_ppm() {
+                Some(fee_ppm) => format!("{fee_ppm} ppm"),
+                None => "unknown".to_string(),
+            };
+
+            println!(
+                "   attested: {} melts, uptime {uptime}, fee {fee}",
+                mint.attested_melts()
+            );
+        }
+
+        if let Some(description) = mint
+            .mint_info
+            .as_ref()
+            .and_then(|info| info.description.as_ref())
+        {
+            println!("   {description}");
+        }
+    }
+
+    if let Some(index) = sub_command_args.add {
+        let mint = match mints.get(index) {
+            Some(mint) => mint,
+            None => bail!("No discovered mint at position {index}"),
+        };
+
+        multi_mint_wallet
+            .add_mint(mint.mint_url.clone(), None)
+            .await?;
+
+        println!("Added mint {}", mint.mint_url);
+    }
+
+    Ok(())
+}
+
+async fn attest(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &AttestSubCommand,
+) -> Result<()> {
+    let wallet = multi_mint_wallet
+        .get_wallet(&sub_command_args.mint_url)
+        .await
+        .ok_or(anyhow!(
+            "Mint {} is not in the wallet",
+            sub_command_args.mint_url
+        ))?;
+
+    let secret_key = match sub_command_args.nostr_key.starts_with("nsec") {
+        true => {
+            let nostr_key = nostr_sdk::SecretKey::from_str(&sub_command_args.nostr_key)?;
+            SecretKey::from_str(&nostr_key.to_secret_hex())?
+        }
+        false => SecretKey::from_str(&sub_command_args.nostr_key)?,
+    };
+
+    let mut attestation = wallet.mint_attestation().await?;
+
+    if let Some(uptime) = sub_command_args.uptime {
+        match MintAttestation::uptime_from_percent(uptime) {
+            Some(uptime) => attestation.uptime_basis_points = Some(uptime),
+            None => bail!("Uptime must be a percentage between 0 and 100"),
+        }
+    }
+
+    println!(
+        "Attesting {} melts for {}",
+        attestation.successful_melts, sub_command_args.mint_url
+    );
+
+    let event_id = wallet
+        .publish_mint_attestation(
+            &secret_key,
+            relays_or_default(&sub_command_args.nostr_relay),
+            &attestation,
+            sub_command_args.rating,
+            &sub_command_args.review,
+        )
+        .await?;
+
+    println!("Published attestation {event_id}");
+
+    Ok(())
+}
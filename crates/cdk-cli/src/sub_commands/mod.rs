@@ -4,17 +4,30 @@ pub mod cat_device_login;
 pub mod cat_login;
 pub mod check_pending;
 pub mod create_request;
+pub mod db;
+pub mod debug;
 pub mod decode_request;
 pub mod decode_token;
+pub mod fees;
+pub mod import_backup;
 pub mod list_mint_proofs;
 pub mod melt;
 pub mod mint;
 pub mod mint_blind_auth;
 pub mod mint_info;
+pub mod mints;
 pub mod pay_request;
 pub mod pending_mints;
+pub mod proof_export;
+pub mod proof_import;
 pub mod receive;
 pub mod restore;
+pub mod savings;
+pub mod self_update;
 pub mod send;
+pub mod spending_limits;
+pub mod stream;
+pub mod swap;
 pub mod transfer;
 pub mod update_mint_url;
+pub mod watch;
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Result;
+use cdk::mint_url::MintUrl;
+use cdk::nuts::{Id, State};
+use cdk::wallet::MultiMintWallet;
+use clap::Args;
+
+#[derive(Args)]
+pub struct ProofExportSubCommand {
+    /// Only export proofs of this mint
+    #[arg(long)]
+    mint_url: Option<MintUrl>,
+    /// Only export proofs of this keyset
+    #[arg(long)]
+    keyset_id: Option<Id>,
+    /// Only export proofs in this state (UNSPENT, PENDING, RESERVED, PENDING_SPENT)
+    /// Can be specified multiple times for multiple states
+    #[arg(long, action = clap::ArgAction::Append, value_parser = State::from_str)]
+    state: Vec<State>,
+    /// File to write the proofs to, printed if not set
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub async fn proof_export(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &ProofExportSubCommand,
+) -> Result<()> {
+    let states = match sub_command_args.state.is_empty() {
+        true => None,
+        false => Some(sub_command_args.state.clone()),
+    };
+
+    let mut proofs = Vec::new();
+
+    for wallet in multi_mint_wallet.get_wallets().await {
+        if sub_command_args
+            .mint_url
+            .as_ref()
+            .is_some_and(|mint_url| mint_url != &wallet.mint_url)
+        {
+            continue;
+        }
+
+        let proof_infos = wallet
+            .localstore
+            .get_proofs(
+                Some(wallet.mint_url.clone()),
+                Some(wallet.unit.clone()),
+                states.clone(),
+                None,
+            )
+            .await?;
+
+        proofs.extend(proof_infos.into_iter().filter(|proof_info| {
+            sub_command_args
+                .keyset_id
+                .is_none_or(|keyset_id| proof_info.proof.keyset_id == keyset_id)
+        }));
+    }
+
+    let json = serde_json::to_string_pretty(&proofs)?;
+
+    match &sub_command_args.output {
+        Some(output) => {
+            fs::write(output, json)?;
+            println!("Exported {} proofs to {}", proofs.len(), output.display());
+        }
+        None => println!("{json}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use cdk::mint_url::MintUrl;
+use cdk::nuts::Proofs;
+use cdk::types::ProofInfo;
+use cdk::wallet::MultiMintWallet;
+use clap::Args;
+
+use crate::utils::get_or_create_wallet;
+
+#[derive(Args)]
+pub struct ProofImportSubCommand {
+    /// File written by `proof-export`
+    input: PathBuf,
+}
+
+pub async fn proof_import(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &ProofImportSubCommand,
+) -> Result<()> {
+    let proof_infos: Vec<ProofInfo> =
+        serde_json::from_str(&fs::read_to_string(&sub_command_args.input)?)?;
+
+    let mut proofs_by_mint: HashMap<MintUrl, Proofs> = HashMap::new();
+    let mut other_unit = 0;
+
+    for proof_info in proof_infos {
+        if &proof_info.unit != multi_mint_wallet.unit() {
+            other_unit += 1;
+            continue;
+        }
+
+        proofs_by_mint
+            .entry(proof_info.mint_url)
+            .or_default()
+            .push(proof_info.proof);
+    }
+
+    if other_unit > 0 {
+        println!(
+            "Skipped {other_unit} proofs of another unit than {}",
+            multi_mint_wallet.unit()
+        );
+    }
+
+    for (mint_url, proofs) in proofs_by_mint {
+        let wallet = get_or_create_wallet(multi_mint_wallet, &mint_url).await?;
+        let import = wallet.import_proofs(proofs).await?;
+
+        println!(
+            "{mint_url}: imported {} proofs worth {} ({} swapped for lack of a DLEQ proof), skipped {} duplicates, {} of unknown keysets, {} with invalid DLEQ proofs and {} spent or pending",
+            import.imported,
+            import.amount,
+            import.swapped,
+            import.duplicates,
+            import.foreign,
+            import.invalid,
+            import.unspendable
+        );
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -8,11 +9,11 @@ use cdk::mint_url::MintUrl;
 use cdk::nuts::{SecretKey, Token};
 use cdk::util::unix_time;
 use cdk::wallet::multi_mint_wallet::MultiMintWallet;
-use cdk::wallet::{MultiMintReceiveOptions, ReceiveOptions};
+use cdk::wallet::{MultiMintReceiveOptions, OfflineVerification, ReceiveOptions, TokenDelivery};
 use cdk::Amount;
+use cdk_nostr::{DirectMessage, RelayPool, RelayUse, GIFT_WRAP_BACKDATE};
 use clap::Args;
-use nostr_sdk::nips::nip04;
-use nostr_sdk::{Filter, Keys, Kind, Timestamp};
+use nostr_sdk::{Event, Filter, Keys, Kind, Timestamp};
 
 use crate::nostr_storage;
 use crate::utils::get_or_create_wallet;
@@ -42,6 +43,12 @@ pub struct ReceiveSubCommand {
     /// Transfer tokens from untrusted mints to this mint
     #[arg(long, value_name = "MINT_URL")]
     transfer_to: Option<String>,
+    /// Only verify the token offline against cached or embedded mint keys, without receiving it
+    #[arg(long)]
+    verify_offline: bool,
+    /// Read the token from a paper backup file, in words or alphanumeric
+    #[arg(long, conflicts_with = "token")]
+    paper: Option<PathBuf>,
 }
 
 pub async fn receive(
@@ -49,26 +56,25 @@ pub async fn receive(
     sub_command_args: &ReceiveSubCommand,
     work_dir: &Path,
 ) -> Result<()> {
-    let mut signing_keys = Vec::new();
-
-    if !sub_command_args.signing_key.is_empty() {
-        let mut s_keys: Vec<SecretKey> = sub_command_args
-            .signing_key
-            .iter()
-            .map(|s| {
-                if s.starts_with("nsec") {
-                    let nostr_key = nostr_sdk::SecretKey::from_str(s).expect("Invalid secret key");
-
-                    SecretKey::from_str(&nostr_key.to_secret_hex())
-                } else {
-                    SecretKey::from_str(s)
-                }
-            })
-            .collect::<Result<Vec<SecretKey>, _>>()?;
-        signing_keys.append(&mut s_keys);
+    let mut signing_keys = sub_command_args
+        .signing_key
+        .iter()
+        .map(|s| parse_signing_key(s))
+        .collect::<Result<Vec<SecretKey>>>()?;
+
+    let paper_token = match &sub_command_args.paper {
+        Some(path) => Some(Token::from_paper_backup(&fs::read_to_string(path)?)?.to_string()),
+        None => None,
+    };
+    let token = paper_token.as_ref().or(sub_command_args.token.as_ref());
+
+    if sub_command_args.verify_offline {
+        let token_str = token.ok_or(anyhow!("Token required to verify offline"))?;
+
+        return verify_offline(multi_mint_wallet, token_str).await;
     }
 
-    let amount = match &sub_command_args.token {
+    let amount = match token {
         Some(token_str) => {
             receive_token(
                 multi_mint_wallet,
@@ -140,7 +146,18 @@ pub async fn receive(
     Ok(())
 }
 
-async fn receive_token(
+/// Parse a hex or nsec secret key
+pub(crate) fn parse_signing_key(key: &str) -> Result<SecretKey> {
+    if key.starts_with("nsec") {
+        let nostr_key = nostr_sdk::SecretKey::from_str(key)?;
+
+        Ok(SecretKey::from_str(&nostr_key.to_secret_hex())?)
+    } else {
+        Ok(SecretKey::from_str(key)?)
+    }
+}
+
+pub(crate) async fn receive_token(
     multi_mint_wallet: &MultiMintWallet,
     token_str: &str,
     signing_keys: &[SecretKey],
@@ -183,53 +200,110 @@ async fn receive_token(
     Ok(amount)
 }
 
+/// Verify a token's DLEQ proofs without contacting the mint
+async fn verify_offline(multi_mint_wallet: &MultiMintWallet, token_str: &str) -> Result<()> {
+    let token = Token::from_str(token_str)?;
+    let mint_url = token.mint_url()?;
+
+    let wallet = multi_mint_wallet
+        .get_wallet(&mint_url)
+        .await
+        .ok_or(anyhow!("Mint {mint_url} is not in the wallet"))?;
+
+    let verification = wallet.verify_token_offline(&token).await?;
+
+    match verification {
+        OfflineVerification::KnownKeys => {
+            println!("Token of {} verified against known mint keys", token.value()?)
+        }
+        OfflineVerification::EmbeddedKeys => println!(
+            "Token of {} verified against keys embedded in the token, receive it once online to confirm",
+            token.value()?
+        ),
+    }
+
+    Ok(())
+}
+
 /// Receive tokens sent to nostr pubkey via dm
 async fn nostr_receive(
     relays: Vec<String>,
     nostr_signing_key: SecretKey,
     since: Option<u32>,
 ) -> Result<HashSet<String>> {
-    let verifying_key = nostr_signing_key.public_key();
-
-    let x_only_pubkey = verifying_key.x_only_public_key();
+    let keys = cdk_nostr::nostr_keys(&nostr_signing_key)?;
 
-    let nostr_pubkey = nostr_sdk::PublicKey::from_hex(&x_only_pubkey.to_string())?;
-
-    let since = since.map(|s| Timestamp::from(s as u64));
-
-    let filter = match since {
-        Some(since) => Filter::new()
-            .pubkey(nostr_pubkey)
-            .kind(Kind::EncryptedDirectMessage)
-            .since(since),
-        None => Filter::new()
-            .pubkey(nostr_pubkey)
-            .kind(Kind::EncryptedDirectMessage),
-    };
-
-    let client = nostr_sdk::Client::default();
-
-    client.connect().await;
+    let client = RelayPool::global().client(&relays, RelayUse::Read).await?;
 
     let events = client
-        .fetch_events_from(relays, filter, Duration::from_secs(30))
+        .fetch_events(dm_filter(&keys, since), Duration::from_secs(30))
         .await?;
 
     let mut tokens: HashSet<String> = HashSet::new();
 
-    let keys = Keys::from_str(&(nostr_signing_key).to_secret_hex())?;
-
     for event in events {
-        if event.kind == Kind::EncryptedDirectMessage {
-            if let Ok(msg) = nip04::decrypt(keys.secret_key(), &event.pubkey, event.content) {
-                if let Some(token) = cdk::wallet::util::token_from_text(&msg) {
-                    tokens.insert(token.to_string());
-                }
-            } else {
-                tracing::error!("Impossible to decrypt direct message");
-            }
+        let Some(message) = direct_message(&keys, &event, since).await else {
+            continue;
+        };
+        if let Some(token) = message_token(&message).await {
+            tokens.insert(token);
         }
     }
 
     Ok(tokens)
 }
+
+/// Filter for direct messages to `keys` sent since `since`
+///
+/// Gift wraps of private direct messages are backdated, so the filter starts
+/// earlier and messages written before `since` are skipped by [`direct_message`].
+pub(crate) fn dm_filter(keys: &Keys, since: Option<u32>) -> Filter {
+    let filter = Filter::new()
+        .pubkey(keys.public_key())
+        .kinds([Kind::EncryptedDirectMessage, Kind::GiftWrap]);
+
+    match since {
+        Some(since) => filter.since(Timestamp::from(
+            (since as u64).saturating_sub(GIFT_WRAP_BACKDATE),
+        )),
+        None => filter,
+    }
+}
+
+/// Direct message to `keys` in `event`, unless it was written before `since`
+pub(crate) async fn direct_message(
+    keys: &Keys,
+    event: &Event,
+    since: Option<u32>,
+) -> Option<DirectMessage> {
+    let message = match cdk_nostr::direct_message(keys, event).await {
+        Ok(message) => message?,
+        Err(err) => {
+            tracing::error!("Impossible to decrypt direct message: {}", err);
+            return None;
+        }
+    };
+
+    match since {
+        Some(since) if message.created_at.as_u64() < since as u64 => None,
+        _ => Some(message),
+    }
+}
+
+/// Token sent in a nostr direct message
+pub(crate) async fn message_token(message: &DirectMessage) -> Option<String> {
+    // Large tokens are sent as a reference to an encrypted blob
+    match TokenDelivery::from_message(&message.content) {
+        Some(delivery) => match delivery.token().await {
+            Ok(token) => Some(token),
+            Err(err) => {
+                tracing::error!("Could not fetch token: {}", err);
+                None
+            }
+        },
+        None => {
+            tracing::debug!("Direct message without a token");
+            None
+        }
+    }
+}
@@ -1,12 +1,27 @@
 use anyhow::Result;
 use cdk::mint_url::MintUrl;
-use cdk::wallet::MultiMintWallet;
+use cdk::wallet::{MultiMintWallet, RestoreOptions};
 use clap::Args;
 
 #[derive(Args)]
 pub struct RestoreSubCommand {
     /// Mint Url
     mint_url: MintUrl,
+    /// Counters asked to the mint per request
+    #[arg(long, default_value_t = 100)]
+    batch_size: u32,
+    /// Batches in a row without any signature after which the scan stops
+    #[arg(long, default_value_t = 3)]
+    gap_limit: u32,
+    /// Scan one more batch when a signature is found near the end of a batch
+    #[arg(long, default_value_t = false)]
+    adaptive: bool,
+    /// Start where the previous restore found the last signature
+    #[arg(long, default_value_t = false)]
+    incremental: bool,
+    /// Rebuild the transaction history from stored quotes and proofs after restoring
+    #[arg(long, default_value_t = false)]
+    rebuild_history: bool,
 }
 
 pub async fn restore(
@@ -27,9 +42,21 @@ pub async fn restore(
         }
     };
 
-    let amount = wallet.restore().await?;
+    let options = RestoreOptions {
+        batch_size: sub_command_args.batch_size,
+        gap_limit: sub_command_args.gap_limit,
+        adaptive: sub_command_args.adaptive,
+        incremental: sub_command_args.incremental,
+    };
+
+    let amount = wallet.restore_with_options(options).await?;
 
     println!("Restored {amount}");
 
+    if sub_command_args.rebuild_history {
+        let rebuilt = wallet.rebuild_history().await?;
+        println!("Rebuilt {} transactions", rebuilt.len());
+    }
+
     Ok(())
 }
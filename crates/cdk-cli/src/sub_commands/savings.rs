@@ -0,0 +1,71 @@
+use anyhow::{anyhow, Result};
+use cdk::mint_url::MintUrl;
+use cdk::util::unix_time;
+use cdk::wallet::MultiMintWallet;
+use cdk::Amount;
+use clap::Args;
+
+/// Seconds in a day
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Args)]
+pub struct SavingsSubCommand {
+    /// Amount to lock
+    #[arg(long, requires = "days")]
+    lock: Option<u64>,
+    /// Days until the locked amount can be unlocked
+    #[arg(long, requires = "lock")]
+    days: Option<u64>,
+    /// Mint to lock savings at, required when the wallet has several mints
+    #[arg(long)]
+    mint_url: Option<MintUrl>,
+}
+
+pub async fn savings(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &SavingsSubCommand,
+) -> Result<()> {
+    let wallets = multi_mint_wallet.get_wallets().await;
+    let unit = multi_mint_wallet.unit();
+
+    if let (Some(amount), Some(days)) = (sub_command_args.lock, sub_command_args.days) {
+        let wallet = match &sub_command_args.mint_url {
+            Some(mint_url) => wallets.iter().find(|wallet| &wallet.mint_url == mint_url),
+            None if wallets.len() == 1 => wallets.first(),
+            None => return Err(anyhow!("Set the mint to lock savings at with --mint-url")),
+        }
+        .ok_or(anyhow!("Unknown mint"))?;
+
+        let savings = wallet
+            .lock_savings(Amount::from(amount), unix_time() + days * DAY_SECS)
+            .await?;
+
+        println!(
+            "Locked {} {unit} at {} until {}",
+            savings.amount, wallet.mint_url, savings.unlock_time
+        );
+    }
+
+    for wallet in &wallets {
+        let unlocked = wallet.unlock_savings().await?;
+        if unlocked > Amount::ZERO {
+            println!(
+                "Unlocked {unlocked} {unit} of savings at {}",
+                wallet.mint_url
+            );
+        }
+
+        for savings in wallet.locked_savings().await? {
+            let days_left = savings
+                .unlock_time
+                .saturating_sub(unix_time())
+                .div_ceil(DAY_SECS);
+            println!(
+                "{}: {} {unit} locked until {} ({days_left} days left)",
+                wallet.mint_url, savings.amount, savings.unlock_time
+            );
+        }
+    }
+
+    Ok(())
+}
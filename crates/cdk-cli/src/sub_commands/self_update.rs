@@ -0,0 +1,59 @@
+use anyhow::{anyhow, Result};
+use clap::Args;
+use url::Url;
+
+use crate::updater::{self, Channel};
+use crate::CARGO_PKG_VERSION;
+
+#[derive(Args)]
+pub struct SelfUpdateSubCommand {
+    /// Release channel to update from
+    #[arg(long, value_enum, default_value_t = Channel::Stable)]
+    channel: Channel,
+    /// Release metadata url, instead of the one of this build
+    #[arg(long)]
+    metadata_url: Option<Url>,
+    /// Only show whether an update is available
+    #[arg(long, default_value_t = false)]
+    check: bool,
+}
+
+pub async fn self_update(sub_command_args: &SelfUpdateSubCommand) -> Result<()> {
+    let metadata_url = match &sub_command_args.metadata_url {
+        Some(metadata_url) => metadata_url.clone(),
+        None => updater::metadata_url(sub_command_args.channel)?,
+    };
+
+    let release = updater::fetch_release(metadata_url, sub_command_args.channel).await?;
+    let current_version = updater::current_version(
+        CARGO_PKG_VERSION.ok_or(anyhow!("This build has no version and cannot be updated"))?,
+    );
+
+    if !release.is_newer_than(&current_version)? {
+        println!(
+            "cdk-cli {current_version} is up to date, latest {} release is {}",
+            sub_command_args.channel, release.version
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Update available: {current_version} -> {} ({})",
+        release.version, release.channel
+    );
+
+    if sub_command_args.check {
+        return Ok(());
+    }
+
+    let artifact = release.artifact()?;
+    let path = updater::install(artifact).await?;
+
+    println!(
+        "Installed cdk-cli {} to {}",
+        release.version,
+        path.display()
+    );
+
+    Ok(())
+}
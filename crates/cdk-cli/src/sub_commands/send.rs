@@ -2,13 +2,34 @@ use std::str::FromStr;
 
 use anyhow::{anyhow, Result};
 use cdk::mint_url::MintUrl;
-use cdk::nuts::{Conditions, PublicKey, SpendingConditions};
+use cdk::nuts::{Conditions, PublicKey, SecretKey, SpendingConditions};
+use cdk::paper_backup::PaperFormat;
 use cdk::wallet::types::SendKind;
-use cdk::wallet::{MultiMintWallet, SendMemo, SendOptions};
+use cdk::wallet::{
+    send_token_nostr, BlobStore, BlossomServer, MultiMintWallet, SendMemo, SendOptions,
+};
 use cdk::Amount;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use url::Url;
 
-use crate::utils::get_number_input;
+use crate::utils::{get_number_input, relays_or_default};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum PaperBackupFormat {
+    /// BIP39 words
+    Words,
+    /// Groups of bech32 characters
+    Alphanumeric,
+}
+
+impl From<PaperBackupFormat> for PaperFormat {
+    fn from(format: PaperBackupFormat) -> Self {
+        match format {
+            PaperBackupFormat::Words => PaperFormat::Words,
+            PaperBackupFormat::Alphanumeric => PaperFormat::Alphanumeric,
+        }
+    }
+}
 
 #[derive(Args)]
 pub struct SendSubCommand {
@@ -42,6 +63,9 @@ pub struct SendSubCommand {
     /// Include fee to redeem in token
     #[arg(short, long)]
     include_fee: bool,
+    /// Embed the mint keys in the token so the receiver can verify it offline
+    #[arg(long)]
+    keyset_hints: bool,
     /// Amount willing to overpay to avoid a swap
     #[arg(short, long)]
     tolerance: Option<u64>,
@@ -60,6 +84,23 @@ pub struct SendSubCommand {
     /// Specific mints to exclude from transfers (can be specified multiple times)
     #[arg(long, action = clap::ArgAction::Append)]
     excluded_mints: Vec<String>,
+    /// Nostr pubkey (npub or hex) to send the token to as a direct message
+    #[arg(long)]
+    nostr_receiver: Option<String>,
+    /// Nostr key (nsec or hex) to send the direct message with, a new key is used if not set
+    #[arg(long, requires = "nostr_receiver")]
+    nostr_key: Option<String>,
+    /// Nostr relays to publish the direct message to
+    /// Can be specified multiple times for multiple relays
+    /// If not provided, defaults to standard relays
+    #[arg(long, action = clap::ArgAction::Append, requires = "nostr_receiver")]
+    nostr_relay: Option<Vec<String>>,
+    /// Blossom server to upload tokens too large for a direct message to
+    #[arg(long, requires = "nostr_receiver")]
+    blossom_server: Option<Url>,
+    /// Print the token as a paper backup sheet for cold storage (words or alphanumeric)
+    #[arg(long, conflicts_with_all = ["v3", "nostr_receiver"])]
+    paper: Option<PaperBackupFormat>,
 }
 
 pub async fn send(
@@ -211,6 +252,7 @@ pub async fn send(
         send_kind,
         include_fee: sub_command_args.include_fee,
         conditions,
+        include_keyset_hints: sub_command_args.keyset_hints,
         ..Default::default()
     };
 
@@ -272,6 +314,57 @@ pub async fn send(
         prepared.confirm(memo).await?
     };
 
+    if let Some(nostr_receiver) = &sub_command_args.nostr_receiver {
+        let receiver = nostr_sdk::PublicKey::parse(nostr_receiver)?;
+        let receiver = PublicKey::from_hex(format!("02{}", receiver.to_hex()))?;
+
+        let sender = match &sub_command_args.nostr_key {
+            Some(nostr_key) => {
+                let secret_key = nostr_sdk::SecretKey::parse(nostr_key)?;
+                SecretKey::from_hex(secret_key.to_secret_hex())?
+            }
+            None => SecretKey::generate(),
+        };
+
+        let blossom_server = match &sub_command_args.blossom_server {
+            Some(url) => Some(BlossomServer::new(url.clone(), &sender)?),
+            None => None,
+        };
+
+        let event_id = send_token_nostr(
+            &token,
+            &sender,
+            &receiver,
+            relays_or_default(&sub_command_args.nostr_relay),
+            blossom_server
+                .as_ref()
+                .map(|server| server as &dyn BlobStore),
+        )
+        .await?;
+
+        println!("Sent token to {nostr_receiver} in event {event_id}");
+
+        return Ok(());
+    }
+
+    if let Some(format) = sub_command_args.paper {
+        let description = format!(
+            "{} {} at {}",
+            token.value()?,
+            token.unit().unwrap_or_default(),
+            token.mint_url()?
+        );
+
+        print!(
+            "{}",
+            token
+                .to_paper_backup()?
+                .sheet(format.into(), Some(&description))
+        );
+
+        return Ok(());
+    }
+
     match sub_command_args.v3 {
         true => {
             let token = token;
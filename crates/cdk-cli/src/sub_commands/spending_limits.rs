@@ -0,0 +1,53 @@
+use anyhow::Result;
+use cdk::wallet::{MultiMintWallet, SpendingLimits};
+use cdk::Amount;
+use clap::Args;
+
+#[derive(Args)]
+pub struct SpendingLimitsSubCommand {
+    /// Largest amount of a single send or melt, 0 to remove the limit
+    #[arg(long)]
+    per_transaction: Option<u64>,
+    /// Largest amount sent and melted in 24 hours, 0 to remove the limit
+    #[arg(long)]
+    daily: Option<u64>,
+}
+
+/// Limit set by an argument, keeping the current one when it is not given
+fn updated_limit(current: Option<Amount>, arg: Option<u64>) -> Option<Amount> {
+    match arg {
+        Some(0) => None,
+        Some(limit) => Some(Amount::from(limit)),
+        None => current,
+    }
+}
+
+pub async fn spending_limits(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &SpendingLimitsSubCommand,
+) -> Result<()> {
+    let mut limits = multi_mint_wallet.spending_limits().await?;
+
+    if sub_command_args.per_transaction.is_some() || sub_command_args.daily.is_some() {
+        limits = SpendingLimits {
+            per_transaction: updated_limit(
+                limits.per_transaction,
+                sub_command_args.per_transaction,
+            ),
+            daily: updated_limit(limits.daily, sub_command_args.daily),
+        };
+
+        multi_mint_wallet.set_spending_limits(limits).await?;
+    }
+
+    let unit = multi_mint_wallet.unit();
+    let format_limit = |limit: Option<Amount>| match limit {
+        Some(limit) => format!("{limit} {unit}"),
+        None => "none".to_string(),
+    };
+
+    println!("Per transaction: {}", format_limit(limits.per_transaction));
+    println!("Daily: {}", format_limit(limits.daily));
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use cdk::mint_url::MintUrl;
+use cdk::wallet::{MultiMintWallet, PaymentStream, PaymentStreamDestination, Wallet};
+use cdk::Amount;
+use clap::{Args, Subcommand};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Args)]
+pub struct StreamSubCommand {
+    #[command(subcommand)]
+    command: StreamCommands,
+}
+
+#[derive(Subcommand)]
+pub enum StreamCommands {
+    /// Start a payment stream and run it until it is stopped or its budget is exhausted
+    Start(StartSubCommand),
+    /// Resume running a payment stream
+    Resume(StreamIdSubCommand),
+    /// Stop a payment stream
+    Stop(StreamIdSubCommand),
+    /// Show payment streams
+    Status(StatusSubCommand),
+}
+
+#[derive(Args)]
+pub struct StartSubCommand {
+    /// BOLT12 offer, BIP353 address or node id to pay with keysend
+    destination: String,
+    /// Amount paid on every interval
+    #[arg(long)]
+    amount: u64,
+    /// Seconds between payments
+    #[arg(long)]
+    interval: u64,
+    /// Maximum total spent by the stream, including fees
+    #[arg(long)]
+    budget: u64,
+    /// Mint URL to melt from
+    #[arg(long)]
+    mint_url: Option<MintUrl>,
+}
+
+#[derive(Args)]
+pub struct StreamIdSubCommand {
+    /// Payment stream id
+    id: String,
+}
+
+#[derive(Args)]
+pub struct StatusSubCommand {
+    /// Payment stream id, shows all streams if not provided
+    id: Option<String>,
+}
+
+pub async fn stream(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &StreamSubCommand,
+) -> Result<()> {
+    match &sub_command_args.command {
+        StreamCommands::Start(start_args) => start(multi_mint_wallet, start_args).await,
+        StreamCommands::Resume(resume_args) => {
+            let (wallet, _) = find_stream(multi_mint_wallet, &resume_args.id).await?;
+            run(&wallet, &resume_args.id).await
+        }
+        StreamCommands::Stop(stop_args) => {
+            let (wallet, _) = find_stream(multi_mint_wallet, &stop_args.id).await?;
+            let stream = wallet.stop_payment_stream(&stop_args.id).await?;
+            print_stream(&stream);
+            Ok(())
+        }
+        StreamCommands::Status(status_args) => {
+            let streams = match &status_args.id {
+                Some(id) => vec![find_stream(multi_mint_wallet, id).await?.1],
+                None => {
+                    let mut streams = Vec::new();
+                    for wallet in multi_mint_wallet.get_wallets().await {
+                        streams.extend(wallet.payment_streams().await?);
+                    }
+                    streams
+                }
+            };
+
+            if streams.is_empty() {
+                println!("No payment streams");
+            }
+
+            for stream in &streams {
+                print_stream(stream);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+async fn start(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &StartSubCommand,
+) -> Result<()> {
+    let destination = PaymentStreamDestination::from_str(&sub_command_args.destination)?;
+
+    let wallet = match &sub_command_args.mint_url {
+        Some(mint_url) => multi_mint_wallet
+            .get_wallet(mint_url)
+            .await
+            .ok_or(anyhow!("Mint {mint_url} is not in the wallet"))?,
+        None => {
+            let wallets = multi_mint_wallet.get_wallets().await;
+            match wallets.as_slice() {
+                [wallet] => wallet.clone(),
+                [] => bail!("No mints in the wallet"),
+                _ => bail!("Multiple mints in the wallet, specify one with --mint-url"),
+            }
+        }
+    };
+
+    let stream = wallet
+        .create_payment_stream(
+            destination,
+            Amount::from(sub_command_args.amount),
+            sub_command_args.interval,
+            Amount::from(sub_command_args.budget),
+        )
+        .await?;
+
+    println!("Started payment stream {}", stream.id);
+    println!(
+        "Run `stream stop {}` to stop it, or `stream resume {}` to resume it after exiting",
+        stream.id, stream.id
+    );
+
+    run(&wallet, &stream.id).await
+}
+
+async fn run(wallet: &Wallet, id: &str) -> Result<()> {
+    let stream = wallet
+        .run_payment_stream(id, CancellationToken::new())
+        .await?;
+
+    print_stream(&stream);
+
+    Ok(())
+}
+
+async fn find_stream(
+    multi_mint_wallet: &MultiMintWallet,
+    id: &str,
+) -> Result<(Wallet, PaymentStream)> {
+    for wallet in multi_mint_wallet.get_wallets().await {
+        if let Some(stream) = wallet.get_payment_stream(id).await? {
+            return Ok((wallet, stream));
+        }
+    }
+
+    bail!("Unknown payment stream {id}")
+}
+
+fn print_stream(stream: &PaymentStream) {
+    println!(
+        "{} {:?}: {} every {}s to {} from {}, spent {} of {} in {} payments",
+        stream.id,
+        stream.state,
+        stream.amount,
+        stream.interval,
+        stream.destination,
+        stream.mint_url,
+        stream.spent,
+        stream.budget,
+        stream.payments
+    );
+
+    if let Some(err) = &stream.last_error {
+        println!("  last error: {err}");
+    }
+}
@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use cdk::mint_url::MintUrl;
+use cdk::wallet::{AtomicSwap, MultiMintWallet, SwapLeg, SwapOffer};
+use cdk::Amount;
+use clap::{Args, Subcommand};
+
+use crate::utils::relays_or_default;
+
+#[derive(Args)]
+pub struct SwapSubCommand {
+    #[command(subcommand)]
+    command: SwapCommands,
+}
+
+#[derive(Subcommand)]
+pub enum SwapCommands {
+    /// Offer ecash of one mint for ecash of another mint
+    Offer(OfferSubCommand),
+    /// Accept a swap offer
+    Accept(AcceptSubCommand),
+    /// Process swap messages, claim and refund swaps
+    Sync,
+    /// List swaps
+    List,
+    /// Refund the locked ecash of a swap after its locktime
+    Refund(RefundSubCommand),
+}
+
+#[derive(Args)]
+pub struct OfferSubCommand {
+    /// Mint of the ecash to give
+    #[arg(long)]
+    give_mint: MintUrl,
+    /// Amount of ecash to give
+    #[arg(long)]
+    give_amount: u64,
+    /// Mint of the ecash wanted in return
+    #[arg(long)]
+    want_mint: MintUrl,
+    /// Amount of ecash wanted in return
+    #[arg(long)]
+    want_amount: u64,
+    /// Seconds until the offered ecash can be refunded
+    #[arg(long, default_value = "3600")]
+    timeout: u64,
+    /// Nostr relays to exchange swap messages on
+    /// Can be specified multiple times for multiple relays
+    /// If not provided, defaults to standard relays
+    #[arg(long, action = clap::ArgAction::Append)]
+    nostr_relay: Option<Vec<String>>,
+}
+
+#[derive(Args)]
+pub struct AcceptSubCommand {
+    /// Swap offer
+    offer: String,
+}
+
+#[derive(Args)]
+pub struct RefundSubCommand {
+    /// Swap hash
+    hash: String,
+}
+
+pub async fn swap(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &SwapSubCommand,
+) -> Result<()> {
+    match &sub_command_args.command {
+        SwapCommands::Offer(offer_args) => offer(multi_mint_wallet, offer_args).await,
+        SwapCommands::Accept(accept_args) => accept(multi_mint_wallet, accept_args).await,
+        SwapCommands::Sync => {
+            let swaps = multi_mint_wallet.sync_atomic_swaps().await?;
+            print_swaps(&swaps);
+            Ok(())
+        }
+        SwapCommands::List => {
+            let swaps = multi_mint_wallet.atomic_swaps().await?;
+            print_swaps(&swaps);
+            Ok(())
+        }
+        SwapCommands::Refund(refund_args) => {
+            let amount = multi_mint_wallet
+                .refund_atomic_swap(&refund_args.hash)
+                .await?;
+            println!("Refunded {amount}");
+            Ok(())
+        }
+    }
+}
+
+async fn offer(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &OfferSubCommand,
+) -> Result<()> {
+    let unit = multi_mint_wallet.unit().clone();
+
+    let offer = multi_mint_wallet
+        .create_atomic_swap(
+            SwapLeg {
+                mint_url: sub_command_args.give_mint.clone(),
+                amount: Amount::from(sub_command_args.give_amount),
+                unit: unit.clone(),
+            },
+            SwapLeg {
+                mint_url: sub_command_args.want_mint.clone(),
+                amount: Amount::from(sub_command_args.want_amount),
+                unit,
+            },
+            sub_command_args.timeout,
+            relays_or_default(&sub_command_args.nostr_relay),
+        )
+        .await?;
+
+    println!("Share this offer with the counterparty:");
+    println!("{offer}");
+    println!("Then run `swap sync` to process the swap");
+
+    Ok(())
+}
+
+async fn accept(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &AcceptSubCommand,
+) -> Result<()> {
+    let offer = SwapOffer::from_str(&sub_command_args.offer)?;
+
+    println!(
+        "Accepting swap of {} {} from {} for {} {} from {}",
+        offer.give.amount,
+        offer.give.unit,
+        offer.give.mint_url,
+        offer.want.amount,
+        offer.want.unit,
+        offer.want.mint_url
+    );
+
+    let message = multi_mint_wallet.accept_atomic_swap(offer).await?;
+    multi_mint_wallet.send_atomic_swap_message(&message).await?;
+
+    println!("Accepted swap {}", message.hash());
+    println!("Run `swap sync` to process the swap");
+
+    Ok(())
+}
+
+fn print_swaps(swaps: &[AtomicSwap]) {
+    if swaps.is_empty() {
+        println!("No swaps");
+        return;
+    }
+
+    for swap in swaps {
+        let sent = swap.sent_leg();
+        let received = swap.received_leg();
+
+        println!(
+            "{} {:?} {:?}: {} {} at {} for {} {} at {}, refundable after {}",
+            swap.offer.hash,
+            swap.role,
+            swap.state,
+            sent.amount,
+            sent.unit,
+            sent.mint_url,
+            received.amount,
+            received.unit,
+            received.mint_url,
+            swap.locktime()
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Result;
+use cdk::nuts::nut00::ProofsMethods;
+use cdk::nuts::{NotificationPayload, ProofState, SecretKey, State, Token};
+use cdk::util::unix_time;
+use cdk::wallet::{MultiMintWallet, WalletSubscription};
+use cdk_nostr::{RelayPool, RelayUse};
+use clap::Args;
+use nostr_sdk::RelayPoolNotification;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+use super::receive::{direct_message, dm_filter, message_token, parse_signing_key, receive_token};
+use crate::nostr_storage;
+
+#[derive(Args)]
+pub struct WatchSubCommand {
+    /// Nostr key tokens are sent to, also signs for tokens locked to it
+    #[arg(short, long)]
+    nostr_key: String,
+    /// Nostr relay
+    #[arg(short, long, action = clap::ArgAction::Append, required = true)]
+    relay: Vec<String>,
+    /// Additional signing key for tokens locked to it
+    #[arg(short, long, action = clap::ArgAction::Append)]
+    signing_key: Vec<String>,
+    /// Allow receiving from untrusted mints (mints not already in the wallet)
+    #[arg(long, default_value = "false")]
+    allow_untrusted: bool,
+    /// Transfer tokens from untrusted mints to this mint
+    #[arg(long, value_name = "MINT_URL")]
+    transfer_to: Option<String>,
+    /// Watch the proof states of unclaimed tokens on the websocket of their mint,
+    /// claiming them once their proofs are no longer pending
+    #[arg(long, default_value = "false")]
+    mint_ws: bool,
+}
+
+/// Change of the proofs of an unclaimed token reported by its mint
+enum ProofsUpdate {
+    /// Proofs that were pending are unspent again
+    Claimable,
+    /// Every proof was spent by someone else
+    Spent,
+}
+
+/// Claim tokens sent to the nostr key as they arrive, until interrupted
+///
+/// Tokens that could not be claimed are kept in the work dir and tried again
+/// on the next start, or as soon as their mint reports their proofs unspent
+/// with `--mint-ws`.
+pub async fn watch(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &WatchSubCommand,
+    work_dir: &Path,
+) -> Result<()> {
+    let nostr_key = parse_signing_key(&sub_command_args.nostr_key)?;
+    let mut signing_keys = sub_command_args
+        .signing_key
+        .iter()
+        .map(|s| parse_signing_key(s))
+        .collect::<Result<Vec<_>>>()?;
+    signing_keys.push(nostr_key.clone());
+
+    let keys = cdk_nostr::nostr_keys(&nostr_key)?;
+    let verifying_key = nostr_key.public_key();
+
+    let (updates_sender, mut updates) = mpsc::channel(32);
+
+    // Tokens left unclaimed by an earlier watch are tried first
+    let mut unclaimed = Vec::new();
+    for token_str in nostr_storage::get_nostr_unclaimed_tokens(work_dir, &verifying_key).await? {
+        if !claim_token(
+            multi_mint_wallet,
+            sub_command_args,
+            &signing_keys,
+            &token_str,
+        )
+        .await
+        {
+            unclaimed.push(token_str);
+        }
+    }
+    nostr_storage::store_nostr_unclaimed_tokens(work_dir, &verifying_key, &unclaimed).await?;
+    if sub_command_args.mint_ws {
+        for token_str in &unclaimed {
+            watch_proofs(multi_mint_wallet, token_str, updates_sender.clone()).await;
+        }
+    }
+
+    let client = RelayPool::global()
+        .client(&sub_command_args.relay, RelayUse::Read)
+        .await?;
+
+    // Direct messages sent while not watching are delivered first
+    let mut last_checked = nostr_storage::get_nostr_last_checked(work_dir, &verifying_key).await?;
+
+    let mut notifications = client.notifications();
+    let subscription_id = client
+        .subscribe(dm_filter(&keys, last_checked), None)
+        .await?
+        .val;
+
+    println!(
+        "Watching {} relay(s) for tokens sent to {}",
+        sub_command_args.relay.len(),
+        keys.public_key()
+    );
+
+    // Relays deliver the same event once each
+    let mut seen = HashSet::new();
+
+    loop {
+        let event = tokio::select! {
+            notification = notifications.recv() => match notification {
+                Ok(RelayPoolNotification::Event {
+                    subscription_id: event_subscription_id,
+                    event,
+                    ..
+                }) if event_subscription_id == subscription_id => event,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Missed {} relay notifications", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            Some((token_str, update)) = updates.recv() => {
+                let Some(index) = unclaimed.iter().position(|unclaimed| *unclaimed == token_str)
+                else {
+                    continue;
+                };
+
+                match update {
+                    ProofsUpdate::Claimable => {
+                        if claim_token(
+                            multi_mint_wallet,
+                            sub_command_args,
+                            &signing_keys,
+                            &token_str,
+                        )
+                        .await
+                        {
+                            unclaimed.remove(index);
+                        } else {
+                            watch_proofs(multi_mint_wallet, &token_str, updates_sender.clone())
+                                .await;
+                        }
+                    }
+                    ProofsUpdate::Spent => {
+                        println!("Unclaimed token was spent by someone else");
+                        unclaimed.remove(index);
+                    }
+                }
+
+                nostr_storage::store_nostr_unclaimed_tokens(work_dir, &verifying_key, &unclaimed)
+                    .await?;
+                continue;
+            }
+        };
+
+        if !seen.insert(event.id) {
+            continue;
+        }
+
+        let Some(message) = direct_message(&keys, &event, last_checked).await else {
+            continue;
+        };
+
+        if let Some(token_str) = message_token(&message).await {
+            if !claim_token(
+                multi_mint_wallet,
+                sub_command_args,
+                &signing_keys,
+                &token_str,
+            )
+            .await
+                && !unclaimed.contains(&token_str)
+            {
+                if sub_command_args.mint_ws {
+                    watch_proofs(multi_mint_wallet, &token_str, updates_sender.clone()).await;
+                }
+                unclaimed.push(token_str);
+                nostr_storage::store_nostr_unclaimed_tokens(work_dir, &verifying_key, &unclaimed)
+                    .await?;
+            }
+        }
+
+        // Messages dated in the future must not move the cursor past messages yet to come
+        let created_at = message.created_at.as_u64().min(unix_time()) as u32;
+        if last_checked.is_none_or(|last_checked| created_at > last_checked) {
+            nostr_storage::store_nostr_last_checked(work_dir, &verifying_key, created_at).await?;
+            last_checked = Some(created_at);
+        }
+    }
+
+    Ok(())
+}
+
+/// Claim a token, returns whether it should no longer be kept
+///
+/// Tokens already spent are not kept, as they can never be claimed.
+async fn claim_token(
+    multi_mint_wallet: &MultiMintWallet,
+    sub_command_args: &WatchSubCommand,
+    signing_keys: &[SecretKey],
+    token_str: &str,
+) -> bool {
+    match receive_token(
+        multi_mint_wallet,
+        token_str,
+        signing_keys,
+        &[],
+        sub_command_args.allow_untrusted,
+        sub_command_args.transfer_to.as_deref(),
+    )
+    .await
+    {
+        Ok(amount) => {
+            let mint_url = Token::from_str(token_str)
+                .ok()
+                .and_then(|token| token.mint_url().ok())
+                .map(|mint_url| mint_url.to_string())
+                .unwrap_or_default();
+            println!("Received {amount} from {mint_url}");
+            tracing::info!("Received {} from {} via nostr", amount, mint_url);
+            true
+        }
+        Err(err)
+            if err
+                .downcast_ref::<cdk::Error>()
+                .is_some_and(|err| matches!(err, cdk::Error::TokenAlreadySpent)) =>
+        {
+            println!("Token was already spent");
+            true
+        }
+        Err(err) => {
+            println!("Could not receive token, keeping it to try again: {err}");
+            tracing::warn!("Could not receive token sent via nostr: {}", err);
+            false
+        }
+    }
+}
+
+/// Report to `updates` once the mint of an unclaimed token sees its proofs
+/// become unspent again or spent by someone else
+///
+/// Tokens of mints not in the wallet are not watched.
+async fn watch_proofs(
+    multi_mint_wallet: &MultiMintWallet,
+    token_str: &str,
+    updates: mpsc::Sender<(String, ProofsUpdate)>,
+) {
+    let watched = async {
+        let token = Token::from_str(token_str)?;
+        let Some(wallet) = multi_mint_wallet.get_wallet(&token.mint_url()?).await else {
+            return Ok(None);
+        };
+        let ys = token.proofs(&wallet.load_mint_keysets().await?)?.ys()?;
+
+        Ok::<_, anyhow::Error>(Some((wallet, ys)))
+    };
+
+    let (wallet, ys) = match watched.await {
+        Ok(Some(watched)) => watched,
+        Ok(None) => {
+            tracing::debug!("Mint of unclaimed token is not in the wallet, not watching it");
+            return;
+        }
+        Err(err) => {
+            tracing::warn!("Could not watch the proofs of unclaimed token: {}", err);
+            return;
+        }
+    };
+
+    let token_str = token_str.to_string();
+    tokio::spawn(async move {
+        let mut subscription = wallet
+            .subscribe(WalletSubscription::ProofState(
+                ys.iter().map(|y| y.to_string()).collect(),
+            ))
+            .await;
+        let mut states = HashMap::new();
+
+        while let Some(payload) = subscription.recv().await {
+            let NotificationPayload::ProofState(ProofState { y, state, .. }) = payload else {
+                continue;
+            };
+            let previous = states.insert(y, state);
+
+            let update = match state {
+                State::Unspent if previous.is_some_and(|previous| previous != State::Unspent) => {
+                    ProofsUpdate::Claimable
+                }
+                State::Spent
+                    if states.len() == ys.len()
+                        && states.values().all(|state| *state == State::Spent) =>
+                {
+                    ProofsUpdate::Spent
+                }
+                _ => continue,
+            };
+
+            let _ = updates.send((token_str, update)).await;
+            return;
+        }
+    });
+}
@@ -0,0 +1,341 @@
+//! Self update
+//!
+//! Release builds embed the maintainers' public key and the location of the
+//! release metadata. The metadata lists the binary of every target and is signed
+//! by the maintainers, so a compromised download host cannot serve another binary.
+//! Signed metadata expires and only newer versions are installed, so the host
+//! cannot hold back updates with old metadata or roll the binary back either.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::schnorr::Signature;
+use cdk::nuts::PublicKey;
+use cdk::util::unix_time;
+use clap::ValueEnum;
+use semver::Version;
+use serde::Deserialize;
+use url::Url;
+
+/// Hex public key release metadata is signed with
+const RELEASE_PUBKEY: Option<&str> = option_env!("CDK_CLI_RELEASE_PUBKEY");
+/// Url release metadata is published under, as `<url>/<channel>.json`
+const RELEASE_URL: Option<&str> = option_env!("CDK_CLI_RELEASE_URL");
+/// Target triple the binary was built for
+pub const TARGET: &str = env!("CDK_CLI_TARGET");
+/// Build metadata of nightly builds, the build time as `YYYYMMDDHHMMSS` then the commit
+const BUILD_METADATA: Option<&str> = option_env!("CDK_CLI_BUILD_METADATA");
+
+/// Version of this build, `package_version` with the build metadata of nightly builds
+pub fn current_version(package_version: &str) -> String {
+    match BUILD_METADATA {
+        Some(build) => format!("{package_version}+{build}"),
+        None => package_version.to_string(),
+    }
+}
+
+/// Release channel
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum Channel {
+    /// Tagged releases
+    #[default]
+    Stable,
+    /// Builds of the main branch
+    Nightly,
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Channel::Stable => write!(f, "stable"),
+            Channel::Nightly => write!(f, "nightly"),
+        }
+    }
+}
+
+/// Release metadata as published, `release` is the signed JSON of a [`Release`]
+#[derive(Debug, Deserialize)]
+struct SignedRelease {
+    release: String,
+    signature: String,
+}
+
+/// Release of the cli
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    /// Version of the release
+    pub version: String,
+    /// Channel of the release
+    pub channel: String,
+    /// Unix time after which the metadata is no longer accepted
+    pub expires_at: u64,
+    /// Binaries of the release
+    pub artifacts: Vec<Artifact>,
+}
+
+/// Binary of a release for a target
+#[derive(Debug, Clone, Deserialize)]
+pub struct Artifact {
+    /// Target triple
+    pub target: String,
+    /// Download url of the binary
+    pub url: Url,
+    /// Hex SHA-256 of the binary
+    pub sha256: String,
+}
+
+impl Release {
+    /// Whether the release is a newer version than `current_version`
+    ///
+    /// Nightly builds of the same version are ordered by the build time leading
+    /// their build metadata, which semver precedence ignores.
+    pub fn is_newer_than(&self, current_version: &str) -> Result<bool> {
+        let version = Version::parse(&self.version)
+            .map_err(|e| anyhow!("Invalid release version {}: {e}", self.version))?;
+        let current_version = Version::parse(current_version)
+            .map_err(|e| anyhow!("Invalid version {current_version}: {e}"))?;
+
+        Ok(match version.cmp_precedence(&current_version) {
+            Ordering::Greater => true,
+            Ordering::Equal => matches!(
+                (build_time(&version), build_time(&current_version)),
+                (Some(release_time), Some(current_time)) if release_time > current_time
+            ),
+            Ordering::Less => false,
+        })
+    }
+
+    /// Binary of the release for the target of this build
+    pub fn artifact(&self) -> Result<&Artifact> {
+        self.artifacts
+            .iter()
+            .find(|artifact| artifact.target == TARGET)
+            .ok_or(anyhow!(
+                "Release {} has no binary for {TARGET}",
+                self.version
+            ))
+    }
+}
+
+/// Build time leading the build metadata of a nightly version
+fn build_time(version: &Version) -> Option<u64> {
+    version.build.as_str().split('.').next()?.parse().ok()
+}
+
+/// Url of the metadata of the latest release of `channel`
+pub fn metadata_url(channel: Channel) -> Result<Url> {
+    let release_url = RELEASE_URL.ok_or(anyhow!(
+        "This build has no release url, pass --metadata-url"
+    ))?;
+
+    Ok(Url::parse(&format!(
+        "{}/{channel}.json",
+        release_url.trim_end_matches('/')
+    ))?)
+}
+
+/// Fetch the release metadata at `url` and verify its signature
+pub async fn fetch_release(url: Url, channel: Channel) -> Result<Release> {
+    let pubkey = RELEASE_PUBKEY.ok_or(anyhow!(
+        "This build has no release signing key and cannot verify updates"
+    ))?;
+    let pubkey = PublicKey::from_hex(pubkey)?;
+
+    let signed: SignedRelease = reqwest::get(url).await?.error_for_status()?.json().await?;
+
+    verify_release(&signed, &pubkey, channel, unix_time())
+}
+
+/// Check the signature, channel and expiry of release metadata at unix time `now`
+fn verify_release(
+    signed: &SignedRelease,
+    pubkey: &PublicKey,
+    channel: Channel,
+    now: u64,
+) -> Result<Release> {
+    let signature = Signature::from_str(&signed.signature)?;
+    pubkey
+        .verify(signed.release.as_bytes(), &signature)
+        .map_err(|_| anyhow!("Release metadata signature is invalid"))?;
+
+    let release: Release = serde_json::from_str(&signed.release)?;
+
+    // A validly signed release of another channel must not be accepted
+    if release.channel != channel.to_string() {
+        bail!(
+            "Expected a {channel} release, metadata is for {}",
+            release.channel
+        );
+    }
+
+    if release.expires_at <= now {
+        bail!(
+            "Release metadata of {} expired at {}",
+            release.version,
+            release.expires_at
+        );
+    }
+
+    Ok(release)
+}
+
+/// Download the binary of `artifact`, check its hash and replace the running binary
+///
+/// The binary is written next to the running one and renamed over it, so an
+/// interrupted update leaves the previous binary in place.
+pub async fn install(artifact: &Artifact) -> Result<PathBuf> {
+    let binary = reqwest::get(artifact.url.clone())
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let sha256 = sha256::Hash::hash(&binary).to_string();
+    if !sha256.eq_ignore_ascii_case(&artifact.sha256) {
+        bail!("Downloaded binary does not match the release hash");
+    }
+
+    let current_exe = std::env::current_exe()?.canonicalize()?;
+    let update_path = current_exe.with_extension("update");
+
+    fs::write(&update_path, &binary)?;
+
+    if let Err(err) = replace_binary(&update_path, &current_exe) {
+        let _ = fs::remove_file(&update_path);
+        return Err(err);
+    }
+
+    Ok(current_exe)
+}
+
+#[cfg(unix)]
+fn replace_binary(update_path: &Path, current_exe: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(update_path, fs::Permissions::from_mode(0o755))?;
+    fs::rename(update_path, current_exe)?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn replace_binary(update_path: &Path, current_exe: &Path) -> Result<()> {
+    // A running binary cannot be overwritten, but it can be moved aside
+    let old_path = current_exe.with_extension("old");
+    fs::rename(current_exe, &old_path)?;
+
+    if let Err(err) = fs::rename(update_path, current_exe) {
+        fs::rename(&old_path, current_exe)?;
+        return Err(err.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk::nuts::SecretKey;
+
+    use super::*;
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn signed_release(secret_key: &SecretKey, release: &str) -> SignedRelease {
+        SignedRelease {
+            release: release.to_string(),
+            signature: secret_key.sign(release.as_bytes()).unwrap().to_string(),
+        }
+    }
+
+    fn release_json(version: &str, channel: &str, expires_at: u64) -> String {
+        format!(
+            r#"{{"version":"{version}","channel":"{channel}","expires_at":{expires_at},"artifacts":[{{"target":"{TARGET}","url":"https://example.com/cdk-cli","sha256":"00"}}]}}"#
+        )
+    }
+
+    #[test]
+    fn test_verify_release() {
+        let secret_key = SecretKey::generate();
+        let pubkey = secret_key.public_key();
+
+        let signed = signed_release(&secret_key, &release_json("0.14.0", "stable", NOW + 60));
+        let release = verify_release(&signed, &pubkey, Channel::Stable, NOW).unwrap();
+        assert_eq!(release.version, "0.14.0");
+        assert!(release.artifact().is_ok());
+
+        // Signed by another key
+        let other_key = SecretKey::generate();
+        let signed_by_other = signed_release(&other_key, &signed.release);
+        assert!(verify_release(&signed_by_other, &pubkey, Channel::Stable, NOW).is_err());
+
+        // Tampered after signing
+        let tampered = SignedRelease {
+            release: signed.release.replace("0.14.0", "0.15.0"),
+            signature: signed.signature.clone(),
+        };
+        assert!(verify_release(&tampered, &pubkey, Channel::Stable, NOW).is_err());
+
+        // Validly signed release of another channel
+        assert!(verify_release(&signed, &pubkey, Channel::Nightly, NOW).is_err());
+
+        // Expired metadata
+        assert!(verify_release(&signed, &pubkey, Channel::Stable, NOW + 60).is_err());
+    }
+
+    #[test]
+    fn test_release_is_newer() {
+        let secret_key = SecretKey::generate();
+        let release = |version: &str| {
+            verify_release(
+                &signed_release(&secret_key, &release_json(version, "stable", NOW + 60)),
+                &secret_key.public_key(),
+                Channel::Stable,
+                NOW,
+            )
+            .unwrap()
+        };
+
+        assert!(release("0.14.0").is_newer_than("0.13.0").unwrap());
+        assert!(release("0.13.10").is_newer_than("0.13.9").unwrap());
+        assert!(release("0.14.0").is_newer_than("0.14.0-rc.1").unwrap());
+        assert!(!release("0.13.0").is_newer_than("0.13.0").unwrap());
+        assert!(!release("0.12.0").is_newer_than("0.13.0").unwrap());
+        assert!(!release("0.14.0-rc.1").is_newer_than("0.14.0").unwrap());
+        assert!(!release("0.13.0+build.2")
+            .is_newer_than("0.13.0+build.1")
+            .unwrap());
+        assert!(release("latest").is_newer_than("0.13.0").is_err());
+    }
+
+    #[test]
+    fn test_nightly_is_newer() {
+        let secret_key = SecretKey::generate();
+        let release = |version: &str| {
+            verify_release(
+                &signed_release(&secret_key, &release_json(version, "nightly", NOW + 60)),
+                &secret_key.public_key(),
+                Channel::Nightly,
+                NOW,
+            )
+            .unwrap()
+        };
+
+        let earlier = "0.14.0-nightly+20261014020000.3f2a1bc";
+        let later = "0.14.0-nightly+20261015020000.9c4d7e0";
+
+        assert!(release(later).is_newer_than(earlier).unwrap());
+        assert!(!release(earlier).is_newer_than(later).unwrap());
+        assert!(!release(later).is_newer_than(later).unwrap());
+
+        // Builds without a build time are not ordered
+        assert!(!release(later).is_newer_than("0.14.0-nightly").unwrap());
+        assert!(release("0.15.0-nightly+20261001020000.1a2b3c4")
+            .is_newer_than(later)
+            .unwrap());
+    }
+}
@@ -2,8 +2,25 @@ use std::io::{self, Write};
 use std::str::FromStr;
 
 use anyhow::Result;
+use async_trait::async_trait;
+use cdk::cdk_database::{self, WalletDatabase};
 use cdk::mint_url::MintUrl;
 use cdk::wallet::multi_mint_wallet::MultiMintWallet;
+use cdk::wallet::{ConfirmationHandler, SpendConfirmation, SpendKind, DEFAULT_ACCOUNT};
+
+/// Key-value store primary namespace for cli data
+const CLI_PRIMARY_NAMESPACE: &str = "cdk_cli";
+/// Key-value store secondary namespace for account names
+const ACCOUNTS_SECONDARY_NAMESPACE: &str = "accounts";
+/// Name of the account using the wallet seed
+const DEFAULT_ACCOUNT_NAME: &str = "default";
+
+/// Nostr relays used when none are provided
+const DEFAULT_RELAYS: [&str; 3] = [
+    "wss://relay.damus.io",
+    "wss://nos.lol",
+    "wss://relay.primal.net",
+];
 
 /// Helper function to get user input with a prompt
 pub fn get_user_input(prompt: &str) -> Result<String> {
@@ -43,3 +60,70 @@ pub async fn get_or_create_wallet(
         }
     }
 }
+
+/// Index of the account named `name`, assigning the next index to a new name
+pub async fn account_index(
+    localstore: &dyn WalletDatabase<Err = cdk_database::Error>,
+    name: &str,
+) -> Result<u32> {
+    if name == DEFAULT_ACCOUNT_NAME {
+        return Ok(DEFAULT_ACCOUNT);
+    }
+
+    if let Some(index) = localstore
+        .kv_read(CLI_PRIMARY_NAMESPACE, ACCOUNTS_SECONDARY_NAMESPACE, name)
+        .await?
+    {
+        return Ok(serde_json::from_slice(&index)?);
+    }
+
+    let index = localstore
+        .kv_list(CLI_PRIMARY_NAMESPACE, ACCOUNTS_SECONDARY_NAMESPACE)
+        .await?
+        .len() as u32
+        + 1;
+
+    localstore
+        .kv_write(
+            CLI_PRIMARY_NAMESPACE,
+            ACCOUNTS_SECONDARY_NAMESPACE,
+            name,
+            &serde_json::to_vec(&index)?,
+        )
+        .await?;
+
+    println!("Created account {name}");
+
+    Ok(index)
+}
+
+/// Helper function to use the provided nostr relays or the default ones
+pub fn relays_or_default(relays: &Option<Vec<String>>) -> Vec<String> {
+    match relays {
+        Some(relays) if !relays.is_empty() => relays.clone(),
+        _ => DEFAULT_RELAYS.iter().map(|r| r.to_string()).collect(),
+    }
+}
+
+/// Asks on the terminal to confirm spends above the spending limits
+#[derive(Debug)]
+pub struct PromptConfirmationHandler;
+
+#[async_trait]
+impl ConfirmationHandler for PromptConfirmationHandler {
+    async fn confirm(&self, spend: &SpendConfirmation) -> Result<bool, cdk::Error> {
+        let kind = match spend.kind {
+            SpendKind::Send => "Send",
+            SpendKind::Melt => "Melt",
+            SpendKind::Lock => "Lock",
+        };
+
+        let answer = get_user_input(&format!(
+            "{kind} of {} {} from {} exceeds the {} spending limit ({} spent today). Continue? [y/N]",
+            spend.amount, spend.unit, spend.mint_url, spend.exceeded, spend.spent_today
+        ))
+        .map_err(|err| cdk::Error::Custom(err.to_string()))?;
+
+        Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+    }
+}
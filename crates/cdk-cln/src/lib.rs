@@ -20,20 +20,22 @@ use cdk_common::database::mint::DynMintKVStore;
 use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState};
 use cdk_common::payment::{
     self, Bolt11IncomingPaymentOptions, Bolt11Settings, Bolt12IncomingPaymentOptions,
-    CreateIncomingPaymentResponse, Event, IncomingPaymentOptions, MakePaymentResponse, MintPayment,
-    OutgoingPaymentOptions, PaymentIdentifier, PaymentQuoteResponse, WaitPaymentResponse,
+    CreateIncomingPaymentResponse, Event, IncomingPaymentOptions, KeysendOutgoingPaymentOptions,
+    MakePaymentResponse, MintPayment, OutgoingPaymentOptions, PaymentIdentifier,
+    PaymentQuoteResponse, WaitPaymentResponse,
 };
 use cdk_common::util::{hex, unix_time};
 use cdk_common::Bolt11Invoice;
 use cln_rpc::model::requests::{
-    DecodeRequest, FetchinvoiceRequest, InvoiceRequest, ListinvoicesRequest, ListpaysRequest,
-    OfferRequest, PayRequest, WaitanyinvoiceRequest,
+    DecodeRequest, FetchinvoiceRequest, GetinfoRequest, GetrouteRequest, InvoiceRequest,
+    KeysendRequest, ListfundsRequest, ListinvoicesRequest, ListpaysRequest, OfferRequest,
+    PayRequest, WaitanyinvoiceRequest,
 };
 use cln_rpc::model::responses::{
     DecodeResponse, ListinvoicesInvoices, ListinvoicesInvoicesStatus, ListpaysPaysStatus,
     PayStatus, WaitanyinvoiceResponse, WaitanyinvoiceStatus,
 };
-use cln_rpc::primitives::{Amount as CLN_Amount, AmountOrAny, Sha256};
+use cln_rpc::primitives::{Amount as CLN_Amount, AmountOrAny, ChannelState, Sha256};
 use cln_rpc::ClnRpc;
 use error::Error;
 use futures::{Stream, StreamExt};
@@ -87,6 +89,7 @@ impl MintPayment for Cln {
             invoice_description: true,
             amountless: true,
             bolt12: true,
+            keysend: true,
         })?)
     }
 
@@ -379,7 +382,102 @@ impl MintPayment for Cln {
                     unit: unit.clone(),
                 })
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                // Convert to target unit
+                let amount = to_unit(keysend_options.amount_msat, &CurrencyUnit::Msat, unit)?;
+
+                // Calculate fee
+                let relative_fee_reserve =
+                    (self.fee_reserve.percent_fee_reserve * u64::from(amount) as f32) as u64;
+                let absolute_fee_reserve: u64 = self.fee_reserve.min_fee_reserve.into();
+                let fee = max(relative_fee_reserve, absolute_fee_reserve);
+
+                // The payment hash is only known once CLN picks the preimage
+                Ok(PaymentQuoteResponse {
+                    request_lookup_id: None,
+                    amount,
+                    fee: fee.into(),
+                    state: MeltQuoteState::Unpaid,
+                    unit: unit.clone(),
+                })
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn probe_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<Option<Amount>, Self::Err> {
+        let OutgoingPaymentOptions::Bolt11(bolt11_options) = options else {
+            return Ok(None);
+        };
+
+        let amount_msat: u64 = match bolt11_options.melt_options {
+            Some(amount) => amount.amount_msat().into(),
+            None => bolt11_options
+                .bolt11
+                .amount_milli_satoshis()
+                .ok_or(Error::UnknownInvoiceAmount)?,
+        };
+
+        // getroute takes no route hints, destinations behind private channels are
+        // probed up to the entry of each hint of the invoice
+        let mut targets = vec![(
+            bolt11_options.bolt11.get_payee_pub_key().to_string(),
+            amount_msat,
+        )];
+        targets.extend(route_hint_entries(&bolt11_options.bolt11, amount_msat));
+
+        let mut cln_client = self.cln_client().await?;
+        let mut fee_msat: Option<u64> = None;
+
+        for (node, target_amount_msat) in targets {
+            let Ok(id) = cln_rpc::primitives::PublicKey::from_str(&node) else {
+                continue;
+            };
+
+            let route = match cln_client
+                .call_typed(&GetrouteRequest {
+                    id,
+                    amount_msat: CLN_Amount::from_msat(target_amount_msat),
+                    riskfactor: 10,
+                    cltv: None,
+                    fromid: None,
+                    fuzzpercent: Some(0),
+                    exclude: None,
+                    maxhops: None,
+                })
+                .await
+            {
+                Ok(response) => response.route,
+                Err(err) => {
+                    tracing::debug!(
+                        "Could not probe route to {} for fee estimate: {}",
+                        node,
+                        err
+                    );
+                    continue;
+                }
+            };
+
+            // The first hop carries the amount plus the fees of every hop, the
+            // hops of the route hint included
+            if let Some(first_hop) = route.first() {
+                let route_fee_msat = first_hop.amount_msat.msat().saturating_sub(amount_msat);
+                fee_msat = Some(fee_msat.map_or(route_fee_msat, |fee| fee.min(route_fee_msat)));
+            }
         }
+
+        let Some(fee_msat) = fee_msat else {
+            return Ok(None);
+        };
+
+        // Rounded up to a whole sat so the reserve covers the probed fee
+        let fee_msat = fee_msat.div_ceil(1000) * 1000;
+
+        Ok(Some(to_unit(fee_msat, &CurrencyUnit::Msat, unit)?))
     }
 
     #[instrument(skip_all)]
@@ -469,6 +567,9 @@ impl MintPayment for Cln {
 
                 cln_response.invoice
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                return self.make_keysend_payment(unit, keysend_options).await;
+            }
         };
 
         let cln_response = cln_client
@@ -504,6 +605,9 @@ impl MintPayment for Cln {
                     OutgoingPaymentOptions::Bolt12(_) => {
                         PaymentIdentifier::Bolt12PaymentHash(*pay_response.payment_hash.as_ref())
                     }
+                    OutgoingPaymentOptions::Keysend(_) => {
+                        unreachable!("Keysend payments are made with the keysend command")
+                    }
                 };
 
                 MakePaymentResponse {
@@ -755,6 +859,39 @@ impl MintPayment for Cln {
             }),
         }
     }
+
+    /// Node identity, spendable channel balance and sync state
+    #[instrument(skip_all)]
+    async fn backend_info(&self) -> Result<Option<payment::BackendInfo>, Self::Err> {
+        let mut cln_client = self.cln_client().await?;
+
+        let info = cln_client
+            .call_typed(&GetinfoRequest {})
+            .await
+            .map_err(Error::from)?;
+
+        let funds = cln_client
+            .call_typed(&ListfundsRequest { spent: None })
+            .await
+            .map_err(Error::from)?;
+
+        let balance_msat: u64 = funds
+            .channels
+            .iter()
+            .filter(|channel| channel.connected && channel.state == ChannelState::CHANNELD_NORMAL)
+            .map(|channel| channel.our_amount_msat.msat())
+            .sum();
+
+        Ok(Some(payment::BackendInfo {
+            backend: "cln".to_string(),
+            version: Some(info.version),
+            node_pubkey: Some(info.id.to_string()),
+            node_alias: info.alias,
+            balance: Some(balance_msat.into()),
+            balance_unit: Some(CurrencyUnit::Msat),
+            connected: info.warning_lightningd_sync.is_none(),
+        }))
+    }
 }
 
 impl Cln {
@@ -819,6 +956,56 @@ impl Cln {
             })
     }
 
+    /// Pay a node directly with the keysend command
+    #[instrument(skip(self))]
+    async fn make_keysend_payment(
+        &self,
+        unit: &CurrencyUnit,
+        keysend_options: &KeysendOutgoingPaymentOptions,
+    ) -> Result<MakePaymentResponse, payment::Error> {
+        let destination =
+            cln_rpc::primitives::PublicKey::from_str(&keysend_options.pubkey.to_string())
+                .map_err(|_| Error::InvalidNodeId)?;
+
+        let mut cln_client = self.cln_client().await?;
+
+        let keysend_response = cln_client
+            .call_typed(&KeysendRequest {
+                destination,
+                amount_msat: CLN_Amount::from_msat(keysend_options.amount_msat.into()),
+                // Labels the payment with the melt quote it pays
+                label: keysend_options.idempotency_key.clone(),
+                maxfeepercent: None,
+                retry_for: keysend_options.timeout_secs.map(|secs| secs as u32),
+                maxdelay: None,
+                exemptfee: None,
+                routehints: None,
+                extratlvs: None,
+                maxfee: keysend_options
+                    .max_fee_amount
+                    .map(|fee| CLN_Amount::from_msat(fee.into())),
+            })
+            .await
+            .map_err(|err| {
+                tracing::error!("Could not pay keysend: {}", err);
+                Error::ClnRpc(err)
+            })?;
+
+        Ok(MakePaymentResponse {
+            payment_proof: Some(hex::encode(keysend_response.payment_preimage.to_vec())),
+            payment_lookup_id: PaymentIdentifier::PaymentHash(
+                *keysend_response.payment_hash.as_ref(),
+            ),
+            status: MeltQuoteState::Paid,
+            total_spent: to_unit(
+                keysend_response.amount_sent_msat.msat(),
+                &CurrencyUnit::Msat,
+                unit,
+            )?,
+            unit: unit.clone(),
+        })
+    }
+
     /// Checks that outgoing payment is not already paid
     #[instrument(skip(self))]
     async fn check_outgoing_unpaided(
@@ -841,6 +1028,29 @@ impl Cln {
     }
 }
 
+/// Entry node of each route hint of `bolt11` and the amount it has to be paid
+/// to forward `amount_msat` over the hinted hops to the payee
+fn route_hint_entries(bolt11: &Bolt11Invoice, amount_msat: u64) -> Vec<(String, u64)> {
+    bolt11
+        .route_hints()
+        .into_iter()
+        .filter_map(|hint| {
+            let entry = hint.0.first()?.src_node_id.to_string();
+
+            // Each hop charges its fee on the amount it forwards
+            let entry_amount_msat = hint.0.iter().rev().fold(amount_msat, |amount, hop| {
+                amount
+                    .saturating_add(hop.fees.base_msat.into())
+                    .saturating_add(
+                        amount.saturating_mul(hop.fees.proportional_millionths.into()) / 1_000_000,
+                    )
+            });
+
+            Some((entry, entry_amount_msat))
+        })
+        .collect()
+}
+
 fn cln_pays_status_to_mint_state(status: ListpaysPaysStatus) -> MeltQuoteState {
     match status {
         ListpaysPaysStatus::PENDING => MeltQuoteState::Pending,
@@ -24,10 +24,27 @@ pub struct Melted {
     pub amount: Amount,
     /// Fee paid
     pub fee_paid: Amount,
+    /// Breakdown of the fee paid
+    #[serde(default)]
+    pub fee_breakdown: MeltFeeBreakdown,
+}
+
+/// Breakdown of the fees paid for a melt
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MeltFeeBreakdown {
+    /// Lightning fee actually paid by the mint
+    pub lightning_fee: Amount,
+    /// Keyset input fee charged for the melt inputs
+    pub input_fee: Amount,
+    /// Unused fee reserve returned as change
+    pub fee_reserve_returned: Amount,
 }
 
 impl Melted {
     /// Create new [`Melted`]
+    ///
+    /// The fee breakdown is left empty, as the input fee is unknown here. Use
+    /// [`Melted::with_fee_breakdown`] to fill it in.
     pub fn from_proofs(
         state: MeltQuoteState,
         preimage: Option<String>,
@@ -52,9 +69,30 @@ impl Melted {
             change: change_proofs,
             amount,
             fee_paid,
+            fee_breakdown: MeltFeeBreakdown::default(),
         })
     }
 
+    /// Split the fee paid into lightning fee, input fee and returned fee reserve
+    ///
+    /// The input fee is taken from the fee paid first, the remainder is the
+    /// lightning fee and whatever is left of the fee reserve was returned as change.
+    pub fn with_fee_breakdown(mut self, fee_reserve: Amount, input_fee: Amount) -> Self {
+        let input_fee = input_fee.min(self.fee_paid);
+        let lightning_fee = self.fee_paid.checked_sub(input_fee).unwrap_or(Amount::ZERO);
+        let fee_reserve_returned = fee_reserve
+            .checked_sub(lightning_fee)
+            .unwrap_or(Amount::ZERO);
+
+        self.fee_breakdown = MeltFeeBreakdown {
+            lightning_fee,
+            input_fee,
+            fee_reserve_returned,
+        };
+
+        self
+    }
+
     /// Total amount melted
     pub fn total_amount(&self) -> Amount {
         self.amount + self.fee_paid
@@ -193,7 +231,7 @@ mod tests {
 
     use cashu::SecretKey;
 
-    use super::{Melted, ProofInfo};
+    use super::{MeltFeeBreakdown, Melted, ProofInfo};
     use crate::mint_url::MintUrl;
     use crate::nuts::{CurrencyUnit, Id, Proof, PublicKey, SpendingConditions, State};
     use crate::secret::Secret;
@@ -256,6 +294,43 @@ mod tests {
         assert_eq!(melted.amount, Amount::from(31));
         assert_eq!(melted.fee_paid, Amount::from(1));
         assert_eq!(melted.total_amount(), Amount::from(32));
+        assert_eq!(melted.fee_breakdown, MeltFeeBreakdown::default());
+    }
+
+    #[test]
+    fn test_melted_fee_breakdown() {
+        let keyset_id = Id::from_str("00deadbeef123456").unwrap();
+        let proof = Proof::new(
+            Amount::from(64),
+            keyset_id,
+            Secret::generate(),
+            PublicKey::from_hex(
+                "02deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef",
+            )
+            .unwrap(),
+        );
+        let change_proof = Proof::new(
+            Amount::from(8),
+            keyset_id,
+            Secret::generate(),
+            PublicKey::from_hex(
+                "03deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef",
+            )
+            .unwrap(),
+        );
+        let melted = Melted::from_proofs(
+            super::MeltQuoteState::Paid,
+            Some("preimage".to_string()),
+            Amount::from(50),
+            vec![proof.clone()],
+            Some(vec![change_proof.clone()]),
+        )
+        .unwrap()
+        .with_fee_breakdown(Amount::from(10), Amount::from(1));
+        assert_eq!(melted.fee_paid, Amount::from(6));
+        assert_eq!(melted.fee_breakdown.input_fee, Amount::from(1));
+        assert_eq!(melted.fee_breakdown.lightning_fee, Amount::from(5));
+        assert_eq!(melted.fee_breakdown.fee_reserve_returned, Amount::from(5));
     }
 
     #[test]
@@ -9,8 +9,8 @@ use cashu::Amount;
 use super::Error;
 use crate::mint::{self, MintKeySetInfo, MintQuote as MintMintQuote};
 use crate::nuts::{
-    BlindSignature, BlindedMessage, CurrencyUnit, Id, MeltQuoteState, Proof, Proofs, PublicKey,
-    State,
+    BlindSignature, BlindedMessage, CurrencyUnit, Id, MeltFailureReason, MeltQuoteState, Proof,
+    Proofs, PublicKey, State,
 };
 use crate::payment::PaymentIdentifier;
 
@@ -23,65 +23,10 @@ pub mod test;
 #[cfg(feature = "auth")]
 pub use auth::{DynMintAuthDatabase, MintAuthDatabase, MintAuthTransaction};
 
-/// Valid ASCII characters for namespace and key strings in KV store
-pub const KVSTORE_NAMESPACE_KEY_ALPHABET: &str =
-    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-";
-
-/// Maximum length for namespace and key strings in KV store
-pub const KVSTORE_NAMESPACE_KEY_MAX_LEN: usize = 120;
-
-/// Validates that a string contains only valid KV store characters and is within length limits
-pub fn validate_kvstore_string(s: &str) -> Result<(), Error> {
-    if s.len() > KVSTORE_NAMESPACE_KEY_MAX_LEN {
-        return Err(Error::KVStoreInvalidKey(format!(
-            "{} exceeds maximum length of key characters",
-            KVSTORE_NAMESPACE_KEY_MAX_LEN
-        )));
-    }
-
-    if !s
-        .chars()
-        .all(|c| KVSTORE_NAMESPACE_KEY_ALPHABET.contains(c))
-    {
-        return Err(Error::KVStoreInvalidKey("key contains invalid characters. Only ASCII letters, numbers, underscore, and hyphen are allowed".to_string()));
-    }
-
-    Ok(())
-}
-
-/// Validates namespace and key parameters for KV store operations
-pub fn validate_kvstore_params(
-    primary_namespace: &str,
-    secondary_namespace: &str,
-    key: &str,
-) -> Result<(), Error> {
-    // Validate primary namespace
-    validate_kvstore_string(primary_namespace)?;
-
-    // Validate secondary namespace
-    validate_kvstore_string(secondary_namespace)?;
-
-    // Validate key
-    validate_kvstore_string(key)?;
-
-    // Check empty namespace rules
-    if primary_namespace.is_empty() && !secondary_namespace.is_empty() {
-        return Err(Error::KVStoreInvalidKey(
-            "If primary_namespace is empty, secondary_namespace must also be empty".to_string(),
-        ));
-    }
-
-    // Check for potential collisions between keys and namespaces in the same namespace
-    let namespace_key = format!("{}/{}", primary_namespace, secondary_namespace);
-    if key == primary_namespace || key == secondary_namespace || key == namespace_key {
-        return Err(Error::KVStoreInvalidKey(format!(
-            "Key '{}' conflicts with namespace names",
-            key
-        )));
-    }
-
-    Ok(())
-}
+pub use super::{
+    validate_kvstore_params, validate_kvstore_string, KVSTORE_NAMESPACE_KEY_ALPHABET,
+    KVSTORE_NAMESPACE_KEY_MAX_LEN,
+};
 
 /// Information about a melt request stored in the database
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -198,6 +143,12 @@ pub trait QuotesTransaction<'a> {
         new_state: MeltQuoteState,
         payment_proof: Option<String>,
     ) -> Result<(MeltQuoteState, mint::MeltQuote), Self::Err>;
+    /// Set or clear the reason the last payment attempt of a [`mint::MeltQuote`] failed
+    async fn update_melt_quote_failure_reason(
+        &mut self,
+        quote_id: &QuoteId,
+        failure_reason: Option<MeltFailureReason>,
+    ) -> Result<(), Self::Err>;
     /// Remove [`mint::MeltQuote`]
     async fn remove_melt_quote(&mut self, quote_id: &QuoteId) -> Result<(), Self::Err>;
     /// Get all [`MintMintQuote`]s and lock it for update in this transaction
@@ -239,6 +190,17 @@ pub trait QuotesDatabase {
         &self,
         quote_id: &QuoteId,
     ) -> Result<Option<mint::MeltQuote>, Self::Err>;
+    /// Get [`mint::MeltQuote`] from a read replica, if the database has one
+    ///
+    /// The quote may lag behind the primary, so it must only be used to answer
+    /// read-only requests and never to decide on a write. Only a paid quote is
+    /// final, any other state has to be read again from the primary.
+    async fn get_melt_quote_from_replica(
+        &self,
+        quote_id: &QuoteId,
+    ) -> Result<Option<mint::MeltQuote>, Self::Err> {
+        self.get_melt_quote(quote_id).await
+    }
     /// Get all [`mint::MeltQuote`]s
     async fn get_melt_quotes(&self) -> Result<Vec<mint::MeltQuote>, Self::Err>;
 }
@@ -294,6 +256,17 @@ pub trait ProofsDatabase {
     ) -> Result<Vec<PublicKey>, Self::Err>;
     /// Get [`Proofs`] state
     async fn get_proofs_states(&self, ys: &[PublicKey]) -> Result<Vec<Option<State>>, Self::Err>;
+    /// Get [`Proofs`] state from a read replica, if the database has one
+    ///
+    /// The states may lag behind the primary, so they must only be used to answer
+    /// read-only requests and never to decide on a write. Only a spent state is
+    /// final, any other state has to be read again from the primary.
+    async fn get_proofs_states_from_replica(
+        &self,
+        ys: &[PublicKey],
+    ) -> Result<Vec<Option<State>>, Self::Err> {
+        self.get_proofs_states(ys).await
+    }
     /// Get [`Proofs`] by state
     async fn get_proofs_by_keyset_id(
         &self,
@@ -438,6 +411,30 @@ pub trait KVStore: KVStoreDatabase {
 /// Type alias for Mint Kv store
 pub type DynMintKVStore = std::sync::Arc<dyn KVStore<Err = Error> + Send + Sync>;
 
+/// Leader election between mint instances sharing a database
+///
+/// Instances serving the same mint share the database, and with it the spent
+/// proofs and quote states, but only the leader runs the recovery of melts that
+/// were left pending, so an instance never resolves a melt another instance is
+/// still paying.
+#[async_trait]
+pub trait LeaderElection {
+    /// Leader Election Error
+    type Err: Into<Error> + From<Error>;
+
+    /// Try to become the leader
+    ///
+    /// Returns whether this instance is the leader. Calling it again while being
+    /// the leader confirms that leadership is still held.
+    async fn try_acquire_leadership(&self) -> Result<bool, Self::Err>;
+
+    /// Give up leadership so another instance can take over
+    async fn release_leadership(&self) -> Result<(), Self::Err>;
+}
+
+/// Type alias for Mint Leader Election
+pub type DynMintLeaderElection = std::sync::Arc<dyn LeaderElection<Err = Error> + Send + Sync>;
+
 /// Mint Database trait
 #[async_trait]
 pub trait Database<Error>:
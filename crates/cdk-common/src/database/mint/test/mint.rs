@@ -1,8 +1,12 @@
 //! Payments
 
+use std::str::FromStr;
+
+use cashu::{Bolt11Invoice, MeltFailureReason};
+
 use crate::database::mint::test::unique_string;
 use crate::database::mint::{Database, Error, KeysDatabase};
-use crate::mint::MintQuote;
+use crate::mint::{MeltPaymentRequest, MeltQuote, MintQuote};
 use crate::payment::PaymentIdentifier;
 
 /// Add a mint quote
@@ -404,3 +408,40 @@ where
         .await
         .is_err());
 }
+
+/// Failure reasons of melt quotes are stored and cleared
+pub async fn melt_quote_failure_reason<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let bolt11 = Bolt11Invoice::from_str("lnbc100n1p5z3a63pp56854ytysg7e5z9fl3w5mgvrlqjfcytnjv8ff5hm5qt6gl6alxesqdqqcqzzsxqyz5vqsp5p0x0dlhn27s63j4emxnk26p7f94u0lyarnfp5yqmac9gzy4ngdss9qxpqysgqne3v0hnzt2lp0hc69xpzckk0cdcar7glvjhq60lsrfe8gejdm8c564prrnsft6ctxxyrewp4jtezrq3gxxqnfjj0f9tw2qs9y0lslmqpfu7et9").unwrap();
+    let melt_quote = MeltQuote::new(
+        MeltPaymentRequest::Bolt11 { bolt11 },
+        cashu::CurrencyUnit::Sat,
+        10.into(),
+        1.into(),
+        0,
+        Some(PaymentIdentifier::CustomId(unique_string())),
+        None,
+        cashu::PaymentMethod::Bolt11,
+    );
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_melt_quote(melt_quote.clone()).await.unwrap();
+    tx.update_melt_quote_failure_reason(&melt_quote.id, Some(MeltFailureReason::FeeExceeded))
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let stored = db.get_melt_quote(&melt_quote.id).await.unwrap().unwrap();
+    assert_eq!(stored.failure_reason, Some(MeltFailureReason::FeeExceeded));
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.update_melt_quote_failure_reason(&melt_quote.id, None)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let stored = db.get_melt_quote(&melt_quote.id).await.unwrap().unwrap();
+    assert_eq!(stored.failure_reason, None);
+}
@@ -13,7 +13,8 @@ use crate::nuts::{
     CurrencyUnit, Id, KeySetInfo, Keys, MintInfo, PublicKey, SpendingConditions, State,
 };
 use crate::wallet::{
-    self, MintQuote as WalletMintQuote, Transaction, TransactionDirection, TransactionId,
+    self, ArchivedProof, FeeEntry, MintQuote as WalletMintQuote, Transaction, TransactionDirection,
+    TransactionId,
 };
 
 /// Wallet Database trait
@@ -64,6 +65,15 @@ pub trait Database: Debug {
     async fn get_mint_quotes(&self) -> Result<Vec<WalletMintQuote>, Self::Err>;
     /// Remove mint quote from storage
     async fn remove_mint_quote(&self, quote_id: &str) -> Result<(), Self::Err>;
+    /// Remove mint quotes from storage
+    ///
+    /// Removes them one by one unless the database removes them at once
+    async fn remove_mint_quotes(&self, quote_ids: &[String]) -> Result<(), Self::Err> {
+        for quote_id in quote_ids {
+            self.remove_mint_quote(quote_id).await?;
+        }
+        Ok(())
+    }
 
     /// Add melt quote to storage
     async fn add_melt_quote(&self, quote: wallet::MeltQuote) -> Result<(), Self::Err>;
@@ -73,6 +83,15 @@ pub trait Database: Debug {
     async fn get_melt_quotes(&self) -> Result<Vec<wallet::MeltQuote>, Self::Err>;
     /// Remove melt quote from storage
     async fn remove_melt_quote(&self, quote_id: &str) -> Result<(), Self::Err>;
+    /// Remove melt quotes from storage
+    ///
+    /// Removes them one by one unless the database removes them at once
+    async fn remove_melt_quotes(&self, quote_ids: &[String]) -> Result<(), Self::Err> {
+        for quote_id in quote_ids {
+            self.remove_melt_quote(quote_id).await?;
+        }
+        Ok(())
+    }
 
     /// Add [`Keys`] to storage
     async fn add_keys(&self, keyset: KeySet) -> Result<(), Self::Err>;
@@ -82,7 +101,8 @@ pub trait Database: Debug {
     async fn remove_keys(&self, id: &Id) -> Result<(), Self::Err>;
 
     /// Update the proofs in storage by adding new proofs or removing proofs by
-    /// their Y value.
+    /// their Y value. Removed proofs in the [`State::Spent`] or [`State::PendingSpent`]
+    /// state are kept as [`ArchivedProof`], other removed proofs are dropped.
     async fn update_proofs(
         &self,
         added: Vec<ProofInfo>,
@@ -98,7 +118,18 @@ pub trait Database: Debug {
     ) -> Result<Vec<ProofInfo>, Self::Err>;
     /// Update proofs state in storage
     async fn update_proofs_state(&self, ys: Vec<PublicKey>, state: State) -> Result<(), Self::Err>;
+    /// Get archived summaries of removed proofs
+    async fn get_archived_proofs(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+    ) -> Result<Vec<ArchivedProof>, Self::Err>;
+    /// Remove archived proofs spent before the given unix timestamp, returning
+    /// how many were removed
+    async fn prune_archived_proofs(&self, spent_before: u64) -> Result<u64, Self::Err>;
 
+    /// Get Keyset counter, the counter of the next secret to derive
+    async fn get_keyset_counter(&self, keyset_id: &Id) -> Result<u32, Self::Err>;
     /// Atomically increment Keyset counter and return new value
     async fn increment_keyset_counter(&self, keyset_id: &Id, count: u32) -> Result<u32, Self::Err>;
 
@@ -118,4 +149,56 @@ pub trait Database: Debug {
     ) -> Result<Vec<Transaction>, Self::Err>;
     /// Remove transaction from storage
     async fn remove_transaction(&self, transaction_id: TransactionId) -> Result<(), Self::Err>;
+
+    /// Add fee paid to a mint to the fee ledger
+    async fn add_fee_entry(&self, entry: FeeEntry) -> Result<(), Self::Err>;
+    /// List fee ledger entries, `since` inclusive and `until` exclusive
+    async fn list_fee_entries(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<FeeEntry>, Self::Err>;
+
+    /// Read value from key-value store
+    ///
+    /// Databases without a key-value store hold no values
+    async fn kv_read(
+        &self,
+        _primary_namespace: &str,
+        _secondary_namespace: &str,
+        _key: &str,
+    ) -> Result<Option<Vec<u8>>, Self::Err> {
+        Ok(None)
+    }
+    /// Write value to key-value store
+    ///
+    /// Fails with [`Error::KVStoreUnsupported`] for databases without a key-value store
+    async fn kv_write(
+        &self,
+        _primary_namespace: &str,
+        _secondary_namespace: &str,
+        _key: &str,
+        _value: &[u8],
+    ) -> Result<(), Self::Err> {
+        Err(Error::KVStoreUnsupported.into())
+    }
+    /// Remove value from key-value store
+    async fn kv_remove(
+        &self,
+        _primary_namespace: &str,
+        _secondary_namespace: &str,
+        _key: &str,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    /// List keys in a namespace
+    async fn kv_list(
+        &self,
+        _primary_namespace: &str,
+        _secondary_namespace: &str,
+    ) -> Result<Vec<String>, Self::Err> {
+        Ok(Vec::new())
+    }
 }
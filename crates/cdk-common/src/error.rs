@@ -3,7 +3,7 @@
 use std::array::TryFromSliceError;
 use std::fmt;
 
-use cashu::{CurrencyUnit, PaymentMethod};
+use cashu::{CurrencyUnit, MeltFailureReason, PaymentMethod};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use thiserror::Error;
@@ -38,6 +38,9 @@ pub enum Error {
     /// Invalid payment request
     #[error("Invalid payment request")]
     InvalidPaymentRequest,
+    /// Quote rejected by the risk policy of the mint
+    #[error("Quote rejected by risk policy")]
+    QuoteRejected,
     /// Bolt11 invoice does not have amount
     #[error("Invoice Amount undefined")]
     InvoiceAmountUndefined,
@@ -47,6 +50,9 @@ pub enum Error {
     /// Amount overflow
     #[error("Amount Overflow")]
     AmountOverflow,
+    /// Keyset counter overflow
+    #[error("Keyset counter overflow")]
+    CounterOverflow,
     /// Witness missing or invalid
     #[error("Signature missing or invalid")]
     SignatureMissingOrInvalid,
@@ -89,6 +95,9 @@ pub enum Error {
     /// Could not get mint info
     #[error("Could not get mint info")]
     CouldNotGetMintInfo,
+    /// Mint does not advertise support for a required capability
+    #[error("Mint does not support {0}")]
+    MintDoesNotSupport(String),
     /// Multi-Part Payment not supported for unit and method
     #[error("Amountless invoices are not supported for unit `{0}` and method `{1}`")]
     AmountlessInvoiceNotSupported(CurrencyUnit, PaymentMethod),
@@ -119,6 +128,15 @@ pub enum Error {
     #[error("Operation timeout")]
     Timeout,
 
+    /// Operation cancelled by the caller
+    #[error("Operation cancelled")]
+    Cancelled,
+    /// Melt cancelled while the mint is paying, the inputs stay pending
+    #[error(
+        "Melt cancelled while quote {0} is pending, inputs are left to the pending melt check"
+    )]
+    MeltCancelledPending(String),
+
     /// BIP353 address resolution error
     #[error("Failed to resolve BIP353 address: {0}")]
     Bip353Resolve(String),
@@ -177,6 +195,9 @@ pub enum Error {
     /// Transaction unbalanced
     #[error("Inputs: `{0}`, Outputs: `{1}`, Expected Fee: `{2}`")]
     TransactionUnbalanced(u64, u64, u64),
+    /// Ledger transaction whose debits and credits differ
+    #[error("Ledger transaction `{0}` debits `{1}` but credits `{2}`")]
+    LedgerUnbalanced(String, u64, u64),
     /// Duplicate proofs provided
     #[error("Duplicate Inputs")]
     DuplicateInputs,
@@ -228,6 +249,9 @@ pub enum Error {
     /// Max Fee Ecxeded
     #[error("Max fee exceeded")]
     MaxFeeExceeded,
+    /// Mint reported why the payment of a melt quote failed
+    #[error("Melt failed: {0}")]
+    MeltFailed(MeltFailureReason),
     /// Url path segments could not be joined
     #[error("Url path segments could not be joined")]
     UrlPathSegments,
@@ -244,6 +268,15 @@ pub enum Error {
     /// Token does not match wallet mint
     #[error("Token does not match wallet mint")]
     IncorrectMint,
+    /// Keys embedded in a token do not match the mint keys
+    #[error("Keyset hint does not match mint keys for keyset `{0}`")]
+    KeysetHintMismatch(String),
+    /// Neither cached nor embedded keys are available to verify a token offline
+    #[error("No keys available offline for keyset `{0}`")]
+    OfflineKeysUnavailable(String),
+    /// Mint info is not signed by the pinned mint identity key
+    #[error("Mint identity does not match pinned pubkey `{0}`")]
+    MintIdentityMismatch(String),
     /// Receive can only be used with tokens from single mint
     #[error("Multiple mint tokens not supported by receive. Please deconstruct the token and use receive with_proof")]
     MultiMintTokenNotSupported,
@@ -294,9 +327,27 @@ pub enum Error {
     /// Invalid transaction direction
     #[error("Invalid transaction direction")]
     InvalidTransactionDirection,
+    /// Invalid transaction status
+    #[error("Invalid transaction status")]
+    InvalidTransactionStatus,
+    /// Invalid fee kind
+    #[error("Invalid fee kind")]
+    InvalidFeeKind,
     /// Invalid transaction id
     #[error("Invalid transaction id")]
     InvalidTransactionId,
+    /// Atomic swap error
+    #[error("Atomic swap error: {0}")]
+    AtomicSwap(String),
+    /// Payment stream error
+    #[error("Payment stream error: {0}")]
+    PaymentStream(String),
+    /// Claims vault entry could not be encrypted or decrypted
+    #[error("Claims vault error: {0}")]
+    ClaimsVault(String),
+    /// Spend exceeds a spending limit and was not confirmed
+    #[error("Spend of {0} exceeds the {1} spending limit and was not confirmed")]
+    SpendingLimitExceeded(Amount, String),
     /// Transaction not found
     #[error("Transaction not found")]
     TransactionNotFound,
@@ -335,6 +386,12 @@ pub enum Error {
     /// Http transport error
     #[error("Http transport error {0:?}: {1}")]
     HttpError(Option<u16>, String),
+    /// Mint is rate limiting requests
+    #[error("Rate limited by mint, retry after {retry_after:?}")]
+    RateLimited {
+        /// Time to wait before sending requests to the mint again
+        retry_after: std::time::Duration,
+    },
     #[cfg(feature = "wallet")]
     // Crate error conversions
     /// Cashu Url Error
@@ -349,6 +406,13 @@ pub enum Error {
     /// DHKE Error
     #[error(transparent)]
     DHKE(#[from] crate::dhke::Error),
+    /// Paper backup Error
+    #[cfg(feature = "wallet")]
+    #[error(transparent)]
+    PaperBackup(#[from] crate::paper_backup::Error),
+    /// Keyset history Error
+    #[error(transparent)]
+    KeysetHistory(#[from] crate::keyset_history::Error),
     /// NUT00 Error
     #[error(transparent)]
     NUT00(#[from] crate::nuts::nut00::Error),
@@ -367,6 +431,9 @@ pub enum Error {
     /// NUT05 error
     #[error(transparent)]
     NUT05(#[from] crate::nuts::nut05::Error),
+    /// NUT06 Error
+    #[error(transparent)]
+    NUT06(#[from] crate::nuts::nut06::Error),
     /// NUT11 Error
     #[error(transparent)]
     NUT11(#[from] crate::nuts::nut11::Error),
@@ -27,9 +27,13 @@ pub mod ws;
 // re-exporting external crates
 pub use bitcoin;
 pub use cashu::amount::{self, Amount};
+pub use cashu::keyset_history::{self, KeysetHistory};
 pub use cashu::lightning_invoice::{self, Bolt11Invoice};
 pub use cashu::nuts::{self, *};
+#[cfg(feature = "wallet")]
+pub use cashu::paper_backup::{self, PaperBackup};
 #[cfg(feature = "mint")]
 pub use cashu::quote_id::{self, *};
+pub use cashu::quote_timestamps::{self, QuoteTimestamps};
 pub use cashu::{dhke, ensure_cdk, mint_url, secret, util, SECP256K1};
 pub use error::Error;
@@ -1,16 +1,18 @@
 //! Melt types
-use cashu::{MeltQuoteBolt11Request, MeltQuoteBolt12Request};
+use cashu::{MeltQuoteBolt11Request, MeltQuoteBolt12Request, MeltQuoteKeysendRequest};
 
 /// Melt quote request enum for different types of quotes
 ///
 /// This enum represents the different types of melt quote requests
-/// that can be made, either BOLT11 or BOLT12.
+/// that can be made, either BOLT11, BOLT12 or keysend.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MeltQuoteRequest {
     /// Lightning Network BOLT11 invoice request
     Bolt11(MeltQuoteBolt11Request),
     /// Lightning Network BOLT12 offer request
     Bolt12(MeltQuoteBolt12Request),
+    /// Lightning Network keysend request
+    Keysend(MeltQuoteKeysendRequest),
 }
 
 impl From<MeltQuoteBolt11Request> for MeltQuoteRequest {
@@ -24,3 +26,9 @@ impl From<MeltQuoteBolt12Request> for MeltQuoteRequest {
         MeltQuoteRequest::Bolt12(request)
     }
 }
+
+impl From<MeltQuoteKeysendRequest> for MeltQuoteRequest {
+    fn from(request: MeltQuoteKeysendRequest) -> Self {
+        MeltQuoteRequest::Keysend(request)
+    }
+}
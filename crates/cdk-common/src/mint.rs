@@ -4,8 +4,8 @@ use bitcoin::bip32::DerivationPath;
 use cashu::quote_id::QuoteId;
 use cashu::util::unix_time;
 use cashu::{
-    Bolt11Invoice, MeltOptions, MeltQuoteBolt11Response, MintQuoteBolt11Response,
-    MintQuoteBolt12Response, PaymentMethod,
+    Bolt11Invoice, MeltFailureReason, MeltOptions, MeltQuoteBolt11Response,
+    MintQuoteBolt11Response, MintQuoteBolt12Response, PaymentMethod, QuoteTimestamps,
 };
 use lightning::offers::offer::Offer;
 use serde::{Deserialize, Serialize};
@@ -134,6 +134,17 @@ impl MintQuote {
         self.compute_quote_state()
     }
 
+    /// Unsigned timestamps of the quote
+    ///
+    /// Paid is the time of the first payment, issued the time of the last issuance.
+    pub fn timestamps(&self) -> QuoteTimestamps {
+        QuoteTimestamps::new(
+            self.created_time,
+            self.payments.iter().map(|payment| payment.time).min(),
+            self.issuance.iter().map(|issuance| issuance.time).max(),
+        )
+    }
+
     /// Existing payment ids of a mint quote
     pub fn payment_ids(&self) -> Vec<&String> {
         self.payments.iter().map(|a| &a.payment_id).collect()
@@ -270,6 +281,9 @@ pub struct MeltQuote {
     /// Payment method
     #[serde(default)]
     pub payment_method: PaymentMethod,
+    /// Reason the last payment attempt failed
+    #[serde(default)]
+    pub failure_reason: Option<MeltFailureReason>,
 }
 
 impl MeltQuote {
@@ -301,8 +315,19 @@ impl MeltQuote {
             created_time: unix_time(),
             paid_time: None,
             payment_method,
+            failure_reason: None,
         }
     }
+
+    /// Unsigned timestamps of the quote
+    pub fn timestamps(&self) -> QuoteTimestamps {
+        let paid_time = match self.state {
+            MeltQuoteState::Paid => self.paid_time,
+            _ => None,
+        };
+
+        QuoteTimestamps::new(self.created_time, paid_time, None)
+    }
 }
 
 /// Mint Keyset Info
@@ -359,6 +384,7 @@ impl From<MintQuote> for MintQuoteBolt11Response<QuoteId> {
             pubkey: mint_quote.pubkey,
             amount: mint_quote.amount,
             unit: Some(mint_quote.unit.clone()),
+            timestamps: Some(mint_quote.timestamps()),
         }
     }
 }
@@ -375,6 +401,8 @@ impl TryFrom<crate::mint::MintQuote> for MintQuoteBolt12Response<QuoteId> {
     type Error = crate::Error;
 
     fn try_from(mint_quote: crate::mint::MintQuote) -> Result<Self, Self::Error> {
+        let timestamps = mint_quote.timestamps();
+
         Ok(MintQuoteBolt12Response {
             quote: mint_quote.id.clone(),
             request: mint_quote.request,
@@ -384,6 +412,7 @@ impl TryFrom<crate::mint::MintQuote> for MintQuoteBolt12Response<QuoteId> {
             pubkey: mint_quote.pubkey.ok_or(crate::Error::PubkeyRequired)?,
             amount: mint_quote.amount,
             unit: mint_quote.unit,
+            timestamps: Some(timestamps),
         })
     }
 }
@@ -411,6 +440,8 @@ impl From<&MeltQuote> for MeltQuoteBolt11Response<QuoteId> {
             fee_reserve: melt_quote.fee_reserve,
             request: None,
             unit: Some(melt_quote.unit.clone()),
+            timestamps: Some(melt_quote.timestamps()),
+            failure_reason: melt_quote.failure_reason,
         }
     }
 }
@@ -429,6 +460,8 @@ impl From<MeltQuote> for MeltQuoteBolt11Response<QuoteId> {
             change: None,
             request: Some(melt_quote.request.to_string()),
             unit: Some(melt_quote.unit.clone()),
+            timestamps: Some(melt_quote.timestamps()),
+            failure_reason: melt_quote.failure_reason,
         }
     }
 }
@@ -447,6 +480,11 @@ pub enum MeltPaymentRequest {
         #[serde(with = "offer_serde")]
         offer: Box<Offer>,
     },
+    /// Keysend Payment
+    Keysend {
+        /// Node id of the payee
+        pubkey: PublicKey,
+    },
 }
 
 impl std::fmt::Display for MeltPaymentRequest {
@@ -454,6 +492,7 @@ impl std::fmt::Display for MeltPaymentRequest {
         match self {
             MeltPaymentRequest::Bolt11 { bolt11 } => write!(f, "{bolt11}"),
             MeltPaymentRequest::Bolt12 { offer } => write!(f, "{offer}"),
+            MeltPaymentRequest::Keysend { pubkey } => write!(f, "{pubkey}"),
         }
     }
 }
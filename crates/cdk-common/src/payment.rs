@@ -15,8 +15,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
+use crate::amount::to_unit;
 use crate::mint::MeltPaymentRequest;
-use crate::nuts::{CurrencyUnit, MeltQuoteState};
+use crate::nuts::{CurrencyUnit, MeltQuoteState, PublicKey};
 use crate::Amount;
 
 /// CDK Lightning Error
@@ -193,6 +194,8 @@ pub struct Bolt11OutgoingPaymentOptions {
     pub timeout_secs: Option<u64>,
     /// Melt options
     pub melt_options: Option<MeltOptions>,
+    /// Key identifying the payment across retries, the same key must never be paid twice
+    pub idempotency_key: Option<String>,
 }
 
 /// Options for BOLT12 outgoing payments
@@ -206,6 +209,23 @@ pub struct Bolt12OutgoingPaymentOptions {
     pub timeout_secs: Option<u64>,
     /// Melt options
     pub melt_options: Option<MeltOptions>,
+    /// Key identifying the payment across retries, the same key must never be paid twice
+    pub idempotency_key: Option<String>,
+}
+
+/// Options for keysend outgoing payments
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeysendOutgoingPaymentOptions {
+    /// Node id of the payee
+    pub pubkey: PublicKey,
+    /// Amount to pay in msat
+    pub amount_msat: Amount,
+    /// Maximum fee amount allowed for the payment
+    pub max_fee_amount: Option<Amount>,
+    /// Optional timeout in seconds
+    pub timeout_secs: Option<u64>,
+    /// Key identifying the payment across retries, the same key must never be paid twice
+    pub idempotency_key: Option<String>,
 }
 
 /// Options for creating an outgoing payment
@@ -215,8 +235,23 @@ pub enum OutgoingPaymentOptions {
     Bolt11(Box<Bolt11OutgoingPaymentOptions>),
     /// BOLT12 payment options
     Bolt12(Box<Bolt12OutgoingPaymentOptions>),
+    /// Keysend payment options
+    Keysend(Box<KeysendOutgoingPaymentOptions>),
+}
+
+impl OutgoingPaymentOptions {
+    /// Key identifying the payment across retries
+    pub fn idempotency_key(&self) -> Option<&str> {
+        match self {
+            OutgoingPaymentOptions::Bolt11(options) => options.idempotency_key.as_deref(),
+            OutgoingPaymentOptions::Bolt12(options) => options.idempotency_key.as_deref(),
+            OutgoingPaymentOptions::Keysend(options) => options.idempotency_key.as_deref(),
+        }
+    }
 }
 
+/// Payments made for a melt quote are keyed by the quote id, so a melt retried
+/// after a crash is recognized by backends that deduplicate payments
 impl TryFrom<crate::mint::MeltQuote> for OutgoingPaymentOptions {
     type Error = Error;
 
@@ -228,6 +263,7 @@ impl TryFrom<crate::mint::MeltQuote> for OutgoingPaymentOptions {
                     timeout_secs: None,
                     bolt11,
                     melt_options: melt_quote.options,
+                    idempotency_key: Some(melt_quote.id.to_string()),
                 },
             ))),
             MeltPaymentRequest::Bolt12 { offer } => {
@@ -243,9 +279,19 @@ impl TryFrom<crate::mint::MeltQuote> for OutgoingPaymentOptions {
                         timeout_secs: None,
                         offer: *offer,
                         melt_options,
+                        idempotency_key: Some(melt_quote.id.to_string()),
                     },
                 )))
             }
+            MeltPaymentRequest::Keysend { pubkey } => Ok(OutgoingPaymentOptions::Keysend(
+                Box::new(KeysendOutgoingPaymentOptions {
+                    pubkey,
+                    amount_msat: to_unit(melt_quote.amount, &melt_quote.unit, &CurrencyUnit::Msat)?,
+                    max_fee_amount: Some(melt_quote.fee_reserve),
+                    timeout_secs: None,
+                    idempotency_key: Some(melt_quote.id.to_string()),
+                }),
+            )),
         }
     }
 }
@@ -289,6 +335,10 @@ pub trait MintPayment {
     ) -> Result<PaymentQuoteResponse, Self::Err>;
 
     /// Pay request
+    ///
+    /// Backends that can deduplicate payments should not pay again for an
+    /// [`OutgoingPaymentOptions::idempotency_key`] they already paid, and return
+    /// the existing payment instead.
     async fn make_payment(
         &self,
         unit: &CurrencyUnit,
@@ -330,6 +380,22 @@ pub trait MintPayment {
         // Default implementation - no internal settlement support
         Ok(None)
     }
+
+    /// Probe the routes of an outgoing payment for its routing fee (optional)
+    /// Returns None if the backend cannot probe routes or found none
+    async fn probe_payment(
+        &self,
+        _unit: &CurrencyUnit,
+        _options: OutgoingPaymentOptions,
+    ) -> Result<Option<Amount>, Self::Err> {
+        Ok(None)
+    }
+
+    /// Backend health and identity information (optional)
+    /// Returns None if the backend does not report any information
+    async fn backend_info(&self) -> Result<Option<BackendInfo>, Self::Err> {
+        Ok(None)
+    }
 }
 
 /// An event emitted which should be handled by the mint
@@ -408,6 +474,25 @@ pub struct PaymentQuoteResponse {
     pub state: MeltQuoteState,
 }
 
+/// Health and identity information reported by a payment backend
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendInfo {
+    /// Name of the backend implementation
+    pub backend: String,
+    /// Version of the backend software
+    pub version: Option<String>,
+    /// Public key of the lightning node
+    pub node_pubkey: Option<String>,
+    /// Alias of the lightning node
+    pub node_alias: Option<String>,
+    /// Spendable balance of the backend
+    pub balance: Option<Amount>,
+    /// Unit of `balance`
+    pub balance_unit: Option<CurrencyUnit>,
+    /// Backend is connected and able to process payments
+    pub connected: bool,
+}
+
 /// Ln backend settings
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Bolt11Settings {
@@ -421,6 +506,9 @@ pub struct Bolt11Settings {
     pub amountless: bool,
     /// Bolt12 supported
     pub bolt12: bool,
+    /// Keysend supported
+    #[serde(default)]
+    pub keysend: bool,
 }
 
 impl TryFrom<Bolt11Settings> for Value {
@@ -623,6 +711,48 @@ where
 
         result
     }
+
+    async fn probe_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<Option<Amount>, Self::Err> {
+        let start = std::time::Instant::now();
+        METRICS.inc_in_flight_requests("probe_payment");
+
+        let result = self.inner.probe_payment(unit, options).await;
+
+        let duration = start.elapsed().as_secs_f64();
+        METRICS.record_mint_operation_histogram("probe_payment", result.is_ok(), duration);
+        METRICS.dec_in_flight_requests("probe_payment");
+
+        result
+    }
+
+    async fn backend_info(&self) -> Result<Option<BackendInfo>, Self::Err> {
+        let start = std::time::Instant::now();
+        METRICS.inc_in_flight_requests("backend_info");
+
+        let result = self.inner.backend_info().await;
+
+        let duration = start.elapsed().as_secs_f64();
+        METRICS.record_mint_operation_histogram("backend_info", result.is_ok(), duration);
+        METRICS.dec_in_flight_requests("backend_info");
+
+        if let Ok(Some(info)) = &result {
+            METRICS.set_payment_backend_connected(&info.backend, info.connected);
+
+            if let (Some(balance), Some(unit)) = (info.balance, &info.balance_unit) {
+                METRICS.set_payment_backend_balance(
+                    &info.backend,
+                    &unit.to_string(),
+                    u64::from(balance) as i64,
+                );
+            }
+        }
+
+        result
+    }
 }
 
 /// Type alias for Mint Payment trait
@@ -10,7 +10,7 @@ use cashu::{nut00, PaymentMethod, Proofs, PublicKey};
 use serde::{Deserialize, Serialize};
 
 use crate::mint_url::MintUrl;
-use crate::nuts::{CurrencyUnit, MeltQuoteState, MintQuoteState, SecretKey};
+use crate::nuts::{CurrencyUnit, Id, MeltQuoteState, MintQuoteState, SecretKey};
 use crate::{Amount, Error};
 
 /// Wallet Key
@@ -87,6 +87,9 @@ pub struct MeltQuote {
     /// Payment method
     #[serde(default)]
     pub payment_method: PaymentMethod,
+    /// Mint of the quote, `None` for quotes stored before it was recorded
+    #[serde(default)]
+    pub mint_url: Option<MintUrl>,
 }
 
 impl MintQuote {
@@ -204,6 +207,9 @@ pub struct Transaction {
     pub metadata: HashMap<String, String>,
     /// Quote ID if this is a mint or melt transaction
     pub quote_id: Option<String>,
+    /// Transaction status
+    #[serde(default)]
+    pub status: TransactionStatus,
 }
 
 impl Transaction {
@@ -280,6 +286,37 @@ impl FromStr for TransactionDirection {
     }
 }
 
+/// Transaction Status
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    /// Outgoing token has been created but not yet claimed by the receiver
+    Pending,
+    /// Transaction is final
+    #[default]
+    Confirmed,
+}
+
+impl std::fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionStatus::Pending => write!(f, "Pending"),
+            TransactionStatus::Confirmed => write!(f, "Confirmed"),
+        }
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "Pending" => Ok(Self::Pending),
+            "Confirmed" => Ok(Self::Confirmed),
+            _ => Err(Error::InvalidTransactionStatus),
+        }
+    }
+}
+
 /// Transaction ID
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -366,6 +403,125 @@ impl TryFrom<Proofs> for TransactionId {
     }
 }
 
+/// Kind of fee paid to a mint
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FeeKind {
+    /// Keyset input fee of a swap or melt
+    Input,
+    /// Lightning fee of a melt
+    Lightning,
+}
+
+impl std::fmt::Display for FeeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeeKind::Input => write!(f, "Input"),
+            FeeKind::Lightning => write!(f, "Lightning"),
+        }
+    }
+}
+
+impl FromStr for FeeKind {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "Input" => Ok(Self::Input),
+            "Lightning" => Ok(Self::Lightning),
+            _ => Err(Error::InvalidFeeKind),
+        }
+    }
+}
+
+/// Fee paid to a mint, as recorded in the fee ledger
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeeEntry {
+    /// Mint Url
+    pub mint_url: MintUrl,
+    /// Currency Unit
+    pub unit: CurrencyUnit,
+    /// Kind of fee
+    pub kind: FeeKind,
+    /// Amount paid
+    pub amount: Amount,
+    /// Unix timestamp
+    pub timestamp: u64,
+    /// Quote ID if the fee was paid for a melt
+    pub quote_id: Option<String>,
+}
+
+impl FeeEntry {
+    /// Check if fee entry matches conditions
+    ///
+    /// `since` is inclusive and `until` exclusive.
+    pub fn matches_conditions(
+        &self,
+        mint_url: &Option<MintUrl>,
+        unit: &Option<CurrencyUnit>,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> bool {
+        if let Some(mint_url) = mint_url {
+            if &self.mint_url != mint_url {
+                return false;
+            }
+        }
+        if let Some(unit) = unit {
+            if &self.unit != unit {
+                return false;
+            }
+        }
+        if since.is_some_and(|since| self.timestamp < since) {
+            return false;
+        }
+        if until.is_some_and(|until| self.timestamp >= until) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Summary of a proof removed from the wallet once spent
+///
+/// Spent proofs are moved out of the proofs table so it only holds proofs the
+/// wallet can still use, the summary is kept for history until it is pruned.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArchivedProof {
+    /// Proof Y
+    pub y: PublicKey,
+    /// Mint Url
+    pub mint_url: MintUrl,
+    /// Keyset id
+    pub keyset_id: Id,
+    /// Amount
+    pub amount: Amount,
+    /// Currency Unit
+    pub unit: CurrencyUnit,
+    /// Unix timestamp of when the proof was archived
+    pub spent_time: u64,
+}
+
+impl ArchivedProof {
+    /// Check if archived proof matches conditions
+    pub fn matches_conditions(
+        &self,
+        mint_url: &Option<MintUrl>,
+        unit: &Option<CurrencyUnit>,
+    ) -> bool {
+        if let Some(mint_url) = mint_url {
+            if &self.mint_url != mint_url {
+                return false;
+            }
+        }
+        if let Some(unit) = unit {
+            if &self.unit != unit {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
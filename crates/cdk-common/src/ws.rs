@@ -76,3 +76,260 @@ pub fn notification_to_ws_message(notification: NotificationInner<QuoteId>) -> W
         params: notification_uuid_to_notification_string(notification),
     })
 }
+
+/// Websocket subprotocol a client requests to receive and send CBOR messages
+///
+/// Peers that do not negotiate it exchange JSON text messages.
+pub const CBOR_SUBPROTOCOL: &str = "cashu.cbor";
+
+/// Error encoding or decoding a websocket message
+#[derive(Debug, thiserror::Error)]
+pub enum WsCodecError {
+    /// JSON error
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// CBOR encoding error
+    #[error("Could not encode CBOR: {0}")]
+    CborEncode(#[from] ciborium::ser::Error<std::io::Error>),
+    /// CBOR decoding error
+    #[error("Could not decode CBOR: {0}")]
+    CborDecode(#[from] ciborium::de::Error<std::io::Error>),
+}
+
+/// Websocket frame carrying a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsFrame {
+    /// Text frame with a JSON message
+    Text(String),
+    /// Binary frame
+    Binary(Vec<u8>),
+}
+
+/// Encoding of the messages of a websocket connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WsEncoding {
+    /// JSON text frames
+    #[default]
+    Json,
+    /// CBOR binary frames, negotiated with [`CBOR_SUBPROTOCOL`]
+    Cbor,
+}
+
+impl WsEncoding {
+    /// Encoding of a connection that negotiated `subprotocol`
+    pub fn from_subprotocol(subprotocol: Option<&str>) -> Self {
+        match subprotocol {
+            Some(CBOR_SUBPROTOCOL) => Self::Cbor,
+            _ => Self::Json,
+        }
+    }
+
+    /// Encode a message into a frame
+    pub fn encode<T>(&self, message: &T) -> Result<WsFrame, WsCodecError>
+    where
+        T: serde::Serialize,
+    {
+        Ok(match self {
+            Self::Json => WsFrame::Text(serde_json::to_string(message)?),
+            Self::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(message, &mut bytes)?;
+                WsFrame::Binary(bytes)
+            }
+        })
+    }
+
+    /// Decode a message from a frame
+    ///
+    /// Text frames are always JSON. Binary frames are CBOR on connections that
+    /// negotiated it, and UTF-8 JSON otherwise.
+    pub fn decode<T>(&self, frame: &WsFrame) -> Result<T, WsCodecError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(match (self, frame) {
+            (_, WsFrame::Text(text)) => serde_json::from_str(text)?,
+            (Self::Json, WsFrame::Binary(bytes)) => serde_json::from_slice(bytes)?,
+            (Self::Cbor, WsFrame::Binary(bytes)) => ciborium::from_reader(&bytes[..])?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cashu::nut17::ws::{WsErrorResponse, WsUnsubscribeResponse as UnsubscribeResponse};
+    use cashu::{
+        Amount, CurrencyUnit, MintQuoteBolt12Response, NotificationPayload, ProofState, SecretKey,
+        State,
+    };
+
+    use super::*;
+
+    fn notification(payload: NotificationPayload<String>) -> WsMessageOrResponse {
+        nut17::ws::WsMessageOrResponse::Notification(nut17::ws::WsNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "subscribe".to_string(),
+            params: NotificationInner {
+                sub_id: SubId::from("sub"),
+                payload,
+            },
+        })
+    }
+
+    /// Encode and decode a message with both encodings, returning the decoded messages
+    fn roundtrip(message: &WsMessageOrResponse) -> Vec<WsMessageOrResponse> {
+        [WsEncoding::Json, WsEncoding::Cbor]
+            .into_iter()
+            .map(|encoding| {
+                let frame = encoding.encode(message).unwrap();
+                assert_eq!(
+                    matches!(frame, WsFrame::Binary(_)),
+                    encoding == WsEncoding::Cbor
+                );
+
+                let decoded: WsMessageOrResponse = encoding.decode(&frame).unwrap();
+                assert_eq!(
+                    serde_json::to_value(&decoded).unwrap(),
+                    serde_json::to_value(message).unwrap()
+                );
+                decoded
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ws_encoding_roundtrip() {
+        let request: WsRequest = (
+            WsMethodRequest::Unsubscribe(WsUnsubscribeRequest {
+                sub_id: SubId::from("sub"),
+            }),
+            7,
+        )
+            .into();
+
+        for encoding in [WsEncoding::Json, WsEncoding::Cbor] {
+            let frame = encoding.encode(&request).unwrap();
+            let decoded: WsRequest = encoding.decode(&frame).unwrap();
+
+            assert_eq!(decoded.id, 7);
+            assert!(matches!(
+                decoded.method,
+                WsMethodRequest::Unsubscribe(WsUnsubscribeRequest { sub_id }) if sub_id == SubId::from("sub")
+            ));
+        }
+
+        assert_eq!(
+            WsEncoding::from_subprotocol(Some(CBOR_SUBPROTOCOL)),
+            WsEncoding::Cbor
+        );
+        assert_eq!(WsEncoding::from_subprotocol(None), WsEncoding::Json);
+    }
+
+    #[test]
+    fn test_ws_encoding_notifications() {
+        let pubkey = SecretKey::generate().public_key();
+
+        let quote = notification(NotificationPayload::MintQuoteBolt12Response(
+            MintQuoteBolt12Response {
+                quote: "quote".to_string(),
+                request: "lno1qgsqvgnwgcg35z6ee2h3yczraddm72xrfua9uve2rlrm9deu7xyfzrc".to_string(),
+                amount: None,
+                unit: CurrencyUnit::Sat,
+                expiry: Some(1_700_000_000),
+                pubkey,
+                amount_paid: Amount::from(u64::MAX / 2),
+                amount_issued: Amount::from(21),
+                timestamps: None,
+            },
+        ));
+        for decoded in roundtrip(&quote) {
+            assert!(matches!(
+                decoded,
+                nut17::ws::WsMessageOrResponse::Notification(nut17::ws::WsNotification {
+                    params: NotificationInner {
+                        payload: NotificationPayload::MintQuoteBolt12Response(_),
+                        ..
+                    },
+                    ..
+                })
+            ));
+        }
+
+        let proof_state = notification(NotificationPayload::ProofState(ProofState {
+            y: pubkey,
+            state: State::Spent,
+            witness: None,
+        }));
+        for decoded in roundtrip(&proof_state) {
+            assert!(matches!(
+                decoded,
+                nut17::ws::WsMessageOrResponse::Notification(nut17::ws::WsNotification {
+                    params: NotificationInner {
+                        payload: NotificationPayload::ProofState(ProofState {
+                            state: State::Spent,
+                            ..
+                        }),
+                        ..
+                    },
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_ws_encoding_responses() {
+        let subscribed = nut17::ws::WsMessageOrResponse::Response(WsResponse {
+            jsonrpc: "2.0".to_string(),
+            result: WsResponseResult::Subscribe(WsSubscribeResponse {
+                status: "OK".to_string(),
+                sub_id: SubId::from("sub"),
+            }),
+            id: 3,
+        });
+        for decoded in roundtrip(&subscribed) {
+            assert!(matches!(
+                decoded,
+                nut17::ws::WsMessageOrResponse::Response(WsResponse {
+                    result: WsResponseResult::Subscribe(_),
+                    id: 3,
+                    ..
+                })
+            ));
+        }
+
+        let unsubscribed = nut17::ws::WsMessageOrResponse::Response(WsResponse {
+            jsonrpc: "2.0".to_string(),
+            result: WsResponseResult::Unsubscribe(UnsubscribeResponse {
+                status: "OK".to_string(),
+                sub_id: SubId::from("sub"),
+            }),
+            id: 4,
+        });
+        for decoded in roundtrip(&unsubscribed) {
+            assert!(matches!(
+                decoded,
+                nut17::ws::WsMessageOrResponse::Response(WsResponse { id: 4, .. })
+            ));
+        }
+
+        let error = nut17::ws::WsMessageOrResponse::ErrorResponse(WsErrorResponse {
+            jsonrpc: "2.0".to_string(),
+            error: WsErrorBody {
+                code: -32601,
+                message: "Method not found".to_string(),
+            },
+            id: 5,
+        });
+        for decoded in roundtrip(&error) {
+            assert!(matches!(
+                decoded,
+                nut17::ws::WsMessageOrResponse::ErrorResponse(WsErrorResponse {
+                    error: WsErrorBody { code: -32601, .. },
+                    id: 5,
+                    ..
+                })
+            ));
+        }
+    }
+}
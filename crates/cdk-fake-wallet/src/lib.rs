@@ -27,9 +27,9 @@ use cdk_common::common::FeeReserve;
 use cdk_common::ensure_cdk;
 use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState};
 use cdk_common::payment::{
-    self, Bolt11Settings, CreateIncomingPaymentResponse, Event, IncomingPaymentOptions,
-    MakePaymentResponse, MintPayment, OutgoingPaymentOptions, PaymentIdentifier,
-    PaymentQuoteResponse, WaitPaymentResponse,
+    self, BackendInfo, Bolt11Settings, CreateIncomingPaymentResponse, Event,
+    IncomingPaymentOptions, MakePaymentResponse, MintPayment, OutgoingPaymentOptions,
+    PaymentIdentifier, PaymentQuoteResponse, WaitPaymentResponse,
 };
 use error::Error;
 use futures::stream::StreamExt;
@@ -195,6 +195,8 @@ pub struct FakeWallet {
     receiver: Arc<Mutex<Option<tokio::sync::mpsc::Receiver<WaitPaymentResponse>>>>,
     payment_states: Arc<Mutex<HashMap<String, MeltQuoteState>>>,
     failed_payment_check: Arc<Mutex<HashSet<String>>>,
+    /// Payments made per idempotency key
+    outgoing_payments: Arc<Mutex<HashMap<String, MakePaymentResponse>>>,
     payment_delay: u64,
     wait_invoice_cancel_token: CancellationToken,
     wait_invoice_is_active: Arc<AtomicBool>,
@@ -243,6 +245,7 @@ impl FakeWallet {
             receiver: Arc::new(Mutex::new(Some(receiver))),
             payment_states: Arc::new(Mutex::new(payment_states)),
             failed_payment_check: Arc::new(Mutex::new(fail_payment_check)),
+            outgoing_payments: Arc::new(Mutex::new(HashMap::new())),
             payment_delay,
             wait_invoice_cancel_token: CancellationToken::new(),
             wait_invoice_is_active: Arc::new(AtomicBool::new(false)),
@@ -289,6 +292,7 @@ impl MintPayment for FakeWallet {
             invoice_description: true,
             amountless: false,
             bolt12: true,
+            keysend: true,
         })?)
     }
 
@@ -374,6 +378,9 @@ impl MintPayment for FakeWallet {
                 };
                 (amount_msat, None)
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                (keysend_options.amount_msat.into(), None)
+            }
         };
 
         let amount = to_unit(amount_msat, &CurrencyUnit::Msat, unit)?;
@@ -400,7 +407,16 @@ impl MintPayment for FakeWallet {
         unit: &CurrencyUnit,
         options: OutgoingPaymentOptions,
     ) -> Result<MakePaymentResponse, Self::Err> {
-        match options {
+        let idempotency_key = options.idempotency_key().map(|key| key.to_string());
+
+        if let Some(key) = &idempotency_key {
+            if let Some(payment) = self.outgoing_payments.lock().await.get(key) {
+                tracing::debug!("Payment {} was already made, not paying again", key);
+                return Ok(payment.clone());
+            }
+        }
+
+        let payment = match options {
             OutgoingPaymentOptions::Bolt11(bolt11_options) => {
                 let bolt11 = bolt11_options.bolt11;
                 let payment_hash = bolt11.payment_hash().to_string();
@@ -443,7 +459,7 @@ impl MintPayment for FakeWallet {
 
                 let total_spent = to_unit(amount_msat, &CurrencyUnit::Msat, unit)?;
 
-                Ok(MakePaymentResponse {
+                MakePaymentResponse {
                     payment_proof: Some("".to_string()),
                     payment_lookup_id: PaymentIdentifier::PaymentHash(
                         *bolt11.payment_hash().as_ref(),
@@ -451,7 +467,7 @@ impl MintPayment for FakeWallet {
                     status: payment_status,
                     total_spent: total_spent + 1.into(),
                     unit: unit.clone(),
-                })
+                }
             }
             OutgoingPaymentOptions::Bolt12(bolt12_options) => {
                 let bolt12 = bolt12_options.offer;
@@ -468,15 +484,45 @@ impl MintPayment for FakeWallet {
 
                 let total_spent = to_unit(amount_msat, &CurrencyUnit::Msat, unit)?;
 
-                Ok(MakePaymentResponse {
+                MakePaymentResponse {
                     payment_proof: Some("".to_string()),
                     payment_lookup_id: PaymentIdentifier::CustomId(Uuid::new_v4().to_string()),
                     status: MeltQuoteState::Paid,
                     total_spent: total_spent + 1.into(),
                     unit: unit.clone(),
-                })
+                }
+            }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                // The payer picks the preimage of a keysend payment
+                let preimage = sha256::Hash::hash(Uuid::new_v4().as_bytes());
+                let payment_hash = sha256::Hash::hash(preimage.as_byte_array());
+
+                let total_spent = to_unit(keysend_options.amount_msat, &CurrencyUnit::Msat, unit)?;
+
+                MakePaymentResponse {
+                    payment_proof: Some(preimage.to_string()),
+                    payment_lookup_id: PaymentIdentifier::PaymentHash(payment_hash.to_byte_array()),
+                    status: MeltQuoteState::Paid,
+                    total_spent: total_spent + 1.into(),
+                    unit: unit.clone(),
+                }
+            }
+        };
+
+        // Failed payments may be retried with the same key
+        if let Some(key) = idempotency_key {
+            if matches!(
+                payment.status,
+                MeltQuoteState::Paid | MeltQuoteState::Pending
+            ) {
+                self.outgoing_payments
+                    .lock()
+                    .await
+                    .insert(key, payment.clone());
             }
         }
+
+        Ok(payment)
     }
 
     #[instrument(skip_all)]
@@ -631,6 +677,20 @@ impl MintPayment for FakeWallet {
             return Err(payment::Error::InvoicePaymentPending);
         }
 
+        // Payments made by the wallet report what they spent
+        if let Some(payment) = self
+            .outgoing_payments
+            .lock()
+            .await
+            .values()
+            .find(|payment| &payment.payment_lookup_id == request_lookup_id)
+        {
+            return Ok(MakePaymentResponse {
+                status,
+                ..payment.clone()
+            });
+        }
+
         Ok(MakePaymentResponse {
             payment_proof: Some("".to_string()),
             payment_lookup_id: request_lookup_id.clone(),
@@ -639,6 +699,16 @@ impl MintPayment for FakeWallet {
             unit: CurrencyUnit::Msat,
         })
     }
+
+    #[instrument(skip_all)]
+    async fn backend_info(&self) -> Result<Option<BackendInfo>, Self::Err> {
+        Ok(Some(BackendInfo {
+            backend: "fake_wallet".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            connected: true,
+            ..Default::default()
+        }))
+    }
 }
 
 /// Create fake invoice
@@ -93,6 +93,9 @@ pub trait WalletDatabase: Send + Sync {
 
     // Proof Management
     /// Update the proofs in storage by adding new proofs or removing proofs by their Y value
+    ///
+    /// Removed proofs in the `Spent` or `PendingSpent` state are kept as archived proofs,
+    /// other removed proofs are dropped.
     async fn update_proofs(
         &self,
         added: Vec<ProofInfo>,
@@ -115,7 +118,20 @@ pub trait WalletDatabase: Send + Sync {
         state: ProofState,
     ) -> Result<(), FfiError>;
 
+    /// Get archived summaries of removed proofs
+    async fn get_archived_proofs(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+    ) -> Result<Vec<ArchivedProof>, FfiError>;
+
+    /// Remove archived proofs spent before the given unix timestamp
+    async fn prune_archived_proofs(&self, spent_before: u64) -> Result<u64, FfiError>;
+
     // Keyset Counter Management
+    /// Get Keyset counter
+    async fn get_keyset_counter(&self, keyset_id: Id) -> Result<u32, FfiError>;
+
     /// Increment Keyset counter
     async fn increment_keyset_counter(&self, keyset_id: Id, count: u32) -> Result<u32, FfiError>;
 
@@ -139,6 +155,52 @@ pub trait WalletDatabase: Send + Sync {
 
     /// Remove transaction from storage
     async fn remove_transaction(&self, transaction_id: TransactionId) -> Result<(), FfiError>;
+
+    // Fee Ledger
+    /// Add fee paid to a mint to the fee ledger
+    async fn add_fee_entry(&self, entry: FeeEntry) -> Result<(), FfiError>;
+
+    /// List fee ledger entries, `since` inclusive and `until` exclusive
+    async fn list_fee_entries(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<FeeEntry>, FfiError>;
+
+    // Key-Value Store
+    /// Read value from key-value store
+    async fn kv_read(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+        key: String,
+    ) -> Result<Option<Vec<u8>>, FfiError>;
+
+    /// Write value to key-value store
+    async fn kv_write(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+        key: String,
+        value: Vec<u8>,
+    ) -> Result<(), FfiError>;
+
+    /// Remove value from key-value store
+    async fn kv_remove(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+        key: String,
+    ) -> Result<(), FfiError>;
+
+    /// List keys in a namespace
+    async fn kv_list(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+    ) -> Result<Vec<String>, FfiError>;
 }
 
 /// Internal bridge trait to convert from the FFI trait to the CDK database trait
@@ -479,7 +541,43 @@ impl CdkWalletDatabase for WalletDatabaseBridge {
             .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
     }
 
+    async fn get_archived_proofs(
+        &self,
+        mint_url: Option<cdk::mint_url::MintUrl>,
+        unit: Option<cdk::nuts::CurrencyUnit>,
+    ) -> Result<Vec<cdk::wallet::types::ArchivedProof>, Self::Err> {
+        let ffi_mint_url = mint_url.map(Into::into);
+        let ffi_unit = unit.map(Into::into);
+
+        let result = self
+            .ffi_db
+            .get_archived_proofs(ffi_mint_url, ffi_unit)
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))?;
+
+        result
+            .into_iter()
+            .map(|archived_proof| archived_proof.try_into())
+            .collect::<Result<Vec<_>, FfiError>>()
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
+    async fn prune_archived_proofs(&self, spent_before: u64) -> Result<u64, Self::Err> {
+        self.ffi_db
+            .prune_archived_proofs(spent_before)
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
     // Keyset Counter Management
+    async fn get_keyset_counter(&self, keyset_id: &cdk::nuts::Id) -> Result<u32, Self::Err> {
+        let ffi_id = (*keyset_id).into();
+        self.ffi_db
+            .get_keyset_counter(ffi_id)
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
     async fn increment_keyset_counter(
         &self,
         keyset_id: &cdk::nuts::Id,
@@ -554,6 +652,101 @@ impl CdkWalletDatabase for WalletDatabaseBridge {
             .await
             .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
     }
+
+    // Fee Ledger
+    async fn add_fee_entry(&self, entry: cdk::wallet::types::FeeEntry) -> Result<(), Self::Err> {
+        self.ffi_db
+            .add_fee_entry(entry.into())
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
+    async fn list_fee_entries(
+        &self,
+        mint_url: Option<cdk::mint_url::MintUrl>,
+        unit: Option<cdk::nuts::CurrencyUnit>,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<cdk::wallet::types::FeeEntry>, Self::Err> {
+        let ffi_mint_url = mint_url.map(Into::into);
+        let ffi_unit = unit.map(Into::into);
+
+        let result = self
+            .ffi_db
+            .list_fee_entries(ffi_mint_url, ffi_unit, since, until)
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))?;
+
+        result
+            .into_iter()
+            .map(|entry| entry.try_into())
+            .collect::<Result<Vec<_>, FfiError>>()
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
+    async fn kv_read(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.ffi_db
+            .kv_read(
+                primary_namespace.to_string(),
+                secondary_namespace.to_string(),
+                key.to_string(),
+            )
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
+    async fn kv_write(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), Self::Err> {
+        self.ffi_db
+            .kv_write(
+                primary_namespace.to_string(),
+                secondary_namespace.to_string(),
+                key.to_string(),
+                value.to_vec(),
+            )
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
+    async fn kv_remove(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<(), Self::Err> {
+        self.ffi_db
+            .kv_remove(
+                primary_namespace.to_string(),
+                secondary_namespace.to_string(),
+                key.to_string(),
+            )
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
+    async fn kv_list(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+    ) -> Result<Vec<String>, Self::Err> {
+        self.ffi_db
+            .kv_list(
+                primary_namespace.to_string(),
+                secondary_namespace.to_string(),
+            )
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
 }
 
 /// FFI-compatible WalletSqliteDatabase implementation that implements the WalletDatabase trait
@@ -886,7 +1079,39 @@ impl WalletDatabase for WalletSqliteDatabase {
             .map_err(|e| FfiError::Database { msg: e.to_string() })
     }
 
+    async fn get_archived_proofs(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+    ) -> Result<Vec<ArchivedProof>, FfiError> {
+        let cdk_mint_url = mint_url.map(|u| u.try_into()).transpose()?;
+        let cdk_unit = unit.map(Into::into);
+
+        let result = self
+            .inner
+            .get_archived_proofs(cdk_mint_url, cdk_unit)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn prune_archived_proofs(&self, spent_before: u64) -> Result<u64, FfiError> {
+        self.inner
+            .prune_archived_proofs(spent_before)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })
+    }
+
     // Keyset Counter Management
+    async fn get_keyset_counter(&self, keyset_id: Id) -> Result<u32, FfiError> {
+        let cdk_id = keyset_id.into();
+        self.inner
+            .get_keyset_counter(&cdk_id)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })
+    }
+
     async fn increment_keyset_counter(&self, keyset_id: Id, count: u32) -> Result<u32, FfiError> {
         let cdk_id = keyset_id.into();
         self.inner
@@ -945,6 +1170,82 @@ impl WalletDatabase for WalletSqliteDatabase {
             .await
             .map_err(|e| FfiError::Database { msg: e.to_string() })
     }
+
+    // Fee Ledger
+    async fn add_fee_entry(&self, entry: FeeEntry) -> Result<(), FfiError> {
+        let cdk_entry = entry.try_into()?;
+        self.inner
+            .add_fee_entry(cdk_entry)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })
+    }
+
+    async fn list_fee_entries(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<FeeEntry>, FfiError> {
+        let cdk_mint_url = mint_url.map(|u| u.try_into()).transpose()?;
+        let cdk_unit = unit.map(Into::into);
+
+        let result = self
+            .inner
+            .list_fee_entries(cdk_mint_url, cdk_unit, since, until)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn kv_read(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+        key: String,
+    ) -> Result<Option<Vec<u8>>, FfiError> {
+        self.inner
+            .kv_read(&primary_namespace, &secondary_namespace, &key)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })
+    }
+
+    async fn kv_write(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+        key: String,
+        value: Vec<u8>,
+    ) -> Result<(), FfiError> {
+        self.inner
+            .kv_write(&primary_namespace, &secondary_namespace, &key, &value)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })
+    }
+
+    async fn kv_remove(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+        key: String,
+    ) -> Result<(), FfiError> {
+        self.inner
+            .kv_remove(&primary_namespace, &secondary_namespace, &key)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })
+    }
+
+    async fn kv_list(
+        &self,
+        primary_namespace: String,
+        secondary_namespace: String,
+    ) -> Result<Vec<String>, FfiError> {
+        self.inner
+            .kv_list(&primary_namespace, &secondary_namespace)
+            .await
+            .map_err(|e| FfiError::Database { msg: e.to_string() })
+    }
 }
 
 /// Helper function to create a CDK database from the FFI trait
@@ -42,6 +42,10 @@ pub enum FfiError {
     #[error("Network error: {msg}")]
     Network { msg: String },
 
+    /// Mint is rate limiting requests
+    #[error("Rate limited, retry after {retry_after_secs} seconds")]
+    RateLimited { retry_after_secs: u64 },
+
     /// Invalid token
     #[error("Invalid token: {msg}")]
     InvalidToken { msg: String },
@@ -92,6 +96,9 @@ impl From<CdkError> for FfiError {
             CdkError::InsufficientFunds => FfiError::InsufficientFunds,
             CdkError::UnsupportedUnit => FfiError::UnitNotSupported,
             CdkError::KeysetUnknown(_) => FfiError::KeysetUnknown,
+            CdkError::RateLimited { retry_after } => FfiError::RateLimited {
+                retry_after_secs: retry_after.as_secs(),
+            },
             _ => FfiError::Generic {
                 msg: err.to_string(),
             },
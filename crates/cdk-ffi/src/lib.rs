@@ -181,6 +181,7 @@ mod tests {
             include_fee: true,
             max_proofs: Some(10),
             metadata,
+            include_keyset_hints: false,
         };
 
         assert!(options.memo.is_some());
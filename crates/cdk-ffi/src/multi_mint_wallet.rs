@@ -13,6 +13,7 @@ use cdk::wallet::multi_mint_wallet::{
 
 use crate::error::FfiError;
 use crate::types::*;
+use crate::wallet::{create_cdk_confirmation_handler_from_ffi, ConfirmationHandler};
 
 /// FFI-compatible MultiMintWallet
 #[derive(uniffi::Object)]
@@ -327,6 +328,24 @@ impl MultiMintWallet {
         Ok(amount.into())
     }
 
+    /// Get the spending limits of the unit of this wallet
+    pub async fn spending_limits(&self) -> Result<SpendingLimits, FfiError> {
+        Ok(self.inner.spending_limits().await?.into())
+    }
+
+    /// Set the spending limits of the unit of this wallet
+    pub async fn set_spending_limits(&self, limits: SpendingLimits) -> Result<(), FfiError> {
+        self.inner.set_spending_limits(limits.into()).await?;
+        Ok(())
+    }
+
+    /// Set the handler approving spends above the spending limits for every mint
+    pub async fn set_confirmation_handler(&self, handler: Option<Arc<dyn ConfirmationHandler>>) {
+        self.inner
+            .set_confirmation_handler(handler.map(create_cdk_confirmation_handler_from_ffi))
+            .await;
+    }
+
     /// Get list of mint URLs
     pub async fn get_mint_urls(&self) -> Vec<String> {
         let wallets = self.inner.get_wallets().await;
@@ -435,6 +435,9 @@ pub struct SendOptions {
     pub max_proofs: Option<u32>,
     /// Metadata
     pub metadata: HashMap<String, String>,
+    /// Embed the keys of the token keysets in the token for offline verification
+    #[serde(default)]
+    pub include_keyset_hints: bool,
 }
 
 impl Default for SendOptions {
@@ -447,6 +450,7 @@ impl Default for SendOptions {
             include_fee: false,
             max_proofs: None,
             metadata: HashMap::new(),
+            include_keyset_hints: false,
         }
     }
 }
@@ -461,6 +465,7 @@ impl From<SendOptions> for cdk::wallet::SendOptions {
             include_fee: opts.include_fee,
             max_proofs: opts.max_proofs.map(|p| p as usize),
             metadata: opts.metadata,
+            include_keyset_hints: opts.include_keyset_hints,
         }
     }
 }
@@ -475,6 +480,7 @@ impl From<cdk::wallet::SendOptions> for SendOptions {
             include_fee: opts.include_fee,
             max_proofs: opts.max_proofs.map(|p| p as u32),
             metadata: opts.metadata,
+            include_keyset_hints: opts.include_keyset_hints,
         }
     }
 }
@@ -551,6 +557,146 @@ impl From<cdk::nuts::SecretKey> for SecretKey {
     }
 }
 
+/// FFI-compatible Restore options
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct RestoreOptions {
+    /// Counters asked to the mint per request
+    pub batch_size: u32,
+    /// Batches in a row without any signature after which the scan stops
+    pub gap_limit: u32,
+    /// Scan one more batch when a signature is found in the last tenth of a batch
+    pub adaptive: bool,
+    /// Start at the highest signed counter found by the previous restore instead of zero
+    pub incremental: bool,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        cdk::wallet::RestoreOptions::default().into()
+    }
+}
+
+impl From<RestoreOptions> for cdk::wallet::RestoreOptions {
+    fn from(opts: RestoreOptions) -> Self {
+        cdk::wallet::RestoreOptions {
+            batch_size: opts.batch_size,
+            gap_limit: opts.gap_limit,
+            adaptive: opts.adaptive,
+            incremental: opts.incremental,
+        }
+    }
+}
+
+impl From<cdk::wallet::RestoreOptions> for RestoreOptions {
+    fn from(opts: cdk::wallet::RestoreOptions) -> Self {
+        Self {
+            batch_size: opts.batch_size,
+            gap_limit: opts.gap_limit,
+            adaptive: opts.adaptive,
+            incremental: opts.incremental,
+        }
+    }
+}
+
+/// FFI-compatible Spending limits
+#[derive(Debug, Clone, Default, Serialize, Deserialize, uniffi::Record)]
+pub struct SpendingLimits {
+    /// Largest amount of a single send or melt
+    pub per_transaction: Option<Amount>,
+    /// Largest amount sent and melted in the last 24 hours
+    pub daily: Option<Amount>,
+}
+
+impl From<SpendingLimits> for cdk::wallet::SpendingLimits {
+    fn from(limits: SpendingLimits) -> Self {
+        cdk::wallet::SpendingLimits {
+            per_transaction: limits.per_transaction.map(Into::into),
+            daily: limits.daily.map(Into::into),
+        }
+    }
+}
+
+impl From<cdk::wallet::SpendingLimits> for SpendingLimits {
+    fn from(limits: cdk::wallet::SpendingLimits) -> Self {
+        Self {
+            per_transaction: limits.per_transaction.map(Into::into),
+            daily: limits.daily.map(Into::into),
+        }
+    }
+}
+
+/// FFI-compatible SpendKind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+pub enum SpendKind {
+    /// Ecash sent as a token
+    Send,
+    /// Ecash melted to pay a payment request
+    Melt,
+    /// Ecash swapped to proofs locked to spending conditions
+    Lock,
+}
+
+impl From<cdk::wallet::SpendKind> for SpendKind {
+    fn from(kind: cdk::wallet::SpendKind) -> Self {
+        match kind {
+            cdk::wallet::SpendKind::Send => SpendKind::Send,
+            cdk::wallet::SpendKind::Melt => SpendKind::Melt,
+            cdk::wallet::SpendKind::Lock => SpendKind::Lock,
+        }
+    }
+}
+
+/// FFI-compatible ExceededLimit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+pub enum ExceededLimit {
+    /// Per transaction limit
+    PerTransaction,
+    /// Daily limit
+    Daily,
+}
+
+impl From<cdk::wallet::ExceededLimit> for ExceededLimit {
+    fn from(limit: cdk::wallet::ExceededLimit) -> Self {
+        match limit {
+            cdk::wallet::ExceededLimit::PerTransaction => ExceededLimit::PerTransaction,
+            cdk::wallet::ExceededLimit::Daily => ExceededLimit::Daily,
+        }
+    }
+}
+
+/// FFI-compatible Spend confirmation
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct SpendConfirmation {
+    /// Mint the ecash is spent from
+    pub mint_url: MintUrl,
+    /// Unit of the amounts
+    pub unit: CurrencyUnit,
+    /// Operation spending the ecash
+    pub kind: SpendKind,
+    /// Amount of the spend, including the fee reserve of a melt
+    pub amount: Amount,
+    /// Amount spent in the last 24 hours, without this spend
+    pub spent_today: Amount,
+    /// Limits of the wallet
+    pub limits: SpendingLimits,
+    /// Limit the spend exceeds
+    pub exceeded: ExceededLimit,
+}
+
+impl From<cdk::wallet::SpendConfirmation> for SpendConfirmation {
+    fn from(spend: cdk::wallet::SpendConfirmation) -> Self {
+        Self {
+            mint_url: spend.mint_url.into(),
+            unit: spend.unit.into(),
+            kind: spend.kind.into(),
+            amount: spend.amount.into(),
+            spent_today: spend.spent_today.into(),
+            limits: spend.limits.into(),
+            exceeded: spend.exceeded.into(),
+        }
+    }
+}
+
 /// FFI-compatible Receive options
 #[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
 pub struct ReceiveOptions {
@@ -865,6 +1011,30 @@ pub fn encode_mint_quote(quote: MintQuote) -> Result<String, FfiError> {
     Ok(serde_json::to_string(&quote)?)
 }
 
+/// FFI-compatible QuoteTimestamps
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Record)]
+pub struct QuoteTimestamps {
+    /// Unix time the quote was created
+    pub created: u64,
+    /// Unix time the quote was paid
+    pub paid: Option<u64>,
+    /// Unix time of the last issuance of a mint quote
+    pub issued: Option<u64>,
+    /// Signature of the mint identity key over the timestamps
+    pub signature: Option<String>,
+}
+
+impl From<cdk::quote_timestamps::QuoteTimestamps> for QuoteTimestamps {
+    fn from(timestamps: cdk::quote_timestamps::QuoteTimestamps) -> Self {
+        Self {
+            created: timestamps.created,
+            paid: timestamps.paid,
+            issued: timestamps.issued,
+            signature: timestamps.signature,
+        }
+    }
+}
+
 /// FFI-compatible MintQuoteBolt11Response
 #[derive(Debug, uniffi::Object)]
 pub struct MintQuoteBolt11Response {
@@ -882,6 +1052,8 @@ pub struct MintQuoteBolt11Response {
     pub unit: Option<CurrencyUnit>,
     /// Pubkey (optional)
     pub pubkey: Option<String>,
+    /// Timestamps (optional)
+    pub timestamps: Option<QuoteTimestamps>,
 }
 
 impl From<cdk::nuts::MintQuoteBolt11Response<String>> for MintQuoteBolt11Response {
@@ -894,6 +1066,7 @@ impl From<cdk::nuts::MintQuoteBolt11Response<String>> for MintQuoteBolt11Respons
             amount: response.amount.map(Into::into),
             unit: response.unit.map(Into::into),
             pubkey: response.pubkey.map(|p| p.to_string()),
+            timestamps: response.timestamps.map(Into::into),
         }
     }
 }
@@ -934,6 +1107,11 @@ impl MintQuoteBolt11Response {
     pub fn pubkey(&self) -> Option<String> {
         self.pubkey.clone()
     }
+
+    /// Get timestamps
+    pub fn timestamps(&self) -> Option<QuoteTimestamps> {
+        self.timestamps.clone()
+    }
 }
 
 /// FFI-compatible MeltQuoteBolt11Response
@@ -955,6 +1133,10 @@ pub struct MeltQuoteBolt11Response {
     pub request: Option<String>,
     /// Unit (optional)
     pub unit: Option<CurrencyUnit>,
+    /// Timestamps (optional)
+    pub timestamps: Option<QuoteTimestamps>,
+    /// Reason the last payment attempt failed (optional)
+    pub failure_reason: Option<MeltFailureReason>,
 }
 
 impl From<cdk::nuts::MeltQuoteBolt11Response<String>> for MeltQuoteBolt11Response {
@@ -968,6 +1150,8 @@ impl From<cdk::nuts::MeltQuoteBolt11Response<String>> for MeltQuoteBolt11Respons
             payment_preimage: response.payment_preimage,
             request: response.request,
             unit: response.unit.map(Into::into),
+            timestamps: response.timestamps.map(Into::into),
+            failure_reason: response.failure_reason.map(Into::into),
         }
     }
 }
@@ -1013,6 +1197,50 @@ impl MeltQuoteBolt11Response {
     pub fn unit(&self) -> Option<CurrencyUnit> {
         self.unit.clone()
     }
+
+    /// Get timestamps
+    pub fn timestamps(&self) -> Option<QuoteTimestamps> {
+        self.timestamps.clone()
+    }
+
+    /// Get failure reason
+    pub fn failure_reason(&self) -> Option<MeltFailureReason> {
+        self.failure_reason.clone()
+    }
+}
+
+/// FFI-compatible MeltFailureReason
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+pub enum MeltFailureReason {
+    PaymentFailed,
+    BackendError,
+    FeeExceeded,
+    ExchangeQuoteExpired,
+    AlreadyPaid,
+}
+
+impl From<cdk::nuts::MeltFailureReason> for MeltFailureReason {
+    fn from(reason: cdk::nuts::MeltFailureReason) -> Self {
+        match reason {
+            cdk::nuts::MeltFailureReason::PaymentFailed => Self::PaymentFailed,
+            cdk::nuts::MeltFailureReason::BackendError => Self::BackendError,
+            cdk::nuts::MeltFailureReason::FeeExceeded => Self::FeeExceeded,
+            cdk::nuts::MeltFailureReason::ExchangeQuoteExpired => Self::ExchangeQuoteExpired,
+            cdk::nuts::MeltFailureReason::AlreadyPaid => Self::AlreadyPaid,
+        }
+    }
+}
+
+impl From<MeltFailureReason> for cdk::nuts::MeltFailureReason {
+    fn from(reason: MeltFailureReason) -> Self {
+        match reason {
+            MeltFailureReason::PaymentFailed => Self::PaymentFailed,
+            MeltFailureReason::BackendError => Self::BackendError,
+            MeltFailureReason::FeeExceeded => Self::FeeExceeded,
+            MeltFailureReason::ExchangeQuoteExpired => Self::ExchangeQuoteExpired,
+            MeltFailureReason::AlreadyPaid => Self::AlreadyPaid,
+        }
+    }
 }
 
 /// FFI-compatible PaymentMethod
@@ -1067,6 +1295,8 @@ pub struct MeltQuote {
     pub payment_preimage: Option<String>,
     /// Payment method
     pub payment_method: PaymentMethod,
+    /// Mint of the quote, if recorded
+    pub mint_url: Option<MintUrl>,
 }
 
 impl From<cdk::wallet::MeltQuote> for MeltQuote {
@@ -1081,6 +1311,7 @@ impl From<cdk::wallet::MeltQuote> for MeltQuote {
             expiry: quote.expiry,
             payment_preimage: quote.payment_preimage.clone(),
             payment_method: quote.payment_method.into(),
+            mint_url: quote.mint_url.map(Into::into),
         }
     }
 }
@@ -1099,6 +1330,7 @@ impl TryFrom<MeltQuote> for cdk::wallet::MeltQuote {
             expiry: quote.expiry,
             payment_preimage: quote.payment_preimage,
             payment_method: quote.payment_method.into(),
+            mint_url: quote.mint_url.map(TryInto::try_into).transpose()?,
         })
     }
 }
@@ -1285,6 +1517,132 @@ impl PreparedSend {
     }
 }
 
+/// FFI-compatible SigningRequest
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct SigningRequest {
+    /// Index of the input the signature is added to
+    pub input_index: u32,
+    /// Message to sign
+    pub message: String,
+    /// Public keys a signature is accepted from
+    pub pubkeys: Vec<PublicKey>,
+    /// Number of signatures required
+    pub required_signatures: u64,
+}
+
+impl From<cdk::wallet::SigningRequest> for SigningRequest {
+    fn from(request: cdk::wallet::SigningRequest) -> Self {
+        Self {
+            input_index: request.input_index as u32,
+            message: request.message,
+            pubkeys: request.pubkeys.into_iter().map(Into::into).collect(),
+            required_signatures: request.required_signatures,
+        }
+    }
+}
+
+/// FFI-compatible SpendSignature
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct SpendSignature {
+    /// Index of the input the signature is for
+    pub input_index: u32,
+    /// Key that made the signature
+    pub pubkey: PublicKey,
+    /// Hex encoded Schnorr signature
+    pub signature: String,
+}
+
+impl From<cdk::wallet::SpendSignature> for SpendSignature {
+    fn from(signature: cdk::wallet::SpendSignature) -> Self {
+        Self {
+            input_index: signature.input_index as u32,
+            pubkey: signature.pubkey.into(),
+            signature: signature.signature.to_string(),
+        }
+    }
+}
+
+impl TryFrom<SpendSignature> for cdk::wallet::SpendSignature {
+    type Error = FfiError;
+
+    fn try_from(signature: SpendSignature) -> Result<Self, Self::Error> {
+        Ok(Self {
+            input_index: signature.input_index as usize,
+            pubkey: signature.pubkey.try_into()?,
+            signature: cdk::secp256k1::schnorr::Signature::from_str(&signature.signature).map_err(
+                |e| FfiError::InvalidCryptographicKey {
+                    msg: format!("Invalid signature: {}", e),
+                },
+            )?,
+        })
+    }
+}
+
+/// FFI-compatible PreparedSpend
+///
+/// Serializes to JSON so it can be handed to an external signer. The secrets of
+/// the outputs stay in the wallet, the signer only returns signatures.
+#[derive(Debug, uniffi::Object)]
+pub struct PreparedSpend {
+    pub(crate) inner: cdk::wallet::PreparedSpend,
+}
+
+impl From<cdk::wallet::PreparedSpend> for PreparedSpend {
+    fn from(spend: cdk::wallet::PreparedSpend) -> Self {
+        Self { inner: spend }
+    }
+}
+
+#[uniffi::export]
+impl PreparedSpend {
+    /// Decode a PreparedSpend from JSON
+    #[uniffi::constructor]
+    pub fn from_json(json: String) -> Result<PreparedSpend, FfiError> {
+        let spend: cdk::wallet::PreparedSpend = serde_json::from_str(&json)?;
+        Ok(spend.into())
+    }
+
+    /// Encode the PreparedSpend to JSON
+    pub fn to_json(&self) -> Result<String, FfiError> {
+        Ok(serde_json::to_string(&self.inner)?)
+    }
+
+    /// Get the id used to finalize or cancel the spend
+    pub fn id(&self) -> String {
+        self.inner.id.clone()
+    }
+
+    /// Get the amount to send
+    pub fn amount(&self) -> Amount {
+        self.inner.amount.into()
+    }
+
+    /// Get the input fee of the swap
+    pub fn fee(&self) -> Amount {
+        self.inner.fee.into()
+    }
+
+    /// Get the signatures the inputs still need
+    pub fn signing_requests(&self) -> Result<Vec<SigningRequest>, FfiError> {
+        Ok(self
+            .inner
+            .signing_requests()?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Sign every input `secret_key` can sign
+    pub fn sign(&self, secret_key: SecretKey) -> Result<Vec<SpendSignature>, FfiError> {
+        Ok(self
+            .inner
+            .sign(&secret_key.into())?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+}
+
 /// FFI-compatible Melted result
 #[derive(Debug, Clone, uniffi::Record)]
 pub struct Melted {
@@ -1293,6 +1651,25 @@ pub struct Melted {
     pub change: Option<Proofs>,
     pub amount: Amount,
     pub fee_paid: Amount,
+    pub fee_breakdown: MeltFeeBreakdown,
+}
+
+/// FFI-compatible breakdown of melt fees
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct MeltFeeBreakdown {
+    pub lightning_fee: Amount,
+    pub input_fee: Amount,
+    pub fee_reserve_returned: Amount,
+}
+
+impl From<cdk::types::MeltFeeBreakdown> for MeltFeeBreakdown {
+    fn from(breakdown: cdk::types::MeltFeeBreakdown) -> Self {
+        Self {
+            lightning_fee: breakdown.lightning_fee.into(),
+            input_fee: breakdown.input_fee.into(),
+            fee_reserve_returned: breakdown.fee_reserve_returned.into(),
+        }
+    }
 }
 
 // MeltQuoteState is just an alias for nut05::QuoteState, so we don't need a separate implementation
@@ -1310,6 +1687,7 @@ impl From<cdk::types::Melted> for Melted {
             }),
             amount: melted.amount.into(),
             fee_paid: melted.fee_paid.into(),
+            fee_breakdown: melted.fee_breakdown.into(),
         }
     }
 }
@@ -1757,6 +2135,8 @@ impl TryFrom<ProtectedEndpoint> for cdk::nuts::ProtectedEndpoint {
             "/v1/mint/bolt12" => cdk::nuts::RoutePath::MintBolt12,
             "/v1/melt/quote/bolt12" => cdk::nuts::RoutePath::MeltQuoteBolt12,
             "/v1/melt/bolt12" => cdk::nuts::RoutePath::MeltBolt12,
+            "/v1/melt/quote/keysend" => cdk::nuts::RoutePath::MeltQuoteKeysend,
+            "/v1/melt/keysend" => cdk::nuts::RoutePath::MeltKeysend,
             _ => {
                 return Err(FfiError::Generic {
                     msg: format!("Unknown route path: {}", endpoint.path),
@@ -1919,6 +2299,8 @@ pub struct MintInfo {
     pub time: Option<u64>,
     /// terms of url service of the mint
     pub tos_url: Option<String>,
+    /// signature over the mint info by the key in `pubkey`
+    pub signature: Option<String>,
 }
 
 impl From<cdk::nuts::MintInfo> for MintInfo {
@@ -1938,6 +2320,7 @@ impl From<cdk::nuts::MintInfo> for MintInfo {
             motd: info.motd,
             time: info.time,
             tos_url: info.tos_url,
+            signature: info.signature,
         }
     }
 }
@@ -1961,6 +2344,7 @@ impl From<MintInfo> for cdk::nuts::MintInfo {
             motd: info.motd,
             time: info.time,
             tos_url: info.tos_url,
+            signature: info.signature,
         }
     }
 }
@@ -2210,6 +2594,9 @@ pub struct Transaction {
     pub metadata: HashMap<String, String>,
     /// Quote ID if this is a mint or melt transaction
     pub quote_id: Option<String>,
+    /// Transaction status
+    #[serde(default)]
+    pub status: TransactionStatus,
 }
 
 impl From<cdk::wallet::types::Transaction> for Transaction {
@@ -2226,6 +2613,7 @@ impl From<cdk::wallet::types::Transaction> for Transaction {
             memo: tx.memo,
             metadata: tx.metadata,
             quote_id: tx.quote_id,
+            status: tx.status.into(),
         }
     }
 }
@@ -2250,6 +2638,7 @@ impl TryFrom<Transaction> for cdk::wallet::types::Transaction {
             memo: tx.memo,
             metadata: tx.metadata,
             quote_id: tx.quote_id,
+            status: tx.status.into(),
         })
     }
 }
@@ -2273,6 +2662,125 @@ pub fn encode_transaction(transaction: Transaction) -> Result<String, FfiError>
     Ok(serde_json::to_string(&transaction)?)
 }
 
+/// FFI-compatible ArchivedProof
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct ArchivedProof {
+    /// Proof Y
+    pub y: PublicKey,
+    /// Mint URL
+    pub mint_url: MintUrl,
+    /// Keyset id
+    pub keyset_id: Id,
+    /// Amount
+    pub amount: Amount,
+    /// Currency Unit
+    pub unit: CurrencyUnit,
+    /// Unix timestamp of when the proof was archived
+    pub spent_time: u64,
+}
+
+impl From<cdk::wallet::types::ArchivedProof> for ArchivedProof {
+    fn from(archived_proof: cdk::wallet::types::ArchivedProof) -> Self {
+        Self {
+            y: archived_proof.y.into(),
+            mint_url: archived_proof.mint_url.into(),
+            keyset_id: archived_proof.keyset_id.into(),
+            amount: archived_proof.amount.into(),
+            unit: archived_proof.unit.into(),
+            spent_time: archived_proof.spent_time,
+        }
+    }
+}
+
+/// Convert FFI ArchivedProof to CDK ArchivedProof
+impl TryFrom<ArchivedProof> for cdk::wallet::types::ArchivedProof {
+    type Error = FfiError;
+
+    fn try_from(archived_proof: ArchivedProof) -> Result<Self, Self::Error> {
+        Ok(Self {
+            y: archived_proof.y.try_into()?,
+            mint_url: archived_proof.mint_url.try_into()?,
+            keyset_id: archived_proof.keyset_id.into(),
+            amount: archived_proof.amount.into(),
+            unit: archived_proof.unit.into(),
+            spent_time: archived_proof.spent_time,
+        })
+    }
+}
+
+/// FFI-compatible FeeKind
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+pub enum FeeKind {
+    /// Keyset input fee of a swap or melt
+    Input,
+    /// Lightning fee of a melt
+    Lightning,
+}
+
+impl From<cdk::wallet::types::FeeKind> for FeeKind {
+    fn from(kind: cdk::wallet::types::FeeKind) -> Self {
+        match kind {
+            cdk::wallet::types::FeeKind::Input => FeeKind::Input,
+            cdk::wallet::types::FeeKind::Lightning => FeeKind::Lightning,
+        }
+    }
+}
+
+impl From<FeeKind> for cdk::wallet::types::FeeKind {
+    fn from(kind: FeeKind) -> Self {
+        match kind {
+            FeeKind::Input => cdk::wallet::types::FeeKind::Input,
+            FeeKind::Lightning => cdk::wallet::types::FeeKind::Lightning,
+        }
+    }
+}
+
+/// FFI-compatible FeeEntry
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct FeeEntry {
+    /// Mint URL
+    pub mint_url: MintUrl,
+    /// Currency Unit
+    pub unit: CurrencyUnit,
+    /// Kind of fee
+    pub kind: FeeKind,
+    /// Amount paid
+    pub amount: Amount,
+    /// Unix timestamp
+    pub timestamp: u64,
+    /// Quote ID if the fee was paid for a melt
+    pub quote_id: Option<String>,
+}
+
+impl From<cdk::wallet::types::FeeEntry> for FeeEntry {
+    fn from(entry: cdk::wallet::types::FeeEntry) -> Self {
+        Self {
+            mint_url: entry.mint_url.into(),
+            unit: entry.unit.into(),
+            kind: entry.kind.into(),
+            amount: entry.amount.into(),
+            timestamp: entry.timestamp,
+            quote_id: entry.quote_id,
+        }
+    }
+}
+
+/// Convert FFI FeeEntry to CDK FeeEntry
+impl TryFrom<FeeEntry> for cdk::wallet::types::FeeEntry {
+    type Error = FfiError;
+
+    fn try_from(entry: FeeEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            mint_url: entry.mint_url.try_into()?,
+            unit: entry.unit.into(),
+            kind: entry.kind.into(),
+            amount: entry.amount.into(),
+            timestamp: entry.timestamp,
+            quote_id: entry.quote_id,
+        })
+    }
+}
+
 /// FFI-compatible TransactionDirection
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
 pub enum TransactionDirection {
@@ -2300,6 +2808,34 @@ impl From<TransactionDirection> for cdk::wallet::types::TransactionDirection {
     }
 }
 
+/// FFI-compatible TransactionStatus
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+pub enum TransactionStatus {
+    /// Outgoing token has been created but not yet claimed by the receiver
+    Pending,
+    /// Transaction is final
+    #[default]
+    Confirmed,
+}
+
+impl From<cdk::wallet::types::TransactionStatus> for TransactionStatus {
+    fn from(status: cdk::wallet::types::TransactionStatus) -> Self {
+        match status {
+            cdk::wallet::types::TransactionStatus::Pending => TransactionStatus::Pending,
+            cdk::wallet::types::TransactionStatus::Confirmed => TransactionStatus::Confirmed,
+        }
+    }
+}
+
+impl From<TransactionStatus> for cdk::wallet::types::TransactionStatus {
+    fn from(status: TransactionStatus) -> Self {
+        match status {
+            TransactionStatus::Pending => cdk::wallet::types::TransactionStatus::Pending,
+            TransactionStatus::Confirmed => cdk::wallet::types::TransactionStatus::Confirmed,
+        }
+    }
+}
+
 /// FFI-compatible TransactionId
 #[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
 #[serde(transparent)]
@@ -2511,11 +3047,7 @@ impl From<SubscribeParams> for cdk::nuts::nut17::Params<cdk::pub_sub::SubId> {
                 SubId::from(uuid.to_string().as_str())
             });
 
-        cdk::nuts::nut17::Params {
-            kind: params.kind.into(),
-            filters: params.filters,
-            id: sub_id,
-        }
+        cdk::nuts::nut17::Params::new(params.kind.into(), params.filters, sub_id)
     }
 }
 
@@ -2538,6 +3070,93 @@ pub fn encode_subscribe_params(params: SubscribeParams) -> Result<String, FfiErr
     Ok(serde_json::to_string(&params)?)
 }
 
+/// FFI-compatible MeltProgressState
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+pub enum MeltProgressState {
+    /// Quote received from the mint
+    QuoteCreated,
+    /// Input proofs reserved for the melt
+    ProofsReserved,
+    /// Melt request sent, the mint is paying
+    PaymentInFlight,
+    /// Payment made, inputs spent and change received
+    Settled,
+    /// Melt failed and the inputs could not be reclaimed
+    Failed,
+    /// Melt failed or was cancelled and the inputs were reclaimed
+    Refunded,
+}
+
+impl From<cdk::wallet::MeltProgressState> for MeltProgressState {
+    fn from(state: cdk::wallet::MeltProgressState) -> Self {
+        match state {
+            cdk::wallet::MeltProgressState::QuoteCreated => Self::QuoteCreated,
+            cdk::wallet::MeltProgressState::ProofsReserved => Self::ProofsReserved,
+            cdk::wallet::MeltProgressState::PaymentInFlight => Self::PaymentInFlight,
+            cdk::wallet::MeltProgressState::Settled => Self::Settled,
+            cdk::wallet::MeltProgressState::Failed => Self::Failed,
+            cdk::wallet::MeltProgressState::Refunded => Self::Refunded,
+        }
+    }
+}
+
+/// FFI-compatible MeltProgress
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct MeltProgress {
+    /// Melt quote ID
+    pub quote_id: String,
+    /// Mint URL
+    pub mint_url: MintUrl,
+    /// Current step
+    pub state: MeltProgressState,
+    /// Unix timestamp the melt reached the step
+    pub updated_at: u64,
+}
+
+impl From<cdk::wallet::MeltProgress> for MeltProgress {
+    fn from(progress: cdk::wallet::MeltProgress) -> Self {
+        Self {
+            quote_id: progress.quote_id,
+            mint_url: progress.mint_url.into(),
+            state: progress.state.into(),
+            updated_at: progress.updated_at,
+        }
+    }
+}
+
+/// FFI-compatible receiver of melt progress
+#[derive(uniffi::Object)]
+pub struct MeltProgressSubscription {
+    inner: tokio::sync::Mutex<tokio::sync::broadcast::Receiver<cdk::wallet::MeltProgress>>,
+}
+
+impl MeltProgressSubscription {
+    pub(crate) fn new(inner: tokio::sync::broadcast::Receiver<cdk::wallet::MeltProgress>) -> Self {
+        Self {
+            inner: tokio::sync::Mutex::new(inner),
+        }
+    }
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl MeltProgressSubscription {
+    /// Receive the next melt progress, skipping updates missed by a slow receiver
+    pub async fn recv(&self) -> Result<MeltProgress, FfiError> {
+        let mut guard = self.inner.lock().await;
+        loop {
+            match guard.recv().await {
+                Ok(progress) => return Ok(progress.into()),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    return Err(FfiError::Generic {
+                        msg: "Subscription closed".to_string(),
+                    })
+                }
+            }
+        }
+    }
+}
+
 /// FFI-compatible ActiveSubscription
 #[derive(uniffi::Object)]
 pub struct ActiveSubscription {
@@ -4,7 +4,10 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use bip39::Mnemonic;
-use cdk::wallet::{Wallet as CdkWallet, WalletBuilder as CdkWalletBuilder};
+use cdk::wallet::{
+    ConfirmationHandler as CdkConfirmationHandler, Wallet as CdkWallet,
+    WalletBuilder as CdkWalletBuilder,
+};
 
 use crate::error::FfiError;
 use crate::types::*;
@@ -85,6 +88,22 @@ impl Wallet {
         Ok(info.map(Into::into))
     }
 
+    /// Pin the mint identity pubkey obtained out of band
+    pub async fn pin_mint_pubkey(&self, pubkey: PublicKey) -> Result<(), FfiError> {
+        Ok(self.inner.pin_mint_pubkey(pubkey.try_into()?).await?)
+    }
+
+    /// Remove the pinned mint identity pubkey
+    pub async fn unpin_mint_pubkey(&self) -> Result<(), FfiError> {
+        Ok(self.inner.unpin_mint_pubkey().await?)
+    }
+
+    /// Get the pinned mint identity pubkey
+    pub async fn pinned_mint_pubkey(&self) -> Result<Option<PublicKey>, FfiError> {
+        let pubkey = self.inner.pinned_mint_pubkey().await?;
+        Ok(pubkey.map(Into::into))
+    }
+
     /// Receive tokens
     pub async fn receive(
         &self,
@@ -98,12 +117,25 @@ impl Wallet {
         Ok(amount.into())
     }
 
+    /// Get a new public key to receive a locked token to
+    ///
+    /// Tokens locked to these keys are signed automatically on receive.
+    pub async fn next_receive_pubkey(&self) -> Result<PublicKey, FfiError> {
+        Ok(self.inner.next_receive_pubkey().await?.into())
+    }
+
     /// Restore wallet from seed
     pub async fn restore(&self) -> Result<Amount, FfiError> {
         let amount = self.inner.restore().await?;
         Ok(amount.into())
     }
 
+    /// Restore wallet from seed, scanning counters as configured
+    pub async fn restore_with_options(&self, options: RestoreOptions) -> Result<Amount, FfiError> {
+        let amount = self.inner.restore_with_options(options.into()).await?;
+        Ok(amount.into())
+    }
+
     /// Verify token DLEQ proofs
     pub async fn verify_token_dleq(&self, token: std::sync::Arc<Token>) -> Result<(), FfiError> {
         let cdk_token = token.inner.clone();
@@ -141,6 +173,43 @@ impl Wallet {
         Ok(std::sync::Arc::new(prepared.into()))
     }
 
+    /// Prepare a send whose inputs are signed by an external signer
+    pub async fn prepare_spend(
+        &self,
+        amount: Amount,
+        spending_conditions: Option<SpendingConditions>,
+    ) -> Result<std::sync::Arc<PreparedSpend>, FfiError> {
+        let conditions = spending_conditions.map(|sc| sc.try_into()).transpose()?;
+        let spend = self.inner.prepare_spend(amount.into(), conditions).await?;
+        Ok(std::sync::Arc::new(spend.into()))
+    }
+
+    /// Finalize a prepared spend with the signatures of its signer and create a token
+    pub async fn finalize_spend(
+        &self,
+        spend_id: String,
+        signatures: Vec<SpendSignature>,
+        memo: Option<String>,
+    ) -> Result<Token, FfiError> {
+        let signatures = signatures
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+        let proofs = self.inner.finalize_spend(&spend_id, signatures).await?;
+        let token = cdk::nuts::Token::new(
+            self.inner.mint_url.clone(),
+            proofs,
+            memo,
+            self.inner.unit.clone(),
+        );
+        Ok(token.into())
+    }
+
+    /// Release the inputs of a prepared spend
+    pub async fn cancel_spend(&self, spend_id: String) -> Result<(), FfiError> {
+        Ok(self.inner.cancel_spend(&spend_id).await?)
+    }
+
     /// Get a mint quote
     pub async fn mint_quote(
         &self,
@@ -283,8 +352,8 @@ impl Wallet {
                 ProofState::Reserved => self.inner.get_reserved_proofs().await?,
                 ProofState::PendingSpent => self.inner.get_pending_spent_proofs().await?,
                 ProofState::Spent => {
-                    // CDK doesn't have a method to get spent proofs directly
-                    // They are removed from the database when spent
+                    // Spent proofs are removed from the database, only their
+                    // summaries are kept, see `get_archived_proofs`
                     continue;
                 }
             };
@@ -297,6 +366,61 @@ impl Wallet {
         Ok(all_proofs)
     }
 
+    /// Get summaries of spent proofs, most recently spent first
+    pub async fn get_archived_proofs(&self) -> Result<Vec<ArchivedProof>, FfiError> {
+        let archived_proofs = self.inner.get_archived_proofs().await?;
+        Ok(archived_proofs.into_iter().map(Into::into).collect())
+    }
+
+    /// Remove archived proofs spent more than `retention_secs` seconds ago
+    pub async fn prune_archived_proofs(&self, retention_secs: u64) -> Result<u64, FfiError> {
+        Ok(self
+            .inner
+            .prune_archived_proofs(std::time::Duration::from_secs(retention_secs))
+            .await?)
+    }
+
+    /// Get the fees paid to the mint between `since` inclusive and `until` exclusive
+    pub async fn fee_entries(
+        &self,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<FeeEntry>, FfiError> {
+        let entries = self
+            .inner
+            .fee_entries((
+                since.map_or(std::ops::Bound::Unbounded, std::ops::Bound::Included),
+                until.map_or(std::ops::Bound::Unbounded, std::ops::Bound::Excluded),
+            ))
+            .await?;
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+
+    /// Total fees paid to the mint between `since` inclusive and `until` exclusive
+    pub async fn fees_paid(
+        &self,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Amount, FfiError> {
+        let fees_paid = self
+            .inner
+            .fees_paid((
+                since.map_or(std::ops::Bound::Unbounded, std::ops::Bound::Included),
+                until.map_or(std::ops::Bound::Unbounded, std::ops::Bound::Excluded),
+            ))
+            .await?;
+        Ok(fees_paid.into())
+    }
+
+    /// Swap proofs of keysets expiring within `within_secs` seconds into the active keyset
+    pub async fn migrate_expiring_proofs(&self, within_secs: u64) -> Result<Amount, FfiError> {
+        let amount = self
+            .inner
+            .migrate_expiring_proofs(std::time::Duration::from_secs(within_secs))
+            .await?;
+        Ok(amount.into())
+    }
+
     /// Check if proofs are spent
     pub async fn check_proofs_spent(&self, proofs: Proofs) -> Result<Vec<bool>, FfiError> {
         let cdk_proofs: Vec<cdk::nuts::Proof> =
@@ -343,6 +467,81 @@ impl Wallet {
         Ok(())
     }
 
+    /// Wait up to `timeout_secs` for a sent token to be claimed and mark its transaction confirmed
+    pub async fn track_sent_token(
+        &self,
+        token: std::sync::Arc<Token>,
+        timeout_secs: u64,
+    ) -> Result<Option<Transaction>, FfiError> {
+        let cdk_token = token.inner.clone();
+        let transaction = self
+            .inner
+            .track_sent_token(&cdk_token, std::time::Duration::from_secs(timeout_secs))
+            .await?;
+        Ok(transaction.map(Into::into))
+    }
+
+    /// Get sent tokens that have not been claimed yet
+    pub async fn pending_sent_tokens(&self) -> Result<Vec<std::sync::Arc<Token>>, FfiError> {
+        let tokens = self.inner.pending_sent_tokens().await?;
+        Ok(tokens
+            .into_iter()
+            .map(|t| std::sync::Arc::new(t.into()))
+            .collect())
+    }
+
+    /// Export the encrypted claims vault as JSON
+    pub async fn export_claims_vault(&self) -> Result<String, FfiError> {
+        Ok(self.inner.export_claims_vault().await?)
+    }
+
+    /// Import an encrypted claims vault backup
+    pub async fn import_claims_vault(&self, backup: String) -> Result<u32, FfiError> {
+        let imported = self.inner.import_claims_vault(&backup).await?;
+        Ok(imported as u32)
+    }
+
+    /// Get the spending limits of the unit of this wallet
+    pub async fn spending_limits(&self) -> Result<SpendingLimits, FfiError> {
+        Ok(self.inner.spending_limits().await?.into())
+    }
+
+    /// Set the spending limits of the unit of this wallet
+    pub async fn set_spending_limits(&self, limits: SpendingLimits) -> Result<(), FfiError> {
+        self.inner.set_spending_limits(limits.into()).await?;
+        Ok(())
+    }
+
+    /// Get the amount sent and melted in the last 24 hours
+    pub async fn spent_today(&self) -> Result<Amount, FfiError> {
+        Ok(self.inner.spent_today().await?.into())
+    }
+
+    /// Set the handler approving spends above the spending limits
+    pub async fn set_confirmation_handler(&self, handler: Option<Arc<dyn ConfirmationHandler>>) {
+        self.inner
+            .set_confirmation_handler(handler.map(create_cdk_confirmation_handler_from_ffi))
+            .await;
+    }
+
+    /// Get the progress of the melt of a quote
+    pub async fn melt_progress(&self, quote_id: String) -> Result<Option<MeltProgress>, FfiError> {
+        Ok(self.inner.melt_progress(&quote_id).await?.map(Into::into))
+    }
+
+    /// Get the progress of every melt stored in the wallet database
+    pub async fn list_melt_progress(&self) -> Result<Vec<MeltProgress>, FfiError> {
+        let progress = self.inner.list_melt_progress().await?;
+        Ok(progress.into_iter().map(Into::into).collect())
+    }
+
+    /// Receive the progress of melts made by this wallet as they happen
+    pub fn subscribe_melt_progress(&self) -> Arc<MeltProgressSubscription> {
+        Arc::new(MeltProgressSubscription::new(
+            self.inner.subscribe_melt_progress(),
+        ))
+    }
+
     /// Subscribe to wallet events
     pub async fn subscribe(
         &self,
@@ -362,6 +561,16 @@ impl Wallet {
         Ok(keysets.into_iter().map(Into::into).collect())
     }
 
+    /// Fetch every keyset of the mint with its keys in one request and cache them
+    pub async fn fetch_keyset_history(&self) -> Result<Vec<KeySetInfo>, FfiError> {
+        let history = self.inner.fetch_keyset_history().await?;
+        Ok(history
+            .keysets
+            .into_iter()
+            .map(|keyset| cdk::nuts::KeySetInfo::from(keyset).into())
+            .collect())
+    }
+
     /// Get the active keyset for the wallet's unit
     pub async fn get_active_keyset(&self) -> Result<KeySetInfo, FfiError> {
         let keyset = self.inner.get_active_keyset().await?;
@@ -468,6 +677,42 @@ pub struct WalletConfig {
     pub target_proof_count: Option<u32>,
 }
 
+/// Handler approving spends above the spending limits, such as a PIN or biometric prompt
+#[uniffi::export(with_foreign)]
+#[async_trait::async_trait]
+pub trait ConfirmationHandler: Send + Sync {
+    /// Whether the spend may proceed
+    async fn confirm(&self, spend: SpendConfirmation) -> Result<bool, FfiError>;
+}
+
+/// Bridge from the FFI confirmation handler to the CDK one
+struct ConfirmationHandlerBridge {
+    handler: Arc<dyn ConfirmationHandler>,
+}
+
+impl std::fmt::Debug for ConfirmationHandlerBridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ConfirmationHandlerBridge")
+    }
+}
+
+#[async_trait::async_trait]
+impl CdkConfirmationHandler for ConfirmationHandlerBridge {
+    async fn confirm(&self, spend: &cdk::wallet::SpendConfirmation) -> Result<bool, cdk::Error> {
+        self.handler
+            .confirm(spend.clone().into())
+            .await
+            .map_err(|e| cdk::Error::Custom(e.to_string()))
+    }
+}
+
+/// Helper function to create a CDK confirmation handler from the FFI one
+pub(crate) fn create_cdk_confirmation_handler_from_ffi(
+    handler: Arc<dyn ConfirmationHandler>,
+) -> Arc<dyn CdkConfirmationHandler> {
+    Arc::new(ConfirmationHandlerBridge { handler })
+}
+
 /// Generates a new random mnemonic phrase
 #[uniffi::export]
 pub fn generate_mnemonic() -> Result<String, FfiError> {
@@ -0,0 +1,164 @@
+//! Binary for replaying recorded mint traffic
+//!
+//! Reproduces a bug from a recording made with the mintd `request_recording_path` option:
+//! 1. Reads the recorded request/response pairs
+//! 2. Starts a fresh fake wallet mint with an in-memory database
+//! 3. Sends every recorded request to it in order
+//! 4. Reports the requests answered with a different status than recorded
+//!
+//! Recordings hold no proof secrets, so requests spending proofs are skipped.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use bip39::Mnemonic;
+use cdk::mint::{MintBuilder, MintMeltLimits};
+use cdk::nuts::{CurrencyUnit, PaymentMethod};
+use cdk::types::{FeeReserve, QuoteTTL};
+use cdk_axum::recorder::{read_recording, replay};
+use cdk_fake_wallet::FakeWallet;
+use cdk_integration_tests::cli::CommonArgs;
+use cdk_integration_tests::shared;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "replay-mint-traffic")]
+#[command(about = "Replay recorded mint traffic against a fresh fake mint", long_about = None)]
+struct Args {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    /// Recording file written by a mint with `request_recording_path` set
+    recording: PathBuf,
+
+    /// Mnemonic of the recorded mint, so the fresh mint derives the same keysets
+    #[arg(long)]
+    mnemonic: Option<String>,
+
+    /// Print the replayed response of every request, not only mismatches
+    #[arg(long, default_value_t = false)]
+    verbose: bool,
+}
+
+/// Build a fresh mint backed by fake wallets and an in-memory database
+async fn create_fresh_mint(mnemonic: Mnemonic) -> Result<cdk::Mint> {
+    let localstore = Arc::new(cdk_sqlite::mint::memory::empty().await?);
+
+    let mut mint_builder = MintBuilder::new(localstore.clone());
+
+    let fee_reserve = FeeReserve {
+        min_fee_reserve: 1.into(),
+        percent_fee_reserve: 1.0,
+    };
+
+    for unit in [CurrencyUnit::Sat, CurrencyUnit::Usd] {
+        let ln_fake_backend = FakeWallet::new(
+            fee_reserve.clone(),
+            HashMap::default(),
+            HashSet::default(),
+            2,
+            unit.clone(),
+        );
+
+        mint_builder
+            .add_payment_processor(
+                unit,
+                PaymentMethod::Bolt11,
+                MintMeltLimits::new(1, 10_000),
+                Arc::new(ln_fake_backend),
+            )
+            .await?;
+    }
+
+    mint_builder = mint_builder
+        .with_name("replay mint".to_string())
+        .with_description("replay mint".to_string());
+
+    let mint = mint_builder
+        .build_with_seed(localstore, &mnemonic.to_seed_normalized(""))
+        .await?;
+
+    mint.set_quote_ttl(QuoteTTL::new(10000, 10000)).await?;
+
+    Ok(mint)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    shared::setup_logging(&args.common);
+
+    let exchanges = read_recording(&args.recording)?;
+    println!(
+        "Replaying {} requests from {}",
+        exchanges.len(),
+        args.recording.display()
+    );
+
+    let mnemonic = match &args.mnemonic {
+        Some(mnemonic) => Mnemonic::parse(mnemonic)?,
+        None => Mnemonic::generate(12)?,
+    };
+
+    let mint = Arc::new(create_fresh_mint(mnemonic).await?);
+    mint.start().await?;
+
+    let router = cdk_axum::create_mint_router(Arc::clone(&mint), false).await?;
+    let outcomes = replay(router, exchanges).await?;
+
+    mint.stop().await?;
+
+    let mut mismatches = 0;
+    let mut skipped = 0;
+
+    for (index, outcome) in outcomes.iter().enumerate() {
+        if outcome.is_skipped() {
+            skipped += 1;
+
+            if args.verbose {
+                println!(
+                    "#{} {} {}: skipped, spends redacted proofs",
+                    index, outcome.recorded.method, outcome.recorded.path
+                );
+            }
+            continue;
+        }
+
+        let matches = outcome.status_matches();
+
+        if !matches {
+            mismatches += 1;
+        }
+
+        if !matches || args.verbose {
+            println!(
+                "#{} {} {}: recorded {}, replayed {}",
+                index,
+                outcome.recorded.method,
+                outcome.recorded.path,
+                outcome.recorded.status,
+                outcome.status.unwrap_or_default()
+            );
+            println!(
+                "  recorded response: {}",
+                serde_json::to_string(&outcome.recorded.response)?
+            );
+            println!(
+                "  replayed response: {}",
+                serde_json::to_string(&outcome.response)?
+            );
+        }
+    }
+
+    println!(
+        "{} of {} replayed requests answered with the recorded status, {} skipped",
+        outcomes.len() - skipped - mismatches,
+        outcomes.len() - skipped,
+        skipped
+    );
+
+    Ok(())
+}
@@ -58,6 +58,7 @@ async fn start_fake_auth_mint(
         supported_units: vec![cdk::nuts::CurrencyUnit::Sat, cdk::nuts::CurrencyUnit::Usd],
         fee_percent: 0.0,
         reserve_fee_min: cdk::Amount::from(1),
+        fee_reserve: None,
         min_delay_time: 1,
         max_delay_time: 3,
     };
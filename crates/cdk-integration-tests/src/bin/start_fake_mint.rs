@@ -74,6 +74,7 @@ async fn start_fake_mint(
         supported_units: vec![CurrencyUnit::Sat, CurrencyUnit::Usd],
         fee_percent: 0.0,
         reserve_fee_min: 1.into(),
+        fee_reserve: None,
         min_delay_time: 1,
         max_delay_time: 3,
     });
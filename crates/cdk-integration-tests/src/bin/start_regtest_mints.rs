@@ -80,6 +80,7 @@ async fn start_cln_mint(
         bolt12: false,
         fee_percent: 0.0,
         reserve_fee_min: 0.into(),
+        fee_reserve: None,
     };
 
     // Create settings struct for CLN mint using shared function
@@ -151,6 +152,7 @@ async fn start_lnd_mint(
         macaroon_file: lnd_macaroon_file,
         fee_percent: 0.0,
         reserve_fee_min: 0.into(),
+        fee_reserve: None,
     };
 
     // Create settings struct for LND mint using shared function
@@ -207,6 +209,7 @@ async fn start_ldk_mint(
     let ldk_config = cdk_mintd::config::LdkNode {
         fee_percent: 0.0,
         reserve_fee_min: 0.into(),
+        fee_reserve: None,
         bitcoin_network: Some("regtest".to_string()),
         // Use bitcoind RPC for regtest
         chain_source_type: Some("bitcoinrpc".to_string()),
@@ -274,9 +277,13 @@ fn create_ldk_settings(
             mnemonic: Some(mnemonic),
             signatory_url: None,
             signatory_certs: None,
+            identity_secret_key: None,
             input_fee_ppk: None,
             http_cache: cdk_axum::cache::Config::default(),
             enable_swagger_ui: None,
+            request_recording_path: None,
+            path_prefix: None,
+            keyset_final_expiry: None,
             logging: LoggingConfig::default(),
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
@@ -298,6 +305,12 @@ fn create_ldk_settings(
         auth_database: None,
         mint_management_rpc: None,
         prometheus: None,
+        chaos: None,
+        notifications: None,
+        risk: None,
+        webhooks: None,
+        vouchers: None,
+        stats: None,
         auth: None,
     }
 }
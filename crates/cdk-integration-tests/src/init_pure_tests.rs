@@ -9,9 +9,13 @@ use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use bip39::Mnemonic;
 use cashu::quote_id::QuoteId;
-use cashu::{MeltQuoteBolt12Request, MintQuoteBolt12Request, MintQuoteBolt12Response};
+use cashu::{
+    MeltQuoteBolt12Request, MeltQuoteKeysendRequest, MintQuoteBolt12Request,
+    MintQuoteBolt12Response,
+};
 use cdk::amount::SplitTarget;
 use cdk::cdk_database::{self, WalletDatabase};
+use cdk::keyset_history::KeysetHistory;
 use cdk::mint::{MintBuilder, MintMeltLimits};
 use cdk::nuts::nut00::ProofsMethods;
 use cdk::nuts::{
@@ -22,7 +26,7 @@ use cdk::nuts::{
 };
 use cdk::types::{FeeReserve, QuoteTTL};
 use cdk::util::unix_time;
-use cdk::wallet::{AuthWallet, MintConnector, Wallet, WalletBuilder};
+use cdk::wallet::{AuthWallet, MintConnector, MultiMintWallet, Wallet, WalletBuilder};
 use cdk::{Amount, Error, Mint, StreamExt};
 use cdk_fake_wallet::FakeWallet;
 use tokio::sync::RwLock;
@@ -71,6 +75,10 @@ impl MintConnector for DirectMintConnection {
         Ok(self.mint.keysets())
     }
 
+    async fn get_keyset_history(&self) -> Result<KeysetHistory, Error> {
+        self.mint.keyset_history()
+    }
+
     async fn post_mint_quote(
         &self,
         request: MintQuoteBolt11Request,
@@ -205,6 +213,34 @@ impl MintConnector for DirectMintConnection {
         // Implementation to be added later
         Err(Error::UnsupportedPaymentMethod)
     }
+
+    async fn post_melt_keysend_quote(
+        &self,
+        request: MeltQuoteKeysendRequest,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error> {
+        self.mint
+            .get_melt_quote(request.into())
+            .await
+            .map(Into::into)
+    }
+
+    async fn get_melt_keysend_quote_status(
+        &self,
+        quote_id: &str,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error> {
+        self.mint
+            .check_melt_quote(&QuoteId::from_str(quote_id)?)
+            .await
+            .map(Into::into)
+    }
+
+    async fn post_melt_keysend(
+        &self,
+        request: MeltRequest<String>,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error> {
+        let request_uuid = request.try_into().unwrap();
+        self.mint.melt(&request_uuid).await.map(Into::into)
+    }
 }
 
 pub fn setup_tracing() {
@@ -247,20 +283,29 @@ pub async fn create_and_start_test_mint() -> Result<Mint> {
         percent_fee_reserve: 1.0,
     };
 
-    let ln_fake_backend = FakeWallet::new(
+    let ln_fake_backend = Arc::new(FakeWallet::new(
         fee_reserve.clone(),
         HashMap::default(),
         HashSet::default(),
         2,
         CurrencyUnit::Sat,
-    );
+    ));
 
     mint_builder
         .add_payment_processor(
             CurrencyUnit::Sat,
             PaymentMethod::Bolt11,
             MintMeltLimits::new(1, 10_000),
-            Arc::new(ln_fake_backend),
+            ln_fake_backend.clone(),
+        )
+        .await?;
+
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::keysend(),
+            MintMeltLimits::new(1, 10_000),
+            ln_fake_backend,
         )
         .await?;
 
@@ -340,6 +385,31 @@ pub async fn create_test_wallet_for_mint(mint: Mint) -> Result<Wallet> {
     Ok(wallet)
 }
 
+/// Creates a multi mint wallet with a wallet for each of `mints`, reached at the given url
+pub async fn create_test_multi_mint_wallet(mints: &[(&str, Mint)]) -> Result<MultiMintWallet> {
+    let seed = Mnemonic::generate(12)?.to_seed_normalized("");
+    let localstore: Arc<dyn WalletDatabase<Err = cdk_database::Error> + Send + Sync> =
+        Arc::new(cdk_sqlite::wallet::memory::empty().await?);
+
+    let multi_mint_wallet =
+        MultiMintWallet::new(localstore.clone(), seed, CurrencyUnit::Sat).await?;
+
+    for (mint_url, mint) in mints {
+        let wallet = WalletBuilder::new()
+            .mint_url(mint_url.parse()?)
+            .unit(CurrencyUnit::Sat)
+            .localstore(localstore.clone())
+            .seed(seed)
+            .client(DirectMintConnection::new(mint.clone()))
+            .build()?;
+        wallet.refresh_keysets().await?;
+
+        multi_mint_wallet.add_wallet(wallet).await?;
+    }
+
+    Ok(multi_mint_wallet)
+}
+
 /// Creates a mint quote for the given amount and checks its state in a loop. Returns when
 /// amount is minted.
 /// Creates a temporary directory with a unique name based on the prefix
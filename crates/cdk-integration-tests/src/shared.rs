@@ -191,14 +191,19 @@ pub fn create_fake_wallet_settings(
             signatory_certs: signatory_config
                 .as_ref()
                 .map(|(_, certs_dir)| certs_dir.clone()),
+            identity_secret_key: None,
             input_fee_ppk: None,
             http_cache: cache::Config::default(),
             logging: cdk_mintd::config::LoggingConfig {
                 output: cdk_mintd::config::LoggingOutput::Both,
                 console_level: Some("debug".to_string()),
                 file_level: Some("debug".to_string()),
+                ..Default::default()
             },
             enable_swagger_ui: None,
+            request_recording_path: None,
+            path_prefix: None,
+            keyset_final_expiry: None,
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
         ln: cdk_mintd::config::Ln {
@@ -218,11 +223,18 @@ pub fn create_fake_wallet_settings(
         database: Database {
             engine: DatabaseEngine::from_str(database).expect("valid database"),
             postgres: None,
+            slow_query_threshold_ms: None,
         },
         auth_database: None,
         mint_management_rpc: None,
         auth: None,
         prometheus: Some(Default::default()),
+        chaos: None,
+        notifications: None,
+        risk: None,
+        webhooks: None,
+        vouchers: None,
+        stats: None,
     }
 }
 
@@ -244,14 +256,19 @@ pub fn create_cln_settings(
             mnemonic: Some(mnemonic),
             signatory_url: None,
             signatory_certs: None,
+            identity_secret_key: None,
             input_fee_ppk: None,
             http_cache: cache::Config::default(),
             logging: cdk_mintd::config::LoggingConfig {
                 output: cdk_mintd::config::LoggingOutput::Both,
                 console_level: Some("debug".to_string()),
                 file_level: Some("debug".to_string()),
+                ..Default::default()
             },
             enable_swagger_ui: None,
+            request_recording_path: None,
+            path_prefix: None,
+            keyset_final_expiry: None,
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
         ln: cdk_mintd::config::Ln {
@@ -273,6 +290,12 @@ pub fn create_cln_settings(
         mint_management_rpc: None,
         auth: None,
         prometheus: Some(Default::default()),
+        chaos: None,
+        notifications: None,
+        risk: None,
+        webhooks: None,
+        vouchers: None,
+        stats: None,
     }
 }
 
@@ -292,14 +315,19 @@ pub fn create_lnd_settings(
             mnemonic: Some(mnemonic),
             signatory_url: None,
             signatory_certs: None,
+            identity_secret_key: None,
             input_fee_ppk: None,
             http_cache: cache::Config::default(),
             logging: cdk_mintd::config::LoggingConfig {
                 output: cdk_mintd::config::LoggingOutput::Both,
                 console_level: Some("debug".to_string()),
                 file_level: Some("debug".to_string()),
+                ..Default::default()
             },
             enable_swagger_ui: None,
+            request_recording_path: None,
+            path_prefix: None,
+            keyset_final_expiry: None,
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
         ln: cdk_mintd::config::Ln {
@@ -321,5 +349,11 @@ pub fn create_lnd_settings(
         mint_management_rpc: None,
         auth: None,
         prometheus: Some(Default::default()),
+        chaos: None,
+        notifications: None,
+        risk: None,
+        webhooks: None,
+        vouchers: None,
+        stats: None,
     }
 }
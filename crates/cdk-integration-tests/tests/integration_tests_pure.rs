@@ -13,24 +13,40 @@ use std::assert_eq;
 use std::collections::{HashMap, HashSet};
 use std::hash::RandomState;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
+use bip39::Mnemonic;
 use cashu::amount::SplitTarget;
 use cashu::dhke::construct_proofs;
 use cashu::mint_url::MintUrl;
 use cashu::{
-    CurrencyUnit, Id, MeltRequest, NotificationPayload, PreMintSecrets, ProofState, SecretKey,
-    SpendingConditions, State, SwapRequest,
+    Conditions, CurrencyUnit, Id, MeltQuoteState, MeltRequest, NotificationPayload, PreMintSecrets,
+    ProofState, SecretKey, SpendingConditions, State, SwapRequest, Token,
 };
+use cdk::cdk_database::WalletDatabase;
 use cdk::mint::Mint;
 use cdk::nuts::nut00::ProofsMethods;
 use cdk::subscription::{IndexableParams, Params};
-use cdk::wallet::types::{TransactionDirection, TransactionId};
-use cdk::wallet::{ReceiveOptions, SendMemo, SendOptions};
+use cdk::util::unix_time;
+use cdk::wallet::types::{FeeKind, TransactionDirection, TransactionId, TransactionStatus};
+use cdk::wallet::{
+    verify_token_with_client, MeltProgressState, MultiMintWallet, PaymentStreamDestination,
+    PaymentStreamState, ReceiveOptions, RestoreOptions, SendMemo, SendOptions, SpendingLimits,
+    SwapLeg, SwapMessage, SwapState, TokenVerdict, Wallet, WalletBuilder, CLAIM_MARGIN,
+    MIN_SWAP_TIMEOUT,
+};
 use cdk::Amount;
-use cdk_fake_wallet::create_fake_invoice;
+use cdk_common::database::MintQuotesTransaction;
+use cdk_common::quote_id::QuoteId;
+use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription};
 use cdk_integration_tests::init_pure_tests::*;
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+/// Urls the atomic swap tests reach their two mints at
+const MINT_A: &str = "https://mint-a.test";
+const MINT_B: &str = "https://mint-b.test";
 
 /// Tests the token swap and send functionality:
 /// 1. Alice gets funded with 64 sats
@@ -446,11 +462,11 @@ pub async fn test_p2pk_swap() {
     let mut listener = mint_bob
         .pubsub_manager()
         .try_subscribe::<IndexableParams>(
-            Params {
-                kind: cdk::nuts::nut17::Kind::ProofState,
-                filters: public_keys_to_listen.clone(),
-                id: "test".into(),
-            }
+            Params::new(
+                cdk::nuts::nut17::Kind::ProofState,
+                public_keys_to_listen.clone(),
+                "test".into(),
+            )
             .into(),
         )
         .await
@@ -886,6 +902,1766 @@ async fn test_concurrent_double_spend_melt() {
     }
 }
 
+/// Tests that a token is verified with the mint without receiving it, and
+/// reported spent once received
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_verify_token_without_receiving() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+    let wallet_carol = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let token = wallet_alice
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .expect("Failed to prepare send")
+        .confirm(None)
+        .await
+        .expect("Failed to send token");
+
+    let client = Arc::new(DirectMintConnection::new(mint_bob.clone()));
+
+    let verification = verify_token_with_client(&token, client.clone())
+        .await
+        .expect("Failed to verify token");
+    assert_eq!(verification.verdict, TokenVerdict::Unspent);
+    assert!(verification.signatures_verified);
+    assert_eq!(verification.amount, Amount::from(10));
+
+    wallet_carol
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .expect("Failed to receive token");
+
+    let verification = verify_token_with_client(&token, client)
+        .await
+        .expect("Failed to verify token");
+    assert_eq!(verification.verdict, TokenVerdict::Spent);
+}
+
+/// Tests that tokens with a forged DLEQ proof are rejected and tokens missing
+/// DLEQ proofs are reported unverified
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_verify_token_with_invalid_or_missing_dleq() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let token = wallet_alice
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .expect("Failed to prepare send")
+        .confirm(None)
+        .await
+        .expect("Failed to send token");
+    let mint_url = token.mint_url().expect("Failed to get mint url");
+    let keysets = wallet_alice
+        .get_mint_keysets()
+        .await
+        .expect("Failed to get keysets");
+    let proofs = token.proofs(&keysets).expect("Failed to get proofs");
+    assert!(proofs.len() > 1);
+    assert!(proofs.iter().all(|proof| proof.dleq.is_some()));
+
+    let client = Arc::new(DirectMintConnection::new(mint_bob.clone()));
+
+    // A blinding factor not matching the signature
+    let mut forged = proofs.clone();
+    if let Some(dleq) = forged[0].dleq.as_mut() {
+        dleq.r = SecretKey::generate();
+    }
+    let forged = Token::new(mint_url.clone(), forged, None, CurrencyUnit::Sat);
+    let verification = verify_token_with_client(&forged, client.clone())
+        .await
+        .expect("Failed to verify token");
+    assert_eq!(verification.verdict, TokenVerdict::InvalidSignatures);
+    assert!(!verification.signatures_verified);
+
+    // One proof without a DLEQ proof
+    let mut stripped = proofs.clone();
+    stripped[0].dleq = None;
+    let stripped = Token::new(mint_url, stripped, None, CurrencyUnit::Sat);
+    let verification = verify_token_with_client(&stripped, client.clone())
+        .await
+        .expect("Failed to verify token");
+    assert_eq!(verification.verdict, TokenVerdict::Unverified);
+    assert!(!verification.signatures_verified);
+
+    // Spent proofs are spent whether they could be verified or not
+    let wallet_carol = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+    wallet_carol
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .expect("Failed to receive token");
+    let verification = verify_token_with_client(&stripped, client)
+        .await
+        .expect("Failed to verify token");
+    assert_eq!(verification.verdict, TokenVerdict::Spent);
+}
+
+/// Tests that a cancelled melt leaves the reserved proofs unspent
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_cancelled_melt_unreserves_proofs() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let fake_invoice = create_fake_invoice(10_000, "".to_string());
+    let melt_quote = wallet_alice
+        .melt_quote(fake_invoice.to_string(), None)
+        .await
+        .expect("Failed to get melt quote");
+
+    let proofs = wallet_alice
+        .get_unspent_proofs()
+        .await
+        .expect("Could not get proofs");
+
+    let cancel_token = CancellationToken::new();
+    cancel_token.cancel();
+
+    let result = wallet_alice
+        .melt_proofs_with_cancel(&melt_quote.id, proofs, cancel_token.clone())
+        .await;
+    assert!(matches!(result, Err(cdk::Error::Cancelled)));
+
+    let result = wallet_alice
+        .melt_with_cancel(&melt_quote.id, cancel_token)
+        .await;
+    assert!(matches!(result, Err(cdk::Error::Cancelled)));
+
+    assert!(wallet_alice
+        .get_pending_proofs()
+        .await
+        .expect("Could not get pending proofs")
+        .is_empty());
+    assert_eq!(
+        wallet_alice
+            .total_balance()
+            .await
+            .expect("Failed to get balance"),
+        Amount::from(100)
+    );
+
+    // The quote can still be paid once the melt is no longer cancelled
+    let melted = wallet_alice
+        .melt(&melt_quote.id)
+        .await
+        .expect("Failed to melt");
+    assert_eq!(melted.state, cashu::MeltQuoteState::Paid);
+}
+
+/// Tests that a cancelled receive does not swap the token and leaves nothing pending
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_cancelled_receive_keeps_token_unspent() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+    let wallet_carol = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let token = wallet_alice
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .expect("Failed to prepare send")
+        .confirm(None)
+        .await
+        .expect("Failed to send token");
+
+    let cancel_token = CancellationToken::new();
+    cancel_token.cancel();
+
+    let result = wallet_carol
+        .receive_with_cancel(&token.to_string(), ReceiveOptions::default(), cancel_token)
+        .await;
+    assert!(matches!(result, Err(cdk::Error::Cancelled)));
+
+    assert!(wallet_carol
+        .get_pending_proofs()
+        .await
+        .expect("Could not get pending proofs")
+        .is_empty());
+    assert!(wallet_carol
+        .get_archived_proofs()
+        .await
+        .expect("Could not get archived proofs")
+        .is_empty());
+    assert_eq!(
+        wallet_carol
+            .total_balance()
+            .await
+            .expect("Failed to get balance"),
+        Amount::ZERO
+    );
+
+    let client = Arc::new(DirectMintConnection::new(mint_bob.clone()));
+    let verification = verify_token_with_client(&token, client)
+        .await
+        .expect("Failed to verify token");
+    assert_eq!(verification.verdict, TokenVerdict::Unspent);
+
+    let received = wallet_carol
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .expect("Failed to receive token");
+    assert_eq!(received, Amount::from(10));
+}
+
+/// Tests that waiting for a payment stops as soon as it is cancelled
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_cancel_wait_for_payment() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    // The fake backend only pays the quote after two seconds
+    let quote = wallet_alice
+        .mint_quote(Amount::from(64), None)
+        .await
+        .expect("Failed to get mint quote");
+
+    let cancel_token = CancellationToken::new();
+    let canceller = cancel_token.clone();
+    tokio::spawn(async move {
+        sleep(Duration::from_millis(100)).await;
+        canceller.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let result = wallet_alice
+        .wait_for_payment_with_cancel(&quote, Duration::from_secs(30), cancel_token)
+        .await;
+
+    assert!(matches!(result, Err(cdk::Error::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+/// Tests that the wallet refuses bolt12 requests before contacting a mint that
+/// only supports bolt11
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_unsupported_capability_fails_early() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    let capabilities = wallet_alice
+        .capabilities()
+        .await
+        .expect("Failed to get capabilities");
+    assert!(capabilities.supports_bolt11_mint);
+    assert!(capabilities.supports_bolt11_melt);
+    assert!(!capabilities.supports_bolt12());
+
+    let result = wallet_alice
+        .mint_bolt12_quote(Some(Amount::from(100)), None)
+        .await;
+    assert!(matches!(result, Err(cdk::Error::MintDoesNotSupport(_))));
+
+    let result = wallet_alice
+        .melt_bolt12_quote("lno1qgsqvgnwgcg35z6ee2h3yczraddm72xrfua9uve2rlrm9deu7xyfzrc2q3skgumxzcssyeyreggqmet8r4k6krvd3knppsx6c8v5g7tj8hcuq8lleta9ve5n".to_string(), None)
+        .await;
+    assert!(matches!(result, Err(cdk::Error::MintDoesNotSupport(_))));
+}
+
+/// Tests that tracking a sent token confirms its transaction once it is received
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_track_sent_token_confirms_transaction() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+    let wallet_carol = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let token = wallet_alice
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .expect("Failed to prepare send")
+        .confirm(None)
+        .await
+        .expect("Failed to send token");
+
+    let sent = wallet_alice
+        .list_transactions(Some(TransactionDirection::Outgoing))
+        .await
+        .expect("Failed to list transactions");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].status, TransactionStatus::Pending);
+
+    let tracking = tokio::spawn({
+        let wallet_alice = wallet_alice.clone();
+        let token = token.clone();
+        async move {
+            wallet_alice
+                .track_sent_token(&token, Duration::from_secs(30))
+                .await
+        }
+    });
+
+    wallet_carol
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .expect("Failed to receive token");
+
+    let transaction = tracking
+        .await
+        .expect("Tracking task panicked")
+        .expect("Failed to track token")
+        .expect("Transaction not recorded");
+    assert_eq!(transaction.status, TransactionStatus::Confirmed);
+    assert_eq!(transaction.id(), sent[0].id());
+
+    assert!(wallet_alice
+        .get_pending_spent_proofs()
+        .await
+        .expect("Could not get pending proofs")
+        .is_empty());
+}
+
+/// Tests that tracking an unclaimed token times out and leaves its transaction pending
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_track_sent_token_times_out() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let token = wallet_alice
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .expect("Failed to prepare send")
+        .confirm(None)
+        .await
+        .expect("Failed to send token");
+
+    let result = wallet_alice
+        .track_sent_token(&token, Duration::from_millis(500))
+        .await;
+    assert!(matches!(result, Err(cdk::Error::Timeout)));
+
+    let sent = wallet_alice
+        .list_transactions(Some(TransactionDirection::Outgoing))
+        .await
+        .expect("Failed to list transactions");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].status, TransactionStatus::Pending);
+}
+
+/// Maker and taker of an atomic swap of 50 sats between two mints, after the taker accepted
+///
+/// Returns the maker, the taker, the hash of the swap and the taker's accept message.
+async fn accepted_atomic_swap() -> (MultiMintWallet, MultiMintWallet, String, SwapMessage) {
+    let mint_a = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let mint_b = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let mints = [(MINT_A, mint_a), (MINT_B, mint_b)];
+
+    let maker = create_test_multi_mint_wallet(&mints)
+        .await
+        .expect("Failed to create maker wallet");
+    let taker = create_test_multi_mint_wallet(&mints)
+        .await
+        .expect("Failed to create taker wallet");
+
+    let mint_a_url = MintUrl::from_str(MINT_A).unwrap();
+    let mint_b_url = MintUrl::from_str(MINT_B).unwrap();
+
+    fund_wallet(maker.get_wallet(&mint_a_url).await.unwrap(), 100, None)
+        .await
+        .expect("Failed to fund maker");
+    fund_wallet(taker.get_wallet(&mint_b_url).await.unwrap(), 100, None)
+        .await
+        .expect("Failed to fund taker");
+
+    let leg = |mint_url: &MintUrl| SwapLeg {
+        mint_url: mint_url.clone(),
+        amount: Amount::from(50),
+        unit: CurrencyUnit::Sat,
+    };
+
+    let offer = maker
+        .create_atomic_swap(leg(&mint_a_url), leg(&mint_b_url), MIN_SWAP_TIMEOUT, vec![])
+        .await
+        .expect("Failed to create offer");
+    let accept = taker
+        .accept_atomic_swap(offer.clone())
+        .await
+        .expect("Failed to accept offer");
+
+    (maker, taker, offer.hash, accept)
+}
+
+/// Shorten the locktimes of a stored swap, standing in for time passing
+async fn set_swap_locktimes(
+    wallet: &MultiMintWallet,
+    hash: &str,
+    maker_locktime: u64,
+    taker_locktime: u64,
+) {
+    let mut swap = wallet.atomic_swap(hash).await.unwrap().unwrap();
+    swap.offer.maker_locktime = maker_locktime;
+    swap.offer.taker_locktime = taker_locktime;
+
+    wallet.get_wallets().await[0]
+        .localstore
+        .kv_write(
+            "cdk_wallet",
+            "atomic_swap",
+            hash,
+            &serde_json::to_vec(&swap).unwrap(),
+        )
+        .await
+        .unwrap();
+}
+
+/// Balance of a multi mint wallet at one mint
+async fn mint_balance(wallet: &MultiMintWallet, mint_url: &str) -> Amount {
+    wallet
+        .get_wallet(&MintUrl::from_str(mint_url).unwrap())
+        .await
+        .unwrap()
+        .total_balance()
+        .await
+        .unwrap()
+}
+
+/// Tests that the maker refunds its lock once the locktime passes without the taker locking
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_atomic_swap_maker_refunds_after_timeout() {
+    setup_tracing();
+    let (maker, _taker, hash, accept) = accepted_atomic_swap().await;
+
+    let now = unix_time();
+    set_swap_locktimes(&maker, &hash, now + 3, now + 2).await;
+
+    let lock = maker
+        .handle_atomic_swap_message(accept)
+        .await
+        .expect("Failed to lock");
+    assert!(matches!(lock, Some(SwapMessage::Lock { .. })));
+    assert_eq!(mint_balance(&maker, MINT_A).await, Amount::from(50));
+
+    // The lock cannot be refunded before its locktime
+    assert!(maker.refund_atomic_swap(&hash).await.is_err());
+
+    sleep(Duration::from_secs(4)).await;
+
+    let swap = maker
+        .check_atomic_swap(&hash)
+        .await
+        .expect("Failed to check swap");
+    assert_eq!(swap.state, SwapState::Refunded);
+    assert_eq!(mint_balance(&maker, MINT_A).await, Amount::from(100));
+}
+
+/// Tests that the maker does not reveal the preimage close to the taker locktime
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_atomic_swap_maker_does_not_claim_near_taker_locktime() {
+    setup_tracing();
+    let (maker, taker, hash, accept) = accepted_atomic_swap().await;
+
+    let now = unix_time();
+    let taker_locktime = now + CLAIM_MARGIN / 2;
+    set_swap_locktimes(&maker, &hash, now + 600, taker_locktime).await;
+    set_swap_locktimes(&taker, &hash, now + 600, taker_locktime).await;
+
+    let maker_lock = maker
+        .handle_atomic_swap_message(accept)
+        .await
+        .expect("Failed to lock")
+        .expect("Maker lock");
+    let taker_lock = taker
+        .handle_atomic_swap_message(maker_lock)
+        .await
+        .expect("Failed to lock")
+        .expect("Taker lock");
+
+    assert!(maker.handle_atomic_swap_message(taker_lock).await.is_err());
+
+    let swap = maker.atomic_swap(&hash).await.unwrap().unwrap();
+    assert_eq!(swap.state, SwapState::Locked);
+    assert_eq!(mint_balance(&maker, MINT_B).await, Amount::ZERO);
+
+    // The taker's lock was not claimed, so no preimage is revealed at the mint
+    let swap = taker
+        .check_atomic_swap(&hash)
+        .await
+        .expect("Failed to check swap");
+    assert_eq!(swap.state, SwapState::Locked);
+    assert_eq!(swap.preimage, None);
+}
+
+/// Tests that the taker refunds its lock once its locktime passes without the maker claiming
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_atomic_swap_taker_refunds_after_timeout() {
+    setup_tracing();
+    let (maker, taker, hash, accept) = accepted_atomic_swap().await;
+
+    let now = unix_time();
+    set_swap_locktimes(&maker, &hash, now + 6, now + 3).await;
+    set_swap_locktimes(&taker, &hash, now + 6, now + 3).await;
+
+    let maker_lock = maker
+        .handle_atomic_swap_message(accept)
+        .await
+        .expect("Failed to lock")
+        .expect("Maker lock");
+    let taker_lock = taker
+        .handle_atomic_swap_message(maker_lock)
+        .await
+        .expect("Failed to lock")
+        .expect("Taker lock");
+    assert_eq!(mint_balance(&taker, MINT_B).await, Amount::from(50));
+
+    sleep(Duration::from_secs(4)).await;
+
+    let swap = taker
+        .check_atomic_swap(&hash)
+        .await
+        .expect("Failed to check swap");
+    assert_eq!(swap.state, SwapState::Refunded);
+    assert_eq!(mint_balance(&taker, MINT_B).await, Amount::from(100));
+
+    // A late lock message no longer makes the maker claim
+    assert!(maker.handle_atomic_swap_message(taker_lock).await.is_err());
+}
+
+/// Payment streams pay keysend destinations through the mint's keysend melts
+#[tokio::test]
+async fn test_payment_stream_keysend() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let node_id = SecretKey::generate().public_key();
+    let stream = wallet_alice
+        .create_payment_stream(
+            PaymentStreamDestination::Keysend(node_id),
+            Amount::from(10),
+            3600,
+            Amount::from(50),
+        )
+        .await
+        .expect("Failed to create payment stream");
+
+    let stream = wallet_alice
+        .pay_payment_stream(&stream.id)
+        .await
+        .expect("Failed to pay payment stream");
+
+    assert_eq!(stream.payments, 1);
+    assert!(stream.spent >= Amount::from(10));
+    assert_eq!(stream.last_error, None);
+
+    // The next interval is not due yet
+    let stream = wallet_alice
+        .pay_payment_stream(&stream.id)
+        .await
+        .expect("Failed to check payment stream");
+    assert_eq!(stream.payments, 1);
+
+    let melt_quotes = wallet_alice.localstore.get_melt_quotes().await.unwrap();
+    assert!(melt_quotes.iter().any(|quote| quote.payment_method
+        == cdk::nuts::PaymentMethod::keysend()
+        && quote.request == node_id.to_string()));
+}
+
+/// A payment stream is paid by a single runner and stays stopped once stopped
+#[tokio::test]
+async fn test_payment_stream_single_runner() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint_bob.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let stream = wallet_alice
+        .create_payment_stream(
+            PaymentStreamDestination::Keysend(SecretKey::generate().public_key()),
+            Amount::from(10),
+            3600,
+            Amount::from(50),
+        )
+        .await
+        .expect("Failed to create payment stream");
+
+    let cancel_token = CancellationToken::new();
+    let runner = tokio::spawn({
+        let wallet = wallet_alice.clone();
+        let id = stream.id.clone();
+        let cancel_token = cancel_token.clone();
+        async move { wallet.run_payment_stream(&id, cancel_token).await }
+    });
+
+    // Wait for the runner to make the first payment
+    let mut payments = 0;
+    for _ in 0..50 {
+        payments = wallet_alice
+            .get_payment_stream(&stream.id)
+            .await
+            .unwrap()
+            .expect("Stream is stored")
+            .payments;
+        if payments == 1 {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(payments, 1);
+
+    // A second runner is refused while the first one is running
+    assert!(wallet_alice.pay_payment_stream(&stream.id).await.is_err());
+    assert!(wallet_alice
+        .run_payment_stream(&stream.id, CancellationToken::new())
+        .await
+        .is_err());
+
+    let stopped = wallet_alice.stop_payment_stream(&stream.id).await.unwrap();
+    assert_eq!(stopped.state, PaymentStreamState::Stopped);
+
+    cancel_token.cancel();
+    runner.await.unwrap().expect("Runner failed");
+
+    // The runner is released and the stop is kept
+    let stream = wallet_alice.pay_payment_stream(&stream.id).await.unwrap();
+    assert_eq!(stream.state, PaymentStreamState::Stopped);
+    assert_eq!(stream.payments, 1);
+}
+
+/// Wallet of `mint` deriving its secrets from `seed`, with an empty database
+async fn create_seeded_wallet(mint: &Mint, seed: [u8; 64]) -> Wallet {
+    let mint_url = mint
+        .mint_info()
+        .await
+        .expect("mint info")
+        .urls
+        .and_then(|urls| urls.first().cloned())
+        .expect("mint url");
+
+    WalletBuilder::new()
+        .mint_url(MintUrl::from_str(&mint_url).expect("valid mint url"))
+        .unit(CurrencyUnit::Sat)
+        .localstore(Arc::new(
+            cdk_sqlite::wallet::memory::empty()
+                .await
+                .expect("wallet db"),
+        ))
+        .seed(seed)
+        .client(DirectMintConnection::new(mint.clone()))
+        .build()
+        .expect("wallet")
+}
+
+/// Restore stops after `gap_limit` batches without signatures, so secrets beyond a
+/// larger gap are only found with a higher gap limit
+#[tokio::test]
+async fn test_restore_gap_limit() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let keyset_id = get_keyset_id(&mint).await;
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+
+    let wallet = create_seeded_wallet(&mint, seed).await;
+    fund_wallet(wallet.clone(), 64, None).await.unwrap();
+
+    // Leave two empty batches of 100 counters before the next secrets
+    wallet
+        .localstore
+        .increment_keyset_counter(&keyset_id, 250)
+        .await
+        .unwrap();
+    fund_wallet(wallet.clone(), 32, None).await.unwrap();
+
+    let options = RestoreOptions {
+        batch_size: 100,
+        gap_limit: 1,
+        ..Default::default()
+    };
+
+    let restored = create_seeded_wallet(&mint, seed)
+        .await
+        .restore_with_options(options)
+        .await
+        .unwrap();
+    assert_eq!(restored, Amount::from(64));
+
+    let restored_wallet = create_seeded_wallet(&mint, seed).await;
+    let restored = restored_wallet
+        .restore_with_options(RestoreOptions {
+            gap_limit: 3,
+            ..options
+        })
+        .await
+        .unwrap();
+    assert_eq!(restored, Amount::from(96));
+
+    // The counter is moved past every restored secret
+    let report = restored_wallet.derivation_report().await.unwrap();
+    let derivation = report
+        .keysets
+        .iter()
+        .find(|keyset| keyset.keyset_id == keyset_id)
+        .expect("keyset derivation");
+    assert!(derivation.next_counter > 250);
+    assert!(!derivation.counter_behind_restore());
+}
+
+/// Incremental restores start at the highest counter found by the previous restore
+#[tokio::test]
+async fn test_restore_incremental() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let keyset_id = get_keyset_id(&mint).await;
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+
+    let wallet = create_seeded_wallet(&mint, seed).await;
+    fund_wallet(wallet.clone(), 64, None).await.unwrap();
+
+    let restored_wallet = create_seeded_wallet(&mint, seed).await;
+    assert_eq!(restored_wallet.restore().await.unwrap(), Amount::from(64));
+
+    let first_scan = restored_wallet
+        .derivation_report()
+        .await
+        .unwrap()
+        .keysets
+        .into_iter()
+        .find(|keyset| keyset.keyset_id == keyset_id)
+        .expect("keyset derivation");
+    assert_eq!(first_scan.restore_high_water, 1);
+
+    fund_wallet(wallet.clone(), 16, None).await.unwrap();
+
+    // Only the secrets derived since the last restore are asked to the mint
+    let restored = restored_wallet
+        .restore_with_options(RestoreOptions {
+            incremental: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(restored, Amount::from(16));
+    assert_eq!(
+        restored_wallet.total_balance().await.unwrap(),
+        Amount::from(80)
+    );
+
+    let second_scan = restored_wallet
+        .derivation_report()
+        .await
+        .unwrap()
+        .keysets
+        .into_iter()
+        .find(|keyset| keyset.keyset_id == keyset_id)
+        .and_then(|keyset| keyset.restore)
+        .expect("restore scan");
+    assert_eq!(second_scan.started_at, 1);
+    assert_eq!(second_scan.used_until, 2);
+}
+
+/// Restore caches the keyset history of the mint and fetches it again once the
+/// mint rotated to a keyset the cached history does not list
+#[tokio::test]
+async fn test_restore_caches_keyset_history() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let first_keyset_id = get_keyset_id(&mint).await;
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+
+    let wallet = create_seeded_wallet(&mint, seed).await;
+    fund_wallet(wallet.clone(), 64, None).await.unwrap();
+    let second_keyset = mint.rotate_keyset(CurrencyUnit::Sat, 32, 0).await.unwrap();
+    wallet.refresh_keysets().await.unwrap();
+    fund_wallet(wallet.clone(), 32, None).await.unwrap();
+
+    let restored_wallet = create_seeded_wallet(&mint, seed).await;
+    assert!(restored_wallet
+        .cached_keyset_history()
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(restored_wallet.restore().await.unwrap(), Amount::from(96));
+
+    let history = restored_wallet
+        .cached_keyset_history()
+        .await
+        .unwrap()
+        .expect("cached keyset history");
+    for keyset_id in [first_keyset_id, second_keyset.id] {
+        assert!(history.keysets.iter().any(|entry| entry.id == keyset_id));
+    }
+
+    let third_keyset = mint.rotate_keyset(CurrencyUnit::Sat, 32, 0).await.unwrap();
+    restored_wallet.refresh_keysets().await.unwrap();
+    restored_wallet.restore().await.unwrap();
+
+    let history = restored_wallet
+        .cached_keyset_history()
+        .await
+        .unwrap()
+        .expect("cached keyset history");
+    assert!(history
+        .keysets
+        .iter()
+        .any(|entry| entry.id == third_keyset.id));
+    assert_eq!(
+        restored_wallet.total_balance().await.unwrap(),
+        Amount::from(96)
+    );
+}
+
+/// Proofs of a keyset with a final expiry are moved to the active keyset, once the
+/// active keyset does not expire as well
+#[tokio::test]
+async fn test_migrate_expiring_proofs() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let first_keyset_id = get_keyset_id(&mint).await;
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+    fund_wallet(wallet.clone(), 64, None).await.unwrap();
+
+    let expiring_keyset = mint
+        .rotate_keyset_with_expiry(CurrencyUnit::Sat, 32, 0, Some(unix_time() + 60))
+        .await
+        .unwrap();
+    wallet.refresh_keysets().await.unwrap();
+    fund_wallet(wallet.clone(), 32, None).await.unwrap();
+
+    let within = Duration::from_secs(60 * 60);
+    let expiring: Vec<Id> = wallet
+        .expiring_keysets(within)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|keyset| keyset.id)
+        .collect();
+    assert_eq!(expiring, vec![expiring_keyset.id]);
+    assert!(wallet
+        .expiring_keysets(Duration::from_secs(10))
+        .await
+        .unwrap()
+        .is_empty());
+
+    // The active keyset expires as well, so there is nowhere to migrate to
+    assert_eq!(
+        wallet.migrate_expiring_proofs(within).await.unwrap(),
+        Amount::ZERO
+    );
+
+    mint.rotate_keyset(CurrencyUnit::Sat, 32, 0).await.unwrap();
+    wallet.refresh_keysets().await.unwrap();
+
+    assert_eq!(
+        wallet.migrate_expiring_proofs(within).await.unwrap(),
+        Amount::from(32)
+    );
+    assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(96));
+
+    let proofs = wallet.get_unspent_proofs().await.unwrap();
+    assert!(proofs
+        .iter()
+        .all(|proof| proof.keyset_id != expiring_keyset.id));
+    // Proofs of keysets without an expiry are left alone
+    assert_eq!(
+        proofs
+            .into_iter()
+            .filter(|proof| proof.keyset_id == first_keyset_id)
+            .collect::<Vec<_>>()
+            .total_amount()
+            .unwrap(),
+        Amount::from(64)
+    );
+}
+
+/// Wallets built with a keyset expiry migration move expiring proofs before sending
+#[tokio::test]
+async fn test_auto_migrate_expiring_proofs_on_send() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let mint_url = mint
+        .mint_info()
+        .await
+        .expect("mint info")
+        .urls
+        .and_then(|urls| urls.first().cloned())
+        .expect("mint url");
+
+    let expiring_keyset = mint
+        .rotate_keyset_with_expiry(CurrencyUnit::Sat, 32, 0, Some(unix_time() + 60))
+        .await
+        .unwrap();
+
+    let wallet = WalletBuilder::new()
+        .mint_url(MintUrl::from_str(&mint_url).expect("valid mint url"))
+        .unit(CurrencyUnit::Sat)
+        .localstore(Arc::new(
+            cdk_sqlite::wallet::memory::empty()
+                .await
+                .expect("wallet db"),
+        ))
+        .seed(Mnemonic::generate(12).unwrap().to_seed_normalized(""))
+        .client(DirectMintConnection::new(mint.clone()))
+        .keyset_expiry_migration(Duration::from_secs(60 * 60))
+        .build()
+        .expect("wallet");
+    fund_wallet(wallet.clone(), 64, None).await.unwrap();
+    assert!(wallet
+        .get_unspent_proofs()
+        .await
+        .unwrap()
+        .iter()
+        .all(|proof| proof.keyset_id == expiring_keyset.id));
+
+    mint.rotate_keyset(CurrencyUnit::Sat, 32, 0).await.unwrap();
+
+    let prepared_send = wallet
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .unwrap();
+    assert!(prepared_send
+        .proofs()
+        .iter()
+        .all(|proof| proof.keyset_id != expiring_keyset.id));
+
+    let proofs = wallet
+        .localstore
+        .get_proofs(None, None, None, None)
+        .await
+        .unwrap();
+    assert!(proofs
+        .iter()
+        .filter(|proof_info| proof_info.state != State::Spent)
+        .all(|proof_info| proof_info.proof.keyset_id != expiring_keyset.id));
+}
+
+/// Imported proofs with a valid DLEQ proof are stored as they are, proofs without one
+/// are swapped and proofs with an invalid one are skipped
+#[tokio::test]
+async fn test_import_proofs_verifies_signatures() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let source = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+    fund_wallet(source.clone(), 7, None).await.unwrap();
+
+    let mut proofs = source.get_unspent_proofs().await.unwrap();
+    proofs.sort_by_key(|proof| proof.amount);
+    assert_eq!(proofs.len(), 3);
+    assert!(proofs.iter().all(|proof| proof.dleq.is_some()));
+
+    // 1 sat keeps its DLEQ proof, 2 sat loses it and 4 sat gets an invalid one
+    proofs[1].dleq = None;
+    if let Some(dleq) = proofs[2].dleq.as_mut() {
+        dleq.r = SecretKey::generate();
+    }
+
+    let import = wallet.import_proofs(proofs.clone()).await.unwrap();
+    assert_eq!(import.imported, 2);
+    assert_eq!(import.swapped, 1);
+    assert_eq!(import.invalid, 1);
+    assert_eq!(import.amount, Amount::from(3));
+    assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(3));
+
+    let held = wallet.get_unspent_proofs().await.unwrap();
+    assert!(held.contains(&proofs[0]));
+    assert!(!held.iter().any(|proof| proof.secret == proofs[1].secret));
+
+    // The swapped proof is spent, the verified one is left spendable for the source
+    let states = source
+        .check_proofs_spent(proofs[..2].to_vec())
+        .await
+        .unwrap();
+    assert_eq!(states[0].state, State::Unspent);
+    assert_eq!(states[1].state, State::Spent);
+
+    // Importing again only finds duplicates
+    let import = wallet.import_proofs(proofs[..1].to_vec()).await.unwrap();
+    assert_eq!(import.imported, 0);
+    assert_eq!(import.duplicates, 1);
+}
+
+/// Tests that the mint ledger records issuance, swaps and melts with the database changes
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_ledger_matches_database() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let token = wallet
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .expect("Failed to prepare send")
+        .confirm(None)
+        .await
+        .expect("Failed to send token");
+    wallet
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .expect("Failed to receive token");
+
+    let melt_quote = wallet
+        .melt_quote(
+            create_fake_invoice(50_000, "".to_string()).to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    wallet.melt(&melt_quote.id).await.unwrap();
+
+    let balances = mint.ledger_balances().await.unwrap();
+    let sat = balances
+        .iter()
+        .find(|balance| balance.unit == CurrencyUnit::Sat)
+        .expect("Ledger has no sat transactions");
+
+    assert!(sat.is_consistent());
+    assert_eq!(sat.outstanding, Some(wallet.total_balance().await.unwrap()));
+    assert!(balances.iter().all(|balance| balance.is_consistent()));
+
+    // Transactions after the ledger was folded into its checkpoint add to it
+    fund_wallet(wallet.clone(), 20, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let balances = mint.ledger_balances().await.unwrap();
+    let sat = balances
+        .iter()
+        .find(|balance| balance.unit == CurrencyUnit::Sat)
+        .expect("Ledger has no sat transactions");
+
+    assert!(sat.is_consistent());
+    assert_eq!(sat.outstanding, Some(wallet.total_balance().await.unwrap()));
+    assert_eq!(mint.ledger_balances().await.unwrap(), balances);
+}
+
+/// Tests that a prepared spend is finalized from the signatures alone and its secrets stay in the wallet
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_prepared_spend() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    // Inputs are reserved until the spend is cancelled
+    let spend = wallet.prepare_spend(Amount::from(10), None).await.unwrap();
+    assert!(wallet.total_balance().await.unwrap() < Amount::from(64));
+    wallet.cancel_spend(&spend.id).await.unwrap();
+    assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(64));
+    assert!(wallet.finalize_spend(&spend.id, vec![]).await.is_err());
+
+    let spend = wallet.prepare_spend(Amount::from(10), None).await.unwrap();
+
+    // The signer sees the blinded outputs but none of their secrets
+    let json = serde_json::to_value(&spend).unwrap();
+    assert!(json.get("pre_mint_secrets").is_none());
+    assert!(spend.signing_requests().unwrap().is_empty());
+
+    let proofs = wallet.finalize_spend(&spend.id, vec![]).await.unwrap();
+    assert_eq!(proofs.total_amount().unwrap(), Amount::from(10));
+    assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(54));
+}
+
+/// Tests that concurrent spends cannot both pass the daily spending limit and that
+/// spends that do not go through are not counted against it
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_spending_limits_count_concurrent_spends() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    wallet
+        .set_spending_limits(SpendingLimits {
+            per_transaction: None,
+            daily: Some(Amount::from(50)),
+        })
+        .await
+        .unwrap();
+
+    // Without a confirmation handler the send over the daily limit is refused
+    let (first, second) = tokio::join!(
+        wallet.prepare_send(Amount::from(30), SendOptions::default()),
+        wallet.prepare_send(Amount::from(30), SendOptions::default()),
+    );
+    let (prepared, refused) = match (first, second) {
+        (Ok(prepared), Err(refused)) | (Err(refused), Ok(prepared)) => (prepared, refused),
+        _ => panic!("Exactly one send should pass the daily limit"),
+    };
+    assert!(matches!(refused, cdk::Error::SpendingLimitExceeded(..)));
+    assert_eq!(wallet.spent_today().await.unwrap(), Amount::from(30));
+
+    prepared.cancel().await.unwrap();
+    assert_eq!(wallet.spent_today().await.unwrap(), Amount::ZERO);
+
+    // Prepared spends count until they are cancelled
+    let spend = wallet.prepare_spend(Amount::from(40), None).await.unwrap();
+    assert!(wallet
+        .prepare_send(Amount::from(20), SendOptions::default())
+        .await
+        .is_err());
+    wallet.cancel_spend(&spend.id).await.unwrap();
+
+    wallet
+        .prepare_send(Amount::from(20), SendOptions::default())
+        .await
+        .unwrap()
+        .confirm(None)
+        .await
+        .unwrap();
+    assert_eq!(wallet.spent_today().await.unwrap(), Amount::from(20));
+}
+
+/// Tests that tokens locked to receive keys of a wallet are received without
+/// passing it signing keys, and that no key is handed out twice
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_receive_to_receive_keys() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+    let wallet_bob = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let (first, second) = tokio::join!(
+        wallet_bob.next_receive_pubkey(),
+        wallet_bob.next_receive_pubkey()
+    );
+    let (first, second) = (first.unwrap(), second.unwrap());
+    assert_ne!(first, second);
+
+    for pubkey in [first, second] {
+        let token = wallet_alice
+            .prepare_send(
+                Amount::from(10),
+                SendOptions {
+                    conditions: Some(SpendingConditions::new_p2pk(pubkey, None)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .confirm(None)
+            .await
+            .unwrap();
+
+        let received = wallet_bob
+            .receive(&token.to_string(), ReceiveOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(received, Amount::from(10));
+    }
+
+    // A token locked to a key of another wallet is not signed
+    let token = wallet_alice
+        .prepare_send(
+            Amount::from(10),
+            SendOptions {
+                conditions: Some(SpendingConditions::new_p2pk(
+                    wallet_alice.next_receive_pubkey().await.unwrap(),
+                    None,
+                )),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .confirm(None)
+        .await
+        .unwrap();
+    assert!(wallet_bob
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .is_err());
+}
+
+/// Tests that savings are locked out of the balance and unlocked before a send after the locktime
+#[tokio::test]
+async fn test_savings_lock_and_unlock() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let savings = wallet
+        .lock_savings(Amount::from(30), unix_time() + 2)
+        .await
+        .unwrap();
+    assert_eq!(savings.amount, Amount::from(30));
+    assert!(!savings.is_mature());
+
+    assert_eq!(
+        wallet.locked_savings_balance().await.unwrap(),
+        Amount::from(30)
+    );
+    assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(70));
+
+    // Not unlocked before the locktime
+    assert_eq!(wallet.unlock_savings().await.unwrap(), Amount::ZERO);
+    assert!(wallet
+        .prepare_send(Amount::from(90), SendOptions::default())
+        .await
+        .is_err());
+
+    sleep(Duration::from_secs(3)).await;
+
+    // The send unlocks the matured savings before selecting proofs
+    let prepared = wallet
+        .prepare_send(Amount::from(90), SendOptions::default())
+        .await
+        .unwrap();
+    prepared.cancel().await.unwrap();
+
+    assert!(wallet.locked_savings().await.unwrap().is_empty());
+    assert_eq!(wallet.locked_savings_balance().await.unwrap(), Amount::ZERO);
+    assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(100));
+}
+
+/// Tests that a token with a receive key as refund key is received after the locktime
+#[tokio::test]
+async fn test_receive_with_refund_key_after_locktime() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+    let wallet_bob = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet_alice.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let conditions = Conditions::new(
+        Some(unix_time() + 2),
+        None,
+        Some(vec![wallet_bob.next_receive_pubkey().await.unwrap()]),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let token = wallet_alice
+        .prepare_send(
+            Amount::from(10),
+            SendOptions {
+                conditions: Some(SpendingConditions::new_p2pk(
+                    SecretKey::generate().public_key(),
+                    Some(conditions),
+                )),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .confirm(None)
+        .await
+        .unwrap();
+
+    // Before the locktime only the lock key can spend
+    assert!(wallet_bob
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .is_err());
+
+    sleep(Duration::from_secs(3)).await;
+
+    let received = wallet_bob
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(received, Amount::from(10));
+    assert_eq!(wallet_bob.total_balance().await.unwrap(), Amount::from(10));
+}
+
+/// Tests that history is rebuilt from the melt quotes of the wallet's own mint only
+#[tokio::test]
+async fn test_rebuild_history_skips_melt_quotes_of_other_mints() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let quote = wallet
+        .melt_quote(
+            create_fake_invoice(10_000, "".to_string()).to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    wallet.melt(&quote.id).await.unwrap();
+
+    let stored = wallet
+        .localstore
+        .get_melt_quote(&quote.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.mint_url, Some(wallet.mint_url.clone()));
+
+    // A wallet of another mint sharing the database, its connection would report the quote paid
+    let other_wallet = WalletBuilder::new()
+        .mint_url(MintUrl::from_str("https://bbb").unwrap())
+        .unit(CurrencyUnit::Sat)
+        .localstore(wallet.localstore.clone())
+        .seed(Mnemonic::generate(12).unwrap().to_seed_normalized(""))
+        .client(DirectMintConnection::new(mint.clone()))
+        .build()
+        .unwrap();
+    let rebuilt = other_wallet.rebuild_history().await.unwrap();
+    assert!(rebuilt
+        .iter()
+        .all(|tx| tx.quote_id.as_deref() != Some(quote.id.as_str())));
+
+    // The wallet of the mint rebuilds the melt once its record is lost
+    for tx in wallet
+        .list_transactions(Some(TransactionDirection::Outgoing))
+        .await
+        .unwrap()
+    {
+        wallet.localstore.remove_transaction(tx.id()).await.unwrap();
+    }
+    let rebuilt = wallet.rebuild_history().await.unwrap();
+    assert!(rebuilt
+        .iter()
+        .any(|tx| tx.quote_id.as_deref() == Some(quote.id.as_str())));
+}
+
+/// Tests that a batch melt pays the invoices it could quote and returns the
+/// proofs of the ones that failed to the balance
+#[tokio::test]
+async fn test_melt_batch() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let failing_invoice = create_fake_invoice(
+        10_000,
+        serde_json::to_string(&FakeInvoiceDescription {
+            pay_invoice_state: MeltQuoteState::Failed,
+            check_payment_state: MeltQuoteState::Failed,
+            pay_err: false,
+            check_err: false,
+        })
+        .unwrap(),
+    );
+    let requests = vec![
+        create_fake_invoice(10_000, "".to_string()).to_string(),
+        "not an invoice".to_string(),
+        failing_invoice.to_string(),
+        create_fake_invoice(10_000, "".to_string()).to_string(),
+    ];
+
+    let melts = wallet.melt_batch(requests.clone()).await.unwrap();
+    assert_eq!(melts.len(), requests.len());
+    for (melt, request) in melts.iter().zip(&requests) {
+        assert_eq!(&melt.request, request);
+    }
+
+    for paid in [&melts[0], &melts[3]] {
+        assert!(paid.quote_id.is_some());
+        assert_eq!(paid.result.as_ref().unwrap().state, MeltQuoteState::Paid);
+    }
+
+    // The invoice that could not be quoted had no proofs reserved
+    assert!(melts[1].quote_id.is_none());
+    assert!(melts[1].result.is_err());
+
+    assert!(melts[2].quote_id.is_some());
+    assert!(melts[2].result.is_err());
+
+    // Each paid invoice costs its amount and the 1 sat fee of the fake backend
+    assert!(wallet.get_reserved_proofs().await.unwrap().is_empty());
+    assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(78));
+}
+
+/// Tests that the lightning fee of a melt is recorded in the fee ledger
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_melt_records_fees() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+    assert_eq!(wallet.fees_paid(..).await.unwrap(), Amount::ZERO);
+
+    let invoice = create_fake_invoice(10_000, "".to_string());
+    let quote = wallet
+        .melt_quote(invoice.to_string(), None)
+        .await
+        .expect("Failed to get melt quote");
+    let melted = wallet.melt(&quote.id).await.expect("Failed to melt");
+    assert_eq!(melted.state, MeltQuoteState::Paid);
+    assert!(melted.fee_breakdown.lightning_fee > Amount::ZERO);
+
+    let entries = wallet.fee_entries(..).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, FeeKind::Lightning);
+    assert_eq!(entries[0].amount, melted.fee_breakdown.lightning_fee);
+    assert_eq!(entries[0].quote_id.as_deref(), Some(quote.id.as_str()));
+    assert_eq!(entries[0].mint_url, wallet.mint_url);
+
+    assert_eq!(wallet.fees_paid(..).await.unwrap(), melted.fee_paid);
+    assert_eq!(
+        wallet.fees_paid(..entries[0].timestamp).await.unwrap(),
+        Amount::ZERO
+    );
+}
+
+/// Tests that checking the quote of a melt left in flight records whether its
+/// payment failed or settled
+#[tokio::test]
+async fn test_melt_quote_status_records_progress() {
+    setup_tracing();
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create test wallet");
+
+    fund_wallet(wallet.clone(), 100, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let mut progress = wallet.subscribe_melt_progress();
+
+    for final_state in [MeltQuoteState::Unpaid, MeltQuoteState::Paid] {
+        let invoice = create_fake_invoice(
+            10_000,
+            serde_json::to_string(&FakeInvoiceDescription {
+                pay_invoice_state: MeltQuoteState::Pending,
+                check_payment_state: MeltQuoteState::Pending,
+                pay_err: false,
+                check_err: false,
+            })
+            .unwrap(),
+        );
+        let quote = wallet
+            .melt_quote(invoice.to_string(), None)
+            .await
+            .expect("Failed to get melt quote");
+
+        assert!(wallet.melt(&quote.id).await.is_err());
+        assert_eq!(
+            wallet
+                .melt_progress(&quote.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .state,
+            MeltProgressState::PaymentInFlight
+        );
+
+        let mut tx = mint.localstore().begin_transaction().await.unwrap();
+        tx.update_melt_quote_state(&QuoteId::from_str(&quote.id).unwrap(), final_state, None)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let response = wallet.melt_quote_status(&quote.id).await.unwrap();
+        assert_eq!(response.state, final_state);
+
+        let expected = match final_state {
+            MeltQuoteState::Paid => MeltProgressState::Settled,
+            _ => MeltProgressState::Failed,
+        };
+        assert_eq!(
+            wallet
+                .melt_progress(&quote.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .state,
+            expected
+        );
+
+        let mut states = Vec::new();
+        while let Ok(update) = progress.try_recv() {
+            assert_eq!(update.quote_id, quote.id);
+            states.push(update.state);
+        }
+        assert_eq!(
+            states,
+            vec![
+                MeltProgressState::QuoteCreated,
+                MeltProgressState::ProofsReserved,
+                MeltProgressState::PaymentInFlight,
+                expected,
+            ]
+        );
+    }
+}
+
+/// Serve a mint over HTTP on `addr`, nested under `path_prefix` as cdk-mintd
+/// does, and return the url wallets reach it at
+async fn serve_mint(mint: Mint, addr: &str, path_prefix: Option<&str>) -> MintUrl {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .expect("Failed to bind listener");
+    let local_addr = listener.local_addr().expect("Failed to get local address");
+
+    let router = cdk_axum::create_mint_router(Arc::new(mint), false)
+        .await
+        .expect("Failed to create router");
+    let router = match path_prefix {
+        Some(path_prefix) => axum::Router::new().nest(path_prefix, router),
+        None => router,
+    };
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    MintUrl::from_str(&format!(
+        "http://{local_addr}{}/",
+        path_prefix.unwrap_or_default()
+    ))
+    .expect("Invalid mint url")
+}
+
+/// Wallet reaching its mint over HTTP
+async fn create_http_wallet(mint_url: &MintUrl) -> Wallet {
+    Wallet::new(
+        &mint_url.to_string(),
+        CurrencyUnit::Sat,
+        Arc::new(
+            cdk_sqlite::wallet::memory::empty()
+                .await
+                .expect("Failed to create wallet database"),
+        ),
+        Mnemonic::generate(12).unwrap().to_seed_normalized(""),
+        None,
+    )
+    .expect("Failed to create wallet")
+}
+
+/// Fund a wallet reaching its mint over HTTP, send a token to a second wallet
+/// of the same mint and check the websocket of the mint is reachable
+async fn exercise_http_mint(mint_url: MintUrl) {
+    let wallet_alice = create_http_wallet(&mint_url).await;
+    let wallet_carol = create_http_wallet(&mint_url).await;
+
+    wallet_alice
+        .fetch_mint_info()
+        .await
+        .expect("Failed to get mint info")
+        .expect("Mint info missing");
+    assert!(!wallet_alice
+        .load_mint_keysets()
+        .await
+        .expect("Failed to get keysets")
+        .is_empty());
+
+    fund_wallet(wallet_alice.clone(), 64, None)
+        .await
+        .expect("Failed to fund wallet");
+
+    let token = wallet_alice
+        .prepare_send(Amount::from(10), SendOptions::default())
+        .await
+        .expect("Failed to prepare send")
+        .confirm(None)
+        .await
+        .expect("Failed to send token");
+    assert_eq!(token.mint_url().expect("Token mint url"), mint_url);
+
+    let received = wallet_carol
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .expect("Failed to receive token");
+    assert_eq!(received, Amount::from(10));
+
+    let mut ws_url = mint_url
+        .join_paths(&["v1", "ws"])
+        .expect("Failed to join ws path");
+    ws_url.set_scheme("ws").expect("Failed to set scheme");
+    tokio_tungstenite::connect_async(ws_url.as_str())
+        .await
+        .expect("Failed to connect to websocket");
+}
+
+/// Tests a mint served under a path prefix end to end
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_mint_under_path_prefix() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+
+    let mint_url = serve_mint(mint_bob, "127.0.0.1:0", Some("/cashu")).await;
+    assert!(mint_url.to_string().ends_with("/cashu"));
+
+    exercise_http_mint(mint_url).await;
+}
+
+/// Tests a mint reached at an IPv6 address with a port end to end
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_mint_at_ipv6_address_with_port() {
+    setup_tracing();
+    let mint_bob = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+
+    let mint_url = serve_mint(mint_bob, "[::1]:0", None).await;
+    assert!(mint_url.to_string().starts_with("http://[::1]:"));
+
+    exercise_http_mint(mint_url).await;
+}
+
 async fn get_keyset_id(mint: &Mint) -> Id {
     let keys = mint.pubkeys().keysets.first().unwrap().clone();
     keys.verify_id()
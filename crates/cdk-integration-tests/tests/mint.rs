@@ -10,16 +10,40 @@
 //! - Internal state transitions
 //! - Fee calculation and enforcement
 //! - Proof validation and state management
+//! - Leader election and failover between instances
 
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use async_trait::async_trait;
 use bip39::Mnemonic;
-use cdk::mint::{MintBuilder, MintMeltLimits};
-use cdk::nuts::{CurrencyUnit, PaymentMethod};
+use cdk::amount::SplitTarget;
+use cdk::cdk_database::{self, DynMintLeaderElection, MintDatabase, MintLeaderElection};
+use cdk::dhke::construct_proofs;
+use cdk::mint::{Mint, MintBuilder, MintMeltLimits};
+use cdk::nuts::{
+    CurrencyUnit, MeltQuoteBolt11Request, MeltQuoteState, PaymentMethod, PreMintSecrets, SecretKey,
+};
 use cdk::types::{FeeReserve, QuoteTTL};
-use cdk_fake_wallet::FakeWallet;
+use cdk::wallet::WalletBuilder;
+use cdk::Amount;
+use cdk_common::database::{MintKVStoreTransaction, MintQuotesDatabase, MintQuotesTransaction};
+use cdk_common::melt::MeltQuoteRequest;
+use cdk_common::payment::{
+    self, Bolt12IncomingPaymentOptions, Bolt12OutgoingPaymentOptions,
+    CreateIncomingPaymentResponse, Event, IncomingPaymentOptions, MakePaymentResponse, MintPayment,
+    OutgoingPaymentOptions, PaymentIdentifier, PaymentQuoteResponse, WaitPaymentResponse,
+};
+use cdk_common::quote_id::QuoteId;
+use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription, FakeWallet};
+use cdk_integration_tests::init_pure_tests::{fund_wallet, DirectMintConnection};
 use cdk_sqlite::mint::memory;
+use cdk_sqlite::MintSqliteDatabase;
+use futures::Stream;
 
 pub const MINT_URL: &str = "http://127.0.0.1:8088";
 
@@ -98,3 +122,916 @@ async fn test_correct_keyset() {
 
     assert_ne!(new_keyset_info.id, keyset_info.id);
 }
+
+/// Leader election over a lock held in memory, standing in for a database lock
+#[derive(Debug)]
+struct SharedLeaderElection {
+    instance: u8,
+    leader: Arc<Mutex<Option<u8>>>,
+}
+
+#[async_trait::async_trait]
+impl MintLeaderElection for SharedLeaderElection {
+    type Err = cdk_database::Error;
+
+    async fn try_acquire_leadership(&self) -> Result<bool, Self::Err> {
+        let mut leader = self.leader.lock().expect("lock");
+        Ok(*leader.get_or_insert(self.instance) == self.instance)
+    }
+
+    async fn release_leadership(&self) -> Result<(), Self::Err> {
+        let mut leader = self.leader.lock().expect("lock");
+        if *leader == Some(self.instance) {
+            *leader = None;
+        }
+        Ok(())
+    }
+}
+
+const LEADER_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Builds a mint instance sharing the database and keys with the other instances
+async fn build_instance(
+    localstore: Arc<MintSqliteDatabase>,
+    seed: &[u8],
+    leader_election: DynMintLeaderElection,
+) -> Mint {
+    let fake_wallet = FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 1.0,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        0,
+        CurrencyUnit::Sat,
+    );
+
+    let mut mint_builder = MintBuilder::new(localstore.clone())
+        .with_name("clustered mint".to_string())
+        .with_leader_election(leader_election, LEADER_CHECK_INTERVAL);
+
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::Bolt11,
+            MintMeltLimits::new(1, 5_000),
+            Arc::new(fake_wallet),
+        )
+        .await
+        .unwrap();
+
+    mint_builder
+        .build_with_seed(localstore, seed)
+        .await
+        .unwrap()
+}
+
+async fn wait_for_leadership(mint: &Mint) {
+    for _ in 0..100 {
+        if mint.is_leader() {
+            return;
+        }
+        tokio::time::sleep(LEADER_CHECK_INTERVAL).await;
+    }
+
+    panic!("Instance did not become the leader");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_single_leader_between_instances() {
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let leader = Arc::new(Mutex::new(None));
+
+    let first = build_instance(
+        localstore.clone(),
+        &seed,
+        Arc::new(SharedLeaderElection {
+            instance: 0,
+            leader: leader.clone(),
+        }),
+    )
+    .await;
+    let second = build_instance(
+        localstore.clone(),
+        &seed,
+        Arc::new(SharedLeaderElection {
+            instance: 1,
+            leader: leader.clone(),
+        }),
+    )
+    .await;
+
+    // Instances are followers until elected
+    assert!(!first.is_leader());
+    assert!(!second.is_leader());
+
+    first.start().await.unwrap();
+    wait_for_leadership(&first).await;
+
+    second.start().await.unwrap();
+    tokio::time::sleep(LEADER_CHECK_INTERVAL * 4).await;
+
+    assert!(first.is_leader());
+    assert!(!second.is_leader());
+
+    // The follower takes over once the leader stops
+    first.stop().await.unwrap();
+    assert!(!first.is_leader());
+
+    wait_for_leadership(&second).await;
+
+    second.stop().await.unwrap();
+    assert_eq!(*leader.lock().unwrap(), None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_failover_recovers_in_flight_melt() {
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let leader = Arc::new(Mutex::new(None));
+
+    let first = build_instance(
+        localstore.clone(),
+        &seed,
+        Arc::new(SharedLeaderElection {
+            instance: 0,
+            leader: leader.clone(),
+        }),
+    )
+    .await;
+    let second = build_instance(
+        localstore.clone(),
+        &seed,
+        Arc::new(SharedLeaderElection {
+            instance: 1,
+            leader: leader.clone(),
+        }),
+    )
+    .await;
+
+    first.start().await.unwrap();
+    wait_for_leadership(&first).await;
+    second.start().await.unwrap();
+
+    // A melt is in flight on the leader, its payment went out but the leader has
+    // not recorded the outcome yet
+    let quote = first
+        .get_melt_quote(MeltQuoteRequest::Bolt11(MeltQuoteBolt11Request {
+            request: create_fake_invoice(100_000, "".to_string()),
+            unit: CurrencyUnit::Sat,
+            options: None,
+        }))
+        .await
+        .unwrap();
+
+    let mut tx = localstore.begin_transaction().await.unwrap();
+    tx.update_melt_quote_state(&quote.quote, MeltQuoteState::Pending, None)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    // The follower leaves the melt to the leader paying it
+    tokio::time::sleep(LEADER_CHECK_INTERVAL * 4).await;
+    let in_flight = second.check_melt_quote(&quote.quote).await.unwrap();
+    assert_eq!(in_flight.state, MeltQuoteState::Pending);
+
+    // The leader goes away and the follower recovers the melt when it takes over
+    first.stop().await.unwrap();
+    wait_for_leadership(&second).await;
+
+    let recovered = second.check_melt_quote(&quote.quote).await.unwrap();
+    assert_eq!(recovered.state, MeltQuoteState::Paid);
+
+    second.stop().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_leader_leaves_follower_melt_in_flight() {
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let leader = Arc::new(Mutex::new(None));
+
+    let first = build_instance(
+        localstore.clone(),
+        &seed,
+        Arc::new(SharedLeaderElection {
+            instance: 0,
+            leader: leader.clone(),
+        }),
+    )
+    .await;
+    let second = build_instance(
+        localstore.clone(),
+        &seed,
+        Arc::new(SharedLeaderElection {
+            instance: 1,
+            leader: leader.clone(),
+        }),
+    )
+    .await;
+
+    first.start().await.unwrap();
+    wait_for_leadership(&first).await;
+    second.start().await.unwrap();
+
+    let wallet = WalletBuilder::new()
+        .mint_url(MINT_URL.parse().unwrap())
+        .unit(CurrencyUnit::Sat)
+        .localstore(Arc::new(
+            cdk_sqlite::wallet::memory::empty()
+                .await
+                .expect("valid db instance"),
+        ))
+        .seed(Mnemonic::generate(12).unwrap().to_seed_normalized(""))
+        .client(DirectMintConnection::new(second.clone()))
+        .build()
+        .unwrap();
+    fund_wallet(wallet.clone(), 100, None).await.unwrap();
+
+    // The follower's payment stays in flight
+    let description = FakeInvoiceDescription {
+        pay_invoice_state: MeltQuoteState::Pending,
+        check_payment_state: MeltQuoteState::Pending,
+        pay_err: false,
+        check_err: false,
+    };
+    let invoice = create_fake_invoice(10_000, serde_json::to_string(&description).unwrap());
+    let quote = wallet.melt_quote(invoice.to_string(), None).await.unwrap();
+    assert!(wallet.melt(&quote.id).await.is_err());
+
+    let quote_id = QuoteId::from_str(&quote.id).unwrap();
+    assert_eq!(
+        second.check_melt_quote(&quote_id).await.unwrap().state,
+        MeltQuoteState::Pending
+    );
+
+    // The leader leaves the melt to the live follower paying it, even though its
+    // own backend would report the payment as paid
+    tokio::time::sleep(LEADER_CHECK_INTERVAL * 4).await;
+    assert!(first.is_leader());
+    assert_eq!(
+        first.check_melt_quote(&quote_id).await.unwrap().state,
+        MeltQuoteState::Pending
+    );
+
+    // Once the follower is gone the leader recovers its melt
+    second.stop().await.unwrap();
+
+    let mut state = MeltQuoteState::Pending;
+    for _ in 0..100 {
+        state = first.check_melt_quote(&quote_id).await.unwrap().state;
+        if state != MeltQuoteState::Pending {
+            break;
+        }
+        tokio::time::sleep(LEADER_CHECK_INTERVAL).await;
+    }
+    assert_eq!(state, MeltQuoteState::Paid);
+
+    first.stop().await.unwrap();
+}
+
+/// A melt retried after a crash between paying and recording the payment reuses
+/// the idempotency key of its quote, the backend must not pay it a second time
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_fake_wallet_deduplicates_payments() {
+    let fake_wallet = FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 1.0,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        0,
+        CurrencyUnit::Sat,
+    );
+
+    let offer = fake_wallet
+        .create_incoming_payment_request(
+            &CurrencyUnit::Sat,
+            IncomingPaymentOptions::Bolt12(Box::new(Bolt12IncomingPaymentOptions {
+                description: None,
+                amount: Some(100.into()),
+                unix_expiry: None,
+            })),
+        )
+        .await
+        .unwrap()
+        .request;
+
+    let options = |idempotency_key: &str| {
+        OutgoingPaymentOptions::Bolt12(Box::new(Bolt12OutgoingPaymentOptions {
+            offer: offer.parse().unwrap(),
+            max_fee_amount: None,
+            timeout_secs: None,
+            melt_options: None,
+            idempotency_key: Some(idempotency_key.to_string()),
+        }))
+    };
+
+    let paid = fake_wallet
+        .make_payment(&CurrencyUnit::Sat, options("quote"))
+        .await
+        .unwrap();
+    assert_eq!(paid.status, MeltQuoteState::Paid);
+
+    // The retry gets the payment already made
+    let retried = fake_wallet
+        .make_payment(&CurrencyUnit::Sat, options("quote"))
+        .await
+        .unwrap();
+    assert_eq!(retried.payment_lookup_id, paid.payment_lookup_id);
+
+    let other = fake_wallet
+        .make_payment(&CurrencyUnit::Sat, options("other quote"))
+        .await
+        .unwrap();
+    assert_ne!(other.payment_lookup_id, paid.payment_lookup_id);
+}
+
+/// Payment backend counting the quotes and payments the mint asks it for
+#[derive(Clone)]
+struct CountingPayment {
+    inner: Arc<FakeWallet>,
+    quotes: Arc<AtomicUsize>,
+    payments: Arc<AtomicUsize>,
+}
+
+impl CountingPayment {
+    fn new(inner: FakeWallet) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            quotes: Arc::new(AtomicUsize::new(0)),
+            payments: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn quotes(&self) -> usize {
+        self.quotes.load(Ordering::SeqCst)
+    }
+
+    fn payments(&self) -> usize {
+        self.payments.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl MintPayment for CountingPayment {
+    type Err = payment::Error;
+
+    async fn get_settings(&self) -> Result<serde_json::Value, Self::Err> {
+        self.inner.get_settings().await
+    }
+
+    async fn create_incoming_payment_request(
+        &self,
+        unit: &CurrencyUnit,
+        options: IncomingPaymentOptions,
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        self.inner
+            .create_incoming_payment_request(unit, options)
+            .await
+    }
+
+    async fn get_payment_quote(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<PaymentQuoteResponse, Self::Err> {
+        self.quotes.fetch_add(1, Ordering::SeqCst);
+        self.inner.get_payment_quote(unit, options).await
+    }
+
+    async fn make_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.payments.fetch_add(1, Ordering::SeqCst);
+        self.inner.make_payment(unit, options).await
+    }
+
+    async fn wait_payment_event(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+        self.inner.wait_payment_event().await
+    }
+
+    fn is_wait_invoice_active(&self) -> bool {
+        self.inner.is_wait_invoice_active()
+    }
+
+    fn cancel_wait_invoice(&self) {
+        self.inner.cancel_wait_invoice()
+    }
+
+    async fn check_incoming_payment_status(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+        self.inner
+            .check_incoming_payment_status(payment_identifier)
+            .await
+    }
+
+    async fn check_outgoing_payment(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.inner.check_outgoing_payment(payment_identifier).await
+    }
+}
+
+/// A mint that crashed after paying a melt but before recording the payment
+/// finds the payment on the backend when the melt is retried, and does not ask
+/// the backend to pay it again
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_melt_retried_after_crash_is_not_paid_again() {
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let backend = CountingPayment::new(FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 1.0,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        0,
+        CurrencyUnit::Sat,
+    ));
+
+    let mut mint_builder = MintBuilder::new(localstore.clone());
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::Bolt11,
+            MintMeltLimits::new(1, 5_000),
+            Arc::new(backend.clone()),
+        )
+        .await
+        .unwrap();
+    let mint = mint_builder
+        .build_with_seed(localstore.clone(), &seed)
+        .await
+        .unwrap();
+    mint.start().await.unwrap();
+
+    let wallet = WalletBuilder::new()
+        .mint_url(MINT_URL.parse().unwrap())
+        .unit(CurrencyUnit::Sat)
+        .localstore(Arc::new(
+            cdk_sqlite::wallet::memory::empty()
+                .await
+                .expect("valid db instance"),
+        ))
+        .seed(Mnemonic::generate(12).unwrap().to_seed_normalized(""))
+        .client(DirectMintConnection::new(mint.clone()))
+        .build()
+        .unwrap();
+    fund_wallet(wallet.clone(), 100, None).await.unwrap();
+
+    let quote = wallet
+        .melt_quote(
+            create_fake_invoice(10_000, "".to_string()).to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    let quote_id = QuoteId::from_str(&quote.id).unwrap();
+
+    // The payment went out and the mint went down before recording it, only
+    // its record of the attempt is left
+    let melt_quote = localstore.get_melt_quote(&quote_id).await.unwrap().unwrap();
+    backend
+        .make_payment(&CurrencyUnit::Sat, melt_quote.try_into().unwrap())
+        .await
+        .unwrap();
+
+    let mut tx = localstore.begin_transaction().await.unwrap();
+    tx.kv_write(
+        "cdk_mint",
+        "melt_attempt",
+        quote_id.to_string().trim_end_matches('='),
+        br#"{"instance_id":"crashed","time":0}"#,
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    let melted = wallet.melt(&quote.id).await.unwrap();
+    assert_eq!(melted.state, MeltQuoteState::Paid);
+    assert_eq!(backend.payments(), 1);
+    // The wallet pays what the payment found on the backend spent
+    assert_eq!(wallet.total_balance().await.unwrap(), 89.into());
+
+    // A melt never attempted before is paid by the backend
+    let quote = wallet
+        .melt_quote(
+            create_fake_invoice(10_000, "".to_string()).to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    let melted = wallet.melt(&quote.id).await.unwrap();
+    assert_eq!(melted.state, MeltQuoteState::Paid);
+    assert_eq!(backend.payments(), 2);
+
+    mint.stop().await.unwrap();
+}
+
+/// A keysend melt has no payment lookup id to ask the backend about an earlier
+/// attempt, so a melt retried after a recorded attempt is left pending instead
+/// of being paid again
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_keysend_melt_retried_after_attempt_is_not_paid_again() {
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let backend = CountingPayment::new(FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 1.0,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        0,
+        CurrencyUnit::Sat,
+    ));
+
+    let mut mint_builder = MintBuilder::new(localstore.clone());
+    for method in [PaymentMethod::Bolt11, PaymentMethod::keysend()] {
+        mint_builder
+            .add_payment_processor(
+                CurrencyUnit::Sat,
+                method,
+                MintMeltLimits::new(1, 5_000),
+                Arc::new(backend.clone()),
+            )
+            .await
+            .unwrap();
+    }
+    let mint = mint_builder
+        .build_with_seed(localstore.clone(), &seed)
+        .await
+        .unwrap();
+    mint.start().await.unwrap();
+
+    let wallet = WalletBuilder::new()
+        .mint_url(MINT_URL.parse().unwrap())
+        .unit(CurrencyUnit::Sat)
+        .localstore(Arc::new(
+            cdk_sqlite::wallet::memory::empty()
+                .await
+                .expect("valid db instance"),
+        ))
+        .seed(Mnemonic::generate(12).unwrap().to_seed_normalized(""))
+        .client(DirectMintConnection::new(mint.clone()))
+        .build()
+        .unwrap();
+    fund_wallet(wallet.clone(), 100, None).await.unwrap();
+
+    let quote = wallet
+        .melt_keysend_quote(SecretKey::generate().public_key(), 10_000.into())
+        .await
+        .unwrap();
+    let quote_id = QuoteId::from_str(&quote.id).unwrap();
+    assert!(localstore
+        .get_melt_quote(&quote_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .request_lookup_id
+        .is_none());
+
+    // An earlier attempt may have paid before the mint went down
+    let mut tx = localstore.begin_transaction().await.unwrap();
+    tx.kv_write(
+        "cdk_mint",
+        "melt_attempt",
+        quote_id.to_string().trim_end_matches('='),
+        br#"{"instance_id":"crashed","time":0}"#,
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    assert!(wallet.melt(&quote.id).await.is_err());
+    assert_eq!(backend.payments(), 0);
+    assert_eq!(
+        localstore
+            .get_melt_quote(&quote_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .state,
+        MeltQuoteState::Pending
+    );
+
+    mint.stop().await.unwrap();
+}
+
+/// Tests that the operator check of a pending melt quote refuses quotes whose
+/// payment attempt is recorded, and settles them once it is not
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_check_pending_melt_quote_refuses_attempted_payments() {
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let backend = CountingPayment::new(FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 1.0,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        0,
+        CurrencyUnit::Sat,
+    ));
+
+    let mut mint_builder = MintBuilder::new(localstore.clone());
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::Bolt11,
+            MintMeltLimits::new(1, 5_000),
+            Arc::new(backend.clone()),
+        )
+        .await
+        .unwrap();
+    let mint = mint_builder
+        .build_with_seed(localstore.clone(), &seed)
+        .await
+        .unwrap();
+
+    let quote = mint
+        .get_melt_quote(MeltQuoteRequest::Bolt11(MeltQuoteBolt11Request {
+            request: create_fake_invoice(10_000, "".to_string()),
+            unit: CurrencyUnit::Sat,
+            options: None,
+        }))
+        .await
+        .unwrap();
+    let quote_id = quote.quote;
+
+    // The payment is in flight: the quote is pending and its attempt recorded
+    let melt_quote = localstore.get_melt_quote(&quote_id).await.unwrap().unwrap();
+    backend
+        .make_payment(&CurrencyUnit::Sat, melt_quote.try_into().unwrap())
+        .await
+        .unwrap();
+
+    let mut tx = localstore.begin_transaction().await.unwrap();
+    tx.update_melt_quote_state(&quote_id, MeltQuoteState::Pending, None)
+        .await
+        .unwrap();
+    tx.kv_write(
+        "cdk_mint",
+        "melt_attempt",
+        quote_id.to_string().trim_end_matches('='),
+        br#"{"instance_id":"other","time":0}"#,
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    assert!(matches!(
+        mint.check_pending_melt_quote(&quote_id).await.unwrap_err(),
+        cdk::Error::PendingQuote
+    ));
+    assert_eq!(
+        localstore
+            .get_melt_quote(&quote_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .state,
+        MeltQuoteState::Pending
+    );
+
+    // Without the attempt the quote is settled from the backend
+    let mut tx = localstore.begin_transaction().await.unwrap();
+    tx.kv_remove(
+        "cdk_mint",
+        "melt_attempt",
+        quote_id.to_string().trim_end_matches('='),
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(
+        mint.check_pending_melt_quote(&quote_id).await.unwrap(),
+        MeltQuoteState::Paid
+    );
+    assert_eq!(backend.payments(), 1);
+}
+
+/// Tests that melts paying the mint's own mint quotes are quoted and settled
+/// without the backend, and are not paid by it once the mint quote is paid or gone
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_internal_melt_never_reaches_backend() {
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let backend = CountingPayment::new(FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 1.0,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        0,
+        CurrencyUnit::Sat,
+    ));
+
+    let mut mint_builder = MintBuilder::new(localstore.clone());
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::Bolt11,
+            MintMeltLimits::new(1, 5_000),
+            Arc::new(backend.clone()),
+        )
+        .await
+        .unwrap();
+    let mint = mint_builder
+        .build_with_seed(localstore.clone(), &seed)
+        .await
+        .unwrap();
+    mint.start().await.unwrap();
+
+    let wallet = |seed: [u8; 64]| {
+        let mint = mint.clone();
+        async move {
+            WalletBuilder::new()
+                .mint_url(MINT_URL.parse().unwrap())
+                .unit(CurrencyUnit::Sat)
+                .localstore(Arc::new(
+                    cdk_sqlite::wallet::memory::empty()
+                        .await
+                        .expect("valid db instance"),
+                ))
+                .seed(seed)
+                .client(DirectMintConnection::new(mint))
+                .build()
+                .unwrap()
+        }
+    };
+    let payer = wallet(Mnemonic::generate(12).unwrap().to_seed_normalized("")).await;
+    let payee = wallet(Mnemonic::generate(12).unwrap().to_seed_normalized("")).await;
+    fund_wallet(payer.clone(), 100, None).await.unwrap();
+
+    let mint_quote = payee.mint_quote(10.into(), None).await.unwrap();
+    let quotes = backend.quotes();
+    let melt_quote = payer
+        .melt_quote(mint_quote.request.clone(), None)
+        .await
+        .unwrap();
+    assert_eq!(melt_quote.fee_reserve, Amount::ZERO);
+    assert_eq!(backend.quotes(), quotes);
+
+    let melted = payer.melt(&melt_quote.id).await.unwrap();
+    assert_eq!(melted.state, MeltQuoteState::Paid);
+    assert_eq!(backend.payments(), 0);
+    assert_eq!(payer.total_balance().await.unwrap(), 90.into());
+
+    // The mint quote was paid by someone else before the melt
+    let mint_quote = payee.mint_quote(10.into(), None).await.unwrap();
+    let melt_quote = payer
+        .melt_quote(mint_quote.request.clone(), None)
+        .await
+        .unwrap();
+    let mut tx = localstore.begin_transaction().await.unwrap();
+    tx.increment_mint_quote_amount_paid(
+        &QuoteId::from_str(&mint_quote.id).unwrap(),
+        10.into(),
+        "other payment".to_string(),
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    assert!(matches!(
+        payer.melt(&melt_quote.id).await.unwrap_err(),
+        cdk::Error::RequestAlreadyPaid
+    ));
+    assert_eq!(backend.payments(), 0);
+
+    // The mint quote was removed before the melt
+    let mint_quote = payee.mint_quote(10.into(), None).await.unwrap();
+    let melt_quote = payer
+        .melt_quote(mint_quote.request.clone(), None)
+        .await
+        .unwrap();
+    let mut tx = localstore.begin_transaction().await.unwrap();
+    tx.remove_mint_quote(&QuoteId::from_str(&mint_quote.id).unwrap())
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    assert!(matches!(
+        payer.melt(&melt_quote.id).await.unwrap_err(),
+        cdk::Error::UnknownQuote
+    ));
+    assert_eq!(backend.payments(), 0);
+
+    mint.stop().await.unwrap();
+}
+
+/// Tests that vouchers are issued and redeemed only in a voucher unit
+#[tokio::test]
+async fn test_issue_and_redeem_vouchers() {
+    let mnemonic = Mnemonic::generate(12).unwrap();
+    let localstore = Arc::new(memory::empty().await.expect("valid db instance"));
+    let mut mint_builder = MintBuilder::new(localstore.clone());
+
+    let fake_wallet = FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 1.0,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        0,
+        CurrencyUnit::Sat,
+    );
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::Bolt11,
+            MintMeltLimits::new(1, 5_000),
+            Arc::new(fake_wallet),
+        )
+        .await
+        .unwrap();
+
+    let points = CurrencyUnit::Custom("points".to_string());
+    mint_builder.add_voucher_unit(points.clone()).unwrap();
+    // A unit with a payment backend cannot also be a voucher unit
+    assert!(mint_builder.add_voucher_unit(CurrencyUnit::Sat).is_err());
+
+    let mint = mint_builder
+        .build_with_seed(localstore.clone(), &mnemonic.to_seed_normalized(""))
+        .await
+        .unwrap();
+
+    let keyset = |unit: &CurrencyUnit| {
+        mint.pubkeys()
+            .keysets
+            .into_iter()
+            .find(|keyset| &keyset.unit == unit)
+            .unwrap()
+    };
+    let points_keyset = keyset(&points);
+    let sat_keyset = keyset(&CurrencyUnit::Sat);
+
+    // Outputs of a unit with a payment backend are refused before they are signed
+    let sat_outputs =
+        PreMintSecrets::random(sat_keyset.id, 100.into(), &SplitTarget::default()).unwrap();
+    assert!(matches!(
+        mint.issue_vouchers(sat_outputs.blinded_messages(), None)
+            .await
+            .unwrap_err(),
+        cdk::Error::UnsupportedUnit
+    ));
+
+    let outputs =
+        PreMintSecrets::random(points_keyset.id, 100.into(), &SplitTarget::default()).unwrap();
+    let signatures = mint
+        .issue_vouchers(outputs.blinded_messages(), Some("campaign".to_string()))
+        .await
+        .unwrap();
+
+    // The same outputs are not signed twice
+    assert!(mint
+        .issue_vouchers(outputs.blinded_messages(), None)
+        .await
+        .is_err());
+
+    let proofs = construct_proofs(
+        signatures,
+        outputs.rs(),
+        outputs.secrets(),
+        &points_keyset.keys,
+    )
+    .unwrap();
+
+    let redemption = mint
+        .redeem_vouchers(proofs.clone(), Some("order".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(redemption.amount, Amount::from(100));
+    assert_eq!(redemption.fee, Amount::ZERO);
+
+    assert!(matches!(
+        mint.redeem_vouchers(proofs, None).await.unwrap_err(),
+        cdk::Error::TokenAlreadySpent
+    ));
+
+    let report = mint.voucher_report().await.unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].unit, points);
+    assert_eq!(report[0].issued, Amount::from(100));
+    assert_eq!(report[0].redeemed, Amount::from(100));
+    assert_eq!(report[0].outstanding, Amount::ZERO);
+    assert_eq!(report[0].issuances, 1);
+    assert_eq!(report[0].redemptions, 1);
+}
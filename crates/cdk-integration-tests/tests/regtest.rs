@@ -80,6 +80,8 @@ async fn test_internal_payment() {
         .unwrap();
 
     assert_eq!(melt.amount, 10.into());
+    // Settled internally, the payment backend is not asked for a fee reserve
+    assert_eq!(melt.fee_reserve, Amount::ZERO);
 
     let _melted = wallet.melt(&melt.id).await.unwrap();
 
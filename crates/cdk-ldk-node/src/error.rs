@@ -65,6 +65,10 @@ pub enum Error {
     #[error("Invalid payment direction")]
     InvalidPaymentDirection,
 
+    /// Invalid node id
+    #[error("Invalid node id")]
+    InvalidNodeId,
+
     /// Hex decode error
     #[error("Hex decode error: {0}")]
     HexDecode(#[from] cdk_common::util::hex::Error),
@@ -474,6 +474,7 @@ impl MintPayment for CdkLdkNode {
             invoice_description: true,
             amountless: true,
             bolt12: true,
+            keysend: true,
         };
         Ok(serde_json::to_value(settings)?)
     }
@@ -630,6 +631,27 @@ impl MintPayment for CdkLdkNode {
                     false => absolute_fee_reserve,
                 };
 
+                Ok(PaymentQuoteResponse {
+                    request_lookup_id: None,
+                    amount,
+                    fee: fee.into(),
+                    state: MeltQuoteState::Unpaid,
+                    unit: unit.clone(),
+                })
+            }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                let amount = to_unit(keysend_options.amount_msat, &CurrencyUnit::Msat, unit)?;
+
+                let relative_fee_reserve =
+                    (self.fee_reserve.percent_fee_reserve * u64::from(amount) as f32) as u64;
+
+                let absolute_fee_reserve: u64 = self.fee_reserve.min_fee_reserve.into();
+
+                let fee = match relative_fee_reserve > absolute_fee_reserve {
+                    true => relative_fee_reserve,
+                    false => absolute_fee_reserve,
+                };
+
                 Ok(PaymentQuoteResponse {
                     request_lookup_id: None,
                     amount,
@@ -809,6 +831,80 @@ impl MintPayment for CdkLdkNode {
 
                 let total_spent = to_unit(total_spent, &CurrencyUnit::Msat, unit)?;
 
+                Ok(MakePaymentResponse {
+                    payment_lookup_id: PaymentIdentifier::PaymentId(payment_id.0),
+                    payment_proof,
+                    status,
+                    total_spent,
+                    unit: unit.clone(),
+                })
+            }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                let node_id = ldk_node::bitcoin::secp256k1::PublicKey::from_slice(
+                    &keysend_options.pubkey.to_bytes(),
+                )
+                .map_err(|_| Error::InvalidNodeId)?;
+
+                let send_params = keysend_options
+                    .max_fee_amount
+                    .map(|f| {
+                        to_unit(f, unit, &CurrencyUnit::Msat).map(|amount_msat| SendingParameters {
+                            max_total_routing_fee_msat: Some(Some(amount_msat.into())),
+                            max_channel_saturation_power_of_half: None,
+                            max_total_cltv_expiry_delta: None,
+                            max_path_count: None,
+                        })
+                    })
+                    .transpose()?;
+
+                let payment_id = self
+                    .inner
+                    .spontaneous_payment()
+                    .send(keysend_options.amount_msat.into(), node_id, send_params)
+                    .map_err(Error::LdkNode)?;
+
+                // Check payment status for up to 10 seconds
+                let start = std::time::Instant::now();
+                let timeout = std::time::Duration::from_secs(10);
+
+                let (status, payment_details) = loop {
+                    let details = self
+                        .inner
+                        .payment(&payment_id)
+                        .ok_or(Error::PaymentNotFound)?;
+
+                    match details.status {
+                        PaymentStatus::Succeeded => break (MeltQuoteState::Paid, details),
+                        PaymentStatus::Failed => {
+                            tracing::error!("Keysend payment with id {} failed.", payment_id);
+                            break (MeltQuoteState::Failed, details);
+                        }
+                        PaymentStatus::Pending => {
+                            if start.elapsed() > timeout {
+                                tracing::warn!(
+                                    "Keysend payment pending for 10 seconds. No longer waiting"
+                                );
+                                break (MeltQuoteState::Pending, details);
+                            }
+                            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    }
+                };
+
+                let payment_proof = match payment_details.kind {
+                    PaymentKind::Spontaneous { hash: _, preimage } => {
+                        preimage.map(|p| p.to_string())
+                    }
+                    _ => return Err(Error::UnexpectedPaymentKind.into()),
+                };
+
+                let total_spent = payment_details
+                    .amount_msat
+                    .ok_or(Error::CouldNotGetAmountSpent)?;
+
+                let total_spent = to_unit(total_spent, &CurrencyUnit::Msat, unit)?;
+
                 Ok(MakePaymentResponse {
                     payment_lookup_id: PaymentIdentifier::PaymentId(payment_id.0),
                     payment_proof,
@@ -968,6 +1064,7 @@ impl MintPayment for CdkLdkNode {
                 preimage,
                 secret: _,
             } => preimage.map(|p| p.to_string()),
+            PaymentKind::Spontaneous { hash: _, preimage } => preimage.map(|p| p.to_string()),
             _ => return Err(Error::UnexpectedPaymentKind.into()),
         };
 
@@ -983,6 +1080,21 @@ impl MintPayment for CdkLdkNode {
             unit: CurrencyUnit::Msat,
         })
     }
+
+    /// Node identity, lightning balance and running state
+    async fn backend_info(&self) -> Result<Option<BackendInfo>, Self::Err> {
+        let balances = self.inner.list_balances();
+
+        Ok(Some(BackendInfo {
+            backend: "ldk_node".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            node_pubkey: Some(self.inner.node_id().to_string()),
+            node_alias: self.inner.node_alias().map(|a| a.to_string()),
+            balance: Some(balances.total_lightning_balance_sats.into()),
+            balance_unit: Some(CurrencyUnit::Sat),
+            connected: self.inner.status().is_running,
+        }))
+    }
 }
 
 impl Drop for CdkLdkNode {
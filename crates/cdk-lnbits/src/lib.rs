@@ -62,6 +62,7 @@ impl LNbits {
                 invoice_description: true,
                 amountless: false,
                 bolt12: false,
+                keysend: false,
             },
         })
     }
@@ -233,6 +234,9 @@ impl MintPayment for LNbits {
             OutgoingPaymentOptions::Bolt12(_bolt12_options) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LNbits")))
             }
+            OutgoingPaymentOptions::Keysend(_) => Err(Self::Err::Anyhow(anyhow!(
+                "Keysend not supported by LNbits"
+            ))),
         }
     }
 
@@ -294,6 +298,9 @@ impl MintPayment for LNbits {
             OutgoingPaymentOptions::Bolt12(_) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LNbits")))
             }
+            OutgoingPaymentOptions::Keysend(_) => Err(Self::Err::Anyhow(anyhow!(
+                "Keysend not supported by LNbits"
+            ))),
         }
     }
 
@@ -409,6 +416,24 @@ impl MintPayment for LNbits {
 
         Ok(pay_response)
     }
+
+    /// Wallet balance, LNbits does not expose the identity of its funding node
+    async fn backend_info(&self) -> Result<Option<payment::BackendInfo>, Self::Err> {
+        let wallet = self.lnbits_api.get_wallet_details().await.map_err(|err| {
+            tracing::error!("Could not get wallet details: {}", err);
+            Self::Err::Anyhow(anyhow!("Could not get wallet details"))
+        })?;
+
+        Ok(Some(payment::BackendInfo {
+            backend: "lnbits".to_string(),
+            version: None,
+            node_pubkey: None,
+            node_alias: None,
+            balance: Some(Amount::from(wallet.balance.max(0) as u64)),
+            balance_unit: Some(CurrencyUnit::Msat),
+            connected: true,
+        }))
+    }
 }
 
 fn lnbits_to_melt_status(status: &str) -> MeltQuoteState {
@@ -7,6 +7,7 @@
 #![warn(rustdoc::bare_urls)]
 
 use std::cmp::max;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::str::FromStr;
@@ -16,10 +17,10 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use async_trait::async_trait;
 use cdk_common::amount::{to_unit, Amount, MSAT_IN_SAT};
-use cdk_common::bitcoin::hashes::Hash;
+use cdk_common::bitcoin::hashes::{sha256, Hash, HashEngine};
 use cdk_common::common::FeeReserve;
 use cdk_common::database::mint::DynMintKVStore;
-use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState};
+use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState, SecretKey};
 use cdk_common::payment::{
     self, Bolt11Settings, CreateIncomingPaymentResponse, Event, IncomingPaymentOptions,
     MakePaymentResponse, MintPayment, OutgoingPaymentOptions, PaymentIdentifier,
@@ -48,6 +49,25 @@ const LND_KV_PRIMARY_NAMESPACE: &str = "cdk_lnd_lightning_backend";
 const LND_KV_SECONDARY_NAMESPACE: &str = "payment_indices";
 const LAST_ADD_INDEX_KV_KEY: &str = "last_add_index";
 const LAST_SETTLE_INDEX_KV_KEY: &str = "last_settle_index";
+/// TLV record carrying the preimage of a keysend payment
+const KEYSEND_RECORD: u64 = 5482373484;
+
+/// Preimage of a keysend payment
+///
+/// Payments with an idempotency key get a preimage derived from it, so a retry
+/// has the same payment hash and LND refuses to pay it a second time.
+fn keysend_preimage(idempotency_key: Option<&str>, dest: &[u8]) -> [u8; 32] {
+    match idempotency_key {
+        Some(idempotency_key) => {
+            let mut engine = sha256::Hash::engine();
+            engine.input(b"cdk-lnd keysend preimage");
+            engine.input(dest);
+            engine.input(idempotency_key.as_bytes());
+            sha256::Hash::from_engine(engine).to_byte_array()
+        }
+        None => SecretKey::generate().to_secret_bytes(),
+    }
+}
 
 /// Lnd mint backend
 #[derive(Clone)]
@@ -122,6 +142,7 @@ impl Lnd {
                 invoice_description: true,
                 amountless: true,
                 bolt12: false,
+                keysend: true,
             },
         })
     }
@@ -381,9 +402,99 @@ impl MintPayment for Lnd {
             OutgoingPaymentOptions::Bolt12(_) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LND")))
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                let amount = to_unit(keysend_options.amount_msat, &CurrencyUnit::Msat, unit)?;
+
+                let relative_fee_reserve =
+                    (self.fee_reserve.percent_fee_reserve * u64::from(amount) as f32) as u64;
+
+                let absolute_fee_reserve: u64 = self.fee_reserve.min_fee_reserve.into();
+
+                let fee = max(relative_fee_reserve, absolute_fee_reserve);
+
+                // The payment hash is only known once the preimage is picked when paying
+                Ok(PaymentQuoteResponse {
+                    request_lookup_id: None,
+                    amount,
+                    fee: fee.into(),
+                    state: MeltQuoteState::Unpaid,
+                    unit: unit.clone(),
+                })
+            }
         }
     }
 
+    #[instrument(skip_all)]
+    async fn probe_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<Option<Amount>, Self::Err> {
+        let OutgoingPaymentOptions::Bolt11(bolt11_options) = options else {
+            return Ok(None);
+        };
+
+        let amount_msat: u64 = match bolt11_options.melt_options {
+            Some(amount) => amount.amount_msat().into(),
+            None => bolt11_options
+                .bolt11
+                .amount_milli_satoshis()
+                .ok_or(Error::UnknownInvoiceAmount)?,
+        };
+
+        // Destinations behind private channels are reached over the route hints
+        let route_hints = bolt11_options
+            .bolt11
+            .route_hints()
+            .into_iter()
+            .map(|hint| lnrpc::RouteHint {
+                hop_hints: hint
+                    .0
+                    .iter()
+                    .map(|hop| lnrpc::HopHint {
+                        node_id: hex::encode(hop.src_node_id.serialize()),
+                        chan_id: hop.short_channel_id,
+                        fee_base_msat: hop.fees.base_msat,
+                        fee_proportional_millionths: hop.fees.proportional_millionths,
+                        cltv_expiry_delta: hop.cltv_expiry_delta.into(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let route_req = lnrpc::QueryRoutesRequest {
+            pub_key: hex::encode(bolt11_options.bolt11.get_payee_pub_key().serialize()),
+            amt_msat: amount_msat as i64,
+            final_cltv_delta: bolt11_options.bolt11.min_final_cltv_expiry_delta() as i32,
+            route_hints,
+            use_mission_control: true,
+            ..Default::default()
+        };
+
+        let mut lnd_client = self.lnd_client.clone();
+
+        let routes = match lnd_client.lightning().query_routes(route_req).await {
+            Ok(response) => response.into_inner().routes,
+            Err(err) => {
+                // Destinations behind private channels have no public route
+                tracing::debug!("Could not probe route for fee estimate: {}", err);
+                return Ok(None);
+            }
+        };
+
+        let Some(fee_msat) = routes
+            .first()
+            .map(|route| route.total_fees_msat.max(0) as u64)
+        else {
+            return Ok(None);
+        };
+
+        // Rounded up to a whole sat so the reserve covers the probed fee
+        let fee_msat = fee_msat.div_ceil(MSAT_IN_SAT) * MSAT_IN_SAT;
+
+        Ok(Some(to_unit(fee_msat, &CurrencyUnit::Msat, unit)?))
+    }
+
     #[instrument(skip_all)]
     async fn make_payment(
         &self,
@@ -579,6 +690,57 @@ impl MintPayment for Lnd {
             OutgoingPaymentOptions::Bolt12(_) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LND")))
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                let mut lnd_client = self.lnd_client.clone();
+
+                // The payer picks the preimage of a keysend payment and sends it
+                // to the payee in the keysend record
+                let preimage = keysend_preimage(
+                    keysend_options.idempotency_key.as_deref(),
+                    &keysend_options.pubkey.to_bytes(),
+                );
+                let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
+
+                let pay_req = lnrpc::SendRequest {
+                    dest: keysend_options.pubkey.to_bytes().to_vec(),
+                    amt_msat: u64::from(keysend_options.amount_msat) as i64,
+                    payment_hash: payment_hash.to_vec(),
+                    fee_limit: keysend_options.max_fee_amount.map(|f| {
+                        let limit = Limit::Fixed(u64::from(f) as i64);
+                        FeeLimit { limit: Some(limit) }
+                    }),
+                    dest_custom_records: HashMap::from([(KEYSEND_RECORD, preimage.to_vec())]),
+                    ..Default::default()
+                };
+
+                let payment_response = lnd_client
+                    .lightning()
+                    .send_payment_sync(tonic::Request::new(pay_req))
+                    .await
+                    .map_err(|err| {
+                        tracing::warn!("Keysend payment failed: {}", err);
+                        Error::PaymentFailed
+                    })?
+                    .into_inner();
+
+                let total_amount = payment_response
+                    .payment_route
+                    .map_or(0, |route| route.total_amt_msat / MSAT_IN_SAT as i64)
+                    as u64;
+
+                let (status, payment_preimage) = match total_amount == 0 {
+                    true => (MeltQuoteState::Unpaid, None),
+                    false => (MeltQuoteState::Paid, Some(hex::encode(preimage))),
+                };
+
+                Ok(MakePaymentResponse {
+                    payment_lookup_id: PaymentIdentifier::PaymentHash(payment_hash),
+                    payment_proof: payment_preimage,
+                    status,
+                    total_spent: total_amount.into(),
+                    unit: CurrencyUnit::Sat,
+                })
+            }
         }
     }
 
@@ -744,4 +906,36 @@ impl MintPayment for Lnd {
         // If the stream is exhausted without a final status
         Err(Error::UnknownPaymentStatus.into())
     }
+
+    /// Node identity, local channel balance and sync state
+    #[instrument(skip_all)]
+    async fn backend_info(&self) -> Result<Option<payment::BackendInfo>, Self::Err> {
+        let mut lnd_client = self.lnd_client.clone();
+
+        let info = lnd_client
+            .lightning()
+            .get_info(lnrpc::GetInfoRequest {})
+            .await
+            .map_err(Error::LndError)?
+            .into_inner();
+
+        let channel_balance = lnd_client
+            .lightning()
+            .channel_balance(lnrpc::ChannelBalanceRequest {})
+            .await
+            .map_err(Error::LndError)?
+            .into_inner();
+
+        Ok(Some(payment::BackendInfo {
+            backend: "lnd".to_string(),
+            version: Some(info.version),
+            node_pubkey: Some(info.identity_pubkey),
+            node_alias: Some(info.alias).filter(|alias| !alias.is_empty()),
+            balance: channel_balance
+                .local_balance
+                .map(|balance| Amount::from(balance.msat)),
+            balance_unit: Some(CurrencyUnit::Msat),
+            connected: info.synced_to_chain,
+        }))
+    }
 }
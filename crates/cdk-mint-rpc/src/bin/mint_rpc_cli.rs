@@ -69,6 +69,7 @@ enum Commands {
     /// Get info
     GetInfo,
     /// Update motd
+    #[command(alias = "set-motd")]
     UpdateMotd(subcommands::UpdateMotdCommand),
     /// Update short description
     UpdateShortDescription(subcommands::UpdateShortDescriptionCommand),
@@ -97,7 +98,20 @@ enum Commands {
     /// Update Nut04 quote
     UpdateNut04QuoteState(subcommands::UpdateNut04QuoteCommand),
     /// Rotate next keyset
+    #[command(alias = "rotate-keys")]
     RotateNextKeyset(subcommands::RotateNextKeysetCommand),
+    /// Get payment backend info
+    GetBackendInfo,
+    /// Get the balances of the mint's ledger
+    GetLedger,
+    /// List melt quotes whose payment is pending or unknown
+    ListPendingMelts,
+    /// Check a quote with the payment backend and update its state
+    #[command(alias = "force-check-quote")]
+    CheckQuote(subcommands::CheckQuoteCommand),
+    /// Export issued and redeemed totals per keyset and the ledger as JSON
+    #[command(alias = "audit")]
+    ExportAudit(subcommands::ExportAuditCommand),
 }
 
 #[tokio::main]
@@ -227,6 +241,21 @@ async fn main() -> Result<()> {
         Commands::RotateNextKeyset(sub_command_args) => {
             subcommands::rotate_next_keyset(&mut client, &sub_command_args).await?;
         }
+        Commands::GetBackendInfo => {
+            subcommands::get_backend_info(&mut client).await?;
+        }
+        Commands::GetLedger => {
+            subcommands::get_ledger(&mut client).await?;
+        }
+        Commands::ListPendingMelts => {
+            subcommands::list_pending_melts(&mut client).await?;
+        }
+        Commands::CheckQuote(sub_command_args) => {
+            subcommands::check_quote(&mut client, &sub_command_args).await?;
+        }
+        Commands::ExportAudit(sub_command_args) => {
+            subcommands::export_audit(&mut client, &sub_command_args).await?;
+        }
     }
 
     Ok(())
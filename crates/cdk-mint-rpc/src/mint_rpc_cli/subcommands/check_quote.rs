@@ -0,0 +1,49 @@
+use anyhow::Result;
+use clap::Args;
+use tonic::transport::Channel;
+use tonic::Request;
+
+use crate::cdk_mint_client::CdkMintClient;
+use crate::CheckQuoteRequest;
+
+/// Command to check a quote with the payment backend
+///
+/// This command makes the mint look up the payment of a mint or melt quote with
+/// its payment backend and update the quote, instead of waiting for a wallet to
+/// check it or for the next restart.
+#[derive(Args)]
+pub struct CheckQuoteCommand {
+    /// The id of the quote to check
+    quote_id: String,
+    /// Check a melt quote instead of a mint quote
+    #[arg(long, default_value_t = false)]
+    melt: bool,
+}
+
+/// Executes the check_quote command against the mint server
+///
+/// This function sends an RPC request to check a quote with the payment backend
+/// and prints the resulting state.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - The quote to check
+pub async fn check_quote(
+    client: &mut CdkMintClient<Channel>,
+    sub_command_args: &CheckQuoteCommand,
+) -> Result<()> {
+    let response = client
+        .check_quote(Request::new(CheckQuoteRequest {
+            quote_id: sub_command_args.quote_id.clone(),
+            kind: match sub_command_args.melt {
+                true => "melt".to_string(),
+                false => "mint".to_string(),
+            },
+        }))
+        .await?
+        .into_inner();
+
+    println!("Quote {} is {}", response.quote_id, response.state);
+
+    Ok(())
+}
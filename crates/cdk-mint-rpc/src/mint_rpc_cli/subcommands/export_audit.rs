@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::json;
+use tonic::transport::Channel;
+use tonic::Request;
+
+use crate::cdk_mint_client::CdkMintClient;
+use crate::ExportAuditRequest;
+
+/// Command to export an audit of the mint
+///
+/// This command exports the amount issued and redeemed per keyset together with
+/// the balances of the mint's ledger as JSON, for bookkeeping or an external audit.
+#[derive(Args)]
+pub struct ExportAuditCommand {
+    /// File to write the audit to, printed if not set
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Executes the export_audit command against the mint server
+///
+/// This function sends an RPC request to retrieve the audit and writes it as JSON.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - Where to write the audit
+pub async fn export_audit(
+    client: &mut CdkMintClient<Channel>,
+    sub_command_args: &ExportAuditCommand,
+) -> Result<()> {
+    let response = client
+        .export_audit(Request::new(ExportAuditRequest {}))
+        .await?
+        .into_inner();
+
+    let audit = json!({
+        "timestamp": response.timestamp,
+        "keysets": response
+            .keysets
+            .iter()
+            .map(|keyset| json!({
+                "id": keyset.id,
+                "unit": keyset.unit,
+                "active": keyset.active,
+                "issued": keyset.issued,
+                "redeemed": keyset.redeemed,
+            }))
+            .collect::<Vec<_>>(),
+        "ledger": response
+            .ledger
+            .iter()
+            .map(|balance| json!({
+                "unit": balance.unit,
+                "outstanding": balance.outstanding,
+                "database_outstanding": balance.database_outstanding,
+                "consistent": balance.consistent,
+                "accounts": balance
+                    .accounts
+                    .iter()
+                    .map(|account| json!({
+                        "account": account.account,
+                        "debits": account.debits,
+                        "credits": account.credits,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let audit = serde_json::to_string_pretty(&audit)?;
+
+    match &sub_command_args.output {
+        Some(output) => {
+            std::fs::write(output, audit)?;
+            println!("Audit written to {}", output.display());
+        }
+        None => println!("{audit}"),
+    }
+
+    Ok(())
+}
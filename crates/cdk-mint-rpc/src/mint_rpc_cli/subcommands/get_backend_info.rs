@@ -0,0 +1,53 @@
+use anyhow::Result;
+use tonic::transport::Channel;
+use tonic::Request;
+
+use crate::cdk_mint_client::CdkMintClient;
+use crate::GetBackendInfoRequest;
+
+/// Executes the get_backend_info command against the mint server
+///
+/// This function sends an RPC request to retrieve health and identity information
+/// of every payment backend configured on the mint.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+pub async fn get_backend_info(client: &mut CdkMintClient<Channel>) -> Result<()> {
+    let response = client
+        .get_backend_info(Request::new(GetBackendInfoRequest {}))
+        .await?
+        .into_inner();
+
+    if response.backends.is_empty() {
+        println!("No payment backend reported any information");
+        return Ok(());
+    }
+
+    for backend in response.backends {
+        println!("{} {}:", backend.unit, backend.method);
+        println!("  Backend: {}", backend.backend);
+        println!("  Connected: {}", backend.connected);
+        println!(
+            "  Version: {}",
+            backend.version.unwrap_or("None".to_string())
+        );
+        println!(
+            "  Node pubkey: {}",
+            backend.node_pubkey.unwrap_or("None".to_string())
+        );
+        println!(
+            "  Node alias: {}",
+            backend.node_alias.unwrap_or("None".to_string())
+        );
+        match backend.balance {
+            Some(balance) => println!(
+                "  Balance: {} {}",
+                balance,
+                backend.balance_unit.unwrap_or_default()
+            ),
+            None => println!("  Balance: None"),
+        }
+    }
+
+    Ok(())
+}
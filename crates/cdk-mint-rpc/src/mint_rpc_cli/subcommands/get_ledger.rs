@@ -0,0 +1,49 @@
+use anyhow::Result;
+use tonic::transport::Channel;
+use tonic::Request;
+
+use crate::cdk_mint_client::CdkMintClient;
+use crate::GetLedgerRequest;
+
+/// Executes the get_ledger command against the mint server
+///
+/// This function sends an RPC request to retrieve the balances of the mint's
+/// double-entry ledger and whether they account for the ecash outstanding in the database.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+pub async fn get_ledger(client: &mut CdkMintClient<Channel>) -> Result<()> {
+    let response = client
+        .get_ledger(Request::new(GetLedgerRequest {}))
+        .await?
+        .into_inner();
+
+    if response.balances.is_empty() {
+        println!("No transactions recorded");
+        return Ok(());
+    }
+
+    for balance in response.balances {
+        println!("{}:", balance.unit);
+        match balance.outstanding {
+            Some(outstanding) => println!("  Outstanding ecash: {outstanding}"),
+            None => println!("  Outstanding ecash: negative"),
+        }
+        match balance.database_outstanding {
+            Some(outstanding) => println!("  Outstanding ecash in database: {outstanding}"),
+            None => println!("  Outstanding ecash in database: negative"),
+        }
+        if !balance.consistent {
+            println!("  Ledger does not match the database");
+        }
+
+        for account in balance.accounts {
+            println!(
+                "  {}: debits {}, credits {}",
+                account.account, account.debits, account.credits
+            );
+        }
+    }
+
+    Ok(())
+}
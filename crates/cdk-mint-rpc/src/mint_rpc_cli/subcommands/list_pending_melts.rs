@@ -0,0 +1,43 @@
+use anyhow::Result;
+use tonic::transport::Channel;
+use tonic::Request;
+
+use crate::cdk_mint_client::CdkMintClient;
+use crate::ListPendingMeltsRequest;
+
+/// Executes the list_pending_melts command against the mint server
+///
+/// This function sends an RPC request to retrieve the melt quotes whose payment
+/// is pending or unknown, such as payments left in flight by a backend outage.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+pub async fn list_pending_melts(client: &mut CdkMintClient<Channel>) -> Result<()> {
+    let response = client
+        .list_pending_melts(Request::new(ListPendingMeltsRequest {}))
+        .await?
+        .into_inner();
+
+    if response.quotes.is_empty() {
+        println!("No pending melt quotes");
+        return Ok(());
+    }
+
+    for quote in response.quotes {
+        println!(
+            "{}: {} {} (fee reserve {}), {}, created {}",
+            quote.quote_id,
+            quote.amount,
+            quote.unit,
+            quote.fee_reserve,
+            quote.state,
+            quote.created_time
+        );
+        println!("  request: {}", quote.request);
+        if let Some(request_lookup_id) = quote.request_lookup_id {
+            println!("  lookup id: {request_lookup_id}");
+        }
+    }
+
+    Ok(())
+}
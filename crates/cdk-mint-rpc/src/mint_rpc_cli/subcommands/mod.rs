@@ -1,3 +1,13 @@
+/// Module for checking a quote with the payment backend
+mod check_quote;
+/// Module for exporting an audit of the mint
+mod export_audit;
+/// Module for getting payment backend information
+mod get_backend_info;
+/// Module for getting the mint's ledger balances
+mod get_ledger;
+/// Module for listing pending melt quotes
+mod list_pending_melts;
 /// Module for rotating to the next keyset
 mod rotate_next_keyset;
 /// Module for updating mint contact information
@@ -23,6 +33,11 @@ mod update_ttl;
 /// Module for managing mint URLs
 mod update_urls;
 
+pub use check_quote::{check_quote, CheckQuoteCommand};
+pub use export_audit::{export_audit, ExportAuditCommand};
+pub use get_backend_info::get_backend_info;
+pub use get_ledger::get_ledger;
+pub use list_pending_melts::list_pending_melts;
 pub use rotate_next_keyset::{rotate_next_keyset, RotateNextKeysetCommand};
 pub use update_contact::{add_contact, remove_contact, AddContactCommand, RemoveContactCommand};
 pub use update_icon_url::{update_icon_url, UpdateIconUrlCommand};
@@ -22,6 +22,9 @@ pub struct RotateNextKeysetCommand {
     /// The input fee in parts per thousand to apply when minting with this keyset
     #[arg(short, long)]
     input_fee_ppk: Option<u64>,
+    /// Unix timestamp after which the keyset expires, advertised to wallets
+    #[arg(long)]
+    final_expiry: Option<u64>,
 }
 
 /// Executes the rotate_next_keyset command against the mint server
@@ -41,6 +44,7 @@ pub async fn rotate_next_keyset(
             unit: sub_command_args.unit.clone(),
             max_order: sub_command_args.max_order.map(|m| m.into()),
             input_fee_ppk: sub_command_args.input_fee_ppk,
+            final_expiry: sub_command_args.final_expiry,
         }))
         .await?;
 
@@ -51,5 +55,9 @@ pub async fn rotate_next_keyset(
         response.id, response.unit, response.max_order, response.input_fee_ppk
     );
 
+    if let Some(final_expiry) = response.final_expiry {
+        println!("The keyset expires at {final_expiry}");
+    }
+
     Ok(())
 }
@@ -6,8 +6,9 @@ use std::sync::Arc;
 use cdk::mint::{Mint, MintQuote};
 use cdk::nuts::nut04::MintMethodSettings;
 use cdk::nuts::nut05::MeltMethodSettings;
-use cdk::nuts::{CurrencyUnit, MintQuoteState, PaymentMethod};
+use cdk::nuts::{CurrencyUnit, MeltQuoteState, MintQuoteState, PaymentMethod};
 use cdk::types::QuoteTTL;
+use cdk::util::unix_time;
 use cdk::Amount;
 use cdk_common::payment::WaitPaymentResponse;
 use thiserror::Error;
@@ -19,8 +20,11 @@ use tonic::{Request, Response, Status};
 
 use crate::cdk_mint_server::{CdkMint, CdkMintServer};
 use crate::{
-    ContactInfo, GetInfoRequest, GetInfoResponse, GetQuoteTtlRequest, GetQuoteTtlResponse,
-    RotateNextKeysetRequest, RotateNextKeysetResponse, UpdateContactRequest,
+    BackendInfo, CheckQuoteRequest, CheckQuoteResponse, ContactInfo, ExportAuditRequest,
+    ExportAuditResponse, GetBackendInfoRequest, GetBackendInfoResponse, GetInfoRequest,
+    GetInfoResponse, GetLedgerRequest, GetLedgerResponse, GetQuoteTtlRequest, GetQuoteTtlResponse,
+    KeysetAudit, LedgerAccount, LedgerBalance, ListPendingMeltsRequest, ListPendingMeltsResponse,
+    PendingMelt, RotateNextKeysetRequest, RotateNextKeysetResponse, UpdateContactRequest,
     UpdateDescriptionRequest, UpdateIconUrlRequest, UpdateMotdRequest, UpdateNameRequest,
     UpdateNut04QuoteRequest, UpdateNut04Request, UpdateNut05Request, UpdateQuoteTtlRequest,
     UpdateResponse, UpdateUrlRequest,
@@ -732,10 +736,11 @@ impl CdkMint for MintRPCServer {
 
         let keyset_info = self
             .mint
-            .rotate_keyset(
+            .rotate_keyset_with_expiry(
                 unit,
                 request.max_order.map(|a| a as u8).unwrap_or(32),
                 request.input_fee_ppk.unwrap_or(0),
+                request.final_expiry,
             )
             .await
             .map_err(|_| Status::invalid_argument("Could not rotate keyset".to_string()))?;
@@ -745,6 +750,199 @@ impl CdkMint for MintRPCServer {
             unit: keyset_info.unit.to_string(),
             max_order: keyset_info.max_order.into(),
             input_fee_ppk: keyset_info.input_fee_ppk,
+            final_expiry: keyset_info.final_expiry,
         }))
     }
+
+    /// Gets health and identity information of the mint's payment backends
+    async fn get_backend_info(
+        &self,
+        _request: Request<GetBackendInfoRequest>,
+    ) -> Result<Response<GetBackendInfoResponse>, Status> {
+        let backends = self
+            .mint
+            .backend_info()
+            .await
+            .into_iter()
+            .map(|(key, info)| BackendInfo {
+                unit: key.unit.to_string(),
+                method: key.method.to_string(),
+                backend: info.backend,
+                version: info.version,
+                node_pubkey: info.node_pubkey,
+                node_alias: info.node_alias,
+                balance: info.balance.map(u64::from),
+                balance_unit: info.balance_unit.map(|unit| unit.to_string()),
+                connected: info.connected,
+            })
+            .collect();
+
+        Ok(Response::new(GetBackendInfoResponse { backends }))
+    }
+
+    /// Gets the balances of the mint's double-entry ledger
+    async fn get_ledger(
+        &self,
+        _request: Request<GetLedgerRequest>,
+    ) -> Result<Response<GetLedgerResponse>, Status> {
+        Ok(Response::new(GetLedgerResponse {
+            balances: self.ledger_balances().await?,
+        }))
+    }
+
+    /// Lists the melt quotes whose payment is pending or unknown
+    async fn list_pending_melts(
+        &self,
+        _request: Request<ListPendingMeltsRequest>,
+    ) -> Result<Response<ListPendingMeltsResponse>, Status> {
+        let quotes = self
+            .mint
+            .melt_quotes()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+            .into_iter()
+            .filter(|quote| {
+                quote.state == MeltQuoteState::Pending || quote.state == MeltQuoteState::Unknown
+            })
+            .map(|quote| PendingMelt {
+                quote_id: quote.id.to_string(),
+                unit: quote.unit.to_string(),
+                amount: quote.amount.into(),
+                fee_reserve: quote.fee_reserve.into(),
+                state: quote.state.to_string(),
+                request: quote.request.to_string(),
+                created_time: quote.created_time,
+                request_lookup_id: quote.request_lookup_id.map(|id| id.to_string()),
+            })
+            .collect();
+
+        Ok(Response::new(ListPendingMeltsResponse { quotes }))
+    }
+
+    /// Checks a quote with the payment backend and updates its state
+    async fn check_quote(
+        &self,
+        request: Request<CheckQuoteRequest>,
+    ) -> Result<Response<CheckQuoteResponse>, Status> {
+        let request = request.into_inner();
+        let quote_id = request
+            .quote_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid quote id".to_string()))?;
+
+        let state = match request.kind.as_str() {
+            "mint" => {
+                self.mint
+                    .check_mint_quote(&quote_id)
+                    .await
+                    .map_err(|err| Status::internal(err.to_string()))?;
+
+                self.mint
+                    .localstore()
+                    .get_mint_quote(&quote_id)
+                    .await
+                    .map_err(|err| Status::internal(err.to_string()))?
+                    .ok_or(Status::invalid_argument("Could not find quote".to_string()))?
+                    .state()
+                    .to_string()
+            }
+            "melt" => self
+                .mint
+                .check_pending_melt_quote(&quote_id)
+                .await
+                .map_err(|err| match err {
+                    cdk::Error::PendingQuote => Status::failed_precondition(
+                        "Melt quote payment is in flight, it cannot be checked".to_string(),
+                    ),
+                    err => Status::internal(err.to_string()),
+                })?
+                .to_string(),
+            _ => {
+                return Err(Status::invalid_argument(
+                    "Quote kind must be mint or melt".to_string(),
+                ))
+            }
+        };
+
+        Ok(Response::new(CheckQuoteResponse {
+            quote_id: quote_id.to_string(),
+            state,
+        }))
+    }
+
+    /// Exports the issued and redeemed totals of every keyset with the ledger balances
+    async fn export_audit(
+        &self,
+        _request: Request<ExportAuditRequest>,
+    ) -> Result<Response<ExportAuditResponse>, Status> {
+        let total_issued = self
+            .mint
+            .total_issued()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        let total_redeemed = self
+            .mint
+            .total_redeemed()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        let keysets = self
+            .mint
+            .keysets()
+            .keysets
+            .into_iter()
+            .map(|keyset| KeysetAudit {
+                id: keyset.id.to_string(),
+                unit: keyset.unit.to_string(),
+                active: keyset.active,
+                issued: total_issued
+                    .get(&keyset.id)
+                    .copied()
+                    .unwrap_or_default()
+                    .into(),
+                redeemed: total_redeemed
+                    .get(&keyset.id)
+                    .copied()
+                    .unwrap_or_default()
+                    .into(),
+            })
+            .collect();
+
+        Ok(Response::new(ExportAuditResponse {
+            keysets,
+            ledger: self.ledger_balances().await?,
+            timestamp: unix_time(),
+        }))
+    }
+}
+
+impl MintRPCServer {
+    /// Balances of the mint's double-entry ledger
+    async fn ledger_balances(&self) -> Result<Vec<LedgerBalance>, Status> {
+        let balances = self
+            .mint
+            .ledger_balances()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(balances
+            .into_iter()
+            .map(|balance| LedgerBalance {
+                consistent: balance.is_consistent(),
+                unit: balance.unit.to_string(),
+                accounts: balance
+                    .accounts
+                    .into_iter()
+                    .map(|(account, totals)| LedgerAccount {
+                        account: account.to_string(),
+                        debits: totals.debits.into(),
+                        credits: totals.credits.into(),
+                    })
+                    .collect(),
+                outstanding: balance.outstanding.map(u64::from),
+                database_outstanding: balance.database_outstanding.map(u64::from),
+            })
+            .collect())
+    }
 }
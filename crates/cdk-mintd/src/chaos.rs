@@ -0,0 +1,342 @@
+//! Chaos payment backend
+//!
+//! Wraps a real payment backend and injects latency, random failures and
+//! delayed settlement so operators can exercise their monitoring and the
+//! mint's recovery logic before going to production.
+
+use std::pin::Pin;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bitcoin::secp256k1::rand::{thread_rng, Rng};
+use cdk_common::payment::{
+    BackendInfo, CreateIncomingPaymentResponse, DynMintPayment, Error, Event,
+    IncomingPaymentOptions, MakePaymentResponse, MintPayment, OutgoingPaymentOptions,
+    PaymentIdentifier, PaymentQuoteResponse, WaitPaymentResponse,
+};
+use cdk_common::{Amount, CurrencyUnit};
+use futures::{Stream, StreamExt};
+
+use crate::config::Chaos;
+
+/// Payment events held back by the settlement delay at the same time
+const MAX_DELAYED_EVENTS: usize = 1_000;
+
+/// Payment backend wrapper that misbehaves on purpose
+pub struct ChaosMintPayment {
+    inner: DynMintPayment,
+    config: Chaos,
+}
+
+impl ChaosMintPayment {
+    /// Wrap `inner` with the given chaos settings
+    pub fn new(inner: DynMintPayment, config: Chaos) -> Self {
+        tracing::warn!(
+            "Chaos mode enabled for payment backend: latency {}-{}ms, failure rate {}, settlement delay {}ms",
+            config.min_latency_ms,
+            config.max_latency_ms,
+            config.failure_rate,
+            config.settlement_delay_ms
+        );
+
+        Self { inner, config }
+    }
+
+    /// Sleep for a random duration within the configured latency range
+    async fn inject_latency(&self) {
+        let min = self.config.min_latency_ms;
+        let max = self.config.max_latency_ms.max(min);
+
+        let latency = match min == max {
+            true => min,
+            false => thread_rng().gen_range(min..=max),
+        };
+
+        if latency > 0 {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+    }
+
+    /// Add latency and fail the call with the configured probability
+    async fn inject_chaos(&self, operation: &str) -> Result<(), Error> {
+        self.inject_latency().await;
+
+        let failure_rate = self.config.failure_rate.clamp(0.0, 1.0);
+
+        if failure_rate > 0.0 && thread_rng().gen_bool(failure_rate) {
+            tracing::warn!("Chaos mode injected failure in {}", operation);
+            return Err(Error::Custom(format!("Chaos mode failure in {operation}")));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MintPayment for ChaosMintPayment {
+    type Err = Error;
+
+    async fn start(&self) -> Result<(), Self::Err> {
+        self.inner.start().await
+    }
+
+    async fn stop(&self) -> Result<(), Self::Err> {
+        self.inner.stop().await
+    }
+
+    async fn get_settings(&self) -> Result<serde_json::Value, Self::Err> {
+        self.inner.get_settings().await
+    }
+
+    async fn create_incoming_payment_request(
+        &self,
+        unit: &CurrencyUnit,
+        options: IncomingPaymentOptions,
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        self.inject_chaos("create_incoming_payment_request").await?;
+        self.inner
+            .create_incoming_payment_request(unit, options)
+            .await
+    }
+
+    async fn get_payment_quote(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<PaymentQuoteResponse, Self::Err> {
+        self.inject_chaos("get_payment_quote").await?;
+        self.inner.get_payment_quote(unit, options).await
+    }
+
+    async fn make_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.inject_chaos("make_payment").await?;
+        self.inner.make_payment(unit, options).await
+    }
+
+    async fn wait_payment_event(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+        let stream = self.inner.wait_payment_event().await?;
+        let delay = Duration::from_millis(self.config.settlement_delay_ms);
+
+        // Every event is delayed from when it arrives, so a burst of payments
+        // settles together instead of each waiting for the ones before it
+        match delay.is_zero() {
+            true => Ok(stream),
+            false => Ok(Box::pin(
+                stream
+                    .map(move |event| async move {
+                        tracing::debug!("Chaos mode delaying payment event by {:?}", delay);
+                        tokio::time::sleep(delay).await;
+                        event
+                    })
+                    .buffer_unordered(MAX_DELAYED_EVENTS),
+            )),
+        }
+    }
+
+    fn is_wait_invoice_active(&self) -> bool {
+        self.inner.is_wait_invoice_active()
+    }
+
+    fn cancel_wait_invoice(&self) {
+        self.inner.cancel_wait_invoice()
+    }
+
+    async fn check_incoming_payment_status(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+        self.inject_chaos("check_incoming_payment_status").await?;
+        self.inner
+            .check_incoming_payment_status(payment_identifier)
+            .await
+    }
+
+    async fn check_outgoing_payment(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.inject_chaos("check_outgoing_payment").await?;
+        self.inner.check_outgoing_payment(payment_identifier).await
+    }
+
+    async fn settle_internally(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<Option<MakePaymentResponse>, Self::Err> {
+        self.inner.settle_internally(unit, options).await
+    }
+
+    async fn probe_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<Option<Amount>, Self::Err> {
+        self.inject_chaos("probe_payment").await?;
+        self.inner.probe_payment(unit, options).await
+    }
+
+    async fn backend_info(&self) -> Result<Option<BackendInfo>, Self::Err> {
+        self.inner.backend_info().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use cdk_common::payment::Bolt11OutgoingPaymentOptions;
+    use futures::stream;
+    use tokio::time::Instant;
+
+    use super::*;
+
+    /// Backend that answers every quote and emits a fixed set of payment events
+    struct TestBackend {
+        events: usize,
+    }
+
+    #[async_trait]
+    impl MintPayment for TestBackend {
+        type Err = Error;
+
+        async fn get_settings(&self) -> Result<serde_json::Value, Self::Err> {
+            Ok(serde_json::Value::Null)
+        }
+
+        async fn create_incoming_payment_request(
+            &self,
+            _unit: &CurrencyUnit,
+            _options: IncomingPaymentOptions,
+        ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+            Err(Error::UnsupportedPaymentOption)
+        }
+
+        async fn get_payment_quote(
+            &self,
+            unit: &CurrencyUnit,
+            _options: OutgoingPaymentOptions,
+        ) -> Result<PaymentQuoteResponse, Self::Err> {
+            Ok(PaymentQuoteResponse {
+                request_lookup_id: None,
+                amount: Amount::from(10),
+                fee: Amount::ZERO,
+                unit: unit.clone(),
+                state: cdk_common::nuts::MeltQuoteState::Unpaid,
+            })
+        }
+
+        async fn make_payment(
+            &self,
+            _unit: &CurrencyUnit,
+            _options: OutgoingPaymentOptions,
+        ) -> Result<MakePaymentResponse, Self::Err> {
+            Err(Error::UnsupportedPaymentOption)
+        }
+
+        async fn wait_payment_event(
+            &self,
+        ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+            Ok(Box::pin(stream::iter(
+                (0..self.events).map(|_| Event::default()),
+            )))
+        }
+
+        fn is_wait_invoice_active(&self) -> bool {
+            false
+        }
+
+        fn cancel_wait_invoice(&self) {}
+
+        async fn check_incoming_payment_status(
+            &self,
+            _payment_identifier: &PaymentIdentifier,
+        ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+            Ok(vec![])
+        }
+
+        async fn check_outgoing_payment(
+            &self,
+            _payment_identifier: &PaymentIdentifier,
+        ) -> Result<MakePaymentResponse, Self::Err> {
+            Err(Error::UnsupportedPaymentOption)
+        }
+    }
+
+    fn chaos_backend(config: Chaos) -> ChaosMintPayment {
+        ChaosMintPayment::new(Arc::new(TestBackend { events: 3 }), config)
+    }
+
+    fn outgoing_options() -> OutgoingPaymentOptions {
+        OutgoingPaymentOptions::Bolt11(Box::new(Bolt11OutgoingPaymentOptions {
+            bolt11: "lnbc100n1p3kdrv5sp5lpdxzghe5j67q9sw3yvs7mkkhc2y2fhu4vwq2ytcpkw3yg5ssf7qpp5cmkvqyvhgu9q8hunmgfzt5nf6d2w5wxamzxmqt2svnc08ta9f0ksdqlw3jhxapqd9h8vmmfvdjjqarfvdjxzqr5v9mkzqrrdanxvet9wgsrsv3hpwd3x8m8xtwfpzmaekdlnkrmj6yhxqmhtzvmxvs".parse().unwrap(),
+            max_fee_amount: None,
+            timeout_secs: None,
+            melt_options: None,
+            idempotency_key: None,
+        }))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_settlement_delay_is_not_serial() {
+        let backend = chaos_backend(Chaos {
+            enabled: true,
+            settlement_delay_ms: 1_000,
+            ..Default::default()
+        });
+
+        let started = Instant::now();
+        let events: Vec<Event> = backend.wait_payment_event().await.unwrap().collect().await;
+
+        assert_eq!(events.len(), 3);
+        assert!(started.elapsed() >= Duration::from_millis(1_000));
+        assert!(started.elapsed() < Duration::from_millis(2_000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_latency_within_range() {
+        let backend = chaos_backend(Chaos {
+            enabled: true,
+            min_latency_ms: 200,
+            max_latency_ms: 300,
+            ..Default::default()
+        });
+
+        let started = Instant::now();
+        backend
+            .get_payment_quote(&CurrencyUnit::Sat, outgoing_options())
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert!(started.elapsed() <= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_failure_rate() {
+        let failing = chaos_backend(Chaos {
+            enabled: true,
+            failure_rate: 1.0,
+            ..Default::default()
+        });
+        assert!(failing
+            .get_payment_quote(&CurrencyUnit::Sat, outgoing_options())
+            .await
+            .is_err());
+
+        let passing = chaos_backend(Chaos {
+            enabled: true,
+            ..Default::default()
+        });
+        assert!(passing
+            .get_payment_quote(&CurrencyUnit::Sat, outgoing_options())
+            .await
+            .is_ok());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use bitcoin::hashes::{sha256, Hash};
@@ -6,18 +7,22 @@ use cdk::Amount;
 use cdk_axum::cache;
 use cdk_common::common::QuoteTTL;
 use config::{Config, ConfigError, File};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LoggingOutput {
     /// Log to stderr only
+    #[serde(alias = "stdout")]
     Stderr,
     /// Log to file only
     File,
     /// Log to both stderr and file (default)
     #[default]
     Both,
+    /// Log to the systemd journal
+    Journald,
 }
 
 impl std::str::FromStr for LoggingOutput {
@@ -25,28 +30,67 @@ impl std::str::FromStr for LoggingOutput {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "stderr" => Ok(LoggingOutput::Stderr),
+            "stderr" | "stdout" => Ok(LoggingOutput::Stderr),
             "file" => Ok(LoggingOutput::File),
             "both" => Ok(LoggingOutput::Both),
+            "journald" => Ok(LoggingOutput::Journald),
             _ => Err(format!(
-                "Unknown logging output: {s}. Valid options: stdout, file, both"
+                "Unknown logging output: {s}. Valid options: stdout, file, both, journald"
             )),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingFormat {
+    /// Human readable lines (default)
+    #[default]
+    Pretty,
+    /// One JSON object per line
+    Json,
+}
+
+impl std::str::FromStr for LoggingFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pretty" => Ok(LoggingFormat::Pretty),
+            "json" => Ok(LoggingFormat::Json),
+            _ => Err(format!(
+                "Unknown logging format: {s}. Valid options: pretty, json"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct LoggingConfig {
-    /// Where to output logs: stdout, file, or both
+    /// Where to output logs: stdout, file, both or journald
     #[serde(default)]
     pub output: LoggingOutput,
-    /// Log level for console output (when stdout or both)
+    /// Log level for console output (when stdout or both) and journald
     pub console_level: Option<String>,
     /// Log level for file output (when file or both)
     pub file_level: Option<String>,
+    /// Format of console and file output: pretty or json
+    #[serde(default)]
+    pub format: LoggingFormat,
+    /// Log level per module, e.g. `cdk_sqlite = "warn"`
+    ///
+    /// Overrides the default levels of noisy dependencies.
+    #[serde(default)]
+    pub modules: BTreeMap<String, String>,
+    /// Size in MiB at which the log file is rotated
+    pub max_file_size_mb: Option<u64>,
+    /// Age in hours at which the log file is rotated (default: 24)
+    pub max_file_age_hours: Option<u64>,
+    /// Number of rotated log files kept, all are kept if unset
+    pub max_files: Option<usize>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Info {
     pub url: String,
     pub listen_host: String,
@@ -56,8 +100,19 @@ pub struct Info {
     pub mnemonic: Option<String>,
     pub signatory_url: Option<String>,
     pub signatory_certs: Option<String>,
+    /// Hex secret key used to sign the mint info
+    ///
+    /// The matching pubkey is served as the mint info pubkey and should be
+    /// published out of band so wallets can pin it.
+    pub identity_secret_key: Option<String>,
     pub input_fee_ppk: Option<u64>,
+    /// Unix timestamp after which the active keysets expire
+    ///
+    /// Active keysets with a different expiry are rotated on startup, so wallets
+    /// can move their proofs to a newer keyset before the expiry passes.
+    pub keyset_final_expiry: Option<u64>,
 
+    #[schemars(with = "serde_json::Value")]
     pub http_cache: cache::Config,
 
     /// Logging configuration
@@ -70,13 +125,41 @@ pub struct Info {
     /// This requires `mintd` was built with the `swagger` feature flag.
     pub enable_swagger_ui: Option<bool>,
 
+    /// When set, the requests served by the mint and their responses are appended
+    /// to this file, relative to the work dir, with witnesses and preimages
+    /// removed and proof secrets hashed. The recording can be replayed with
+    /// `replay_mint_traffic`.
+    pub request_recording_path: Option<PathBuf>,
+
+    /// Path the mint routes are served under, e.g. `/cashu`
+    ///
+    /// Set this when a reverse proxy forwards `https://example.com/cashu/v1/...`
+    /// to the mint without stripping the prefix. The mint `url` must include it.
+    pub path_prefix: Option<String>,
+
+    /// Seconds websocket notifications are kept for wallets resubscribing after
+    /// a reconnect, which then receive the notifications they missed
+    ///
+    /// No notifications are kept when unset.
+    pub notification_replay_secs: Option<u64>,
+
     /// Optional persisted quote TTL values (seconds) to initialize the database with
     /// when RPC is disabled or on first-run when RPC is enabled.
     /// If not provided, defaults are used.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<QuoteTtlSchema>")]
     pub quote_ttl: Option<QuoteTTL>,
 }
 
+/// Schema of [`QuoteTTL`] in the config file, [`QuoteTTL`] lives outside this crate
+#[derive(Debug, Clone, Copy, JsonSchema)]
+pub struct QuoteTtlSchema {
+    /// Seconds mint quote is valid
+    pub mint_ttl: u64,
+    /// Seconds melt quote is valid
+    pub melt_ttl: u64,
+}
+
 impl Default for Info {
     fn default() -> Self {
         Info {
@@ -87,9 +170,14 @@ impl Default for Info {
             mnemonic: None,
             signatory_url: None,
             signatory_certs: None,
+            identity_secret_key: None,
             input_fee_ppk: None,
+            keyset_final_expiry: None,
             http_cache: cache::Config::default(),
             enable_swagger_ui: None,
+            request_recording_path: None,
+            path_prefix: None,
+            notification_replay_secs: None,
             logging: LoggingConfig::default(),
             quote_ttl: None,
         }
@@ -114,14 +202,18 @@ impl std::fmt::Debug for Info {
             .field("listen_port", &self.listen_port)
             .field("mnemonic", &mnemonic_display)
             .field("input_fee_ppk", &self.input_fee_ppk)
+            .field("keyset_final_expiry", &self.keyset_final_expiry)
             .field("http_cache", &self.http_cache)
             .field("logging", &self.logging)
             .field("enable_swagger_ui", &self.enable_swagger_ui)
+            .field("request_recording_path", &self.request_recording_path)
+            .field("path_prefix", &self.path_prefix)
+            .field("notification_replay_secs", &self.notification_replay_secs)
             .finish()
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LnBackend {
     #[default]
@@ -162,13 +254,17 @@ impl std::str::FromStr for LnBackend {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Ln {
     pub ln_backend: LnBackend,
     pub invoice_description: Option<String>,
+    #[schemars(with = "u64")]
     pub min_mint: Amount,
+    #[schemars(with = "u64")]
     pub max_mint: Amount,
+    #[schemars(with = "u64")]
     pub min_melt: Amount,
+    #[schemars(with = "u64")]
     pub max_melt: Amount,
 }
 
@@ -186,44 +282,60 @@ impl Default for Ln {
 }
 
 #[cfg(feature = "lnbits")]
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct LNbits {
     pub admin_api_key: String,
     pub invoice_api_key: String,
     pub lnbits_api: String,
     pub fee_percent: f32,
+    #[schemars(with = "u64")]
     pub reserve_fee_min: Amount,
+    /// Melt fee reserve strategy, in place of `fee_percent` and `reserve_fee_min`
+    #[serde(default)]
+    pub fee_reserve: Option<FeeReserve>,
 }
 
 #[cfg(feature = "cln")]
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Cln {
     pub rpc_path: PathBuf,
     #[serde(default)]
     pub bolt12: bool,
     pub fee_percent: f32,
+    #[schemars(with = "u64")]
     pub reserve_fee_min: Amount,
+    /// Melt fee reserve strategy, in place of `fee_percent` and `reserve_fee_min`
+    #[serde(default)]
+    pub fee_reserve: Option<FeeReserve>,
 }
 
 #[cfg(feature = "lnd")]
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Lnd {
     pub address: String,
     pub cert_file: PathBuf,
     pub macaroon_file: PathBuf,
     pub fee_percent: f32,
+    #[schemars(with = "u64")]
     pub reserve_fee_min: Amount,
+    /// Melt fee reserve strategy, in place of `fee_percent` and `reserve_fee_min`
+    #[serde(default)]
+    pub fee_reserve: Option<FeeReserve>,
 }
 
 #[cfg(feature = "ldk-node")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LdkNode {
     /// Fee percentage (e.g., 0.02 for 2%)
     #[serde(default = "default_ldk_fee_percent")]
     pub fee_percent: f32,
     /// Minimum reserve fee
     #[serde(default = "default_ldk_reserve_fee_min")]
+    #[schemars(with = "u64")]
     pub reserve_fee_min: Amount,
+    /// Melt fee reserve strategy, in place of `fee_percent` and `reserve_fee_min`
+    #[serde(default)]
+    pub fee_reserve: Option<FeeReserve>,
     /// Bitcoin network (mainnet, testnet, signet, regtest)
     pub bitcoin_network: Option<String>,
     /// Chain source type (esplora or bitcoinrpc)
@@ -259,6 +371,7 @@ impl Default for LdkNode {
         Self {
             fee_percent: default_ldk_fee_percent(),
             reserve_fee_min: default_ldk_reserve_fee_min(),
+            fee_reserve: None,
             bitcoin_network: None,
             chain_source_type: None,
             esplora_url: None,
@@ -298,11 +411,16 @@ fn default_webserver_port() -> Option<u16> {
 }
 
 #[cfg(feature = "fakewallet")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FakeWallet {
+    #[schemars(with = "Vec<String>")]
     pub supported_units: Vec<CurrencyUnit>,
     pub fee_percent: f32,
+    #[schemars(with = "u64")]
     pub reserve_fee_min: Amount,
+    /// Melt fee reserve strategy, in place of `fee_percent` and `reserve_fee_min`
+    #[serde(default)]
+    pub fee_reserve: Option<FeeReserve>,
     #[serde(default = "default_min_delay_time")]
     pub min_delay_time: u64,
     #[serde(default = "default_max_delay_time")]
@@ -316,6 +434,7 @@ impl Default for FakeWallet {
             supported_units: vec![CurrencyUnit::Sat],
             fee_percent: 0.02,
             reserve_fee_min: 2.into(),
+            fee_reserve: None,
             min_delay_time: 1,
             max_delay_time: 3,
         }
@@ -333,15 +452,139 @@ fn default_max_delay_time() -> u64 {
     3
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
 pub struct GrpcProcessor {
+    #[schemars(with = "Vec<String>")]
     pub supported_units: Vec<CurrencyUnit>,
     pub addr: String,
     pub port: u16,
     pub tls_dir: Option<PathBuf>,
+    /// Melt fee reserve strategy, in place of the reserve quoted by the processor
+    #[serde(default)]
+    pub fee_reserve: Option<FeeReserve>,
+}
+
+/// Strategy setting the fee reserve of melt quotes
+///
+/// Amounts are in the unit of the backend.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum FeeReserveStrategy {
+    /// Reserve quoted by the backend
+    #[default]
+    Backend,
+    /// Same reserve for every amount
+    Flat {
+        #[schemars(with = "u64")]
+        fee: Amount,
+    },
+    /// Percent of the amount with a minimum
+    Percent {
+        /// Share of the amount (e.g., 0.02 for 2%)
+        percent: f32,
+        #[schemars(with = "u64")]
+        min: Amount,
+    },
+    /// Routing fee found by probing a route, for backends that support it
+    RouteProbe {
+        /// Share added on top of the probed fee (e.g., 0.5 for 50%)
+        #[serde(default = "default_fee_reserve_headroom")]
+        headroom: f32,
+        #[schemars(with = "u64")]
+        min: Amount,
+    },
+    /// Percent and minimum depending on the amount
+    Tiered { tiers: Vec<FeeReserveTier> },
+}
+
+/// Tier of the `tiered` fee reserve strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FeeReserveTier {
+    /// Largest amount of the tier, every amount when not set
+    #[schemars(with = "Option<u64>")]
+    pub max_amount: Option<Amount>,
+    pub percent: f32,
+    #[schemars(with = "u64")]
+    pub min: Amount,
+}
+
+/// Tuning of fee reserves from the fees paid by the mint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FeeReserveFeedback {
+    /// Payments whose fees are kept
+    #[serde(default = "default_fee_reserve_window")]
+    pub window: usize,
+    /// Payments observed before reserves are tuned
+    #[serde(default = "default_fee_reserve_min_samples")]
+    pub min_samples: usize,
+    /// Share added on top of the highest observed fee rate
+    #[serde(default = "default_fee_reserve_headroom")]
+    pub headroom: f32,
+}
+
+fn default_fee_reserve_window() -> usize {
+    cdk::mint::FeeReserveFeedback::default().window
+}
+
+fn default_fee_reserve_min_samples() -> usize {
+    cdk::mint::FeeReserveFeedback::default().min_samples
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+fn default_fee_reserve_headroom() -> f32 {
+    cdk::mint::FeeReserveFeedback::default().headroom
+}
+
+impl Default for FeeReserveFeedback {
+    fn default() -> Self {
+        Self {
+            window: default_fee_reserve_window(),
+            min_samples: default_fee_reserve_min_samples(),
+            headroom: default_fee_reserve_headroom(),
+        }
+    }
+}
+
+/// Melt fee reserve settings of a payment backend
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FeeReserve {
+    #[serde(flatten)]
+    pub strategy: FeeReserveStrategy,
+    /// Lower reserves towards the fees actually paid, reducing the change returned
+    pub feedback: Option<FeeReserveFeedback>,
+}
+
+impl From<FeeReserveStrategy> for cdk::mint::FeeReserveStrategy {
+    fn from(strategy: FeeReserveStrategy) -> Self {
+        match strategy {
+            FeeReserveStrategy::Backend => Self::Backend,
+            FeeReserveStrategy::Flat { fee } => Self::Flat { fee },
+            FeeReserveStrategy::Percent { percent, min } => Self::Percent { percent, min },
+            FeeReserveStrategy::RouteProbe { headroom, min } => Self::RouteProbe { headroom, min },
+            FeeReserveStrategy::Tiered { tiers } => Self::Tiered {
+                tiers: tiers
+                    .into_iter()
+                    .map(|tier| cdk::mint::FeeReserveTier {
+                        max_amount: tier.max_amount,
+                        percent: tier.percent,
+                        min: tier.min,
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl From<FeeReserveFeedback> for cdk::mint::FeeReserveFeedback {
+    fn from(feedback: FeeReserveFeedback) -> Self {
+        Self {
+            window: feedback.window,
+            min_samples: feedback.min_samples,
+            headroom: feedback.headroom,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum DatabaseEngine {
     #[default]
@@ -361,18 +604,20 @@ impl std::str::FromStr for DatabaseEngine {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Database {
     pub engine: DatabaseEngine,
     pub postgres: Option<PostgresConfig>,
+    /// Queries slower than this are logged, defaults to 20 ms
+    pub slow_query_threshold_ms: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct AuthDatabase {
     pub postgres: Option<PostgresAuthConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PostgresAuthConfig {
     pub url: String,
     pub tls_mode: Option<String>,
@@ -391,12 +636,19 @@ impl Default for PostgresAuthConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PostgresConfig {
     pub url: String,
     pub tls_mode: Option<String>,
     pub max_connections: Option<usize>,
     pub connection_timeout_seconds: Option<u64>,
+    /// Read replica for quote and proof state checks, writes always go to `url`
+    pub read_replica_url: Option<String>,
+    /// Elect a leader between several mintd instances sharing the database
+    #[serde(default)]
+    pub leader_election: bool,
+    /// Seconds between leadership checks
+    pub leader_election_interval_seconds: Option<u64>,
 }
 
 impl Default for PostgresConfig {
@@ -406,11 +658,14 @@ impl Default for PostgresConfig {
             tls_mode: Some("disable".to_string()),
             max_connections: Some(20),
             connection_timeout_seconds: Some(10),
+            read_replica_url: None,
+            leader_election: false,
+            leader_election_interval_seconds: Some(5),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AuthType {
     Clear,
@@ -432,7 +687,7 @@ impl std::str::FromStr for AuthType {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Auth {
     #[serde(default)]
     pub auth_enabled: bool,
@@ -464,7 +719,7 @@ fn default_blind() -> AuthType {
 }
 
 /// CDK settings, derived from `config.toml`
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Settings {
     pub info: Info,
     pub mint_info: MintInfo,
@@ -488,9 +743,15 @@ pub struct Settings {
     pub auth: Option<Auth>,
     #[cfg(feature = "prometheus")]
     pub prometheus: Option<Prometheus>,
+    pub chaos: Option<Chaos>,
+    pub notifications: Option<Notifications>,
+    pub risk: Option<Risk>,
+    pub webhooks: Option<Webhooks>,
+    pub vouchers: Option<Vouchers>,
+    pub stats: Option<Stats>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[cfg(feature = "prometheus")]
 pub struct Prometheus {
     pub enabled: bool,
@@ -498,11 +759,313 @@ pub struct Prometheus {
     pub port: Option<u16>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Simulation mode that wraps the configured payment backend and injects
+/// latency, failures and delayed settlement.
+///
+/// **Never** enable this on a production mint.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct Chaos {
+    /// Wrap the payment backend with the chaos settings below. They are
+    /// ignored while this is false
+    pub enabled: bool,
+    /// Minimum latency added to every backend call in milliseconds
+    #[serde(default)]
+    pub min_latency_ms: u64,
+    /// Maximum latency added to every backend call in milliseconds
+    #[serde(default)]
+    pub max_latency_ms: u64,
+    /// Probability (0.0 - 1.0) that a backend call fails
+    #[serde(default)]
+    pub failure_rate: f64,
+    /// Delay in milliseconds before incoming payment events are forwarded to the mint
+    #[serde(default)]
+    pub settlement_delay_ms: u64,
+}
+
+/// Alerts sent to the operator when the mint detects a critical condition:
+/// a payment backend failing repeatedly, melt quotes left pending, more ecash
+/// redeemed than issued, or a failing database.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Notifications {
+    pub enabled: bool,
+    /// Urls alerts are posted to as JSON
+    #[serde(default)]
+    pub webhook_urls: Vec<String>,
+    /// Hex nostr pubkeys alerts are sent to as NIP-17 private direct messages
+    #[serde(default)]
+    pub nostr_pubkeys: Vec<String>,
+    /// Relays direct messages are published to
+    #[serde(default)]
+    pub nostr_relays: Vec<String>,
+    /// Hex nostr secret key direct messages are sent from, a new key on every start when unset
+    pub nostr_secret_key: Option<String>,
+    /// Seconds between two checks of the mint
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// Minimum seconds between two alerts of the same kind and source
+    #[serde(default = "default_min_alert_interval_secs")]
+    pub min_interval_secs: u64,
+    /// Alert message, `{mint}`, `{kind}` and `{message}` are replaced
+    #[serde(default = "default_alert_template")]
+    pub template: String,
+}
+
+fn default_check_interval_secs() -> u64 {
+    60
+}
+
+fn default_min_alert_interval_secs() -> u64 {
+    15 * 60
+}
+
+fn default_alert_template() -> String {
+    "[{mint}] {kind}: {message}".to_string()
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            webhook_urls: Vec::new(),
+            nostr_pubkeys: Vec::new(),
+            nostr_relays: Vec::new(),
+            nostr_secret_key: None,
+            check_interval_secs: default_check_interval_secs(),
+            min_interval_secs: default_min_alert_interval_secs(),
+            template: default_alert_template(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Notifications {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notifications")
+            .field("enabled", &self.enabled)
+            .field("webhook_urls", &self.webhook_urls)
+            .field("nostr_pubkeys", &self.nostr_pubkeys)
+            .field("nostr_relays", &self.nostr_relays)
+            .field(
+                "nostr_secret_key",
+                &self.nostr_secret_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("check_interval_secs", &self.check_interval_secs)
+            .field("min_interval_secs", &self.min_interval_secs)
+            .field("template", &self.template)
+            .finish()
+    }
+}
+
+/// External risk scoring service consulted before mint and melt quotes are
+/// created. Decisions are stored with the quote and logged to the `cdk::audit`
+/// target.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Risk {
+    pub enabled: bool,
+    /// Url the quote is posted to as JSON, answered with `{"allow", "score", "reason"}`
+    pub url: String,
+    /// Milliseconds to wait for a decision
+    #[serde(default = "default_risk_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Accept quotes when the service fails or times out, reject them otherwise
+    #[serde(default = "default_risk_fail_open")]
+    pub fail_open: bool,
+}
+
+fn default_risk_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_risk_fail_open() -> bool {
+    true
+}
+
+impl Default for Risk {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            timeout_ms: default_risk_timeout_ms(),
+            fail_open: default_risk_fail_open(),
+        }
+    }
+}
+
+/// Callback urls integrators register when creating quotes, notified of every
+/// state change of the quote instead of polling or keeping a websocket open.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Webhooks {
+    pub enabled: bool,
+    /// Keys integrators authenticate with, as `Authorization: Bearer <key>`
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// Secret notifications are signed with, sent as the hex HMAC-SHA256 of the body
+    pub signing_secret: String,
+    /// Seconds between two checks for state changes to deliver
+    #[serde(default = "default_webhook_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Attempts to deliver a state change before giving up
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    /// Seconds before the first retry, doubled on every further retry
+    #[serde(default = "default_webhook_retry_base_secs")]
+    pub retry_base_secs: u64,
+    /// Allow callback urls on loopback, private and link-local addresses
+    #[serde(default)]
+    pub allow_private_urls: bool,
+}
+
+fn default_webhook_poll_interval_secs() -> u64 {
+    5
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    8
+}
+
+fn default_webhook_retry_base_secs() -> u64 {
+    10
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_keys: Vec::new(),
+            signing_secret: String::new(),
+            poll_interval_secs: default_webhook_poll_interval_secs(),
+            max_attempts: default_webhook_max_attempts(),
+            retry_base_secs: default_webhook_retry_base_secs(),
+            allow_private_urls: false,
+        }
+    }
+}
+
+impl std::fmt::Debug for Webhooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Webhooks")
+            .field("enabled", &self.enabled)
+            .field("api_keys", &format!("<{} redacted>", self.api_keys.len()))
+            .field("signing_secret", &"<redacted>")
+            .field("poll_interval_secs", &self.poll_interval_secs)
+            .field("max_attempts", &self.max_attempts)
+            .field("retry_base_secs", &self.retry_base_secs)
+            .field("allow_private_urls", &self.allow_private_urls)
+            .finish()
+    }
+}
+
+/// Closed-loop vouchers of a custom unit, issued by the operator and redeemed
+/// by merchants instead of paid in and out through a payment backend.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Vouchers {
+    pub enabled: bool,
+    /// Unit of the vouchers, must not be a unit of the payment backend
+    #[schemars(with = "String")]
+    #[serde(default = "default_voucher_unit")]
+    pub unit: CurrencyUnit,
+    /// Input fee of the voucher keyset, in parts per thousand
+    #[serde(default)]
+    pub input_fee_ppk: u64,
+    /// Admin keys that issue vouchers and read reports, as `Authorization: Bearer <key>`
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// Merchant keys that can only redeem vouchers
+    #[serde(default)]
+    pub redeem_api_keys: Vec<String>,
+}
+
+fn default_voucher_unit() -> CurrencyUnit {
+    CurrencyUnit::Custom("point".to_string())
+}
+
+impl Default for Vouchers {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            unit: default_voucher_unit(),
+            input_fee_ppk: 0,
+            api_keys: Vec::new(),
+            redeem_api_keys: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Vouchers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Vouchers")
+            .field("enabled", &self.enabled)
+            .field("unit", &self.unit)
+            .field("input_fee_ppk", &self.input_fee_ppk)
+            .field("api_keys", &format!("<{} redacted>", self.api_keys.len()))
+            .field(
+                "redeem_api_keys",
+                &format!("<{} redacted>", self.redeem_api_keys.len()),
+            )
+            .finish()
+    }
+}
+
+/// Public `/v1/stats` endpoint with coarse aggregates for mint directories.
+/// Volumes are rounded down to buckets so single users cannot be told apart.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Stats {
+    pub enabled: bool,
+    /// Publish the uptime, rounded down to hours
+    #[serde(default = "default_stats_include_uptime")]
+    pub include_uptime: bool,
+    /// Publish the mint and melt volume of the last 30 days
+    #[serde(default = "default_stats_include_volume")]
+    pub include_volume: bool,
+    /// Volumes are rounded down to a power of this base
+    #[serde(default = "default_stats_volume_bucket_base")]
+    pub volume_bucket_base: u64,
+    /// Volumes below this amount are published as 0
+    #[serde(default = "default_stats_min_volume")]
+    pub min_volume: u64,
+    /// Seconds the published stats are cached
+    #[serde(default = "default_stats_refresh_secs")]
+    pub refresh_secs: u64,
+}
+
+fn default_stats_include_uptime() -> bool {
+    true
+}
+
+fn default_stats_include_volume() -> bool {
+    true
+}
+
+fn default_stats_volume_bucket_base() -> u64 {
+    10
+}
+
+fn default_stats_min_volume() -> u64 {
+    1000
+}
+
+fn default_stats_refresh_secs() -> u64 {
+    3600
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            include_uptime: default_stats_include_uptime(),
+            include_volume: default_stats_include_volume(),
+            volume_bucket_base: default_stats_volume_bucket_base(),
+            min_volume: default_stats_min_volume(),
+            refresh_secs: default_stats_refresh_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct MintInfo {
     /// name of the mint and should be recognizable
     pub name: String,
     /// hex pubkey of the mint
+    #[schemars(with = "Option<String>")]
     pub pubkey: Option<PublicKey>,
     /// short description of the mint
     pub description: String,
@@ -521,7 +1084,7 @@ pub struct MintInfo {
 }
 
 #[cfg(feature = "management-rpc")]
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct MintManagementRpc {
     /// When this is set to `true` the mint use the config file for the initial set up on first start.
     /// Changes to the `[mint_info]` after this **MUST** be made via the RPC changes to the config file or env vars will be ignored.
@@ -577,7 +1140,14 @@ impl Settings {
         let settings: Settings = config.try_deserialize()?;
 
         match settings.ln.ln_backend {
-            LnBackend::None => panic!("Ln backend must be set"),
+            // A closed-loop voucher mint needs no payment backend
+            LnBackend::None => assert!(
+                settings
+                    .vouchers
+                    .as_ref()
+                    .is_some_and(|vouchers| vouchers.enabled),
+                "Ln backend must be set"
+            ),
             #[cfg(feature = "cln")]
             LnBackend::Cln => assert!(
                 settings.cln.is_some(),
@@ -0,0 +1,46 @@
+//! Chaos mode environment variables
+
+use super::common::env_var;
+use crate::config::Chaos;
+
+pub const ENV_CHAOS_ENABLED: &str = "CDK_MINTD_CHAOS_ENABLED";
+pub const ENV_CHAOS_MIN_LATENCY_MS: &str = "CDK_MINTD_CHAOS_MIN_LATENCY_MS";
+pub const ENV_CHAOS_MAX_LATENCY_MS: &str = "CDK_MINTD_CHAOS_MAX_LATENCY_MS";
+pub const ENV_CHAOS_FAILURE_RATE: &str = "CDK_MINTD_CHAOS_FAILURE_RATE";
+pub const ENV_CHAOS_SETTLEMENT_DELAY_MS: &str = "CDK_MINTD_CHAOS_SETTLEMENT_DELAY_MS";
+
+impl Chaos {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(enabled_str) = env_var(ENV_CHAOS_ENABLED) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.enabled = enabled;
+            }
+        }
+
+        if let Ok(min_latency_str) = env_var(ENV_CHAOS_MIN_LATENCY_MS) {
+            if let Ok(min_latency) = min_latency_str.parse() {
+                self.min_latency_ms = min_latency;
+            }
+        }
+
+        if let Ok(max_latency_str) = env_var(ENV_CHAOS_MAX_LATENCY_MS) {
+            if let Ok(max_latency) = max_latency_str.parse() {
+                self.max_latency_ms = max_latency;
+            }
+        }
+
+        if let Ok(failure_rate_str) = env_var(ENV_CHAOS_FAILURE_RATE) {
+            if let Ok(failure_rate) = failure_rate_str.parse() {
+                self.failure_rate = failure_rate;
+            }
+        }
+
+        if let Ok(settlement_delay_str) = env_var(ENV_CHAOS_SETTLEMENT_DELAY_MS) {
+            if let Ok(settlement_delay) = settlement_delay_str.parse() {
+                self.settlement_delay_ms = settlement_delay;
+            }
+        }
+
+        self
+    }
+}
@@ -10,14 +10,93 @@ pub const ENV_SEED: &str = "CDK_MINTD_SEED";
 pub const ENV_MNEMONIC: &str = "CDK_MINTD_MNEMONIC";
 pub const ENV_SIGNATORY_URL: &str = "CDK_MINTD_SIGNATORY_URL";
 pub const ENV_SIGNATORY_CERTS: &str = "CDK_MINTD_SIGNATORY_CERTS";
+pub const ENV_IDENTITY_SECRET_KEY: &str = "CDK_MINTD_IDENTITY_SECRET_KEY";
 pub const ENV_SECONDS_QUOTE_VALID: &str = "CDK_MINTD_SECONDS_QUOTE_VALID";
 pub const ENV_CACHE_SECONDS: &str = "CDK_MINTD_CACHE_SECONDS";
 pub const ENV_EXTEND_CACHE_SECONDS: &str = "CDK_MINTD_EXTEND_CACHE_SECONDS";
 pub const ENV_INPUT_FEE_PPK: &str = "CDK_MINTD_INPUT_FEE_PPK";
+pub const ENV_KEYSET_FINAL_EXPIRY: &str = "CDK_MINTD_KEYSET_FINAL_EXPIRY";
 pub const ENV_QUOTE_TTL_MINT: &str = "CDK_MINTD_QUOTE_TTL_MINT";
 pub const ENV_QUOTE_TTL_MELT: &str = "CDK_MINTD_QUOTE_TTL_MELT";
 
 pub const ENV_ENABLE_SWAGGER: &str = "CDK_MINTD_ENABLE_SWAGGER";
+pub const ENV_REQUEST_RECORDING_PATH: &str = "CDK_MINTD_REQUEST_RECORDING_PATH";
+pub const ENV_PATH_PREFIX: &str = "CDK_MINTD_PATH_PREFIX";
+pub const ENV_NOTIFICATION_REPLAY_SECS: &str = "CDK_MINTD_NOTIFICATION_REPLAY_SECS";
 pub const ENV_LOGGING_OUTPUT: &str = "CDK_MINTD_LOGGING_OUTPUT";
 pub const ENV_LOGGING_CONSOLE_LEVEL: &str = "CDK_MINTD_LOGGING_CONSOLE_LEVEL";
 pub const ENV_LOGGING_FILE_LEVEL: &str = "CDK_MINTD_LOGGING_FILE_LEVEL";
+pub const ENV_LOGGING_FORMAT: &str = "CDK_MINTD_LOGGING_FORMAT";
+pub const ENV_LOGGING_MODULES: &str = "CDK_MINTD_LOGGING_MODULES";
+pub const ENV_LOGGING_MAX_FILE_SIZE_MB: &str = "CDK_MINTD_LOGGING_MAX_FILE_SIZE_MB";
+pub const ENV_LOGGING_MAX_FILE_AGE_HOURS: &str = "CDK_MINTD_LOGGING_MAX_FILE_AGE_HOURS";
+pub const ENV_LOGGING_MAX_FILES: &str = "CDK_MINTD_LOGGING_MAX_FILES";
+
+/// Name of the mint instance, used to scope environment variables
+///
+/// When set to e.g. `ALICE`, every `CDK_MINTD_<NAME>` variable is first looked up as
+/// `CDK_MINTD_ALICE_<NAME>` before falling back to the unscoped variable.
+pub const ENV_INSTANCE: &str = "CDK_MINTD_INSTANCE";
+
+const ENV_PREFIX: &str = "CDK_MINTD_";
+
+/// Instance name from [`ENV_INSTANCE`], upper-cased
+///
+/// Names may only contain ASCII letters, digits and underscores; anything else is ignored.
+pub fn instance_name() -> Option<String> {
+    let instance = std::env::var(ENV_INSTANCE).ok()?;
+
+    if instance.is_empty()
+        || !instance
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        tracing::warn!("Ignoring invalid {} value: {}", ENV_INSTANCE, instance);
+        return None;
+    }
+
+    Some(instance.to_uppercase())
+}
+
+/// Instance scoped name of a `CDK_MINTD_` environment variable
+pub fn instance_env_var_name(name: &str, instance: &str) -> Option<String> {
+    name.strip_prefix(ENV_PREFIX)
+        .map(|rest| format!("{ENV_PREFIX}{instance}_{rest}"))
+}
+
+/// Value of the instance scoped variant of an environment variable, if set
+pub fn instance_scoped_env_var(name: &str) -> Option<String> {
+    instance_name()
+        .and_then(|instance| instance_env_var_name(name, &instance))
+        .and_then(|scoped| std::env::var(scoped).ok())
+}
+
+/// Read an environment variable, preferring the instance scoped variant
+pub fn env_var(name: &str) -> Result<String, std::env::VarError> {
+    match instance_scoped_env_var(name) {
+        Some(value) => Ok(value),
+        None => std::env::var(name),
+    }
+}
+
+/// Comma separated list
+pub fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_env_var_name() {
+        assert_eq!(
+            instance_env_var_name(ENV_LISTEN_PORT, "ALICE"),
+            Some("CDK_MINTD_ALICE_LISTEN_PORT".to_string())
+        );
+        assert_eq!(instance_env_var_name("OTHER_VAR", "ALICE"), None);
+    }
+}
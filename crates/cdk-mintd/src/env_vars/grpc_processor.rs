@@ -1,9 +1,8 @@
 //! gRPC Payment Processor environment variables
 
-use std::env;
-
 use cdk::nuts::CurrencyUnit;
 
+use super::common::env_var;
 use crate::config::GrpcProcessor;
 
 // gRPC Payment Processor environment variables
@@ -15,7 +14,7 @@ pub const ENV_GRPC_PROCESSOR_TLS_DIR: &str = "CDK_MINTD_GRPC_PAYMENT_PROCESSOR_T
 
 impl GrpcProcessor {
     pub fn from_env(mut self) -> Self {
-        if let Ok(units_str) = env::var(ENV_GRPC_PROCESSOR_SUPPORTED_UNITS) {
+        if let Ok(units_str) = env_var(ENV_GRPC_PROCESSOR_SUPPORTED_UNITS) {
             if let Ok(units) = units_str
                 .split(',')
                 .map(|s| s.trim().parse())
@@ -25,17 +24,17 @@ impl GrpcProcessor {
             }
         }
 
-        if let Ok(addr) = env::var(ENV_GRPC_PROCESSOR_ADDRESS) {
+        if let Ok(addr) = env_var(ENV_GRPC_PROCESSOR_ADDRESS) {
             self.addr = addr;
         }
 
-        if let Ok(port) = env::var(ENV_GRPC_PROCESSOR_PORT) {
+        if let Ok(port) = env_var(ENV_GRPC_PROCESSOR_PORT) {
             if let Ok(port) = port.parse() {
                 self.port = port;
             }
         }
 
-        if let Ok(tls_dir) = env::var(ENV_GRPC_PROCESSOR_TLS_DIR) {
+        if let Ok(tls_dir) = env_var(ENV_GRPC_PROCESSOR_TLS_DIR) {
             self.tls_dir = Some(tls_dir.into());
         }
 
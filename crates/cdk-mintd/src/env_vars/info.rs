@@ -1,72 +1,95 @@
 //! Info environment variables
 
-use std::env;
 use std::str::FromStr;
 
 use cdk_common::common::QuoteTTL;
 
 use super::common::*;
-use crate::config::{Info, LoggingOutput};
+use crate::config::{Info, LoggingFormat, LoggingOutput};
 
 impl Info {
     pub fn from_env(mut self) -> Self {
         // Required fields
-        if let Ok(url) = env::var(ENV_URL) {
+        if let Ok(url) = env_var(ENV_URL) {
             self.url = url;
         }
 
-        if let Ok(host) = env::var(ENV_LISTEN_HOST) {
+        if let Ok(host) = env_var(ENV_LISTEN_HOST) {
             self.listen_host = host;
         }
 
-        if let Ok(port_str) = env::var(ENV_LISTEN_PORT) {
+        if let Ok(port_str) = env_var(ENV_LISTEN_PORT) {
             if let Ok(port) = port_str.parse() {
                 self.listen_port = port;
             }
         }
 
-        if let Ok(signatory_url) = env::var(ENV_SIGNATORY_URL) {
+        if let Ok(signatory_url) = env_var(ENV_SIGNATORY_URL) {
             self.signatory_url = Some(signatory_url);
         }
 
-        if let Ok(signatory_certs) = env::var(ENV_SIGNATORY_CERTS) {
+        if let Ok(signatory_certs) = env_var(ENV_SIGNATORY_CERTS) {
             self.signatory_certs = Some(signatory_certs);
         }
 
-        if let Ok(seed) = env::var(ENV_SEED) {
+        if let Ok(seed) = env_var(ENV_SEED) {
             self.seed = Some(seed);
         }
 
-        if let Ok(mnemonic) = env::var(ENV_MNEMONIC) {
+        if let Ok(mnemonic) = env_var(ENV_MNEMONIC) {
             self.mnemonic = Some(mnemonic);
         }
 
-        if let Ok(cache_seconds_str) = env::var(ENV_CACHE_SECONDS) {
+        if let Ok(identity_secret_key) = env_var(ENV_IDENTITY_SECRET_KEY) {
+            self.identity_secret_key = Some(identity_secret_key);
+        }
+
+        if let Ok(cache_seconds_str) = env_var(ENV_CACHE_SECONDS) {
             if let Ok(seconds) = cache_seconds_str.parse() {
                 self.http_cache.ttl = Some(seconds);
             }
         }
 
-        if let Ok(extend_cache_str) = env::var(ENV_EXTEND_CACHE_SECONDS) {
+        if let Ok(extend_cache_str) = env_var(ENV_EXTEND_CACHE_SECONDS) {
             if let Ok(seconds) = extend_cache_str.parse() {
                 self.http_cache.tti = Some(seconds);
             }
         }
 
-        if let Ok(fee_str) = env::var(ENV_INPUT_FEE_PPK) {
+        if let Ok(fee_str) = env_var(ENV_INPUT_FEE_PPK) {
             if let Ok(fee) = fee_str.parse() {
                 self.input_fee_ppk = Some(fee);
             }
         }
 
-        if let Ok(swagger_str) = env::var(ENV_ENABLE_SWAGGER) {
+        if let Ok(final_expiry_str) = env_var(ENV_KEYSET_FINAL_EXPIRY) {
+            if let Ok(final_expiry) = final_expiry_str.parse() {
+                self.keyset_final_expiry = Some(final_expiry);
+            }
+        }
+
+        if let Ok(swagger_str) = env_var(ENV_ENABLE_SWAGGER) {
             if let Ok(enable) = swagger_str.parse() {
                 self.enable_swagger_ui = Some(enable);
             }
         }
 
+        if let Ok(recording_path) = env_var(ENV_REQUEST_RECORDING_PATH) {
+            self.request_recording_path = Some(recording_path.into());
+        }
+
+        if let Ok(path_prefix) = env_var(ENV_PATH_PREFIX) {
+            self.path_prefix = Some(path_prefix);
+        }
+
+        if let Ok(replay_secs_str) = env_var(ENV_NOTIFICATION_REPLAY_SECS) {
+            if let Ok(replay_secs) = replay_secs_str.parse() {
+                self.notification_replay_secs = Some(replay_secs);
+            }
+        }
+
         // Logging configuration
-        if let Ok(output_str) = env::var(ENV_LOGGING_OUTPUT) {
+        if let Ok(output_str) = env_var(ENV_LOGGING_OUTPUT) {
             if let Ok(output) = LoggingOutput::from_str(&output_str) {
                 self.logging.output = output;
             } else {
@@ -77,25 +100,71 @@ impl Info {
             }
         }
 
-        if let Ok(console_level) = env::var(ENV_LOGGING_CONSOLE_LEVEL) {
+        if let Ok(console_level) = env_var(ENV_LOGGING_CONSOLE_LEVEL) {
             self.logging.console_level = Some(console_level);
         }
 
-        if let Ok(file_level) = env::var(ENV_LOGGING_FILE_LEVEL) {
+        if let Ok(file_level) = env_var(ENV_LOGGING_FILE_LEVEL) {
             self.logging.file_level = Some(file_level);
         }
 
+        if let Ok(format_str) = env_var(ENV_LOGGING_FORMAT) {
+            if let Ok(format) = LoggingFormat::from_str(&format_str) {
+                self.logging.format = format;
+            } else {
+                tracing::warn!(
+                    "Invalid logging format '{}' in environment variable. Valid options: pretty, json",
+                    format_str
+                );
+            }
+        }
+
+        // Comma separated `module=level` pairs
+        if let Ok(modules_str) = env_var(ENV_LOGGING_MODULES) {
+            for directive in modules_str.split(',').filter(|d| !d.trim().is_empty()) {
+                match directive.split_once('=') {
+                    Some((module, level)) => {
+                        self.logging
+                            .modules
+                            .insert(module.trim().to_string(), level.trim().to_string());
+                    }
+                    None => tracing::warn!(
+                        "Invalid logging module level '{}' in environment variable, expected module=level",
+                        directive
+                    ),
+                }
+            }
+        }
+
+        if let Ok(size_str) = env_var(ENV_LOGGING_MAX_FILE_SIZE_MB) {
+            if let Ok(size) = size_str.parse() {
+                self.logging.max_file_size_mb = Some(size);
+            }
+        }
+
+        if let Ok(age_str) = env_var(ENV_LOGGING_MAX_FILE_AGE_HOURS) {
+            if let Ok(age) = age_str.parse() {
+                self.logging.max_file_age_hours = Some(age);
+            }
+        }
+
+        if let Ok(files_str) = env_var(ENV_LOGGING_MAX_FILES) {
+            if let Ok(files) = files_str.parse() {
+                self.logging.max_files = Some(files);
+            }
+        }
+
         self.http_cache = self.http_cache.from_env();
 
         // Quote TTL from env
         let mut mint_ttl_env: Option<u64> = None;
         let mut melt_ttl_env: Option<u64> = None;
-        if let Ok(mint_ttl_str) = env::var(ENV_QUOTE_TTL_MINT) {
+        if let Ok(mint_ttl_str) = env_var(ENV_QUOTE_TTL_MINT) {
             if let Ok(v) = mint_ttl_str.parse::<u64>() {
                 mint_ttl_env = Some(v);
             }
         }
-        if let Ok(melt_ttl_str) = env::var(ENV_QUOTE_TTL_MELT) {
+        if let Ok(melt_ttl_str) = env_var(ENV_QUOTE_TTL_MELT) {
             if let Ok(v) = melt_ttl_str.parse::<u64>() {
                 melt_ttl_env = Some(v);
             }
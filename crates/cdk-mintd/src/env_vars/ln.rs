@@ -1,7 +1,6 @@
 //! Lightning Network common environment variables
 
-use std::env;
-
+use super::common::env_var;
 use crate::config::Ln;
 
 // LN environment variables
@@ -15,7 +14,7 @@ pub const ENV_LN_MAX_MELT: &str = "CDK_MINTD_LN_MAX_MELT";
 impl Ln {
     pub fn from_env(mut self) -> Self {
         // LnBackend
-        if let Ok(backend_str) = env::var(ENV_LN_BACKEND) {
+        if let Ok(backend_str) = env_var(ENV_LN_BACKEND) {
             if let Ok(backend) = backend_str.parse() {
                 self.ln_backend = backend;
             } else {
@@ -24,30 +23,30 @@ impl Ln {
         }
 
         // Optional invoice description
-        if let Ok(description) = env::var(ENV_LN_INVOICE_DESCRIPTION) {
+        if let Ok(description) = env_var(ENV_LN_INVOICE_DESCRIPTION) {
             self.invoice_description = Some(description);
         }
 
         // Amount fields
-        if let Ok(min_mint_str) = env::var(ENV_LN_MIN_MINT) {
+        if let Ok(min_mint_str) = env_var(ENV_LN_MIN_MINT) {
             if let Ok(amount) = min_mint_str.parse::<u64>() {
                 self.min_mint = amount.into();
             }
         }
 
-        if let Ok(max_mint_str) = env::var(ENV_LN_MAX_MINT) {
+        if let Ok(max_mint_str) = env_var(ENV_LN_MAX_MINT) {
             if let Ok(amount) = max_mint_str.parse::<u64>() {
                 self.max_mint = amount.into();
             }
         }
 
-        if let Ok(min_melt_str) = env::var(ENV_LN_MIN_MELT) {
+        if let Ok(min_melt_str) = env_var(ENV_LN_MIN_MELT) {
             if let Ok(amount) = min_melt_str.parse::<u64>() {
                 self.min_melt = amount.into();
             }
         }
 
-        if let Ok(max_melt_str) = env::var(ENV_LN_MAX_MELT) {
+        if let Ok(max_melt_str) = env_var(ENV_LN_MAX_MELT) {
             if let Ok(amount) = max_melt_str.parse::<u64>() {
                 self.max_melt = amount.into();
             }
@@ -1,7 +1,6 @@
 //! Management RPC environment variables
 
-use std::env;
-
+use super::common::env_var;
 use crate::config::MintManagementRpc;
 
 // Mint RPC Server environment variables
@@ -12,23 +11,23 @@ pub const ENV_MINT_MANAGEMENT_TLS_DIR_PATH: &str = "CDK_MINTD_MANAGEMENT_TLS_DIR
 
 impl MintManagementRpc {
     pub fn from_env(mut self) -> Self {
-        if let Ok(enabled) = env::var(ENV_MINT_MANAGEMENT_ENABLED) {
+        if let Ok(enabled) = env_var(ENV_MINT_MANAGEMENT_ENABLED) {
             if let Ok(enabled) = enabled.parse() {
                 self.enabled = enabled;
             }
         }
 
-        if let Ok(address) = env::var(ENV_MINT_MANAGEMENT_ADDRESS) {
+        if let Ok(address) = env_var(ENV_MINT_MANAGEMENT_ADDRESS) {
             self.address = Some(address);
         }
 
-        if let Ok(port) = env::var(ENV_MINT_MANAGEMENT_PORT) {
+        if let Ok(port) = env_var(ENV_MINT_MANAGEMENT_PORT) {
             if let Ok(port) = port.parse::<u16>() {
                 self.port = Some(port);
             }
         }
 
-        if let Ok(tls_path) = env::var(ENV_MINT_MANAGEMENT_TLS_DIR_PATH) {
+        if let Ok(tls_path) = env_var(ENV_MINT_MANAGEMENT_TLS_DIR_PATH) {
             self.tls_dir_path = Some(tls_path.into());
         }
 
@@ -1,7 +1,6 @@
 //! MintInfo environment variables
 
-use std::env;
-
+use super::common::env_var;
 use crate::config::MintInfo;
 
 // MintInfo environment variables
@@ -18,43 +17,43 @@ pub const ENV_MINT_TOS_URL: &str = "CDK_MINTD_MINT_TOS_URL";
 impl MintInfo {
     pub fn from_env(mut self) -> Self {
         // Required fields
-        if let Ok(name) = env::var(ENV_MINT_NAME) {
+        if let Ok(name) = env_var(ENV_MINT_NAME) {
             self.name = name;
         }
 
-        if let Ok(description) = env::var(ENV_MINT_DESCRIPTION) {
+        if let Ok(description) = env_var(ENV_MINT_DESCRIPTION) {
             self.description = description;
         }
 
         // Optional fields
-        if let Ok(pubkey_str) = env::var(ENV_MINT_PUBKEY) {
+        if let Ok(pubkey_str) = env_var(ENV_MINT_PUBKEY) {
             // Assuming PublicKey has a from_str implementation
             if let Ok(pubkey) = pubkey_str.parse() {
                 self.pubkey = Some(pubkey);
             }
         }
 
-        if let Ok(desc_long) = env::var(ENV_MINT_DESCRIPTION_LONG) {
+        if let Ok(desc_long) = env_var(ENV_MINT_DESCRIPTION_LONG) {
             self.description_long = Some(desc_long);
         }
 
-        if let Ok(icon_url) = env::var(ENV_MINT_ICON_URL) {
+        if let Ok(icon_url) = env_var(ENV_MINT_ICON_URL) {
             self.icon_url = Some(icon_url);
         }
 
-        if let Ok(motd) = env::var(ENV_MINT_MOTD) {
+        if let Ok(motd) = env_var(ENV_MINT_MOTD) {
             self.motd = Some(motd);
         }
 
-        if let Ok(nostr_key) = env::var(ENV_MINT_CONTACT_NOSTR) {
+        if let Ok(nostr_key) = env_var(ENV_MINT_CONTACT_NOSTR) {
             self.contact_nostr_public_key = Some(nostr_key);
         }
 
-        if let Ok(email) = env::var(ENV_MINT_CONTACT_EMAIL) {
+        if let Ok(email) = env_var(ENV_MINT_CONTACT_EMAIL) {
             self.contact_email = Some(email);
         }
 
-        if let Ok(tos_url) = env::var(ENV_MINT_TOS_URL) {
+        if let Ok(tos_url) = env_var(ENV_MINT_TOS_URL) {
             self.tos_url = Some(tos_url);
         }
 
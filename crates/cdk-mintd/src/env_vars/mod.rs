@@ -3,11 +3,17 @@
 //! This module contains all environment variable definitions and parsing logic
 //! organized by component.
 
+mod chaos;
 mod common;
 mod database;
 mod info;
 mod ln;
 mod mint_info;
+mod notifications;
+mod risk;
+mod stats;
+mod vouchers;
+mod webhooks;
 
 #[cfg(feature = "auth")]
 mod auth;
@@ -28,12 +34,12 @@ mod management_rpc;
 #[cfg(feature = "prometheus")]
 mod prometheus;
 
-use std::env;
 use std::str::FromStr;
 
 use anyhow::{anyhow, bail, Result};
 #[cfg(feature = "auth")]
 pub use auth::*;
+pub use chaos::*;
 #[cfg(feature = "cln")]
 pub use cln::*;
 pub use common::*;
@@ -52,18 +58,29 @@ pub use lnd::*;
 #[cfg(feature = "management-rpc")]
 pub use management_rpc::*;
 pub use mint_info::*;
+pub use notifications::*;
 #[cfg(feature = "prometheus")]
 pub use prometheus::*;
+pub use risk::*;
+pub use stats::*;
+pub use vouchers::*;
+pub use webhooks::*;
 
 use crate::config::{DatabaseEngine, LnBackend, Settings};
 
 impl Settings {
     pub fn from_env(&mut self) -> Result<Self> {
-        if let Ok(database) = env::var(DATABASE_ENV_VAR) {
+        if let Ok(database) = env_var(DATABASE_ENV_VAR) {
             let engine = DatabaseEngine::from_str(&database).map_err(|err| anyhow!(err))?;
             self.database.engine = engine;
         }
 
+        if let Ok(threshold) = env_var(ENV_DATABASE_SLOW_QUERY_THRESHOLD) {
+            if let Ok(parsed) = threshold.parse::<u64>() {
+                self.database.slow_query_threshold_ms = Some(parsed);
+            }
+        }
+
         // Parse PostgreSQL-specific configuration from environment variables
         if self.database.engine == DatabaseEngine::Postgres {
             self.database.postgres = Some(
@@ -122,6 +139,30 @@ impl Settings {
             self.prometheus = Some(self.prometheus.clone().unwrap_or_default().from_env());
         }
 
+        // Only set chaos if the enabled flag is true
+        let chaos = self.chaos.clone().unwrap_or_default().from_env();
+        self.chaos = chaos.enabled.then_some(chaos);
+
+        // Only set notifications if the enabled flag is true
+        let notifications = self.notifications.clone().unwrap_or_default().from_env();
+        self.notifications = notifications.enabled.then_some(notifications);
+
+        // Only set the risk policy if the enabled flag is true
+        let risk = self.risk.clone().unwrap_or_default().from_env();
+        self.risk = risk.enabled.then_some(risk);
+
+        // Only set quote webhooks if the enabled flag is true
+        let webhooks = self.webhooks.clone().unwrap_or_default().from_env();
+        self.webhooks = webhooks.enabled.then_some(webhooks);
+
+        // Only set vouchers if the enabled flag is true
+        let vouchers = self.vouchers.clone().unwrap_or_default().from_env();
+        self.vouchers = vouchers.enabled.then_some(vouchers);
+
+        // Only set the stats endpoint if the enabled flag is true
+        let stats = self.stats.clone().unwrap_or_default().from_env();
+        self.stats = stats.enabled.then_some(stats);
+
         match self.ln.ln_backend {
             #[cfg(feature = "cln")]
             LnBackend::Cln => {
@@ -148,7 +189,11 @@ impl Settings {
                 self.grpc_processor =
                     Some(self.grpc_processor.clone().unwrap_or_default().from_env());
             }
-            LnBackend::None => bail!("Ln backend must be set"),
+            LnBackend::None => {
+                if self.vouchers.is_none() {
+                    bail!("Ln backend must be set");
+                }
+            }
             #[allow(unreachable_patterns)]
             _ => bail!("Selected Ln backend is not enabled in this build"),
         }
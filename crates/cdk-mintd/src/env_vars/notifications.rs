@@ -0,0 +1,58 @@
+//! Operator notification environment variables
+
+use super::common::{env_var, split_list};
+use crate::config::Notifications;
+
+pub const ENV_NOTIFICATIONS_ENABLED: &str = "CDK_MINTD_NOTIFICATIONS_ENABLED";
+pub const ENV_NOTIFICATIONS_WEBHOOK_URLS: &str = "CDK_MINTD_NOTIFICATIONS_WEBHOOK_URLS";
+pub const ENV_NOTIFICATIONS_NOSTR_PUBKEYS: &str = "CDK_MINTD_NOTIFICATIONS_NOSTR_PUBKEYS";
+pub const ENV_NOTIFICATIONS_NOSTR_RELAYS: &str = "CDK_MINTD_NOTIFICATIONS_NOSTR_RELAYS";
+pub const ENV_NOTIFICATIONS_NOSTR_SECRET_KEY: &str = "CDK_MINTD_NOTIFICATIONS_NOSTR_SECRET_KEY";
+pub const ENV_NOTIFICATIONS_CHECK_INTERVAL_SECS: &str =
+    "CDK_MINTD_NOTIFICATIONS_CHECK_INTERVAL_SECS";
+pub const ENV_NOTIFICATIONS_MIN_INTERVAL_SECS: &str = "CDK_MINTD_NOTIFICATIONS_MIN_INTERVAL_SECS";
+pub const ENV_NOTIFICATIONS_TEMPLATE: &str = "CDK_MINTD_NOTIFICATIONS_TEMPLATE";
+
+impl Notifications {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(enabled_str) = env_var(ENV_NOTIFICATIONS_ENABLED) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.enabled = enabled;
+            }
+        }
+
+        if let Ok(webhook_urls) = env_var(ENV_NOTIFICATIONS_WEBHOOK_URLS) {
+            self.webhook_urls = split_list(&webhook_urls);
+        }
+
+        if let Ok(nostr_pubkeys) = env_var(ENV_NOTIFICATIONS_NOSTR_PUBKEYS) {
+            self.nostr_pubkeys = split_list(&nostr_pubkeys);
+        }
+
+        if let Ok(nostr_relays) = env_var(ENV_NOTIFICATIONS_NOSTR_RELAYS) {
+            self.nostr_relays = split_list(&nostr_relays);
+        }
+
+        if let Ok(nostr_secret_key) = env_var(ENV_NOTIFICATIONS_NOSTR_SECRET_KEY) {
+            self.nostr_secret_key = Some(nostr_secret_key);
+        }
+
+        if let Ok(check_interval_str) = env_var(ENV_NOTIFICATIONS_CHECK_INTERVAL_SECS) {
+            if let Ok(check_interval) = check_interval_str.parse() {
+                self.check_interval_secs = check_interval;
+            }
+        }
+
+        if let Ok(min_interval_str) = env_var(ENV_NOTIFICATIONS_MIN_INTERVAL_SECS) {
+            if let Ok(min_interval) = min_interval_str.parse() {
+                self.min_interval_secs = min_interval;
+            }
+        }
+
+        if let Ok(template) = env_var(ENV_NOTIFICATIONS_TEMPLATE) {
+            self.template = template;
+        }
+
+        self
+    }
+}
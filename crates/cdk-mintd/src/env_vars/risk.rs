@@ -0,0 +1,37 @@
+//! Risk policy environment variables
+
+use super::common::env_var;
+use crate::config::Risk;
+
+pub const ENV_RISK_ENABLED: &str = "CDK_MINTD_RISK_ENABLED";
+pub const ENV_RISK_URL: &str = "CDK_MINTD_RISK_URL";
+pub const ENV_RISK_TIMEOUT_MS: &str = "CDK_MINTD_RISK_TIMEOUT_MS";
+pub const ENV_RISK_FAIL_OPEN: &str = "CDK_MINTD_RISK_FAIL_OPEN";
+
+impl Risk {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(enabled_str) = env_var(ENV_RISK_ENABLED) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.enabled = enabled;
+            }
+        }
+
+        if let Ok(url) = env_var(ENV_RISK_URL) {
+            self.url = url;
+        }
+
+        if let Ok(timeout_str) = env_var(ENV_RISK_TIMEOUT_MS) {
+            if let Ok(timeout) = timeout_str.parse() {
+                self.timeout_ms = timeout;
+            }
+        }
+
+        if let Ok(fail_open_str) = env_var(ENV_RISK_FAIL_OPEN) {
+            if let Ok(fail_open) = fail_open_str.parse() {
+                self.fail_open = fail_open;
+            }
+        }
+
+        self
+    }
+}
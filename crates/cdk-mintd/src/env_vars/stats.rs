@@ -0,0 +1,53 @@
+//! Public stats environment variables
+
+use super::common::env_var;
+use crate::config::Stats;
+
+pub const ENV_STATS_ENABLED: &str = "CDK_MINTD_STATS_ENABLED";
+pub const ENV_STATS_INCLUDE_UPTIME: &str = "CDK_MINTD_STATS_INCLUDE_UPTIME";
+pub const ENV_STATS_INCLUDE_VOLUME: &str = "CDK_MINTD_STATS_INCLUDE_VOLUME";
+pub const ENV_STATS_VOLUME_BUCKET_BASE: &str = "CDK_MINTD_STATS_VOLUME_BUCKET_BASE";
+pub const ENV_STATS_MIN_VOLUME: &str = "CDK_MINTD_STATS_MIN_VOLUME";
+pub const ENV_STATS_REFRESH_SECS: &str = "CDK_MINTD_STATS_REFRESH_SECS";
+
+impl Stats {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(enabled_str) = env_var(ENV_STATS_ENABLED) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.enabled = enabled;
+            }
+        }
+
+        if let Ok(include_uptime_str) = env_var(ENV_STATS_INCLUDE_UPTIME) {
+            if let Ok(include_uptime) = include_uptime_str.parse() {
+                self.include_uptime = include_uptime;
+            }
+        }
+
+        if let Ok(include_volume_str) = env_var(ENV_STATS_INCLUDE_VOLUME) {
+            if let Ok(include_volume) = include_volume_str.parse() {
+                self.include_volume = include_volume;
+            }
+        }
+
+        if let Ok(bucket_base_str) = env_var(ENV_STATS_VOLUME_BUCKET_BASE) {
+            if let Ok(bucket_base) = bucket_base_str.parse() {
+                self.volume_bucket_base = bucket_base;
+            }
+        }
+
+        if let Ok(min_volume_str) = env_var(ENV_STATS_MIN_VOLUME) {
+            if let Ok(min_volume) = min_volume_str.parse() {
+                self.min_volume = min_volume;
+            }
+        }
+
+        if let Ok(refresh_str) = env_var(ENV_STATS_REFRESH_SECS) {
+            if let Ok(refresh) = refresh_str.parse() {
+                self.refresh_secs = refresh;
+            }
+        }
+
+        self
+    }
+}
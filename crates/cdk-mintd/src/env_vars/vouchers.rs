@@ -0,0 +1,46 @@
+//! Voucher environment variables
+
+use std::str::FromStr;
+
+use cdk::nuts::CurrencyUnit;
+
+use super::common::{env_var, split_list};
+use crate::config::Vouchers;
+
+pub const ENV_VOUCHERS_ENABLED: &str = "CDK_MINTD_VOUCHERS_ENABLED";
+pub const ENV_VOUCHERS_UNIT: &str = "CDK_MINTD_VOUCHERS_UNIT";
+pub const ENV_VOUCHERS_INPUT_FEE_PPK: &str = "CDK_MINTD_VOUCHERS_INPUT_FEE_PPK";
+pub const ENV_VOUCHERS_API_KEYS: &str = "CDK_MINTD_VOUCHERS_API_KEYS";
+pub const ENV_VOUCHERS_REDEEM_API_KEYS: &str = "CDK_MINTD_VOUCHERS_REDEEM_API_KEYS";
+
+impl Vouchers {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(enabled_str) = env_var(ENV_VOUCHERS_ENABLED) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.enabled = enabled;
+            }
+        }
+
+        if let Ok(unit_str) = env_var(ENV_VOUCHERS_UNIT) {
+            if let Ok(unit) = CurrencyUnit::from_str(&unit_str) {
+                self.unit = unit;
+            }
+        }
+
+        if let Ok(input_fee_str) = env_var(ENV_VOUCHERS_INPUT_FEE_PPK) {
+            if let Ok(input_fee) = input_fee_str.parse() {
+                self.input_fee_ppk = input_fee;
+            }
+        }
+
+        if let Ok(api_keys) = env_var(ENV_VOUCHERS_API_KEYS) {
+            self.api_keys = split_list(&api_keys);
+        }
+
+        if let Ok(redeem_api_keys) = env_var(ENV_VOUCHERS_REDEEM_API_KEYS) {
+            self.redeem_api_keys = split_list(&redeem_api_keys);
+        }
+
+        self
+    }
+}
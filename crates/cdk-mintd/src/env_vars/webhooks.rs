@@ -0,0 +1,56 @@
+//! Quote webhook environment variables
+
+use super::common::{env_var, split_list};
+use crate::config::Webhooks;
+
+pub const ENV_WEBHOOKS_ENABLED: &str = "CDK_MINTD_WEBHOOKS_ENABLED";
+pub const ENV_WEBHOOKS_API_KEYS: &str = "CDK_MINTD_WEBHOOKS_API_KEYS";
+pub const ENV_WEBHOOKS_SIGNING_SECRET: &str = "CDK_MINTD_WEBHOOKS_SIGNING_SECRET";
+pub const ENV_WEBHOOKS_POLL_INTERVAL_SECS: &str = "CDK_MINTD_WEBHOOKS_POLL_INTERVAL_SECS";
+pub const ENV_WEBHOOKS_MAX_ATTEMPTS: &str = "CDK_MINTD_WEBHOOKS_MAX_ATTEMPTS";
+pub const ENV_WEBHOOKS_RETRY_BASE_SECS: &str = "CDK_MINTD_WEBHOOKS_RETRY_BASE_SECS";
+pub const ENV_WEBHOOKS_ALLOW_PRIVATE_URLS: &str = "CDK_MINTD_WEBHOOKS_ALLOW_PRIVATE_URLS";
+
+impl Webhooks {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(enabled_str) = env_var(ENV_WEBHOOKS_ENABLED) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.enabled = enabled;
+            }
+        }
+
+        if let Ok(api_keys) = env_var(ENV_WEBHOOKS_API_KEYS) {
+            self.api_keys = split_list(&api_keys);
+        }
+
+        if let Ok(signing_secret) = env_var(ENV_WEBHOOKS_SIGNING_SECRET) {
+            self.signing_secret = signing_secret;
+        }
+
+        if let Ok(poll_interval_str) = env_var(ENV_WEBHOOKS_POLL_INTERVAL_SECS) {
+            if let Ok(poll_interval) = poll_interval_str.parse() {
+                self.poll_interval_secs = poll_interval;
+            }
+        }
+
+        if let Ok(max_attempts_str) = env_var(ENV_WEBHOOKS_MAX_ATTEMPTS) {
+            if let Ok(max_attempts) = max_attempts_str.parse() {
+                self.max_attempts = max_attempts;
+            }
+        }
+
+        if let Ok(retry_base_str) = env_var(ENV_WEBHOOKS_RETRY_BASE_SECS) {
+            if let Ok(retry_base) = retry_base_str.parse() {
+                self.retry_base_secs = retry_base;
+            }
+        }
+
+        if let Ok(allow_private_str) = env_var(ENV_WEBHOOKS_ALLOW_PRIVATE_URLS) {
+            if let Ok(allow_private) = allow_private_str.parse() {
+                self.allow_private_urls = allow_private;
+            }
+        }
+
+        self
+    }
+}
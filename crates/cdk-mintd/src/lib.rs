@@ -4,17 +4,18 @@
 #[cfg(feature = "auth")]
 use std::collections::HashMap;
 use std::env::{self};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 // external crates
 use anyhow::{anyhow, bail, Result};
 use axum::Router;
 use bip39::Mnemonic;
 use cdk::cdk_database::{self, MintDatabase, MintKVStore, MintKeysDatabase};
-use cdk::mint::{Mint, MintBuilder, MintMeltLimits};
+use cdk::mint::{FeeReserveMintPayment, HttpRiskPolicy, Mint, MintBuilder, MintMeltLimits};
 #[cfg(any(
     feature = "cln",
     feature = "lnbits",
@@ -35,10 +36,13 @@ use cdk::nuts::nut19::{CachedEndpoint, Method as NUT19Method, Path as NUT19Path}
 use cdk::nuts::CurrencyUnit;
 #[cfg(feature = "auth")]
 use cdk::nuts::{AuthRequired, Method, ProtectedEndpoint, RoutePath};
-use cdk::nuts::{ContactInfo, MintVersion, PaymentMethod};
+use cdk::nuts::{ContactInfo, MintVersion, PaymentMethod, SecretKey};
+use cdk::pub_sub::DEFAULT_REPLAY_SIZE;
 use cdk_axum::cache::HttpCache;
+use cdk_axum::recorder::RequestRecorder;
 use cdk_common::common::QuoteTTL;
 use cdk_common::database::DynMintDatabase;
+use cdk_common::util::unix_time;
 // internal crate modules
 #[cfg(feature = "prometheus")]
 use cdk_common::payment::MetricsMintPayment;
@@ -46,31 +50,43 @@ use cdk_common::payment::MintPayment;
 #[cfg(all(feature = "auth", feature = "postgres"))]
 use cdk_postgres::MintPgAuthDatabase;
 #[cfg(feature = "postgres")]
-use cdk_postgres::MintPgDatabase;
+use cdk_postgres::{MintPgDatabase, PgConfig, PgLeaderElection};
 #[cfg(all(feature = "auth", feature = "sqlite"))]
 use cdk_sqlite::mint::MintSqliteAuthDatabase;
 #[cfg(feature = "sqlite")]
-use cdk_sqlite::MintSqliteDatabase;
+use cdk_sqlite::{MintSqliteDatabase, SqliteConfig};
+use chaos::ChaosMintPayment;
 use cli::CLIArgs;
 #[cfg(feature = "auth")]
 use config::AuthType;
 use config::{DatabaseEngine, LnBackend};
-use env_vars::ENV_WORK_DIR;
+use env_vars::{instance_name, instance_scoped_env_var, ENV_WORK_DIR};
+use logging::RotatingFile;
 use setup::LnBackendSetup;
 use tower::ServiceBuilder;
 use tower_http::compression::CompressionLayer;
 use tower_http::decompression::RequestDecompressionLayer;
 use tower_http::trace::TraceLayer;
-use tracing_appender::{non_blocking, rolling};
+use tracing_appender::non_blocking;
 use tracing_subscriber::fmt::writer::MakeWriterExt;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+#[cfg(feature = "journald")]
+use tracing_subscriber::Layer;
 #[cfg(feature = "swagger")]
 use utoipa::OpenApi;
 
+pub mod chaos;
 pub mod cli;
 pub mod config;
 pub mod env_vars;
+pub mod logging;
+pub mod notifier;
+pub mod schema;
 pub mod setup;
+pub mod stats;
+pub mod vouchers;
+pub mod webhooks;
 
 const CARGO_PKG_VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 
@@ -107,38 +123,39 @@ async fn initial_setup(
 }
 
 /// Sets up and initializes a tracing subscriber with custom log filtering.
-/// Logs can be configured to output to stdout only, file only, or both.
+/// Logs can be configured to output to stdout, a rotated file, both, or the systemd
+/// journal, as pretty or JSON lines.
 /// Returns a guard that must be kept alive and properly dropped on shutdown.
 pub fn setup_tracing(
     work_dir: &Path,
     logging_config: &config::LoggingConfig,
 ) -> Result<Option<tracing_appender::non_blocking::WorkerGuard>> {
-    let default_filter = "debug";
-    let hyper_filter = "hyper=warn,rustls=warn,reqwest=warn";
-    let h2_filter = "h2=warn";
-    let tower_http = "tower_http=warn";
-    let rustls = "rustls=warn";
+    use config::LoggingOutput;
 
-    let env_filter = EnvFilter::new(format!(
-        "{default_filter},{hyper_filter},{h2_filter},{tower_http},{rustls}"
-    ));
+    let console_level = logging_config
+        .console_level
+        .as_deref()
+        .unwrap_or("info")
+        .parse::<tracing::Level>()
+        .unwrap_or(tracing::Level::INFO);
+    let file_level = logging_config
+        .file_level
+        .as_deref()
+        .unwrap_or("debug")
+        .parse::<tracing::Level>()
+        .unwrap_or(tracing::Level::DEBUG);
+    let format = logging_config.format;
+    let logs_dir = work_dir.join("logs");
+
+    let registry = tracing_subscriber::registry().with(logging::env_filter(logging_config));
 
-    use config::LoggingOutput;
     match logging_config.output {
         LoggingOutput::Stderr => {
             // Console output only (stderr)
-            let console_level = logging_config
-                .console_level
-                .as_deref()
-                .unwrap_or("info")
-                .parse::<tracing::Level>()
-                .unwrap_or(tracing::Level::INFO);
-
             let stderr = std::io::stderr.with_max_level(console_level);
 
-            tracing_subscriber::fmt()
-                .with_env_filter(env_filter)
-                .with_writer(stderr)
+            registry
+                .with(logging::fmt_layer(format, stderr, true))
                 .init();
 
             tracing::info!("Logging initialized: console only ({}+)", console_level);
@@ -146,75 +163,62 @@ pub fn setup_tracing(
         }
         LoggingOutput::File => {
             // File output only
-            let file_level = logging_config
-                .file_level
-                .as_deref()
-                .unwrap_or("debug")
-                .parse::<tracing::Level>()
-                .unwrap_or(tracing::Level::DEBUG);
-
-            // Create logs directory in work_dir if it doesn't exist
-            let logs_dir = work_dir.join("logs");
-            std::fs::create_dir_all(&logs_dir)?;
-
-            // Set up file appender with daily rotation
-            let file_appender = rolling::daily(&logs_dir, "cdk-mintd.log");
-            let (non_blocking_appender, guard) = non_blocking(file_appender);
+            let log_file =
+                RotatingFile::new(&logs_dir, logging::LOG_FILE_NAME, logging_config.into())?;
+            let (non_blocking_appender, guard) = non_blocking(log_file);
 
             let file_writer = non_blocking_appender.with_max_level(file_level);
 
-            tracing_subscriber::fmt()
-                .with_env_filter(env_filter)
-                .with_writer(file_writer)
+            registry
+                .with(logging::fmt_layer(format, file_writer, false))
                 .init();
 
             tracing::info!(
-                "Logging initialized: file only at {}/cdk-mintd.log ({}+)",
+                "Logging initialized: file only at {}/{} ({}+)",
                 logs_dir.display(),
+                logging::LOG_FILE_NAME,
                 file_level
             );
             Ok(Some(guard))
         }
         LoggingOutput::Both => {
             // Both console and file output (stderr + file)
-            let console_level = logging_config
-                .console_level
-                .as_deref()
-                .unwrap_or("info")
-                .parse::<tracing::Level>()
-                .unwrap_or(tracing::Level::INFO);
-            let file_level = logging_config
-                .file_level
-                .as_deref()
-                .unwrap_or("debug")
-                .parse::<tracing::Level>()
-                .unwrap_or(tracing::Level::DEBUG);
-
-            // Create logs directory in work_dir if it doesn't exist
-            let logs_dir = work_dir.join("logs");
-            std::fs::create_dir_all(&logs_dir)?;
-
-            // Set up file appender with daily rotation
-            let file_appender = rolling::daily(&logs_dir, "cdk-mintd.log");
-            let (non_blocking_appender, guard) = non_blocking(file_appender);
-
-            // Combine console output (stderr) and file output
+            let log_file =
+                RotatingFile::new(&logs_dir, logging::LOG_FILE_NAME, logging_config.into())?;
+            let (non_blocking_appender, guard) = non_blocking(log_file);
+
             let stderr = std::io::stderr.with_max_level(console_level);
             let file_writer = non_blocking_appender.with_max_level(file_level);
 
-            tracing_subscriber::fmt()
-                .with_env_filter(env_filter)
-                .with_writer(stderr.and(file_writer))
+            registry
+                .with(logging::fmt_layer(format, stderr, true))
+                .with(logging::fmt_layer(format, file_writer, false))
                 .init();
 
             tracing::info!(
-                "Logging initialized: console ({}+) and file at {}/cdk-mintd.log ({}+)",
+                "Logging initialized: console ({}+) and file at {}/{} ({}+)",
                 console_level,
                 logs_dir.display(),
+                logging::LOG_FILE_NAME,
                 file_level
             );
             Ok(Some(guard))
         }
+        #[cfg(feature = "journald")]
+        LoggingOutput::Journald => {
+            let journald = tracing_journald::layer()?.with_filter(
+                tracing_subscriber::filter::LevelFilter::from_level(console_level),
+            );
+
+            registry.with(journald).init();
+
+            tracing::info!("Logging initialized: journald ({}+)", console_level);
+            Ok(None)
+        }
+        #[cfg(not(feature = "journald"))]
+        LoggingOutput::Journald => {
+            bail!("Logging to journald requires cdk-mintd to be built with the journald feature")
+        }
     }
 }
 
@@ -223,9 +227,12 @@ pub async fn get_work_directory(args: &CLIArgs) -> Result<PathBuf> {
     let work_dir = if let Some(work_dir) = &args.work_dir {
         tracing::info!("Using work dir from cmd arg");
         work_dir.clone()
+    } else if let Some(env_work_dir) = instance_scoped_env_var(ENV_WORK_DIR) {
+        tracing::info!("Using instance work dir from env var");
+        env_work_dir.into()
     } else if let Ok(env_work_dir) = env::var(ENV_WORK_DIR) {
         tracing::info!("Using work dir from env var");
-        env_work_dir.into()
+        instance_work_dir(Path::new(&env_work_dir))
     } else {
         work_dir()?
     };
@@ -262,10 +269,15 @@ async fn setup_database(
     Arc<dyn MintKeysDatabase<Err = cdk_database::Error> + Send + Sync>,
     Arc<dyn MintKVStore<Err = cdk_database::Error> + Send + Sync>,
 )> {
+    let _slow_query_threshold = settings
+        .database
+        .slow_query_threshold_ms
+        .map(Duration::from_millis);
+
     match settings.database.engine {
         #[cfg(feature = "sqlite")]
         DatabaseEngine::Sqlite => {
-            let db = setup_sqlite_database(_work_dir, _db_password).await?;
+            let db = setup_sqlite_database(_work_dir, _db_password, _slow_query_threshold).await?;
             let localstore: Arc<dyn MintDatabase<cdk_database::Error> + Send + Sync> = db.clone();
             let kv: Arc<dyn MintKVStore<Err = cdk_database::Error> + Send + Sync> = db.clone();
             let keystore: Arc<dyn MintKeysDatabase<Err = cdk_database::Error> + Send + Sync> = db;
@@ -283,7 +295,25 @@ async fn setup_database(
             }
 
             #[cfg(feature = "postgres")]
-            let pg_db = Arc::new(MintPgDatabase::new(pg_config.url.as_str()).await?);
+            let pg_db_config = |url: &str| {
+                let config = PgConfig::from(url);
+                match _slow_query_threshold {
+                    Some(threshold) => config.with_slow_query_threshold(threshold),
+                    None => config,
+                }
+            };
+            #[cfg(feature = "postgres")]
+            let pg_db = Arc::new(match &pg_config.read_replica_url {
+                Some(read_replica_url) => {
+                    tracing::info!("Reading quote and proof states from read replica");
+                    MintPgDatabase::new_with_read_replica(
+                        pg_db_config(&pg_config.url),
+                        pg_db_config(read_replica_url),
+                    )
+                    .await?
+                }
+                None => MintPgDatabase::new(pg_db_config(&pg_config.url)).await?,
+            });
             #[cfg(feature = "postgres")]
             let localstore: Arc<dyn MintDatabase<cdk_database::Error> + Send + Sync> =
                 pg_db.clone();
@@ -314,18 +344,22 @@ async fn setup_database(
 async fn setup_sqlite_database(
     work_dir: &Path,
     _password: Option<String>,
+    slow_query_threshold: Option<Duration>,
 ) -> Result<Arc<MintSqliteDatabase>> {
     let sql_db_path = work_dir.join("cdk-mintd.sqlite");
 
     #[cfg(not(feature = "sqlcipher"))]
-    let db = MintSqliteDatabase::new(&sql_db_path).await?;
+    let config = SqliteConfig::from(&sql_db_path);
+    // Get password from command line arguments for sqlcipher
     #[cfg(feature = "sqlcipher")]
-    let db = {
-        // Get password from command line arguments for sqlcipher
-        MintSqliteDatabase::new((sql_db_path, _password.unwrap())).await?
+    let config = SqliteConfig::from((sql_db_path, _password.unwrap()));
+
+    let config = match slow_query_threshold {
+        Some(threshold) => config.with_slow_query_threshold(threshold),
+        None => config,
     };
 
-    Ok(Arc::new(db))
+    Ok(Arc::new(MintSqliteDatabase::new(config).await?))
 }
 
 /**
@@ -342,13 +376,26 @@ async fn configure_mint_builder(
     // Configure basic mint information
     let mint_builder = configure_basic_info(settings, mint_builder);
 
+    // Configure the identity key used to sign the mint info
+    let mint_builder = configure_identity_key(settings, mint_builder)?;
+
     // Configure lightning backend
     let mint_builder =
         configure_lightning_backend(settings, mint_builder, runtime, work_dir, kv_store).await?;
 
+    // Configure the voucher unit of a closed-loop mint
+    let mint_builder = configure_vouchers(settings, mint_builder)?;
+
     // Configure caching
     let mint_builder = configure_cache(settings, mint_builder);
 
+    // Configure leader election between instances sharing the database
+    let mint_builder = configure_leader_election(settings, mint_builder);
+
+    // Configure the risk policy consulted before quotes are created
+    let mint_builder = configure_risk_policy(settings, mint_builder);
+    let mint_builder = configure_notification_replay(settings, mint_builder);
+
     Ok(mint_builder)
 }
 
@@ -402,6 +449,106 @@ fn configure_basic_info(settings: &config::Settings, mint_builder: MintBuilder)
 
     builder
 }
+
+/// Configures the identity key used to sign the mint info
+fn configure_identity_key(
+    settings: &config::Settings,
+    mint_builder: MintBuilder,
+) -> Result<MintBuilder> {
+    match &settings.info.identity_secret_key {
+        Some(identity_secret_key) => {
+            let secret_key = SecretKey::from_hex(identity_secret_key)?;
+
+            tracing::info!(
+                "Signing mint info with identity pubkey {}",
+                secret_key.public_key()
+            );
+
+            Ok(mint_builder.with_info_signing_key(secret_key))
+        }
+        None => Ok(mint_builder),
+    }
+}
+
+/// Configures leader election when several instances share the PostgreSQL database
+fn configure_leader_election(
+    _settings: &config::Settings,
+    mint_builder: MintBuilder,
+) -> MintBuilder {
+    #[cfg(feature = "postgres")]
+    if let (DatabaseEngine::Postgres, Some(pg_config)) =
+        (&_settings.database.engine, &_settings.database.postgres)
+    {
+        if pg_config.leader_election {
+            let interval = pg_config.leader_election_interval_seconds.unwrap_or(5);
+            tracing::info!(
+                "Leader election enabled, checking every {} seconds",
+                interval
+            );
+
+            return mint_builder.with_leader_election(
+                Arc::new(PgLeaderElection::new(pg_config.url.as_str())),
+                Duration::from_secs(interval),
+            );
+        }
+    }
+
+    mint_builder
+}
+
+/// Configures the external risk scoring service consulted before quotes are created
+fn configure_risk_policy(settings: &config::Settings, mint_builder: MintBuilder) -> MintBuilder {
+    match &settings.risk {
+        Some(risk) if risk.enabled => {
+            tracing::info!(
+                "Consulting risk service {} for quotes, failing {}",
+                risk.url,
+                if risk.fail_open { "open" } else { "closed" }
+            );
+
+            mint_builder.with_risk_policy(
+                Arc::new(HttpRiskPolicy::new(risk.url.clone())),
+                Duration::from_millis(risk.timeout_ms),
+                risk.fail_open,
+            )
+        }
+        _ => mint_builder,
+    }
+}
+
+/// Keeps recent websocket notifications for wallets resubscribing after a reconnect
+fn configure_notification_replay(
+    settings: &config::Settings,
+    mint_builder: MintBuilder,
+) -> MintBuilder {
+    match settings.info.notification_replay_secs {
+        Some(secs) if secs > 0 => {
+            tracing::info!("Keeping websocket notifications for {} seconds", secs);
+
+            mint_builder.with_notification_replay(DEFAULT_REPLAY_SIZE, Duration::from_secs(secs))
+        }
+        _ => mint_builder,
+    }
+}
+
+fn configure_vouchers(
+    settings: &config::Settings,
+    mut mint_builder: MintBuilder,
+) -> Result<MintBuilder> {
+    if let Some(vouchers) = settings
+        .vouchers
+        .as_ref()
+        .filter(|vouchers| vouchers.enabled)
+    {
+        tracing::info!("Issuing vouchers in {}", vouchers.unit);
+
+        mint_builder.add_voucher_unit(vouchers.unit.clone())?;
+        mint_builder.set_unit_fee(&vouchers.unit, vouchers.input_fee_ppk)?;
+    }
+
+    Ok(mint_builder)
+}
+
 /// Configures Lightning Network backend based on the specified backend type
 async fn configure_lightning_backend(
     settings: &config::Settings,
@@ -438,6 +585,7 @@ async fn configure_lightning_backend(
                 CurrencyUnit::Sat,
                 mint_melt_limits,
                 Arc::new(cln),
+                cln_settings.fee_reserve.clone(),
             )
             .await?;
         }
@@ -456,6 +604,7 @@ async fn configure_lightning_backend(
                 CurrencyUnit::Sat,
                 mint_melt_limits,
                 Arc::new(lnbits),
+                lnbits_settings.fee_reserve.clone(),
             )
             .await?;
         }
@@ -474,6 +623,7 @@ async fn configure_lightning_backend(
                 CurrencyUnit::Sat,
                 mint_melt_limits,
                 Arc::new(lnd),
+                lnd_settings.fee_reserve.clone(),
             )
             .await?;
         }
@@ -495,6 +645,7 @@ async fn configure_lightning_backend(
                     unit.clone(),
                     mint_melt_limits,
                     Arc::new(fake),
+                    fake_wallet.fee_reserve.clone(),
                 )
                 .await?;
             }
@@ -526,6 +677,7 @@ async fn configure_lightning_backend(
                     unit.clone(),
                     mint_melt_limits,
                     Arc::new(processor),
+                    grpc_processor.fee_reserve.clone(),
                 )
                 .await?;
             }
@@ -545,9 +697,18 @@ async fn configure_lightning_backend(
                 CurrencyUnit::Sat,
                 mint_melt_limits,
                 Arc::new(ldk_node),
+                ldk_node_settings.fee_reserve.clone(),
             )
             .await?;
         }
+        LnBackend::None
+            if settings
+                .vouchers
+                .as_ref()
+                .is_some_and(|vouchers| vouchers.enabled) =>
+        {
+            tracing::info!("No payment backend, only vouchers are issued");
+        }
         LnBackend::None => {
             tracing::error!(
                 "Payment backend was not set or feature disabled. {:?}",
@@ -567,7 +728,24 @@ async fn configure_backend_for_unit(
     unit: cdk::nuts::CurrencyUnit,
     mint_melt_limits: MintMeltLimits,
     backend: Arc<dyn MintPayment<Err = cdk_common::payment::Error> + Send + Sync>,
+    fee_reserve: Option<config::FeeReserve>,
 ) -> Result<MintBuilder> {
+    let backend: Arc<dyn MintPayment<Err = cdk_common::payment::Error> + Send + Sync> =
+        match settings.chaos.clone() {
+            Some(chaos) if chaos.enabled => Arc::new(ChaosMintPayment::new(backend, chaos)),
+            _ => backend,
+        };
+
+    let backend: Arc<dyn MintPayment<Err = cdk_common::payment::Error> + Send + Sync> =
+        match fee_reserve {
+            Some(fee_reserve) => Arc::new(FeeReserveMintPayment::new(
+                backend,
+                fee_reserve.strategy.into(),
+                fee_reserve.feedback.map(Into::into),
+            )),
+            None => backend,
+        };
+
     let payment_settings = backend.get_settings().await?;
 
     if let Some(bolt12) = payment_settings.get("bolt12") {
@@ -586,6 +764,19 @@ async fn configure_backend_for_unit(
         }
     }
 
+    if let Some(keysend) = payment_settings.get("keysend") {
+        if keysend.as_bool().unwrap_or_default() {
+            mint_builder
+                .add_payment_processor(
+                    unit.clone(),
+                    PaymentMethod::keysend(),
+                    mint_melt_limits,
+                    Arc::clone(&backend),
+                )
+                .await?;
+        }
+    }
+
     mint_builder
         .add_payment_processor(
             unit.clone(),
@@ -945,6 +1136,51 @@ async fn start_services_with_shutdown(
         cdk_axum::create_mint_router_with_custom_cache(Arc::clone(&mint), cache, bolt12_supported)
             .await?;
 
+    let quote_webhooks = settings
+        .webhooks
+        .as_ref()
+        .filter(|webhooks| webhooks.enabled)
+        .map(|webhooks| webhooks::QuoteWebhooks::new(webhooks.clone()).map(Arc::new))
+        .transpose()?;
+
+    let v1_service = match &quote_webhooks {
+        Some(quote_webhooks) => Arc::clone(quote_webhooks).layer(Arc::clone(&mint), v1_service),
+        None => v1_service,
+    };
+
+    let v1_service = match &settings.info.request_recording_path {
+        Some(recording_path) => {
+            let recording_path = work_dir.join(recording_path);
+            tracing::warn!("Recording mint requests to {}", recording_path.display());
+            Arc::new(RequestRecorder::new(&recording_path).await?).layer(v1_service)
+        }
+        None => v1_service,
+    };
+
+    let v1_service = match settings
+        .vouchers
+        .as_ref()
+        .filter(|vouchers| vouchers.enabled)
+    {
+        Some(vouchers) => v1_service.merge(
+            Arc::new(vouchers::VoucherApi::new(
+                vouchers.clone(),
+                Arc::clone(&mint),
+            ))
+            .router(),
+        ),
+        None => v1_service,
+    };
+
+    let v1_service = match settings.stats.as_ref().filter(|stats| stats.enabled) {
+        Some(stats) => {
+            tracing::info!("Publishing public stats on /v1/stats");
+            v1_service
+                .merge(Arc::new(stats::StatsApi::new(stats.clone(), Arc::clone(&mint))).router())
+        }
+        None => v1_service,
+    };
+
     let mut mint_service = Router::new()
         .merge(v1_service)
         .layer(
@@ -967,6 +1203,17 @@ async fn start_services_with_shutdown(
             );
         }
     }
+
+    if let Some(path_prefix) = settings
+        .info
+        .path_prefix
+        .as_deref()
+        .and_then(normalized_path_prefix)
+    {
+        tracing::info!("Serving mint under {}", path_prefix);
+        mint_service = Router::new().nest(&path_prefix, mint_service);
+    }
+
     // Create a broadcast channel to share shutdown signal between services
     let (shutdown_tx, _) = tokio::sync::broadcast::channel::<()>(1);
 
@@ -1012,7 +1259,22 @@ async fn start_services_with_shutdown(
 
     mint.start().await?;
 
-    let socket_addr = SocketAddr::from_str(&format!("{listen_addr}:{listen_port}"))?;
+    let notifier_handle = match &settings.notifications {
+        Some(notifications) if notifications.enabled => {
+            let notifier = Arc::new(notifier::Notifier::new(
+                settings.info.url.clone(),
+                notifications.clone(),
+            )?);
+            Some(notifier.spawn_monitor(Arc::clone(&mint), shutdown_tx.subscribe()))
+        }
+        _ => None,
+    };
+
+    let webhook_handle = quote_webhooks.map(|quote_webhooks| {
+        quote_webhooks.spawn_dispatcher(Arc::clone(&mint), shutdown_tx.subscribe())
+    });
+
+    let socket_addr = listen_socket_addr(&listen_addr, listen_port)?;
 
     let listener = tokio::net::TcpListener::bind(socket_addr).await?;
 
@@ -1059,6 +1321,18 @@ async fn start_services_with_shutdown(
         }
     }
 
+    if let Some(handle) = notifier_handle {
+        if let Err(e) = handle.await {
+            tracing::warn!("Notifier task failed: {}", e);
+        }
+    }
+
+    if let Some(handle) = webhook_handle {
+        if let Err(e) = handle.await {
+            tracing::warn!("Webhook dispatcher task failed: {}", e);
+        }
+    }
+
     mint.stop().await?;
 
     #[cfg(feature = "management-rpc")]
@@ -1071,6 +1345,52 @@ async fn start_services_with_shutdown(
     Ok(())
 }
 
+/// Rotate the active keysets whose final expiry differs from `final_expiry`
+///
+/// The final expiry is part of the keyset id, so it can only be set on a new keyset.
+async fn apply_keyset_final_expiry(mint: &Mint, final_expiry: u64) -> Result<()> {
+    if final_expiry <= unix_time() {
+        tracing::warn!(
+            "Keyset final expiry {} has passed, active keysets are not rotated",
+            final_expiry
+        );
+        return Ok(());
+    }
+
+    for (unit, keyset_id) in mint.get_active_keysets() {
+        if unit == CurrencyUnit::Auth {
+            continue;
+        }
+
+        let Some(keyset_info) = mint.get_keyset_info(&keyset_id) else {
+            continue;
+        };
+
+        if keyset_info.final_expiry == Some(final_expiry) {
+            continue;
+        }
+
+        let rotated = mint
+            .rotate_keyset_with_expiry(
+                unit.clone(),
+                keyset_info.max_order,
+                keyset_info.input_fee_ppk,
+                Some(final_expiry),
+            )
+            .await?;
+
+        tracing::info!(
+            "Rotated {} keyset {} to {} expiring at {}",
+            unit,
+            keyset_id,
+            rotated.id,
+            final_expiry
+        );
+    }
+
+    Ok(())
+}
+
 async fn shutdown_signal() {
     tokio::signal::ctrl_c()
         .await
@@ -1078,9 +1398,20 @@ async fn shutdown_signal() {
     tracing::info!("Shutdown signal received");
 }
 
+/// Isolate a shared work directory per mint instance
+///
+/// Returns `<base>/<instance>` when [`env_vars::ENV_INSTANCE`] is set, so instances sharing a
+/// host keep separate config, database and logs. Returns `base` otherwise.
+pub fn instance_work_dir(base: &Path) -> PathBuf {
+    match instance_name() {
+        Some(instance) => base.join(instance.to_lowercase()),
+        None => base.to_path_buf(),
+    }
+}
+
 fn work_dir() -> Result<PathBuf> {
     let home_dir = home::home_dir().ok_or(anyhow!("Unknown home dir"))?;
-    let dir = home_dir.join(".cdk-mintd");
+    let dir = instance_work_dir(&home_dir.join(".cdk-mintd"));
 
     std::fs::create_dir_all(&dir)?;
 
@@ -1177,10 +1508,19 @@ pub async fn run_mintd_with_shutdown(
 
     let mint = Arc::new(mint);
 
+    // Every instance applies the final expiry, as leadership is only acquired once
+    // the mint is started and keysets already carrying the expiry are left as is
+    if let Some(final_expiry) = settings.info.keyset_final_expiry {
+        apply_keyset_final_expiry(&mint, final_expiry).await?;
+    }
+
     // Checks the status of all pending melt quotes
     // Pending melt quotes where the payment has gone through inputs are burnt
     // Pending melt quotes where the payment has **failed** inputs are reset to unspent
-    mint.check_pending_melt_quotes().await?;
+    // With leader election only the leader checks them, once it is elected
+    if mint.is_leader() {
+        mint.check_pending_melt_quotes().await?;
+    }
 
     start_services_with_shutdown(
         mint.clone(),
@@ -1193,10 +1533,52 @@ pub async fn run_mintd_with_shutdown(
     .await
 }
 
+/// Socket address to listen on, accepting IPv6 hosts with or without brackets
+fn listen_socket_addr(host: &str, port: u16) -> Result<SocketAddr> {
+    let ip = IpAddr::from_str(host.trim_start_matches('[').trim_end_matches(']'))
+        .map_err(|_| anyhow!("Invalid listen host: {host}"))?;
+
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// Path prefix with a leading and no trailing slash, `None` for the root path
+fn normalized_path_prefix(prefix: &str) -> Option<String> {
+    let prefix = prefix.trim_matches('/');
+    (!prefix.is_empty()).then(|| format!("/{prefix}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_listen_socket_addr() {
+        assert_eq!(
+            listen_socket_addr("127.0.0.1", 8085).unwrap().to_string(),
+            "127.0.0.1:8085"
+        );
+        assert_eq!(
+            listen_socket_addr("::", 3338).unwrap().to_string(),
+            "[::]:3338"
+        );
+        assert_eq!(
+            listen_socket_addr("[::1]", 3338).unwrap().to_string(),
+            "[::1]:3338"
+        );
+        assert!(listen_socket_addr("mint.example.com", 3338).is_err());
+    }
+
+    #[test]
+    fn test_normalized_path_prefix() {
+        assert_eq!(normalized_path_prefix("/cashu"), Some("/cashu".to_string()));
+        assert_eq!(
+            normalized_path_prefix("cashu/mint/"),
+            Some("/cashu/mint".to_string())
+        );
+        assert_eq!(normalized_path_prefix("/"), None);
+        assert_eq!(normalized_path_prefix(""), None);
+    }
+
     #[test]
     fn test_postgres_auth_url_validation() {
         // Test that the auth database config requires explicit configuration
@@ -0,0 +1,360 @@
+//! Log output
+//!
+//! The log file is rotated once it reaches the configured size or age. The
+//! rotated file is renamed with the unix time of the rotation appended, e.g.
+//! `cdk-mintd.log.1700000000`, and the oldest rotated files are removed once
+//! more than the configured number are kept.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::config::{LoggingConfig, LoggingFormat};
+
+/// Name of the log file in the logs directory
+pub const LOG_FILE_NAME: &str = "cdk-mintd.log";
+/// Age at which the log file is rotated when not configured
+const DEFAULT_MAX_FILE_AGE_HOURS: u64 = 24;
+/// Levels of noisy dependencies, overridden by module levels of the config
+const DEFAULT_MODULE_LEVELS: &[(&str, &str)] = &[
+    ("hyper", "warn"),
+    ("rustls", "warn"),
+    ("reqwest", "warn"),
+    ("h2", "warn"),
+    ("tower_http", "warn"),
+];
+
+/// Filter of the default level and the level of every module
+pub fn env_filter(logging_config: &LoggingConfig) -> EnvFilter {
+    let mut directives = vec!["debug".to_string()];
+
+    for (module, level) in DEFAULT_MODULE_LEVELS {
+        if !logging_config.modules.contains_key(*module) {
+            directives.push(format!("{module}={level}"));
+        }
+    }
+
+    for (module, level) in &logging_config.modules {
+        directives.push(format!("{module}={level}"));
+    }
+
+    EnvFilter::new(directives.join(","))
+}
+
+/// Layer writing events to `writer` in `format`
+pub fn fmt_layer<S, W>(
+    format: LoggingFormat,
+    writer: W,
+    ansi: bool,
+) -> Box<dyn Layer<S> + Send + Sync + 'static>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+
+    match format {
+        LoggingFormat::Pretty => layer.boxed(),
+        LoggingFormat::Json => layer.json().boxed(),
+    }
+}
+
+/// When the log file is rotated and how many rotated files are kept
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// Size in bytes at which the file is rotated
+    pub max_size: Option<u64>,
+    /// Age at which the file is rotated
+    pub max_age: Option<Duration>,
+    /// Number of rotated files kept
+    pub max_files: Option<usize>,
+}
+
+impl From<&LoggingConfig> for RotationPolicy {
+    fn from(logging_config: &LoggingConfig) -> Self {
+        Self {
+            max_size: logging_config
+                .max_file_size_mb
+                .map(|mb| mb.saturating_mul(1024 * 1024)),
+            max_age: Some(Duration::from_secs(
+                logging_config
+                    .max_file_age_hours
+                    .unwrap_or(DEFAULT_MAX_FILE_AGE_HOURS)
+                    .saturating_mul(60 * 60),
+            )),
+            max_files: logging_config.max_files,
+        }
+    }
+}
+
+/// Log file rotated by size and age
+#[derive(Debug)]
+pub struct RotatingFile {
+    dir: PathBuf,
+    file_name: String,
+    policy: RotationPolicy,
+    file: File,
+    size: u64,
+    opened_at: SystemTime,
+}
+
+impl RotatingFile {
+    /// Open the log file `file_name` in `dir`, appending to it if it exists
+    pub fn new(dir: &Path, file_name: &str, policy: RotationPolicy) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        let file = open_append(&dir.join(file_name))?;
+        let metadata = file.metadata()?;
+        let opened_at = metadata
+            .created()
+            .or_else(|_| metadata.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file_name: file_name.to_string(),
+            policy,
+            file,
+            size: metadata.len(),
+            opened_at,
+        })
+    }
+
+    /// Whether writing `len` more bytes must go to a new file
+    fn should_rotate(&self, len: usize) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+
+        let too_big = self
+            .policy
+            .max_size
+            .is_some_and(|max_size| self.size.saturating_add(len as u64) > max_size);
+        let too_old = self.policy.max_age.is_some_and(|max_age| {
+            self.opened_at
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed >= max_age)
+        });
+
+        too_big || too_old
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let rotated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut rotated = self.dir.join(format!("{}.{rotated_at}", self.file_name));
+        let mut suffix = 1;
+        while rotated.exists() {
+            rotated = self
+                .dir
+                .join(format!("{}.{rotated_at}-{suffix}", self.file_name));
+            suffix += 1;
+        }
+
+        let path = self.dir.join(&self.file_name);
+        fs::rename(&path, &rotated)?;
+
+        self.file = open_append(&path)?;
+        self.size = 0;
+        self.opened_at = SystemTime::now();
+
+        self.remove_old_files()
+    }
+
+    /// Rotated files, oldest first
+    ///
+    /// Files are ordered by their last write rather than by name, as daily
+    /// files left by earlier versions are named by date instead of unix time.
+    fn rotated_files(&self) -> io::Result<Vec<PathBuf>> {
+        let prefix = format!("{}.", self.file_name);
+
+        let mut files = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name.starts_with(&prefix))
+            })
+            .map(|entry| {
+                let modified = entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(UNIX_EPOCH);
+                (modified, entry.path())
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+
+        Ok(files.into_iter().map(|(_, path)| path).collect())
+    }
+
+    fn remove_old_files(&self) -> io::Result<()> {
+        let Some(max_files) = self.policy.max_files else {
+            return Ok(());
+        };
+
+        let files = self.rotated_files()?;
+        let excess = files.len().saturating_sub(max_files);
+        for file in &files[..excess] {
+            fs::remove_file(file)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.should_rotate(buf.len()) {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size = self.size.saturating_add(written as u64);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cdk-mintd-logging-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_max_files() {
+        let dir = logs_dir("size");
+        let mut file = RotatingFile::new(
+            &dir,
+            LOG_FILE_NAME,
+            RotationPolicy {
+                max_size: Some(10),
+                max_age: None,
+                max_files: Some(2),
+            },
+        )
+        .unwrap();
+
+        for _ in 0..5 {
+            file.write_all(b"0123456789").unwrap();
+        }
+        file.flush().unwrap();
+
+        // Every write but the first rotated the file, only two rotations are kept
+        let rotated = file.rotated_files().unwrap();
+        assert_eq!(rotated.len(), 2);
+        assert_eq!(
+            fs::read(dir.join(LOG_FILE_NAME)).unwrap(),
+            b"0123456789".to_vec()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_removes_daily_files_before_rotated_files() {
+        let dir = logs_dir("daily");
+        fs::create_dir_all(&dir).unwrap();
+
+        // Daily file of an earlier version, last written before any rotation
+        let daily = dir.join(format!("{LOG_FILE_NAME}.2026-01-01"));
+        File::create(&daily)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(1_767_225_600))
+            .unwrap();
+
+        let mut file = RotatingFile::new(
+            &dir,
+            LOG_FILE_NAME,
+            RotationPolicy {
+                max_size: Some(10),
+                max_age: None,
+                max_files: Some(2),
+            },
+        )
+        .unwrap();
+
+        for _ in 0..3 {
+            file.write_all(b"0123456789").unwrap();
+        }
+        file.flush().unwrap();
+
+        let rotated = file.rotated_files().unwrap();
+        assert_eq!(rotated.len(), 2);
+        assert!(!daily.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_no_rotation_within_limits() {
+        let dir = logs_dir("limits");
+        let mut file = RotatingFile::new(
+            &dir,
+            LOG_FILE_NAME,
+            RotationPolicy {
+                max_size: Some(1024),
+                max_age: Some(Duration::from_secs(3600)),
+                max_files: None,
+            },
+        )
+        .unwrap();
+
+        file.write_all(b"first\n").unwrap();
+        file.write_all(b"second\n").unwrap();
+        file.flush().unwrap();
+
+        assert!(file.rotated_files().unwrap().is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.join(LOG_FILE_NAME)).unwrap(),
+            "first\nsecond\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_module_levels_override_defaults() {
+        let mut logging_config = LoggingConfig::default();
+        logging_config
+            .modules
+            .insert("hyper".to_string(), "debug".to_string());
+        logging_config
+            .modules
+            .insert("cdk_sqlite".to_string(), "warn".to_string());
+
+        let filter = env_filter(&logging_config).to_string();
+
+        assert!(filter.contains("hyper=debug"));
+        assert!(!filter.contains("hyper=warn"));
+        assert!(filter.contains("cdk_sqlite=warn"));
+        assert!(filter.contains("h2=warn"));
+    }
+}
@@ -5,7 +5,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use cdk_mintd::cli::CLIArgs;
+use cdk_mintd::cli::{CLIArgs, Commands};
 use cdk_mintd::{get_work_directory, load_settings};
 use clap::Parser;
 use tokio::runtime::Runtime;
@@ -17,6 +17,15 @@ fn main() -> Result<()> {
 
     rt.block_on(async {
         let args = CLIArgs::parse();
+
+        if let Some(Commands::PrintConfigSchema) = args.command {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&cdk_mintd::schema::config_schema()?)?
+            );
+            return Ok(());
+        }
+
         let work_dir = get_work_directory(&args).await?;
         let settings = load_settings(&work_dir, args.config)?;
 
@@ -0,0 +1,519 @@
+//! Operator notifications
+//!
+//! Periodically checks the mint for conditions the operator has to act on and
+//! sends alerts to the configured webhooks and nostr pubkeys. Alerts of the same
+//! kind and source, such as one payment backend, are rate limited so a lasting
+//! outage does not flood the operator while other sources are still reported.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use cdk::mint::{MeltQuote, Mint};
+use cdk::nuts::{CurrencyUnit, MeltQuoteState, PaymentMethod};
+use cdk::util::unix_time;
+use cdk::Amount;
+use cdk_nostr::{RelayPool, RelayUse};
+use nostr_sdk::{Keys, PublicKey};
+use reqwest::Client;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::config::Notifications;
+
+/// Consecutive failed checks after which the circuit of a payment backend is open
+const CIRCUIT_OPEN_FAILURES: u32 = 3;
+/// Age after which a pending melt quote is reported as not reconciled
+const STUCK_MELT_QUOTE_SECS: u64 = 60 * 60;
+
+/// Condition an alert is sent for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// A payment backend failed several checks in a row
+    CircuitOpen,
+    /// Melt quotes stay pending without being reconciled with the payment backend
+    Reconciliation,
+    /// Issued and redeemed amounts are inconsistent
+    Liabilities,
+    /// The database returned an error
+    Database,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertKind::CircuitOpen => write!(f, "circuit open"),
+            AlertKind::Reconciliation => write!(f, "reconciliation"),
+            AlertKind::Liabilities => write!(f, "liabilities"),
+            AlertKind::Database => write!(f, "database"),
+        }
+    }
+}
+
+/// Alert posted to webhooks
+#[derive(Debug, Serialize)]
+struct WebhookAlert<'a> {
+    mint: &'a str,
+    kind: AlertKind,
+    message: &'a str,
+}
+
+/// Sends operator alerts
+pub struct Notifier {
+    mint_url: String,
+    settings: Notifications,
+    client: Client,
+    nostr_keys: Keys,
+    nostr_pubkeys: Vec<PublicKey>,
+    /// Time the last alert of a kind was sent for a source
+    last_sent: Mutex<HashMap<(AlertKind, String), Instant>>,
+    /// Consecutive failed checks of each payment backend
+    backend_failures: Mutex<HashMap<String, u32>>,
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifier")
+            .field("mint_url", &self.mint_url)
+            .field("settings", &self.settings)
+            .field("nostr_pubkeys", &self.nostr_pubkeys)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Notifier {
+    /// Notifier for the mint at `mint_url`
+    pub fn new(mint_url: String, settings: Notifications) -> Result<Self> {
+        let nostr_keys = match &settings.nostr_secret_key {
+            Some(secret_key) => Keys::parse(secret_key)
+                .map_err(|e| anyhow!("Invalid notification nostr secret key: {e}"))?,
+            None => Keys::generate(),
+        };
+
+        let nostr_pubkeys = settings
+            .nostr_pubkeys
+            .iter()
+            .map(|pubkey| {
+                PublicKey::parse(pubkey)
+                    .map_err(|e| anyhow!("Invalid notification nostr pubkey {pubkey}: {e}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if !nostr_pubkeys.is_empty() && settings.nostr_relays.is_empty() {
+            tracing::warn!("Notification nostr pubkeys are set without relays");
+        }
+
+        Ok(Self {
+            mint_url,
+            settings,
+            client: Client::new(),
+            nostr_keys,
+            nostr_pubkeys,
+            last_sent: Mutex::new(HashMap::new()),
+            backend_failures: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Send an alert on every channel, unless one of the same kind and source was sent recently
+    pub async fn notify(&self, kind: AlertKind, source: &str, message: &str) {
+        if !self.should_send(kind, source, Instant::now()) {
+            tracing::debug!("Rate limited {} alert: {}", kind, message);
+            return;
+        }
+
+        tracing::warn!("Sending {} alert: {}", kind, message);
+
+        let text = self.render(kind, message);
+
+        for url in &self.settings.webhook_urls {
+            let alert = WebhookAlert {
+                mint: &self.mint_url,
+                kind,
+                message: &text,
+            };
+
+            let result = self
+                .client
+                .post(url)
+                .json(&alert)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+
+            if let Err(err) = result {
+                tracing::error!("Could not send alert to webhook {}: {}", url, err);
+            }
+        }
+
+        if !self.nostr_pubkeys.is_empty() && !self.settings.nostr_relays.is_empty() {
+            if let Err(err) = self.send_nostr(&text).await {
+                tracing::error!("Could not send alert over nostr: {}", err);
+            }
+        }
+    }
+
+    /// Whether an alert of `kind` for `source` may be sent at `now`, recording it as sent if so
+    fn should_send(&self, kind: AlertKind, source: &str, now: Instant) -> bool {
+        let min_interval = Duration::from_secs(self.settings.min_interval_secs);
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| e.into_inner());
+
+        match last_sent.get(&(kind, source.to_string())) {
+            Some(sent) if now.duration_since(*sent) < min_interval => false,
+            _ => {
+                last_sent.insert((kind, source.to_string()), now);
+                true
+            }
+        }
+    }
+
+    /// Record a check of the payment backend `backend`
+    ///
+    /// Returns the number of consecutive failures once the circuit is open.
+    fn record_backend_check(&self, backend: &str, ok: bool) -> Option<u32> {
+        let mut backend_failures = self
+            .backend_failures
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        if ok {
+            if backend_failures.remove(backend).unwrap_or_default() >= CIRCUIT_OPEN_FAILURES {
+                tracing::info!("Payment backend {} recovered", backend);
+            }
+            return None;
+        }
+
+        let failures = backend_failures.entry(backend.to_string()).or_default();
+        *failures += 1;
+
+        (*failures >= CIRCUIT_OPEN_FAILURES).then_some(*failures)
+    }
+
+    fn render(&self, kind: AlertKind, message: &str) -> String {
+        self.settings
+            .template
+            .replace("{mint}", &self.mint_url)
+            .replace("{kind}", &kind.to_string())
+            .replace("{message}", message)
+    }
+
+    /// Send `text` to every nostr pubkey as a NIP-17 private direct message
+    async fn send_nostr(&self, text: &str) -> Result<()> {
+        // The client is connected on the first alert and kept by the pool for later ones
+        let client = RelayPool::global()
+            .client(&self.settings.nostr_relays, RelayUse::Write)
+            .await?;
+
+        for pubkey in &self.nostr_pubkeys {
+            cdk_nostr::send_private_message(&client, &self.nostr_keys, pubkey, text).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Check the mint every `check_interval_secs` until shutdown
+    pub fn spawn_monitor(
+        self: Arc<Self>,
+        mint: Arc<Mint>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> JoinHandle<()> {
+        let check_interval = Duration::from_secs(self.settings.check_interval_secs.max(1));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+
+            loop {
+                tokio::select! {
+                    _ = shutdown.recv() => break,
+                    _ = interval.tick() => {
+                        // With leader election only the leader alerts
+                        if mint.is_leader() {
+                            self.check(&mint).await;
+                        }
+                    }
+                }
+            }
+
+            RelayPool::global().shutdown().await;
+        })
+    }
+
+    async fn check(&self, mint: &Mint) {
+        for (unit, method) in payment_backends(mint).await {
+            let Ok(processor) = mint.get_payment_processor(unit.clone(), method.clone()) else {
+                continue;
+            };
+
+            let backend = format!("{unit} {method}");
+            let failure = match processor.backend_info().await {
+                Ok(Some(info)) if !info.connected => Some("is not connected".to_string()),
+                Ok(_) => None,
+                Err(err) => Some(format!("is not responding: {err}")),
+            };
+
+            let Some(failures) = self.record_backend_check(&backend, failure.is_none()) else {
+                if let Some(failure) = failure {
+                    tracing::warn!("Payment backend for {} {}", backend, failure);
+                }
+                continue;
+            };
+
+            self.notify(
+                AlertKind::CircuitOpen,
+                &backend,
+                &format!(
+                    "Payment backend for {backend} failed {failures} checks in a row, it {}",
+                    failure.unwrap_or_default()
+                ),
+            )
+            .await;
+        }
+
+        match mint.melt_quotes().await {
+            Ok(melt_quotes) => {
+                let stuck = stuck_melt_quotes(&melt_quotes, unix_time());
+
+                if !stuck.is_empty() {
+                    self.notify(
+                        AlertKind::Reconciliation,
+                        "melt",
+                        &format!(
+                            "{} melt quotes are pending for over {} minutes: {}",
+                            stuck.len(),
+                            STUCK_MELT_QUOTE_SECS / 60,
+                            stuck.join(", ")
+                        ),
+                    )
+                    .await;
+                }
+            }
+            Err(err) => {
+                self.notify(
+                    AlertKind::Database,
+                    "melt_quotes",
+                    &format!("Could not read melt quotes: {err}"),
+                )
+                .await;
+            }
+        }
+
+        match mint.ledger_balances().await {
+            Ok(balances) => {
+                for balance in balances.iter().filter(|balance| !balance.is_consistent()) {
+                    self.notify(
+                        AlertKind::Liabilities,
+                        &format!("ledger_{}", balance.unit),
+                        &format!(
+                            "Ledger has {} {} outstanding but the database {}",
+                            fmt_outstanding(balance.outstanding),
+                            balance.unit,
+                            fmt_outstanding(balance.database_outstanding)
+                        ),
+                    )
+                    .await;
+                }
+            }
+            Err(err) => {
+                self.notify(
+                    AlertKind::Database,
+                    "ledger",
+                    &format!("Could not read ledger: {err}"),
+                )
+                .await;
+            }
+        }
+
+        let totals = match mint.total_issued().await {
+            Ok(issued) => mint
+                .total_redeemed()
+                .await
+                .map(|redeemed| (issued, redeemed)),
+            Err(err) => Err(err),
+        };
+
+        match totals {
+            Ok((issued, redeemed)) => {
+                for (keyset_id, redeemed) in redeemed {
+                    let issued = issued.get(&keyset_id).copied().unwrap_or_default();
+
+                    if redeemed > issued {
+                        self.notify(
+                            AlertKind::Liabilities,
+                            &keyset_id.to_string(),
+                            &format!(
+                                "Keyset {keyset_id} redeemed {redeemed} but only issued {issued}"
+                            ),
+                        )
+                        .await;
+                    }
+                }
+            }
+            Err(err) => {
+                self.notify(
+                    AlertKind::Database,
+                    "totals",
+                    &format!("Could not read issued and redeemed totals: {err}"),
+                )
+                .await;
+            }
+        }
+    }
+}
+
+/// Outstanding ecash of a ledger balance, which is negative when more was redeemed than issued
+fn fmt_outstanding(outstanding: Option<Amount>) -> String {
+    match outstanding {
+        Some(outstanding) => outstanding.to_string(),
+        None => "negative".to_string(),
+    }
+}
+
+/// Ids of melt quotes left pending or unknown for longer than [`STUCK_MELT_QUOTE_SECS`]
+///
+/// The mint reconciles such quotes with the payment backend on start up and when
+/// instances fail over, quotes still pending long after creation were not settled.
+fn stuck_melt_quotes(melt_quotes: &[MeltQuote], now: u64) -> Vec<String> {
+    melt_quotes
+        .iter()
+        .filter(|quote| {
+            matches!(
+                quote.state,
+                MeltQuoteState::Pending | MeltQuoteState::Unknown
+            )
+        })
+        .filter(|quote| quote.created_time + STUCK_MELT_QUOTE_SECS <= now)
+        .map(|quote| quote.id.to_string())
+        .collect()
+}
+
+/// Unit and method pairs the mint offers minting or melting for
+async fn payment_backends(mint: &Mint) -> Vec<(CurrencyUnit, PaymentMethod)> {
+    let mint_info = match mint.mint_info().await {
+        Ok(mint_info) => mint_info,
+        Err(err) => {
+            tracing::warn!("Could not get mint info to check payment backends: {}", err);
+            return Vec::new();
+        }
+    };
+
+    let mut backends: Vec<(CurrencyUnit, PaymentMethod)> = mint_info
+        .nuts
+        .nut04
+        .methods
+        .iter()
+        .map(|settings| (settings.unit.clone(), settings.method.clone()))
+        .chain(
+            mint_info
+                .nuts
+                .nut05
+                .methods
+                .iter()
+                .map(|settings| (settings.unit.clone(), settings.method.clone())),
+        )
+        .collect();
+    backends.sort_by_key(|(unit, method)| (unit.to_string(), method.to_string()));
+    backends.dedup();
+
+    backends
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::mint::MeltPaymentRequest;
+
+    use super::*;
+
+    const BOLT11: &str = "lnbc100n1pnvpufspp5djn8hrq49r8cghwye9kqw752qjncwyfnrprhprpqk43mwcy4yfsqdq5g9kxy7fqd9h8vmmfvdjscqzzsxqyz5vqsp5uhpjt36rj75pl7jq2sshaukzfkt7uulj456s4mh7uy7l6vx7lvxs9qxpqysgqedwz08acmqwtk8g4vkwm2w78suwt2qyzz6jkkwcgrjm3r3hs6fskyhvud4fan3keru7emjm8ygqpcrwtlmhfjfmer3afs5hhwamgr4cqtactdq";
+
+    #[test]
+    fn test_alert_rate_limit() {
+        let notifier = Notifier::new(
+            "https://mint.example.com".to_string(),
+            Notifications {
+                enabled: true,
+                min_interval_secs: 60,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let now = Instant::now();
+        let later = now + Duration::from_secs(30);
+
+        assert!(notifier.should_send(AlertKind::Database, "totals", now));
+        assert!(!notifier.should_send(AlertKind::Database, "totals", later));
+        assert!(notifier.should_send(AlertKind::Liabilities, "totals", later));
+        assert!(notifier.should_send(AlertKind::Database, "totals", now + Duration::from_secs(61)));
+
+        // Each payment backend is rate limited on its own
+        assert!(notifier.should_send(AlertKind::CircuitOpen, "sat bolt11", now));
+        assert!(notifier.should_send(AlertKind::CircuitOpen, "usd bolt11", later));
+        assert!(!notifier.should_send(AlertKind::CircuitOpen, "sat bolt11", later));
+
+        assert_eq!(
+            notifier.render(AlertKind::Database, "down"),
+            "[https://mint.example.com] database: down"
+        );
+    }
+
+    #[test]
+    fn test_backend_circuit() {
+        let notifier = Notifier::new(
+            "https://mint.example.com".to_string(),
+            Notifications::default(),
+        )
+        .unwrap();
+
+        for _ in 1..CIRCUIT_OPEN_FAILURES {
+            assert_eq!(notifier.record_backend_check("sat bolt11", false), None);
+        }
+        assert_eq!(
+            notifier.record_backend_check("sat bolt11", false),
+            Some(CIRCUIT_OPEN_FAILURES)
+        );
+        assert_eq!(notifier.record_backend_check("usd bolt11", false), None);
+
+        // A successful check closes the circuit again
+        assert_eq!(notifier.record_backend_check("sat bolt11", true), None);
+        assert_eq!(notifier.record_backend_check("sat bolt11", false), None);
+
+        assert!(format!("{notifier:?}").contains("mint.example.com"));
+    }
+
+    #[test]
+    fn test_stuck_melt_quotes() {
+        let now = 1_800_000_000;
+        let quote = |state: MeltQuoteState, created_time: u64| {
+            let mut quote = MeltQuote::new(
+                MeltPaymentRequest::Bolt11 {
+                    bolt11: BOLT11.parse().unwrap(),
+                },
+                CurrencyUnit::Sat,
+                1.into(),
+                0.into(),
+                0,
+                None,
+                None,
+                PaymentMethod::Bolt11,
+            );
+            quote.state = state;
+            quote.created_time = created_time;
+            quote
+        };
+
+        let melt_quotes = vec![
+            quote(MeltQuoteState::Pending, now - STUCK_MELT_QUOTE_SECS),
+            quote(MeltQuoteState::Unknown, now - 2 * STUCK_MELT_QUOTE_SECS),
+            quote(MeltQuoteState::Pending, now - 60),
+            quote(MeltQuoteState::Paid, now - 2 * STUCK_MELT_QUOTE_SECS),
+        ];
+
+        assert_eq!(
+            stuck_melt_quotes(&melt_quotes, now),
+            vec![melt_quotes[0].id.to_string(), melt_quotes[1].id.to_string()]
+        );
+    }
+}
@@ -0,0 +1,394 @@
+//! Config file JSON schema
+//!
+//! Generates a JSON Schema for [`Settings`] so deployment tooling can validate
+//! `config.toml` files. Every field that can also be set from the environment is
+//! annotated with the variable name under the `x-env-var` keyword.
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::config::Settings;
+use crate::env_vars::*;
+
+/// Keyword used to annotate config fields with their environment variable
+pub const ENV_VAR_KEYWORD: &str = "x-env-var";
+
+/// `(schema definition, field, environment variable)` for every env-var backed field
+fn env_var_annotations() -> Vec<(&'static str, &'static str, &'static str)> {
+    #[allow(unused_mut)]
+    let mut annotations = vec![
+        ("Info", "url", ENV_URL),
+        ("Info", "listen_host", ENV_LISTEN_HOST),
+        ("Info", "listen_port", ENV_LISTEN_PORT),
+        ("Info", "seed", ENV_SEED),
+        ("Info", "mnemonic", ENV_MNEMONIC),
+        ("Info", "signatory_url", ENV_SIGNATORY_URL),
+        ("Info", "signatory_certs", ENV_SIGNATORY_CERTS),
+        ("Info", "identity_secret_key", ENV_IDENTITY_SECRET_KEY),
+        ("Info", "input_fee_ppk", ENV_INPUT_FEE_PPK),
+        ("Info", "keyset_final_expiry", ENV_KEYSET_FINAL_EXPIRY),
+        ("Info", "enable_swagger_ui", ENV_ENABLE_SWAGGER),
+        ("Info", "request_recording_path", ENV_REQUEST_RECORDING_PATH),
+        ("Info", "path_prefix", ENV_PATH_PREFIX),
+        (
+            "Info",
+            "notification_replay_secs",
+            ENV_NOTIFICATION_REPLAY_SECS,
+        ),
+        ("LoggingConfig", "output", ENV_LOGGING_OUTPUT),
+        ("LoggingConfig", "console_level", ENV_LOGGING_CONSOLE_LEVEL),
+        ("LoggingConfig", "file_level", ENV_LOGGING_FILE_LEVEL),
+        ("LoggingConfig", "format", ENV_LOGGING_FORMAT),
+        ("LoggingConfig", "modules", ENV_LOGGING_MODULES),
+        (
+            "LoggingConfig",
+            "max_file_size_mb",
+            ENV_LOGGING_MAX_FILE_SIZE_MB,
+        ),
+        (
+            "LoggingConfig",
+            "max_file_age_hours",
+            ENV_LOGGING_MAX_FILE_AGE_HOURS,
+        ),
+        ("LoggingConfig", "max_files", ENV_LOGGING_MAX_FILES),
+        ("QuoteTtlSchema", "mint_ttl", ENV_QUOTE_TTL_MINT),
+        ("QuoteTtlSchema", "melt_ttl", ENV_QUOTE_TTL_MELT),
+        ("MintInfo", "name", ENV_MINT_NAME),
+        ("MintInfo", "pubkey", ENV_MINT_PUBKEY),
+        ("MintInfo", "description", ENV_MINT_DESCRIPTION),
+        ("MintInfo", "description_long", ENV_MINT_DESCRIPTION_LONG),
+        ("MintInfo", "icon_url", ENV_MINT_ICON_URL),
+        ("MintInfo", "motd", ENV_MINT_MOTD),
+        (
+            "MintInfo",
+            "contact_nostr_public_key",
+            ENV_MINT_CONTACT_NOSTR,
+        ),
+        ("MintInfo", "contact_email", ENV_MINT_CONTACT_EMAIL),
+        ("MintInfo", "tos_url", ENV_MINT_TOS_URL),
+        ("Ln", "ln_backend", ENV_LN_BACKEND),
+        ("Ln", "invoice_description", ENV_LN_INVOICE_DESCRIPTION),
+        ("Ln", "min_mint", ENV_LN_MIN_MINT),
+        ("Ln", "max_mint", ENV_LN_MAX_MINT),
+        ("Ln", "min_melt", ENV_LN_MIN_MELT),
+        ("Ln", "max_melt", ENV_LN_MAX_MELT),
+        ("Database", "engine", DATABASE_ENV_VAR),
+        (
+            "Database",
+            "slow_query_threshold_ms",
+            ENV_DATABASE_SLOW_QUERY_THRESHOLD,
+        ),
+        ("PostgresConfig", "url", ENV_POSTGRES_URL),
+        ("PostgresConfig", "tls_mode", ENV_POSTGRES_TLS_MODE),
+        (
+            "PostgresConfig",
+            "max_connections",
+            ENV_POSTGRES_MAX_CONNECTIONS,
+        ),
+        (
+            "PostgresConfig",
+            "connection_timeout_seconds",
+            ENV_POSTGRES_CONNECTION_TIMEOUT,
+        ),
+        (
+            "PostgresConfig",
+            "leader_election",
+            ENV_POSTGRES_LEADER_ELECTION,
+        ),
+        (
+            "PostgresConfig",
+            "leader_election_interval_seconds",
+            ENV_POSTGRES_LEADER_ELECTION_INTERVAL,
+        ),
+        (
+            "PostgresConfig",
+            "read_replica_url",
+            ENV_POSTGRES_READ_REPLICA_URL,
+        ),
+        ("PostgresAuthConfig", "url", ENV_AUTH_POSTGRES_URL),
+        ("PostgresAuthConfig", "tls_mode", ENV_AUTH_POSTGRES_TLS_MODE),
+        (
+            "PostgresAuthConfig",
+            "max_connections",
+            ENV_AUTH_POSTGRES_MAX_CONNECTIONS,
+        ),
+        (
+            "PostgresAuthConfig",
+            "connection_timeout_seconds",
+            ENV_AUTH_POSTGRES_CONNECTION_TIMEOUT,
+        ),
+        ("Chaos", "enabled", ENV_CHAOS_ENABLED),
+        ("Chaos", "min_latency_ms", ENV_CHAOS_MIN_LATENCY_MS),
+        ("Chaos", "max_latency_ms", ENV_CHAOS_MAX_LATENCY_MS),
+        ("Chaos", "failure_rate", ENV_CHAOS_FAILURE_RATE),
+        (
+            "Chaos",
+            "settlement_delay_ms",
+            ENV_CHAOS_SETTLEMENT_DELAY_MS,
+        ),
+        ("Notifications", "enabled", ENV_NOTIFICATIONS_ENABLED),
+        (
+            "Notifications",
+            "webhook_urls",
+            ENV_NOTIFICATIONS_WEBHOOK_URLS,
+        ),
+        (
+            "Notifications",
+            "nostr_pubkeys",
+            ENV_NOTIFICATIONS_NOSTR_PUBKEYS,
+        ),
+        (
+            "Notifications",
+            "nostr_relays",
+            ENV_NOTIFICATIONS_NOSTR_RELAYS,
+        ),
+        (
+            "Notifications",
+            "nostr_secret_key",
+            ENV_NOTIFICATIONS_NOSTR_SECRET_KEY,
+        ),
+        (
+            "Notifications",
+            "check_interval_secs",
+            ENV_NOTIFICATIONS_CHECK_INTERVAL_SECS,
+        ),
+        (
+            "Notifications",
+            "min_interval_secs",
+            ENV_NOTIFICATIONS_MIN_INTERVAL_SECS,
+        ),
+        ("Notifications", "template", ENV_NOTIFICATIONS_TEMPLATE),
+        ("Risk", "enabled", ENV_RISK_ENABLED),
+        ("Risk", "url", ENV_RISK_URL),
+        ("Risk", "timeout_ms", ENV_RISK_TIMEOUT_MS),
+        ("Risk", "fail_open", ENV_RISK_FAIL_OPEN),
+        ("Webhooks", "enabled", ENV_WEBHOOKS_ENABLED),
+        ("Webhooks", "api_keys", ENV_WEBHOOKS_API_KEYS),
+        ("Webhooks", "signing_secret", ENV_WEBHOOKS_SIGNING_SECRET),
+        (
+            "Webhooks",
+            "poll_interval_secs",
+            ENV_WEBHOOKS_POLL_INTERVAL_SECS,
+        ),
+        ("Webhooks", "max_attempts", ENV_WEBHOOKS_MAX_ATTEMPTS),
+        ("Webhooks", "retry_base_secs", ENV_WEBHOOKS_RETRY_BASE_SECS),
+        (
+            "Webhooks",
+            "allow_private_urls",
+            ENV_WEBHOOKS_ALLOW_PRIVATE_URLS,
+        ),
+        ("Vouchers", "enabled", ENV_VOUCHERS_ENABLED),
+        ("Vouchers", "unit", ENV_VOUCHERS_UNIT),
+        ("Vouchers", "input_fee_ppk", ENV_VOUCHERS_INPUT_FEE_PPK),
+        ("Vouchers", "api_keys", ENV_VOUCHERS_API_KEYS),
+        ("Vouchers", "redeem_api_keys", ENV_VOUCHERS_REDEEM_API_KEYS),
+        ("Stats", "enabled", ENV_STATS_ENABLED),
+        ("Stats", "include_uptime", ENV_STATS_INCLUDE_UPTIME),
+        ("Stats", "include_volume", ENV_STATS_INCLUDE_VOLUME),
+        ("Stats", "volume_bucket_base", ENV_STATS_VOLUME_BUCKET_BASE),
+        ("Stats", "min_volume", ENV_STATS_MIN_VOLUME),
+        ("Stats", "refresh_secs", ENV_STATS_REFRESH_SECS),
+    ];
+
+    #[cfg(feature = "auth")]
+    annotations.extend([
+        ("Auth", "auth_enabled", ENV_AUTH_ENABLED),
+        ("Auth", "openid_discovery", ENV_AUTH_OPENID_DISCOVERY),
+        ("Auth", "openid_client_id", ENV_AUTH_OPENID_CLIENT_ID),
+        ("Auth", "mint_max_bat", ENV_AUTH_MINT_MAX_BAT),
+        ("Auth", "mint", ENV_AUTH_MINT),
+        ("Auth", "get_mint_quote", ENV_AUTH_GET_MINT_QUOTE),
+        ("Auth", "check_mint_quote", ENV_AUTH_CHECK_MINT_QUOTE),
+        ("Auth", "melt", ENV_AUTH_MELT),
+        ("Auth", "get_melt_quote", ENV_AUTH_GET_MELT_QUOTE),
+        ("Auth", "check_melt_quote", ENV_AUTH_CHECK_MELT_QUOTE),
+        ("Auth", "swap", ENV_AUTH_SWAP),
+        ("Auth", "restore", ENV_AUTH_RESTORE),
+        ("Auth", "check_proof_state", ENV_AUTH_CHECK_PROOF_STATE),
+    ]);
+
+    #[cfg(feature = "cln")]
+    annotations.extend([
+        ("Cln", "rpc_path", ENV_CLN_RPC_PATH),
+        ("Cln", "bolt12", ENV_CLN_BOLT12),
+        ("Cln", "fee_percent", ENV_CLN_FEE_PERCENT),
+        ("Cln", "reserve_fee_min", ENV_CLN_RESERVE_FEE_MIN),
+    ]);
+
+    #[cfg(feature = "lnbits")]
+    annotations.extend([
+        ("LNbits", "admin_api_key", ENV_LNBITS_ADMIN_API_KEY),
+        ("LNbits", "invoice_api_key", ENV_LNBITS_INVOICE_API_KEY),
+        ("LNbits", "lnbits_api", ENV_LNBITS_API),
+        ("LNbits", "fee_percent", ENV_LNBITS_FEE_PERCENT),
+        ("LNbits", "reserve_fee_min", ENV_LNBITS_RESERVE_FEE_MIN),
+    ]);
+
+    #[cfg(feature = "lnd")]
+    annotations.extend([
+        ("Lnd", "address", ENV_LND_ADDRESS),
+        ("Lnd", "cert_file", ENV_LND_CERT_FILE),
+        ("Lnd", "macaroon_file", ENV_LND_MACAROON_FILE),
+        ("Lnd", "fee_percent", ENV_LND_FEE_PERCENT),
+        ("Lnd", "reserve_fee_min", ENV_LND_RESERVE_FEE_MIN),
+    ]);
+
+    #[cfg(feature = "ldk-node")]
+    annotations.extend([
+        ("LdkNode", "fee_percent", LDK_NODE_FEE_PERCENT_ENV_VAR),
+        (
+            "LdkNode",
+            "reserve_fee_min",
+            LDK_NODE_RESERVE_FEE_MIN_ENV_VAR,
+        ),
+        (
+            "LdkNode",
+            "bitcoin_network",
+            LDK_NODE_BITCOIN_NETWORK_ENV_VAR,
+        ),
+        (
+            "LdkNode",
+            "chain_source_type",
+            LDK_NODE_CHAIN_SOURCE_TYPE_ENV_VAR,
+        ),
+        ("LdkNode", "esplora_url", LDK_NODE_ESPLORA_URL_ENV_VAR),
+        (
+            "LdkNode",
+            "bitcoind_rpc_host",
+            LDK_NODE_BITCOIND_RPC_HOST_ENV_VAR,
+        ),
+        (
+            "LdkNode",
+            "bitcoind_rpc_port",
+            LDK_NODE_BITCOIND_RPC_PORT_ENV_VAR,
+        ),
+        (
+            "LdkNode",
+            "bitcoind_rpc_user",
+            LDK_NODE_BITCOIND_RPC_USER_ENV_VAR,
+        ),
+        (
+            "LdkNode",
+            "bitcoind_rpc_password",
+            LDK_NODE_BITCOIND_RPC_PASSWORD_ENV_VAR,
+        ),
+        (
+            "LdkNode",
+            "storage_dir_path",
+            LDK_NODE_STORAGE_DIR_PATH_ENV_VAR,
+        ),
+        ("LdkNode", "ldk_node_host", LDK_NODE_LDK_NODE_HOST_ENV_VAR),
+        ("LdkNode", "ldk_node_port", LDK_NODE_LDK_NODE_PORT_ENV_VAR),
+        (
+            "LdkNode",
+            "gossip_source_type",
+            LDK_NODE_GOSSIP_SOURCE_TYPE_ENV_VAR,
+        ),
+        ("LdkNode", "rgs_url", LDK_NODE_RGS_URL_ENV_VAR),
+        ("LdkNode", "webserver_host", LDK_NODE_WEBSERVER_HOST_ENV_VAR),
+        ("LdkNode", "webserver_port", LDK_NODE_WEBSERVER_PORT_ENV_VAR),
+    ]);
+
+    #[cfg(feature = "fakewallet")]
+    annotations.extend([
+        (
+            "FakeWallet",
+            "supported_units",
+            ENV_FAKE_WALLET_SUPPORTED_UNITS,
+        ),
+        ("FakeWallet", "fee_percent", ENV_FAKE_WALLET_FEE_PERCENT),
+        (
+            "FakeWallet",
+            "reserve_fee_min",
+            ENV_FAKE_WALLET_RESERVE_FEE_MIN,
+        ),
+        ("FakeWallet", "min_delay_time", ENV_FAKE_WALLET_MIN_DELAY),
+        ("FakeWallet", "max_delay_time", ENV_FAKE_WALLET_MAX_DELAY),
+    ]);
+
+    #[cfg(feature = "grpc-processor")]
+    annotations.extend([
+        (
+            "GrpcProcessor",
+            "supported_units",
+            ENV_GRPC_PROCESSOR_SUPPORTED_UNITS,
+        ),
+        ("GrpcProcessor", "addr", ENV_GRPC_PROCESSOR_ADDRESS),
+        ("GrpcProcessor", "port", ENV_GRPC_PROCESSOR_PORT),
+        ("GrpcProcessor", "tls_dir", ENV_GRPC_PROCESSOR_TLS_DIR),
+    ]);
+
+    #[cfg(feature = "management-rpc")]
+    annotations.extend([
+        ("MintManagementRpc", "enabled", ENV_MINT_MANAGEMENT_ENABLED),
+        ("MintManagementRpc", "address", ENV_MINT_MANAGEMENT_ADDRESS),
+        ("MintManagementRpc", "port", ENV_MINT_MANAGEMENT_PORT),
+        (
+            "MintManagementRpc",
+            "tls_dir_path",
+            ENV_MINT_MANAGEMENT_TLS_DIR_PATH,
+        ),
+    ]);
+
+    #[cfg(feature = "prometheus")]
+    annotations.extend([
+        ("Prometheus", "enabled", ENV_PROMETHEUS_ENABLED),
+        ("Prometheus", "address", ENV_PROMETHEUS_ADDRESS),
+        ("Prometheus", "port", ENV_PROMETHEUS_PORT),
+    ]);
+
+    annotations
+}
+
+/// JSON Schema of the mintd config file, annotated with environment variable names
+///
+/// Fails if an environment variable is annotated on a field the schema does not have.
+pub fn config_schema() -> Result<Value> {
+    let mut schema = serde_json::to_value(schemars::schema_for!(Settings))?;
+
+    for (definition, field, env_var) in env_var_annotations() {
+        let pointer = format!("/$defs/{definition}/properties/{field}");
+
+        let property = schema
+            .pointer_mut(&pointer)
+            .and_then(|property| property.as_object_mut())
+            .ok_or_else(|| anyhow!("No config schema property at {} for {}", pointer, env_var))?;
+
+        property.insert(
+            ENV_VAR_KEYWORD.to_string(),
+            Value::String(env_var.to_string()),
+        );
+    }
+
+    Ok(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_schema_env_var_annotations() {
+        let schema = config_schema().expect("Every annotated field is in the schema");
+
+        // Run with all features to cover the annotations of every backend
+        for (definition, field, env_var) in env_var_annotations() {
+            let pointer = format!("/$defs/{definition}/properties/{field}/{ENV_VAR_KEYWORD}");
+            assert_eq!(
+                schema.pointer(&pointer),
+                Some(&Value::String(env_var.to_string())),
+                "{pointer}"
+            );
+        }
+
+        assert_eq!(
+            schema.pointer("/$defs/Info/properties/url/x-env-var"),
+            Some(&Value::String(ENV_URL.to_string()))
+        );
+        assert_eq!(
+            schema.pointer("/$defs/Ln/properties/ln_backend/x-env-var"),
+            Some(&Value::String(ENV_LN_BACKEND.to_string()))
+        );
+        assert!(schema.pointer("/properties/info").is_some());
+    }
+}
@@ -0,0 +1,283 @@
+//! Public mint statistics
+//!
+//! `GET /v1/stats` publishes coarse aggregates for mint directories: the number
+//! of active keysets, the supported NUTs, the uptime in hours and the mint and
+//! melt volume of the last 30 days. Volumes are rounded down to a power of
+//! `volume_bucket_base` and published as 0 below `min_volume`, so the activity
+//! of single users cannot be read from them. The stats are computed at most
+//! once every `refresh_secs`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use cdk::mint::Mint;
+use cdk::nuts::{CurrencyUnit, MeltQuoteState, Nuts};
+use cdk::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::config::Stats;
+
+/// Days of quotes counted in the volumes
+pub const VOLUME_WINDOW_DAYS: u64 = 30;
+
+/// Rounded volume of a unit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitVolume {
+    /// Unit of the volume
+    pub unit: CurrencyUnit,
+    /// Amount issued, rounded down to its bucket
+    pub minted: u64,
+    /// Amount paid out, rounded down to its bucket
+    pub melted: u64,
+}
+
+/// Stats published on `/v1/stats`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicStats {
+    /// Number of active keysets
+    pub keysets: usize,
+    /// Supported NUTs
+    pub nuts: Vec<u8>,
+    /// Hours since the mint started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_hours: Option<u64>,
+    /// Days counted in the volumes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_days: Option<u64>,
+    /// Volumes of every unit with quotes in the window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<Vec<UnitVolume>>,
+    /// Unix time the stats were computed
+    pub timestamp: u64,
+}
+
+/// Serves the public stats of a mint
+pub struct StatsApi {
+    settings: Stats,
+    mint: Arc<Mint>,
+    started: Instant,
+    cached: Mutex<Option<(Instant, PublicStats)>>,
+}
+
+impl StatsApi {
+    /// Stats API with the given settings, counting uptime from now
+    pub fn new(settings: Stats, mint: Arc<Mint>) -> Self {
+        Self {
+            settings,
+            mint,
+            started: Instant::now(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Route of the stats endpoint
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/v1/stats", get(get_stats))
+            .with_state(self)
+    }
+
+    /// Stats, from the cache while it is fresh
+    async fn stats(&self) -> Result<PublicStats, cdk::Error> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some((computed, stats)) = cached.as_ref() {
+            if computed.elapsed() < Duration::from_secs(self.settings.refresh_secs) {
+                return Ok(stats.clone());
+            }
+        }
+
+        let stats = self.compute().await?;
+        *cached = Some((Instant::now(), stats.clone()));
+
+        Ok(stats)
+    }
+
+    async fn compute(&self) -> Result<PublicStats, cdk::Error> {
+        let keysets = self
+            .mint
+            .keysets()
+            .keysets
+            .iter()
+            .filter(|keyset| keyset.active)
+            .count();
+
+        let mint_info = self.mint.mint_info().await?;
+
+        let uptime_hours = self
+            .settings
+            .include_uptime
+            .then(|| self.started.elapsed().as_secs() / 3600);
+
+        let volume = match self.settings.include_volume {
+            true => Some(self.volume().await?),
+            false => None,
+        };
+
+        Ok(PublicStats {
+            keysets,
+            nuts: supported_nuts(&mint_info.nuts),
+            uptime_hours,
+            volume_days: volume.as_ref().map(|_| VOLUME_WINDOW_DAYS),
+            volume,
+            timestamp: unix_time(),
+        })
+    }
+
+    /// Rounded mint and melt volume of every unit over the window
+    async fn volume(&self) -> Result<Vec<UnitVolume>, cdk::Error> {
+        let since = unix_time().saturating_sub(VOLUME_WINDOW_DAYS * 24 * 60 * 60);
+        let mut volumes: BTreeMap<CurrencyUnit, (u64, u64)> = BTreeMap::new();
+
+        for quote in self.mint.mint_quotes().await? {
+            for issuance in quote
+                .issuance
+                .iter()
+                .filter(|issuance| issuance.time >= since)
+            {
+                let (minted, _) = volumes.entry(quote.unit.clone()).or_default();
+                *minted = minted.saturating_add(issuance.amount.into());
+            }
+        }
+
+        for quote in self.mint.melt_quotes().await? {
+            if quote.state == MeltQuoteState::Paid
+                && quote.paid_time.is_some_and(|paid_time| paid_time >= since)
+            {
+                let (_, melted) = volumes.entry(quote.unit.clone()).or_default();
+                *melted = melted.saturating_add(quote.amount.into());
+            }
+        }
+
+        Ok(volumes
+            .into_iter()
+            .map(|(unit, (minted, melted))| UnitVolume {
+                unit,
+                minted: self.bucket(minted),
+                melted: self.bucket(melted),
+            })
+            .collect())
+    }
+
+    fn bucket(&self, amount: u64) -> u64 {
+        bucket(
+            amount,
+            self.settings.volume_bucket_base,
+            self.settings.min_volume,
+        )
+    }
+}
+
+/// `amount` rounded down to a power of `base`, 0 below `min`
+fn bucket(amount: u64, base: u64, min: u64) -> u64 {
+    if amount == 0 || amount < min {
+        return 0;
+    }
+
+    let base = base.max(2);
+    let mut bucket = 1u64;
+    while let Some(next) = bucket.checked_mul(base) {
+        if next > amount {
+            break;
+        }
+        bucket = next;
+    }
+
+    bucket
+}
+
+/// NUTs the mint info advertises as supported
+fn supported_nuts(nuts: &Nuts) -> Vec<u8> {
+    // Required by every mint
+    let mut supported = vec![0, 1, 2, 3, 6];
+
+    if !nuts.nut04.disabled {
+        supported.push(4);
+    }
+    if !nuts.nut05.disabled {
+        supported.push(5);
+    }
+
+    for (nut, settings) in [
+        (7, &nuts.nut07),
+        (8, &nuts.nut08),
+        (9, &nuts.nut09),
+        (10, &nuts.nut10),
+        (11, &nuts.nut11),
+        (12, &nuts.nut12),
+        (14, &nuts.nut14),
+    ] {
+        if settings.supported {
+            supported.push(nut);
+        }
+    }
+
+    if !nuts.nut15.methods.is_empty() {
+        supported.push(15);
+    }
+    if !nuts.nut17.supported.is_empty() {
+        supported.push(17);
+    }
+    if !nuts.nut19.cached_endpoints.is_empty() {
+        supported.push(19);
+    }
+    if nuts.nut20.supported {
+        supported.push(20);
+    }
+
+    #[cfg(feature = "auth")]
+    {
+        if nuts.nut21.is_some() {
+            supported.push(21);
+        }
+        if nuts.nut22.is_some() {
+            supported.push(22);
+        }
+    }
+
+    supported
+}
+
+async fn get_stats(State(api): State<Arc<StatsApi>>) -> Response {
+    match api.stats().await {
+        Ok(stats) => Json(stats).into_response(),
+        Err(err) => {
+            tracing::error!("Could not compute public stats: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volume_buckets() {
+        assert_eq!(bucket(0, 10, 0), 0);
+        assert_eq!(bucket(7, 10, 0), 1);
+        assert_eq!(bucket(12_345, 10, 0), 10_000);
+        assert_eq!(bucket(100_000, 10, 0), 100_000);
+        assert_eq!(bucket(999, 10, 1000), 0);
+        assert_eq!(bucket(1500, 2, 1000), 1024);
+        assert_eq!(bucket(u64::MAX, 10, 0), 10_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_supported_nuts() {
+        let nuts = Nuts::new().nut07(true).nut12(true);
+        let supported = supported_nuts(&nuts);
+
+        assert!(supported.contains(&7));
+        assert!(supported.contains(&12));
+        assert!(!supported.contains(&11));
+        assert!(supported.contains(&0));
+    }
+}
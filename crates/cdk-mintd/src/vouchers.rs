@@ -0,0 +1,205 @@
+//! Voucher API
+//!
+//! Routes for closed-loop deployments, where the mint issues a custom unit
+//! that no payment backend pays in or out, such as loyalty points:
+//!
+//! - `POST /v1/vouchers/issue` signs `outputs` of the voucher unit, with an
+//!   admin key only.
+//! - `POST /v1/vouchers/redeem` spends `inputs` of the voucher unit and returns
+//!   the redemption, with an admin or a redeem key.
+//! - `GET /v1/vouchers/report` returns the issued, redeemed and outstanding
+//!   totals, and `GET /v1/vouchers/redemptions` every redemption, with an admin
+//!   key only.
+//!
+//! Keys are passed as `Authorization: Bearer <key>`.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use cdk::mint::Mint;
+use cdk::nuts::{BlindSignature, BlindedMessage, Proofs};
+use cdk_axum::into_response;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Vouchers;
+
+/// Request to issue vouchers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssueVouchersRequest {
+    /// Outputs of the voucher unit to sign
+    pub outputs: Vec<BlindedMessage>,
+    /// Reference recorded with the issuance
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+/// Signatures of issued vouchers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssueVouchersResponse {
+    /// Blind signatures of the outputs
+    pub signatures: Vec<BlindSignature>,
+}
+
+/// Request to redeem vouchers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedeemVouchersRequest {
+    /// Proofs of the voucher unit to spend
+    pub inputs: Proofs,
+    /// Reference recorded with the redemption
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+/// Role of an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Admin,
+    Redeem,
+}
+
+/// Voucher API of a closed-loop mint
+pub struct VoucherApi {
+    settings: Vouchers,
+    mint: Arc<Mint>,
+}
+
+impl VoucherApi {
+    /// Voucher API with the given settings
+    pub fn new(settings: Vouchers, mint: Arc<Mint>) -> Self {
+        if settings.api_keys.is_empty() {
+            tracing::warn!("Vouchers are enabled without admin API keys, none can be issued");
+        }
+
+        Self { settings, mint }
+    }
+
+    /// Routes of the voucher API
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/v1/vouchers/issue", post(issue))
+            .route("/v1/vouchers/redeem", post(redeem))
+            .route("/v1/vouchers/report", get(report))
+            .route("/v1/vouchers/redemptions", get(redemptions))
+            .with_state(self)
+    }
+
+    /// Whether the request may use an endpoint requiring `role`
+    fn is_authorized(&self, headers: &HeaderMap, role: Role) -> bool {
+        match key_role(&self.settings, headers) {
+            Some(Role::Admin) => true,
+            Some(Role::Redeem) => role == Role::Redeem,
+            None => false,
+        }
+    }
+}
+
+/// Role of the API key the request is authenticated with
+fn key_role(settings: &Vouchers, headers: &HeaderMap) -> Option<Role> {
+    let key = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))?;
+
+    if settings.api_keys.iter().any(|api_key| api_key == key) {
+        Some(Role::Admin)
+    } else if settings
+        .redeem_api_keys
+        .iter()
+        .any(|api_key| api_key == key)
+    {
+        Some(Role::Redeem)
+    } else {
+        None
+    }
+}
+
+async fn issue(
+    State(api): State<Arc<VoucherApi>>,
+    headers: HeaderMap,
+    Json(request): Json<IssueVouchersRequest>,
+) -> Response {
+    if !api.is_authorized(&headers, Role::Admin) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match api
+        .mint
+        .issue_vouchers(request.outputs, request.reference)
+        .await
+    {
+        Ok(signatures) => Json(IssueVouchersResponse { signatures }).into_response(),
+        Err(err) => into_response(err),
+    }
+}
+
+async fn redeem(
+    State(api): State<Arc<VoucherApi>>,
+    headers: HeaderMap,
+    Json(request): Json<RedeemVouchersRequest>,
+) -> Response {
+    if !api.is_authorized(&headers, Role::Redeem) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match api
+        .mint
+        .redeem_vouchers(request.inputs, request.reference)
+        .await
+    {
+        Ok(redemption) => Json(redemption).into_response(),
+        Err(err) => into_response(err),
+    }
+}
+
+async fn report(State(api): State<Arc<VoucherApi>>, headers: HeaderMap) -> Response {
+    if !api.is_authorized(&headers, Role::Admin) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match api.mint.voucher_report().await {
+        Ok(report) => Json(report).into_response(),
+        Err(err) => into_response(err),
+    }
+}
+
+async fn redemptions(State(api): State<Arc<VoucherApi>>, headers: HeaderMap) -> Response {
+    if !api.is_authorized(&headers, Role::Admin) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match api.mint.voucher_redemptions().await {
+        Ok(redemptions) => Json(redemptions).into_response(),
+        Err(err) => into_response(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voucher_key_roles() {
+        let settings = Vouchers {
+            enabled: true,
+            api_keys: vec!["admin".to_string()],
+            redeem_api_keys: vec!["merchant".to_string()],
+            ..Default::default()
+        };
+        let mut headers = HeaderMap::new();
+
+        assert_eq!(key_role(&settings, &headers), None);
+
+        headers.insert(header::AUTHORIZATION, "Bearer other".parse().unwrap());
+        assert_eq!(key_role(&settings, &headers), None);
+
+        headers.insert(header::AUTHORIZATION, "Bearer merchant".parse().unwrap());
+        assert_eq!(key_role(&settings, &headers), Some(Role::Redeem));
+
+        headers.insert(header::AUTHORIZATION, "Bearer admin".parse().unwrap());
+        assert_eq!(key_role(&settings, &headers), Some(Role::Admin));
+    }
+}
@@ -0,0 +1,522 @@
+//! Quote webhooks
+//!
+//! Integrators holding an API key can pass a callback url when creating a mint
+//! or melt quote. The url is registered with the mint, and the dispatcher posts
+//! every state change of the quote to it, signed with HMAC-SHA256 so the
+//! integrator can check the notification came from this mint. Failed deliveries
+//! are retried with exponential backoff.
+//!
+//! Callback urls are posted to by the mint, so unless `allow_private_urls` is set
+//! they must not reach loopback, private or link-local addresses. Host names are
+//! checked when they are resolved for each delivery, and redirects are not followed.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::{to_bytes, Body};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::middleware::{from_fn_with_state, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use cdk::mint::{Mint, QuoteEvent, QuoteId, QuoteWebhook, WebhookDelivery};
+use cdk::util::unix_time;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::config::Webhooks;
+
+/// Header carrying the callback url of a quote creation request
+pub const CALLBACK_URL_HEADER: &str = "X-Cdk-Callback-Url";
+/// Header carrying the hex HMAC-SHA256 of a notification body
+pub const SIGNATURE_HEADER: &str = "X-Cdk-Signature";
+
+/// Largest quote response buffered to read the quote id
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+/// Seconds to wait for a webhook to answer
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// Registers and delivers quote webhooks
+pub struct QuoteWebhooks {
+    settings: Webhooks,
+    client: Client,
+}
+
+impl QuoteWebhooks {
+    /// Webhooks with the given settings
+    pub fn new(settings: Webhooks) -> anyhow::Result<Self> {
+        if settings.api_keys.is_empty() {
+            tracing::warn!("Quote webhooks are enabled without API keys, none can be registered");
+        }
+
+        let client = Client::builder().redirect(Policy::none());
+        let client = match settings.allow_private_urls {
+            true => client,
+            false => client.dns_resolver(Arc::new(PublicResolver)),
+        };
+
+        Ok(Self {
+            client: client.build()?,
+            settings,
+        })
+    }
+
+    /// Whether notifications may be posted to `url`
+    ///
+    /// Host names are checked again when they are resolved for a delivery.
+    fn is_allowed_url(&self, url: &Url) -> bool {
+        if !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+
+        if self.settings.allow_private_urls {
+            return true;
+        }
+
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+
+        // IPv6 hosts are written in brackets
+        match host.trim_start_matches('[').trim_end_matches(']').parse() {
+            Ok(ip) => is_public_ip(ip),
+            Err(_) => {
+                let host = host.trim_end_matches('.').to_ascii_lowercase();
+                host != "localhost" && !host.ends_with(".localhost")
+            }
+        }
+    }
+
+    /// Register the callback urls passed to the quote routes of `router`
+    pub fn layer(self: Arc<Self>, mint: Arc<Mint>, router: Router) -> Router {
+        router.layer(from_fn_with_state((self, mint), register_middleware))
+    }
+
+    /// Whether the request is authenticated with one of the API keys
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|key| self.settings.api_keys.iter().any(|api_key| api_key == key))
+    }
+
+    /// Hex HMAC-SHA256 of a notification body
+    fn sign(&self, body: &[u8]) -> String {
+        let mut engine =
+            hmac::HmacEngine::<sha256::Hash>::new(self.settings.signing_secret.as_bytes());
+        engine.input(body);
+        hmac::Hmac::<sha256::Hash>::from_engine(engine).to_string()
+    }
+
+    /// Unix time of the next attempt after `attempts` failed ones, `None` to give up
+    fn retry_at(&self, attempts: u32, now: u64) -> Option<u64> {
+        if attempts + 1 >= self.settings.max_attempts {
+            return None;
+        }
+
+        let backoff = self
+            .settings
+            .retry_base_secs
+            .saturating_mul(2u64.saturating_pow(attempts));
+
+        Some(now.saturating_add(backoff))
+    }
+
+    /// Deliver due notifications every `poll_interval_secs` until shutdown
+    pub fn spawn_dispatcher(
+        self: Arc<Self>,
+        mint: Arc<Mint>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> JoinHandle<()> {
+        let poll_interval = Duration::from_secs(self.settings.poll_interval_secs.max(1));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+
+            loop {
+                tokio::select! {
+                    _ = shutdown.recv() => break,
+                    _ = interval.tick() => {
+                        // With leader election only the leader delivers
+                        if mint.is_leader() {
+                            self.dispatch(&mint).await;
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    async fn dispatch(&self, mint: &Mint) {
+        let due = match mint.due_quote_webhooks().await {
+            Ok(due) => due,
+            Err(err) => {
+                tracing::error!("Could not read quote webhooks: {}", err);
+                return;
+            }
+        };
+
+        for (webhook, event) in due {
+            let delivery = self.deliver(&webhook, &event).await;
+            let retry_at = match delivery.is_success() {
+                true => None,
+                false => self.retry_at(webhook.attempts, delivery.timestamp),
+            };
+
+            if let Err(err) = mint
+                .record_webhook_delivery(&webhook.quote_id, delivery, retry_at)
+                .await
+            {
+                tracing::error!(
+                    "Could not record webhook delivery for quote {}: {}",
+                    webhook.quote_id,
+                    err
+                );
+            }
+        }
+    }
+
+    async fn deliver(&self, webhook: &QuoteWebhook, event: &QuoteEvent) -> WebhookDelivery {
+        let mut delivery = WebhookDelivery {
+            state: event.state,
+            timestamp: unix_time(),
+            status: None,
+            error: None,
+        };
+
+        match Url::parse(&webhook.url) {
+            Ok(url) if self.is_allowed_url(&url) => (),
+            _ => {
+                delivery.error = Some(format!("Callback url {} is not allowed", webhook.url));
+                return delivery;
+            }
+        }
+
+        let body = match serde_json::to_vec(event) {
+            Ok(body) => body,
+            Err(err) => {
+                delivery.error = Some(err.to_string());
+                return delivery;
+            }
+        };
+
+        let result = self
+            .client
+            .post(&webhook.url)
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, self.sign(&body))
+            .body(body)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) => delivery.status = Some(response.status().as_u16()),
+            Err(err) => delivery.error = Some(err.to_string()),
+        }
+
+        if !delivery.is_success() {
+            tracing::debug!(
+                "Webhook {} did not accept {:?} of quote {}: {:?} {:?}",
+                webhook.url,
+                event.state,
+                event.quote,
+                delivery.status,
+                delivery.error
+            );
+        }
+
+        delivery
+    }
+}
+
+/// Resolves host names to public addresses only
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{} does not resolve to a public address", name.as_str()),
+                )
+                .into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is reachable on the public internet
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // 0.0.0.0/8 and carrier grade NAT 100.64.0.0/10
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(ip));
+            }
+
+            let first = ip.segments()[0];
+
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Whether the request creates a mint or melt quote
+fn is_quote_creation(method: &Method, path: &str) -> bool {
+    method == Method::POST
+        && (path.starts_with("/v1/mint/quote/") || path.starts_with("/v1/melt/quote/"))
+}
+
+async fn register_middleware(
+    State((webhooks, mint)): State<(Arc<QuoteWebhooks>, Arc<Mint>)>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(callback_url) = req.headers().get(CALLBACK_URL_HEADER) else {
+        return next.run(req).await;
+    };
+
+    if !is_quote_creation(req.method(), req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    if !webhooks.is_authorized(req.headers()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    // Checked before the quote is created, so a bad url does not leave a quote without webhook
+    let callback_url = match callback_url
+        .to_str()
+        .ok()
+        .and_then(|url| Url::parse(url).ok())
+    {
+        Some(url) if webhooks.is_allowed_url(&url) => url.to_string(),
+        _ => {
+            return (StatusCode::BAD_REQUEST, "Invalid callback url").into_response();
+        }
+    };
+
+    let response = next.run(req).await;
+
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::error!("Could not read quote response: {}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let quote_id = serde_json::from_slice::<Value>(&body)
+        .ok()
+        .and_then(|value| value.get("quote")?.as_str()?.parse::<QuoteId>().ok());
+
+    // The integrator relies on the callback, so the quote is not handed out without it
+    let Some(quote_id) = quote_id else {
+        tracing::error!("Quote response has no quote id, webhook not registered");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not register callback url",
+        )
+            .into_response();
+    };
+
+    if let Err(err) = mint.register_quote_webhook(&quote_id, &callback_url).await {
+        tracing::error!("Could not register webhook for quote {}: {}", quote_id, err);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not register callback url",
+        )
+            .into_response();
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use axum::routing::post;
+    use cdk::mint::{QuoteEventState, QuoteOperation};
+
+    use super::*;
+
+    fn webhooks_with(allow_private_urls: bool) -> QuoteWebhooks {
+        QuoteWebhooks::new(Webhooks {
+            enabled: true,
+            api_keys: vec!["key".to_string()],
+            signing_secret: "secret".to_string(),
+            max_attempts: 3,
+            retry_base_secs: 10,
+            allow_private_urls,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn webhooks() -> QuoteWebhooks {
+        webhooks_with(false)
+    }
+
+    fn registered_webhook(url: String) -> QuoteWebhook {
+        QuoteWebhook {
+            quote_id: QuoteId::new_uuid(),
+            operation: QuoteOperation::Mint,
+            url,
+            created: unix_time(),
+            delivered_state: None,
+            attempts: 0,
+            retry_at: 0,
+            deliveries: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_webhook_url_restrictions() {
+        let webhooks = webhooks();
+        let allowed = |url: &str| webhooks.is_allowed_url(&Url::parse(url).unwrap());
+
+        assert!(allowed("https://shop.example.com/cashu"));
+        assert!(allowed("http://93.184.216.34:8080/hook"));
+        assert!(!allowed("ftp://shop.example.com"));
+        assert!(!allowed("http://localhost:3338"));
+        assert!(!allowed("http://api.localhost"));
+        assert!(!allowed("http://127.0.0.1"));
+        assert!(!allowed("http://10.0.0.1"));
+        assert!(!allowed("http://192.168.1.1"));
+        assert!(!allowed("http://169.254.169.254/latest/meta-data"));
+        assert!(!allowed("http://100.64.0.1"));
+        assert!(!allowed("http://0.0.0.0"));
+        assert!(!allowed("http://[::1]:8080"));
+        assert!(!allowed("http://[fd00::1]"));
+        assert!(!allowed("http://[fe80::1]"));
+        assert!(!allowed("http://[::ffff:127.0.0.1]"));
+        assert!(allowed("http://[2606:4700::1111]"));
+
+        assert!(webhooks_with(true).is_allowed_url(&Url::parse("http://127.0.0.1").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_delivery() {
+        let received = Arc::new(Mutex::new(Vec::<(String, Vec<u8>)>::new()));
+
+        let app = Router::new()
+            .route(
+                "/hook",
+                post({
+                    let received = Arc::clone(&received);
+                    move |headers: HeaderMap, body: axum::body::Bytes| async move {
+                        let signature = headers
+                            .get(SIGNATURE_HEADER)
+                            .and_then(|value| value.to_str().ok())
+                            .unwrap_or_default()
+                            .to_string();
+                        received.lock().unwrap().push((signature, body.to_vec()));
+                        StatusCode::OK
+                    }
+                }),
+            )
+            .route("/fail", post(|| async { StatusCode::SERVICE_UNAVAILABLE }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let webhooks = webhooks_with(true);
+        let webhook = registered_webhook(format!("http://{addr}/hook"));
+        let event = QuoteEvent {
+            quote: webhook.quote_id.clone(),
+            operation: QuoteOperation::Mint,
+            state: QuoteEventState::Paid,
+            timestamp: unix_time(),
+        };
+
+        // The notification arrives signed with the secret
+        let delivery = webhooks.deliver(&webhook, &event).await;
+        assert!(delivery.is_success());
+        assert_eq!(delivery.state, QuoteEventState::Paid);
+
+        let (signature, body) = received.lock().unwrap().pop().unwrap();
+        assert_eq!(signature, webhooks.sign(&body));
+        assert_eq!(serde_json::from_slice::<QuoteEvent>(&body).unwrap(), event);
+
+        // Failed deliveries are recorded with their status
+        let failing = registered_webhook(format!("http://{addr}/fail"));
+        let delivery = webhooks.deliver(&failing, &event).await;
+        assert!(!delivery.is_success());
+        assert_eq!(delivery.status, Some(503));
+
+        // Private addresses are refused without being contacted
+        let delivery = webhooks_with(false).deliver(&webhook, &event).await;
+        assert!(!delivery.is_success());
+        assert_eq!(delivery.status, None);
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_webhook_retry_backoff() {
+        let webhooks = webhooks();
+
+        assert_eq!(webhooks.retry_at(0, 100), Some(110));
+        assert_eq!(webhooks.retry_at(1, 100), Some(120));
+        assert_eq!(webhooks.retry_at(2, 100), None);
+    }
+
+    #[test]
+    fn test_webhook_authorization() {
+        let webhooks = webhooks();
+        let mut headers = HeaderMap::new();
+
+        assert!(!webhooks.is_authorized(&headers));
+
+        headers.insert(header::AUTHORIZATION, "Bearer other".parse().unwrap());
+        assert!(!webhooks.is_authorized(&headers));
+
+        headers.insert(header::AUTHORIZATION, "Bearer key".parse().unwrap());
+        assert!(webhooks.is_authorized(&headers));
+
+        assert!(is_quote_creation(&Method::POST, "/v1/mint/quote/bolt11"));
+        assert!(!is_quote_creation(&Method::GET, "/v1/mint/quote/bolt11/id"));
+        assert!(!is_quote_creation(&Method::POST, "/v1/swap"));
+    }
+}
@@ -0,0 +1,137 @@
+//! Direct messages
+//!
+//! Messages are sent as NIP-17 private direct messages, gift wrapped so relays
+//! see neither the sender nor the time they were sent. NIP-04 direct messages
+//! are still read, as earlier versions sent tokens that way.
+
+use nostr_sdk::nips::nip04;
+use nostr_sdk::nips::nip59::UnwrappedGift;
+use nostr_sdk::{
+    Client, Event, EventBuilder, EventId, Keys, Kind, PublicKey, Timestamp, UnsignedEvent,
+};
+
+use crate::Error;
+
+/// Gift wraps are dated up to two days before they were sent (NIP-59), so
+/// queries for private direct messages sent since a time start this much earlier
+pub const GIFT_WRAP_BACKDATE: u64 = 2 * 24 * 60 * 60;
+
+/// Direct message read from an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectMessage {
+    /// Sender of the message
+    pub sender: PublicKey,
+    /// Decrypted text of the message
+    pub content: String,
+    /// Time the message was written, not the time of a gift wrap carrying it
+    pub created_at: Timestamp,
+}
+
+/// Send `message` from `keys` to `receiver` as a NIP-17 private direct message
+///
+/// Returns the id of the gift wrap, relays failing to accept it are logged.
+pub async fn send_private_message(
+    client: &Client,
+    keys: &Keys,
+    receiver: &PublicKey,
+    message: &str,
+) -> Result<EventId, Error> {
+    let event = EventBuilder::private_msg(keys, *receiver, message, []).await?;
+
+    let output = client.send_event(&event).await?;
+    if !output.failed.is_empty() {
+        tracing::warn!(
+            "Could not publish direct message to {} relays",
+            output.failed.len()
+        );
+    }
+
+    Ok(output.val)
+}
+
+/// Gift wrap `rumor` of `keys` for `receiver` (NIP-59)
+pub async fn gift_wrap(
+    keys: &Keys,
+    receiver: &PublicKey,
+    rumor: UnsignedEvent,
+) -> Result<Event, Error> {
+    Ok(EventBuilder::gift_wrap(keys, receiver, rumor, []).await?)
+}
+
+/// Unwrap a gift wrap sent to `keys` (NIP-59)
+pub async fn unwrap_gift_wrap(keys: &Keys, event: &Event) -> Result<UnwrappedGift, Error> {
+    Ok(UnwrappedGift::from_gift_wrap(keys, event).await?)
+}
+
+/// Direct message to `keys` carried by `event`
+///
+/// Reads NIP-04 direct messages and NIP-17 private direct messages, other
+/// events are `None`.
+pub async fn direct_message(keys: &Keys, event: &Event) -> Result<Option<DirectMessage>, Error> {
+    match event.kind {
+        Kind::EncryptedDirectMessage => {
+            let content = nip04::decrypt(keys.secret_key(), &event.pubkey, &event.content)?;
+            Ok(Some(DirectMessage {
+                sender: event.pubkey,
+                content,
+                created_at: event.created_at,
+            }))
+        }
+        Kind::GiftWrap => {
+            let unwrapped = unwrap_gift_wrap(keys, event).await?;
+            Ok(
+                (unwrapped.rumor.kind == Kind::PrivateDirectMessage).then(|| DirectMessage {
+                    sender: unwrapped.sender,
+                    content: unwrapped.rumor.content,
+                    created_at: unwrapped.rumor.created_at,
+                }),
+            )
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::Tag;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_direct_messages_are_read() {
+        let sender = Keys::generate();
+        let receiver = Keys::generate();
+
+        let gift_wrap = EventBuilder::private_msg(&sender, receiver.public_key(), "cashuA", [])
+            .await
+            .unwrap();
+        assert_eq!(gift_wrap.kind, Kind::GiftWrap);
+        let message = direct_message(&receiver, &gift_wrap).await.unwrap().unwrap();
+        assert_eq!(message.sender, sender.public_key());
+        assert_eq!(message.content, "cashuA");
+        // The gift wrap is backdated, the message keeps the time it was written
+        assert!(message.created_at >= gift_wrap.created_at);
+        assert!(message.created_at.as_u64() <= Timestamp::now().as_u64());
+        assert!(direct_message(&sender, &gift_wrap).await.is_err());
+
+        let content =
+            nip04::encrypt(sender.secret_key(), &receiver.public_key(), "cashuB").unwrap();
+        let nip04_message = EventBuilder::new(Kind::EncryptedDirectMessage, content)
+            .tag(Tag::public_key(receiver.public_key()))
+            .sign_with_keys(&sender)
+            .unwrap();
+        assert_eq!(
+            direct_message(&receiver, &nip04_message).await.unwrap(),
+            Some(DirectMessage {
+                sender: sender.public_key(),
+                content: "cashuB".to_string(),
+                created_at: nip04_message.created_at,
+            })
+        );
+
+        let note = EventBuilder::text_note("hello")
+            .sign_with_keys(&sender)
+            .unwrap();
+        assert_eq!(direct_message(&receiver, &note).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,32 @@
+//! Nostr errors
+
+use nostr_sdk::event::builder;
+use nostr_sdk::nips::{nip04, nip59};
+use nostr_sdk::{client, key};
+use thiserror::Error;
+
+/// Nostr error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// No relay was given
+    #[error("No relays provided")]
+    NoRelays,
+    /// Relay could not be added to a client
+    #[error("Add relay {0}: {1}")]
+    AddRelay(String, client::Error),
+    /// Invalid key
+    #[error("Invalid nostr key: {0}")]
+    Key(#[from] key::Error),
+    /// Relay client error
+    #[error(transparent)]
+    Client(#[from] client::Error),
+    /// Event could not be built or signed
+    #[error(transparent)]
+    EventBuilder(#[from] builder::Error),
+    /// NIP-04 encryption error
+    #[error(transparent)]
+    Nip04(#[from] nip04::Error),
+    /// NIP-59 gift wrap error
+    #[error(transparent)]
+    Nip59(#[from] nip59::Error),
+}
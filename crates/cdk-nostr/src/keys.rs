@@ -0,0 +1,37 @@
+//! Nostr keys of cashu keys
+
+use cashu::nuts::{PublicKey, SecretKey};
+use nostr_sdk::Keys;
+
+use crate::Error;
+
+/// Nostr keys of a cashu secret key
+pub fn nostr_keys(secret_key: &SecretKey) -> Result<Keys, Error> {
+    let secret_key = nostr_sdk::SecretKey::from_slice(&secret_key.to_secret_bytes())?;
+
+    Ok(Keys::new(secret_key))
+}
+
+/// Nostr pubkey of a cashu pubkey
+pub fn nostr_public_key(pubkey: &PublicKey) -> Result<nostr_sdk::PublicKey, Error> {
+    Ok(nostr_sdk::PublicKey::from_hex(
+        &pubkey.x_only_public_key().to_string(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nostr_keys_match_cashu_keys() {
+        let secret_key = SecretKey::generate();
+
+        let keys = nostr_keys(&secret_key).unwrap();
+
+        assert_eq!(
+            keys.public_key(),
+            nostr_public_key(&secret_key.public_key()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Nostr event kinds used by CDK
+
+use nostr_sdk::Kind;
+
+/// Nostr event kinds used by CDK
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// NIP-04 direct message, tokens sent by earlier versions
+    TokenDirectMessage,
+    /// NIP-17 private direct message, sent gift wrapped
+    PrivateDirectMessage,
+    /// NIP-59 gift wrap
+    GiftWrap,
+    /// NIP-87 cashu mint announcement
+    MintAnnouncement,
+    /// NIP-87 recommendation
+    Recommendation,
+    /// Blossom authorization event (BUD-01)
+    BlossomAuth,
+}
+
+impl EventKind {
+    /// Kind number of the event
+    pub const fn as_u16(self) -> u16 {
+        match self {
+            Self::TokenDirectMessage => 4,
+            Self::PrivateDirectMessage => 14,
+            Self::GiftWrap => 1059,
+            Self::MintAnnouncement => 38172,
+            Self::Recommendation => 38000,
+            Self::BlossomAuth => 24242,
+        }
+    }
+}
+
+impl From<EventKind> for Kind {
+    fn from(kind: EventKind) -> Self {
+        Kind::from_u16(kind.as_u16())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_kinds_match_nips() {
+        assert_eq!(
+            Kind::from(EventKind::TokenDirectMessage),
+            Kind::EncryptedDirectMessage
+        );
+        assert_eq!(
+            Kind::from(EventKind::PrivateDirectMessage),
+            Kind::PrivateDirectMessage
+        );
+        assert_eq!(Kind::from(EventKind::GiftWrap), Kind::GiftWrap);
+    }
+}
@@ -0,0 +1,27 @@
+//! Nostr for CDK
+//!
+//! Token delivery, mint discovery, mint attestations, atomic swaps, payment
+//! requests, the `cdk-cli` watch command and the `cdk-mintd` notifier all talk to
+//! nostr relays. Their relay connections are shared through a [`RelayPool`],
+//! and the event kinds they use, the conversion of cashu keys to nostr keys and
+//! direct messages are kept here, so every feature handles them the same way.
+
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![warn(rustdoc::bare_urls)]
+
+pub mod direct_message;
+pub mod error;
+pub mod keys;
+pub mod kind;
+pub mod pool;
+
+pub use direct_message::{
+    direct_message, gift_wrap, send_private_message, unwrap_gift_wrap, DirectMessage,
+    GIFT_WRAP_BACKDATE,
+};
+pub use error::Error;
+pub use keys::{nostr_keys, nostr_public_key};
+pub use kind::EventKind;
+pub use nostr_sdk;
+pub use pool::{RelayPool, RelayUse, RELAY_TIMEOUT};
@@ -0,0 +1,131 @@
+//! Relay pool
+//!
+//! Clients are not bound to keys: events are signed and gift wraps unwrapped
+//! with the keys of each feature, so every feature using the same relays
+//! shares one connection to them.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use nostr_sdk::Client;
+use tokio::sync::RwLock;
+
+use crate::Error;
+
+/// Timeout for relay queries
+pub const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the relays of a client are used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelayUse {
+    /// Events are only fetched from the relays
+    Read,
+    /// Events are only published to the relays
+    Write,
+    /// Events are fetched from and published to the relays
+    ReadWrite,
+}
+
+/// Clients connected to sets of relays, kept for later use
+#[derive(Default)]
+pub struct RelayPool {
+    clients: RwLock<HashMap<(RelayUse, Vec<String>), Client>>,
+}
+
+impl fmt::Debug for RelayPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelayPool").finish_non_exhaustive()
+    }
+}
+
+impl RelayPool {
+    /// Pool shared by everything in the process
+    pub fn global() -> &'static Self {
+        static POOL: OnceLock<RelayPool> = OnceLock::new();
+
+        POOL.get_or_init(Self::default)
+    }
+
+    /// Client connected to `relays`, connected on first use
+    pub async fn client(&self, relays: &[String], relay_use: RelayUse) -> Result<Client, Error> {
+        if relays.is_empty() {
+            return Err(Error::NoRelays);
+        }
+
+        let mut relays = relays.to_vec();
+        relays.sort();
+        relays.dedup();
+        let key = (relay_use, relays);
+
+        if let Some(client) = self.clients.read().await.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let mut clients = self.clients.write().await;
+
+        // Another task may have connected while waiting for the lock
+        if let Some(client) = clients.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let client = Client::default();
+        for relay in &key.1 {
+            let added = match relay_use {
+                RelayUse::Read => client.add_read_relay(relay.as_str()).await,
+                RelayUse::Write => client.add_write_relay(relay.as_str()).await,
+                RelayUse::ReadWrite => client.add_relay(relay.as_str()).await,
+            };
+            added.map_err(|e| Error::AddRelay(relay.clone(), e))?;
+        }
+
+        client.connect().await;
+        tracing::debug!("Connected to nostr relays {:?}", key.1);
+
+        clients.insert(key, client.clone());
+
+        Ok(client)
+    }
+
+    /// Disconnect and drop every client of the pool
+    pub async fn shutdown(&self) {
+        let clients: Vec<Client> = self.clients.write().await.drain().map(|(_, c)| c).collect();
+
+        for client in clients {
+            client.disconnect().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_client_is_reused_for_the_same_relays() {
+        let pool = RelayPool::default();
+
+        assert!(matches!(
+            pool.client(&[], RelayUse::Read).await,
+            Err(Error::NoRelays)
+        ));
+
+        let relays = vec![
+            "wss://relay.example.com".to_string(),
+            "wss://other.example.com".to_string(),
+        ];
+        pool.client(&relays, RelayUse::Read).await.unwrap();
+
+        // Order and duplicates of the relays do not matter
+        let reordered = vec![relays[1].clone(), relays[0].clone(), relays[1].clone()];
+        pool.client(&reordered, RelayUse::Read).await.unwrap();
+        assert_eq!(pool.clients.read().await.len(), 1);
+
+        pool.client(&relays, RelayUse::Write).await.unwrap();
+        assert_eq!(pool.clients.read().await.len(), 2);
+
+        pool.shutdown().await;
+        assert!(pool.clients.read().await.is_empty());
+    }
+}
@@ -172,16 +172,24 @@ impl MintPayment for PaymentProcessorClient {
             cdk_common::payment::OutgoingPaymentOptions::Bolt12(_) => {
                 OutgoingPaymentRequestType::Bolt12Offer
             }
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(_) => {
+                OutgoingPaymentRequestType::Keysend
+            }
         };
 
         let proto_request = match &options {
             cdk_common::payment::OutgoingPaymentOptions::Bolt11(opts) => opts.bolt11.to_string(),
             cdk_common::payment::OutgoingPaymentOptions::Bolt12(opts) => opts.offer.to_string(),
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(opts) => opts.pubkey.to_string(),
         };
 
+        // Keysend amounts are sent as amountless melt options
         let proto_options = match &options {
             cdk_common::payment::OutgoingPaymentOptions::Bolt11(opts) => opts.melt_options,
             cdk_common::payment::OutgoingPaymentOptions::Bolt12(opts) => opts.melt_options,
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(opts) => {
+                Some(cdk_common::MeltOptions::new_amountless(opts.amount_msat))
+            }
         };
 
         let response = inner
@@ -218,6 +226,7 @@ impl MintPayment for PaymentProcessorClient {
                             max_fee_amount: opts.max_fee_amount.map(Into::into),
                             timeout_secs: opts.timeout_secs,
                             melt_options: opts.melt_options.map(Into::into),
+                            idempotency_key: opts.idempotency_key,
                         },
                     )),
                 }
@@ -230,6 +239,20 @@ impl MintPayment for PaymentProcessorClient {
                             max_fee_amount: opts.max_fee_amount.map(Into::into),
                             timeout_secs: opts.timeout_secs,
                             melt_options: opts.melt_options.map(Into::into),
+                            idempotency_key: opts.idempotency_key,
+                        },
+                    )),
+                }
+            }
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(opts) => {
+                super::OutgoingPaymentVariant {
+                    options: Some(super::outgoing_payment_variant::Options::Keysend(
+                        super::KeysendOutgoingPaymentOptions {
+                            pubkey: opts.pubkey.to_string(),
+                            amount_msat: opts.amount_msat.into(),
+                            max_fee_amount: opts.max_fee_amount.map(Into::into),
+                            timeout_secs: opts.timeout_secs,
+                            idempotency_key: opts.idempotency_key,
                         },
                     )),
                 }
@@ -238,6 +238,7 @@ impl CdkPaymentProcessor for PaymentProcessorServer {
                         max_fee_amount: None,
                         timeout_secs: None,
                         melt_options: request.options.map(Into::into),
+                        idempotency_key: None,
                     },
                 ))
             }
@@ -251,6 +252,26 @@ impl CdkPaymentProcessor for PaymentProcessorServer {
                         max_fee_amount: None,
                         timeout_secs: None,
                         melt_options: request.options.map(Into::into),
+                        idempotency_key: None,
+                    },
+                ))
+            }
+            OutgoingPaymentRequestType::Keysend => {
+                let pubkey = cdk_common::PublicKey::from_str(&request.request)
+                    .map_err(|_| Status::invalid_argument("Invalid node id"))?;
+
+                let amount_msat = request
+                    .options
+                    .map(|options| cdk_common::MeltOptions::from(options).amount_msat())
+                    .ok_or_else(|| Status::invalid_argument("Missing keysend amount"))?;
+
+                cdk_common::payment::OutgoingPaymentOptions::Keysend(Box::new(
+                    cdk_common::payment::KeysendOutgoingPaymentOptions {
+                        pubkey,
+                        amount_msat,
+                        max_fee_amount: None,
+                        timeout_secs: None,
+                        idempotency_key: None,
                     },
                 ))
             }
@@ -292,6 +313,7 @@ impl CdkPaymentProcessor for PaymentProcessorServer {
                         max_fee_amount: opts.max_fee_amount.map(Into::into),
                         timeout_secs: opts.timeout_secs,
                         melt_options: opts.melt_options.map(Into::into),
+                        idempotency_key: opts.idempotency_key,
                     }),
                 );
 
@@ -308,6 +330,23 @@ impl CdkPaymentProcessor for PaymentProcessorServer {
                         max_fee_amount: opts.max_fee_amount.map(Into::into),
                         timeout_secs: opts.timeout_secs,
                         melt_options: opts.melt_options.map(Into::into),
+                        idempotency_key: opts.idempotency_key,
+                    }),
+                );
+
+                (CurrencyUnit::Msat, payment_options)
+            }
+            outgoing_payment_variant::Options::Keysend(opts) => {
+                let pubkey = cdk_common::PublicKey::from_str(&opts.pubkey)
+                    .map_err(|_| Status::invalid_argument("Invalid node id"))?;
+
+                let payment_options = cdk_common::payment::OutgoingPaymentOptions::Keysend(
+                    Box::new(cdk_common::payment::KeysendOutgoingPaymentOptions {
+                        pubkey,
+                        amount_msat: opts.amount_msat.into(),
+                        max_fee_amount: opts.max_fee_amount.map(Into::into),
+                        timeout_secs: opts.timeout_secs,
+                        idempotency_key: opts.idempotency_key,
                     }),
                 );
 
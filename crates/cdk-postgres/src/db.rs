@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use cdk_common::database::Error;
 use cdk_sql_common::run_db_operation;
 use cdk_sql_common::stmt::{Column, Statement};
@@ -20,19 +22,38 @@ fn to_pgsql_error(err: PgError) -> Error {
 }
 
 #[inline(always)]
-pub async fn pg_batch(conn: &Client, statement: Statement) -> Result<(), Error> {
+pub async fn pg_batch(
+    conn: &Client,
+    statement: Statement,
+    slow_query_threshold: Duration,
+) -> Result<(), Error> {
+    let name = statement.name();
     let (sql, _placeholder_values) = statement.to_sql()?;
 
-    run_db_operation(&sql, conn.batch_execute(&sql), to_pgsql_error).await
+    run_db_operation(
+        &name,
+        &sql,
+        slow_query_threshold,
+        conn.batch_execute(&sql),
+        to_pgsql_error,
+    )
+    .await
 }
 
 #[inline(always)]
-pub async fn pg_execute(conn: &Client, statement: Statement) -> Result<usize, Error> {
+pub async fn pg_execute(
+    conn: &Client,
+    statement: Statement,
+    slow_query_threshold: Duration,
+) -> Result<usize, Error> {
+    let name = statement.name();
     let (sql, placeholder_values) = statement.to_sql()?;
     let prepared_statement = conn.prepare(&sql).await.map_err(to_pgsql_error)?;
 
     run_db_operation(
+        &name,
         &sql,
+        slow_query_threshold,
         async {
             conn.execute_raw(
                 &prepared_statement,
@@ -53,12 +74,16 @@ pub async fn pg_execute(conn: &Client, statement: Statement) -> Result<usize, Er
 pub async fn pg_fetch_one(
     conn: &Client,
     statement: Statement,
+    slow_query_threshold: Duration,
 ) -> Result<Option<Vec<Column>>, Error> {
+    let name = statement.name();
     let (sql, placeholder_values) = statement.to_sql()?;
     let prepared_statement = conn.prepare(&sql).await.map_err(to_pgsql_error)?;
 
     run_db_operation(
+        &name,
         &sql,
+        slow_query_threshold,
         async {
             let stream = conn
                 .query_raw(
@@ -88,12 +113,19 @@ pub async fn pg_fetch_one(
 }
 
 #[inline(always)]
-pub async fn pg_fetch_all(conn: &Client, statement: Statement) -> Result<Vec<Vec<Column>>, Error> {
+pub async fn pg_fetch_all(
+    conn: &Client,
+    statement: Statement,
+    slow_query_threshold: Duration,
+) -> Result<Vec<Vec<Column>>, Error> {
+    let name = statement.name();
     let (sql, placeholder_values) = statement.to_sql()?;
     let prepared_statement = conn.prepare(&sql).await.map_err(to_pgsql_error)?;
 
     run_db_operation(
+        &name,
         &sql,
+        slow_query_threshold,
         async {
             let stream = conn
                 .query_raw(
@@ -124,12 +156,19 @@ pub async fn pg_fetch_all(conn: &Client, statement: Statement) -> Result<Vec<Vec
 }
 
 #[inline(always)]
-pub async fn pg_pluck(conn: &Client, statement: Statement) -> Result<Option<Column>, Error> {
+pub async fn pg_pluck(
+    conn: &Client,
+    statement: Statement,
+    slow_query_threshold: Duration,
+) -> Result<Option<Column>, Error> {
+    let name = statement.name();
     let (sql, placeholder_values) = statement.to_sql()?;
     let prepared_statement = conn.prepare(&sql).await.map_err(to_pgsql_error)?;
 
     run_db_operation(
+        &name,
         &sql,
+        slow_query_threshold,
         async {
             let stream = conn
                 .query_raw(
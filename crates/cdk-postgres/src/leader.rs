@@ -0,0 +1,118 @@
+//! Leader election with PostgreSQL advisory locks
+//!
+//! Advisory locks are held by a session, so the leader keeps a dedicated
+//! connection outside the pool. The lock is released by PostgreSQL as soon as
+//! that connection is closed, which lets another instance take over when the
+//! leader dies.
+
+use cdk_common::database::{Error, MintLeaderElection};
+use tokio::sync::Mutex;
+use tokio_postgres::{connect, Client};
+
+use crate::{PgConfig, SslMode};
+
+/// Name of the advisory lock, scoped by schema so mints sharing a server do not
+/// elect a single leader
+const LEADER_LOCK_NAME: &str = "cdk_mint_leader";
+
+/// Connection holding the advisory lock
+#[derive(Debug)]
+struct LeaderSession {
+    client: Client,
+    is_leader: bool,
+}
+
+/// Leader election between mint instances sharing a PostgreSQL database
+#[derive(Debug)]
+pub struct PgLeaderElection {
+    config: PgConfig,
+    lock_name: String,
+    session: Mutex<Option<LeaderSession>>,
+}
+
+impl PgLeaderElection {
+    /// Creates a new instance, the connection is opened on the first election
+    pub fn new(conn_str: &str) -> Self {
+        let config = PgConfig::from(conn_str);
+        let lock_name = match &config.schema {
+            Some(schema) => format!("{LEADER_LOCK_NAME}:{schema}"),
+            None => LEADER_LOCK_NAME.to_owned(),
+        };
+
+        Self {
+            config,
+            lock_name,
+            session: Mutex::new(None),
+        }
+    }
+
+    async fn connect(&self) -> Result<Client, Error> {
+        let client = match &self.config.tls {
+            SslMode::NoTls(tls) => {
+                let (client, connection) = connect(&self.config.url, *tls)
+                    .await
+                    .map_err(|e| Error::Database(Box::new(e)))?;
+                tokio::spawn(async move {
+                    let _ = connection.await;
+                });
+                client
+            }
+            SslMode::NativeTls(tls) => {
+                let (client, connection) = connect(&self.config.url, tls.clone())
+                    .await
+                    .map_err(|e| Error::Database(Box::new(e)))?;
+                tokio::spawn(async move {
+                    let _ = connection.await;
+                });
+                client
+            }
+        };
+
+        Ok(client)
+    }
+}
+
+#[async_trait::async_trait]
+impl MintLeaderElection for PgLeaderElection {
+    type Err = Error;
+
+    async fn try_acquire_leadership(&self) -> Result<bool, Self::Err> {
+        let mut session = self.session.lock().await;
+
+        // The lock went away with a closed connection
+        if session.as_ref().is_some_and(|s| s.client.is_closed()) {
+            tracing::warn!("Leader election connection closed");
+            *session = None;
+        }
+
+        let session = match session.as_mut() {
+            Some(session) => session,
+            None => session.insert(LeaderSession {
+                client: self.connect().await?,
+                is_leader: false,
+            }),
+        };
+
+        if !session.is_leader {
+            let row = session
+                .client
+                .query_one(
+                    "SELECT pg_try_advisory_lock(hashtext($1)::bigint)",
+                    &[&self.lock_name],
+                )
+                .await
+                .map_err(|e| Error::Database(Box::new(e)))?;
+
+            session.is_leader = row.get(0);
+        }
+
+        Ok(session.is_leader)
+    }
+
+    async fn release_leadership(&self) -> Result<(), Self::Err> {
+        // Closing the connection releases the lock
+        self.session.lock().await.take();
+
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use cdk_sql_common::database::{DatabaseConnector, DatabaseExecutor, GenericTrans
 use cdk_sql_common::mint::SQLMintAuthDatabase;
 use cdk_sql_common::pool::{DatabaseConfig, DatabasePool};
 use cdk_sql_common::stmt::{Column, Statement};
-use cdk_sql_common::{SQLMintDatabase, SQLWalletDatabase};
+use cdk_sql_common::{SQLMintDatabase, SQLWalletDatabase, DEFAULT_SLOW_QUERY_THRESHOLD};
 use db::{pg_batch, pg_execute, pg_fetch_all, pg_fetch_one, pg_pluck};
 use native_tls::TlsConnector;
 use postgres_native_tls::MakeTlsConnector;
@@ -17,8 +17,13 @@ use tokio::time::timeout;
 use tokio_postgres::{connect, Client, Error as PgError, NoTls};
 
 mod db;
+#[cfg(feature = "mint")]
+mod leader;
 mod value;
 
+#[cfg(feature = "mint")]
+pub use leader::PgLeaderElection;
+
 #[derive(Debug)]
 pub struct PgConnectionPool;
 
@@ -56,6 +61,7 @@ pub struct PgConfig {
     url: String,
     schema: Option<String>,
     tls: SslMode,
+    slow_query_threshold: Duration,
 }
 
 impl DatabaseConfig for PgConfig {
@@ -69,6 +75,12 @@ impl DatabaseConfig for PgConfig {
 }
 
 impl PgConfig {
+    /// Log queries slower than `threshold`, defaults to [`DEFAULT_SLOW_QUERY_THRESHOLD`]
+    pub fn with_slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = threshold;
+        self
+    }
+
     /// strip schema from the connection string
     fn strip_schema(input: &str) -> (Option<String>, String) {
         let mut schema: Option<String> = None;
@@ -129,6 +141,7 @@ impl From<&str> for PgConfig {
             url: conn_str.to_owned(),
             schema,
             tls,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
         }
     }
 }
@@ -153,6 +166,7 @@ impl DatabasePool for PgConnectionPool {
 #[derive(Debug)]
 pub struct PostgresConnection {
     timeout: Duration,
+    slow_query_threshold: Duration,
     error: Arc<Mutex<Option<cdk_common::database::Error>>>,
     result: Arc<OnceLock<Client>>,
     notify: Arc<Notify>,
@@ -167,6 +181,7 @@ impl PostgresConnection {
         let error_clone = failed.clone();
         let result_clone = result.clone();
         let notify_clone = notify.clone();
+        let slow_query_threshold = config.slow_query_threshold;
 
         async fn select_schema(conn: &Client, schema: &str) -> Result<(), Error> {
             conn.batch_execute(&format!(
@@ -247,6 +262,7 @@ impl PostgresConnection {
         Self {
             error: failed,
             timeout,
+            slow_query_threshold,
             result,
             notify,
         }
@@ -292,23 +308,23 @@ impl DatabaseExecutor for PostgresConnection {
     }
 
     async fn execute(&self, statement: Statement) -> Result<usize, Error> {
-        pg_execute(self.inner().await?, statement).await
+        pg_execute(self.inner().await?, statement, self.slow_query_threshold).await
     }
 
     async fn fetch_one(&self, statement: Statement) -> Result<Option<Vec<Column>>, Error> {
-        pg_fetch_one(self.inner().await?, statement).await
+        pg_fetch_one(self.inner().await?, statement, self.slow_query_threshold).await
     }
 
     async fn fetch_all(&self, statement: Statement) -> Result<Vec<Vec<Column>>, Error> {
-        pg_fetch_all(self.inner().await?, statement).await
+        pg_fetch_all(self.inner().await?, statement, self.slow_query_threshold).await
     }
 
     async fn pluck(&self, statement: Statement) -> Result<Option<Column>, Error> {
-        pg_pluck(self.inner().await?, statement).await
+        pg_pluck(self.inner().await?, statement, self.slow_query_threshold).await
     }
 
     async fn batch(&self, statement: Statement) -> Result<(), Error> {
-        pg_batch(self.inner().await?, statement).await
+        pg_batch(self.inner().await?, statement, self.slow_query_threshold).await
     }
 }
 
@@ -342,4 +358,61 @@ mod test {
     }
 
     mint_db_test!(provide_db);
+
+    mod read_replica {
+        use cdk_common::mint_db_test;
+
+        use super::*;
+
+        /// The replica is the primary itself, so reads never lag behind writes
+        async fn provide_db(test_id: String) -> MintPgDatabase {
+            let db_url = std::env::var("CDK_MINTD_DATABASE_URL")
+                .or_else(|_| std::env::var("PG_DB_URL"))
+                .unwrap_or(
+                    "host=localhost user=test password=test dbname=testdb port=5433".to_owned(),
+                );
+
+            let db_url = format!("{db_url} schema={test_id}");
+
+            MintPgDatabase::new_with_read_replica(db_url.as_str(), db_url.as_str())
+                .await
+                .expect("database")
+        }
+
+        mint_db_test!(provide_db);
+    }
+
+    #[tokio::test]
+    async fn leader_election_failover() {
+        use cdk_common::database::MintLeaderElection;
+
+        let db_url = std::env::var("CDK_MINTD_DATABASE_URL")
+            .or_else(|_| std::env::var("PG_DB_URL"))
+            .unwrap_or("host=localhost user=test password=test dbname=testdb port=5433".to_owned());
+        let db_url = format!("{db_url} schema=leader_election_failover");
+
+        let first = PgLeaderElection::new(&db_url);
+        let second = PgLeaderElection::new(&db_url);
+
+        assert!(first.try_acquire_leadership().await.expect("election"));
+        assert!(!second.try_acquire_leadership().await.expect("election"));
+
+        // Leadership is kept while the leader is alive
+        assert!(first.try_acquire_leadership().await.expect("election"));
+        assert!(!second.try_acquire_leadership().await.expect("election"));
+
+        first.release_leadership().await.expect("release");
+
+        // The server releases the lock once it notices the closed connection
+        let mut took_over = false;
+        for _ in 0..50 {
+            if second.try_acquire_leadership().await.expect("election") {
+                took_over = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(took_over);
+        assert!(!first.try_acquire_leadership().await.expect("election"));
+    }
 }
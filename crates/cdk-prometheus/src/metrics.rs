@@ -28,6 +28,7 @@ pub struct CdkMetrics {
     // Database metrics
     db_operations_total: IntCounter,
     db_operation_duration: HistogramVec,
+    db_slow_queries_total: IntCounterVec,
     db_connections_active: IntGauge,
 
     // Error metrics
@@ -37,6 +38,10 @@ pub struct CdkMetrics {
     mint_operations_total: IntCounterVec,
     mint_in_flight_requests: IntGaugeVec,
     mint_operation_duration: HistogramVec,
+
+    // Payment backend metrics
+    payment_backend_balance: IntGaugeVec,
+    payment_backend_connected: IntGaugeVec,
 }
 
 impl CdkMetrics {
@@ -58,8 +63,12 @@ impl CdkMetrics {
             Self::create_lightning_metrics(&registry)?;
 
         // Create and register database metrics
-        let (db_operations_total, db_operation_duration, db_connections_active) =
-            Self::create_db_metrics(&registry)?;
+        let (
+            db_operations_total,
+            db_operation_duration,
+            db_slow_queries_total,
+            db_connections_active,
+        ) = Self::create_db_metrics(&registry)?;
 
         // Create and register error metrics
         let errors_total = Self::create_error_metrics(&registry)?;
@@ -68,6 +77,10 @@ impl CdkMetrics {
         let (mint_operations_total, mint_operation_duration, mint_in_flight_requests) =
             Self::create_mint_metrics(&registry)?;
 
+        // Create and register payment backend metrics
+        let (payment_backend_balance, payment_backend_connected) =
+            Self::create_payment_backend_metrics(&registry)?;
+
         Ok(Self {
             registry,
             http_requests_total,
@@ -79,11 +92,14 @@ impl CdkMetrics {
             lightning_payment_fees,
             db_operations_total,
             db_operation_duration,
+            db_slow_queries_total,
             db_connections_active,
             errors_total,
             mint_operations_total,
             mint_in_flight_requests,
             mint_operation_duration,
+            payment_backend_balance,
+            payment_backend_connected,
         })
     }
 
@@ -185,7 +201,7 @@ impl CdkMetrics {
     /// Returns an error if any of the metrics cannot be created or registered
     fn create_db_metrics(
         registry: &Registry,
-    ) -> crate::Result<(IntCounter, HistogramVec, IntGauge)> {
+    ) -> crate::Result<(IntCounter, HistogramVec, IntCounterVec, IntGauge)> {
         let db_operations_total =
             IntCounter::new("cdk_db_operations_total", "Total database operations")?;
         registry.register(Box::new(db_operations_total.clone()))?;
@@ -198,6 +214,14 @@ impl CdkMetrics {
             &["operation"],
         )?;
         registry.register(Box::new(db_operation_duration.clone()))?;
+        let db_slow_queries_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_db_slow_queries_total",
+                "Database operations slower than the slow query threshold",
+            ),
+            &["operation"],
+        )?;
+        registry.register(Box::new(db_slow_queries_total.clone()))?;
 
         let db_connections_active = IntGauge::new(
             "cdk_db_connections_active",
@@ -208,6 +232,7 @@ impl CdkMetrics {
         Ok((
             db_operations_total,
             db_operation_duration,
+            db_slow_queries_total,
             db_connections_active,
         ))
     }
@@ -267,6 +292,34 @@ impl CdkMetrics {
         ))
     }
 
+    /// Create and register payment backend metrics
+    ///
+    /// # Errors
+    /// Returns an error if any of the metrics cannot be created or registered
+    fn create_payment_backend_metrics(
+        registry: &Registry,
+    ) -> crate::Result<(IntGaugeVec, IntGaugeVec)> {
+        let payment_backend_balance = IntGaugeVec::new(
+            prometheus::Opts::new(
+                "cdk_payment_backend_balance",
+                "Spendable balance reported by the payment backend",
+            ),
+            &["backend", "unit"],
+        )?;
+        registry.register(Box::new(payment_backend_balance.clone()))?;
+
+        let payment_backend_connected = IntGaugeVec::new(
+            prometheus::Opts::new(
+                "cdk_payment_backend_connected",
+                "Whether the payment backend is connected (1) or not (0)",
+            ),
+            &["backend"],
+        )?;
+        registry.register(Box::new(payment_backend_connected.clone()))?;
+
+        Ok((payment_backend_balance, payment_backend_connected))
+    }
+
     /// Get the metrics registry
     #[must_use]
     pub fn registry(&self) -> Arc<Registry> {
@@ -310,6 +363,10 @@ impl CdkMetrics {
             .observe(duration_seconds);
     }
 
+    pub fn record_db_slow_query(&self, op: &str) {
+        self.db_slow_queries_total.with_label_values(&[op]).inc();
+    }
+
     pub fn set_db_connections_active(&self, count: i64) {
         self.db_connections_active.set(count);
     }
@@ -348,6 +405,19 @@ impl CdkMetrics {
             .with_label_values(&[operation])
             .dec();
     }
+
+    // Payment backend metrics methods
+    pub fn set_payment_backend_balance(&self, backend: &str, unit: &str, balance: i64) {
+        self.payment_backend_balance
+            .with_label_values(&[backend, unit])
+            .set(balance);
+    }
+
+    pub fn set_payment_backend_connected(&self, backend: &str, connected: bool) {
+        self.payment_backend_connected
+            .with_label_values(&[backend])
+            .set(i64::from(connected));
+    }
 }
 
 impl Default for CdkMetrics {
@@ -390,6 +460,11 @@ pub mod global {
         METRICS.record_db_operation(duration_seconds, op);
     }
 
+    /// Record slow database operation using the global metrics instance
+    pub fn record_db_slow_query(op: &str) {
+        METRICS.record_db_slow_query(op);
+    }
+
     /// Set database connections active using the global metrics instance
     pub fn set_db_connections_active(count: i64) {
         METRICS.set_db_connections_active(count);
@@ -420,6 +495,16 @@ pub mod global {
         METRICS.dec_in_flight_requests(operation);
     }
 
+    /// Set payment backend balance using the global metrics instance
+    pub fn set_payment_backend_balance(backend: &str, unit: &str, balance: i64) {
+        METRICS.set_payment_backend_balance(backend, unit, balance);
+    }
+
+    /// Set payment backend connection status using the global metrics instance
+    pub fn set_payment_backend_connected(backend: &str, connected: bool) {
+        METRICS.set_payment_backend_connected(backend, connected);
+    }
+
     /// Get the metrics registry from the global instance
     pub fn registry() -> std::sync::Arc<prometheus::Registry> {
         METRICS.registry()
@@ -4,14 +4,20 @@ use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use cdk_common::common::ProofInfo;
 use cdk_common::mint_url::MintUrl;
-use cdk_common::Id;
+use cdk_common::util::unix_time;
+use cdk_common::wallet::ArchivedProof;
+use cdk_common::{Id, State};
 use redb::{
     Database, MultimapTableDefinition, ReadableMultimapTable, ReadableTable, TableDefinition,
 };
 
 use super::Error;
-use crate::wallet::{KEYSETS_TABLE, KEYSET_COUNTER, KEYSET_U32_MAPPING, MINT_KEYS_TABLE};
+use crate::wallet::{
+    FEE_LEDGER_TABLE, KEYSETS_TABLE, KEYSET_COUNTER, KEYSET_U32_MAPPING, KV_STORE_TABLE,
+    MINT_KEYS_TABLE, PROOFS_TABLE, SPENT_PROOFS_TABLE,
+};
 
 // <Mint_url, Info>
 const MINTS_TABLE: TableDefinition<&str, &str> = TableDefinition::new("mints_table");
@@ -200,3 +206,74 @@ pub(crate) fn migrate_03_to_04(db: Arc<Database>) -> Result<u32, Error> {
 
     Ok(4)
 }
+
+pub(crate) fn migrate_04_to_05(db: Arc<Database>) -> Result<u32, Error> {
+    let write_txn = db.begin_write().map_err(Error::from)?;
+
+    // Create the kv store table
+    {
+        let _ = write_txn.open_table(KV_STORE_TABLE).map_err(Error::from)?;
+    }
+
+    write_txn.commit()?;
+
+    Ok(5)
+}
+
+pub(crate) fn migrate_05_to_06(db: Arc<Database>) -> Result<u32, Error> {
+    let write_txn = db.begin_write().map_err(Error::from)?;
+
+    // Move proofs already known to be spent into the archive
+    {
+        let mut table = write_txn.open_table(PROOFS_TABLE).map_err(Error::from)?;
+        let mut spent_table = write_txn
+            .open_table(SPENT_PROOFS_TABLE)
+            .map_err(Error::from)?;
+
+        let spent_proofs: Vec<ProofInfo> = table
+            .iter()
+            .map_err(Error::from)?
+            .flatten()
+            .filter_map(|(_k, v)| serde_json::from_str::<ProofInfo>(v.value()).ok())
+            .filter(|proof_info| proof_info.state == State::Spent)
+            .collect();
+
+        let spent_time = unix_time();
+
+        for proof_info in spent_proofs {
+            let archived_proof = ArchivedProof {
+                y: proof_info.y,
+                mint_url: proof_info.mint_url,
+                keyset_id: proof_info.proof.keyset_id,
+                amount: proof_info.proof.amount,
+                unit: proof_info.unit,
+                spent_time,
+            };
+
+            spent_table.insert(
+                proof_info.y.to_bytes().as_slice(),
+                serde_json::to_string(&archived_proof)?.as_str(),
+            )?;
+            table.remove(proof_info.y.to_bytes().as_slice())?;
+        }
+    }
+
+    write_txn.commit()?;
+
+    Ok(6)
+}
+
+pub(crate) fn migrate_06_to_07(db: Arc<Database>) -> Result<u32, Error> {
+    let write_txn = db.begin_write().map_err(Error::from)?;
+
+    // Create the fee ledger table
+    {
+        let _ = write_txn
+            .open_table(FEE_LEDGER_TABLE)
+            .map_err(Error::from)?;
+    }
+
+    write_txn.commit()?;
+
+    Ok(7)
+}
@@ -8,10 +8,12 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use cdk_common::common::ProofInfo;
-use cdk_common::database::WalletDatabase;
+use cdk_common::database::{validate_kvstore_params, validate_kvstore_string, WalletDatabase};
 use cdk_common::mint_url::MintUrl;
 use cdk_common::util::unix_time;
-use cdk_common::wallet::{self, MintQuote, Transaction, TransactionDirection, TransactionId};
+use cdk_common::wallet::{
+    self, ArchivedProof, FeeEntry, MintQuote, Transaction, TransactionDirection, TransactionId,
+};
 use cdk_common::{
     database, CurrencyUnit, Id, KeySet, KeySetInfo, Keys, MintInfo, PublicKey, SpendingConditions,
     State,
@@ -21,7 +23,10 @@ use tracing::instrument;
 
 use super::error::Error;
 use crate::migrations::migrate_00_to_01;
-use crate::wallet::migrations::{migrate_01_to_02, migrate_02_to_03, migrate_03_to_04};
+use crate::wallet::migrations::{
+    migrate_01_to_02, migrate_02_to_03, migrate_03_to_04, migrate_04_to_05, migrate_05_to_06,
+    migrate_06_to_07,
+};
 
 mod migrations;
 
@@ -45,8 +50,14 @@ const KEYSET_COUNTER: TableDefinition<&str, u32> = TableDefinition::new("keyset_
 const TRANSACTIONS_TABLE: TableDefinition<&[u8], &str> = TableDefinition::new("transactions");
 
 const KEYSET_U32_MAPPING: TableDefinition<u32, &str> = TableDefinition::new("keyset_u32_mapping");
+// <(Primary_namespace, Secondary_namespace, Key), Value>
+const KV_STORE_TABLE: TableDefinition<(&str, &str, &str), &[u8]> = TableDefinition::new("kv_store");
+// <Y, Archived Proof>
+const SPENT_PROOFS_TABLE: TableDefinition<&[u8], &str> = TableDefinition::new("spent_proofs");
+// <Sequence, Fee Entry>
+const FEE_LEDGER_TABLE: TableDefinition<u64, &str> = TableDefinition::new("fee_ledger");
 
-const DATABASE_VERSION: u32 = 4;
+const DATABASE_VERSION: u32 = 7;
 
 /// Wallet Redb Database
 #[derive(Debug, Clone)]
@@ -100,6 +111,18 @@ impl WalletRedbDatabase {
                                 current_file_version = migrate_03_to_04(Arc::clone(&db))?;
                             }
 
+                            if current_file_version == 4 {
+                                current_file_version = migrate_04_to_05(Arc::clone(&db))?;
+                            }
+
+                            if current_file_version == 5 {
+                                current_file_version = migrate_05_to_06(Arc::clone(&db))?;
+                            }
+
+                            if current_file_version == 6 {
+                                current_file_version = migrate_06_to_07(Arc::clone(&db))?;
+                            }
+
                             if current_file_version != DATABASE_VERSION {
                                 tracing::warn!(
                                     "Database upgrade did not complete at {} current is {}",
@@ -147,6 +170,9 @@ impl WalletRedbDatabase {
                         let _ = write_txn.open_table(KEYSET_COUNTER)?;
                         let _ = write_txn.open_table(TRANSACTIONS_TABLE)?;
                         let _ = write_txn.open_table(KEYSET_U32_MAPPING)?;
+                        let _ = write_txn.open_table(KV_STORE_TABLE)?;
+                        let _ = write_txn.open_table(SPENT_PROOFS_TABLE)?;
+                        let _ = write_txn.open_table(FEE_LEDGER_TABLE)?;
                         table.insert("db_version", DATABASE_VERSION.to_string().as_str())?;
                     }
 
@@ -507,6 +533,24 @@ impl WalletDatabase for WalletRedbDatabase {
         Ok(())
     }
 
+    #[instrument(skip_all)]
+    async fn remove_mint_quotes(&self, quote_ids: &[String]) -> Result<(), Self::Err> {
+        let write_txn = self.db.begin_write().map_err(Error::from)?;
+
+        {
+            let mut table = write_txn
+                .open_table(MINT_QUOTES_TABLE)
+                .map_err(Error::from)?;
+            for quote_id in quote_ids {
+                table.remove(quote_id.as_str()).map_err(Error::from)?;
+            }
+        }
+
+        write_txn.commit().map_err(Error::from)?;
+
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     async fn add_melt_quote(&self, quote: wallet::MeltQuote) -> Result<(), Self::Err> {
         let write_txn = self.db.begin_write().map_err(Error::from)?;
@@ -573,6 +617,24 @@ impl WalletDatabase for WalletRedbDatabase {
         Ok(())
     }
 
+    #[instrument(skip_all)]
+    async fn remove_melt_quotes(&self, quote_ids: &[String]) -> Result<(), Self::Err> {
+        let write_txn = self.db.begin_write().map_err(Error::from)?;
+
+        {
+            let mut table = write_txn
+                .open_table(MELT_QUOTES_TABLE)
+                .map_err(Error::from)?;
+            for quote_id in quote_ids {
+                table.remove(quote_id.as_str()).map_err(Error::from)?;
+            }
+        }
+
+        write_txn.commit().map_err(Error::from)?;
+
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     async fn add_keys(&self, keyset: KeySet) -> Result<(), Self::Err> {
         let write_txn = self.db.begin_write().map_err(Error::from)?;
@@ -667,6 +729,9 @@ impl WalletDatabase for WalletRedbDatabase {
 
         {
             let mut table = write_txn.open_table(PROOFS_TABLE).map_err(Error::from)?;
+            let mut spent_table = write_txn
+                .open_table(SPENT_PROOFS_TABLE)
+                .map_err(Error::from)?;
 
             for proof_info in added.iter() {
                 table
@@ -679,8 +744,38 @@ impl WalletDatabase for WalletRedbDatabase {
                     .map_err(Error::from)?;
             }
 
+            let spent_time = unix_time();
+
             for y in deleted_ys.iter() {
-                table.remove(y.to_bytes().as_slice()).map_err(Error::from)?;
+                let removed = table
+                    .remove(y.to_bytes().as_slice())
+                    .map_err(Error::from)?
+                    .map(|v| serde_json::from_str::<ProofInfo>(v.value()))
+                    .transpose()
+                    .map_err(Error::from)?;
+
+                // Keep a summary of the removed proof if it was spent
+                if let Some(proof_info) = removed.filter(|proof_info| {
+                    matches!(proof_info.state, State::Spent | State::PendingSpent)
+                }) {
+                    let archived_proof = ArchivedProof {
+                        y: proof_info.y,
+                        mint_url: proof_info.mint_url,
+                        keyset_id: proof_info.proof.keyset_id,
+                        amount: proof_info.proof.amount,
+                        unit: proof_info.unit,
+                        spent_time,
+                    };
+
+                    spent_table
+                        .insert(
+                            y.to_bytes().as_slice(),
+                            serde_json::to_string(&archived_proof)
+                                .map_err(Error::from)?
+                                .as_str(),
+                        )
+                        .map_err(Error::from)?;
+                }
             }
         }
         write_txn.commit().map_err(Error::from)?;
@@ -721,6 +816,64 @@ impl WalletDatabase for WalletRedbDatabase {
         Ok(proofs)
     }
 
+    #[instrument(skip(self))]
+    async fn get_archived_proofs(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+    ) -> Result<Vec<ArchivedProof>, Self::Err> {
+        let read_txn = self.db.begin_read().map_err(Error::from)?;
+
+        let table = read_txn
+            .open_table(SPENT_PROOFS_TABLE)
+            .map_err(Error::from)?;
+
+        let mut archived_proofs = Vec::new();
+        for entry in table.iter().map_err(Error::from)? {
+            let (_k, v) = entry.map_err(Error::from)?;
+            let archived_proof =
+                serde_json::from_str::<ArchivedProof>(v.value()).map_err(Error::from)?;
+            if archived_proof.matches_conditions(&mint_url, &unit) {
+                archived_proofs.push(archived_proof);
+            }
+        }
+
+        archived_proofs.sort_by(|a, b| b.spent_time.cmp(&a.spent_time));
+
+        Ok(archived_proofs)
+    }
+
+    #[instrument(skip(self))]
+    async fn prune_archived_proofs(&self, spent_before: u64) -> Result<u64, Self::Err> {
+        let write_txn = self.db.begin_write().map_err(Error::from)?;
+
+        let removed = {
+            let mut table = write_txn
+                .open_table(SPENT_PROOFS_TABLE)
+                .map_err(Error::from)?;
+
+            let mut expired: Vec<Vec<u8>> = Vec::new();
+            for entry in table.iter().map_err(Error::from)? {
+                let (k, v) = entry.map_err(Error::from)?;
+                let archived_proof =
+                    serde_json::from_str::<ArchivedProof>(v.value()).map_err(Error::from)?;
+                if archived_proof.spent_time < spent_before {
+                    expired.push(k.value().to_vec());
+                }
+            }
+
+            for y in expired.iter() {
+                table.remove(y.as_slice()).map_err(Error::from)?;
+            }
+
+            expired.len() as u64
+        };
+
+        write_txn.commit().map_err(Error::from)?;
+
+        Ok(removed)
+    }
+
     async fn update_proofs_state(
         &self,
         ys: Vec<PublicKey>,
@@ -761,6 +914,18 @@ impl WalletDatabase for WalletRedbDatabase {
         Ok(())
     }
 
+    #[instrument(skip(self), fields(keyset_id = %keyset_id))]
+    async fn get_keyset_counter(&self, keyset_id: &Id) -> Result<u32, Self::Err> {
+        let read_txn = self.db.begin_read().map_err(Error::from)?;
+        let table = read_txn.open_table(KEYSET_COUNTER).map_err(Error::from)?;
+
+        let counter = table
+            .get(keyset_id.to_string().as_str())
+            .map_err(Error::from)?;
+
+        Ok(counter.map(|c| c.value()).unwrap_or(0))
+    }
+
     #[instrument(skip(self), fields(keyset_id = %keyset_id))]
     async fn increment_keyset_counter(&self, keyset_id: &Id, count: u32) -> Result<u32, Self::Err> {
         let write_txn = self.db.begin_write().map_err(Error::from)?;
@@ -883,4 +1048,156 @@ impl WalletDatabase for WalletRedbDatabase {
 
         Ok(())
     }
+
+    #[instrument(skip(self))]
+    async fn add_fee_entry(&self, entry: FeeEntry) -> Result<(), Self::Err> {
+        let write_txn = self.db.begin_write().map_err(Error::from)?;
+
+        {
+            let mut table = write_txn
+                .open_table(FEE_LEDGER_TABLE)
+                .map_err(Error::from)?;
+
+            let sequence = table
+                .last()
+                .map_err(Error::from)?
+                .map(|(k, _v)| k.value() + 1)
+                .unwrap_or_default();
+
+            table
+                .insert(
+                    sequence,
+                    serde_json::to_string(&entry).map_err(Error::from)?.as_str(),
+                )
+                .map_err(Error::from)?;
+        }
+
+        write_txn.commit().map_err(Error::from)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_fee_entries(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<FeeEntry>, Self::Err> {
+        let read_txn = self.db.begin_read().map_err(Error::from)?;
+
+        let table = read_txn.open_table(FEE_LEDGER_TABLE).map_err(Error::from)?;
+
+        let entries: Vec<FeeEntry> = table
+            .iter()
+            .map_err(Error::from)?
+            .flatten()
+            .filter_map(|(_k, v)| serde_json::from_str::<FeeEntry>(v.value()).ok())
+            .filter(|entry| entry.matches_conditions(&mint_url, &unit, since, until))
+            .collect();
+
+        Ok(entries)
+    }
+
+    #[instrument(skip(self))]
+    async fn kv_read(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Self::Err> {
+        validate_kvstore_params(primary_namespace, secondary_namespace, key)?;
+
+        let read_txn = self.db.begin_read().map_err(Error::from)?;
+        let table = read_txn.open_table(KV_STORE_TABLE).map_err(Error::from)?;
+
+        let value = table
+            .get((primary_namespace, secondary_namespace, key))
+            .map_err(Error::from)?
+            .map(|v| v.value().to_vec());
+
+        Ok(value)
+    }
+
+    #[instrument(skip(self, value))]
+    async fn kv_write(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), Self::Err> {
+        validate_kvstore_params(primary_namespace, secondary_namespace, key)?;
+
+        let write_txn = self.db.begin_write().map_err(Error::from)?;
+
+        {
+            let mut table = write_txn.open_table(KV_STORE_TABLE).map_err(Error::from)?;
+            table
+                .insert((primary_namespace, secondary_namespace, key), value)
+                .map_err(Error::from)?;
+        }
+
+        write_txn.commit().map_err(Error::from)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn kv_remove(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<(), Self::Err> {
+        validate_kvstore_params(primary_namespace, secondary_namespace, key)?;
+
+        let write_txn = self.db.begin_write().map_err(Error::from)?;
+
+        {
+            let mut table = write_txn.open_table(KV_STORE_TABLE).map_err(Error::from)?;
+            table
+                .remove((primary_namespace, secondary_namespace, key))
+                .map_err(Error::from)?;
+        }
+
+        write_txn.commit().map_err(Error::from)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn kv_list(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+    ) -> Result<Vec<String>, Self::Err> {
+        validate_kvstore_string(primary_namespace)?;
+        validate_kvstore_string(secondary_namespace)?;
+
+        if primary_namespace.is_empty() && !secondary_namespace.is_empty() {
+            return Err(database::Error::KVStoreInvalidKey(
+                "If primary_namespace is empty, secondary_namespace must also be empty".to_string(),
+            ));
+        }
+
+        let read_txn = self.db.begin_read().map_err(Error::from)?;
+        let table = read_txn.open_table(KV_STORE_TABLE).map_err(Error::from)?;
+
+        let mut keys: Vec<String> = table
+            .iter()
+            .map_err(Error::from)?
+            .flatten()
+            .filter_map(|(k, _v)| {
+                let (primary, secondary, key) = k.value();
+                (primary == primary_namespace && secondary == secondary_namespace)
+                    .then(|| key.to_string())
+            })
+            .collect();
+
+        keys.sort();
+
+        Ok(keys)
+    }
 }
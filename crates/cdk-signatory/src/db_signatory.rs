@@ -2,20 +2,115 @@
 //!
 //! It is named db_signatory because it uses a database to maintain state.
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use bitcoin::bip32::{DerivationPath, Xpriv};
 use bitcoin::secp256k1::{self, Secp256k1};
 use cdk_common::dhke::{sign_message, verify_message};
 use cdk_common::mint::MintKeySetInfo;
-use cdk_common::nuts::{BlindSignature, BlindedMessage, CurrencyUnit, Id, MintKeySet, Proof};
+use cdk_common::nuts::{
+    BlindSignature, BlindedMessage, CurrencyUnit, Id, MintKeySet, Proof, SecretKey,
+};
 use cdk_common::{database, Error, PublicKey};
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 use tracing::instrument;
 
 use crate::common::{create_new_keyset, derivation_path_from_unit, init_keysets};
 use crate::signatory::{RotateKeyArguments, Signatory, SignatoryKeySet, SignatoryKeysets};
 
+/// Fewest proofs verified by one thread, smaller batches are verified on the calling thread
+const MIN_PROOFS_PER_THREAD: usize = 16;
+
+/// Threads verifying signatures at once
+fn verify_threads() -> usize {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        1
+    }
+}
+
+/// Verify the signatures of a chunk of proofs, stopping once any chunk failed
+fn verify_chunk(proofs: &[(SecretKey, Proof)], failed: &AtomicBool) -> Result<(), Error> {
+    for (secret_key, proof) in proofs {
+        // Another chunk already failed and reports its error
+        if failed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        if let Err(err) = verify_message(secret_key, proof.c, proof.secret.as_bytes()) {
+            failed.store(true, Ordering::Relaxed);
+            return Err(err.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify the signatures of proofs in chunks on the blocking thread pool
+///
+/// Each chunk holds one of `permits` while it is verified, so requests share a
+/// bounded number of threads instead of tying up the async workers. Every
+/// chunk stops at the first invalid signature found by any of them.
+#[cfg(not(target_arch = "wasm32"))]
+async fn verify_signatures(
+    proofs: Vec<(SecretKey, Proof)>,
+    permits: &Arc<Semaphore>,
+) -> Result<(), Error> {
+    let failed = Arc::new(AtomicBool::new(false));
+    let threads = verify_threads();
+
+    if threads == 1 || proofs.len() <= MIN_PROOFS_PER_THREAD {
+        return verify_chunk(&proofs, &failed);
+    }
+
+    let chunk_size = proofs.len().div_ceil(threads).max(MIN_PROOFS_PER_THREAD);
+    let proofs = Arc::new(proofs);
+    let mut workers = tokio::task::JoinSet::new();
+
+    for start in (0..proofs.len()).step_by(chunk_size) {
+        if failed.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let permit = Arc::clone(permits)
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::Internal)?;
+        let proofs = Arc::clone(&proofs);
+        let failed = Arc::clone(&failed);
+
+        workers.spawn_blocking(move || {
+            let _permit = permit;
+            let end = (start + chunk_size).min(proofs.len());
+            verify_chunk(&proofs[start..end], &failed)
+        });
+    }
+
+    while let Some(worker) = workers.join_next().await {
+        worker.map_err(|_| Error::Internal)??;
+    }
+
+    Ok(())
+}
+
+/// Verify the signatures of proofs
+#[cfg(target_arch = "wasm32")]
+async fn verify_signatures(
+    proofs: Vec<(SecretKey, Proof)>,
+    _permits: &Arc<Semaphore>,
+) -> Result<(), Error> {
+    verify_chunk(&proofs, &AtomicBool::new(false))
+}
+
 /// In-memory Signatory
 ///
 /// This is the default signatory implementation for the mint.
@@ -30,6 +125,7 @@ pub struct DbSignatory {
     custom_paths: HashMap<CurrencyUnit, DerivationPath>,
     xpriv: Xpriv,
     xpub: PublicKey,
+    verify_permits: Arc<Semaphore>,
 }
 
 impl DbSignatory {
@@ -98,6 +194,7 @@ impl DbSignatory {
             xpub: xpriv.to_keypair(&secp_ctx).public_key().into(),
             secp_ctx,
             xpriv,
+            verify_permits: Arc::new(Semaphore::new(verify_threads())),
         };
         keys.reload_keys_from_db().await?;
 
@@ -193,12 +290,19 @@ impl Signatory for DbSignatory {
     async fn verify_proofs(&self, proofs: Vec<Proof>) -> Result<(), Error> {
         let keysets = self.keysets.read().await;
 
-        proofs.into_iter().try_for_each(|proof| {
-            let (_, key) = keysets.get(&proof.keyset_id).ok_or(Error::UnknownKeySet)?;
-            let key_pair = key.keys.get(&proof.amount).ok_or(Error::UnknownKeySet)?;
-            verify_message(&key_pair.secret_key, proof.c, proof.secret.as_bytes())?;
-            Ok(())
-        })
+        // Look up every key before checking any signature, so requests with unknown
+        // keysets or amounts are refused without elliptic curve work
+        let proofs = proofs
+            .into_iter()
+            .map(|proof| {
+                let (_, key) = keysets.get(&proof.keyset_id).ok_or(Error::UnknownKeySet)?;
+                let key_pair = key.keys.get(&proof.amount).ok_or(Error::UnknownKeySet)?;
+                Ok((key_pair.secret_key.clone(), proof))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        drop(keysets);
+
+        verify_signatures(proofs, &self.verify_permits).await
     }
 
     #[tracing::instrument(skip_all)]
@@ -247,8 +351,7 @@ impl Signatory for DbSignatory {
             args.unit.clone(),
             &args.amounts,
             args.input_fee_ppk,
-            // TODO: add and connect settings for this
-            None,
+            args.final_expiry,
         );
         let id = info.id;
         let mut tx = self.localstore.begin_transaction().await?;
@@ -265,13 +368,53 @@ impl Signatory for DbSignatory {
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
+    use std::str::FromStr;
 
     use bitcoin::key::Secp256k1;
     use bitcoin::Network;
+    use cdk_common::dhke::hash_to_curve;
+    use cdk_common::secret::Secret;
     use cdk_common::{Amount, MintKeySet, PublicKey};
 
     use super::*;
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_signatures_aborts_on_invalid_proof() {
+        let secret_key = SecretKey::generate();
+        let keyset_id = Id::from_str("00deadbeef123456").unwrap();
+
+        let mut proofs = (0..100)
+            .map(|_| {
+                let secret = Secret::generate();
+                let c =
+                    sign_message(&secret_key, &hash_to_curve(secret.as_bytes()).unwrap()).unwrap();
+                Proof::new(Amount::from(1), keyset_id, secret, c)
+            })
+            .collect::<Vec<_>>();
+
+        let permits = Arc::new(Semaphore::new(2));
+        let signed = |proofs: &[Proof]| {
+            proofs
+                .iter()
+                .map(|proof| (secret_key.clone(), proof.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert!(verify_signatures(signed(&proofs), &permits).await.is_ok());
+
+        proofs[73].c = SecretKey::generate().public_key();
+
+        assert!(verify_signatures(signed(&proofs), &permits).await.is_err());
+        assert!(verify_signatures(signed(&proofs[70..75]), &permits)
+            .await
+            .is_err());
+        assert!(verify_signatures(signed(&proofs[..70]), &permits)
+            .await
+            .is_ok());
+
+        // Every permit is given back once the proofs are verified
+        assert_eq!(permits.available_permits(), 2);
+    }
+
     #[test]
     fn mint_mod_generate_keyset_from_seed() {
         let seed = "test_seed".as_bytes();
@@ -61,6 +61,7 @@ impl TryInto<crate::signatory::SignatoryKeySet> for KeySet {
                     .collect::<Result<BTreeMap<Amount, _>, _>>()?,
             ),
             final_expiry: self.final_expiry,
+            valid_from: self.valid_from,
         })
     }
 }
@@ -81,6 +82,7 @@ impl From<crate::signatory::SignatoryKeySet> for KeySet {
             }),
             final_expiry: keyset.final_expiry,
             version: Default::default(),
+            valid_from: keyset.valid_from,
         }
     }
 }
@@ -333,6 +335,7 @@ impl From<crate::signatory::RotateKeyArguments> for RotationRequest {
             unit: Some(value.unit.into()),
             amounts: value.amounts,
             input_fee_ppk: value.input_fee_ppk,
+            final_expiry: value.final_expiry,
         }
     }
 }
@@ -348,6 +351,7 @@ impl TryInto<crate::signatory::RotateKeyArguments> for RotationRequest {
                 .try_into()?,
             amounts: self.amounts,
             input_fee_ppk: self.input_fee_ppk,
+            final_expiry: self.final_expiry,
         })
     }
 }
@@ -362,6 +366,7 @@ impl From<cdk_common::KeySetInfo> for KeySet {
             keys: Default::default(),
             final_expiry: value.final_expiry,
             version: Default::default(),
+            valid_from: Default::default(),
         }
     }
 }
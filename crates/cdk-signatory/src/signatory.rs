@@ -46,6 +46,8 @@ pub struct RotateKeyArguments {
     pub amounts: Vec<u64>,
     /// Input fee
     pub input_fee_ppk: u64,
+    /// Unix timestamp after which the keyset expires
+    pub final_expiry: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +77,8 @@ pub struct SignatoryKeySet {
     pub input_fee_ppk: u64,
     /// Final expiry of the keyset (unix timestamp in the future)
     pub final_expiry: Option<u64>,
+    /// Unix timestamp the keyset is valid from
+    pub valid_from: u64,
 }
 
 impl From<&SignatoryKeySet> for KeySet {
@@ -112,7 +116,7 @@ impl From<SignatoryKeySet> for MintKeySetInfo {
             max_order: 0,
             amounts: vec![],
             final_expiry: val.final_expiry,
-            valid_from: 0,
+            valid_from: val.valid_from,
         }
     }
 }
@@ -126,6 +130,7 @@ impl From<&(MintKeySetInfo, MintKeySet)> for SignatoryKeySet {
             input_fee_ppk: info.input_fee_ppk,
             keys: key.keys.clone().into(),
             final_expiry: key.final_expiry,
+            valid_from: info.valid_from,
         }
     }
 }
@@ -1,19 +1,48 @@
 use std::fmt::Debug;
 use std::future::Future;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use cdk_common::database::Error;
+#[cfg(feature = "prometheus")]
+use cdk_prometheus::metrics::METRICS;
 
 use crate::database::DatabaseExecutor;
 use crate::stmt::query;
 
-const SLOW_QUERY_THRESHOLD_MS: u128 = 20;
+/// Default duration after which a query is logged as slow
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(20);
 
-/// Run a database operation and log slow operations, it also converts and logs any error with a
-/// given info for more context. This function is expecting a synchronous database operation
+/// Record a finished query under its statement name and log it if it was slow
+#[inline(always)]
+fn record_db_operation(name: &str, sql: &str, slow_query_threshold: Duration, duration: Duration) {
+    let is_slow = duration > slow_query_threshold;
+
+    if is_slow {
+        tracing::warn!(
+            "[SLOW QUERY] {} took {} ms: {}",
+            name,
+            duration.as_millis(),
+            sql
+        );
+    }
+
+    #[cfg(feature = "prometheus")]
+    {
+        METRICS.record_db_operation(duration.as_secs_f64(), name);
+        if is_slow {
+            METRICS.record_db_slow_query(name);
+        }
+    }
+}
+
+/// Run a database operation, record it under the statement `name` and log it when slower than
+/// `slow_query_threshold`, it also converts and logs any error with the given sql for more
+/// context. This function is expecting a synchronous database operation
 #[inline(always)]
 pub fn run_db_operation_sync<F, E, E1, T>(
-    info: &str,
+    name: &str,
+    sql: &str,
+    slow_query_threshold: Duration,
     operation: F,
     error_map: E,
 ) -> Result<T, Error>
@@ -24,26 +53,26 @@ where
 {
     let start = Instant::now();
 
-    tracing::trace!("Running db operation {}", info);
+    tracing::trace!("Running db operation {}", sql);
 
     let result = operation().map_err(|e| {
-        tracing::error!("Query {} failed with error {:?}", info, e);
+        tracing::error!("Query {} failed with error {:?}", sql, e);
         error_map(e)
     });
 
-    let duration = start.elapsed();
-    if duration.as_millis() > SLOW_QUERY_THRESHOLD_MS {
-        tracing::warn!("[SLOW QUERY] Took {} ms: {}", duration.as_millis(), info);
-    }
+    record_db_operation(name, sql, slow_query_threshold, start.elapsed());
 
     result
 }
 
-/// Run a database operation and log slow operations, it also converts and logs any error with a
-/// given info for more context
+/// Run a database operation, record it under the statement `name` and log it when slower than
+/// `slow_query_threshold`, it also converts and logs any error with the given sql for more
+/// context
 #[inline(always)]
 pub async fn run_db_operation<Fut, E, E1, T>(
-    info: &str,
+    name: &str,
+    sql: &str,
+    slow_query_threshold: Duration,
     operation: Fut,
     error_map: E,
 ) -> Result<T, Error>
@@ -54,17 +83,14 @@ where
 {
     let start = Instant::now();
 
-    tracing::trace!("Running db operation {}", info);
+    tracing::trace!("Running db operation {}", sql);
 
     let result = operation.await.map_err(|e| {
-        tracing::error!("Query {} failed with error {:?}", info, e);
+        tracing::error!("Query {} failed with error {:?}", sql, e);
         error_map(e)
     });
 
-    let duration = start.elapsed();
-    if duration.as_millis() > SLOW_QUERY_THRESHOLD_MS {
-        tracing::warn!("[SLOW QUERY] Took {} ms: {}", duration.as_millis(), info);
-    }
+    record_db_operation(name, sql, slow_query_threshold, start.elapsed());
 
     result
 }
@@ -11,7 +11,7 @@ pub mod stmt;
 pub mod value;
 
 pub use cdk_common::database::ConversionError;
-pub use common::{run_db_operation, run_db_operation_sync};
+pub use common::{run_db_operation, run_db_operation_sync, DEFAULT_SLOW_QUERY_THRESHOLD};
 
 #[cfg(feature = "mint")]
 pub mod mint;
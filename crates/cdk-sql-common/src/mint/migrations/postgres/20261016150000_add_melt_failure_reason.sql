@@ -0,0 +1,2 @@
+-- Reason the last payment attempt of a melt quote failed
+ALTER TABLE melt_quote ADD COLUMN failure_reason TEXT;
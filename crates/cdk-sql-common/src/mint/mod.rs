@@ -31,8 +31,8 @@ use cdk_common::secret::Secret;
 use cdk_common::state::check_state_transition;
 use cdk_common::util::unix_time;
 use cdk_common::{
-    Amount, BlindSignature, BlindSignatureDleq, BlindedMessage, CurrencyUnit, Id, MeltQuoteState,
-    PaymentMethod, Proof, Proofs, PublicKey, SecretKey, State,
+    Amount, BlindSignature, BlindSignatureDleq, BlindedMessage, CurrencyUnit, Id,
+    MeltFailureReason, MeltQuoteState, PaymentMethod, Proof, Proofs, PublicKey, SecretKey, State,
 };
 use lightning_invoice::Bolt11Invoice;
 use migrations::MIGRATIONS;
@@ -67,6 +67,8 @@ where
     RM: DatabasePool + 'static,
 {
     pool: Arc<Pool<RM>>,
+    /// Read replica for hot read paths, writes always go to `pool`
+    read_pool: Option<Arc<Pool<RM>>>,
 }
 
 /// SQL Transaction Writer
@@ -115,7 +117,32 @@ where
 
         Self::migrate(pool.get().map_err(|e| Error::Database(Box::new(e)))?).await?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            read_pool: None,
+        })
+    }
+
+    /// Creates a new instance answering read-only API requests from a read replica
+    ///
+    /// Only the `*_from_replica` reads are served by the replica and may lag
+    /// behind the primary. Every other read, including the ones that decide on
+    /// writes, and every write go to the primary. Migrations run on the primary
+    /// only.
+    pub async fn new_with_read_replica<X, Y>(db: X, read_replica: Y) -> Result<Self, Error>
+    where
+        X: Into<RM::Config>,
+        Y: Into<RM::Config>,
+    {
+        let mut db = Self::new(db).await?;
+        db.read_pool = Some(Pool::new(read_replica.into()));
+
+        Ok(db)
+    }
+
+    /// Pool for reads that tolerate replication lag
+    fn read_pool(&self) -> &Arc<Pool<RM>> {
+        self.read_pool.as_ref().unwrap_or(&self.pool)
     }
 
     /// Migrate
@@ -372,6 +399,79 @@ WHERE quote_id=:quote_id
     .collect()
 }
 
+#[inline(always)]
+async fn get_mint_quote_by_id<C>(conn: &C, quote_id: &QuoteId) -> Result<Option<MintQuote>, Error>
+where
+    C: DatabaseExecutor + Send + Sync,
+{
+    let payments = get_mint_quote_payments(conn, quote_id).await?;
+    let issuance = get_mint_quote_issuance(conn, quote_id).await?;
+
+    query(
+        r#"
+        SELECT
+            id,
+            amount,
+            unit,
+            request,
+            expiry,
+            request_lookup_id,
+            pubkey,
+            created_time,
+            amount_paid,
+            amount_issued,
+            payment_method,
+            request_lookup_id_kind
+        FROM
+            mint_quote
+        WHERE id = :id"#,
+    )?
+    .bind("id", quote_id.to_string())
+    .fetch_one(conn)
+    .await?
+    .map(|row| sql_row_to_mint_quote(row, payments, issuance))
+    .transpose()
+}
+
+#[inline(always)]
+async fn get_melt_quote_by_id<C>(
+    conn: &C,
+    quote_id: &QuoteId,
+) -> Result<Option<mint::MeltQuote>, Error>
+where
+    C: DatabaseExecutor + Send + Sync,
+{
+    query(
+        r#"
+        SELECT
+            id,
+            unit,
+            amount,
+            request,
+            fee_reserve,
+            expiry,
+            state,
+            payment_preimage,
+            request_lookup_id,
+            created_time,
+            paid_time,
+            payment_method,
+            options,
+            request_lookup_id_kind,
+            failure_reason
+        FROM
+            melt_quote
+        WHERE
+            id=:id
+        "#,
+    )?
+    .bind("id", quote_id.to_string())
+    .fetch_one(conn)
+    .await?
+    .map(sql_row_to_melt_quote)
+    .transpose()
+}
+
 #[async_trait]
 impl<RM> MintKeyDatabaseTransaction<'_, Error> for SQLTransaction<RM>
 where
@@ -969,7 +1069,8 @@ VALUES (:quote_id, :amount, :timestamp);
                 paid_time,
                 payment_method,
                 options,
-                request_lookup_id_kind
+                request_lookup_id_kind,
+                failure_reason
             FROM
                 melt_quote
             WHERE
@@ -987,6 +1088,8 @@ VALUES (:quote_id, :amount, :timestamp);
 
         let rec = if state == MeltQuoteState::Paid {
             let current_time = unix_time();
+            quote.paid_time = Some(current_time);
+            quote.payment_preimage = payment_proof.clone();
             query(r#"UPDATE melt_quote SET state = :state, paid_time = :paid_time, payment_preimage = :payment_preimage WHERE id = :id"#)?
                 .bind("state", state.to_string())
                 .bind("paid_time", current_time as i64)
@@ -1020,6 +1123,23 @@ VALUES (:quote_id, :amount, :timestamp);
         Ok((old_state, quote))
     }
 
+    async fn update_melt_quote_failure_reason(
+        &mut self,
+        quote_id: &QuoteId,
+        failure_reason: Option<MeltFailureReason>,
+    ) -> Result<(), Self::Err> {
+        query(r#"UPDATE melt_quote SET failure_reason = :failure_reason WHERE id = :id"#)?
+            .bind(
+                "failure_reason",
+                failure_reason.map(|reason| reason.to_string()),
+            )
+            .bind("id", quote_id.to_string())
+            .execute(&self.inner)
+            .await?;
+
+        Ok(())
+    }
+
     async fn remove_melt_quote(&mut self, quote_id: &QuoteId) -> Result<(), Self::Err> {
         query(
             r#"
@@ -1086,7 +1206,8 @@ VALUES (:quote_id, :amount, :timestamp);
                 paid_time,
                 payment_method,
                 options,
-                request_lookup_id
+                request_lookup_id,
+                failure_reason
             FROM
                 melt_quote
             WHERE
@@ -1200,36 +1321,7 @@ where
         let start_time = std::time::Instant::now();
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
 
-        let result = async {
-            let payments = get_mint_quote_payments(&*conn, quote_id).await?;
-            let issuance = get_mint_quote_issuance(&*conn, quote_id).await?;
-
-            query(
-                r#"
-                SELECT
-                    id,
-                    amount,
-                    unit,
-                    request,
-                    expiry,
-                    request_lookup_id,
-                    pubkey,
-                    created_time,
-                    amount_paid,
-                    amount_issued,
-                    payment_method,
-                    request_lookup_id_kind
-                FROM
-                    mint_quote
-                WHERE id = :id"#,
-            )?
-            .bind("id", quote_id.to_string())
-            .fetch_one(&*conn)
-            .await?
-            .map(|row| sql_row_to_mint_quote(row, payments, issuance))
-            .transpose()
-        }
-        .await;
+        let result = get_mint_quote_by_id(&*conn, quote_id).await;
 
         #[cfg(feature = "prometheus")]
         {
@@ -1379,37 +1471,7 @@ where
         let start_time = std::time::Instant::now();
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
 
-        let result = async {
-            query(
-                r#"
-                SELECT
-                    id,
-                    unit,
-                    amount,
-                    request,
-                    fee_reserve,
-                    expiry,
-                    state,
-                    payment_preimage,
-                    request_lookup_id,
-                    created_time,
-                    paid_time,
-                    payment_method,
-                    options,
-                    request_lookup_id_kind
-                FROM
-                    melt_quote
-                WHERE
-                    id=:id
-                "#,
-            )?
-            .bind("id", quote_id.to_string())
-            .fetch_one(&*conn)
-            .await?
-            .map(sql_row_to_melt_quote)
-            .transpose()
-        }
-        .await;
+        let result = get_melt_quote_by_id(&*conn, quote_id).await;
 
         #[cfg(feature = "prometheus")]
         {
@@ -1427,6 +1489,23 @@ where
         result
     }
 
+    async fn get_melt_quote_from_replica(
+        &self,
+        quote_id: &QuoteId,
+    ) -> Result<Option<mint::MeltQuote>, Self::Err> {
+        let conn = self
+            .read_pool()
+            .get()
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        match get_melt_quote_by_id(&*conn, quote_id).await? {
+            Some(quote) => Ok(Some(quote)),
+            // A quote created moments ago may not have reached the read replica yet
+            None if self.read_pool.is_some() => self.get_melt_quote(quote_id).await,
+            None => Ok(None),
+        }
+    }
+
     async fn get_melt_quotes(&self) -> Result<Vec<mint::MeltQuote>, Self::Err> {
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
         Ok(query(
@@ -1445,7 +1524,8 @@ where
                 paid_time,
                 payment_method,
                 options,
-                request_lookup_id_kind
+                request_lookup_id_kind,
+                failure_reason
             FROM
                 melt_quote
             "#,
@@ -1536,6 +1616,19 @@ where
         Ok(ys.iter().map(|y| current_states.remove(y)).collect())
     }
 
+    async fn get_proofs_states_from_replica(
+        &self,
+        ys: &[PublicKey],
+    ) -> Result<Vec<Option<State>>, Self::Err> {
+        let conn = self
+            .read_pool()
+            .get()
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        let mut current_states = get_current_states(&*conn, ys).await?;
+
+        Ok(ys.iter().map(|y| current_states.remove(y)).collect())
+    }
+
     async fn get_proofs_by_keyset_id(
         &self,
         keyset_id: &Id,
@@ -2105,7 +2198,8 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<mint::MeltQuote, Error> {
                 paid_time,
                 payment_method,
                 options,
-                request_lookup_id_kind
+                request_lookup_id_kind,
+                failure_reason
         ) = row
     );
 
@@ -2120,6 +2214,10 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<mint::MeltQuote, Error> {
     let created_time: i64 = column_as_number!(created_time);
     let paid_time = column_as_nullable_number!(paid_time);
     let payment_method = PaymentMethod::from_str(&column_as_string!(payment_method))?;
+    let failure_reason = column_as_nullable_string!(failure_reason)
+        .map(|reason| MeltFailureReason::from_str(&reason))
+        .transpose()
+        .map_err(ConversionError::from)?;
 
     let state =
         MeltQuoteState::from_str(&column_as_string!(&state)).map_err(ConversionError::from)?;
@@ -2172,6 +2270,7 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<mint::MeltQuote, Error> {
         created_time: created_time as u64,
         paid_time,
         payment_method,
+        failure_reason,
     })
 }
 
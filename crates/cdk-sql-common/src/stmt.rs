@@ -142,7 +142,43 @@ pub fn split_sql_parts(input: &str) -> Result<Vec<SqlPart>, SqlParseError> {
     Ok(parts)
 }
 
-type Cache = HashMap<String, (Vec<SqlPart>, Option<Arc<str>>)>;
+/// Short name of a SQL statement made of its verb and the table it works on
+///
+/// Queries differing only in their conditions or placeholders share a name, such
+/// as `SELECT proof`, so the name is fit to group statistics by.
+fn statement_name(sql: &str) -> Arc<str> {
+    let tokens = sql
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ',' || c == ';')
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>();
+
+    let verb = match tokens.first() {
+        Some(verb) => verb.to_uppercase(),
+        None => return "".into(),
+    };
+
+    let table_after = |keyword: &str| {
+        tokens
+            .iter()
+            .position(|token| token.eq_ignore_ascii_case(keyword))
+            .and_then(|i| tokens.get(i + 1))
+    };
+
+    let table = match verb.as_str() {
+        "SELECT" | "DELETE" => table_after("FROM"),
+        "INSERT" => table_after("INTO"),
+        "UPDATE" => tokens.get(1),
+        _ => None,
+    };
+
+    match table {
+        Some(table) => format!("{verb} {}", table.trim_matches('"').to_lowercase()).into(),
+        None => verb.into(),
+    }
+}
+
+/// Parsed parts, placeholder free SQL and name of a statement
+type Cache = HashMap<String, (Vec<SqlPart>, Option<Arc<str>>, Arc<str>)>;
 
 /// Sql message
 #[derive(Debug, Default)]
@@ -150,6 +186,7 @@ pub struct Statement {
     cache: Arc<RwLock<Cache>>,
     cached_sql: Option<Arc<str>>,
     sql: Option<String>,
+    name: Arc<str>,
     /// The SQL statement
     pub parts: Vec<SqlPart>,
     /// The expected response type
@@ -165,19 +202,21 @@ impl Statement {
             .ok()
             .flatten();
 
-        if let Some((parts, cached_sql)) = parsed {
+        if let Some((parts, cached_sql, name)) = parsed {
             Ok(Self {
                 parts,
                 cached_sql,
                 sql: None,
+                name,
                 cache,
                 ..Default::default()
             })
         } else {
             let parts = split_sql_parts(sql)?;
+            let name = statement_name(sql);
 
             if let Ok(mut cache) = cache.write() {
-                cache.insert(sql.to_owned(), (parts.clone(), None));
+                cache.insert(sql.to_owned(), (parts.clone(), None, name.clone()));
             } else {
                 tracing::warn!("Failed to acquire write lock for SQL statement cache");
             }
@@ -185,12 +224,20 @@ impl Statement {
             Ok(Self {
                 parts,
                 sql: Some(sql.to_owned()),
+                name,
                 cache,
                 ..Default::default()
             })
         }
     }
 
+    /// Short name of the statement, such as `SELECT proof`
+    ///
+    /// The name is derived once per SQL statement and cached with its parts.
+    pub fn name(&self) -> Arc<str> {
+        self.name.clone()
+    }
+
     /// Convert Statement into a SQL statement and the list of placeholders
     ///
     /// By default it converts the statement into placeholder using $1..$n placeholders which seems
@@ -250,7 +297,7 @@ impl Statement {
         if can_be_cached {
             if let Some(original_sql) = self.sql {
                 let _ = self.cache.write().map(|mut cache| {
-                    if let Some((_, cached_sql)) = cache.get_mut(&original_sql) {
+                    if let Some((_, cached_sql, _)) = cache.get_mut(&original_sql) {
                         *cached_sql = Some(sql.clone().into());
                     }
                 });
@@ -357,3 +404,45 @@ pub fn query(sql: &str) -> Result<Statement, Error> {
     static CACHE: Lazy<Arc<RwLock<Cache>>> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
     Statement::new(sql, CACHE.clone()).map_err(|e| Error::Database(Box::new(e)))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_statement_name() {
+        assert_eq!(
+            &*statement_name("SELECT y, state FROM proof WHERE y IN (:ys)"),
+            "SELECT proof"
+        );
+        assert_eq!(
+            &*statement_name("\n  INSERT INTO mint_quote (id, amount) VALUES (:id, :amount)"),
+            "INSERT mint_quote"
+        );
+        assert_eq!(
+            &*statement_name("UPDATE melt_quote SET state = :state WHERE id = :id"),
+            "UPDATE melt_quote"
+        );
+        assert_eq!(
+            &*statement_name("delete from \"proof\" where y = :y"),
+            "DELETE proof"
+        );
+        assert_eq!(&*statement_name("CREATE TABLE foo (id TEXT)"), "CREATE");
+        assert_eq!(&*statement_name("   "), "");
+    }
+
+    #[test]
+    fn test_statement_name_is_cached() {
+        let sql = "SELECT id FROM test_statement_name_is_cached WHERE id = :id";
+
+        assert_eq!(
+            &*query(sql).expect("valid sql").name(),
+            "SELECT test_statement_name_is_cached"
+        );
+        // The second statement is built from the cached parts and name
+        assert_eq!(
+            &*query(sql).expect("valid sql").name(),
+            "SELECT test_statement_name_is_cached"
+        );
+    }
+}
@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN status TEXT NOT NULL DEFAULT 'Confirmed';
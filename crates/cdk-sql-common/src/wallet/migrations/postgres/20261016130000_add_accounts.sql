@@ -0,0 +1,28 @@
+-- Accounts derived from one seed share the database, their rows are scoped by account index
+ALTER TABLE proof ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE spent_proof ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE transactions ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE mint_quote ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE melt_quote ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+
+-- Derivation counters per account, starting from the counters of the default account
+CREATE TABLE IF NOT EXISTS keyset_counter (
+    account INTEGER NOT NULL,
+    keyset_id TEXT NOT NULL,
+    counter INTEGER NOT NULL,
+    PRIMARY KEY (account, keyset_id)
+);
+
+INSERT INTO keyset_counter (account, keyset_id, counter)
+SELECT 0, id, counter
+FROM keyset
+WHERE counter > 0;
+
+-- The account is part of the key of the kv store
+ALTER TABLE kv_store ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE kv_store DROP CONSTRAINT kv_store_pkey;
+ALTER TABLE kv_store ADD PRIMARY KEY (account, primary_namespace, secondary_namespace, key);
+
+DROP INDEX IF EXISTS idx_kv_store_namespaces;
+CREATE INDEX IF NOT EXISTS idx_kv_store_namespaces
+ON kv_store (account, primary_namespace, secondary_namespace);
@@ -0,0 +1,15 @@
+-- Ledger of the fees paid to mints
+CREATE TABLE IF NOT EXISTS fee_ledger (
+    id SERIAL PRIMARY KEY,
+    account INTEGER NOT NULL DEFAULT 0,
+    mint_url TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    quote_id TEXT
+);
+
+-- Index for efficient reports by time
+CREATE INDEX IF NOT EXISTS idx_fee_ledger_timestamp
+ON fee_ledger (account, timestamp);
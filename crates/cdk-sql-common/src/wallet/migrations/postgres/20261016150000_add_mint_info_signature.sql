@@ -0,0 +1 @@
+ALTER TABLE mint ADD COLUMN signature TEXT;
@@ -0,0 +1 @@
+ALTER TABLE melt_quote ADD COLUMN mint_url TEXT;
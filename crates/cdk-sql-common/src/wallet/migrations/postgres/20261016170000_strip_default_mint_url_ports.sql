@@ -0,0 +1,88 @@
+-- Mint urls are stored without the default port of their scheme, as
+-- `https://mint:443` and `https://mint` are the same mint
+CREATE TABLE mint_url_default_port (
+    old_url TEXT PRIMARY KEY,
+    new_url TEXT NOT NULL
+);
+
+INSERT INTO mint_url_default_port (old_url, new_url)
+SELECT url,
+    CASE
+        WHEN scheme = 'https://' THEN scheme || substr(host, 1, length(host) - 4) || path
+        ELSE scheme || substr(host, 1, length(host) - 3) || path
+    END
+FROM (
+    SELECT url,
+        scheme,
+        substr(rest, 1, strpos(rest || '/', '/') - 1) AS host,
+        substr(rest, strpos(rest || '/', '/')) AS path
+    FROM (
+        SELECT url,
+            substr(url, 1, strpos(url, '://') + 2) AS scheme,
+            substr(url, strpos(url, '://') + 3) AS rest
+        FROM (
+            SELECT mint_url AS url FROM mint
+            UNION SELECT mint_url AS url FROM keyset
+            UNION SELECT mint_url AS url FROM mint_quote
+            UNION SELECT mint_url AS url FROM melt_quote
+            UNION SELECT mint_url AS url FROM proof
+            UNION SELECT mint_url AS url FROM spent_proof
+            UNION SELECT mint_url AS url FROM transactions
+            UNION SELECT mint_url AS url FROM fee_ledger
+        ) urls
+        WHERE url IS NOT NULL AND strpos(url, '://') > 0
+    ) parts
+) hosts
+WHERE (scheme = 'https://' AND host LIKE '%:443')
+    OR (scheme = 'http://' AND host LIKE '%:80');
+
+-- Mints stored both with and without the port keep the row without it
+UPDATE mint SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = mint.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port)
+AND NOT EXISTS (
+    SELECT 1 FROM mint AS existing
+    WHERE existing.mint_url = (
+        SELECT new_url FROM mint_url_default_port WHERE old_url = mint.mint_url
+    )
+);
+
+UPDATE keyset SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = keyset.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+UPDATE mint_quote SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = mint_quote.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+UPDATE melt_quote SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = melt_quote.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+UPDATE proof SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = proof.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+UPDATE spent_proof SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = spent_proof.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+UPDATE transactions SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = transactions.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+UPDATE fee_ledger SET mint_url = (
+    SELECT new_url FROM mint_url_default_port WHERE old_url = fee_ledger.mint_url
+)
+WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+DELETE FROM mint WHERE mint_url IN (SELECT old_url FROM mint_url_default_port);
+
+DROP TABLE mint_url_default_port;
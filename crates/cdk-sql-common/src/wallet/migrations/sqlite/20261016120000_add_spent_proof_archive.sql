@@ -0,0 +1,21 @@
+-- Archive of spent proofs, kept out of the proof table for history
+CREATE TABLE IF NOT EXISTS spent_proof (
+    y BLOB PRIMARY KEY,
+    mint_url TEXT NOT NULL,
+    keyset_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    unit TEXT NOT NULL,
+    spent_time INTEGER NOT NULL
+);
+
+-- Index for efficient pruning by spent time
+CREATE INDEX IF NOT EXISTS idx_spent_proof_spent_time
+ON spent_proof (spent_time);
+
+-- Move proofs already known to be spent into the archive
+INSERT INTO spent_proof (y, mint_url, keyset_id, amount, unit, spent_time)
+SELECT y, mint_url, keyset_id, amount, unit, CAST(strftime('%s', 'now') AS INTEGER)
+FROM proof
+WHERE state = 'SPENT';
+
+DELETE FROM proof WHERE state = 'SPENT';
@@ -0,0 +1,45 @@
+-- Accounts derived from one seed share the database, their rows are scoped by account index
+ALTER TABLE proof ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE spent_proof ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE transactions ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE mint_quote ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE melt_quote ADD COLUMN account INTEGER NOT NULL DEFAULT 0;
+
+-- Derivation counters per account, starting from the counters of the default account
+CREATE TABLE IF NOT EXISTS keyset_counter (
+    account INTEGER NOT NULL,
+    keyset_id TEXT NOT NULL,
+    counter INTEGER NOT NULL,
+    PRIMARY KEY (account, keyset_id)
+);
+
+INSERT INTO keyset_counter (account, keyset_id, counter)
+SELECT 0, id, counter
+FROM keyset
+WHERE counter > 0;
+
+-- The account is part of the key of the kv store
+CREATE TABLE kv_store_new (
+    account INTEGER NOT NULL DEFAULT 0,
+    primary_namespace TEXT NOT NULL,
+    secondary_namespace TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value BLOB NOT NULL,
+    created_time INTEGER NOT NULL,
+    updated_time INTEGER NOT NULL,
+    PRIMARY KEY (account, primary_namespace, secondary_namespace, key)
+);
+
+INSERT INTO kv_store_new
+(account, primary_namespace, secondary_namespace, key, value, created_time, updated_time)
+SELECT 0, primary_namespace, secondary_namespace, key, value, created_time, updated_time
+FROM kv_store;
+
+DROP TABLE kv_store;
+ALTER TABLE kv_store_new RENAME TO kv_store;
+
+CREATE INDEX IF NOT EXISTS idx_kv_store_namespaces
+ON kv_store (account, primary_namespace, secondary_namespace);
+
+CREATE INDEX IF NOT EXISTS idx_kv_store_updated_time
+ON kv_store (updated_time);
@@ -7,11 +7,17 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use cdk_common::common::ProofInfo;
-use cdk_common::database::{ConversionError, Error, WalletDatabase};
+use cdk_common::database::{
+    validate_kvstore_params, validate_kvstore_string, ConversionError, Error, WalletDatabase,
+};
 use cdk_common::mint_url::MintUrl;
 use cdk_common::nuts::{MeltQuoteState, MintQuoteState};
 use cdk_common::secret::Secret;
-use cdk_common::wallet::{self, MintQuote, Transaction, TransactionDirection, TransactionId};
+use cdk_common::util::unix_time;
+use cdk_common::wallet::{
+    self, ArchivedProof, FeeEntry, FeeKind, MintQuote, Transaction, TransactionDirection,
+    TransactionId, TransactionStatus,
+};
 use cdk_common::{
     database, Amount, CurrencyUnit, Id, KeySet, KeySetInfo, Keys, MintInfo, PaymentMethod, Proof,
     ProofDleq, PublicKey, SecretKey, SpendingConditions, State,
@@ -39,6 +45,8 @@ where
     RM: DatabasePool + 'static,
 {
     pool: Arc<Pool<RM>>,
+    /// Account the proofs, quotes, transactions, counters and kv entries belong to
+    account: u32,
 }
 
 impl<RM> SQLWalletDatabase<RM>
@@ -53,7 +61,22 @@ where
         let pool = Pool::new(db.into());
         Self::migrate(pool.get().map_err(|e| Error::Database(Box::new(e)))?).await?;
 
-        Ok(Self { pool })
+        Ok(Self { pool, account: 0 })
+    }
+
+    /// Database of another account, sharing the connections of this one
+    ///
+    /// Mints, keysets and keys are shared by all accounts.
+    pub fn with_account(&self, account: u32) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            account,
+        }
+    }
+
+    /// Account of this database
+    pub fn account(&self) -> u32 {
+        self.account
     }
 
     /// Migrate [`WalletSqliteDatabase`]
@@ -159,11 +182,15 @@ where
                   state,
                   expiry,
                   payment_preimage,
-                  payment_method
+                  payment_method,
+                  mint_url
               FROM
                   melt_quote
+              WHERE
+                  account = :account
               "#,
         )?
+        .bind("account", self.account)
         .fetch_all(&*conn)
         .await?
         .into_iter()
@@ -190,6 +217,7 @@ where
             motd,
             time,
             tos_url,
+            signature,
         ) = match mint_info {
             Some(mint_info) => {
                 let MintInfo {
@@ -205,6 +233,7 @@ where
                     motd,
                     time,
                     tos_url,
+                    signature,
                 } = mint_info;
 
                 (
@@ -220,10 +249,11 @@ where
                     motd,
                     time,
                     tos_url,
+                    signature,
                 )
             }
             None => (
-                None, None, None, None, None, None, None, None, None, None, None, None,
+                None, None, None, None, None, None, None, None, None, None, None, None, None,
             ),
         };
 
@@ -234,12 +264,12 @@ where
 INSERT INTO mint
 (
     mint_url, name, pubkey, version, description, description_long,
-    contact, nuts, icon_url, urls, motd, mint_time, tos_url
+    contact, nuts, icon_url, urls, motd, mint_time, tos_url, signature
 )
 VALUES
 (
     :mint_url, :name, :pubkey, :version, :description, :description_long,
-    :contact, :nuts, :icon_url, :urls, :motd, :mint_time, :tos_url
+    :contact, :nuts, :icon_url, :urls, :motd, :mint_time, :tos_url, :signature
 )
 ON CONFLICT(mint_url) DO UPDATE SET
     name = excluded.name,
@@ -253,7 +283,8 @@ ON CONFLICT(mint_url) DO UPDATE SET
     urls = excluded.urls,
     motd = excluded.motd,
     mint_time = excluded.mint_time,
-    tos_url = excluded.tos_url
+    tos_url = excluded.tos_url,
+    signature = excluded.signature
 ;
         "#,
         )?
@@ -270,6 +301,7 @@ ON CONFLICT(mint_url) DO UPDATE SET
         .bind("motd", motd)
         .bind("mint_time", time.map(|v| v as i64))
         .bind("tos_url", tos_url)
+        .bind("signature", signature)
         .execute(&*conn)
         .await?;
 
@@ -305,7 +337,8 @@ ON CONFLICT(mint_url) DO UPDATE SET
                 motd,
                 urls,
                 mint_time,
-                tos_url
+                tos_url,
+                signature
             FROM
                 mint
             WHERE mint_url = :mint_url
@@ -336,6 +369,7 @@ ON CONFLICT(mint_url) DO UPDATE SET
                     urls,
                     mint_time,
                     tos_url,
+                    signature,
                     mint_url
                 FROM
                     mint
@@ -477,9 +511,9 @@ ON CONFLICT(mint_url) DO UPDATE SET
         query(
             r#"
 INSERT INTO mint_quote
-(id, mint_url, amount, unit, request, state, expiry, secret_key, payment_method, amount_issued, amount_paid)
+(id, mint_url, amount, unit, request, state, expiry, secret_key, payment_method, amount_issued, amount_paid, account)
 VALUES
-(:id, :mint_url, :amount, :unit, :request, :state, :expiry, :secret_key, :payment_method, :amount_issued, :amount_paid)
+(:id, :mint_url, :amount, :unit, :request, :state, :expiry, :secret_key, :payment_method, :amount_issued, :amount_paid, :account)
 ON CONFLICT(id) DO UPDATE SET
     mint_url = excluded.mint_url,
     amount = excluded.amount,
@@ -505,6 +539,7 @@ ON CONFLICT(id) DO UPDATE SET
         .bind("payment_method", quote.payment_method.to_string())
         .bind("amount_issued", quote.amount_issued.to_i64())
         .bind("amount_paid", quote.amount_paid.to_i64())
+        .bind("account", self.account)
         .execute(&*conn).await?;
 
         Ok(())
@@ -531,9 +566,11 @@ ON CONFLICT(id) DO UPDATE SET
                 mint_quote
             WHERE
                 id = :id
+                AND account = :account
             "#,
         )?
         .bind("id", quote_id.to_string())
+        .bind("account", self.account)
         .fetch_one(&*conn)
         .await?
         .map(sql_row_to_mint_quote)
@@ -559,8 +596,11 @@ ON CONFLICT(id) DO UPDATE SET
                 amount_paid
             FROM
                 mint_quote
+            WHERE
+                account = :account
             "#,
         )?
+        .bind("account", self.account)
         .fetch_all(&*conn)
         .await?
         .into_iter()
@@ -571,8 +611,25 @@ ON CONFLICT(id) DO UPDATE SET
     #[instrument(skip(self))]
     async fn remove_mint_quote(&self, quote_id: &str) -> Result<(), Self::Err> {
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
-        query(r#"DELETE FROM mint_quote WHERE id=:id"#)?
+        query(r#"DELETE FROM mint_quote WHERE id=:id AND account=:account"#)?
             .bind("id", quote_id.to_string())
+            .bind("account", self.account)
+            .execute(&*conn)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn remove_mint_quotes(&self, quote_ids: &[String]) -> Result<(), Self::Err> {
+        if quote_ids.is_empty() {
+            return Ok(());
+        }
+
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+        query(r#"DELETE FROM mint_quote WHERE id IN (:ids) AND account=:account"#)?
+            .bind_vec("ids", quote_ids.to_vec())
+            .bind("account", self.account)
             .execute(&*conn)
             .await?;
 
@@ -585,9 +642,9 @@ ON CONFLICT(id) DO UPDATE SET
         query(
             r#"
 INSERT INTO melt_quote
-(id, unit, amount, request, fee_reserve, state, expiry, payment_method)
+(id, unit, amount, request, fee_reserve, state, expiry, payment_method, mint_url, account)
 VALUES
-(:id, :unit, :amount, :request, :fee_reserve, :state, :expiry, :payment_method)
+(:id, :unit, :amount, :request, :fee_reserve, :state, :expiry, :payment_method, :mint_url, :account)
 ON CONFLICT(id) DO UPDATE SET
     unit = excluded.unit,
     amount = excluded.amount,
@@ -595,7 +652,8 @@ ON CONFLICT(id) DO UPDATE SET
     fee_reserve = excluded.fee_reserve,
     state = excluded.state,
     expiry = excluded.expiry,
-    payment_method = excluded.payment_method
+    payment_method = excluded.payment_method,
+    mint_url = excluded.mint_url
 ;
         "#,
         )?
@@ -607,6 +665,11 @@ ON CONFLICT(id) DO UPDATE SET
         .bind("state", quote.state.to_string())
         .bind("expiry", quote.expiry as i64)
         .bind("payment_method", quote.payment_method.to_string())
+        .bind(
+            "mint_url",
+            quote.mint_url.map(|mint_url| mint_url.to_string()),
+        )
+        .bind("account", self.account)
         .execute(&*conn)
         .await?;
 
@@ -627,14 +690,17 @@ ON CONFLICT(id) DO UPDATE SET
                 state,
                 expiry,
                 payment_preimage,
-                payment_method
+                payment_method,
+                mint_url
             FROM
                 melt_quote
             WHERE
                 id=:id
+                AND account=:account
             "#,
         )?
         .bind("id", quote_id.to_owned())
+        .bind("account", self.account)
         .fetch_one(&*conn)
         .await?
         .map(sql_row_to_melt_quote)
@@ -644,8 +710,25 @@ ON CONFLICT(id) DO UPDATE SET
     #[instrument(skip(self))]
     async fn remove_melt_quote(&self, quote_id: &str) -> Result<(), Self::Err> {
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
-        query(r#"DELETE FROM melt_quote WHERE id=:id"#)?
+        query(r#"DELETE FROM melt_quote WHERE id=:id AND account=:account"#)?
             .bind("id", quote_id.to_owned())
+            .bind("account", self.account)
+            .execute(&*conn)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn remove_melt_quotes(&self, quote_ids: &[String]) -> Result<(), Self::Err> {
+        if quote_ids.is_empty() {
+            return Ok(());
+        }
+
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+        query(r#"DELETE FROM melt_quote WHERE id IN (:ids) AND account=:account"#)?
+            .bind_vec("ids", quote_ids.to_vec())
+            .bind("account", self.account)
             .execute(&*conn)
             .await?;
 
@@ -725,9 +808,9 @@ ON CONFLICT(id) DO UPDATE SET
             query(
                 r#"
     INSERT INTO proof
-    (y, mint_url, state, spending_condition, unit, amount, keyset_id, secret, c, witness, dleq_e, dleq_s, dleq_r)
+    (y, mint_url, state, spending_condition, unit, amount, keyset_id, secret, c, witness, dleq_e, dleq_s, dleq_r, account)
     VALUES
-    (:y, :mint_url, :state, :spending_condition, :unit, :amount, :keyset_id, :secret, :c, :witness, :dleq_e, :dleq_s, :dleq_r)
+    (:y, :mint_url, :state, :spending_condition, :unit, :amount, :keyset_id, :secret, :c, :witness, :dleq_e, :dleq_s, :dleq_r, :account)
     ON CONFLICT(y) DO UPDATE SET
         mint_url = excluded.mint_url,
         state = excluded.state,
@@ -777,14 +860,40 @@ ON CONFLICT(id) DO UPDATE SET
                 "dleq_r",
                 proof.proof.dleq.as_ref().map(|dleq| dleq.r.to_secret_bytes().to_vec()),
             )
+            .bind("account", self.account)
             .execute(&tx).await?;
         }
 
-        query(r#"DELETE FROM proof WHERE y IN (:ys)"#)?
+        // Keep a summary of the removed proofs that were spent before deleting them
+        query(
+            r#"
+            INSERT INTO spent_proof
+            (y, mint_url, keyset_id, amount, unit, spent_time, account)
+            SELECT y, mint_url, keyset_id, amount, unit, :spent_time, account
+            FROM proof
+            WHERE y IN (:ys)
+            AND state IN (:spent, :pending_spent)
+            AND account = :account
+            ON CONFLICT(y) DO NOTHING
+            "#,
+        )?
+        .bind("spent_time", unix_time() as i64)
+        .bind("spent", State::Spent.to_string())
+        .bind("pending_spent", State::PendingSpent.to_string())
+        .bind("account", self.account)
+        .bind_vec(
+            "ys",
+            removed_ys.iter().map(|y| y.to_bytes().to_vec()).collect(),
+        )
+        .execute(&tx)
+        .await?;
+
+        query(r#"DELETE FROM proof WHERE y IN (:ys) AND account = :account"#)?
             .bind_vec(
                 "ys",
                 removed_ys.iter().map(|y| y.to_bytes().to_vec()).collect(),
             )
+            .bind("account", self.account)
             .execute(&tx)
             .await?;
 
@@ -819,8 +928,10 @@ ON CONFLICT(id) DO UPDATE SET
                 state,
                 spending_condition
             FROM proof
+            WHERE account = :account
         "#,
         )?
+        .bind("account", self.account)
         .fetch_all(&*conn)
         .await?
         .into_iter()
@@ -838,15 +949,97 @@ ON CONFLICT(id) DO UPDATE SET
 
     async fn update_proofs_state(&self, ys: Vec<PublicKey>, state: State) -> Result<(), Self::Err> {
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
-        query("UPDATE proof SET state = :state WHERE y IN (:ys)")?
+        query("UPDATE proof SET state = :state WHERE y IN (:ys) AND account = :account")?
             .bind_vec("ys", ys.iter().map(|y| y.to_bytes().to_vec()).collect())
             .bind("state", state.to_string())
+            .bind("account", self.account)
             .execute(&*conn)
             .await?;
 
         Ok(())
     }
 
+    #[instrument(skip(self))]
+    async fn get_archived_proofs(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+    ) -> Result<Vec<ArchivedProof>, Self::Err> {
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+
+        let mut conditions = vec!["account = :account"];
+        if mint_url.is_some() {
+            conditions.push("mint_url = :mint_url");
+        }
+        if unit.is_some() {
+            conditions.push("unit = :unit");
+        }
+
+        let mut stmt = query(&format!(
+            r#"
+            SELECT
+                y,
+                mint_url,
+                keyset_id,
+                amount,
+                unit,
+                spent_time
+            FROM spent_proof
+            WHERE {}
+            ORDER BY spent_time DESC
+            "#,
+            conditions.join(" AND ")
+        ))?
+        .bind("account", self.account);
+        if let Some(mint_url) = mint_url {
+            stmt = stmt.bind("mint_url", mint_url.to_string());
+        }
+        if let Some(unit) = unit {
+            stmt = stmt.bind("unit", unit.to_string());
+        }
+
+        stmt.fetch_all(&*conn)
+            .await?
+            .into_iter()
+            .map(sql_row_to_archived_proof)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    #[instrument(skip(self))]
+    async fn prune_archived_proofs(&self, spent_before: u64) -> Result<u64, Self::Err> {
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+        let removed = query(
+            r#"DELETE FROM spent_proof WHERE spent_time < :spent_before AND account = :account"#,
+        )?
+        .bind("spent_before", spent_before as i64)
+        .bind("account", self.account)
+        .execute(&*conn)
+        .await?;
+
+        Ok(removed as u64)
+    }
+
+    #[instrument(skip(self), fields(keyset_id = %keyset_id))]
+    async fn get_keyset_counter(&self, keyset_id: &Id) -> Result<u32, Self::Err> {
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+
+        Ok(query(
+            r#"
+            SELECT counter
+            FROM keyset_counter
+            WHERE account=:account
+            AND keyset_id=:keyset_id
+            "#,
+        )?
+        .bind("account", self.account)
+        .bind("keyset_id", keyset_id.to_string())
+        .pluck(&*conn)
+        .await?
+        .map(|n| Ok::<_, Error>(column_as_number!(n)))
+        .transpose()?
+        .unwrap_or(0))
+    }
+
     #[instrument(skip(self), fields(keyset_id = %keyset_id))]
     async fn increment_keyset_counter(&self, keyset_id: &Id, count: u32) -> Result<u32, Self::Err> {
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
@@ -856,12 +1049,14 @@ ON CONFLICT(id) DO UPDATE SET
         let current_counter = query(
             r#"
             SELECT counter
-            FROM keyset
-            WHERE id=:id
+            FROM keyset_counter
+            WHERE account=:account
+            AND keyset_id=:keyset_id
             FOR UPDATE
             "#,
         )?
-        .bind("id", keyset_id.to_string())
+        .bind("account", self.account)
+        .bind("keyset_id", keyset_id.to_string())
         .pluck(&tx)
         .await?
         .map(|n| Ok::<_, Error>(column_as_number!(n)))
@@ -873,13 +1068,17 @@ ON CONFLICT(id) DO UPDATE SET
         // Update with the new counter value
         query(
             r#"
-            UPDATE keyset
-            SET counter=:new_counter
-            WHERE id=:id
+            INSERT INTO keyset_counter
+            (account, keyset_id, counter)
+            VALUES
+            (:account, :keyset_id, :new_counter)
+            ON CONFLICT(account, keyset_id) DO UPDATE SET
+                counter = excluded.counter
             "#,
         )?
+        .bind("account", self.account)
+        .bind("keyset_id", keyset_id.to_string())
         .bind("new_counter", new_counter)
-        .bind("id", keyset_id.to_string())
         .execute(&tx)
         .await?;
 
@@ -905,9 +1104,9 @@ ON CONFLICT(id) DO UPDATE SET
         query(
             r#"
 INSERT INTO transactions
-(id, mint_url, direction, unit, amount, fee, ys, timestamp, memo, metadata, quote_id)
+(id, mint_url, direction, unit, amount, fee, ys, timestamp, memo, metadata, quote_id, status, account)
 VALUES
-(:id, :mint_url, :direction, :unit, :amount, :fee, :ys, :timestamp, :memo, :metadata, :quote_id)
+(:id, :mint_url, :direction, :unit, :amount, :fee, :ys, :timestamp, :memo, :metadata, :quote_id, :status, :account)
 ON CONFLICT(id) DO UPDATE SET
     mint_url = excluded.mint_url,
     direction = excluded.direction,
@@ -918,7 +1117,8 @@ ON CONFLICT(id) DO UPDATE SET
     timestamp = excluded.timestamp,
     memo = excluded.memo,
     metadata = excluded.metadata,
-    quote_id = excluded.quote_id
+    quote_id = excluded.quote_id,
+    status = excluded.status
 ;
         "#,
         )?
@@ -936,6 +1136,8 @@ ON CONFLICT(id) DO UPDATE SET
             serde_json::to_string(&transaction.metadata).map_err(Error::from)?,
         )
         .bind("quote_id", transaction.quote_id)
+        .bind("status", transaction.status.to_string())
+        .bind("account", self.account)
         .execute(&*conn)
         .await?;
 
@@ -960,14 +1162,17 @@ ON CONFLICT(id) DO UPDATE SET
                 timestamp,
                 memo,
                 metadata,
-                quote_id
+                quote_id,
+                status
             FROM
                 transactions
             WHERE
                 id = :id
+                AND account = :account
             "#,
         )?
         .bind("id", transaction_id.as_slice().to_vec())
+        .bind("account", self.account)
         .fetch_one(&*conn)
         .await?
         .map(sql_row_to_transaction)
@@ -995,11 +1200,15 @@ ON CONFLICT(id) DO UPDATE SET
                 timestamp,
                 memo,
                 metadata,
-                quote_id
+                quote_id,
+                status
             FROM
                 transactions
+            WHERE
+                account = :account
             "#,
         )?
+        .bind("account", self.account)
         .fetch_all(&*conn)
         .await?
         .into_iter()
@@ -1019,13 +1228,239 @@ ON CONFLICT(id) DO UPDATE SET
     async fn remove_transaction(&self, transaction_id: TransactionId) -> Result<(), Self::Err> {
         let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
 
-        query(r#"DELETE FROM transactions WHERE id=:id"#)?
+        query(r#"DELETE FROM transactions WHERE id=:id AND account=:account"#)?
             .bind("id", transaction_id.as_slice().to_vec())
+            .bind("account", self.account)
             .execute(&*conn)
             .await?;
 
         Ok(())
     }
+
+    #[instrument(skip(self))]
+    async fn add_fee_entry(&self, entry: FeeEntry) -> Result<(), Self::Err> {
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+
+        query(
+            r#"
+INSERT INTO fee_ledger
+(account, mint_url, unit, kind, amount, timestamp, quote_id)
+VALUES
+(:account, :mint_url, :unit, :kind, :amount, :timestamp, :quote_id)
+        "#,
+        )?
+        .bind("account", self.account)
+        .bind("mint_url", entry.mint_url.to_string())
+        .bind("unit", entry.unit.to_string())
+        .bind("kind", entry.kind.to_string())
+        .bind("amount", u64::from(entry.amount) as i64)
+        .bind("timestamp", entry.timestamp as i64)
+        .bind("quote_id", entry.quote_id)
+        .execute(&*conn)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_fee_entries(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<FeeEntry>, Self::Err> {
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+
+        let mut conditions = vec!["account = :account"];
+        if mint_url.is_some() {
+            conditions.push("mint_url = :mint_url");
+        }
+        if unit.is_some() {
+            conditions.push("unit = :unit");
+        }
+        if since.is_some() {
+            conditions.push("timestamp >= :since");
+        }
+        if until.is_some() {
+            conditions.push("timestamp < :until");
+        }
+
+        let mut stmt = query(&format!(
+            r#"
+            SELECT
+                mint_url,
+                unit,
+                kind,
+                amount,
+                timestamp,
+                quote_id
+            FROM
+                fee_ledger
+            WHERE {}
+            ORDER BY timestamp
+            "#,
+            conditions.join(" AND ")
+        ))?
+        .bind("account", self.account);
+        if let Some(mint_url) = mint_url {
+            stmt = stmt.bind("mint_url", mint_url.to_string());
+        }
+        if let Some(unit) = unit {
+            stmt = stmt.bind("unit", unit.to_string());
+        }
+        if let Some(since) = since {
+            stmt = stmt.bind("since", since as i64);
+        }
+        if let Some(until) = until {
+            stmt = stmt.bind("until", until as i64);
+        }
+
+        stmt.fetch_all(&*conn)
+            .await?
+            .into_iter()
+            .map(sql_row_to_fee_entry)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    #[instrument(skip(self))]
+    async fn kv_read(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Self::Err> {
+        // Validate parameters according to KV store requirements
+        validate_kvstore_params(primary_namespace, secondary_namespace, key)?;
+
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+        Ok(query(
+            r#"
+            SELECT value
+            FROM kv_store
+            WHERE account = :account
+            AND primary_namespace = :primary_namespace
+            AND secondary_namespace = :secondary_namespace
+            AND key = :key
+            "#,
+        )?
+        .bind("account", self.account)
+        .bind("primary_namespace", primary_namespace.to_owned())
+        .bind("secondary_namespace", secondary_namespace.to_owned())
+        .bind("key", key.to_owned())
+        .pluck(&*conn)
+        .await?
+        .and_then(|col| match col {
+            Column::Blob(data) => Some(data),
+            _ => None,
+        }))
+    }
+
+    #[instrument(skip(self, value))]
+    async fn kv_write(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), Self::Err> {
+        // Validate parameters according to KV store requirements
+        validate_kvstore_params(primary_namespace, secondary_namespace, key)?;
+
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+        let current_time = unix_time();
+
+        query(
+            r#"
+            INSERT INTO kv_store
+            (account, primary_namespace, secondary_namespace, key, value, created_time, updated_time)
+            VALUES (:account, :primary_namespace, :secondary_namespace, :key, :value, :created_time, :updated_time)
+            ON CONFLICT(account, primary_namespace, secondary_namespace, key)
+            DO UPDATE SET
+                value = excluded.value,
+                updated_time = excluded.updated_time
+            "#,
+        )?
+        .bind("account", self.account)
+        .bind("primary_namespace", primary_namespace.to_owned())
+        .bind("secondary_namespace", secondary_namespace.to_owned())
+        .bind("key", key.to_owned())
+        .bind("value", value.to_vec())
+        .bind("created_time", current_time as i64)
+        .bind("updated_time", current_time as i64)
+        .execute(&*conn)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn kv_remove(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<(), Self::Err> {
+        // Validate parameters according to KV store requirements
+        validate_kvstore_params(primary_namespace, secondary_namespace, key)?;
+
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+        query(
+            r#"
+            DELETE FROM kv_store
+            WHERE account = :account
+            AND primary_namespace = :primary_namespace
+            AND secondary_namespace = :secondary_namespace
+            AND key = :key
+            "#,
+        )?
+        .bind("account", self.account)
+        .bind("primary_namespace", primary_namespace.to_owned())
+        .bind("secondary_namespace", secondary_namespace.to_owned())
+        .bind("key", key.to_owned())
+        .execute(&*conn)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn kv_list(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+    ) -> Result<Vec<String>, Self::Err> {
+        // Validate namespace parameters according to KV store requirements
+        validate_kvstore_string(primary_namespace)?;
+        validate_kvstore_string(secondary_namespace)?;
+
+        // Check empty namespace rules
+        if primary_namespace.is_empty() && !secondary_namespace.is_empty() {
+            return Err(Error::KVStoreInvalidKey(
+                "If primary_namespace is empty, secondary_namespace must also be empty".to_string(),
+            ));
+        }
+
+        let conn = self.pool.get().map_err(|e| Error::Database(Box::new(e)))?;
+        Ok(query(
+            r#"
+            SELECT key
+            FROM kv_store
+            WHERE account = :account
+            AND primary_namespace = :primary_namespace
+            AND secondary_namespace = :secondary_namespace
+            ORDER BY key
+            "#,
+        )?
+        .bind("account", self.account)
+        .bind("primary_namespace", primary_namespace.to_owned())
+        .bind("secondary_namespace", secondary_namespace.to_owned())
+        .fetch_all(&*conn)
+        .await?
+        .into_iter()
+        .map(|row| Ok(column_as_string!(&row[0])))
+        .collect::<Result<Vec<_>, Error>>()?)
+    }
 }
 
 fn sql_row_to_mint_info(row: Vec<Column>) -> Result<MintInfo, Error> {
@@ -1042,7 +1477,8 @@ fn sql_row_to_mint_info(row: Vec<Column>) -> Result<MintInfo, Error> {
             motd,
             urls,
             mint_time,
-            tos_url
+            tos_url,
+            signature
         ) = row
     );
 
@@ -1062,6 +1498,7 @@ fn sql_row_to_mint_info(row: Vec<Column>) -> Result<MintInfo, Error> {
         motd: column_as_nullable_string!(motd),
         time: column_as_nullable_number!(mint_time).map(|t| t),
         tos_url: column_as_nullable_string!(tos_url),
+        signature: column_as_nullable_string!(signature),
     })
 }
 
@@ -1138,7 +1575,8 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<wallet::MeltQuote, Error> {
             state,
             expiry,
             payment_preimage,
-            row_method
+            row_method,
+            mint_url
         ) = row
     );
 
@@ -1158,6 +1596,7 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<wallet::MeltQuote, Error> {
         expiry: column_as_number!(expiry),
         payment_preimage: column_as_nullable_string!(payment_preimage),
         payment_method,
+        mint_url: column_as_nullable_string!(mint_url, |v| MintUrl::from_str(&v).ok()),
     })
 }
 
@@ -1221,6 +1660,54 @@ fn sql_row_to_proof_info(row: Vec<Column>) -> Result<ProofInfo, Error> {
     })
 }
 
+fn sql_row_to_archived_proof(row: Vec<Column>) -> Result<ArchivedProof, Error> {
+    unpack_into!(
+        let (
+            y,
+            mint_url,
+            keyset_id,
+            amount,
+            unit,
+            spent_time
+        ) = row
+    );
+
+    let amount: u64 = column_as_number!(amount);
+
+    Ok(ArchivedProof {
+        y: column_as_string!(y, PublicKey::from_str, PublicKey::from_slice),
+        mint_url: column_as_string!(mint_url, MintUrl::from_str),
+        keyset_id: column_as_string!(keyset_id, Id::from_str),
+        amount: Amount::from(amount),
+        unit: column_as_string!(unit, CurrencyUnit::from_str),
+        spent_time: column_as_number!(spent_time),
+    })
+}
+
+fn sql_row_to_fee_entry(row: Vec<Column>) -> Result<FeeEntry, Error> {
+    unpack_into!(
+        let (
+            mint_url,
+            unit,
+            kind,
+            amount,
+            timestamp,
+            quote_id
+        ) = row
+    );
+
+    let amount: u64 = column_as_number!(amount);
+
+    Ok(FeeEntry {
+        mint_url: column_as_string!(mint_url, MintUrl::from_str),
+        unit: column_as_string!(unit, CurrencyUnit::from_str),
+        kind: column_as_string!(kind, FeeKind::from_str),
+        amount: Amount::from(amount),
+        timestamp: column_as_number!(timestamp),
+        quote_id: column_as_nullable_string!(quote_id),
+    })
+}
+
 fn sql_row_to_transaction(row: Vec<Column>) -> Result<Transaction, Error> {
     unpack_into!(
         let (
@@ -1233,7 +1720,8 @@ fn sql_row_to_transaction(row: Vec<Column>) -> Result<Transaction, Error> {
             timestamp,
             memo,
             metadata,
-            quote_id
+            quote_id,
+            status
         ) = row
     );
 
@@ -1257,5 +1745,6 @@ fn sql_row_to_transaction(row: Vec<Column>) -> Result<Transaction, Error> {
         })
         .unwrap_or_default(),
         quote_id: column_as_nullable_string!(quote_id),
+        status: column_as_string!(status, TransactionStatus::from_str),
     })
 }
@@ -1,4 +1,7 @@
 //! Simple SQLite
+use std::sync::Arc;
+use std::time::Duration;
+
 use cdk_common::database::Error;
 use cdk_sql_common::database::{DatabaseConnector, DatabaseExecutor, DatabaseTransaction};
 use cdk_sql_common::run_db_operation_sync;
@@ -12,12 +15,14 @@ use crate::common::{from_sqlite, to_sqlite};
 #[derive(Debug)]
 pub struct AsyncSqlite {
     inner: Mutex<Connection>,
+    slow_query_threshold: Duration,
 }
 
 impl AsyncSqlite {
-    pub fn new(inner: Connection) -> Self {
+    pub fn new(inner: Connection, slow_query_threshold: Duration) -> Self {
         Self {
             inner: inner.into(),
+            slow_query_threshold,
         }
     }
 }
@@ -26,7 +31,8 @@ impl AsyncSqlite {
         &self,
         conn: &'a Connection,
         statement: Statement,
-    ) -> Result<(String, CachedStatement<'a>), Error> {
+    ) -> Result<(Arc<str>, String, CachedStatement<'a>), Error> {
+        let name = statement.name();
         let (sql, placeholder_values) = statement.to_sql()?;
 
         let new_sql = sql.trim().trim_end_matches("FOR UPDATE");
@@ -40,7 +46,7 @@ impl AsyncSqlite {
                 .map_err(|e| Error::Database(Box::new(e)))?;
         }
 
-        Ok((sql, stmt))
+        Ok((name, sql, stmt))
     }
 }
 
@@ -105,21 +111,29 @@ impl DatabaseExecutor for AsyncSqlite {
     async fn execute(&self, statement: Statement) -> Result<usize, Error> {
         let conn = self.inner.lock().await;
 
-        let (sql, mut stmt) = self
+        let (name, sql, mut stmt) = self
             .get_stmt(&conn, statement)
             .map_err(|e| Error::Database(Box::new(e)))?;
 
-        run_db_operation_sync(&sql, || stmt.raw_execute(), to_sqlite_error)
+        run_db_operation_sync(
+            &name,
+            &sql,
+            self.slow_query_threshold,
+            || stmt.raw_execute(),
+            to_sqlite_error,
+        )
     }
 
     async fn fetch_one(&self, statement: Statement) -> Result<Option<Vec<Column>>, Error> {
         let conn = self.inner.lock().await;
-        let (sql, mut stmt) = self
+        let (name, sql, mut stmt) = self
             .get_stmt(&conn, statement)
             .map_err(|e| Error::Database(Box::new(e)))?;
 
         run_db_operation_sync(
+            &name,
             &sql,
+            self.slow_query_threshold,
             || {
                 let columns = stmt.column_count();
 
@@ -138,14 +152,16 @@ impl DatabaseExecutor for AsyncSqlite {
 
     async fn fetch_all(&self, statement: Statement) -> Result<Vec<Vec<Column>>, Error> {
         let conn = self.inner.lock().await;
-        let (sql, mut stmt) = self
+        let (name, sql, mut stmt) = self
             .get_stmt(&conn, statement)
             .map_err(|e| Error::Database(Box::new(e)))?;
 
         let columns = stmt.column_count();
 
         run_db_operation_sync(
+            &name,
             &sql,
+            self.slow_query_threshold,
             || {
                 let mut rows = stmt.raw_query();
                 let mut results = vec![];
@@ -166,12 +182,14 @@ impl DatabaseExecutor for AsyncSqlite {
 
     async fn pluck(&self, statement: Statement) -> Result<Option<Column>, Error> {
         let conn = self.inner.lock().await;
-        let (sql, mut stmt) = self
+        let (name, sql, mut stmt) = self
             .get_stmt(&conn, statement)
             .map_err(|e| Error::Database(Box::new(e)))?;
 
         run_db_operation_sync(
+            &name,
             &sql,
+            self.slow_query_threshold,
             || {
                 let mut rows = stmt.raw_query();
                 rows.next()?
@@ -183,6 +201,7 @@ impl DatabaseExecutor for AsyncSqlite {
     }
 
     async fn batch(&self, mut statement: Statement) -> Result<(), Error> {
+        let name = statement.name();
         let sql = {
             let part = statement
                 .parts
@@ -203,6 +222,12 @@ impl DatabaseExecutor for AsyncSqlite {
         };
         let conn = self.inner.lock().await;
 
-        run_db_operation_sync(&sql, || conn.execute_batch(&sql), to_sqlite_error)
+        run_db_operation_sync(
+            &name,
+            &sql,
+            self.slow_query_threshold,
+            || conn.execute_batch(&sql),
+            to_sqlite_error,
+        )
     }
 }
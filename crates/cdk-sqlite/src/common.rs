@@ -5,6 +5,7 @@ use std::time::Duration;
 
 use cdk_sql_common::pool::{self, DatabasePool};
 use cdk_sql_common::value::Value;
+use cdk_sql_common::DEFAULT_SLOW_QUERY_THRESHOLD;
 use rusqlite::Connection;
 
 use crate::async_sqlite;
@@ -14,6 +15,15 @@ use crate::async_sqlite;
 pub struct Config {
     path: Option<String>,
     password: Option<String>,
+    slow_query_threshold: Duration,
+}
+
+impl Config {
+    /// Log queries slower than `threshold`, defaults to [`DEFAULT_SLOW_QUERY_THRESHOLD`]
+    pub fn with_slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = threshold;
+        self
+    }
 }
 
 impl pool::DatabaseConfig for Config {
@@ -69,7 +79,10 @@ impl DatabasePool for SqliteConnectionManager {
 
         conn.busy_timeout(Duration::from_secs(10))?;
 
-        Ok(async_sqlite::AsyncSqlite::new(conn))
+        Ok(async_sqlite::AsyncSqlite::new(
+            conn,
+            config.slow_query_threshold,
+        ))
     }
 }
 
@@ -97,11 +110,13 @@ impl From<&str> for Config {
             Config {
                 path: None,
                 password: None,
+                slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
             }
         } else {
             Config {
                 path: Some(path.to_owned()),
                 password: None,
+                slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
             }
         }
     }
@@ -113,11 +128,13 @@ impl From<(&str, &str)> for Config {
             Config {
                 path: None,
                 password: Some(pass.to_owned()),
+                slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
             }
         } else {
             Config {
                 path: Some(path.to_owned()),
                 password: Some(pass.to_owned()),
+                slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
             }
         }
     }
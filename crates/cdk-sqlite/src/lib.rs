@@ -11,6 +11,7 @@ pub mod mint;
 #[cfg(feature = "wallet")]
 pub mod wallet;
 
+pub use common::Config as SqliteConfig;
 #[cfg(feature = "mint")]
 pub use mint::MintSqliteDatabase;
 #[cfg(feature = "wallet")]
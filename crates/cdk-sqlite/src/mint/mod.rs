@@ -66,4 +66,118 @@ mod test {
 
         let _ = remove_file(&file);
     }
+
+    /// Only the explicit replica reads are served by the read replica
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn read_replica_serves_only_replica_reads() {
+        use std::str::FromStr;
+
+        use cdk_common::database::{
+            MintDatabase, MintDbWriterFinalizer, MintKeyDatabaseTransaction, MintKeysDatabase,
+            MintProofsDatabase, MintProofsTransaction, MintQuotesDatabase, MintQuotesTransaction,
+        };
+        use cdk_common::mint::{MeltPaymentRequest, MeltQuote, MintKeySetInfo};
+        use cdk_common::nuts::{CurrencyUnit, Id, MeltQuoteState, PaymentMethod, Proof, State};
+        use cdk_common::secret::Secret;
+        use cdk_common::{Amount, Bolt11Invoice, SecretKey};
+
+        let dir = std::env::temp_dir();
+        let primary_file = format!("{}/read-replica-primary.sqlite", dir.display());
+        let replica_file = format!("{}/read-replica-replica.sqlite", dir.display());
+        let _ = remove_file(&primary_file);
+        let _ = remove_file(&replica_file);
+
+        // The replica lags behind: it has the quote as unpaid and none of the proofs
+        let replica = MintSqliteDatabase::new(replica_file.as_str())
+            .await
+            .expect("replica");
+        let db =
+            MintSqliteDatabase::new_with_read_replica(primary_file.as_str(), replica_file.as_str())
+                .await
+                .expect("database");
+
+        let bolt11 = Bolt11Invoice::from_str("lnbc100n1p5z3a63pp56854ytysg7e5z9fl3w5mgvrlqjfcytnjv8ff5hm5qt6gl6alxesqdqqcqzzsxqyz5vqsp5p0x0dlhn27s63j4emxnk26p7f94u0lyarnfp5yqmac9gzy4ngdss9qxpqysgqne3v0hnzt2lp0hc69xpzckk0cdcar7glvjhq60lsrfe8gejdm8c564prrnsft6ctxxyrewp4jtezrq3gxxqnfjj0f9tw2qs9y0lslmqpfu7et9").unwrap();
+        let quote = MeltQuote::new(
+            MeltPaymentRequest::Bolt11 { bolt11 },
+            CurrencyUnit::Sat,
+            10.into(),
+            1.into(),
+            0,
+            None,
+            None,
+            PaymentMethod::Bolt11,
+        );
+
+        for store in [&replica, &db] {
+            let mut tx = MintDatabase::begin_transaction(store).await.unwrap();
+            tx.add_melt_quote(quote.clone()).await.unwrap();
+            tx.commit().await.unwrap();
+        }
+
+        let keyset_id = Id::from_str("00916bbf7ef91a36").unwrap();
+        let mut tx = MintKeysDatabase::begin_transaction(&db).await.unwrap();
+        tx.add_keyset_info(MintKeySetInfo {
+            id: keyset_id,
+            unit: CurrencyUnit::Sat,
+            active: true,
+            valid_from: 0,
+            final_expiry: None,
+            derivation_path: "m/0'/0'/0'".parse().unwrap(),
+            derivation_path_index: Some(0),
+            max_order: 32,
+            input_fee_ppk: 0,
+            amounts: vec![],
+        })
+        .await
+        .unwrap();
+        tx.commit().await.unwrap();
+
+        let proof = Proof {
+            amount: Amount::from(8),
+            keyset_id,
+            secret: Secret::generate(),
+            c: SecretKey::generate().public_key(),
+            witness: None,
+            dleq: None,
+        };
+        let y = proof.y().unwrap();
+
+        let mut tx = MintDatabase::begin_transaction(&db).await.unwrap();
+        tx.add_proofs(vec![proof], Some(quote.id.clone()))
+            .await
+            .unwrap();
+        tx.update_proofs_states(&[y], State::Pending).await.unwrap();
+        tx.update_melt_quote_state(&quote.id, MeltQuoteState::Pending, None)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        // Reads feeding writes see the primary
+        assert_eq!(
+            db.get_melt_quote(&quote.id).await.unwrap().unwrap().state,
+            MeltQuoteState::Pending
+        );
+        assert_eq!(
+            db.get_proofs_states(&[y]).await.unwrap(),
+            vec![Some(State::Pending)]
+        );
+
+        // Replica reads see the replica
+        assert_eq!(
+            db.get_melt_quote_from_replica(&quote.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .state,
+            MeltQuoteState::Unpaid
+        );
+        assert_eq!(
+            db.get_proofs_states_from_replica(&[y]).await.unwrap(),
+            vec![None]
+        );
+
+        let _ = remove_file(&primary_file);
+        let _ = remove_file(&replica_file);
+    }
 }
@@ -179,4 +179,345 @@ mod tests {
             assert_eq!(retrieved.amount_paid, Amount::from(0));
         }
     }
+
+    #[tokio::test]
+    async fn test_removed_proofs_are_archived() {
+        use cdk_common::common::ProofInfo;
+        use cdk_common::mint_url::MintUrl;
+        use cdk_common::nuts::{CurrencyUnit, Id, Proof, PublicKey};
+        use cdk_common::util::unix_time;
+        use cdk_common::Amount;
+
+        // Create a temporary database
+        let path = std::env::temp_dir()
+            .to_path_buf()
+            .join(format!("cdk-test-archive-{}.sqlite", uuid::Uuid::new_v4()));
+
+        #[cfg(feature = "sqlcipher")]
+        let db = WalletSqliteDatabase::new((path, "password".to_string()))
+            .await
+            .unwrap();
+
+        #[cfg(not(feature = "sqlcipher"))]
+        let db = WalletSqliteDatabase::new(path).await.unwrap();
+
+        let keyset_id = Id::from_str("00deadbeef123456").unwrap();
+        let mint_url = MintUrl::from_str("https://example.com").unwrap();
+
+        let proof = Proof::new(
+            Amount::from(64),
+            keyset_id,
+            Secret::new("test_secret_for_archive"),
+            PublicKey::from_hex(
+                "02deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef",
+            )
+            .unwrap(),
+        );
+        let proof_info =
+            ProofInfo::new(proof, mint_url.clone(), State::Unspent, CurrencyUnit::Sat).unwrap();
+
+        db.update_proofs(vec![proof_info.clone()], vec![])
+            .await
+            .unwrap();
+        assert!(db.get_archived_proofs(None, None).await.unwrap().is_empty());
+
+        // Removing a proof that was never spent does not archive it
+        db.update_proofs(vec![], vec![proof_info.y]).await.unwrap();
+        assert!(db.get_archived_proofs(None, None).await.unwrap().is_empty());
+
+        // Removing a spent proof moves its summary to the archive
+        db.update_proofs(vec![proof_info.clone()], vec![])
+            .await
+            .unwrap();
+        db.update_proofs_state(vec![proof_info.y], State::Spent)
+            .await
+            .unwrap();
+        db.update_proofs(vec![], vec![proof_info.y]).await.unwrap();
+
+        assert!(db
+            .get_proofs(None, None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let archived = db
+            .get_archived_proofs(Some(mint_url), Some(CurrencyUnit::Sat))
+            .await
+            .unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].y, proof_info.y);
+        assert_eq!(archived[0].keyset_id, keyset_id);
+        assert_eq!(archived[0].amount, Amount::from(64));
+
+        // The archive is filtered by mint and unit
+        assert!(db
+            .get_archived_proofs(
+                Some(MintUrl::from_str("https://other.example.com").unwrap()),
+                None
+            )
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .get_archived_proofs(None, Some(CurrencyUnit::Msat))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Proofs archived after the cutoff are kept
+        assert_eq!(
+            db.prune_archived_proofs(archived[0].spent_time)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(db.prune_archived_proofs(unix_time() + 1).await.unwrap(), 1);
+        assert!(db.get_archived_proofs(None, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_accounts_are_isolated() {
+        use cdk_common::common::ProofInfo;
+        use cdk_common::mint_url::MintUrl;
+        use cdk_common::nuts::{CurrencyUnit, Id, Proof, PublicKey};
+        use cdk_common::Amount;
+
+        // Create a temporary database
+        let path = std::env::temp_dir()
+            .to_path_buf()
+            .join(format!("cdk-test-accounts-{}.sqlite", uuid::Uuid::new_v4()));
+
+        #[cfg(feature = "sqlcipher")]
+        let db = WalletSqliteDatabase::new((path, "password".to_string()))
+            .await
+            .unwrap();
+
+        #[cfg(not(feature = "sqlcipher"))]
+        let db = WalletSqliteDatabase::new(path).await.unwrap();
+
+        let business = db.with_account(1);
+        assert_eq!(db.account(), 0);
+        assert_eq!(business.account(), 1);
+
+        let keyset_id = Id::from_str("00deadbeef123456").unwrap();
+        let mint_url = MintUrl::from_str("https://example.com").unwrap();
+
+        let proof = Proof::new(
+            Amount::from(64),
+            keyset_id,
+            Secret::new("test_secret_for_accounts"),
+            PublicKey::from_hex(
+                "02deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef",
+            )
+            .unwrap(),
+        );
+        let proof_info =
+            ProofInfo::new(proof, mint_url, State::Unspent, CurrencyUnit::Sat).unwrap();
+
+        business
+            .update_proofs(vec![proof_info.clone()], vec![])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            business.get_proofs(None, None, None, None).await.unwrap(),
+            vec![proof_info]
+        );
+        assert!(db
+            .get_proofs(None, None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Each account derives from its own counter
+        assert_eq!(db.get_keyset_counter(&keyset_id).await.unwrap(), 0);
+        assert_eq!(db.increment_keyset_counter(&keyset_id, 5).await.unwrap(), 5);
+        assert_eq!(
+            business
+                .increment_keyset_counter(&keyset_id, 2)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(db.increment_keyset_counter(&keyset_id, 1).await.unwrap(), 6);
+        assert_eq!(db.get_keyset_counter(&keyset_id).await.unwrap(), 6);
+        assert_eq!(business.get_keyset_counter(&keyset_id).await.unwrap(), 2);
+
+        business
+            .kv_write("cdk_wallet", "test", "key", b"business")
+            .await
+            .unwrap();
+        assert_eq!(db.kv_read("cdk_wallet", "test", "key").await.unwrap(), None);
+        assert!(db.kv_list("cdk_wallet", "test").await.unwrap().is_empty());
+        assert_eq!(
+            business.kv_read("cdk_wallet", "test", "key").await.unwrap(),
+            Some(b"business".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_mint_info_round_trip() {
+        use cdk_common::mint_url::MintUrl;
+        use cdk_common::nuts::SecretKey;
+        use cdk_common::MintInfo;
+
+        // Create a temporary database
+        let path = std::env::temp_dir().to_path_buf().join(format!(
+            "cdk-test-mint-info-{}.sqlite",
+            uuid::Uuid::new_v4()
+        ));
+
+        #[cfg(feature = "sqlcipher")]
+        let db = WalletSqliteDatabase::new((path, "password".to_string()))
+            .await
+            .unwrap();
+
+        #[cfg(not(feature = "sqlcipher"))]
+        let db = WalletSqliteDatabase::new(path).await.unwrap();
+
+        let mut mint_info = MintInfo::new().name("Cashu mint");
+        mint_info.sign(&SecretKey::generate()).unwrap();
+        let mint_url = MintUrl::from_str("https://mint.xyz").unwrap();
+
+        db.add_mint(mint_url.clone(), Some(mint_info.clone()))
+            .await
+            .unwrap();
+
+        let stored = db.get_mint(mint_url.clone()).await.unwrap().unwrap();
+        assert_eq!(stored.signature, mint_info.signature);
+        assert!(stored.verify_signature().is_ok());
+
+        let mints = db.get_mints().await.unwrap();
+        assert_eq!(
+            mints.get(&mint_url).cloned().flatten().unwrap().signature,
+            mint_info.signature
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fee_ledger() {
+        use cdk_common::mint_url::MintUrl;
+        use cdk_common::nuts::CurrencyUnit;
+        use cdk_common::wallet::{FeeEntry, FeeKind};
+        use cdk_common::Amount;
+
+        // Create a temporary database
+        let path = std::env::temp_dir()
+            .to_path_buf()
+            .join(format!("cdk-test-fees-{}.sqlite", uuid::Uuid::new_v4()));
+
+        #[cfg(feature = "sqlcipher")]
+        let db = WalletSqliteDatabase::new((path, "password".to_string()))
+            .await
+            .unwrap();
+
+        #[cfg(not(feature = "sqlcipher"))]
+        let db = WalletSqliteDatabase::new(path).await.unwrap();
+
+        let mint_a = MintUrl::from_str("https://a.example.com").unwrap();
+        let mint_b = MintUrl::from_str("https://b.example.com").unwrap();
+
+        let entries = [
+            (
+                mint_a.clone(),
+                CurrencyUnit::Sat,
+                FeeKind::Input,
+                1,
+                100,
+                None,
+            ),
+            (
+                mint_a.clone(),
+                CurrencyUnit::Sat,
+                FeeKind::Lightning,
+                4,
+                200,
+                Some("quote".to_string()),
+            ),
+            (
+                mint_b.clone(),
+                CurrencyUnit::Sat,
+                FeeKind::Input,
+                2,
+                300,
+                None,
+            ),
+            (
+                mint_b.clone(),
+                CurrencyUnit::Msat,
+                FeeKind::Input,
+                3,
+                400,
+                None,
+            ),
+        ]
+        .map(
+            |(mint_url, unit, kind, amount, timestamp, quote_id)| FeeEntry {
+                mint_url,
+                unit,
+                kind,
+                amount: Amount::from(amount),
+                timestamp,
+                quote_id,
+            },
+        );
+
+        for entry in entries.iter().cloned() {
+            db.add_fee_entry(entry).await.unwrap();
+        }
+
+        assert_eq!(
+            db.list_fee_entries(None, None, None, None).await.unwrap(),
+            entries.to_vec()
+        );
+        assert_eq!(
+            db.list_fee_entries(Some(mint_a.clone()), None, None, None)
+                .await
+                .unwrap(),
+            entries[..2].to_vec()
+        );
+        assert_eq!(
+            db.list_fee_entries(None, Some(CurrencyUnit::Msat), None, None)
+                .await
+                .unwrap(),
+            entries[3..].to_vec()
+        );
+        // `since` is inclusive and `until` exclusive
+        assert_eq!(
+            db.list_fee_entries(None, None, Some(200), Some(300))
+                .await
+                .unwrap(),
+            entries[1..2].to_vec()
+        );
+        assert_eq!(
+            db.list_fee_entries(None, None, Some(300), None)
+                .await
+                .unwrap(),
+            entries[2..].to_vec()
+        );
+        assert_eq!(
+            db.list_fee_entries(
+                Some(mint_b.clone()),
+                Some(CurrencyUnit::Sat),
+                Some(100),
+                Some(400)
+            )
+            .await
+            .unwrap(),
+            entries[2..3].to_vec()
+        );
+        assert!(db
+            .list_fee_entries(Some(mint_a), Some(CurrencyUnit::Msat), None, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Other accounts keep their own ledger
+        assert!(db
+            .with_account(1)
+            .list_fee_entries(Some(mint_b), None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
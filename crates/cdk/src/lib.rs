@@ -12,8 +12,9 @@ pub mod cdk_database {
     pub use cdk_common::database::WalletDatabase;
     #[cfg(feature = "mint")]
     pub use cdk_common::database::{
-        MintDatabase, MintKVStore, MintKVStoreDatabase, MintKVStoreTransaction, MintKeysDatabase,
-        MintProofsDatabase, MintQuotesDatabase, MintSignaturesDatabase, MintTransaction,
+        DynMintLeaderElection, MintDatabase, MintKVStore, MintKVStoreDatabase,
+        MintKVStoreTransaction, MintKeysDatabase, MintLeaderElection, MintProofsDatabase,
+        MintQuotesDatabase, MintSignaturesDatabase, MintTransaction,
     };
 }
 
@@ -33,12 +34,16 @@ pub use oidc_client::OidcClient;
 
 pub mod pub_sub;
 
+#[cfg(feature = "wallet")]
+#[doc(hidden)]
+pub use cdk_common::paper_backup;
 /// Re-export amount type
 #[doc(hidden)]
 pub use cdk_common::{
     amount, common as types, dhke, ensure_cdk,
     error::{self, Error},
-    lightning_invoice, mint_url, nuts, secret, util, ws, Amount, Bolt11Invoice,
+    keyset_history, lightning_invoice, mint_url, nuts, quote_timestamps, secret, util, ws, Amount,
+    Bolt11Invoice,
 };
 #[cfg(feature = "mint")]
 #[doc(hidden)]
@@ -58,6 +63,8 @@ pub use wallet::{Wallet, WalletSubscription};
 #[doc(hidden)]
 pub use self::util::SECP256K1;
 #[cfg(feature = "wallet")]
+pub use self::wallet::verify_token;
+#[cfg(feature = "wallet")]
 #[doc(hidden)]
 pub use self::wallet::HttpClient;
 
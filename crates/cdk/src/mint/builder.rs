@@ -2,9 +2,10 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bitcoin::bip32::DerivationPath;
-use cdk_common::database::{DynMintDatabase, MintKeysDatabase};
+use cdk_common::database::{DynMintDatabase, DynMintLeaderElection, MintKeysDatabase};
 use cdk_common::error::Error;
 use cdk_common::nut04::MintMethodOptions;
 use cdk_common::nut05::MeltMethodOptions;
@@ -15,15 +16,17 @@ use cdk_signatory::signatory::Signatory;
 
 use super::nut17::SupportedMethods;
 use super::nut19::{self, CachedEndpoint};
+use super::risk::{RiskCheck, RiskPolicy};
 use super::Nuts;
 use crate::amount::Amount;
 use crate::cdk_database;
+use crate::ensure_cdk;
 use crate::mint::Mint;
 #[cfg(feature = "auth")]
 use crate::nuts::ProtectedEndpoint;
 use crate::nuts::{
     ContactInfo, CurrencyUnit, MeltMethodSettings, MintInfo, MintMethodSettings, MintVersion,
-    MppMethodSettings, PaymentMethod,
+    MppMethodSettings, PaymentMethod, SecretKey,
 };
 use crate::types::PaymentProcessorKey;
 
@@ -36,6 +39,11 @@ pub struct MintBuilder {
     payment_processors: HashMap<PaymentProcessorKey, DynMintPayment>,
     supported_units: HashMap<CurrencyUnit, (u64, u8)>,
     custom_paths: HashMap<CurrencyUnit, DerivationPath>,
+    info_signing_key: Option<SecretKey>,
+    leader_election: Option<(DynMintLeaderElection, Duration)>,
+    risk_check: Option<RiskCheck>,
+    voucher_units: Vec<CurrencyUnit>,
+    notification_replay: Option<(usize, Duration)>,
 }
 
 impl MintBuilder {
@@ -61,6 +69,11 @@ impl MintBuilder {
             payment_processors: HashMap::new(),
             supported_units: HashMap::new(),
             custom_paths: HashMap::new(),
+            info_signing_key: None,
+            leader_election: None,
+            risk_check: None,
+            voucher_units: Vec::new(),
+            notification_replay: None,
         }
     }
 
@@ -200,6 +213,60 @@ impl MintBuilder {
         self
     }
 
+    /// Set the identity key used to sign the mint info
+    ///
+    /// Sets the mint info pubkey to the public key of `secret_key`, which the
+    /// operator should publish out of band so wallets can pin it.
+    pub fn with_info_signing_key(mut self, secret_key: SecretKey) -> Self {
+        self.mint_info.pubkey = Some(secret_key.public_key());
+        self.info_signing_key = Some(secret_key);
+
+        self
+    }
+
+    /// Set the leader election used when several instances serve the mint
+    ///
+    /// Leadership is checked every `check_interval`, and an instance that becomes
+    /// the leader recovers the melts left pending by the previous leader.
+    pub fn with_leader_election(
+        mut self,
+        leader_election: DynMintLeaderElection,
+        check_interval: Duration,
+    ) -> Self {
+        self.leader_election = Some((leader_election, check_interval));
+
+        self
+    }
+
+    /// Set the risk policy consulted before mint and melt quotes are stored
+    ///
+    /// A policy that fails or does not answer within `timeout` accepts the quote
+    /// if `fail_open` is set and rejects it otherwise.
+    pub fn with_risk_policy(
+        mut self,
+        policy: Arc<dyn RiskPolicy>,
+        timeout: Duration,
+        fail_open: bool,
+    ) -> Self {
+        self.risk_check = Some(RiskCheck {
+            policy,
+            timeout,
+            fail_open,
+        });
+
+        self
+    }
+
+    /// Keep at most `max_events` NUT-17 notifications of the last `max_age`
+    ///
+    /// Subscriptions resumed with `since` after a reconnect receive the kept
+    /// notifications they missed. No notifications are kept unless this is set.
+    pub fn with_notification_replay(mut self, max_events: usize, max_age: Duration) -> Self {
+        self.notification_replay = Some((max_events, max_age));
+
+        self
+    }
+
     /// Support websockets
     pub fn with_supported_websockets(mut self, supported_method: SupportedMethods) -> Self {
         let mut supported_settings = self.mint_info.nuts.nut17.supported.clone();
@@ -251,7 +318,10 @@ impl MintBuilder {
 
         let settings: Bolt11Settings = settings.try_into()?;
 
-        if settings.mpp {
+        // Keysend only pays out, there is nothing to mint and no invoice to split
+        let keysend = method == PaymentMethod::keysend();
+
+        if settings.mpp && !keysend {
             let mpp_settings = MppMethodSettings {
                 method: method.clone(),
                 unit: unit.clone(),
@@ -274,15 +344,17 @@ impl MintBuilder {
             }),
         };
 
-        self.mint_info.nuts.nut04.methods.push(mint_method_settings);
-        self.mint_info.nuts.nut04.disabled = false;
+        if !keysend {
+            self.mint_info.nuts.nut04.methods.push(mint_method_settings);
+            self.mint_info.nuts.nut04.disabled = false;
+        }
 
         let melt_method_settings = MeltMethodSettings {
             method,
             unit,
             min_amount: Some(limits.melt_min),
             max_amount: Some(limits.melt_max),
-            options: Some(MeltMethodOptions::Bolt11 {
+            options: (!keysend).then_some(MeltMethodOptions::Bolt11 {
                 amountless: settings.amountless,
             }),
         };
@@ -297,9 +369,29 @@ impl MintBuilder {
         self.payment_processors.insert(key, payment_processor);
         Ok(())
     }
+
+    /// Add a unit without payment backend that the operator issues vouchers in
+    ///
+    /// The unit gets a keyset but no mint or melt method, its ecash is issued
+    /// with [`Mint::issue_vouchers`] and redeemed with [`Mint::redeem_vouchers`].
+    pub fn add_voucher_unit(&mut self, unit: CurrencyUnit) -> Result<(), Error> {
+        ensure_cdk!(
+            !self.payment_processors.keys().any(|key| key.unit == unit),
+            Error::UnsupportedUnit
+        );
+
+        self.supported_units.entry(unit.clone()).or_insert((0, 32));
+
+        if !self.voucher_units.contains(&unit) {
+            self.voucher_units.push(unit);
+        }
+
+        Ok(())
+    }
+
     /// Sets the input fee ppk for a given unit
     ///
-    /// The unit **MUST** already have been added with a ln backend
+    /// The unit **MUST** already have been added with a ln backend or as a voucher unit
     pub fn set_unit_fee(&mut self, unit: &CurrencyUnit, input_fee_ppk: u64) -> Result<(), Error> {
         let (input_fee, _max_order) = self
             .supported_units
@@ -318,22 +410,38 @@ impl MintBuilder {
     ) -> Result<Mint, Error> {
         #[cfg(feature = "auth")]
         if let Some(auth_localstore) = self.auth_localstore {
-            return Mint::new_with_auth(
+            let mut mint = Mint::new_with_auth(
                 self.mint_info,
                 signatory,
                 self.localstore,
                 auth_localstore,
                 self.payment_processors,
             )
-            .await;
+            .await?;
+            mint.set_info_signing_key(self.info_signing_key);
+            mint.leader_election = self.leader_election;
+            mint.risk_check = self.risk_check;
+            mint.voucher_units = self.voucher_units;
+            if let Some((max_events, max_age)) = self.notification_replay {
+                mint.pubsub_manager.set_replay_buffer(max_events, max_age);
+            }
+            return Ok(mint);
         }
-        Mint::new(
+        let mut mint = Mint::new(
             self.mint_info,
             signatory,
             self.localstore,
             self.payment_processors,
         )
-        .await
+        .await?;
+        mint.set_info_signing_key(self.info_signing_key);
+        mint.leader_election = self.leader_election;
+        mint.risk_check = self.risk_check;
+        mint.voucher_units = self.voucher_units;
+        if let Some((max_events, max_age)) = self.notification_replay {
+            mint.pubsub_manager.set_replay_buffer(max_events, max_age);
+        }
+        Ok(mint)
     }
 
     /// Build the mint with the provided keystore and seed
@@ -11,7 +11,15 @@ impl Mint {
         &self,
         check_state: &CheckStateRequest,
     ) -> Result<CheckStateResponse, Error> {
-        let states = self.localstore.get_proofs_states(&check_state.ys).await?;
+        // A proof stays spent, so spent states of a lagging replica are current. Any
+        // other state is read from the primary, like the witnesses below
+        let mut states = self
+            .localstore
+            .get_proofs_states_from_replica(&check_state.ys)
+            .await?;
+        if states.iter().any(|state| *state != Some(State::Spent)) {
+            states = self.localstore.get_proofs_states(&check_state.ys).await?;
+        }
         assert_eq!(check_state.ys.len(), states.len());
 
         let proof_states_futures =
@@ -0,0 +1,501 @@
+//! Melt fee reserve strategies
+//!
+//! The fee reserve of a melt quote is whatever the payment backend quotes, for
+//! the built in backends a percent of the amount with a minimum.
+//! [`FeeReserveMintPayment`] wraps a backend and replaces the quoted reserve with
+//! the one of a [`FeeReserveStrategy`]. With [`FeeReserveFeedback`] the fees
+//! actually paid are observed and reserves are lowered towards them, so less
+//! change has to be returned for every melt. Observed fees are kept in memory
+//! and collected again after a restart.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use cdk_common::payment::{
+    BackendInfo, CreateIncomingPaymentResponse, DynMintPayment, Error, Event,
+    IncomingPaymentOptions, MakePaymentResponse, MintPayment, OutgoingPaymentOptions,
+    PaymentIdentifier, PaymentQuoteResponse, WaitPaymentResponse,
+};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::amount::to_unit;
+use crate::nuts::{CurrencyUnit, MeltQuoteState};
+use crate::Amount;
+
+/// Parts per million, the precision of observed fee rates
+const PPM: u128 = 1_000_000;
+
+/// How the fee reserve of a melt quote is set
+///
+/// Amounts are in the unit of the quote.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum FeeReserveStrategy {
+    /// Reserve quoted by the payment backend
+    #[default]
+    Backend,
+    /// Same reserve for every amount
+    Flat {
+        /// Reserve
+        fee: Amount,
+    },
+    /// Percent of the amount with a minimum
+    Percent {
+        /// Share of the amount, e.g. 0.02 for 2%
+        percent: f32,
+        /// Smallest reserve
+        min: Amount,
+    },
+    /// Routing fee found by probing a route, with headroom
+    ///
+    /// Backends that cannot probe keep the reserve they quote.
+    RouteProbe {
+        /// Share added on top of the probed fee, e.g. 0.5 for 50%
+        headroom: f32,
+        /// Smallest reserve
+        min: Amount,
+    },
+    /// Percent and minimum depending on the amount
+    Tiered {
+        /// Tiers by increasing [`FeeReserveTier::max_amount`]
+        tiers: Vec<FeeReserveTier>,
+    },
+}
+
+/// Tier of [`FeeReserveStrategy::Tiered`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeReserveTier {
+    /// Largest amount of the tier, `None` for every amount
+    pub max_amount: Option<Amount>,
+    /// Share of the amount, e.g. 0.02 for 2%
+    pub percent: f32,
+    /// Smallest reserve
+    pub min: Amount,
+}
+
+/// Percent of `amount`, at least `min`
+fn percent_reserve(amount: Amount, percent: f32, min: Amount) -> Amount {
+    let relative = (percent * u64::from(amount) as f32) as u64;
+
+    Amount::from(relative.max(u64::from(min)))
+}
+
+impl FeeReserveStrategy {
+    /// Reserve for a payment of `amount`
+    ///
+    /// `quoted` is the reserve quoted by the backend and `probed` the routing fee
+    /// it found by probing, if any.
+    pub fn reserve(&self, amount: Amount, quoted: Amount, probed: Option<Amount>) -> Amount {
+        match self {
+            FeeReserveStrategy::Backend => quoted,
+            FeeReserveStrategy::Flat { fee } => *fee,
+            FeeReserveStrategy::Percent { percent, min } => percent_reserve(amount, *percent, *min),
+            FeeReserveStrategy::RouteProbe { headroom, min } => match probed {
+                Some(probed) => {
+                    let fee = (u64::from(probed) as f64 * (1.0 + f64::from(*headroom))).ceil();
+
+                    Amount::from(fee as u64).max(*min)
+                }
+                None => quoted,
+            },
+            FeeReserveStrategy::Tiered { tiers } => tiers
+                .iter()
+                .find(|tier| tier.max_amount.is_none_or(|max| amount <= max))
+                .map(|tier| percent_reserve(amount, tier.percent, tier.min))
+                .unwrap_or(quoted),
+        }
+    }
+}
+
+/// Tuning of fee reserves from the fees actually paid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeReserveFeedback {
+    /// Payments whose fees are kept
+    pub window: usize,
+    /// Payments observed before reserves are tuned
+    pub min_samples: usize,
+    /// Share added on top of the highest observed fee rate, e.g. 0.5 for 50%
+    pub headroom: f32,
+}
+
+impl Default for FeeReserveFeedback {
+    fn default() -> Self {
+        Self {
+            window: 100,
+            min_samples: 20,
+            headroom: 0.5,
+        }
+    }
+}
+
+/// Fee paid for a payment, in msat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ObservedFee {
+    amount_msat: u64,
+    fee_msat: u64,
+}
+
+impl FeeReserveFeedback {
+    /// Reserve tuned to the observed fees, never above `reserve_msat`
+    ///
+    /// The reserve covers the highest observed fee rate with headroom, and at
+    /// least the highest fee paid for a payment of at most the amount, since base
+    /// fees do not scale with the amount.
+    fn tune(&self, observed: &VecDeque<ObservedFee>, amount_msat: u64, reserve_msat: u64) -> u64 {
+        if observed.len() < self.min_samples.max(1) {
+            return reserve_msat;
+        }
+
+        let max_rate_ppm = observed
+            .iter()
+            .filter(|fee| fee.amount_msat > 0)
+            .map(|fee| u128::from(fee.fee_msat) * PPM / u128::from(fee.amount_msat))
+            .max()
+            .unwrap_or_default();
+
+        let proportional = (u128::from(amount_msat) * max_rate_ppm).div_ceil(PPM);
+        let proportional = (proportional as f64 * (1.0 + f64::from(self.headroom))).ceil() as u64;
+
+        let floor = observed
+            .iter()
+            .filter(|fee| fee.amount_msat <= amount_msat)
+            .map(|fee| fee.fee_msat)
+            .max()
+            .unwrap_or_default();
+
+        reserve_msat.min(proportional.max(floor))
+    }
+}
+
+/// Amount paid to the payee without fees, in `unit`
+fn payment_amount(options: &OutgoingPaymentOptions, unit: &CurrencyUnit) -> Option<Amount> {
+    let amount_msat = match options {
+        OutgoingPaymentOptions::Bolt11(options) => match options.melt_options {
+            Some(melt_options) => melt_options.amount_msat(),
+            None => options.bolt11.amount_milli_satoshis()?.into(),
+        },
+        OutgoingPaymentOptions::Bolt12(options) => options.melt_options?.amount_msat(),
+        OutgoingPaymentOptions::Keysend(options) => options.amount_msat,
+    };
+
+    to_unit(amount_msat, &CurrencyUnit::Msat, unit).ok()
+}
+
+/// Payment backend wrapper setting melt fee reserves with a [`FeeReserveStrategy`]
+pub struct FeeReserveMintPayment {
+    inner: DynMintPayment,
+    strategy: FeeReserveStrategy,
+    feedback: Option<FeeReserveFeedback>,
+    observed: Mutex<VecDeque<ObservedFee>>,
+}
+
+impl FeeReserveMintPayment {
+    /// Wrap `inner`, tuning reserves to the paid fees when `feedback` is set
+    pub fn new(
+        inner: DynMintPayment,
+        strategy: FeeReserveStrategy,
+        feedback: Option<FeeReserveFeedback>,
+    ) -> Self {
+        tracing::info!(
+            "Melt fee reserve strategy {:?}, feedback {:?}",
+            strategy,
+            feedback
+        );
+
+        Self {
+            inner,
+            strategy,
+            feedback,
+            observed: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Reserve of a quote tuned to the observed fees
+    async fn tuned_reserve(&self, amount: Amount, reserve: Amount, unit: &CurrencyUnit) -> Amount {
+        let Some(feedback) = self.feedback.as_ref() else {
+            return reserve;
+        };
+
+        // Fees are observed in msat, other units are not tuned
+        let (Ok(amount_msat), Ok(reserve_msat)) = (
+            to_unit(amount, unit, &CurrencyUnit::Msat),
+            to_unit(reserve, unit, &CurrencyUnit::Msat),
+        ) else {
+            return reserve;
+        };
+
+        let tuned_msat = feedback.tune(
+            &*self.observed.lock().await,
+            amount_msat.into(),
+            reserve_msat.into(),
+        );
+
+        // Rounded up so the reserve does not fall below the tuned one
+        let tuned_msat = match unit {
+            CurrencyUnit::Msat => tuned_msat,
+            _ => tuned_msat.div_ceil(1000) * 1000,
+        };
+
+        to_unit(tuned_msat, &CurrencyUnit::Msat, unit).unwrap_or(reserve)
+    }
+
+    /// Record the fee of a paid payment
+    async fn observe(&self, options: &OutgoingPaymentOptions, response: &MakePaymentResponse) {
+        let Some(feedback) = self.feedback.as_ref() else {
+            return;
+        };
+
+        if response.status != MeltQuoteState::Paid {
+            return;
+        }
+
+        let Some(amount) = payment_amount(options, &response.unit) else {
+            return;
+        };
+        let Some(fee) = response.total_spent.checked_sub(amount) else {
+            return;
+        };
+
+        let (Ok(amount_msat), Ok(fee_msat)) = (
+            to_unit(amount, &response.unit, &CurrencyUnit::Msat),
+            to_unit(fee, &response.unit, &CurrencyUnit::Msat),
+        ) else {
+            return;
+        };
+
+        let mut observed = self.observed.lock().await;
+        observed.push_back(ObservedFee {
+            amount_msat: amount_msat.into(),
+            fee_msat: fee_msat.into(),
+        });
+        while observed.len() > feedback.window.max(1) {
+            observed.pop_front();
+        }
+
+        tracing::debug!(
+            "Observed melt fee of {} {} for {} {}",
+            fee,
+            response.unit,
+            amount,
+            response.unit
+        );
+    }
+}
+
+#[async_trait]
+impl MintPayment for FeeReserveMintPayment {
+    type Err = Error;
+
+    async fn start(&self) -> Result<(), Self::Err> {
+        self.inner.start().await
+    }
+
+    async fn stop(&self) -> Result<(), Self::Err> {
+        self.inner.stop().await
+    }
+
+    async fn get_settings(&self) -> Result<serde_json::Value, Self::Err> {
+        self.inner.get_settings().await
+    }
+
+    async fn create_incoming_payment_request(
+        &self,
+        unit: &CurrencyUnit,
+        options: IncomingPaymentOptions,
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        self.inner
+            .create_incoming_payment_request(unit, options)
+            .await
+    }
+
+    async fn get_payment_quote(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<PaymentQuoteResponse, Self::Err> {
+        let probed = match self.strategy {
+            FeeReserveStrategy::RouteProbe { .. } => self
+                .inner
+                .probe_payment(unit, options.clone())
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::warn!("Could not probe routing fee: {}", err);
+                    None
+                }),
+            _ => None,
+        };
+
+        let mut quote = self.inner.get_payment_quote(unit, options).await?;
+
+        let reserve = self.strategy.reserve(quote.amount, quote.fee, probed);
+        let reserve = self.tuned_reserve(quote.amount, reserve, &quote.unit).await;
+
+        tracing::debug!(
+            "Fee reserve {} {} for {} {}, backend quoted {}",
+            reserve,
+            quote.unit,
+            quote.amount,
+            quote.unit,
+            quote.fee
+        );
+
+        quote.fee = reserve;
+
+        Ok(quote)
+    }
+
+    async fn make_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        let response = self.inner.make_payment(unit, options.clone()).await?;
+
+        self.observe(&options, &response).await;
+
+        Ok(response)
+    }
+
+    async fn wait_payment_event(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+        self.inner.wait_payment_event().await
+    }
+
+    fn is_wait_invoice_active(&self) -> bool {
+        self.inner.is_wait_invoice_active()
+    }
+
+    fn cancel_wait_invoice(&self) {
+        self.inner.cancel_wait_invoice()
+    }
+
+    async fn check_incoming_payment_status(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+        self.inner
+            .check_incoming_payment_status(payment_identifier)
+            .await
+    }
+
+    async fn check_outgoing_payment(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.inner.check_outgoing_payment(payment_identifier).await
+    }
+
+    async fn settle_internally(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<Option<MakePaymentResponse>, Self::Err> {
+        self.inner.settle_internally(unit, options).await
+    }
+
+    async fn probe_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<Option<Amount>, Self::Err> {
+        self.inner.probe_payment(unit, options).await
+    }
+
+    async fn backend_info(&self) -> Result<Option<BackendInfo>, Self::Err> {
+        self.inner.backend_info().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_reserve_strategies() {
+        let amount = Amount::from(10_000);
+        let quoted = Amount::from(200);
+
+        assert_eq!(
+            FeeReserveStrategy::Backend.reserve(amount, quoted, None),
+            quoted
+        );
+        assert_eq!(
+            FeeReserveStrategy::Flat { fee: 5.into() }.reserve(amount, quoted, None),
+            Amount::from(5)
+        );
+        assert_eq!(
+            FeeReserveStrategy::Percent {
+                percent: 0.01,
+                min: 2.into()
+            }
+            .reserve(amount, quoted, None),
+            Amount::from(100)
+        );
+
+        let probe = FeeReserveStrategy::RouteProbe {
+            headroom: 0.5,
+            min: 2.into(),
+        };
+        assert_eq!(
+            probe.reserve(amount, quoted, Some(10.into())),
+            Amount::from(15)
+        );
+        assert_eq!(
+            probe.reserve(amount, quoted, Some(0.into())),
+            Amount::from(2)
+        );
+        assert_eq!(probe.reserve(amount, quoted, None), quoted);
+
+        let tiered = FeeReserveStrategy::Tiered {
+            tiers: vec![
+                FeeReserveTier {
+                    max_amount: Some(1_000.into()),
+                    percent: 0.02,
+                    min: 4.into(),
+                },
+                FeeReserveTier {
+                    max_amount: None,
+                    percent: 0.005,
+                    min: 20.into(),
+                },
+            ],
+        };
+        assert_eq!(tiered.reserve(100.into(), quoted, None), Amount::from(4));
+        assert_eq!(tiered.reserve(1_000.into(), quoted, None), Amount::from(20));
+        assert_eq!(tiered.reserve(amount, quoted, None), Amount::from(50));
+    }
+
+    #[test]
+    fn test_fee_reserve_feedback() {
+        let feedback = FeeReserveFeedback {
+            window: 10,
+            min_samples: 2,
+            headroom: 0.5,
+        };
+        let mut observed = VecDeque::new();
+
+        observed.push_back(ObservedFee {
+            amount_msat: 1_000_000,
+            fee_msat: 1_000,
+        });
+        // Too few payments observed
+        assert_eq!(feedback.tune(&observed, 1_000_000, 20_000), 20_000);
+
+        observed.push_back(ObservedFee {
+            amount_msat: 10_000,
+            fee_msat: 1_000,
+        });
+
+        // Highest rate is 10%, the reserve stays below the configured one
+        assert_eq!(feedback.tune(&observed, 100_000, 20_000), 15_000);
+        // Base fee of the small payment is a floor
+        assert_eq!(feedback.tune(&observed, 5_000, 20_000), 750);
+        assert_eq!(feedback.tune(&observed, 10_000, 20_000), 1_500);
+        // Never above the configured reserve
+        assert_eq!(feedback.tune(&observed, 1_000_000, 20_000), 20_000);
+    }
+}
@@ -14,7 +14,8 @@ use cdk_common::{
 use cdk_prometheus::METRICS;
 use tracing::instrument;
 
-use crate::mint::Verification;
+use crate::mint::ledger::{record_ledger_transaction, LedgerTransaction};
+use crate::mint::{QuoteOperation, RiskAssessment, Verification};
 use crate::Mint;
 
 #[cfg(feature = "auth")]
@@ -147,6 +148,7 @@ impl From<MintQuoteResponse> for MintQuoteBolt11Response<String> {
                 pubkey: bolt11_response.pubkey,
                 amount: bolt11_response.amount,
                 unit: bolt11_response.unit,
+                timestamps: bolt11_response.timestamps,
             },
             _ => panic!("Expected Bolt11 response"),
         }
@@ -244,6 +246,19 @@ impl Mint {
 
             let ln = self.get_payment_processor(unit.clone(), payment_method.clone())?;
 
+            // Rejected quotes never get a payment request from the backend
+            let quote_id = QuoteId::new_uuid();
+            let risk_record = self
+                .assess_quote_risk(RiskAssessment {
+                    quote_id: quote_id.clone(),
+                    operation: QuoteOperation::Mint,
+                    unit: unit.clone(),
+                    payment_method: payment_method.clone(),
+                    amount,
+                    request: None,
+                })
+                .await?;
+
             let payment_options = match mint_quote_request {
                 MintQuoteRequest::Bolt11(bolt11_request) => {
                     let mint_ttl = self.quote_ttl().await?.mint_ttl;
@@ -290,7 +305,7 @@ impl Mint {
                 })?;
 
             let quote = MintQuote::new(
-                None,
+                Some(quote_id),
                 create_invoice_response.request.to_string(),
                 unit.clone(),
                 amount,
@@ -316,6 +331,9 @@ impl Mint {
 
             let mut tx = self.localstore.begin_transaction().await?;
             tx.add_mint_quote(quote.clone()).await?;
+            if let Some(risk_record) = risk_record {
+                risk_record.write(&mut tx).await?;
+            }
             tx.commit().await?;
 
             match payment_method {
@@ -332,7 +350,7 @@ impl Mint {
                 PaymentMethod::Custom(_) => {}
             }
 
-            quote.try_into()
+            self.mint_quote_response(quote)
         }
         .await;
 
@@ -514,6 +532,28 @@ impl Mint {
         result
     }
 
+    /// Response for a mint quote with its timestamps signed
+    fn mint_quote_response(&self, quote: MintQuote) -> Result<MintQuoteResponse, Error> {
+        let mut response: MintQuoteResponse = quote.try_into()?;
+
+        match &mut response {
+            MintQuoteResponse::Bolt11(response) => {
+                response.timestamps = response
+                    .timestamps
+                    .take()
+                    .map(|timestamps| self.signed_quote_timestamps(&response.quote, timestamps));
+            }
+            MintQuoteResponse::Bolt12(response) => {
+                response.timestamps = response
+                    .timestamps
+                    .take()
+                    .map(|timestamps| self.signed_quote_timestamps(&response.quote, timestamps));
+            }
+        }
+
+        Ok(response)
+    }
+
     /// Checks the status of a mint quote and updates it if necessary
     ///
     /// If the quote is unpaid, this will check if payment has been received.
@@ -540,7 +580,7 @@ impl Mint {
                 self.check_mint_quote_paid(&mut quote).await?;
             }
 
-            quote.try_into()
+            self.mint_quote_response(quote)
         }
         .await;
 
@@ -707,6 +747,9 @@ impl Mint {
             .increment_mint_quote_amount_issued(&mint_request.quote, amount_issued)
             .await?;
 
+        record_ledger_transaction(&mut tx, LedgerTransaction::issue(&unit, amount_issued)?)
+            .await?;
+
         tx.commit().await?;
 
         self.pubsub_manager
@@ -1,3 +1,5 @@
+use cdk_common::keyset_history::{KeysetHistory, KeysetHistoryEntry};
+use cdk_common::util::unix_time;
 use cdk_signatory::signatory::RotateKeyArguments;
 use tracing::instrument;
 
@@ -59,6 +61,37 @@ impl Mint {
         }
     }
 
+    /// History of every keyset, active and retired, with its keys and validity range
+    ///
+    /// Signed with the mint identity key if one is configured, so wallets can
+    /// cache it instead of fetching the keys of each keyset.
+    #[instrument(skip_all)]
+    pub fn keyset_history(&self) -> Result<KeysetHistory, Error> {
+        let keysets = self
+            .keysets
+            .load()
+            .iter()
+            .filter(|k| k.unit != CurrencyUnit::Auth)
+            .map(|k| KeysetHistoryEntry {
+                id: k.id,
+                unit: k.unit.clone(),
+                active: k.active,
+                input_fee_ppk: k.input_fee_ppk,
+                valid_from: k.valid_from,
+                final_expiry: k.final_expiry,
+                keys: k.keys.clone(),
+            })
+            .collect();
+
+        let mut history = KeysetHistory::new(unix_time(), keysets);
+
+        if let Some(secret_key) = self.info_signing_key.as_ref() {
+            history.sign(secret_key)?;
+        }
+
+        Ok(history)
+    }
+
     /// Get keysets
     #[instrument(skip(self))]
     pub fn keyset(&self, id: &Id) -> Option<KeySet> {
@@ -77,6 +110,23 @@ impl Mint {
         unit: CurrencyUnit,
         max_order: u8,
         input_fee_ppk: u64,
+    ) -> Result<MintKeySetInfo, Error> {
+        self.rotate_keyset_with_expiry(unit, max_order, input_fee_ppk, None)
+            .await
+    }
+
+    /// Add current keyset to inactive keysets
+    /// Generate new keyset expiring at `final_expiry`
+    ///
+    /// A `final_expiry` unix timestamp is advertised to wallets in the keyset info, so
+    /// they can move their proofs to a newer keyset before it expires.
+    #[instrument(skip(self))]
+    pub async fn rotate_keyset_with_expiry(
+        &self,
+        unit: CurrencyUnit,
+        max_order: u8,
+        input_fee_ppk: u64,
+        final_expiry: Option<u64>,
     ) -> Result<MintKeySetInfo, Error> {
         let result = self
             .signatory
@@ -84,6 +134,7 @@ impl Mint {
                 unit,
                 amounts: (0..max_order).map(|n| 2u64.pow(n.into())).collect(),
                 input_fee_ppk,
+                final_expiry,
             })
             .await?;
 
@@ -0,0 +1,235 @@
+//! Leader election between mint instances
+//!
+//! Instances serving the same mint share the database, but melts left pending
+//! by an instance that went away must only be recovered by one instance, and
+//! only once no other instance can still be paying them. Every instance records
+//! a heartbeat on each election check and its id on the melts it pays. The
+//! leader recovers the pending melts of instances whose heartbeat stopped when
+//! it takes over and whenever an instance goes away, and leaves the melts of
+//! live instances to them.
+
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cdk_common::database::DynMintLeaderElection;
+use tokio::sync::Notify;
+
+use super::melt::melt_attempt;
+use super::{Mint, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::nuts::MeltQuoteState;
+use crate::util::unix_time;
+use crate::Error;
+
+const CDK_MINT_INSTANCE_HEARTBEAT_SECONDARY_NAMESPACE: &str = "instance_heartbeat";
+
+/// Missed election checks after which an instance is considered gone
+const INSTANCE_TIMEOUT_CHECKS: u64 = 3;
+/// Shortest time without heartbeat after which an instance is considered gone
+const MIN_INSTANCE_TIMEOUT_SECS: u64 = 10;
+
+/// Heartbeats of the instances serving the mint
+struct InstanceHeartbeats {
+    /// Instances with a recent heartbeat
+    live: HashSet<String>,
+    /// Instances whose heartbeat stopped
+    gone: Vec<String>,
+}
+
+impl Mint {
+    /// Keep trying to become the leader until shutdown, then give up leadership
+    pub(super) async fn run_leader_election(
+        &self,
+        leader_election: DynMintLeaderElection,
+        interval: Duration,
+        shutdown: Arc<Notify>,
+    ) {
+        let shutdown = shutdown.notified();
+        tokio::pin!(shutdown);
+        // Register for the shutdown signal before the first check so it is not missed
+        shutdown.as_mut().enable();
+
+        let timeout = (interval.as_secs() * INSTANCE_TIMEOUT_CHECKS).max(MIN_INSTANCE_TIMEOUT_SECS);
+
+        loop {
+            if let Err(err) = self.write_instance_heartbeat(unix_time()).await {
+                tracing::error!("Could not record instance heartbeat: {}", err);
+            }
+
+            let was_leader = self.is_leader.load(Ordering::SeqCst);
+
+            match leader_election.try_acquire_leadership().await {
+                Ok(true) if !was_leader => {
+                    tracing::info!("Mint instance became the leader");
+
+                    // Only hold the leader flag once the melts of gone instances are
+                    // recovered, so a failed recovery is retried on the next check
+                    match self.recover_orphaned_melt_quotes(timeout, true).await {
+                        Ok(()) => self.is_leader.store(true, Ordering::SeqCst),
+                        Err(err) => {
+                            tracing::error!("Could not recover pending melt quotes: {}", err)
+                        }
+                    }
+                }
+                Ok(true) => {
+                    if let Err(err) = self.recover_orphaned_melt_quotes(timeout, false).await {
+                        tracing::error!("Could not recover pending melt quotes: {}", err);
+                    }
+                }
+                Ok(false) => {
+                    if was_leader {
+                        tracing::warn!("Mint instance lost leadership");
+                    }
+                    self.is_leader.store(false, Ordering::SeqCst);
+                }
+                Err(err) => {
+                    tracing::error!("Could not check leadership: {}", err);
+                    self.is_leader.store(false, Ordering::SeqCst);
+                }
+            }
+
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = tokio::time::sleep(interval) => (),
+            }
+        }
+
+        self.is_leader.store(false, Ordering::SeqCst);
+
+        match leader_election.release_leadership().await {
+            Ok(()) => tracing::info!("Mint instance released leadership"),
+            Err(err) => tracing::error!("Could not release leadership: {}", err),
+        }
+
+        // Mark the heartbeat as stopped, so the leader recovers the melts this
+        // instance left without waiting for the timeout
+        if let Err(err) = self.write_instance_heartbeat(0).await {
+            tracing::error!("Could not record instance heartbeat: {}", err);
+        }
+    }
+
+    /// Record the heartbeat of this instance at unix time `time`
+    async fn write_instance_heartbeat(&self, time: u64) -> Result<(), Error> {
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_INSTANCE_HEARTBEAT_SECONDARY_NAMESPACE,
+            &self.instance_id,
+            time.to_string().as_bytes(),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Remove the heartbeats of `instance_ids`
+    async fn remove_instance_heartbeats(&self, instance_ids: &[String]) -> Result<(), Error> {
+        let mut tx = self.localstore.begin_transaction().await?;
+        for instance_id in instance_ids {
+            tx.kv_remove(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_INSTANCE_HEARTBEAT_SECONDARY_NAMESPACE,
+                instance_id,
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Instances with a heartbeat in the last `timeout` seconds, and the ones without
+    async fn instance_heartbeats(&self, timeout: u64) -> Result<InstanceHeartbeats, Error> {
+        let now = unix_time();
+        let mut heartbeats = InstanceHeartbeats {
+            live: HashSet::new(),
+            gone: Vec::new(),
+        };
+
+        let instance_ids = self
+            .localstore
+            .kv_list(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_INSTANCE_HEARTBEAT_SECONDARY_NAMESPACE,
+            )
+            .await?;
+
+        for instance_id in instance_ids {
+            let heartbeat = self
+                .localstore
+                .kv_read(
+                    CDK_MINT_PRIMARY_NAMESPACE,
+                    CDK_MINT_INSTANCE_HEARTBEAT_SECONDARY_NAMESPACE,
+                    &instance_id,
+                )
+                .await?
+                .and_then(|heartbeat| String::from_utf8(heartbeat).ok()?.parse::<u64>().ok());
+
+            match heartbeat {
+                Some(heartbeat) if heartbeat + timeout >= now => {
+                    heartbeats.live.insert(instance_id);
+                }
+                _ => heartbeats.gone.push(instance_id),
+            }
+        }
+
+        Ok(heartbeats)
+    }
+
+    /// Recover the pending melts of instances that went away
+    ///
+    /// Melts attempted by an instance with a recent heartbeat are left to it.
+    /// On takeover every pending melt is checked, afterwards only once the
+    /// heartbeat of an instance stops.
+    async fn recover_orphaned_melt_quotes(
+        &self,
+        timeout: u64,
+        takeover: bool,
+    ) -> Result<(), Error> {
+        let heartbeats = self.instance_heartbeats(timeout).await?;
+
+        if !takeover && heartbeats.gone.is_empty() {
+            return Ok(());
+        }
+
+        let pending_quotes: Vec<_> = self
+            .localstore
+            .get_melt_quotes()
+            .await?
+            .into_iter()
+            .filter(|q| q.state == MeltQuoteState::Pending || q.state == MeltQuoteState::Unknown)
+            .collect();
+
+        let mut tx = self.localstore.begin_transaction().await?;
+
+        for pending_quote in pending_quotes {
+            if let Some(attempt) = melt_attempt(&mut tx, &pending_quote.id).await? {
+                if heartbeats.live.contains(&attempt.instance_id) {
+                    tracing::debug!(
+                        "Melt quote {} is being paid by live instance {}",
+                        pending_quote.id,
+                        attempt.instance_id
+                    );
+                    continue;
+                }
+            }
+
+            tracing::info!(
+                "Recovering melt quote {} left pending by a gone instance",
+                pending_quote.id
+            );
+            self.reconcile_pending_melt_quote(&mut tx, pending_quote)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        if !heartbeats.gone.is_empty() {
+            self.remove_instance_heartbeats(&heartbeats.gone).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,655 @@
+//! Double-entry accounting ledger
+//!
+//! Issuance, swaps and melts are recorded as balanced debits and credits on the
+//! accounts of their unit. Each transaction is stored in the key-value store in
+//! the same database transaction as the operation it records, so the ledger is
+//! shared by every instance and survives restarts. The leader folds stored
+//! transactions into a checkpoint of running account totals, so reading the
+//! ledger only goes through the transactions since. The ecash the ledger counts
+//! as outstanding is checked against the signatures issued and proofs spent in
+//! the database: a difference means an operation changed the mint's liabilities
+//! without being accounted for.
+
+use std::collections::{BTreeMap, HashSet};
+
+use bitcoin::hashes::{sha256, Hash};
+use cdk_common::database::{self, MintTransaction};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::{Mint, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::nuts::CurrencyUnit;
+use crate::util::{hex, unix_time};
+use crate::{Amount, Error};
+
+const CDK_MINT_LEDGER_SECONDARY_NAMESPACE: &str = "ledger";
+const CDK_MINT_LEDGER_CHECKPOINT_SECONDARY_NAMESPACE: &str = "ledger_checkpoint";
+/// Key of the account totals of the transactions folded into the checkpoint
+const LEDGER_CHECKPOINT_KEY: &str = "totals";
+/// Key prefix of opening transactions
+const OPENING_KEY_PREFIX: &str = "opening-";
+/// Times the ledger is read again when operations commit while it is compared with the database
+const CONSISTENT_READ_ATTEMPTS: usize = 5;
+
+/// Account of the ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerAccount {
+    /// Ecash held by users, a liability of the mint
+    Ecash,
+    /// Funds received from and paid through the payment backend
+    Backend,
+    /// Input fees and melt fee reserve the wallet did not get back as change, less
+    /// payment fees the mint paid itself
+    Fees,
+}
+
+impl std::fmt::Display for LedgerAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LedgerAccount::Ecash => write!(f, "ecash"),
+            LedgerAccount::Backend => write!(f, "backend"),
+            LedgerAccount::Fees => write!(f, "fees"),
+        }
+    }
+}
+
+/// Side of a ledger entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Side {
+    Debit,
+    Credit,
+}
+
+/// Debits and credits of an account
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountTotals {
+    /// Sum of the debits
+    pub debits: Amount,
+    /// Sum of the credits
+    pub credits: Amount,
+}
+
+/// Balances of the ledger for a unit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LedgerBalance {
+    /// Unit of the accounts
+    pub unit: CurrencyUnit,
+    /// Totals of every account
+    pub accounts: BTreeMap<LedgerAccount, AccountTotals>,
+    /// Ecash outstanding, credits minus debits of [`LedgerAccount::Ecash`]
+    pub outstanding: Option<Amount>,
+    /// Ecash outstanding in the database, signatures issued minus proofs spent
+    pub database_outstanding: Option<Amount>,
+}
+
+impl LedgerBalance {
+    /// Whether the ledger accounts for the ecash outstanding in the database
+    pub fn is_consistent(&self) -> bool {
+        self.outstanding == self.database_outstanding
+    }
+}
+
+/// Entry of a ledger transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct LedgerEntry {
+    account: LedgerAccount,
+    side: Side,
+    amount: Amount,
+}
+
+/// Balanced transaction of the ledger
+///
+/// Fees are posted explicitly on [`LedgerAccount::Fees`], as income when the mint
+/// kept more than it gave out and as an expense otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LedgerTransaction {
+    unit: CurrencyUnit,
+    kind: String,
+    entries: Vec<LedgerEntry>,
+    time: u64,
+}
+
+impl LedgerTransaction {
+    fn new(
+        unit: &CurrencyUnit,
+        kind: &str,
+        entries: &[(LedgerAccount, Side, Amount)],
+    ) -> Result<Self, Error> {
+        let total = |side: Side| {
+            Amount::try_sum(
+                entries
+                    .iter()
+                    .filter(|(_, entry_side, _)| *entry_side == side)
+                    .map(|(_, _, amount)| *amount),
+            )
+        };
+        let debits = total(Side::Debit)?;
+        let credits = total(Side::Credit)?;
+
+        if debits != credits {
+            return Err(Error::LedgerUnbalanced(
+                kind.to_string(),
+                debits.into(),
+                credits.into(),
+            ));
+        }
+
+        Ok(Self {
+            unit: unit.clone(),
+            kind: kind.to_string(),
+            entries: entries
+                .iter()
+                .filter(|(_, _, amount)| *amount > Amount::ZERO)
+                .map(|(account, side, amount)| LedgerEntry {
+                    account: *account,
+                    side: *side,
+                    amount: *amount,
+                })
+                .collect(),
+            time: unix_time(),
+        })
+    }
+
+    /// Ecash that was outstanding before the ledger started
+    pub(crate) fn opening(unit: &CurrencyUnit, outstanding: Amount) -> Result<Self, Error> {
+        Self::new(
+            unit,
+            "opening",
+            &[
+                (LedgerAccount::Backend, Side::Debit, outstanding),
+                (LedgerAccount::Ecash, Side::Credit, outstanding),
+            ],
+        )
+    }
+
+    /// Ecash issued against a paid mint quote
+    pub(crate) fn issue(unit: &CurrencyUnit, amount: Amount) -> Result<Self, Error> {
+        Self::new(
+            unit,
+            "issue",
+            &[
+                (LedgerAccount::Backend, Side::Debit, amount),
+                (LedgerAccount::Ecash, Side::Credit, amount),
+            ],
+        )
+    }
+
+    /// Ecash swapped for new ecash less the input fee
+    pub(crate) fn swap(
+        unit: &CurrencyUnit,
+        inputs: Amount,
+        outputs: Amount,
+        fee: Amount,
+    ) -> Result<Self, Error> {
+        Self::new(
+            unit,
+            "swap",
+            &[
+                (LedgerAccount::Ecash, Side::Debit, inputs),
+                (LedgerAccount::Ecash, Side::Credit, outputs),
+                (LedgerAccount::Fees, Side::Credit, fee),
+            ],
+        )
+    }
+
+    /// Ecash redeemed for a payment of `paid`
+    ///
+    /// Inputs above the payment are fee income until returned as change, a payment
+    /// above the inputs is a fee the mint paid.
+    pub(crate) fn melt(unit: &CurrencyUnit, inputs: Amount, paid: Amount) -> Result<Self, Error> {
+        let (fee_side, fee) = match inputs.checked_sub(paid) {
+            Some(kept) => (Side::Credit, kept),
+            None => (Side::Debit, paid - inputs),
+        };
+
+        Self::new(
+            unit,
+            "melt",
+            &[
+                (LedgerAccount::Ecash, Side::Debit, inputs),
+                (LedgerAccount::Backend, Side::Credit, paid),
+                (LedgerAccount::Fees, fee_side, fee),
+            ],
+        )
+    }
+
+    /// Change of a melt returned as new ecash out of the fees the melt kept
+    pub(crate) fn melt_change(unit: &CurrencyUnit, change: Amount) -> Result<Self, Error> {
+        Self::new(
+            unit,
+            "melt_change",
+            &[
+                (LedgerAccount::Fees, Side::Debit, change),
+                (LedgerAccount::Ecash, Side::Credit, change),
+            ],
+        )
+    }
+}
+
+/// Store `transaction` in the ledger, as part of the operation of `tx`
+pub(crate) async fn record_ledger_transaction(
+    tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    transaction: LedgerTransaction,
+) -> Result<(), Error> {
+    let key = match transaction.kind.as_str() {
+        // Only one opening is kept per unit, when instances open the ledger together
+        "opening" => opening_key(&transaction.unit),
+        _ => format!("{}-{}", transaction.time, Uuid::new_v4().simple()),
+    };
+
+    tx.kv_write(
+        CDK_MINT_PRIMARY_NAMESPACE,
+        CDK_MINT_LEDGER_SECONDARY_NAMESPACE,
+        &key,
+        &serde_json::to_vec(&transaction)?,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Key of the opening transaction of a unit
+fn opening_key(unit: &CurrencyUnit) -> String {
+    let hash = sha256::Hash::hash(unit.to_string().as_bytes());
+    format!(
+        "{OPENING_KEY_PREFIX}{}",
+        hex::encode(&hash.to_byte_array()[..16])
+    )
+}
+
+/// Account totals of ledger transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct LedgerTotals {
+    accounts: BTreeMap<CurrencyUnit, BTreeMap<LedgerAccount, AccountTotals>>,
+}
+
+impl LedgerTotals {
+    /// Add the transactions stored since these totals were checkpointed, by key
+    ///
+    /// An instance that opened the ledger while another one's opening was already
+    /// folded into the checkpoint stores a second opening, which is skipped.
+    fn apply_stored(&mut self, stored: &[(String, LedgerTransaction)]) -> Result<(), Error> {
+        let checkpointed: HashSet<CurrencyUnit> = self.accounts.keys().cloned().collect();
+
+        for (key, transaction) in stored {
+            if key.starts_with(OPENING_KEY_PREFIX) && checkpointed.contains(&transaction.unit) {
+                continue;
+            }
+
+            self.apply(transaction)?;
+        }
+
+        Ok(())
+    }
+
+    fn apply(&mut self, transaction: &LedgerTransaction) -> Result<(), Error> {
+        let accounts = self.accounts.entry(transaction.unit.clone()).or_default();
+
+        for entry in &transaction.entries {
+            let totals = accounts.entry(entry.account).or_default();
+            let total = match entry.side {
+                Side::Debit => &mut totals.debits,
+                Side::Credit => &mut totals.credits,
+            };
+            *total = total
+                .checked_add(entry.amount)
+                .ok_or(Error::AmountOverflow)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// State of the ledger kept by an instance
+#[derive(Debug, Default)]
+pub(crate) struct Ledger {
+    /// Held while stored transactions are folded into the checkpoint
+    compaction: Mutex<()>,
+}
+
+impl Mint {
+    /// Balances of the double-entry ledger, checked against the database
+    ///
+    /// Units whose ledger outstanding ecash differs from the signatures issued minus
+    /// the proofs spent in the database are not [`LedgerBalance::is_consistent`].
+    #[instrument(skip_all)]
+    pub async fn ledger_balances(&self) -> Result<Vec<LedgerBalance>, Error> {
+        // Only the leader compacts, so two instances never fold the same transactions
+        if self.is_leader() {
+            self.compact_ledger().await?;
+        }
+
+        let mut totals = self.read_ledger().await?;
+        let mut database_outstanding = self.database_outstanding().await?;
+
+        // Operations record their transaction in the database transaction that changes
+        // the totals, so the two only match when nothing committed in between
+        for _ in 1..CONSISTENT_READ_ATTEMPTS {
+            let read_again = self.read_ledger().await?;
+            if read_again == totals {
+                break;
+            }
+
+            totals = read_again;
+            database_outstanding = self.database_outstanding().await?;
+        }
+
+        let mut units: Vec<CurrencyUnit> = totals.accounts.keys().cloned().collect();
+        units.extend(database_outstanding.keys().cloned());
+        units.sort_by_key(|unit| unit.to_string());
+        units.dedup();
+
+        Ok(units
+            .into_iter()
+            .map(|unit| {
+                let accounts = totals.accounts.get(&unit).cloned().unwrap_or_default();
+                let ecash = accounts
+                    .get(&LedgerAccount::Ecash)
+                    .copied()
+                    .unwrap_or_default();
+
+                LedgerBalance {
+                    outstanding: ecash.credits.checked_sub(ecash.debits),
+                    database_outstanding: database_outstanding
+                        .get(&unit)
+                        .copied()
+                        .unwrap_or(Some(Amount::ZERO)),
+                    accounts,
+                    unit,
+                }
+            })
+            .collect())
+    }
+
+    /// Account totals of the checkpoint and the transactions stored since
+    async fn read_ledger(&self) -> Result<LedgerTotals, Error> {
+        let mut totals = match self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_LEDGER_CHECKPOINT_SECONDARY_NAMESPACE,
+                LEDGER_CHECKPOINT_KEY,
+            )
+            .await?
+        {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => LedgerTotals::default(),
+        };
+
+        let keys = self
+            .localstore
+            .kv_list(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_LEDGER_SECONDARY_NAMESPACE,
+            )
+            .await?;
+
+        let mut stored = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            // Folded into the checkpoint since it was listed
+            let Some(value) = self
+                .localstore
+                .kv_read(
+                    CDK_MINT_PRIMARY_NAMESPACE,
+                    CDK_MINT_LEDGER_SECONDARY_NAMESPACE,
+                    &key,
+                )
+                .await?
+            else {
+                continue;
+            };
+
+            stored.push((key, serde_json::from_slice(&value)?));
+        }
+
+        totals.apply_stored(&stored)?;
+
+        Ok(totals)
+    }
+
+    /// Fold the stored ledger transactions into the checkpoint and remove them
+    async fn compact_ledger(&self) -> Result<(), Error> {
+        let _compacting = self.ledger.compaction.lock().await;
+
+        let mut tx = self.localstore.begin_transaction().await?;
+
+        let keys = tx
+            .kv_list(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_LEDGER_SECONDARY_NAMESPACE,
+            )
+            .await?;
+
+        if keys.is_empty() {
+            tx.rollback().await?;
+            return Ok(());
+        }
+
+        let mut totals = match tx
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_LEDGER_CHECKPOINT_SECONDARY_NAMESPACE,
+                LEDGER_CHECKPOINT_KEY,
+            )
+            .await?
+        {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => LedgerTotals::default(),
+        };
+
+        let mut stored = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let Some(value) = tx
+                .kv_read(
+                    CDK_MINT_PRIMARY_NAMESPACE,
+                    CDK_MINT_LEDGER_SECONDARY_NAMESPACE,
+                    &key,
+                )
+                .await?
+            else {
+                continue;
+            };
+
+            tx.kv_remove(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_LEDGER_SECONDARY_NAMESPACE,
+                &key,
+            )
+            .await?;
+
+            stored.push((key, serde_json::from_slice(&value)?));
+        }
+
+        totals.apply_stored(&stored)?;
+
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_LEDGER_CHECKPOINT_SECONDARY_NAMESPACE,
+            LEDGER_CHECKPOINT_KEY,
+            &serde_json::to_vec(&totals)?,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::debug!(
+            "Folded {} ledger transactions into the checkpoint",
+            stored.len()
+        );
+
+        Ok(())
+    }
+
+    /// Ecash outstanding per unit in the database, `None` when more was spent than issued
+    async fn database_outstanding(&self) -> Result<BTreeMap<CurrencyUnit, Option<Amount>>, Error> {
+        let issued = self.total_issued().await?;
+        let redeemed = self.total_redeemed().await?;
+
+        let mut units: BTreeMap<CurrencyUnit, (Amount, Amount)> = BTreeMap::new();
+
+        for keyset in self.keysets().keysets {
+            if keyset.unit == CurrencyUnit::Auth {
+                continue;
+            }
+
+            let (unit_issued, unit_redeemed) = units.entry(keyset.unit).or_default();
+            *unit_issued = unit_issued
+                .checked_add(issued.get(&keyset.id).copied().unwrap_or_default())
+                .ok_or(Error::AmountOverflow)?;
+            *unit_redeemed = unit_redeemed
+                .checked_add(redeemed.get(&keyset.id).copied().unwrap_or_default())
+                .ok_or(Error::AmountOverflow)?;
+        }
+
+        Ok(units
+            .into_iter()
+            .map(|(unit, (issued, redeemed))| (unit, issued.checked_sub(redeemed)))
+            .collect())
+    }
+
+    /// Record the ecash outstanding of units without ledger transactions as their opening balance
+    ///
+    /// Ecash issued before the ledger was kept is otherwise missing from it. Units
+    /// that already have transactions were opened before.
+    #[instrument(skip_all)]
+    pub(crate) async fn open_ledger(&self) -> Result<(), Error> {
+        let opened: HashSet<CurrencyUnit> =
+            self.read_ledger().await?.accounts.into_keys().collect();
+
+        let database_outstanding = self.database_outstanding().await?;
+        let mut tx = self.localstore.begin_transaction().await?;
+
+        for (unit, outstanding) in database_outstanding {
+            if opened.contains(&unit) {
+                continue;
+            }
+
+            let Some(outstanding) = outstanding else {
+                tracing::error!("More {} ecash was redeemed than issued", unit);
+                continue;
+            };
+
+            if outstanding == Amount::ZERO {
+                continue;
+            }
+
+            tracing::info!("Opening ledger with {} {} outstanding", outstanding, unit);
+            record_ledger_transaction(&mut tx, LedgerTransaction::opening(&unit, outstanding)?)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn apply(totals: &mut LedgerTotals, transaction: LedgerTransaction) {
+        totals.apply(&transaction).unwrap();
+    }
+
+    #[test]
+    fn test_ledger_transactions_balance() {
+        let unit = CurrencyUnit::Sat;
+        let mut totals = LedgerTotals::default();
+
+        let transactions = [
+            LedgerTransaction::opening(&unit, Amount::from(10)).unwrap(),
+            LedgerTransaction::issue(&unit, Amount::from(100)).unwrap(),
+            LedgerTransaction::swap(&unit, Amount::from(64), Amount::from(63), Amount::from(1))
+                .unwrap(),
+            LedgerTransaction::melt(&unit, Amount::from(50), Amount::from(40)).unwrap(),
+            LedgerTransaction::melt_change(&unit, Amount::from(8)).unwrap(),
+            // The payment cost more than the inputs, the mint pays the difference
+            LedgerTransaction::melt(&unit, Amount::from(10), Amount::from(11)).unwrap(),
+        ];
+
+        for transaction in transactions {
+            let sum = |side: Side| {
+                Amount::try_sum(
+                    transaction
+                        .entries
+                        .iter()
+                        .filter(|entry| entry.side == side)
+                        .map(|entry| entry.amount),
+                )
+                .unwrap()
+            };
+            assert_eq!(sum(Side::Debit), sum(Side::Credit));
+
+            apply(&mut totals, transaction);
+        }
+
+        let accounts = &totals.accounts[&unit];
+        let ecash = accounts[&LedgerAccount::Ecash];
+        assert_eq!(ecash.credits - ecash.debits, Amount::from(57));
+        assert_eq!(
+            accounts[&LedgerAccount::Fees],
+            AccountTotals {
+                debits: Amount::from(9),
+                credits: Amount::from(11),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_ledger_transaction_is_refused() {
+        let unit = CurrencyUnit::Sat;
+
+        assert!(matches!(
+            LedgerTransaction::swap(&unit, Amount::from(64), Amount::from(63), Amount::ZERO),
+            Err(Error::LedgerUnbalanced(..))
+        ));
+        assert!(matches!(
+            LedgerTransaction::swap(&unit, Amount::from(64), Amount::from(64), Amount::from(1)),
+            Err(Error::LedgerUnbalanced(..))
+        ));
+    }
+
+    #[test]
+    fn test_ledger_opening_applied_once() {
+        let unit = CurrencyUnit::Sat;
+        let mut totals = LedgerTotals::default();
+        let opening = (
+            opening_key(&unit),
+            LedgerTransaction::opening(&unit, Amount::from(5)).unwrap(),
+        );
+        let issue = (
+            format!("{}-{}", unix_time(), Uuid::new_v4().simple()),
+            LedgerTransaction::issue(&unit, Amount::from(3)).unwrap(),
+        );
+
+        // Transactions sort before the opening of their unit
+        totals
+            .apply_stored(&[issue.clone(), opening.clone()])
+            .unwrap();
+        let checkpoint: LedgerTotals =
+            serde_json::from_slice(&serde_json::to_vec(&totals).unwrap()).unwrap();
+        assert_eq!(checkpoint, totals);
+
+        // A second opening stored after the first was folded into the checkpoint
+        totals.apply_stored(&[opening]).unwrap();
+
+        assert_eq!(totals, checkpoint);
+        assert_eq!(
+            totals.accounts[&unit][&LedgerAccount::Ecash].credits,
+            Amount::from(8)
+        );
+        assert_eq!(
+            opening_key(&unit),
+            opening_key(&CurrencyUnit::from_str("sat").unwrap())
+        );
+    }
+}
@@ -9,30 +9,173 @@ use cdk_common::mint::MeltPaymentRequest;
 use cdk_common::nut05::MeltMethodOptions;
 use cdk_common::payment::{
     Bolt11OutgoingPaymentOptions, Bolt12OutgoingPaymentOptions, DynMintPayment,
-    OutgoingPaymentOptions, PaymentIdentifier,
+    KeysendOutgoingPaymentOptions, OutgoingPaymentOptions, PaymentIdentifier, PaymentQuoteResponse,
 };
 use cdk_common::quote_id::QuoteId;
-use cdk_common::{MeltOptions, MeltQuoteBolt12Request};
+use cdk_common::{MeltOptions, MeltQuoteBolt12Request, MeltQuoteKeysendRequest};
 #[cfg(feature = "prometheus")]
 use cdk_prometheus::METRICS;
 use lightning::offers::offer::Offer;
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
 use super::{
-    CurrencyUnit, MeltQuote, MeltQuoteBolt11Request, MeltQuoteBolt11Response, MeltRequest, Mint,
-    PaymentMethod, PublicKey, State,
+    quote_kv_key, CurrencyUnit, MeltQuote, MeltQuoteBolt11Request, MeltQuoteBolt11Response,
+    MeltRequest, Mint, PaymentMethod, PublicKey, State, CDK_MINT_PRIMARY_NAMESPACE,
 };
 use crate::amount::to_unit;
 use crate::cdk_payment::MakePaymentResponse;
+use crate::mint::ledger::{record_ledger_transaction, LedgerTransaction};
 use crate::mint::proof_writer::ProofWriter;
 use crate::mint::verification::Verification;
-use crate::mint::SigFlag;
+use crate::mint::{QuoteOperation, RiskAssessment, SigFlag};
 use crate::nuts::nut11::{enforce_sig_flag, EnforceSigFlag};
-use crate::nuts::MeltQuoteState;
+use crate::nuts::{MeltFailureReason, MeltQuoteState, MintQuoteState};
 use crate::types::PaymentProcessorKey;
 use crate::util::unix_time;
 use crate::{cdk_payment, ensure_cdk, Amount, Error};
 
+const CDK_MINT_MELT_ATTEMPT_SECONDARY_NAMESPACE: &str = "melt_attempt";
+const CDK_MINT_INTERNAL_MELT_SECONDARY_NAMESPACE: &str = "internal_melt";
+
+/// Payment attempt of a melt quote by a mint instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MeltAttempt {
+    /// Instance making the payment
+    pub instance_id: String,
+    /// Unix time of the attempt
+    pub time: u64,
+}
+
+/// Whether a payment was attempted for the melt quote before
+pub(crate) async fn melt_attempted(
+    tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    quote_id: &QuoteId,
+) -> Result<bool, Error> {
+    Ok(tx
+        .kv_read(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_MELT_ATTEMPT_SECONDARY_NAMESPACE,
+            &quote_kv_key(quote_id),
+        )
+        .await?
+        .is_some())
+}
+
+/// Payment attempt recorded for the melt quote
+///
+/// Attempts recorded before the owning instance was stored have no owner and
+/// are returned as `None`, like quotes that were never attempted.
+pub(crate) async fn melt_attempt(
+    tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    quote_id: &QuoteId,
+) -> Result<Option<MeltAttempt>, Error> {
+    let attempt = tx
+        .kv_read(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_MELT_ATTEMPT_SECONDARY_NAMESPACE,
+            &quote_kv_key(quote_id),
+        )
+        .await?;
+
+    Ok(attempt.and_then(|attempt| serde_json::from_slice(&attempt).ok()))
+}
+
+/// Record that `instance_id` is about to make a payment for the melt quote
+async fn record_melt_attempt(
+    tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    quote_id: &QuoteId,
+    instance_id: &str,
+) -> Result<(), Error> {
+    let attempt = MeltAttempt {
+        instance_id: instance_id.to_string(),
+        time: unix_time(),
+    };
+
+    tx.kv_write(
+        CDK_MINT_PRIMARY_NAMESPACE,
+        CDK_MINT_MELT_ATTEMPT_SECONDARY_NAMESPACE,
+        &quote_kv_key(quote_id),
+        &serde_json::to_vec(&attempt)?,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Forget the payment attempt of a melt quote whose payment was recorded
+pub(crate) async fn remove_melt_attempt(
+    tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    quote_id: &QuoteId,
+) -> Result<(), Error> {
+    tx.kv_remove(
+        CDK_MINT_PRIMARY_NAMESPACE,
+        CDK_MINT_MELT_ATTEMPT_SECONDARY_NAMESPACE,
+        &quote_kv_key(quote_id),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Record that the melt quote is settled internally with `mint_quote_id`
+async fn record_internal_melt(
+    tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    quote_id: &QuoteId,
+    mint_quote_id: &QuoteId,
+) -> Result<(), Error> {
+    tx.kv_write(
+        CDK_MINT_PRIMARY_NAMESPACE,
+        CDK_MINT_INTERNAL_MELT_SECONDARY_NAMESPACE,
+        &quote_kv_key(quote_id),
+        mint_quote_id.to_string().as_bytes(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Whether the melt quote was quoted to be settled internally
+///
+/// Such quotes have no fee reserve, they must never be paid by the backend.
+async fn is_internal_melt(
+    tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    quote_id: &QuoteId,
+) -> Result<bool, Error> {
+    Ok(tx
+        .kv_read(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_INTERNAL_MELT_SECONDARY_NAMESPACE,
+            &quote_kv_key(quote_id),
+        )
+        .await?
+        .is_some())
+}
+
+/// Payment the backend already has for a melt quote that was attempted before
+///
+/// Backends deduplicating on the idempotency key return the existing payment
+/// themselves, this lookup covers the backends that cannot.
+async fn previous_melt_payment(
+    ln: DynMintPayment,
+    quote: &MeltQuote,
+    lookup_id: &PaymentIdentifier,
+) -> Result<Option<MakePaymentResponse>, cdk_payment::Error> {
+    let payment = ln.check_outgoing_payment(lookup_id).await?;
+
+    match payment.status {
+        MeltQuoteState::Paid | MeltQuoteState::Pending => {
+            tracing::warn!(
+                "Melt quote {} was already paid with status {}, not paying again",
+                quote.id,
+                payment.status
+            );
+            Ok(Some(payment))
+        }
+        MeltQuoteState::Unpaid | MeltQuoteState::Unknown | MeltQuoteState::Failed => Ok(None),
+    }
+}
+
 impl Mint {
     #[instrument(skip_all)]
     async fn check_melt_request_acceptable(
@@ -117,14 +260,23 @@ impl Mint {
         &self,
         melt_quote_request: MeltQuoteRequest,
     ) -> Result<MeltQuoteBolt11Response<QuoteId>, Error> {
-        match melt_quote_request {
+        let mut response = match melt_quote_request {
             MeltQuoteRequest::Bolt11(bolt11_request) => {
-                self.get_melt_bolt11_quote_impl(&bolt11_request).await
+                self.get_melt_bolt11_quote_impl(&bolt11_request).await?
             }
             MeltQuoteRequest::Bolt12(bolt12_request) => {
-                self.get_melt_bolt12_quote_impl(&bolt12_request).await
+                self.get_melt_bolt12_quote_impl(&bolt12_request).await?
             }
-        }
+            MeltQuoteRequest::Keysend(keysend_request) => {
+                self.get_melt_keysend_quote_impl(&keysend_request).await?
+            }
+        };
+
+        response.timestamps = response
+            .timestamps
+            .map(|timestamps| self.signed_quote_timestamps(&response.quote, timestamps));
+
+        Ok(response)
     }
 
     /// Implementation of get_melt_bolt11_quote
@@ -155,46 +307,79 @@ impl Mint {
         )
         .await?;
 
-        let ln = self
-            .payment_processors
-            .get(&PaymentProcessorKey::new(
-                unit.clone(),
-                PaymentMethod::Bolt11,
-            ))
-            .ok_or_else(|| {
-                tracing::info!("Could not get ln backend for {}, bolt11 ", unit);
-
-                Error::UnsupportedUnit
-            })?;
+        // Invoices of the mint's own unpaid mint quotes are settled internally
+        // when melted, whatever the backend, so the backend is not asked to pay
+        // itself and no fee reserve is needed
+        let internal_mint_quote = self
+            .localstore
+            .get_mint_quote_by_request(&request.to_string())
+            .await?
+            .filter(|mint_quote| mint_quote.state() == MintQuoteState::Unpaid);
 
-        let bolt11 = Bolt11OutgoingPaymentOptions {
-            bolt11: melt_request.request.clone(),
-            max_fee_amount: None,
-            timeout_secs: None,
-            melt_options: melt_request.options,
-        };
+        let internal_mint_quote_id = internal_mint_quote
+            .as_ref()
+            .map(|mint_quote| mint_quote.id.clone());
 
-        let payment_quote = ln
-            .get_payment_quote(
-                &melt_request.unit,
-                OutgoingPaymentOptions::Bolt11(Box::new(bolt11)),
-            )
-            .await
-            .map_err(|err| {
-                tracing::error!(
-                    "Could not get payment quote for mint quote, {} bolt11, {}",
-                    unit,
-                    err
+        let payment_quote = match internal_mint_quote {
+            Some(mint_quote) => {
+                tracing::debug!(
+                    "Melt request is paid by mint quote {}, settling internally",
+                    mint_quote.id
                 );
 
-                #[cfg(feature = "prometheus")]
-                {
-                    METRICS.dec_in_flight_requests("get_melt_bolt11_quote");
-                    METRICS.record_mint_operation("get_melt_bolt11_quote", false);
-                    METRICS.record_error();
+                PaymentQuoteResponse {
+                    request_lookup_id: Some(PaymentIdentifier::PaymentHash(
+                        *request.payment_hash().as_ref(),
+                    )),
+                    amount: amount_quote_unit,
+                    fee: Amount::ZERO,
+                    unit: unit.clone(),
+                    state: MeltQuoteState::Unpaid,
                 }
-                Error::UnsupportedUnit
-            })?;
+            }
+            None => {
+                let ln = self
+                    .payment_processors
+                    .get(&PaymentProcessorKey::new(
+                        unit.clone(),
+                        PaymentMethod::Bolt11,
+                    ))
+                    .ok_or_else(|| {
+                        tracing::info!("Could not get ln backend for {}, bolt11 ", unit);
+
+                        Error::UnsupportedUnit
+                    })?;
+
+                let bolt11 = Bolt11OutgoingPaymentOptions {
+                    bolt11: melt_request.request.clone(),
+                    max_fee_amount: None,
+                    timeout_secs: None,
+                    melt_options: melt_request.options,
+                    idempotency_key: None,
+                };
+
+                ln.get_payment_quote(
+                    &melt_request.unit,
+                    OutgoingPaymentOptions::Bolt11(Box::new(bolt11)),
+                )
+                .await
+                .map_err(|err| {
+                    tracing::error!(
+                        "Could not get payment quote for mint quote, {} bolt11, {}",
+                        unit,
+                        err
+                    );
+
+                    #[cfg(feature = "prometheus")]
+                    {
+                        METRICS.dec_in_flight_requests("get_melt_bolt11_quote");
+                        METRICS.record_mint_operation("get_melt_bolt11_quote", false);
+                        METRICS.record_error();
+                    }
+                    Error::UnsupportedUnit
+                })?
+            }
+        };
 
         let melt_ttl = self.quote_ttl().await?.melt_ttl;
 
@@ -220,8 +405,25 @@ impl Mint {
             payment_quote.request_lookup_id
         );
 
+        let risk_record = self
+            .assess_quote_risk(RiskAssessment {
+                quote_id: quote.id.clone(),
+                operation: QuoteOperation::Melt,
+                unit: unit.clone(),
+                payment_method: quote.payment_method.clone(),
+                amount: Some(amount_quote_unit),
+                request: Some(request.to_string()),
+            })
+            .await?;
+
         let mut tx = self.localstore.begin_transaction().await?;
         tx.add_melt_quote(quote.clone()).await?;
+        if let Some(mint_quote_id) = internal_mint_quote_id {
+            record_internal_melt(&mut tx, &quote.id, &mint_quote_id).await?;
+        }
+        if let Some(risk_record) = risk_record {
+            risk_record.write(&mut tx).await?;
+        }
         tx.commit().await?;
 
         Ok(quote.into())
@@ -279,6 +481,7 @@ impl Mint {
             max_fee_amount: None,
             timeout_secs: None,
             melt_options: *options,
+            idempotency_key: None,
         };
 
         let payment_quote = ln
@@ -321,8 +524,22 @@ impl Mint {
             payment_quote.request_lookup_id
         );
 
+        let risk_record = self
+            .assess_quote_risk(RiskAssessment {
+                quote_id: quote.id.clone(),
+                operation: QuoteOperation::Melt,
+                unit: unit.clone(),
+                payment_method: quote.payment_method.clone(),
+                amount: Some(amount),
+                request: Some(request.clone()),
+            })
+            .await?;
+
         let mut tx = self.localstore.begin_transaction().await?;
         tx.add_melt_quote(quote.clone()).await?;
+        if let Some(risk_record) = risk_record {
+            risk_record.write(&mut tx).await?;
+        }
         tx.commit().await?;
 
         #[cfg(feature = "prometheus")]
@@ -334,6 +551,96 @@ impl Mint {
         Ok(quote.into())
     }
 
+    /// Implementation of get_melt_keysend_quote
+    #[instrument(skip_all)]
+    async fn get_melt_keysend_quote_impl(
+        &self,
+        melt_request: &MeltQuoteKeysendRequest,
+    ) -> Result<MeltQuoteBolt11Response<QuoteId>, Error> {
+        let MeltQuoteKeysendRequest {
+            pubkey,
+            amount: amount_msat,
+            unit,
+        } = melt_request;
+
+        let amount = to_unit(*amount_msat, &CurrencyUnit::Msat, unit)?;
+
+        self.check_melt_request_acceptable(
+            amount,
+            unit.clone(),
+            PaymentMethod::keysend(),
+            pubkey.to_string(),
+            None,
+        )
+        .await?;
+
+        let ln = self.get_payment_processor(unit.clone(), PaymentMethod::keysend())?;
+
+        let outgoing_payment_options = KeysendOutgoingPaymentOptions {
+            pubkey: *pubkey,
+            amount_msat: *amount_msat,
+            max_fee_amount: None,
+            timeout_secs: None,
+            idempotency_key: None,
+        };
+
+        let payment_quote = ln
+            .get_payment_quote(
+                unit,
+                OutgoingPaymentOptions::Keysend(Box::new(outgoing_payment_options)),
+            )
+            .await
+            .map_err(|err| {
+                tracing::error!(
+                    "Could not get payment quote for melt quote, {} keysend, {}",
+                    unit,
+                    err
+                );
+
+                Error::UnsupportedUnit
+            })?;
+
+        let quote = MeltQuote::new(
+            MeltPaymentRequest::Keysend { pubkey: *pubkey },
+            unit.clone(),
+            payment_quote.amount,
+            payment_quote.fee,
+            unix_time() + self.quote_ttl().await?.melt_ttl,
+            payment_quote.request_lookup_id.clone(),
+            None,
+            PaymentMethod::keysend(),
+        );
+
+        tracing::debug!(
+            "New {} melt quote {} for {} {} to node {}",
+            quote.payment_method,
+            quote.id,
+            amount,
+            unit,
+            pubkey
+        );
+
+        let risk_record = self
+            .assess_quote_risk(RiskAssessment {
+                quote_id: quote.id.clone(),
+                operation: QuoteOperation::Melt,
+                unit: unit.clone(),
+                payment_method: quote.payment_method.clone(),
+                amount: Some(amount),
+                request: Some(pubkey.to_string()),
+            })
+            .await?;
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.add_melt_quote(quote.clone()).await?;
+        if let Some(risk_record) = risk_record {
+            risk_record.write(&mut tx).await?;
+        }
+        tx.commit().await?;
+
+        Ok(quote.into())
+    }
+
     /// Check melt quote status
     #[instrument(skip(self))]
     pub async fn check_melt_quote(
@@ -342,7 +649,15 @@ impl Mint {
     ) -> Result<MeltQuoteBolt11Response<QuoteId>, Error> {
         #[cfg(feature = "prometheus")]
         METRICS.inc_in_flight_requests("check_melt_quote");
-        let quote = match self.localstore.get_melt_quote(quote_id).await {
+        // A paid quote no longer changes, any other state is read from the primary so it
+        // matches the change signatures read from it
+        let quote = match self.localstore.get_melt_quote_from_replica(quote_id).await {
+            Ok(Some(quote)) if quote.state == MeltQuoteState::Paid => Ok(Some(quote)),
+            Ok(_) => self.localstore.get_melt_quote(quote_id).await,
+            Err(err) => Err(err),
+        };
+
+        let quote = match quote {
             Ok(Some(quote)) => quote,
             Ok(None) => {
                 #[cfg(feature = "prometheus")]
@@ -384,6 +699,7 @@ impl Mint {
         let change = (!blind_signatures.is_empty()).then_some(blind_signatures);
 
         let response = MeltQuoteBolt11Response {
+            timestamps: Some(self.signed_quote_timestamps(&quote.id, quote.timestamps())),
             quote: quote.id,
             paid: Some(quote.state == MeltQuoteState::Paid),
             state: quote.state,
@@ -394,6 +710,7 @@ impl Mint {
             change,
             request: Some(quote.request.to_string()),
             unit: Some(quote.unit.clone()),
+            failure_reason: quote.failure_reason,
         };
 
         #[cfg(feature = "prometheus")]
@@ -453,6 +770,7 @@ impl Mint {
                     .ok_or(Error::InvoiceAmountUndefined)?
                     .amount_msat(),
             },
+            MeltPaymentRequest::Keysend { .. } => quote_msats,
         };
 
         let partial_amount = match invoice_amount_msats > quote_msats {
@@ -520,7 +838,7 @@ impl Mint {
             .await?;
 
         // Only after proof verification succeeds, proceed with quote state check
-        let (state, quote) = tx
+        let (state, mut quote) = tx
             .update_melt_quote_state(melt_request.quote(), MeltQuoteState::Pending, None)
             .await?;
 
@@ -531,6 +849,11 @@ impl Mint {
             MeltQuoteState::Unknown => Err(Error::UnknownPaymentState),
         }?;
 
+        // The failure of a previous attempt no longer applies
+        if quote.failure_reason.take().is_some() {
+            tx.update_melt_quote_failure_reason(&quote.id, None).await?;
+        }
+
         self.pubsub_manager
             .melt_quote_status(&quote, None, None, MeltQuoteState::Pending);
 
@@ -654,10 +977,15 @@ impl Mint {
                 }
                 Err(e) => {
                     tracing::error!("Payment processor internal settlement failed: {:?}", e);
+                    tx.rollback().await?;
                     // Only return ExpiredQuote if the error string contains the expired message from strike
                     if format!("{e:?}").contains("Currency exchange quote has expired") {
+                        self.fail_melt_quote(&quote.id, MeltFailureReason::ExchangeQuoteExpired)
+                            .await;
                         return Err(Error::ExpiredQuote(0, 0));
                     }
+                    self.fail_melt_quote(&quote.id, MeltFailureReason::BackendError)
+                        .await;
                     return Err(Error::PaymentFailed);
                 }
             }
@@ -686,6 +1014,20 @@ impl Mint {
             }
         };
 
+        // The mint quote of a melt quoted as internal was removed meanwhile, the
+        // quote has no fee reserve for the backend to pay it
+        if payment_processor_result.is_none()
+            && mint_internal_result.is_none()
+            && is_internal_melt(&mut tx, &quote.id).await?
+        {
+            tracing::warn!(
+                "Mint quote settling melt quote {} internally is gone",
+                quote.id
+            );
+            tx.rollback().await?;
+            return Err(Error::UnknownQuote);
+        }
+
         // Determine the final settlement result and handle quote updates
         let (settled_internally_amount, updated_quote) = match (
             payment_processor_result,
@@ -757,8 +1099,17 @@ impl Mint {
                         match self.check_melt_expected_ln_fees(&quote, melt_request).await {
                             Ok(amount) => amount,
                             Err(err) => {
-                                tracing::error!("Fee is not expected: {}", err);
-                                return Err(Error::Internal);
+                                // The request does not cover the quote, nothing was paid
+                                // and the quote stays unpaid
+                                tracing::debug!("Fee is not expected: {}", err);
+                                tx.rollback().await?;
+                                self.pubsub_manager.melt_quote_status(
+                                    &quote,
+                                    None,
+                                    None,
+                                    MeltQuoteState::Unpaid,
+                                );
+                                return Err(err);
                             }
                         }
                     }
@@ -776,13 +1127,53 @@ impl Mint {
                     }
                 };
 
+                // A previous attempt of this melt may have paid before the mint could
+                // record the outcome, it must not be paid a second time
+                let attempted = melt_attempted(&mut tx, &quote.id).await?;
+                record_melt_attempt(&mut tx, &quote.id, &self.instance_id).await?;
+
                 // Commit before talking to the external call
                 tx.commit().await?;
 
-                let pre = match ln
-                    .make_payment(&quote.unit, quote.clone().try_into()?)
-                    .await
-                {
+                let previous_payment = if attempted {
+                    // Without a lookup id, as for keysend quotes, the backend cannot tell
+                    // whether the earlier attempt paid, so the quote is left to reconciliation
+                    let Some(lookup_id) = quote.request_lookup_id.as_ref() else {
+                        tracing::warn!(
+                            "Melt quote {} was attempted before and has no payment lookup id, not paying again",
+                            quote.id
+                        );
+                        proof_writer.commit();
+                        return Err(Error::PendingQuote);
+                    };
+
+                    match previous_melt_payment(Arc::clone(ln), &quote, lookup_id).await {
+                        Ok(payment) => payment,
+                        Err(err) => {
+                            tracing::error!(
+                                "Could not look up previous payment of melt quote {}, proofs stuck as pending: {}",
+                                quote.id,
+                                err
+                            );
+                            proof_writer.commit();
+                            return Err(Error::Internal);
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                let payment = match previous_payment {
+                    Some(payment) => Ok(payment),
+                    None => {
+                        ln.make_payment(&quote.unit, quote.clone().try_into()?)
+                            .await
+                    }
+                };
+
+                let payment_failed_by_backend = payment.is_err();
+
+                let pre = match payment {
                     Ok(pay)
                         if pay.status == MeltQuoteState::Unknown
                             || pay.status == MeltQuoteState::Failed =>
@@ -812,6 +1203,8 @@ impl Mint {
                         // hold the proofs as pending to we reset them  and return an error.
                         if matches!(err, cdk_payment::Error::InvoiceAlreadyPaid) {
                             tracing::debug!("Invoice already paid, resetting melt quote");
+                            self.fail_melt_quote(&quote.id, MeltFailureReason::AlreadyPaid)
+                                .await;
                             return Err(Error::RequestAlreadyPaid);
                         }
 
@@ -851,6 +1244,13 @@ impl Mint {
                         );
                         proof_writer.rollback().await?;
 
+                        let reason = if payment_failed_by_backend {
+                            MeltFailureReason::BackendError
+                        } else {
+                            MeltFailureReason::PaymentFailed
+                        };
+                        self.fail_melt_quote(&quote.id, reason).await;
+
                         #[cfg(feature = "prometheus")]
                         {
                             METRICS.dec_in_flight_requests("melt_bolt11");
@@ -885,6 +1285,7 @@ impl Mint {
 
                 let payment_lookup_id = pre.payment_lookup_id;
                 let mut tx = self.localstore.begin_transaction().await?;
+                remove_melt_attempt(&mut tx, &quote.id).await?;
 
                 if Some(payment_lookup_id.clone()).as_ref() != quote.request_lookup_id.as_ref() {
                     tracing::info!(
@@ -977,7 +1378,9 @@ impl Mint {
         }
         tracing::debug!("Successfully updated proof states to Spent");
 
-        tx.update_melt_quote_state(&quote.id, MeltQuoteState::Paid, payment_preimage.clone())
+        // The stored quote now carries the paid time of its timestamps
+        let (_, quote) = tx
+            .update_melt_quote_state(&quote.id, MeltQuoteState::Paid, payment_preimage.clone())
             .await?;
 
         let mut change = None;
@@ -991,6 +1394,12 @@ impl Mint {
             .await?
             .ok_or(Error::UnknownQuote)?;
 
+        record_ledger_transaction(
+            &mut tx,
+            LedgerTransaction::melt(&quote.unit, inputs_amount, total_spent)?,
+        )
+        .await?;
+
         // Check if there is change to return
         if inputs_amount > total_spent {
             // Check if wallet provided change outputs
@@ -1054,6 +1463,15 @@ impl Mint {
                 )
                 .await?;
 
+                record_ledger_transaction(
+                    &mut tx,
+                    LedgerTransaction::melt_change(
+                        &quote.unit,
+                        Amount::try_sum(change_sigs.iter().map(|signature| signature.amount))?,
+                    )?,
+                )
+                .await?;
+
                 change = Some(change_sigs);
 
                 proof_writer.commit();
@@ -1085,23 +1503,21 @@ impl Mint {
             quote.id,
             total_spent,
             inputs_amount,
-            change
-                .as_ref()
-                .map(|c| Amount::try_sum(c.iter().map(|a| a.amount))
-                    .expect("Change cannot overflow"))
-                .unwrap_or_default()
+            change_amount
         );
         let response = MeltQuoteBolt11Response {
             amount: quote.amount,
             paid: Some(true),
             payment_preimage,
             change,
-            quote: quote.id,
             fee_reserve: quote.fee_reserve,
             state: MeltQuoteState::Paid,
             expiry: quote.expiry,
             request: Some(quote.request.to_string()),
             unit: Some(quote.unit.clone()),
+            timestamps: Some(self.signed_quote_timestamps(&quote.id, quote.timestamps())),
+            failure_reason: None,
+            quote: quote.id,
         };
 
         #[cfg(feature = "prometheus")]
@@ -1113,6 +1529,48 @@ impl Mint {
         Ok(response)
     }
 
+    /// Return a melt quote whose payment failed to unpaid and record why
+    ///
+    /// The melt already failed, so errors recording the reason are only logged.
+    async fn fail_melt_quote(&self, quote_id: &QuoteId, reason: MeltFailureReason) {
+        let result: Result<MeltQuote, Error> = async {
+            let mut tx = self.localstore.begin_transaction().await?;
+            let mut quote = tx
+                .get_melt_quote(quote_id)
+                .await?
+                .ok_or(Error::UnknownQuote)?;
+
+            if quote.state == MeltQuoteState::Pending {
+                (_, quote) = tx
+                    .update_melt_quote_state(quote_id, MeltQuoteState::Unpaid, None)
+                    .await?;
+            }
+
+            tx.update_melt_quote_failure_reason(quote_id, Some(reason))
+                .await?;
+            tx.commit().await?;
+
+            quote.failure_reason = Some(reason);
+            Ok(quote)
+        }
+        .await;
+
+        match result {
+            Ok(quote) => {
+                tracing::info!("Melt quote {} failed: {}", quote_id, reason);
+                self.pubsub_manager
+                    .melt_quote_status(&quote, None, None, MeltQuoteState::Unpaid);
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Could not record failure of melt quote {}: {}",
+                    quote_id,
+                    err
+                );
+            }
+        }
+    }
+
     #[cfg(feature = "prometheus")]
     fn record_melt_quote_failure(&self, operation: &str) {
         METRICS.dec_in_flight_requests(operation);
@@ -1,6 +1,7 @@
 //! Cashu Mint
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -9,21 +10,24 @@ use cdk_common::amount::to_unit;
 use cdk_common::common::{PaymentProcessorKey, QuoteTTL};
 #[cfg(feature = "auth")]
 use cdk_common::database::DynMintAuthDatabase;
-use cdk_common::database::{self, DynMintDatabase, MintTransaction};
+use cdk_common::database::{self, DynMintDatabase, DynMintLeaderElection, MintTransaction};
 use cdk_common::nuts::{self, BlindSignature, BlindedMessage, CurrencyUnit, Id, Kind};
-use cdk_common::payment::{DynMintPayment, WaitPaymentResponse};
+use cdk_common::payment::{BackendInfo, DynMintPayment, WaitPaymentResponse};
 pub use cdk_common::quote_id::QuoteId;
-use cdk_common::secret;
+use cdk_common::{secret, QuoteTimestamps};
 #[cfg(feature = "prometheus")]
 use cdk_prometheus::global;
 use cdk_signatory::signatory::{Signatory, SignatoryKeySet};
 use futures::StreamExt;
+use ledger::Ledger;
 #[cfg(feature = "auth")]
 use nut21::ProtectedEndpoint;
+use risk::RiskCheck;
 use subscription::PubSubManager;
 use tokio::sync::{Mutex, Notify};
 use tokio::task::{JoinHandle, JoinSet};
 use tracing::instrument;
+use webhook::QuoteWebhookCache;
 
 use crate::error::Error;
 use crate::fees::calculate_fee;
@@ -36,25 +40,62 @@ use crate::{cdk_database, Amount};
 pub(crate) mod auth;
 mod builder;
 mod check_spendable;
+mod fee_reserve;
 mod issue;
 mod keysets;
+mod leader;
+mod ledger;
 mod ln;
 mod melt;
 mod proof_writer;
+mod risk;
 mod start_up_check;
 pub mod subscription;
 mod swap;
 mod verification;
+mod voucher;
+mod webhook;
 
 pub use builder::{MintBuilder, MintMeltLimits};
 pub use cdk_common::mint::{MeltQuote, MintKeySetInfo, MintQuote};
+pub use fee_reserve::{
+    FeeReserveFeedback, FeeReserveMintPayment, FeeReserveStrategy, FeeReserveTier,
+};
+pub use ledger::{AccountTotals, LedgerAccount, LedgerBalance};
+pub use risk::{
+    HttpRiskPolicy, QuoteOperation, RiskAssessment, RiskDecision, RiskPolicy, RiskRecord,
+};
 pub use verification::Verification;
+pub use voucher::{VoucherIssuance, VoucherRedemption, VoucherReport};
+pub use webhook::{QuoteEvent, QuoteEventState, QuoteWebhook, WebhookDelivery};
 
 const CDK_MINT_PRIMARY_NAMESPACE: &str = "cdk_mint";
 const CDK_MINT_CONFIG_SECONDARY_NAMESPACE: &str = "config";
 const CDK_MINT_CONFIG_KV_KEY: &str = "mint_info";
 const CDK_MINT_QUOTE_TTL_KV_KEY: &str = "quote_ttl";
 
+/// KV store key of a record kept for a quote, base64 ids may end in padding
+fn quote_kv_key(quote_id: &QuoteId) -> String {
+    quote_id.to_string().trim_end_matches('=').to_string()
+}
+
+/// Sign quote timestamps with the mint identity key, if one is configured
+///
+/// Timestamps that cannot be signed are returned unsigned.
+fn sign_quote_timestamps(
+    signing_key: Option<&SecretKey>,
+    quote_id: &QuoteId,
+    mut timestamps: QuoteTimestamps,
+) -> QuoteTimestamps {
+    if let Some(secret_key) = signing_key {
+        if let Err(err) = timestamps.sign(&quote_id.to_string(), secret_key) {
+            tracing::error!("Could not sign timestamps of quote {}: {}", quote_id, err);
+        }
+    }
+
+    timestamps
+}
+
 /// Cashu Mint
 #[derive(Clone)]
 pub struct Mint {
@@ -78,6 +119,22 @@ pub struct Mint {
     keysets: Arc<ArcSwap<Vec<SignatoryKeySet>>>,
     /// Background task management
     task_state: Arc<Mutex<TaskState>>,
+    /// Long-lived identity key used to sign the mint info
+    info_signing_key: Option<SecretKey>,
+    /// Leader election between instances serving the mint and its check interval
+    leader_election: Option<(DynMintLeaderElection, Duration)>,
+    /// Whether this instance is the leader
+    is_leader: Arc<AtomicBool>,
+    /// Id of this instance, recorded on the melts it pays
+    instance_id: String,
+    /// Double-entry ledger of the transactions stored in the database
+    ledger: Arc<Ledger>,
+    /// Risk policy consulted before quotes are stored
+    risk_check: Option<RiskCheck>,
+    /// Units without payment backend the operator issues vouchers in
+    voucher_units: Vec<CurrencyUnit>,
+    /// Quote webhooks read by the dispatcher
+    quote_webhooks: Arc<QuoteWebhookCache>,
 }
 
 /// State for managing background tasks
@@ -87,6 +144,8 @@ struct TaskState {
     shutdown_notify: Option<Arc<Notify>>,
     /// Handle to the main supervisor task
     supervisor_handle: Option<JoinHandle<Result<(), Error>>>,
+    /// Handle to the leader election task
+    leader_handle: Option<JoinHandle<()>>,
 }
 
 impl Mint {
@@ -222,6 +281,14 @@ impl Mint {
             auth_localstore,
             keysets: Arc::new(ArcSwap::new(keysets.keysets.into())),
             task_state: Arc::new(Mutex::new(TaskState::default())),
+            info_signing_key: None,
+            leader_election: None,
+            is_leader: Arc::new(AtomicBool::new(false)),
+            instance_id: uuid::Uuid::new_v4().to_string(),
+            ledger: Arc::new(Ledger::default()),
+            quote_webhooks: Arc::new(QuoteWebhookCache::default()),
+            risk_check: None,
+            voucher_units: Vec::new(),
         })
     }
 
@@ -240,6 +307,7 @@ impl Mint {
     /// Currently manages:
     /// - Payment processor initialization and startup
     /// - Invoice payment monitoring across all configured payment processors
+    /// - Leader election, when configured with [`MintBuilder::with_leader_election`]
     pub async fn start(&self) -> Result<(), Error> {
         let mut task_state = self.task_state.lock().await;
 
@@ -275,6 +343,10 @@ impl Mint {
 
         tracing::info!("Payment processor startup completed");
 
+        if let Err(err) = self.open_ledger().await {
+            tracing::error!("Could not open ledger: {}", err);
+        }
+
         // Create shutdown signal
         let shutdown_notify = Arc::new(Notify::new());
 
@@ -295,9 +367,23 @@ impl Mint {
             .await
         });
 
+        // Spawn the leader election task
+        let leader_handle = self
+            .leader_election
+            .clone()
+            .map(|(leader_election, interval)| {
+                let mint = self.clone();
+                let shutdown_clone = shutdown_notify.clone();
+                tokio::spawn(async move {
+                    mint.run_leader_election(leader_election, interval, shutdown_clone)
+                        .await
+                })
+            });
+
         // Store the handles
         task_state.shutdown_notify = Some(shutdown_notify);
         task_state.supervisor_handle = Some(supervisor_handle);
+        task_state.leader_handle = leader_handle;
 
         // Give the background task a tiny bit of time to start waiting
         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
@@ -321,6 +407,7 @@ impl Mint {
         // Take the handles out of the state
         let shutdown_notify = task_state.shutdown_notify.take();
         let supervisor_handle = task_state.supervisor_handle.take();
+        let leader_handle = task_state.leader_handle.take();
 
         // If nothing to stop, return early
         let (shutdown_notify, supervisor_handle) = match (shutdown_notify, supervisor_handle) {
@@ -340,6 +427,13 @@ impl Mint {
         // Signal shutdown
         shutdown_notify.notify_waiters();
 
+        // Wait for the leader election task to give up leadership
+        if let Some(leader_handle) = leader_handle {
+            if let Err(join_error) = leader_handle.await {
+                tracing::error!("Leader election task panicked: {:?}", join_error);
+            }
+        }
+
         // Wait for supervisor to complete
         let result = match supervisor_handle.await {
             Ok(result) => {
@@ -402,6 +496,44 @@ impl Mint {
         })
     }
 
+    /// Health and identity information of the configured payment backends
+    ///
+    /// Intended for operators only, this is not part of the public mint info.
+    /// Backends that fail to respond are left out.
+    #[instrument(skip_all)]
+    pub async fn backend_info(&self) -> Vec<(PaymentProcessorKey, BackendInfo)> {
+        let mut backend_info = Vec::new();
+
+        for (key, processor) in &self.payment_processors {
+            match processor.backend_info().await {
+                Ok(Some(info)) => backend_info.push((key.clone(), info)),
+                Ok(None) => (),
+                Err(err) => {
+                    tracing::warn!("Could not get backend info for {:?}: {}", key, err);
+                }
+            }
+        }
+
+        backend_info
+    }
+
+    /// Whether this instance is the leader of the instances serving the mint
+    ///
+    /// A mint without leader election is always its own leader.
+    pub fn is_leader(&self) -> bool {
+        match self.leader_election {
+            Some(_) => self.is_leader.load(Ordering::SeqCst),
+            None => true,
+        }
+    }
+
+    /// Id of this instance among the instances serving the mint
+    ///
+    /// The id is generated when the mint is built, so it changes on restart.
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
     /// Localstore
     pub fn localstore(&self) -> DynMintDatabase {
         Arc::clone(&self.localstore)
@@ -464,6 +596,40 @@ impl Mint {
         Ok(mint_info)
     }
 
+    /// Sign mint info with the mint identity key
+    ///
+    /// Returns the mint info unchanged if no identity key is configured. Must be
+    /// called after the last change to the mint info, since any later change
+    /// invalidates the signature.
+    pub fn sign_mint_info(&self, mut mint_info: MintInfo) -> Result<MintInfo, Error> {
+        if let Some(secret_key) = self.info_signing_key.as_ref() {
+            mint_info.sign(secret_key)?;
+        }
+
+        Ok(mint_info)
+    }
+
+    /// Sign the timestamps of a quote with the mint identity key
+    ///
+    /// Returns the timestamps unsigned if no identity key is configured.
+    pub fn signed_quote_timestamps(
+        &self,
+        quote_id: &QuoteId,
+        timestamps: QuoteTimestamps,
+    ) -> QuoteTimestamps {
+        sign_quote_timestamps(self.info_signing_key.as_ref(), quote_id, timestamps)
+    }
+
+    /// Set the identity key signing the mint info and quote timestamps
+    fn set_info_signing_key(&mut self, secret_key: Option<SecretKey>) {
+        if let Some(secret_key) = secret_key.as_ref() {
+            self.pubsub_manager
+                .set_quote_signing_key(secret_key.clone());
+        }
+
+        self.info_signing_key = secret_key;
+    }
+
     /// Set mint info
     #[instrument(skip_all)]
     pub async fn set_mint_info(&self, mint_info: MintInfo) -> Result<(), Error> {
@@ -1081,6 +1247,7 @@ mod tests {
     use cdk_sqlite::mint::memory::new_with_state;
 
     use super::*;
+    use crate::util::unix_time;
 
     #[derive(Default)]
     struct MintConfig<'a> {
@@ -1235,4 +1402,180 @@ mod tests {
         mint.start().await.expect("Should be able to restart");
         mint.stop().await.expect("Final stop should work");
     }
+
+    /// Accepts quotes up to 100, fails for larger ones
+    struct LimitPolicy;
+
+    #[async_trait::async_trait]
+    impl RiskPolicy for LimitPolicy {
+        async fn assess(&self, assessment: &RiskAssessment) -> Result<RiskDecision, Error> {
+            match assessment.amount {
+                Some(amount) if amount > Amount::from(100) => {
+                    Err(Error::Custom("Service unavailable".to_string()))
+                }
+                _ => Ok(RiskDecision {
+                    allow: assessment.amount != Some(Amount::from(100)),
+                    score: Some(0.5),
+                    reason: None,
+                }),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_risk_policy() {
+        let mut supported_units = HashMap::new();
+        supported_units.insert(CurrencyUnit::default(), (0, 32));
+        let config = MintConfig::<'_> {
+            supported_units,
+            ..Default::default()
+        };
+        let mut mint = create_mint(config).await;
+        mint.risk_check = Some(risk::RiskCheck {
+            policy: Arc::new(LimitPolicy),
+            timeout: Duration::from_secs(1),
+            fail_open: false,
+        });
+
+        let assessment = |amount: u64| RiskAssessment {
+            quote_id: QuoteId::new_uuid(),
+            operation: QuoteOperation::Mint,
+            unit: CurrencyUnit::Sat,
+            payment_method: PaymentMethod::Bolt11,
+            amount: Some(Amount::from(amount)),
+            request: None,
+        };
+
+        let accepted = mint.assess_quote_risk(assessment(10)).await.unwrap();
+        assert!(accepted.unwrap().decision.allow);
+
+        // Rejected quotes are recorded on their own
+        let rejected = assessment(100);
+        assert!(matches!(
+            mint.assess_quote_risk(rejected.clone()).await,
+            Err(Error::QuoteRejected)
+        ));
+        let record = mint
+            .quote_risk_record(&rejected.quote_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.decision.score, Some(0.5));
+
+        // A failing policy rejects when failing closed and accepts when failing open
+        assert!(mint.assess_quote_risk(assessment(1000)).await.is_err());
+
+        mint.risk_check.as_mut().unwrap().fail_open = true;
+        let record = mint
+            .assess_quote_risk(assessment(1000))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(record.decision.allow);
+        assert!(record.policy_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_quote_webhook_registration() {
+        let mut supported_units = HashMap::new();
+        supported_units.insert(CurrencyUnit::default(), (0, 32));
+        let config = MintConfig::<'_> {
+            supported_units,
+            ..Default::default()
+        };
+        let mint = create_mint(config).await;
+
+        assert!(matches!(
+            mint.register_quote_webhook(&QuoteId::new_uuid(), "https://shop.example.com")
+                .await,
+            Err(Error::UnknownQuote)
+        ));
+        assert!(mint
+            .register_quote_webhook(&QuoteId::new_uuid(), "ftp://shop.example.com")
+            .await
+            .is_err());
+        assert!(mint.due_quote_webhooks().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_quote_webhook_states() {
+        let quote = |amount_paid: u64, expiry: u64| {
+            MintQuote::new(
+                None,
+                "lnbc1".to_string(),
+                CurrencyUnit::Sat,
+                Some(Amount::from(10)),
+                expiry,
+                cdk_common::payment::PaymentIdentifier::CustomId(QuoteId::new_uuid().to_string()),
+                None,
+                Amount::from(amount_paid),
+                Amount::ZERO,
+                PaymentMethod::Bolt11,
+                unix_time(),
+                vec![],
+                vec![],
+            )
+        };
+        let unpaid = quote(0, unix_time() + 3600);
+        let paid = quote(10, unix_time() + 3600);
+        let expired = quote(0, unix_time() - 1);
+
+        let mut supported_units = HashMap::new();
+        supported_units.insert(CurrencyUnit::default(), (0, 32));
+        let config = MintConfig::<'_> {
+            supported_units,
+            mint_quotes: vec![unpaid.clone(), paid.clone(), expired.clone()],
+            ..Default::default()
+        };
+        let mint = create_mint(config).await;
+
+        for quote in [&unpaid, &paid, &expired] {
+            mint.register_quote_webhook(&quote.id, "https://shop.example.com/cashu")
+                .await
+                .unwrap();
+        }
+
+        // Unpaid quotes have nothing to notify yet
+        let mut due = mint.due_quote_webhooks().await.unwrap();
+        due.sort_by_key(|(_, event)| event.state == QuoteEventState::Expired);
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].1.quote, paid.id);
+        assert_eq!(due[0].1.state, QuoteEventState::Paid);
+        assert_eq!(due[1].1.quote, expired.id);
+        assert_eq!(due[1].1.state, QuoteEventState::Expired);
+
+        let delivery = |status: u16| WebhookDelivery {
+            state: QuoteEventState::Paid,
+            timestamp: unix_time(),
+            status: Some(status),
+            error: None,
+        };
+
+        // A failed delivery waits for its retry
+        mint.record_webhook_delivery(&paid.id, delivery(500), Some(unix_time() + 600))
+            .await
+            .unwrap();
+        let webhook = mint.quote_webhook(&paid.id).await.unwrap().unwrap();
+        assert_eq!(webhook.attempts, 1);
+        assert_eq!(webhook.delivered_state, None);
+        assert!(mint
+            .due_quote_webhooks()
+            .await
+            .unwrap()
+            .iter()
+            .all(|(webhook, _)| webhook.quote_id != paid.id));
+
+        // A delivered state is not delivered again
+        mint.record_webhook_delivery(&paid.id, delivery(200), None)
+            .await
+            .unwrap();
+        let webhook = mint.quote_webhook(&paid.id).await.unwrap().unwrap();
+        assert_eq!(webhook.attempts, 0);
+        assert_eq!(webhook.delivered_state, Some(QuoteEventState::Paid));
+        assert_eq!(webhook.deliveries.len(), 2);
+
+        let due = mint.due_quote_webhooks().await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.quote, expired.id);
+    }
 }
@@ -0,0 +1,246 @@
+//! Quote risk policy
+//!
+//! Before a mint or melt quote is stored the mint can consult a [`RiskPolicy`],
+//! such as an external fraud scoring service. The decision is stored next to the
+//! quote and written to the `cdk::audit` log, rejected quotes included.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cdk_common::database::{self, MintTransaction};
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{quote_kv_key, Mint, QuoteId, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::nuts::{CurrencyUnit, PaymentMethod};
+use crate::{Amount, Error};
+
+const CDK_MINT_RISK_SECONDARY_NAMESPACE: &str = "risk";
+
+/// Operation a quote is created for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteOperation {
+    /// Mint quote, the user pays the mint
+    Mint,
+    /// Melt quote, the mint pays for the user
+    Melt,
+}
+
+impl fmt::Display for QuoteOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteOperation::Mint => write!(f, "mint"),
+            QuoteOperation::Melt => write!(f, "melt"),
+        }
+    }
+}
+
+/// Quote submitted to a [`RiskPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    /// Id of the quote
+    pub quote_id: QuoteId,
+    /// Operation of the quote
+    pub operation: QuoteOperation,
+    /// Unit of the quote
+    pub unit: CurrencyUnit,
+    /// Payment method of the quote
+    pub payment_method: PaymentMethod,
+    /// Amount of the quote, if it has one
+    pub amount: Option<Amount>,
+    /// Payment request paid by the mint
+    ///
+    /// Mint quotes are assessed before the backend creates their payment request.
+    pub request: Option<String>,
+}
+
+/// Decision of a [`RiskPolicy`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskDecision {
+    /// Whether the quote is accepted
+    pub allow: bool,
+    /// Risk score assigned by the policy
+    #[serde(default)]
+    pub score: Option<f64>,
+    /// Reason given by the policy
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Decision recorded for a quote
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskRecord {
+    /// Quote that was assessed
+    pub assessment: RiskAssessment,
+    /// Decision applied to the quote
+    pub decision: RiskDecision,
+    /// Error of the policy when the decision was made by the fail open setting
+    pub policy_error: Option<String>,
+    /// Unix time of the decision
+    pub time: u64,
+}
+
+/// Policy deciding whether quotes are accepted
+#[async_trait]
+pub trait RiskPolicy: Send + Sync {
+    /// Assess a quote before it is stored
+    async fn assess(&self, assessment: &RiskAssessment) -> Result<RiskDecision, Error>;
+}
+
+/// Risk policy consulted by the mint, with the bounds of the consultation
+#[derive(Clone)]
+pub(crate) struct RiskCheck {
+    pub(crate) policy: Arc<dyn RiskPolicy>,
+    pub(crate) timeout: Duration,
+    pub(crate) fail_open: bool,
+}
+
+/// Risk policy of an external HTTP service
+///
+/// The [`RiskAssessment`] is posted as JSON to the service, which answers with a
+/// [`RiskDecision`].
+#[derive(Debug, Clone)]
+pub struct HttpRiskPolicy {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl HttpRiskPolicy {
+    /// Policy of the service at `url`
+    pub fn new(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl RiskPolicy for HttpRiskPolicy {
+    async fn assess(&self, assessment: &RiskAssessment) -> Result<RiskDecision, Error> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(assessment)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| Error::Custom(format!("Risk service request failed: {err}")))?;
+
+        response
+            .json()
+            .await
+            .map_err(|err| Error::Custom(format!("Invalid risk service response: {err}")))
+    }
+}
+
+impl RiskRecord {
+    /// Store the record with the quote it was made for
+    pub(crate) async fn write(
+        &self,
+        tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+    ) -> Result<(), Error> {
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_RISK_SECONDARY_NAMESPACE,
+            &quote_kv_key(&self.assessment.quote_id),
+            &serde_json::to_vec(self)?,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Mint {
+    /// Consult the risk policy about a quote
+    ///
+    /// Returns the record to store with the quote, or `None` without a policy. A
+    /// rejected quote is recorded on its own and returns [`Error::QuoteRejected`].
+    #[instrument(skip_all)]
+    pub(crate) async fn assess_quote_risk(
+        &self,
+        assessment: RiskAssessment,
+    ) -> Result<Option<RiskRecord>, Error> {
+        let Some(risk_check) = self.risk_check.as_ref() else {
+            return Ok(None);
+        };
+
+        let result =
+            tokio::time::timeout(risk_check.timeout, risk_check.policy.assess(&assessment))
+                .await
+                .unwrap_or_else(|_| Err(Error::Custom("Risk policy timed out".to_string())));
+
+        let (decision, policy_error) = match result {
+            Ok(decision) => (decision, None),
+            Err(err) => {
+                tracing::warn!(
+                    "Risk policy failed for {} quote {}: {}",
+                    assessment.operation,
+                    assessment.quote_id,
+                    err
+                );
+
+                (
+                    RiskDecision {
+                        allow: risk_check.fail_open,
+                        score: None,
+                        reason: None,
+                    },
+                    Some(err.to_string()),
+                )
+            }
+        };
+
+        tracing::info!(
+            target: "cdk::audit",
+            quote_id = %assessment.quote_id,
+            operation = %assessment.operation,
+            unit = %assessment.unit,
+            amount = ?assessment.amount,
+            allow = decision.allow,
+            score = ?decision.score,
+            reason = ?decision.reason,
+            policy_error = ?policy_error,
+            "Risk decision"
+        );
+
+        let record = RiskRecord {
+            assessment,
+            decision,
+            policy_error,
+            time: unix_time(),
+        };
+
+        if !record.decision.allow {
+            let mut tx = self.localstore.begin_transaction().await?;
+            record.write(&mut tx).await?;
+            tx.commit().await?;
+
+            return Err(Error::QuoteRejected);
+        }
+
+        Ok(Some(record))
+    }
+
+    /// Risk decision recorded for a quote
+    #[instrument(skip(self))]
+    pub async fn quote_risk_record(&self, quote_id: &QuoteId) -> Result<Option<RiskRecord>, Error> {
+        let record = self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_RISK_SECONDARY_NAMESPACE,
+                &quote_kv_key(quote_id),
+            )
+            .await?;
+
+        Ok(record
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+}
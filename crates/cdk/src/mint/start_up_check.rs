@@ -3,9 +3,18 @@
 //! These checks are need in the case the mint was offline and the lightning node was node.
 //! These ensure that the status of the mint or melt quote matches in the mint db and on the node.
 
+use cdk_common::database::{self, MintTransaction};
+use cdk_common::quote_id::QuoteId;
+use tracing::instrument;
+
+use super::ledger::{record_ledger_transaction, LedgerTransaction};
+use super::melt::{melt_attempted, remove_melt_attempt};
 use super::{Error, Mint};
+use crate::amount::to_unit;
 use crate::mint::{MeltQuote, MeltQuoteState, PaymentMethod};
+use crate::nuts::State;
 use crate::types::PaymentProcessorKey;
+use crate::Amount;
 
 impl Mint {
     /// Checks the states of melt quotes that are **PENDING** or **UNKNOWN** to the mint with the ln node
@@ -25,58 +34,139 @@ impl Mint {
         let mut tx = self.localstore.begin_transaction().await?;
 
         for pending_quote in pending_quotes {
-            tracing::debug!("Checking status for melt quote {}.", pending_quote.id);
-
-            let ln_key = PaymentProcessorKey {
-                unit: pending_quote.unit,
-                method: PaymentMethod::Bolt11,
-            };
-
-            let ln_backend = match self.payment_processors.get(&ln_key) {
-                Some(ln_backend) => ln_backend,
-                None => {
-                    tracing::warn!("No backend for ln key: {:?}", ln_key);
-                    continue;
-                }
-            };
-
-            if let Some(lookup_id) = pending_quote.request_lookup_id {
-                let pay_invoice_response = ln_backend.check_outgoing_payment(&lookup_id).await?;
-
-                tracing::warn!(
-                    "There is no stored melt request for pending melt quote: {}",
-                    pending_quote.id
-                );
-
-                let melt_quote_state = match pay_invoice_response.status {
-                    MeltQuoteState::Unpaid => MeltQuoteState::Unpaid,
-                    MeltQuoteState::Paid => MeltQuoteState::Paid,
-                    MeltQuoteState::Pending => MeltQuoteState::Pending,
-                    MeltQuoteState::Failed => MeltQuoteState::Unpaid,
-                    MeltQuoteState::Unknown => MeltQuoteState::Unpaid,
-                };
-
-                if let Err(err) = tx
-                    .update_melt_quote_state(
-                        &pending_quote.id,
-                        melt_quote_state,
-                        pay_invoice_response.payment_proof,
-                    )
-                    .await
-                {
-                    tracing::error!(
-                        "Could not update quote {} to state {}, current state {}, {}",
-                        pending_quote.id,
-                        melt_quote_state,
-                        pending_quote.state,
-                        err
-                    );
-                };
-            }
+            self.reconcile_pending_melt_quote(&mut tx, pending_quote)
+                .await?;
         }
 
         tx.commit().await?;
 
         Ok(())
     }
+
+    /// Checks the state of a single melt quote that is **PENDING** or **UNKNOWN** with the ln node
+    ///
+    /// Lets the operator settle a quote stuck after a backend outage without
+    /// restarting the mint. Quotes in any other state are returned as they are.
+    ///
+    /// Quotes with a recorded payment attempt are refused with
+    /// [`Error::PendingQuote`]: the payment may still be in flight and not yet
+    /// known to the ln node, they are settled by the melt or the crash recovery
+    /// of the mint instead.
+    #[instrument(skip(self))]
+    pub async fn check_pending_melt_quote(
+        &self,
+        quote_id: &QuoteId,
+    ) -> Result<MeltQuoteState, Error> {
+        let quote = self
+            .localstore
+            .get_melt_quote(quote_id)
+            .await?
+            .ok_or(Error::UnknownQuote)?;
+
+        if quote.state != MeltQuoteState::Pending && quote.state != MeltQuoteState::Unknown {
+            return Ok(quote.state);
+        }
+
+        let mut tx = self.localstore.begin_transaction().await?;
+
+        if melt_attempted(&mut tx, &quote.id).await? {
+            tx.rollback().await?;
+            return Err(Error::PendingQuote);
+        }
+
+        let state = self.reconcile_pending_melt_quote(&mut tx, quote).await?;
+        tx.commit().await?;
+
+        Ok(state)
+    }
+
+    /// Updates a pending melt quote to the state of its payment on the ln node
+    pub(super) async fn reconcile_pending_melt_quote(
+        &self,
+        tx: &mut Box<dyn MintTransaction<'_, database::Error> + Send + Sync + '_>,
+        pending_quote: MeltQuote,
+    ) -> Result<MeltQuoteState, Error> {
+        tracing::debug!("Checking status for melt quote {}.", pending_quote.id);
+
+        let ln_key = PaymentProcessorKey {
+            unit: pending_quote.unit.clone(),
+            method: PaymentMethod::Bolt11,
+        };
+
+        let ln_backend = match self.payment_processors.get(&ln_key) {
+            Some(ln_backend) => ln_backend,
+            None => {
+                tracing::warn!("No backend for ln key: {:?}", ln_key);
+                return Ok(pending_quote.state);
+            }
+        };
+
+        let lookup_id = match pending_quote.request_lookup_id {
+            Some(lookup_id) => lookup_id,
+            None => return Ok(pending_quote.state),
+        };
+
+        let pay_invoice_response = ln_backend.check_outgoing_payment(&lookup_id).await?;
+
+        tracing::warn!(
+            "There is no stored melt request for pending melt quote: {}",
+            pending_quote.id
+        );
+
+        let melt_quote_state = match pay_invoice_response.status {
+            MeltQuoteState::Unpaid => MeltQuoteState::Unpaid,
+            MeltQuoteState::Paid => MeltQuoteState::Paid,
+            MeltQuoteState::Pending => MeltQuoteState::Pending,
+            MeltQuoteState::Failed => MeltQuoteState::Unpaid,
+            MeltQuoteState::Unknown => MeltQuoteState::Unpaid,
+        };
+
+        if let Err(err) = tx
+            .update_melt_quote_state(
+                &pending_quote.id,
+                melt_quote_state,
+                pay_invoice_response.payment_proof,
+            )
+            .await
+        {
+            tracing::error!(
+                "Could not update quote {} to state {}, current state {}, {}",
+                pending_quote.id,
+                melt_quote_state,
+                pending_quote.state,
+                err
+            );
+            return Ok(pending_quote.state);
+        };
+
+        if melt_quote_state == MeltQuoteState::Paid {
+            remove_melt_attempt(tx, &pending_quote.id).await?;
+
+            // The inputs paid for the melt, spend them as the melt would have
+            let input_ys = tx.get_proof_ys_by_quote_id(&pending_quote.id).await?;
+            tx.update_proofs_states(&input_ys, State::Spent).await?;
+
+            let inputs_amount = Amount::try_sum(
+                self.localstore
+                    .get_proofs_by_ys(&input_ys)
+                    .await?
+                    .iter()
+                    .flatten()
+                    .map(|proof| proof.amount),
+            )?;
+            let total_spent = to_unit(
+                pay_invoice_response.total_spent,
+                &pay_invoice_response.unit,
+                &pending_quote.unit,
+            )?;
+
+            record_ledger_transaction(
+                tx,
+                LedgerTransaction::melt(&pending_quote.unit, inputs_amount, total_spent)?,
+            )
+            .await?;
+        }
+
+        Ok(melt_quote_state)
+    }
 }
@@ -1,6 +1,7 @@
 //! Specific Subscription for the cdk crate
 use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
 
 use cdk_common::common::PaymentProcessorKey;
 use cdk_common::database::DynMintDatabase;
@@ -8,40 +9,71 @@ use cdk_common::mint::MintQuote;
 use cdk_common::nut17::Notification;
 use cdk_common::payment::DynMintPayment;
 use cdk_common::quote_id::QuoteId;
-use cdk_common::{Amount, MintQuoteBolt12Response, NotificationPayload, PaymentMethod};
+use cdk_common::{
+    Amount, MintQuoteBolt12Response, NotificationPayload, PaymentMethod, QuoteTimestamps,
+};
 
 use super::OnSubscription;
+use crate::mint::sign_quote_timestamps;
 use crate::nuts::{
     BlindSignature, MeltQuoteBolt11Response, MeltQuoteState, MintQuoteBolt11Response,
-    MintQuoteState, ProofState,
+    MintQuoteState, ProofState, SecretKey,
 };
 use crate::pub_sub;
 
+/// Sign the quote timestamps of a notification with the mint identity key
+pub(super) fn sign_notification_timestamps(
+    signing_key: Option<&SecretKey>,
+    payload: NotificationPayload<QuoteId>,
+) -> NotificationPayload<QuoteId> {
+    let Some(signing_key) = signing_key else {
+        return payload;
+    };
+
+    let sign = |quote_id: &QuoteId, timestamps: Option<QuoteTimestamps>| {
+        timestamps.map(|timestamps| sign_quote_timestamps(Some(signing_key), quote_id, timestamps))
+    };
+
+    match payload {
+        NotificationPayload::MeltQuoteBolt11Response(mut response) => {
+            response.timestamps = sign(&response.quote, response.timestamps);
+            NotificationPayload::MeltQuoteBolt11Response(response)
+        }
+        NotificationPayload::MintQuoteBolt11Response(mut response) => {
+            response.timestamps = sign(&response.quote, response.timestamps);
+            NotificationPayload::MintQuoteBolt11Response(response)
+        }
+        NotificationPayload::MintQuoteBolt12Response(mut response) => {
+            response.timestamps = sign(&response.quote, response.timestamps);
+            NotificationPayload::MintQuoteBolt12Response(response)
+        }
+        NotificationPayload::ProofState(_) => payload,
+    }
+}
+
 /// Manager
 /// Publish–subscribe manager
 ///
 /// Nut-17 implementation is system-wide and not only through the WebSocket, so
 /// it is possible for another part of the system to subscribe to events.
-pub struct PubSubManager(
-    pub_sub::Manager<NotificationPayload<QuoteId>, Notification, OnSubscription>,
-);
+pub struct PubSubManager {
+    inner: pub_sub::Manager<NotificationPayload<QuoteId>, Notification, OnSubscription>,
+    /// Mint identity key signing the quote timestamps, shared with [`OnSubscription`]
+    quote_signing_key: Arc<OnceLock<SecretKey>>,
+}
 
-#[allow(clippy::default_constructed_unit_structs)]
 impl Default for PubSubManager {
     fn default() -> Self {
-        PubSubManager(OnSubscription::default().into())
+        PubSubManager::from_on_subscription(OnSubscription::default())
     }
 }
 
 impl From<DynMintDatabase> for PubSubManager {
     fn from(val: DynMintDatabase) -> Self {
-        PubSubManager(
-            OnSubscription {
-                localstore: Some(val),
-                payment_processors: None,
-            }
-            .into(),
-        )
+        PubSubManager::from_on_subscription(OnSubscription {
+            localstore: Some(val),
+            ..Default::default()
+        })
     }
 }
 
@@ -51,13 +83,37 @@ impl PubSubManager {
         localstore: DynMintDatabase,
         payment_processors: HashMap<PaymentProcessorKey, DynMintPayment>,
     ) -> Self {
-        PubSubManager(
-            OnSubscription {
-                localstore: Some(localstore),
-                payment_processors: Some(payment_processors),
-            }
-            .into(),
-        )
+        PubSubManager::from_on_subscription(OnSubscription {
+            localstore: Some(localstore),
+            payment_processors: Some(payment_processors),
+            ..Default::default()
+        })
+    }
+
+    fn from_on_subscription(on_subscription: OnSubscription) -> Self {
+        let quote_signing_key = Arc::clone(&on_subscription.quote_signing_key);
+
+        PubSubManager {
+            inner: on_subscription.into(),
+            quote_signing_key,
+        }
+    }
+
+    /// Set the mint identity key signing the timestamps of quote notifications
+    ///
+    /// The key can only be set once, later calls are ignored.
+    pub fn set_quote_signing_key(&self, secret_key: SecretKey) {
+        if self.quote_signing_key.set(secret_key).is_err() {
+            tracing::warn!("Quote signing key of the subscription manager is already set");
+        }
+    }
+
+    /// Broadcasts an event to all listeners, signing the timestamps of quotes
+    pub fn broadcast(&self, event: NotificationPayload<QuoteId>) {
+        self.inner.broadcast(sign_notification_timestamps(
+            self.quote_signing_key.get(),
+            event,
+        ));
     }
 }
 
@@ -65,7 +121,7 @@ impl Deref for PubSubManager {
     type Target = pub_sub::Manager<NotificationPayload<QuoteId>, Notification, OnSubscription>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.inner
     }
 }
 
@@ -163,6 +219,7 @@ impl PubSubManager {
 mod test {
     use std::time::Duration;
 
+    use cdk_common::util::unix_time;
     use tokio::time::sleep;
 
     use super::*;
@@ -173,13 +230,11 @@ mod test {
     #[tokio::test]
     async fn active_and_drop() {
         let manager = PubSubManager::default();
-        let params: IndexableParams = Params {
-            kind: Kind::ProofState,
-            filters: vec![
-                "02a9acc1e48c25eeeb9289b5031cc57da9fe72f3fe2861d264bdc074209b107ba2".to_owned(),
-            ],
-            id: "uno".into(),
-        }
+        let params: IndexableParams = Params::new(
+            Kind::ProofState,
+            vec!["02a9acc1e48c25eeeb9289b5031cc57da9fe72f3fe2861d264bdc074209b107ba2".to_owned()],
+            "uno".into(),
+        )
         .into();
 
         // Although the same param is used, two subscriptions are created, that
@@ -210,28 +265,28 @@ mod test {
         let mut subscriptions = [
             manager
                 .try_subscribe::<IndexableParams>(
-                    Params {
-                        kind: Kind::ProofState,
-                        filters: vec![
+                    Params::new(
+                        Kind::ProofState,
+                        vec![
                             "02194603ffa36356f4a56b7df9371fc3192472351453ec7398b8da8117e7c3e104"
                                 .to_string(),
                         ],
-                        id: "uno".into(),
-                    }
+                        "uno".into(),
+                    )
                     .into(),
                 )
                 .await
                 .expect("valid subscription"),
             manager
                 .try_subscribe::<IndexableParams>(
-                    Params {
-                        kind: Kind::ProofState,
-                        filters: vec![
+                    Params::new(
+                        Kind::ProofState,
+                        vec![
                             "02194603ffa36356f4a56b7df9371fc3192472351453ec7398b8da8117e7c3e104"
                                 .to_string(),
                         ],
-                        id: "dos".into(),
-                    }
+                        "dos".into(),
+                    )
                     .into(),
                 )
                 .await
@@ -261,6 +316,87 @@ mod test {
         assert!(subscriptions[1].try_recv().is_err());
     }
 
+    #[tokio::test]
+    async fn replay_missed_events() {
+        let manager = PubSubManager::default();
+        manager.set_replay_buffer(pub_sub::DEFAULT_REPLAY_SIZE, pub_sub::DEFAULT_REPLAY_WINDOW);
+        let params = || -> IndexableParams {
+            Params::new(
+                Kind::ProofState,
+                vec![
+                    "02194603ffa36356f4a56b7df9371fc3192472351453ec7398b8da8117e7c3e104"
+                        .to_string(),
+                ],
+                "uno".into(),
+            )
+            .into()
+        };
+        let y = PublicKey::from_hex(
+            "02194603ffa36356f4a56b7df9371fc3192472351453ec7398b8da8117e7c3e104",
+        )
+        .expect("valid pk");
+
+        let disconnected_at = unix_time();
+
+        // Broadcast while nobody is subscribed
+        for state in [State::Pending, State::Spent] {
+            manager
+                .broadcast_async(
+                    ProofState {
+                        y,
+                        state,
+                        witness: None,
+                    }
+                    .into(),
+                )
+                .await;
+        }
+        manager
+            .broadcast_async(
+                ProofState {
+                    y: PublicKey::from_hex(
+                        "020000000000000000000000000000000000000000000000000000000000000001",
+                    )
+                    .expect("valid pk"),
+                    state: State::Spent,
+                    witness: None,
+                }
+                .into(),
+            )
+            .await;
+
+        let mut resumed = manager
+            .try_subscribe_since(params(), Some(disconnected_at))
+            .await
+            .expect("valid subscription");
+        let mut fresh = manager
+            .try_subscribe(params())
+            .await
+            .expect("valid subscription");
+
+        sleep(Duration::from_millis(10)).await;
+
+        // Only the missed events of the subscribed proof, in broadcast order
+        for expected in [State::Pending, State::Spent] {
+            let (_, msg) = resumed.try_recv().expect("valid message");
+            let NotificationPayload::ProofState(proof_state) = msg else {
+                panic!("Expected a proof state notification");
+            };
+            assert_eq!(proof_state.y, y);
+            assert_eq!(proof_state.state, expected);
+        }
+        assert!(resumed.try_recv().is_err());
+        assert!(fresh.try_recv().is_err());
+
+        // Nothing is replayed from after the events
+        let mut later = manager
+            .try_subscribe_since(params(), Some(unix_time() + 1))
+            .await
+            .expect("valid subscription");
+        sleep(Duration::from_millis(10)).await;
+        assert!(later.try_recv().is_err());
+    }
+
     #[test]
     fn parsing_request() {
         let json = r#"{"kind":"proof_state","filters":["x"],"subId":"uno"}"#;
@@ -270,6 +406,37 @@ mod test {
         assert_eq!(*params.id, "uno");
     }
 
+    #[tokio::test]
+    async fn no_replay_without_buffer() {
+        let manager = PubSubManager::default();
+        let y = PublicKey::from_hex(
+            "02194603ffa36356f4a56b7df9371fc3192472351453ec7398b8da8117e7c3e104",
+        )
+        .expect("valid pk");
+
+        let disconnected_at = unix_time();
+        manager
+            .broadcast_async(
+                ProofState {
+                    y,
+                    state: State::Spent,
+                    witness: None,
+                }
+                .into(),
+            )
+            .await;
+
+        let params: IndexableParams =
+            Params::new(Kind::ProofState, vec![y.to_string()], "uno".into()).into();
+        let mut resumed = manager
+            .try_subscribe_since(params, Some(disconnected_at))
+            .await
+            .expect("valid subscription");
+
+        sleep(Duration::from_millis(10)).await;
+        assert!(resumed.try_recv().is_err());
+    }
+
     #[tokio::test]
     async fn json_test() {
         let manager = PubSubManager::default();
@@ -314,4 +481,53 @@ mod test {
         );
         assert!(subscription.try_recv().is_err());
     }
+
+    #[tokio::test]
+    async fn signed_quote_timestamps() {
+        let manager = PubSubManager::default();
+        let secret_key = SecretKey::generate();
+        manager.set_quote_signing_key(secret_key.clone());
+
+        let quote_id = QuoteId::new_uuid();
+        let mut subscription = manager
+            .try_subscribe::<IndexableParams>(
+                Params::new(
+                    Kind::Bolt11MintQuote,
+                    vec![quote_id.to_string()],
+                    "uno".into(),
+                )
+                .into(),
+            )
+            .await
+            .expect("valid subscription");
+
+        manager.mint_quote_bolt11_status(
+            MintQuoteBolt11Response {
+                quote: quote_id.clone(),
+                request: "lnbc".to_owned(),
+                amount: None,
+                unit: None,
+                state: MintQuoteState::Unpaid,
+                expiry: None,
+                pubkey: None,
+                timestamps: Some(QuoteTimestamps::new(
+                    1_700_000_000,
+                    Some(1_700_000_060),
+                    None,
+                )),
+            },
+            MintQuoteState::Paid,
+        );
+
+        sleep(Duration::from_millis(10)).await;
+        let (_, msg) = subscription.try_recv().expect("valid message");
+        let NotificationPayload::MintQuoteBolt11Response(response) = msg else {
+            panic!("Expected a mint quote notification");
+        };
+
+        let timestamps = response.timestamps.expect("timestamps");
+        assert!(timestamps
+            .verify_signature(&quote_id.to_string(), &secret_key.public_key())
+            .is_ok());
+    }
 }
@@ -3,6 +3,7 @@
 //! This module contains the code that is triggered when a new subscription is created.
 
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 
 use cdk_common::amount::to_unit;
 use cdk_common::common::PaymentProcessorKey;
@@ -18,7 +19,10 @@ use cdk_common::{
 };
 use tracing::instrument;
 
-use crate::nuts::{MeltQuoteBolt11Response, MintQuoteBolt11Response, ProofState, PublicKey};
+use super::manager::sign_notification_timestamps;
+use crate::nuts::{
+    MeltQuoteBolt11Response, MintQuoteBolt11Response, ProofState, PublicKey, SecretKey,
+};
 
 #[derive(Default)]
 /// Subscription Init
@@ -29,6 +33,8 @@ use crate::nuts::{MeltQuoteBolt11Response, MintQuoteBolt11Response, ProofState,
 pub struct OnSubscription {
     pub(crate) localstore: Option<DynMintDatabase>,
     pub(crate) payment_processors: Option<HashMap<PaymentProcessorKey, DynMintPayment>>,
+    /// Mint identity key signing the quote timestamps of the initial state
+    pub(crate) quote_signing_key: Arc<OnceLock<SecretKey>>,
 }
 
 impl OnSubscription {
@@ -218,6 +224,9 @@ impl OnNewSubscription for OnSubscription {
             );
         }
 
-        Ok(to_return)
+        Ok(to_return
+            .into_iter()
+            .map(|payload| sign_notification_timestamps(self.quote_signing_key.get(), payload))
+            .collect())
     }
 }
@@ -2,6 +2,7 @@
 use cdk_prometheus::METRICS;
 use tracing::instrument;
 
+use super::ledger::{record_ledger_transaction, LedgerTransaction};
 use super::nut11::{enforce_sig_flag, EnforceSigFlag};
 use super::proof_writer::ProofWriter;
 use super::{Mint, PublicKey, SigFlag, State, SwapRequest, SwapResponse};
@@ -18,10 +19,10 @@ impl Mint {
         METRICS.inc_in_flight_requests("process_swap_request");
         // Do the external call before beginning the db transaction
         // Check any overflow before talking to the signatory
-        swap_request.input_amount()?;
-        swap_request.output_amount()?;
+        let input_amount = swap_request.input_amount()?;
+        let output_amount = swap_request.output_amount()?;
 
-        let promises = self.blind_sign(swap_request.outputs().to_owned()).await?;
+        // Verify the inputs before signing so invalid requests cost the mint no signatures
         let input_verification =
             self.verify_inputs(swap_request.inputs())
                 .await
@@ -29,6 +30,8 @@ impl Mint {
                     tracing::debug!("Input verification failed: {:?}", err);
                     err
                 })?;
+        let promises = self.blind_sign(swap_request.outputs().to_owned()).await?;
+        let unit = input_verification.unit.clone();
         let mut tx = self.localstore.begin_transaction().await?;
 
         if let Err(err) = self
@@ -97,6 +100,15 @@ impl Mint {
         )
         .await?;
 
+        if let Some(unit) = unit {
+            let fee = self.get_proofs_fee(swap_request.inputs()).await?;
+            record_ledger_transaction(
+                &mut tx,
+                LedgerTransaction::swap(&unit, input_amount, output_amount, fee)?,
+            )
+            .await?;
+        }
+
         proof_writer.commit();
         tx.commit().await?;
 
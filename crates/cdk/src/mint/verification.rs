@@ -143,6 +143,33 @@ impl Mint {
             .expect("Length is check above"))
     }
 
+    /// Verify that the mint has a key for the amount of every input
+    ///
+    /// Cheap check done before any signature is verified, so requests with
+    /// amounts the mint never signed are refused without elliptic curve work.
+    #[instrument(skip_all)]
+    pub fn verify_inputs_amounts(&self, inputs: &Proofs) -> Result<(), Error> {
+        let keysets = self.keysets.load();
+
+        for proof in inputs {
+            let keyset = keysets
+                .iter()
+                .find(|keyset| keyset.id == proof.keyset_id)
+                .ok_or(Error::UnknownKeySet)?;
+
+            if keyset.keys.amount_key(proof.amount).is_none() {
+                tracing::debug!(
+                    "Transaction attempted with input amount {} unknown to keyset {}.",
+                    proof.amount,
+                    proof.keyset_id
+                );
+                return Err(Error::AmountKey);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Verifies that the outputs have not already been signed
     #[instrument(skip_all)]
     pub async fn check_output_already_signed(
@@ -203,6 +230,7 @@ impl Mint {
     pub async fn verify_inputs(&self, inputs: &Proofs) -> Result<Verification, Error> {
         Mint::check_inputs_unique(inputs)?;
         let unit = self.verify_inputs_keyset(inputs).await?;
+        self.verify_inputs_amounts(inputs)?;
         let amount = inputs.total_amount()?;
 
         self.verify_proofs(inputs.clone()).await?;
@@ -0,0 +1,288 @@
+//! Vouchers
+//!
+//! Closed-loop deployments, such as loyalty points or gift cards, use the mint
+//! for a unit that has no payment backend. The operator issues ecash of a
+//! voucher unit by signing outputs directly instead of through a paid mint
+//! quote, and merchants redeem it, which spends the proofs without paying
+//! anything out. Wallets swap vouchers between each other like any other ecash.
+//!
+//! Issuances and redemptions are recorded in the KV store, so the operator can
+//! report what was issued, redeemed and is still outstanding.
+
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::ledger::{record_ledger_transaction, LedgerTransaction};
+use super::proof_writer::ProofWriter;
+use super::{Mint, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::{BlindSignature, BlindedMessage, CurrencyUnit, Proofs, PublicKey, State};
+use crate::{ensure_cdk, Amount, Error};
+
+const CDK_MINT_VOUCHER_ISSUANCE_SECONDARY_NAMESPACE: &str = "voucher_issuance";
+const CDK_MINT_VOUCHER_REDEMPTION_SECONDARY_NAMESPACE: &str = "voucher_redemption";
+
+/// Vouchers issued by the operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoucherIssuance {
+    /// Id of the issuance, the hex `B_` of its first output
+    pub id: String,
+    /// Unit of the vouchers
+    pub unit: CurrencyUnit,
+    /// Amount issued
+    pub amount: Amount,
+    /// Unix time of the issuance
+    pub timestamp: u64,
+    /// Reference of the operator, such as a campaign or customer id
+    pub reference: Option<String>,
+}
+
+/// Vouchers redeemed by a merchant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoucherRedemption {
+    /// Id of the redemption, the hex `Y` of its first proof
+    pub id: String,
+    /// Unit of the vouchers
+    pub unit: CurrencyUnit,
+    /// Amount redeemed, the proofs minus the input fee
+    pub amount: Amount,
+    /// Input fee kept by the mint
+    pub fee: Amount,
+    /// Unix time of the redemption
+    pub timestamp: u64,
+    /// Reference of the merchant, such as an order id
+    pub reference: Option<String>,
+}
+
+/// Totals of a voucher unit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoucherReport {
+    /// Unit of the vouchers
+    pub unit: CurrencyUnit,
+    /// Amount issued
+    pub issued: Amount,
+    /// Amount redeemed, without input fees
+    pub redeemed: Amount,
+    /// Input fees kept on redemptions
+    pub fees: Amount,
+    /// Amount issued and not redeemed yet
+    pub outstanding: Amount,
+    /// Number of issuances
+    pub issuances: u64,
+    /// Number of redemptions
+    pub redemptions: u64,
+}
+
+impl Mint {
+    /// Units the mint issues vouchers in
+    pub fn voucher_units(&self) -> &[CurrencyUnit] {
+        &self.voucher_units
+    }
+
+    /// Sign `outputs` of a voucher unit without a mint quote
+    ///
+    /// Callers must restrict this to the operator, anyone able to call it can
+    /// issue vouchers.
+    #[instrument(skip(self, outputs))]
+    pub async fn issue_vouchers(
+        &self,
+        outputs: Vec<BlindedMessage>,
+        reference: Option<String>,
+    ) -> Result<Vec<BlindSignature>, Error> {
+        ensure_cdk!(!outputs.is_empty(), Error::AmountUndefined);
+
+        // Checked before signing, outputs of any other unit must never be signed here
+        Mint::check_outputs_unique(&outputs)?;
+        let unit = self.verify_outputs_keyset(&outputs)?;
+        ensure_cdk!(self.voucher_units.contains(&unit), Error::UnsupportedUnit);
+
+        let blind_signatures = self.blind_sign(outputs.clone()).await?;
+
+        let mut tx = self.localstore.begin_transaction().await?;
+
+        let verification = self.verify_outputs(&mut tx, &outputs).await?;
+
+        let blinded_secrets = outputs
+            .iter()
+            .map(|output| output.blinded_secret)
+            .collect::<Vec<PublicKey>>();
+
+        tx.add_blind_signatures(&blinded_secrets, &blind_signatures, None)
+            .await?;
+
+        let issuance = VoucherIssuance {
+            id: blinded_secrets[0].to_hex(),
+            unit: unit.clone(),
+            amount: verification.amount,
+            timestamp: unix_time(),
+            reference,
+        };
+
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_VOUCHER_ISSUANCE_SECONDARY_NAMESPACE,
+            &issuance.id,
+            &serde_json::to_vec(&issuance)?,
+        )
+        .await?;
+
+        // The operator stands in for the payment backend
+        record_ledger_transaction(&mut tx, LedgerTransaction::issue(&unit, issuance.amount)?)
+            .await?;
+
+        tx.commit().await?;
+
+        tracing::info!("Issued {} {} of vouchers", issuance.amount, unit);
+
+        Ok(blind_signatures)
+    }
+
+    /// Spend `inputs` of a voucher unit without paying anything out
+    #[instrument(skip(self, inputs))]
+    pub async fn redeem_vouchers(
+        &self,
+        inputs: Proofs,
+        reference: Option<String>,
+    ) -> Result<VoucherRedemption, Error> {
+        ensure_cdk!(!inputs.is_empty(), Error::AmountUndefined);
+
+        let verification = self.verify_inputs(&inputs).await?;
+        let unit = verification.unit.ok_or(Error::UnsupportedUnit)?;
+        ensure_cdk!(self.voucher_units.contains(&unit), Error::UnsupportedUnit);
+
+        let fee = self.get_proofs_fee(&inputs).await?;
+        let amount = verification
+            .amount
+            .checked_sub(fee)
+            .ok_or(Error::TransactionUnbalanced(
+                verification.amount.into(),
+                0,
+                fee.into(),
+            ))?;
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        let mut proof_writer =
+            ProofWriter::new(self.localstore.clone(), self.pubsub_manager.clone());
+
+        let ys = proof_writer.add_proofs(&mut tx, &inputs, None).await?;
+        proof_writer
+            .update_proofs_states(&mut tx, &ys, State::Spent)
+            .await?;
+
+        let redemption = VoucherRedemption {
+            id: ys[0].to_hex(),
+            unit: unit.clone(),
+            amount,
+            fee,
+            timestamp: unix_time(),
+            reference,
+        };
+
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_VOUCHER_REDEMPTION_SECONDARY_NAMESPACE,
+            &redemption.id,
+            &serde_json::to_vec(&redemption)?,
+        )
+        .await?;
+
+        record_ledger_transaction(
+            &mut tx,
+            LedgerTransaction::melt(&unit, verification.amount, amount)?,
+        )
+        .await?;
+
+        proof_writer.commit();
+        tx.commit().await?;
+
+        tracing::info!("Redeemed {} {} of vouchers", amount, unit);
+
+        Ok(redemption)
+    }
+
+    /// Voucher issuances, oldest first
+    #[instrument(skip_all)]
+    pub async fn voucher_issuances(&self) -> Result<Vec<VoucherIssuance>, Error> {
+        let mut issuances: Vec<VoucherIssuance> = self
+            .read_voucher_records(CDK_MINT_VOUCHER_ISSUANCE_SECONDARY_NAMESPACE)
+            .await?;
+        issuances.sort_by_key(|issuance| issuance.timestamp);
+
+        Ok(issuances)
+    }
+
+    /// Voucher redemptions, oldest first
+    #[instrument(skip_all)]
+    pub async fn voucher_redemptions(&self) -> Result<Vec<VoucherRedemption>, Error> {
+        let mut redemptions: Vec<VoucherRedemption> = self
+            .read_voucher_records(CDK_MINT_VOUCHER_REDEMPTION_SECONDARY_NAMESPACE)
+            .await?;
+        redemptions.sort_by_key(|redemption| redemption.timestamp);
+
+        Ok(redemptions)
+    }
+
+    /// Totals of every voucher unit
+    #[instrument(skip_all)]
+    pub async fn voucher_report(&self) -> Result<Vec<VoucherReport>, Error> {
+        let issuances = self.voucher_issuances().await?;
+        let redemptions = self.voucher_redemptions().await?;
+
+        self.voucher_units
+            .iter()
+            .map(|unit| {
+                let issuances = issuances
+                    .iter()
+                    .filter(|issuance| &issuance.unit == unit)
+                    .collect::<Vec<_>>();
+                let redemptions = redemptions
+                    .iter()
+                    .filter(|redemption| &redemption.unit == unit)
+                    .collect::<Vec<_>>();
+
+                let issued = Amount::try_sum(issuances.iter().map(|issuance| issuance.amount))?;
+                let redeemed =
+                    Amount::try_sum(redemptions.iter().map(|redemption| redemption.amount))?;
+                let fees = Amount::try_sum(redemptions.iter().map(|redemption| redemption.fee))?;
+
+                Ok(VoucherReport {
+                    unit: unit.clone(),
+                    issued,
+                    redeemed,
+                    fees,
+                    // Swap fees also reduce the vouchers outstanding, so this is an upper bound
+                    outstanding: issued
+                        .checked_sub(redeemed)
+                        .and_then(|outstanding| outstanding.checked_sub(fees))
+                        .unwrap_or(Amount::ZERO),
+                    issuances: issuances.len() as u64,
+                    redemptions: redemptions.len() as u64,
+                })
+            })
+            .collect()
+    }
+
+    async fn read_voucher_records<T>(&self, secondary_namespace: &str) -> Result<Vec<T>, Error>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let keys = self
+            .localstore
+            .kv_list(CDK_MINT_PRIMARY_NAMESPACE, secondary_namespace)
+            .await?;
+
+        let mut records = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(bytes) = self
+                .localstore
+                .kv_read(CDK_MINT_PRIMARY_NAMESPACE, secondary_namespace, &key)
+                .await?
+            {
+                records.push(serde_json::from_slice(&bytes)?);
+            }
+        }
+
+        Ok(records)
+    }
+}
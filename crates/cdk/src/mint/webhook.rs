@@ -0,0 +1,421 @@
+//! Quote webhooks
+//!
+//! Integrators can register a callback url for a quote instead of keeping a
+//! websocket open. Registrations, the state last delivered and a log of the
+//! delivery attempts are kept in the KV store, so deliveries survive restarts
+//! and can be made by any instance sharing the database.
+//!
+//! The dispatching instance keeps the webhooks it read in memory and only reads
+//! the ones registered since, refreshing them after [`WEBHOOK_CACHE_SECS`]. A
+//! state may be delivered twice when another instance took over delivery in the
+//! meantime.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use url::Url;
+
+use super::{quote_kv_key, Mint, QuoteId, QuoteOperation, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::nuts::{MeltQuoteState, MintQuoteState};
+use crate::{ensure_cdk, Error};
+
+const CDK_MINT_WEBHOOK_SECONDARY_NAMESPACE: &str = "webhook";
+/// Delivery attempts kept in the log of a webhook
+const MAX_DELIVERY_LOG: usize = 20;
+/// Seconds a webhook is kept after its quote reached a final state
+const FINISHED_WEBHOOK_RETENTION_SECS: u64 = 24 * 60 * 60;
+/// Seconds a webhook read from the store is used before it is read again
+const WEBHOOK_CACHE_SECS: u64 = 60;
+
+/// Webhooks read from the store by their key, with the unix time they were read
+#[derive(Debug, Default)]
+pub(crate) struct QuoteWebhookCache {
+    webhooks: Mutex<HashMap<String, (QuoteWebhook, u64)>>,
+}
+
+/// State change of a quote notified to webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteEventState {
+    /// Melt payment is in flight
+    Pending,
+    /// Mint quote was paid by the user, or melt quote was paid by the mint
+    Paid,
+    /// Ecash of the mint quote was issued
+    Issued,
+    /// Melt payment failed
+    Failed,
+    /// Quote expired unpaid
+    Expired,
+}
+
+impl QuoteEventState {
+    /// Whether the quote will not change state anymore
+    fn is_final(&self, operation: QuoteOperation) -> bool {
+        match self {
+            QuoteEventState::Expired | QuoteEventState::Issued => true,
+            QuoteEventState::Paid => operation == QuoteOperation::Melt,
+            QuoteEventState::Pending | QuoteEventState::Failed => false,
+        }
+    }
+}
+
+/// Notification posted to a webhook
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteEvent {
+    /// Id of the quote
+    pub quote: QuoteId,
+    /// Operation of the quote
+    pub operation: QuoteOperation,
+    /// New state of the quote
+    pub state: QuoteEventState,
+    /// Unix time of the notification
+    pub timestamp: u64,
+}
+
+/// Attempt to deliver a [`QuoteEvent`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    /// State that was delivered
+    pub state: QuoteEventState,
+    /// Unix time of the attempt
+    pub timestamp: u64,
+    /// HTTP status returned by the webhook
+    pub status: Option<u16>,
+    /// Error of a failed attempt
+    pub error: Option<String>,
+}
+
+impl WebhookDelivery {
+    /// Whether the webhook accepted the notification
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+            && self
+                .status
+                .is_some_and(|status| (200..300).contains(&status))
+    }
+}
+
+/// Callback url registered for a quote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteWebhook {
+    /// Id of the quote
+    pub quote_id: QuoteId,
+    /// Operation of the quote
+    pub operation: QuoteOperation,
+    /// Url notifications are posted to
+    pub url: String,
+    /// Unix time of the registration
+    pub created: u64,
+    /// Last state delivered or given up on
+    pub delivered_state: Option<QuoteEventState>,
+    /// Failed attempts to deliver the current state
+    pub attempts: u32,
+    /// Unix time before which delivery is not retried
+    pub retry_at: u64,
+    /// Last delivery attempts, oldest first
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+impl Mint {
+    /// Register a callback url notified of the state changes of a quote
+    #[instrument(skip(self))]
+    pub async fn register_quote_webhook(
+        &self,
+        quote_id: &QuoteId,
+        url: &str,
+    ) -> Result<QuoteWebhook, Error> {
+        let parsed = Url::parse(url).map_err(|_| Error::Custom(format!("Invalid url {url}")))?;
+        ensure_cdk!(
+            matches!(parsed.scheme(), "http" | "https"),
+            Error::Custom(format!("Webhook url {url} must be http or https"))
+        );
+
+        let operation = if self.localstore.get_mint_quote(quote_id).await?.is_some() {
+            QuoteOperation::Mint
+        } else if self.localstore.get_melt_quote(quote_id).await?.is_some() {
+            QuoteOperation::Melt
+        } else {
+            return Err(Error::UnknownQuote);
+        };
+
+        let webhook = QuoteWebhook {
+            quote_id: quote_id.clone(),
+            operation,
+            url: parsed.to_string(),
+            created: unix_time(),
+            delivered_state: None,
+            attempts: 0,
+            retry_at: 0,
+            deliveries: Vec::new(),
+        };
+
+        self.write_quote_webhook(&webhook).await?;
+
+        tracing::debug!("Registered webhook for {} quote {}", operation, quote_id);
+
+        Ok(webhook)
+    }
+
+    /// Webhook registered for a quote, with its delivery log
+    #[instrument(skip(self))]
+    pub async fn quote_webhook(&self, quote_id: &QuoteId) -> Result<Option<QuoteWebhook>, Error> {
+        let webhook = self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_WEBHOOK_SECONDARY_NAMESPACE,
+                &quote_kv_key(quote_id),
+            )
+            .await?;
+
+        Ok(webhook
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    /// Webhooks with a state change to deliver now, and the event to deliver
+    ///
+    /// Webhooks whose quote reached a final state more than a day ago are removed.
+    #[instrument(skip_all)]
+    pub async fn due_quote_webhooks(&self) -> Result<Vec<(QuoteWebhook, QuoteEvent)>, Error> {
+        let now = unix_time();
+        let mut due = Vec::new();
+
+        for (key, webhook) in self.cached_quote_webhooks(now).await? {
+            if let Some(delivered_state) = webhook.delivered_state {
+                if delivered_state.is_final(webhook.operation) {
+                    let finished = webhook
+                        .deliveries
+                        .last()
+                        .map(|delivery| delivery.timestamp)
+                        .unwrap_or(webhook.created);
+
+                    if finished + FINISHED_WEBHOOK_RETENTION_SECS < now {
+                        self.remove_quote_webhook(&key).await?;
+                    }
+
+                    // The quote does not change anymore, it is not read again
+                    continue;
+                }
+            }
+
+            if webhook.retry_at > now {
+                continue;
+            }
+
+            let Some(state) = self.quote_event_state(&webhook, now).await? else {
+                continue;
+            };
+
+            if webhook.delivered_state != Some(state) {
+                let event = QuoteEvent {
+                    quote: webhook.quote_id.clone(),
+                    operation: webhook.operation,
+                    state,
+                    timestamp: now,
+                };
+
+                due.push((webhook, event));
+            }
+        }
+
+        Ok(due)
+    }
+
+    /// Registered webhooks by key, reading only new ones and ones not read for a while
+    async fn cached_quote_webhooks(&self, now: u64) -> Result<Vec<(String, QuoteWebhook)>, Error> {
+        let keys: HashSet<String> = self
+            .localstore
+            .kv_list(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_WEBHOOK_SECONDARY_NAMESPACE,
+            )
+            .await?
+            .into_iter()
+            .collect();
+
+        let stale: Vec<String> = {
+            let mut cache = self
+                .quote_webhooks
+                .webhooks
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            // Webhooks removed by another instance
+            cache.retain(|key, _| keys.contains(key));
+
+            keys.iter()
+                .filter(|key| {
+                    cache
+                        .get(*key)
+                        .is_none_or(|(_, read)| read + WEBHOOK_CACHE_SECS < now)
+                })
+                .cloned()
+                .collect()
+        };
+
+        for key in stale {
+            let webhook = self
+                .localstore
+                .kv_read(
+                    CDK_MINT_PRIMARY_NAMESPACE,
+                    CDK_MINT_WEBHOOK_SECONDARY_NAMESPACE,
+                    &key,
+                )
+                .await?
+                .map(|bytes| serde_json::from_slice::<QuoteWebhook>(&bytes))
+                .transpose()?;
+
+            let mut cache = self
+                .quote_webhooks
+                .webhooks
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            match webhook {
+                Some(webhook) => {
+                    cache.insert(key, (webhook, now));
+                }
+                None => {
+                    cache.remove(&key);
+                }
+            }
+        }
+
+        let cache = self
+            .quote_webhooks
+            .webhooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        Ok(cache
+            .iter()
+            .map(|(key, (webhook, _))| (key.clone(), webhook.clone()))
+            .collect())
+    }
+
+    /// Record an attempt to deliver a quote event
+    ///
+    /// A failed attempt is retried at `retry_at`, or given up on when it is `None`.
+    #[instrument(skip(self, delivery))]
+    pub async fn record_webhook_delivery(
+        &self,
+        quote_id: &QuoteId,
+        delivery: WebhookDelivery,
+        retry_at: Option<u64>,
+    ) -> Result<(), Error> {
+        let Some(mut webhook) = self.quote_webhook(quote_id).await? else {
+            return Ok(());
+        };
+
+        match (delivery.is_success(), retry_at) {
+            (false, Some(retry_at)) => {
+                webhook.attempts += 1;
+                webhook.retry_at = retry_at;
+            }
+            (success, _) => {
+                if !success {
+                    tracing::warn!(
+                        "Giving up delivering {:?} of quote {} to {}",
+                        delivery.state,
+                        quote_id,
+                        webhook.url
+                    );
+                }
+
+                webhook.delivered_state = Some(delivery.state);
+                webhook.attempts = 0;
+                webhook.retry_at = 0;
+            }
+        }
+
+        webhook.deliveries.push(delivery);
+        if webhook.deliveries.len() > MAX_DELIVERY_LOG {
+            webhook.deliveries.remove(0);
+        }
+
+        self.write_quote_webhook(&webhook).await
+    }
+
+    /// Current state of the quote of a webhook, `None` while there is nothing to notify
+    async fn quote_event_state(
+        &self,
+        webhook: &QuoteWebhook,
+        now: u64,
+    ) -> Result<Option<QuoteEventState>, Error> {
+        let state = match webhook.operation {
+            QuoteOperation::Mint => {
+                let Some(quote) = self.localstore.get_mint_quote(&webhook.quote_id).await? else {
+                    return Ok(None);
+                };
+
+                match quote.state() {
+                    MintQuoteState::Unpaid if quote.expiry > 0 && quote.expiry < now => {
+                        Some(QuoteEventState::Expired)
+                    }
+                    MintQuoteState::Unpaid => None,
+                    MintQuoteState::Paid => Some(QuoteEventState::Paid),
+                    MintQuoteState::Issued => Some(QuoteEventState::Issued),
+                }
+            }
+            QuoteOperation::Melt => {
+                let Some(quote) = self.localstore.get_melt_quote(&webhook.quote_id).await? else {
+                    return Ok(None);
+                };
+
+                match quote.state {
+                    MeltQuoteState::Unpaid if quote.expiry < now => Some(QuoteEventState::Expired),
+                    MeltQuoteState::Unpaid | MeltQuoteState::Unknown => None,
+                    MeltQuoteState::Pending => Some(QuoteEventState::Pending),
+                    MeltQuoteState::Paid => Some(QuoteEventState::Paid),
+                    MeltQuoteState::Failed => Some(QuoteEventState::Failed),
+                }
+            }
+        };
+
+        Ok(state)
+    }
+
+    async fn write_quote_webhook(&self, webhook: &QuoteWebhook) -> Result<(), Error> {
+        let key = quote_kv_key(&webhook.quote_id);
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_WEBHOOK_SECONDARY_NAMESPACE,
+            &key,
+            &serde_json::to_vec(webhook)?,
+        )
+        .await?;
+        tx.commit().await?;
+
+        self.quote_webhooks
+            .webhooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, (webhook.clone(), unix_time()));
+
+        Ok(())
+    }
+
+    async fn remove_quote_webhook(&self, key: &str) -> Result<(), Error> {
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.kv_remove(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_WEBHOOK_SECONDARY_NAMESPACE,
+            key,
+        )
+        .await?;
+        tx.commit().await?;
+
+        self.quote_webhooks
+            .webhooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key);
+
+        Ok(())
+    }
+}
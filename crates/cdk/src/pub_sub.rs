@@ -8,16 +8,22 @@
 //! generic type that must be converted to a vector of indexes.
 //!
 //! Events are also generic that should implement the `Indexable` trait.
+//!
+//! Managers with a replay buffer keep recently broadcast events for a short
+//! time, so a subscriber that reconnects with the time it was last connected
+//! receives the events it missed.
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::ops::{Deref, DerefMut};
 use std::sync::atomic::{self, AtomicUsize};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 pub use cdk_common::pub_sub::index::{Index, Indexable, SubscriptionGlobalId};
 use cdk_common::pub_sub::OnNewSubscription;
 pub use cdk_common::pub_sub::SubId;
+use cdk_common::util::unix_time;
 use tokio::sync::{mpsc, RwLock};
 use tokio::task::JoinHandle;
 
@@ -29,6 +35,67 @@ pub const DEFAULT_REMOVE_SIZE: usize = 10_000;
 /// Default channel size for subscription buffering
 pub const DEFAULT_CHANNEL_SIZE: usize = 10;
 
+/// Suggested number of recent events kept for replay
+pub const DEFAULT_REPLAY_SIZE: usize = 1_000;
+
+/// Suggested time recent events are kept for replay
+pub const DEFAULT_REPLAY_WINDOW: Duration = Duration::from_secs(120);
+
+/// Recently broadcast events, replayed to subscribers resuming from a time
+struct ReplayBuffer<T> {
+    events: RwLock<VecDeque<(u64, T)>>,
+    max_events: usize,
+    max_age: u64,
+}
+
+impl<T> ReplayBuffer<T>
+where
+    T: Indexable + Clone,
+{
+    fn new(max_events: usize, max_age: Duration) -> Self {
+        Self {
+            events: Default::default(),
+            max_events,
+            max_age: max_age.as_secs(),
+        }
+    }
+
+    /// Keep `event`, dropping the oldest events over the size or age limit
+    async fn push(&self, event: T) {
+        let now = unix_time();
+        let mut events = self.events.write().await;
+        events.push_back((now, event));
+
+        while events.len() > self.max_events
+            || events
+                .front()
+                .is_some_and(|(at, _)| at.saturating_add(self.max_age) < now)
+        {
+            events.pop_front();
+        }
+    }
+
+    /// Events broadcast at or after `since` matching any of `indexes`, oldest first
+    async fn since(&self, indexes: &[Index<T::Type>], since: u64) -> Vec<T> {
+        let oldest = unix_time().saturating_sub(self.max_age);
+
+        self.events
+            .read()
+            .await
+            .iter()
+            .filter(|(at, _)| *at >= since && *at >= oldest)
+            .filter(|(_, event)| {
+                event.to_indexes().iter().any(|event_index| {
+                    indexes
+                        .iter()
+                        .any(|index| event_index.cmp_prefix(index) == Ordering::Equal)
+                })
+            })
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+}
+
 /// Subscription manager
 ///
 /// This object keep track of all subscription listener and it is also
@@ -48,6 +115,8 @@ where
     unsubscription_sender: mpsc::Sender<(SubId, Vec<Index<I>>)>,
     active_subscriptions: Arc<AtomicUsize>,
     background_subscription_remover: Option<JoinHandle<()>>,
+    /// Recent events, only kept once a replay buffer is set
+    replay: Arc<OnceLock<ReplayBuffer<T>>>,
 }
 
 impl<T, I, F> Default for Manager<T, I, F>
@@ -71,6 +140,7 @@ where
             unsubscription_sender: sender,
             active_subscriptions,
             indexes: storage,
+            replay: Default::default(),
         }
     }
 }
@@ -94,12 +164,38 @@ where
     I: PartialOrd + Clone + Debug + Ord + Send + Sync + 'static,
     F: OnNewSubscription<Index = I, Event = T> + Send + Sync + 'static,
 {
+    /// Keep at most `max_events` events broadcast in the last `max_age` for replay
+    ///
+    /// Without a replay buffer no events are kept and `since` is ignored. The
+    /// buffer can only be set once, later calls are ignored.
+    pub fn set_replay_buffer(&self, max_events: usize, max_age: Duration) {
+        if max_events == 0 {
+            return;
+        }
+
+        if self
+            .replay
+            .set(ReplayBuffer::new(max_events, max_age))
+            .is_err()
+        {
+            tracing::warn!("Replay buffer of the subscription manager is already set");
+        }
+    }
+
     #[inline]
     /// Broadcast an event to all listeners
     ///
     /// This function takes an Arc to the storage struct, the event_id, the kind
     /// and the vent to broadcast
-    async fn broadcast_impl(storage: &IndexTree<T, I>, event: T) {
+    async fn broadcast_impl(
+        storage: &IndexTree<T, I>,
+        replay: &OnceLock<ReplayBuffer<T>>,
+        event: T,
+    ) {
+        if let Some(replay) = replay.get() {
+            replay.push(event.clone()).await;
+        }
+
         let index_storage = storage.read().await;
         let mut sent = HashSet::new();
         for index in event.to_indexes() {
@@ -123,8 +219,9 @@ where
     /// instead
     pub fn broadcast(&self, event: T) {
         let storage = self.indexes.clone();
+        let replay = self.replay.clone();
         tokio::spawn(async move {
-            Self::broadcast_impl(&storage, event).await;
+            Self::broadcast_impl(&storage, &replay, event).await;
         });
     }
 
@@ -132,7 +229,7 @@ where
     ///
     /// This method is async and will await for the broadcast to be completed
     pub async fn broadcast_async(&self, event: T) {
-        Self::broadcast_impl(&self.indexes, event).await;
+        Self::broadcast_impl(&self.indexes, &self.replay, event).await;
     }
 
     /// Specific of the subscription, this is the abstraction between `subscribe` and `try_subscribe`
@@ -141,6 +238,7 @@ where
         &self,
         sub_id: SubId,
         indexes: Vec<Index<I>>,
+        since: Option<u64>,
     ) -> ActiveSubscription<T, I> {
         let (sender, receiver) = mpsc::channel(10);
 
@@ -151,12 +249,33 @@ where
         }
         drop(index_storage);
 
-        if let Some(on_new_subscription) = self.on_new_subscription.clone() {
-            // After we're subscribed already, fetch the current status of matching events. It is
-            // down in another thread to return right away
+        let missed = match (since, self.replay.get()) {
+            (Some(since), Some(replay)) => replay.since(&indexes, since).await,
+            _ => Vec::new(),
+        };
+        let on_new_subscription = self.on_new_subscription.clone();
+
+        if !missed.is_empty() || on_new_subscription.is_some() {
+            // After we're subscribed already, replay the missed events and fetch the current
+            // status of matching events. It is down in another thread to return right away
             let indexes_for_worker = indexes.clone();
             let sub_id_for_worker = sub_id.clone();
             tokio::spawn(async move {
+                // Missed events go first, so the current status is the last one received
+                for event in missed {
+                    if sender
+                        .send((sub_id_for_worker.clone(), event))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+
+                let Some(on_new_subscription) = on_new_subscription else {
+                    return;
+                };
+
                 match on_new_subscription
                     .on_new_subscription(
                         &indexes_for_worker
@@ -195,11 +314,23 @@ where
 
     /// Try to subscribe to a specific event
     pub async fn try_subscribe<P>(&self, params: P) -> Result<ActiveSubscription<T, I>, P::Error>
+    where
+        P: AsRef<SubId> + TryInto<Vec<Index<I>>>,
+    {
+        self.try_subscribe_since(params, None).await
+    }
+
+    /// Try to subscribe to a specific event, replaying the events broadcast since `since`
+    pub async fn try_subscribe_since<P>(
+        &self,
+        params: P,
+        since: Option<u64>,
+    ) -> Result<ActiveSubscription<T, I>, P::Error>
     where
         P: AsRef<SubId> + TryInto<Vec<Index<I>>>,
     {
         Ok(self
-            .subscribe_inner(params.as_ref().clone(), params.try_into()?)
+            .subscribe_inner(params.as_ref().clone(), params.try_into()?, since)
             .await)
     }
 
@@ -208,7 +339,7 @@ where
     where
         P: AsRef<SubId> + Into<Vec<Index<I>>>,
     {
-        self.subscribe_inner(params.as_ref().clone(), params.into())
+        self.subscribe_inner(params.as_ref().clone(), params.into(), None)
             .await
     }
 
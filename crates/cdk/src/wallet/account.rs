@@ -0,0 +1,70 @@
+//! Accounts
+//!
+//! One mnemonic can hold several logical accounts, such as personal and business
+//! funds. Each account derives its own seed from the wallet seed at
+//! `m/129372'/1'/<account>'`, so its secrets and counters never overlap with the
+//! ones of another account. Account `0` is the wallet seed itself, which keeps
+//! wallets created before accounts existed on the default account.
+
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv};
+use bitcoin::Network;
+
+use crate::{Error, SECP256K1};
+
+/// Purpose of the NUT-13 derivation paths
+pub(crate) const CASHU_PURPOSE: u32 = 129372;
+/// Branch of the account seeds, next to the `0'` branch of the proof secrets
+const ACCOUNT_BRANCH: u32 = 1;
+
+/// Index of the account using the wallet seed
+pub const DEFAULT_ACCOUNT: u32 = 0;
+
+/// Derivation path of an account
+pub fn account_derivation_path(account: u32) -> Result<DerivationPath, Error> {
+    Ok(DerivationPath::from(vec![
+        ChildNumber::from_hardened_idx(CASHU_PURPOSE)?,
+        ChildNumber::from_hardened_idx(ACCOUNT_BRANCH)?,
+        ChildNumber::from_hardened_idx(account)?,
+    ]))
+}
+
+/// Seed of an account derived from the wallet seed
+///
+/// The seed is the private key of the account path followed by its chain code.
+pub fn account_seed(seed: &[u8; 64], account: u32) -> Result<[u8; 64], Error> {
+    if account == DEFAULT_ACCOUNT {
+        return Ok(*seed);
+    }
+
+    let xpriv = Xpriv::new_master(Network::Bitcoin, seed)?
+        .derive_priv(&SECP256K1, &account_derivation_path(account)?)?;
+
+    let mut account_seed = [0u8; 64];
+    account_seed[..32].copy_from_slice(&xpriv.private_key.secret_bytes());
+    account_seed[32..].copy_from_slice(xpriv.chain_code.as_bytes());
+
+    Ok(account_seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_seed() {
+        let seed = [7u8; 64];
+
+        assert_eq!(account_seed(&seed, DEFAULT_ACCOUNT).unwrap(), seed);
+
+        let first = account_seed(&seed, 1).unwrap();
+        let second = account_seed(&seed, 2).unwrap();
+        assert_ne!(first, seed);
+        assert_ne!(first, second);
+        assert_eq!(account_seed(&seed, 1).unwrap(), first);
+
+        assert_eq!(
+            account_derivation_path(1).unwrap().to_string(),
+            "m/129372'/1'/1'"
+        );
+    }
+}
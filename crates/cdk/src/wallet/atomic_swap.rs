@@ -0,0 +1,993 @@
+//! Atomic swaps
+//!
+//! Trustless exchange of ecash of one mint for ecash of another mint, using
+//! NUT-14 HTLCs locked to the same hash on both sides.
+//!
+//! The maker creates a [`SwapOffer`] holding the hash of a secret preimage and
+//! shares it out of band. The rest of the protocol is a series of
+//! [`SwapMessage`]s exchanged between the swap keys of the two parties, sent as
+//! gift wrapped nostr messages when the `nostr` feature is enabled:
+//!
+//! 1. The taker accepts the offer with the pubkey the maker's ecash is locked to.
+//! 2. The maker locks the ecash it gives to the taker until the maker locktime.
+//! 3. The taker verifies the maker's lock and locks the ecash it gives to the
+//!    maker until the earlier taker locktime.
+//! 4. The maker verifies the taker's lock and claims it, revealing the preimage,
+//!    unless the taker locktime is less than [`CLAIM_MARGIN`] away.
+//! 5. The taker claims the maker's lock with the preimage, taken from the maker's
+//!    message or from the witness of its own spent proofs at the mint.
+//!
+//! A party that stops responding is handled by refunding the own lock once its
+//! locktime has passed. The taker locktime is earlier than the maker locktime,
+//! so the taker can always claim after the preimage is revealed and before the
+//! maker can refund.
+
+use std::fmt;
+use std::str::FromStr;
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use cdk_common::util::unix_time;
+#[cfg(feature = "nostr")]
+use nostr_sdk::{EventBuilder, Filter, Keys, Kind};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+#[cfg(feature = "nostr")]
+use super::nostr_transport::{
+    nostr_error, nostr_keys, nostr_public_key, relay_client, EventKind, RelayUse, RELAY_TIMEOUT,
+};
+use crate::amount::SplitTarget;
+use crate::mint_url::MintUrl;
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::nut14::HTLCWitness;
+use crate::nuts::{
+    Conditions, CurrencyUnit, Proofs, PublicKey, SecretKey, SpendingConditions, State, Token,
+    Witness,
+};
+use crate::secret::Secret;
+use crate::util::hex;
+use crate::wallet::{MultiMintWallet, SendOptions, Wallet};
+use crate::{ensure_cdk, Amount, Error};
+
+/// Key-value store primary namespace for wallet data
+const ATOMIC_SWAP_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for atomic swaps
+const ATOMIC_SWAP_SECONDARY_NAMESPACE: &str = "atomic_swap";
+/// Domain separation tag for swap keys
+const ATOMIC_SWAP_KEY_TAG: &[u8] = b"cdk_atomic_swap";
+/// Minimum time in seconds until the maker lock can be refunded
+pub const MIN_SWAP_TIMEOUT: u64 = 600;
+/// Seconds before the taker locktime after which the maker no longer claims
+///
+/// Claiming reveals the preimage, so a maker claiming close to the taker
+/// locktime risks the taker refunding its lock and claiming the maker's lock.
+pub const CLAIM_MARGIN: u64 = 120;
+
+/// Ecash of one side of an atomic swap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapLeg {
+    /// Mint of the ecash
+    pub mint_url: MintUrl,
+    /// Amount of ecash
+    pub amount: Amount,
+    /// Unit of the ecash
+    pub unit: CurrencyUnit,
+}
+
+/// Atomic swap offer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapOffer {
+    /// Hex sha256 hash of the preimage, identifying the swap
+    pub hash: String,
+    /// Maker swap pubkey
+    pub maker: PublicKey,
+    /// Ecash the maker gives
+    pub give: SwapLeg,
+    /// Ecash the maker wants
+    pub want: SwapLeg,
+    /// Unix time after which the maker can refund its lock
+    pub maker_locktime: u64,
+    /// Unix time after which the taker can refund its lock
+    pub taker_locktime: u64,
+    /// Nostr relays the swap messages are exchanged on
+    pub relays: Vec<String>,
+}
+
+impl fmt::Display for SwapOffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{json}")
+    }
+}
+
+impl FromStr for SwapOffer {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+/// Atomic swap protocol message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SwapMessage {
+    /// Taker accepts the offer
+    Accept {
+        /// Swap hash
+        hash: String,
+        /// Taker swap pubkey
+        taker: PublicKey,
+    },
+    /// Ecash locked to the counterparty
+    Lock {
+        /// Swap hash
+        hash: String,
+        /// HTLC locked token
+        token: String,
+    },
+    /// Maker claimed the taker's lock
+    Claim {
+        /// Swap hash
+        hash: String,
+        /// Preimage of the swap hash
+        preimage: String,
+    },
+}
+
+impl SwapMessage {
+    /// Hash of the swap the message belongs to
+    pub fn hash(&self) -> &str {
+        match self {
+            Self::Accept { hash, .. } | Self::Lock { hash, .. } | Self::Claim { hash, .. } => hash,
+        }
+    }
+}
+
+/// Role of the wallet in an atomic swap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapRole {
+    /// Created the offer
+    Maker,
+    /// Accepted the offer
+    Taker,
+}
+
+/// State of an atomic swap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapState {
+    /// Offer created, waiting for a taker
+    Offered,
+    /// Offer accepted, waiting for the maker's lock
+    Accepted,
+    /// Own ecash locked, waiting for the counterparty
+    Locked,
+    /// Claiming the counterparty's lock, the preimage is stored
+    Claiming,
+    /// Counterparty's lock claimed
+    Completed,
+    /// Own lock refunded after its locktime
+    Refunded,
+}
+
+/// Atomic swap stored in the wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtomicSwap {
+    /// Swap offer
+    pub offer: SwapOffer,
+    /// Role of the wallet
+    pub role: SwapRole,
+    /// Swap state
+    pub state: SwapState,
+    /// Swap pubkey of the wallet
+    pub pubkey: PublicKey,
+    /// Swap pubkey of the counterparty
+    pub counterparty: Option<PublicKey>,
+    /// Preimage of the swap hash, known to the taker once the maker claims
+    pub preimage: Option<String>,
+    /// Token locked by the wallet, kept to refund it
+    pub locked_token: Option<String>,
+    /// Token locked by the counterparty
+    pub counterparty_token: Option<String>,
+}
+
+impl AtomicSwap {
+    /// Ecash the wallet gives
+    pub fn sent_leg(&self) -> &SwapLeg {
+        match self.role {
+            SwapRole::Maker => &self.offer.give,
+            SwapRole::Taker => &self.offer.want,
+        }
+    }
+
+    /// Ecash the wallet receives
+    pub fn received_leg(&self) -> &SwapLeg {
+        match self.role {
+            SwapRole::Maker => &self.offer.want,
+            SwapRole::Taker => &self.offer.give,
+        }
+    }
+
+    /// Unix time after which the wallet can refund its lock
+    pub fn locktime(&self) -> u64 {
+        match self.role {
+            SwapRole::Maker => self.offer.maker_locktime,
+            SwapRole::Taker => self.offer.taker_locktime,
+        }
+    }
+
+    /// Unix time after which the counterparty can refund its lock
+    fn counterparty_locktime(&self) -> u64 {
+        match self.role {
+            SwapRole::Maker => self.offer.taker_locktime,
+            SwapRole::Taker => self.offer.maker_locktime,
+        }
+    }
+
+    /// Whether the swap is still in progress
+    pub fn is_active(&self) -> bool {
+        !matches!(self.state, SwapState::Completed | SwapState::Refunded)
+    }
+
+    /// Message the counterparty needs to make progress, resent until it responds
+    #[cfg(feature = "nostr")]
+    fn pending_message(&self) -> Option<SwapMessage> {
+        let hash = self.offer.hash.clone();
+
+        match (self.role, self.state, &self.locked_token) {
+            (SwapRole::Taker, SwapState::Accepted, _) => Some(SwapMessage::Accept {
+                hash,
+                taker: self.pubkey,
+            }),
+            (_, SwapState::Locked, Some(token)) => Some(SwapMessage::Lock {
+                hash,
+                token: token.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `preimage` is the hex preimage of `hash`
+fn preimage_matches(hash: &str, preimage: &str) -> bool {
+    match (Sha256Hash::from_str(hash), hex::decode(preimage)) {
+        (Ok(hash), Ok(preimage)) => Sha256Hash::hash(&preimage) == hash,
+        _ => false,
+    }
+}
+
+/// Preimage of `hash` revealed by spending `proofs` at the mint of `wallet`
+async fn spent_preimage(
+    wallet: &Wallet,
+    proofs: Proofs,
+    hash: &str,
+) -> Result<Option<String>, Error> {
+    let preimage = wallet
+        .check_proofs_spent(proofs)
+        .await?
+        .into_iter()
+        .filter(|state| state.state == State::Spent)
+        .find_map(|state| match state.witness {
+            Some(Witness::HTLCWitness(witness)) => Some(witness.preimage),
+            _ => None,
+        })
+        .filter(|preimage| preimage_matches(hash, preimage));
+
+    Ok(preimage)
+}
+
+impl Wallet {
+    /// Swap key derived as `HMAC-SHA256(seed, "cdk_atomic_swap" || hash)`
+    fn atomic_swap_key(&self, hash: &str) -> Result<SecretKey, Error> {
+        let mut engine = hmac::HmacEngine::<sha256::Hash>::new(&self.seed);
+        engine.input(ATOMIC_SWAP_KEY_TAG);
+        engine.input(hash.as_bytes());
+        let key = hmac::Hmac::<sha256::Hash>::from_engine(engine).to_byte_array();
+
+        Ok(SecretKey::from_slice(&key)?)
+    }
+
+    /// Swap HTLC proofs into the wallet with a witness signed by `secret_key`
+    ///
+    /// The preimage is empty when refunding after the locktime.
+    async fn redeem_htlc(
+        &self,
+        proofs: Proofs,
+        preimage: &str,
+        secret_key: &SecretKey,
+    ) -> Result<Amount, Error> {
+        let proofs = proofs
+            .into_iter()
+            .map(|mut proof| {
+                let signature = secret_key.sign(&proof.secret.to_bytes())?;
+                proof.witness = Some(Witness::HTLCWitness(HTLCWitness {
+                    preimage: preimage.to_string(),
+                    signatures: Some(vec![signature.to_string()]),
+                }));
+                Ok(proof)
+            })
+            .collect::<Result<Proofs, Error>>()?;
+
+        let amount = proofs.total_amount()? - self.get_proofs_fee(&proofs).await?;
+
+        self.swap(None, SplitTarget::default(), proofs, None, false)
+            .await?;
+
+        Ok(amount)
+    }
+}
+
+impl MultiMintWallet {
+    /// Wallet of a swap mint
+    async fn atomic_swap_wallet(&self, mint_url: &MintUrl) -> Result<Wallet, Error> {
+        self.get_wallet(mint_url).await.ok_or(Error::UnknownMint {
+            mint_url: mint_url.to_string(),
+        })
+    }
+
+    /// Store an atomic swap
+    async fn save_atomic_swap(&self, swap: &AtomicSwap) -> Result<(), Error> {
+        self.localstore()
+            .kv_write(
+                ATOMIC_SWAP_PRIMARY_NAMESPACE,
+                ATOMIC_SWAP_SECONDARY_NAMESPACE,
+                &swap.offer.hash,
+                &serde_json::to_vec(swap)?,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get an atomic swap by its hash
+    #[instrument(skip(self))]
+    pub async fn atomic_swap(&self, hash: &str) -> Result<Option<AtomicSwap>, Error> {
+        ensure_cdk!(
+            Sha256Hash::from_str(hash).is_ok(),
+            Error::AtomicSwap("Invalid swap hash".to_string())
+        );
+
+        let swap = self
+            .localstore()
+            .kv_read(
+                ATOMIC_SWAP_PRIMARY_NAMESPACE,
+                ATOMIC_SWAP_SECONDARY_NAMESPACE,
+                hash,
+            )
+            .await?;
+
+        match swap {
+            Some(swap) => Ok(Some(serde_json::from_slice(&swap)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List atomic swaps
+    #[instrument(skip(self))]
+    pub async fn atomic_swaps(&self) -> Result<Vec<AtomicSwap>, Error> {
+        let hashes = self
+            .localstore()
+            .kv_list(
+                ATOMIC_SWAP_PRIMARY_NAMESPACE,
+                ATOMIC_SWAP_SECONDARY_NAMESPACE,
+            )
+            .await?;
+
+        let mut swaps = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            if let Some(swap) = self.atomic_swap(&hash).await? {
+                swaps.push(swap);
+            }
+        }
+
+        Ok(swaps)
+    }
+
+    /// Create an atomic swap offer
+    ///
+    /// The maker lock can be refunded `timeout` seconds from now and the taker
+    /// lock after half of that. The offer is shared with the taker out of band.
+    #[instrument(skip(self))]
+    pub async fn create_atomic_swap(
+        &self,
+        give: SwapLeg,
+        want: SwapLeg,
+        timeout: u64,
+        relays: Vec<String>,
+    ) -> Result<SwapOffer, Error> {
+        ensure_cdk!(
+            &give.unit == self.unit() && &want.unit == self.unit(),
+            Error::UnsupportedUnit
+        );
+        ensure_cdk!(
+            give.mint_url != want.mint_url,
+            Error::AtomicSwap("Both sides of the swap use the same mint".to_string())
+        );
+        ensure_cdk!(
+            timeout >= MIN_SWAP_TIMEOUT,
+            Error::AtomicSwap(format!(
+                "Timeout must be at least {MIN_SWAP_TIMEOUT} seconds"
+            ))
+        );
+
+        let wallet = self.atomic_swap_wallet(&give.mint_url).await?;
+        self.atomic_swap_wallet(&want.mint_url).await?;
+
+        ensure_cdk!(
+            wallet.total_balance().await? >= give.amount,
+            Error::InsufficientFunds
+        );
+
+        let preimage = Secret::generate().to_string();
+        let hash = Sha256Hash::hash(&hex::decode(&preimage)?).to_string();
+        let pubkey = wallet.atomic_swap_key(&hash)?.public_key();
+        let now = unix_time();
+
+        let offer = SwapOffer {
+            hash,
+            maker: pubkey,
+            give,
+            want,
+            maker_locktime: now + timeout,
+            taker_locktime: now + timeout / 2,
+            relays,
+        };
+
+        self.save_atomic_swap(&AtomicSwap {
+            offer: offer.clone(),
+            role: SwapRole::Maker,
+            state: SwapState::Offered,
+            pubkey,
+            counterparty: None,
+            preimage: Some(preimage),
+            locked_token: None,
+            counterparty_token: None,
+        })
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Accept an atomic swap offer
+    ///
+    /// Returns the message to send to the maker.
+    #[instrument(skip(self))]
+    pub async fn accept_atomic_swap(&self, offer: SwapOffer) -> Result<SwapMessage, Error> {
+        ensure_cdk!(
+            &offer.give.unit == self.unit() && &offer.want.unit == self.unit(),
+            Error::UnsupportedUnit
+        );
+        ensure_cdk!(
+            unix_time() < offer.taker_locktime && offer.taker_locktime < offer.maker_locktime,
+            Error::AtomicSwap("Offer locktimes are not valid".to_string())
+        );
+        ensure_cdk!(
+            self.atomic_swap(&offer.hash).await?.is_none(),
+            Error::AtomicSwap("Swap is already known".to_string())
+        );
+
+        let wallet = self.atomic_swap_wallet(&offer.want.mint_url).await?;
+        self.atomic_swap_wallet(&offer.give.mint_url).await?;
+
+        ensure_cdk!(
+            wallet.total_balance().await? >= offer.want.amount,
+            Error::InsufficientFunds
+        );
+
+        let pubkey = wallet.atomic_swap_key(&offer.hash)?.public_key();
+
+        ensure_cdk!(
+            pubkey != offer.maker,
+            Error::AtomicSwap("Cannot accept own offer".to_string())
+        );
+
+        let swap = AtomicSwap {
+            counterparty: Some(offer.maker),
+            offer,
+            role: SwapRole::Taker,
+            state: SwapState::Accepted,
+            pubkey,
+            preimage: None,
+            locked_token: None,
+            counterparty_token: None,
+        };
+
+        self.save_atomic_swap(&swap).await?;
+
+        Ok(SwapMessage::Accept {
+            hash: swap.offer.hash,
+            taker: pubkey,
+        })
+    }
+
+    /// Handle a message of the counterparty
+    ///
+    /// Advances the swap and returns the message to send back, if any. Messages
+    /// that do not fit the state of the swap, e.g. duplicates, are ignored.
+    #[instrument(skip(self))]
+    pub async fn handle_atomic_swap_message(
+        &self,
+        message: SwapMessage,
+    ) -> Result<Option<SwapMessage>, Error> {
+        let mut swap = self
+            .atomic_swap(message.hash())
+            .await?
+            .ok_or(Error::AtomicSwap("Unknown swap".to_string()))?;
+
+        match (swap.role, swap.state, message) {
+            (SwapRole::Maker, SwapState::Offered, SwapMessage::Accept { taker, .. }) => {
+                ensure_cdk!(
+                    unix_time() < swap.offer.taker_locktime,
+                    Error::AtomicSwap("Offer has expired".to_string())
+                );
+
+                swap.counterparty = Some(taker);
+                let token = self.lock_atomic_swap(&swap).await?;
+                swap.locked_token = Some(token.clone());
+                swap.state = SwapState::Locked;
+                self.save_atomic_swap(&swap).await?;
+
+                Ok(Some(SwapMessage::Lock {
+                    hash: swap.offer.hash,
+                    token,
+                }))
+            }
+            (SwapRole::Taker, SwapState::Accepted, SwapMessage::Lock { token, .. }) => {
+                ensure_cdk!(
+                    unix_time() < swap.offer.taker_locktime,
+                    Error::AtomicSwap("Offer has expired".to_string())
+                );
+
+                self.verify_atomic_swap_lock(&swap, &token).await?;
+                swap.counterparty_token = Some(token);
+                let token = self.lock_atomic_swap(&swap).await?;
+                swap.locked_token = Some(token.clone());
+                swap.state = SwapState::Locked;
+                self.save_atomic_swap(&swap).await?;
+
+                Ok(Some(SwapMessage::Lock {
+                    hash: swap.offer.hash,
+                    token,
+                }))
+            }
+            (SwapRole::Maker, SwapState::Locked, SwapMessage::Lock { token, .. }) => {
+                ensure_cdk!(
+                    unix_time() + CLAIM_MARGIN < swap.offer.taker_locktime,
+                    Error::AtomicSwap("Too close to the taker locktime to claim".to_string())
+                );
+
+                self.verify_atomic_swap_lock(&swap, &token).await?;
+                swap.counterparty_token = Some(token);
+                self.save_atomic_swap(&swap).await?;
+
+                let preimage = swap
+                    .preimage
+                    .clone()
+                    .ok_or(Error::AtomicSwap("Preimage is missing".to_string()))?;
+                let swap = self.complete_atomic_swap(swap, preimage.clone()).await?;
+
+                Ok(Some(SwapMessage::Claim {
+                    hash: swap.offer.hash,
+                    preimage,
+                }))
+            }
+            (SwapRole::Taker, SwapState::Locked, SwapMessage::Claim { preimage, .. }) => {
+                self.complete_atomic_swap(swap, preimage).await?;
+
+                Ok(None)
+            }
+            (role, state, message) => {
+                tracing::debug!(
+                    "Ignoring swap message {:?} as {:?} in state {:?}",
+                    message,
+                    role,
+                    state
+                );
+
+                Ok(None)
+            }
+        }
+    }
+
+    /// Lock the ecash the wallet gives to the counterparty
+    async fn lock_atomic_swap(&self, swap: &AtomicSwap) -> Result<String, Error> {
+        let counterparty = swap
+            .counterparty
+            .ok_or(Error::AtomicSwap("Counterparty is unknown".to_string()))?;
+        let leg = swap.sent_leg();
+        let wallet = self.atomic_swap_wallet(&leg.mint_url).await?;
+
+        let conditions = Conditions::new(
+            Some(swap.locktime()),
+            Some(vec![counterparty]),
+            Some(vec![swap.pubkey]),
+            None,
+            None,
+            None,
+        )?;
+
+        let prepared_send = wallet
+            .prepare_send(
+                leg.amount,
+                SendOptions {
+                    conditions: Some(SpendingConditions::new_htlc_hash(
+                        &swap.offer.hash,
+                        Some(conditions),
+                    )?),
+                    include_fee: true,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        Ok(prepared_send.confirm(None).await?.to_string())
+    }
+
+    /// Verify that the counterparty's token is locked to the wallet for the swap
+    async fn verify_atomic_swap_lock(&self, swap: &AtomicSwap, token: &str) -> Result<(), Error> {
+        let leg = swap.received_leg();
+        let token = Token::from_str(token)?;
+
+        ensure_cdk!(token.mint_url()? == leg.mint_url, Error::IncorrectMint);
+        ensure_cdk!(
+            token.unit().is_none_or(|unit| unit == leg.unit),
+            Error::UnsupportedUnit
+        );
+
+        let wallet = self.atomic_swap_wallet(&leg.mint_url).await?;
+        let proofs = token.proofs(&wallet.load_mint_keysets().await?)?;
+
+        ensure_cdk!(
+            proofs.total_amount()? >= leg.amount,
+            Error::AtomicSwap("Locked amount is below the swap amount".to_string())
+        );
+
+        let hash = Sha256Hash::from_str(&swap.offer.hash)
+            .map_err(|_| Error::AtomicSwap("Invalid swap hash".to_string()))?;
+
+        for proof in &proofs {
+            let locked = match SpendingConditions::try_from(&proof.secret)? {
+                SpendingConditions::HTLCConditions {
+                    data,
+                    conditions: Some(conditions),
+                } => {
+                    data == hash
+                        && conditions.locktime == Some(swap.counterparty_locktime())
+                        && conditions.pubkeys == Some(vec![swap.pubkey])
+                        && conditions.num_sigs.unwrap_or(1) == 1
+                }
+                _ => false,
+            };
+
+            ensure_cdk!(
+                locked,
+                Error::AtomicSwap("Token is not locked to the swap".to_string())
+            );
+        }
+
+        wallet.verify_token_dleq(&token).await?;
+
+        let spent = wallet
+            .check_proofs_spent(proofs)
+            .await?
+            .iter()
+            .any(|state| state.state != State::Unspent);
+
+        ensure_cdk!(
+            !spent,
+            Error::AtomicSwap("Locked proofs are already spent".to_string())
+        );
+
+        Ok(())
+    }
+
+    /// Claim the counterparty's lock with the preimage
+    ///
+    /// The preimage is stored before the claim, so a claim interrupted by a
+    /// crash is retried by [`MultiMintWallet::check_atomic_swap`]. A lock that
+    /// turns out to be claimed already completes the swap.
+    async fn complete_atomic_swap(
+        &self,
+        mut swap: AtomicSwap,
+        preimage: String,
+    ) -> Result<AtomicSwap, Error> {
+        ensure_cdk!(
+            preimage_matches(&swap.offer.hash, &preimage),
+            Error::AtomicSwap("Preimage does not match swap hash".to_string())
+        );
+
+        let token = Token::from_str(swap.counterparty_token.as_ref().ok_or(Error::AtomicSwap(
+            "Counterparty lock is missing".to_string(),
+        ))?)?;
+        let wallet = self
+            .atomic_swap_wallet(&swap.received_leg().mint_url)
+            .await?;
+        let proofs = token.proofs(&wallet.load_mint_keysets().await?)?;
+        let secret_key = wallet.atomic_swap_key(&swap.offer.hash)?;
+
+        swap.preimage = Some(preimage.clone());
+        swap.state = SwapState::Claiming;
+        self.save_atomic_swap(&swap).await?;
+
+        match wallet
+            .redeem_htlc(proofs.clone(), &preimage, &secret_key)
+            .await
+        {
+            Ok(amount) => tracing::info!("Claimed {} for swap {}", amount, swap.offer.hash),
+            Err(err) => match spent_preimage(&wallet, proofs, &swap.offer.hash).await? {
+                Some(_) => tracing::info!("Lock of swap {} was already claimed", swap.offer.hash),
+                None => return Err(err),
+            },
+        }
+
+        swap.state = SwapState::Completed;
+        self.save_atomic_swap(&swap).await?;
+
+        Ok(swap)
+    }
+
+    /// Preimage revealed at the mint by the counterparty claiming the wallet's lock
+    async fn atomic_swap_preimage_from_mint(
+        &self,
+        swap: &AtomicSwap,
+    ) -> Result<Option<String>, Error> {
+        let token = match &swap.locked_token {
+            Some(token) => Token::from_str(token)?,
+            None => return Ok(None),
+        };
+        let wallet = self.atomic_swap_wallet(&swap.sent_leg().mint_url).await?;
+        let proofs = token.proofs(&wallet.load_mint_keysets().await?)?;
+
+        spent_preimage(&wallet, proofs, &swap.offer.hash).await
+    }
+
+    /// Refund the wallet's lock of a swap after its locktime
+    #[instrument(skip(self))]
+    pub async fn refund_atomic_swap(&self, hash: &str) -> Result<Amount, Error> {
+        let mut swap = self
+            .atomic_swap(hash)
+            .await?
+            .ok_or(Error::AtomicSwap("Unknown swap".to_string()))?;
+
+        ensure_cdk!(
+            matches!(swap.state, SwapState::Locked | SwapState::Claiming),
+            Error::AtomicSwap("Swap has no lock to refund".to_string())
+        );
+        ensure_cdk!(
+            unix_time() > swap.locktime(),
+            Error::AtomicSwap("Locktime has not passed".to_string())
+        );
+
+        let token = Token::from_str(
+            swap.locked_token
+                .as_ref()
+                .ok_or(Error::AtomicSwap("Lock is missing".to_string()))?,
+        )?;
+        let wallet = self.atomic_swap_wallet(&swap.sent_leg().mint_url).await?;
+        let proofs = token.proofs(&wallet.load_mint_keysets().await?)?;
+        let secret_key = wallet.atomic_swap_key(&swap.offer.hash)?;
+
+        let amount = wallet.redeem_htlc(proofs, "", &secret_key).await?;
+
+        swap.state = SwapState::Refunded;
+        self.save_atomic_swap(&swap).await?;
+
+        Ok(amount)
+    }
+
+    /// Settle a swap without the counterparty's messages
+    ///
+    /// A taker whose lock was claimed takes the preimage from the mint and
+    /// claims the maker's lock, and an interrupted claim is retried. A lock
+    /// whose locktime has passed is refunded.
+    #[instrument(skip(self))]
+    pub async fn check_atomic_swap(&self, hash: &str) -> Result<AtomicSwap, Error> {
+        let swap = self
+            .atomic_swap(hash)
+            .await?
+            .ok_or(Error::AtomicSwap("Unknown swap".to_string()))?;
+
+        if swap.role == SwapRole::Taker && swap.state == SwapState::Locked {
+            if let Some(preimage) = self.atomic_swap_preimage_from_mint(&swap).await? {
+                return self.complete_atomic_swap(swap, preimage).await;
+            }
+        }
+
+        if swap.state == SwapState::Claiming {
+            if let Some(preimage) = swap.preimage.clone() {
+                match self.complete_atomic_swap(swap.clone(), preimage).await {
+                    Ok(swap) => return Ok(swap),
+                    Err(err) => tracing::warn!("Could not claim lock of swap {}: {}", hash, err),
+                }
+            }
+        }
+
+        if matches!(swap.state, SwapState::Locked | SwapState::Claiming)
+            && unix_time() > swap.locktime()
+        {
+            self.refund_atomic_swap(hash).await?;
+        }
+
+        self.atomic_swap(hash)
+            .await?
+            .ok_or(Error::AtomicSwap("Unknown swap".to_string()))
+    }
+}
+
+#[cfg(feature = "nostr")]
+impl MultiMintWallet {
+    /// Nostr keys of the wallet's swap key
+    async fn atomic_swap_nostr_keys(&self, swap: &AtomicSwap) -> Result<Keys, Error> {
+        let wallet = self.atomic_swap_wallet(&swap.sent_leg().mint_url).await?;
+
+        nostr_keys(&wallet.atomic_swap_key(&swap.offer.hash)?)
+    }
+
+    /// Send a swap message to the counterparty over nostr
+    #[instrument(skip(self))]
+    pub async fn send_atomic_swap_message(&self, message: &SwapMessage) -> Result<(), Error> {
+        let swap = self
+            .atomic_swap(message.hash())
+            .await?
+            .ok_or(Error::AtomicSwap("Unknown swap".to_string()))?;
+        let counterparty = swap
+            .counterparty
+            .ok_or(Error::AtomicSwap("Counterparty is unknown".to_string()))?;
+
+        let keys = self.atomic_swap_nostr_keys(&swap).await?;
+        let client = relay_client(&swap.offer.relays, RelayUse::ReadWrite).await?;
+        let receiver = nostr_public_key(&counterparty)?;
+        let sender = nostr_public_key(&swap.pubkey)?;
+
+        let rumor = EventBuilder::new(
+            EventKind::PrivateDirectMessage.into(),
+            serde_json::to_string(message)?,
+        )
+        .build(sender);
+
+        let gift_wrap = cdk_nostr::gift_wrap(&keys, &receiver, rumor)
+            .await
+            .map_err(nostr_error)?;
+
+        let output = client
+            .send_event(&gift_wrap)
+            .await
+            .map_err(|e| Error::Custom(format!("Publish Nostr event: {e}")))?;
+
+        if !output.failed.is_empty() {
+            tracing::warn!(
+                "Could not publish swap message to {} relays",
+                output.failed.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Process the nostr messages of all active swaps
+    ///
+    /// Handles the counterparty's messages, sends the replies, resends messages
+    /// the counterparty has not yet acted on and settles swaps with
+    /// [`MultiMintWallet::check_atomic_swap`]. Returns the updated swaps.
+    #[instrument(skip(self))]
+    pub async fn sync_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, Error> {
+        let mut swaps = Vec::new();
+
+        for swap in self.atomic_swaps().await? {
+            if !swap.is_active() {
+                swaps.push(swap);
+                continue;
+            }
+
+            let hash = swap.offer.hash.clone();
+
+            if let Err(err) = self.sync_atomic_swap(swap).await {
+                tracing::warn!("Could not sync swap {}: {}", hash, err);
+            }
+
+            if let Some(swap) = self.atomic_swap(&hash).await? {
+                swaps.push(swap);
+            }
+        }
+
+        Ok(swaps)
+    }
+
+    /// Process the nostr messages of a swap
+    async fn sync_atomic_swap(&self, swap: AtomicSwap) -> Result<(), Error> {
+        let client = relay_client(&swap.offer.relays, RelayUse::ReadWrite).await?;
+        let keys = self.atomic_swap_nostr_keys(&swap).await?;
+
+        // Gift wraps are backdated, so all events to the swap key are fetched
+        let events = client
+            .fetch_events(
+                Filter::new().kind(Kind::GiftWrap).pubkey(keys.public_key()),
+                RELAY_TIMEOUT,
+            )
+            .await
+            .map_err(|e| Error::Custom(format!("Fetch swap messages: {e}")))?;
+
+        let mut messages = Vec::new();
+        for event in events.iter() {
+            let unwrapped = match cdk_nostr::unwrap_gift_wrap(&keys, event).await {
+                Ok(unwrapped) => unwrapped,
+                Err(err) => {
+                    tracing::debug!("Could not unwrap swap message: {}", err);
+                    continue;
+                }
+            };
+
+            match serde_json::from_str::<SwapMessage>(&unwrapped.rumor.content) {
+                Ok(message) if message.hash() == swap.offer.hash => messages.push((
+                    unwrapped.sender.to_hex(),
+                    unwrapped.rumor.created_at,
+                    message,
+                )),
+                _ => continue,
+            }
+        }
+
+        messages.sort_by_key(|(_, created_at, _)| *created_at);
+
+        for (sender, _, message) in messages {
+            let current = self
+                .atomic_swap(&swap.offer.hash)
+                .await?
+                .ok_or(Error::AtomicSwap("Unknown swap".to_string()))?;
+
+            // Only the counterparty, or for an open offer the accepting taker, may message
+            let expected = match (&message, current.counterparty) {
+                (_, Some(counterparty)) => counterparty,
+                (SwapMessage::Accept { taker, .. }, None) => *taker,
+                _ => continue,
+            };
+
+            if sender != expected.x_only_public_key().to_string() {
+                tracing::debug!("Ignoring swap message from {}", sender);
+                continue;
+            }
+
+            match self.handle_atomic_swap_message(message).await {
+                Ok(Some(reply)) => self.send_atomic_swap_message(&reply).await?,
+                Ok(None) => (),
+                Err(err) => tracing::warn!("Could not handle swap message: {}", err),
+            }
+        }
+
+        let swap = self.check_atomic_swap(&swap.offer.hash).await?;
+
+        if let Some(message) = swap.pending_message() {
+            self.send_atomic_swap_message(&message).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preimage_matches() {
+        let preimage = Secret::generate().to_string();
+        let hash = Sha256Hash::hash(&hex::decode(&preimage).unwrap()).to_string();
+
+        assert!(preimage_matches(&hash, &preimage));
+        assert!(!preimage_matches(&hash, &Secret::generate().to_string()));
+        assert!(!preimage_matches(&hash, "not hex"));
+    }
+
+    #[test]
+    fn test_swap_message_serialization() {
+        let message = SwapMessage::Claim {
+            hash: "a".repeat(64),
+            preimage: "b".repeat(64),
+        };
+
+        let json = serde_json::to_string(&message).unwrap();
+        assert!(json.contains(r#""type":"claim""#));
+        assert_eq!(serde_json::from_str::<SwapMessage>(&json).unwrap(), message);
+    }
+}
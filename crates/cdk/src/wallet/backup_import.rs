@@ -0,0 +1,249 @@
+//! Wallet backup import
+//!
+//! Reads the backups other Cashu wallets export, either lists of tokens or JSON
+//! dumps of proofs, and imports their proofs with [`Wallet::import_proofs`] so
+//! moving to a cdk wallet does not require spending everything first.
+//!
+//! [`Wallet::import_proofs`]: crate::Wallet::import_proofs
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde_json::Value;
+use tracing::instrument;
+
+use super::proof_import::ProofImport;
+use crate::mint_url::MintUrl;
+use crate::nuts::{Proof, Proofs, Token};
+use crate::paper_backup;
+use crate::types::ProofInfo;
+use crate::wallet::MultiMintWallet;
+use crate::{ensure_cdk, Error};
+
+/// Keys wallets store the mint url of a proof or backup under
+const MINT_URL_KEYS: [&str; 4] = ["mint", "mintUrl", "mintURL", "mint_url"];
+
+/// Proofs read from a wallet backup
+#[derive(Debug, Clone, Default)]
+pub struct WalletBackup {
+    /// Tokens found in the backup
+    pub tokens: Vec<Token>,
+    /// Proofs found in the backup, by mint
+    pub proofs: BTreeMap<MintUrl, Proofs>,
+}
+
+impl WalletBackup {
+    /// Parse a wallet backup
+    ///
+    /// Accepts text containing `cashu` tokens, a JSON list of tokens or of proofs,
+    /// a JSON object with `proofs` or `tokens` lists, files written by
+    /// `cdk-cli proof-export` and paper backups of a token. Proofs are assigned to
+    /// the mint url stored next to them or in the backup, and to `mint_url` when
+    /// the backup has none.
+    pub fn parse(backup: &str, mint_url: Option<MintUrl>) -> Result<Self, Error> {
+        let mut wallet_backup = Self::default();
+
+        match serde_json::from_str::<Value>(backup) {
+            Ok(value) => wallet_backup.read_value(&value, mint_url.as_ref())?,
+            Err(_) => {
+                for word in backup.split_whitespace() {
+                    if word.starts_with("cashu") {
+                        wallet_backup.tokens.push(Token::from_str(word)?);
+                    }
+                }
+
+                if wallet_backup.tokens.is_empty() {
+                    match Token::from_paper_backup(backup) {
+                        Ok(token) => wallet_backup.tokens.push(token),
+                        Err(paper_backup::Error::NotPaperBackup) => (),
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+            }
+        }
+
+        ensure_cdk!(
+            !wallet_backup.tokens.is_empty() || !wallet_backup.proofs.is_empty(),
+            Error::Custom("No tokens or proofs found in backup".to_string())
+        );
+
+        Ok(wallet_backup)
+    }
+
+    /// Number of proofs in the backup, not counting the proofs of tokens
+    pub fn proof_count(&self) -> usize {
+        self.proofs.values().map(|proofs| proofs.len()).sum()
+    }
+
+    fn read_value(&mut self, value: &Value, mint_url: Option<&MintUrl>) -> Result<(), Error> {
+        match value {
+            Value::String(token) => self.tokens.push(Token::from_str(token.trim())?),
+            Value::Array(values) => {
+                for value in values {
+                    self.read_value(value, mint_url)?;
+                }
+            }
+            Value::Object(object) => {
+                let mint_url = match value_mint_url(value)? {
+                    Some(mint_url) => Some(mint_url),
+                    None => backup_mint_url(value)?.or(mint_url.cloned()),
+                };
+
+                if object.contains_key("proof") {
+                    let proof_info: ProofInfo = serde_json::from_value(value.clone())?;
+                    self.add_proof(Some(proof_info.mint_url), proof_info.proof)?;
+                } else if object.contains_key("secret") {
+                    let proof: Proof = serde_json::from_value(value.clone())?;
+                    self.add_proof(mint_url, proof)?;
+                } else {
+                    for key in ["token", "tokens", "proofs"] {
+                        if let Some(value) = object.get(key) {
+                            self.read_value(value, mint_url.as_ref())?;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn add_proof(&mut self, mint_url: Option<MintUrl>, proof: Proof) -> Result<(), Error> {
+        let mint_url = mint_url.ok_or(Error::Custom(
+            "Backup has proofs without a mint url, set one for them".to_string(),
+        ))?;
+
+        self.proofs.entry(mint_url).or_default().push(proof);
+
+        Ok(())
+    }
+}
+
+/// Mint url stored in a JSON object
+fn value_mint_url(value: &Value) -> Result<Option<MintUrl>, Error> {
+    for key in MINT_URL_KEYS {
+        if let Some(Value::String(mint_url)) = value.get(key) {
+            return Ok(Some(MintUrl::from_str(mint_url)?));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Mint url of a backup listing a single mint under `mints`
+fn backup_mint_url(value: &Value) -> Result<Option<MintUrl>, Error> {
+    match value.get("mints") {
+        Some(Value::Array(mints)) if mints.len() == 1 => match &mints[0] {
+            Value::String(mint_url) => Ok(Some(MintUrl::from_str(mint_url)?)),
+            mint => value_mint_url(mint),
+        },
+        _ => Ok(None),
+    }
+}
+
+impl MultiMintWallet {
+    /// Import the proofs of a wallet backup
+    ///
+    /// Mints of the backup that are not in the wallet are added. Proofs are checked
+    /// with their mint and only unspent proofs of the wallet's unit are imported.
+    #[instrument(skip_all)]
+    pub async fn import_backup(
+        &self,
+        backup: WalletBackup,
+    ) -> Result<BTreeMap<MintUrl, ProofImport>, Error> {
+        let WalletBackup { tokens, mut proofs } = backup;
+
+        for token in &tokens {
+            let mint_url = token.mint_url()?;
+            let wallet = self.get_or_add_wallet(&mint_url).await?;
+
+            // Tokens may refer to keysets by short id, resolved with the mint's current keysets
+            wallet.refresh_keysets().await?;
+
+            let keysets = wallet
+                .localstore
+                .get_mint_keysets(mint_url.clone())
+                .await?
+                .unwrap_or_default();
+
+            proofs
+                .entry(mint_url)
+                .or_default()
+                .extend(token.proofs(&keysets)?);
+        }
+
+        let mut imports = BTreeMap::new();
+
+        for (mint_url, proofs) in proofs {
+            let wallet = self.get_or_add_wallet(&mint_url).await?;
+            let import = wallet.import_proofs(proofs).await?;
+
+            tracing::info!(
+                "Imported {} proofs worth {} from backup for mint {}",
+                import.imported,
+                import.amount,
+                mint_url
+            );
+
+            imports.insert(mint_url, import);
+        }
+
+        Ok(imports)
+    }
+
+    async fn get_or_add_wallet(&self, mint_url: &MintUrl) -> Result<crate::Wallet, Error> {
+        if !self.has_mint(mint_url).await {
+            self.add_mint(mint_url.clone(), None).await?;
+        }
+
+        self.get_wallet(mint_url).await.ok_or(Error::UnknownMint {
+            mint_url: mint_url.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROOF: &str = r#"{"amount":2,"id":"009a1f293253e41e","secret":"b6f95821bfe652b60dd6f600587e2f3a99835a0c25b814383eab0cd39ab41c75","C":"025ae8da2966ca98efb09d708c13abfa0d914e0a979182ac34809e261869a0a42e"}"#;
+
+    #[test]
+    fn test_parse_proof_backups() {
+        let mint_url = MintUrl::from_str("https://testnut.cashu.space").unwrap();
+
+        let backup = format!(r#"{{"proofs":[{PROOF}],"mints":["{mint_url}"]}}"#);
+        let backup = WalletBackup::parse(&backup, None).unwrap();
+        assert_eq!(backup.proofs[&mint_url].len(), 1);
+
+        let proof = PROOF.replacen('{', r#"{"mintUrl":"https://testnut.cashu.space","#, 1);
+        let backup = WalletBackup::parse(&format!("[{proof},{proof}]"), None).unwrap();
+        assert_eq!(backup.proof_count(), 2);
+
+        assert!(WalletBackup::parse(&format!("[{PROOF}]"), None).is_err());
+        let backup = WalletBackup::parse(&format!("[{PROOF}]"), Some(mint_url.clone())).unwrap();
+        assert_eq!(backup.proofs[&mint_url].len(), 1);
+    }
+
+    #[test]
+    fn test_parse_token_backups() {
+        let token = "cashuAeyJ0b2tlbiI6W3sicHJvb2ZzIjpbeyJhbW91bnQiOjIsInNlY3JldCI6ImI2Zjk1ODIxYmZlNjUyYjYwZGQ2ZjYwMDU4N2UyZjNhOTk4MzVhMGMyNWI4MTQzODNlYWIwY2QzOWFiNDFjNzUiLCJDIjoiMDI1YWU4ZGEyOTY2Y2E5OGVmYjA5ZDcwOGMxM2FiZmEwZDkxNGUwYTk3OTE4MmFjMzQ4MDllMjYxODY5YTBhNDJlIiwicmVzZXJ2ZWQiOmZhbHNlLCJpZCI6IjAwOWExZjI5MzI1M2U0MWUifSx7ImFtb3VudCI6Miwic2VjcmV0IjoiZjU0Y2JjNmNhZWZmYTY5MTUyOTgyM2M1MjU1MDkwYjRhMDZjNGQ3ZDRjNzNhNDFlZTFkNDBlM2ExY2EzZGZhNyIsIkMiOiIwMjMyMTIzN2JlYjcyMWU3NGI1NzcwNWE5MjJjNjUxMGQwOTYyYzAzNzlhZDM0OTJhMDYwMDliZTAyNjA5ZjA3NTAiLCJyZXNlcnZlZCI6ZmFsc2UsImlkIjoiMDA5YTFmMjkzMjUzZTQxZSJ9LHsiYW1vdW50IjoxLCJzZWNyZXQiOiJhNzdhM2NjODY4YWM4ZGU3YmNiOWMxMzJmZWI3YzEzMDY4Nzg3ODk5Yzk3YTk2NWE2ZThkZTFiMzliMmQ2NmQ3IiwiQyI6IjAzMTY0YTMxNWVhNjM0NGE5NWI2NzM1NzBkYzg0YmZlMTQ2NDhmMTQwM2EwMDJiZmJlMDhlNWFhMWE0NDQ0YWE0MCIsInJlc2VydmVkIjpmYWxzZSwiaWQiOiIwMDlhMWYyOTMyNTNlNDFlIn1dLCJtaW50IjoiaHR0cHM6Ly90ZXN0bnV0LmNhc2h1LnNwYWNlIn1dLCJ1bml0Ijoic2F0In0=";
+
+        let backup = WalletBackup::parse(&format!("{token}\n{token}\n"), None).unwrap();
+        assert_eq!(backup.tokens.len(), 2);
+
+        let backup = WalletBackup::parse(&format!(r#"{{"tokens":["{token}"]}}"#), None).unwrap();
+        assert_eq!(backup.tokens.len(), 1);
+
+        let sheet = Token::from_str(token)
+            .unwrap()
+            .to_paper_backup()
+            .unwrap()
+            .sheet(paper_backup::PaperFormat::Words, None);
+        let backup = WalletBackup::parse(&sheet, None).unwrap();
+        assert_eq!(backup.tokens[0].value().unwrap(), crate::Amount::from(5));
+
+        assert!(WalletBackup::parse("no ecash here", None).is_err());
+    }
+}
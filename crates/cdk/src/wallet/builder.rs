@@ -1,12 +1,11 @@
-#[cfg(feature = "auth")]
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use cdk_common::database;
 #[cfg(feature = "auth")]
 use cdk_common::AuthToken;
-#[cfg(feature = "auth")]
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 
 use crate::cdk_database::WalletDatabase;
 use crate::error::Error;
@@ -14,7 +13,8 @@ use crate::mint_url::MintUrl;
 use crate::nuts::CurrencyUnit;
 #[cfg(feature = "auth")]
 use crate::wallet::auth::AuthWallet;
-use crate::wallet::{HttpClient, MintConnector, SubscriptionManager, Wallet};
+use crate::wallet::melt::MELT_PROGRESS_CAPACITY;
+use crate::wallet::{ConfirmationHandler, HttpClient, MintConnector, SubscriptionManager, Wallet};
 
 /// Builder for creating a new [`Wallet`]
 #[derive(Debug)]
@@ -28,6 +28,9 @@ pub struct WalletBuilder {
     seed: Option<[u8; 64]>,
     use_http_subscription: bool,
     client: Option<Arc<dyn MintConnector + Send + Sync>>,
+    confirmation_handler: Option<Arc<dyn ConfirmationHandler>>,
+    archived_proof_retention: Option<Duration>,
+    keyset_expiry_migration: Option<Duration>,
 }
 
 impl Default for WalletBuilder {
@@ -42,6 +45,9 @@ impl Default for WalletBuilder {
             seed: None,
             client: None,
             use_http_subscription: false,
+            confirmation_handler: None,
+            archived_proof_retention: None,
+            keyset_expiry_migration: None,
         }
     }
 }
@@ -117,6 +123,12 @@ impl WalletBuilder {
         self
     }
 
+    /// Set the handler approving spends above the spending limits
+    pub fn confirmation_handler(mut self, handler: Arc<dyn ConfirmationHandler>) -> Self {
+        self.confirmation_handler = Some(handler);
+        self
+    }
+
     /// Set auth CAT (Clear Auth Token)
     #[cfg(feature = "auth")]
     pub fn set_auth_cat(mut self, cat: String) -> Self {
@@ -130,6 +142,18 @@ impl WalletBuilder {
         self
     }
 
+    /// Keep archived spent proofs for `retention`, they are kept until pruned by default
+    pub fn archived_proof_retention(mut self, retention: Duration) -> Self {
+        self.archived_proof_retention = Some(retention);
+        self
+    }
+
+    /// Move proofs out of keysets expiring within `within` before sending and receiving
+    pub fn keyset_expiry_migration(mut self, within: Duration) -> Self {
+        self.keyset_expiry_migration = Some(within);
+        self
+    }
+
     /// Build the wallet
     pub fn build(self) -> Result<Wallet, Error> {
         let mint_url = self
@@ -145,6 +169,13 @@ impl WalletBuilder {
             .seed
             .ok_or(Error::Custom("Seed required".to_string()))?;
 
+        // Wallets reaching the mint through the default client share its connection,
+        // unless they authenticate with their own auth wallet
+        #[cfg(feature = "auth")]
+        let share_subscriptions = self.client.is_none() && self.auth_wallet.is_none();
+        #[cfg(not(feature = "auth"))]
+        let share_subscriptions = self.client.is_none();
+
         let client = match self.client {
             Some(client) => client,
             None => {
@@ -171,7 +202,16 @@ impl WalletBuilder {
             auth_wallet: Arc::new(RwLock::new(self.auth_wallet)),
             seed,
             client: client.clone(),
-            subscription: SubscriptionManager::new(client, self.use_http_subscription),
+            subscription: if share_subscriptions {
+                SubscriptionManager::shared(client, self.use_http_subscription)
+            } else {
+                SubscriptionManager::new(client, self.use_http_subscription)
+            },
+            restore_scans: Arc::new(RwLock::new(HashMap::new())),
+            confirmation_handler: Arc::new(RwLock::new(self.confirmation_handler)),
+            melt_progress: broadcast::channel(MELT_PROGRESS_CAPACITY).0,
+            archived_proof_retention: self.archived_proof_retention,
+            keyset_expiry_migration: self.keyset_expiry_migration,
         })
     }
 }
@@ -0,0 +1,219 @@
+//! Mint capabilities
+//!
+//! Typed view over the NUTs a mint advertises in its info, used by the wallet
+//! to fail early with [`Error::MintDoesNotSupport`] instead of an opaque HTTP error.
+
+use tracing::instrument;
+
+use crate::nuts::{CurrencyUnit, MintInfo, PaymentMethod};
+use crate::{ensure_cdk, Error, Wallet};
+
+/// Capabilities advertised by a mint for the wallet's unit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MintCapabilities {
+    /// Bolt11 minting is supported (NUT-04)
+    pub supports_bolt11_mint: bool,
+    /// Bolt11 melting is supported (NUT-05)
+    pub supports_bolt11_melt: bool,
+    /// Bolt12 minting is supported
+    pub supports_bolt12_mint: bool,
+    /// Bolt12 melting is supported
+    pub supports_bolt12_melt: bool,
+    /// Keysend melting is supported
+    pub supports_keysend_melt: bool,
+    /// Proof state checks are supported (NUT-07)
+    pub supports_state_check: bool,
+    /// Restore from seed is supported (NUT-09)
+    pub supports_restore: bool,
+    /// Pay to public key spending conditions are supported (NUT-10 and NUT-11)
+    pub supports_p2pk: bool,
+    /// Hashed timelock spending conditions are supported (NUT-10 and NUT-14)
+    pub supports_htlc: bool,
+    /// DLEQ proofs are returned with signatures (NUT-12)
+    pub supports_dleq: bool,
+    /// Multi-path payments are supported (NUT-15)
+    pub supports_mpp: bool,
+    /// Websocket subscriptions are supported (NUT-17)
+    pub supports_websocket: bool,
+    /// Mint quotes can be locked to a public key (NUT-20)
+    pub supports_mint_quote_pubkey: bool,
+    /// Some endpoints require clear auth (NUT-21)
+    pub requires_clear_auth: bool,
+    /// Some endpoints require blind auth (NUT-22)
+    pub requires_blind_auth: bool,
+}
+
+impl MintCapabilities {
+    /// Derive [`MintCapabilities`] from [`MintInfo`] for the given unit
+    pub fn new(mint_info: &MintInfo, unit: &CurrencyUnit) -> Self {
+        let nuts = &mint_info.nuts;
+
+        let can_mint = |method: PaymentMethod| {
+            !nuts.nut04.disabled && nuts.nut04.get_settings(unit, &method).is_some()
+        };
+        let can_melt = |method: PaymentMethod| {
+            !nuts.nut05.disabled && nuts.nut05.get_settings(unit, &method).is_some()
+        };
+
+        #[cfg(feature = "auth")]
+        let (requires_clear_auth, requires_blind_auth) =
+            (nuts.nut21.is_some(), nuts.nut22.is_some());
+        #[cfg(not(feature = "auth"))]
+        let (requires_clear_auth, requires_blind_auth) = (false, false);
+
+        Self {
+            supports_bolt11_mint: can_mint(PaymentMethod::Bolt11),
+            supports_bolt11_melt: can_melt(PaymentMethod::Bolt11),
+            supports_bolt12_mint: can_mint(PaymentMethod::Bolt12),
+            supports_bolt12_melt: can_melt(PaymentMethod::Bolt12),
+            supports_keysend_melt: can_melt(PaymentMethod::keysend()),
+            supports_state_check: nuts.nut07.supported,
+            supports_restore: nuts.nut09.supported,
+            supports_p2pk: nuts.nut10.supported && nuts.nut11.supported,
+            supports_htlc: nuts.nut10.supported && nuts.nut14.supported,
+            supports_dleq: nuts.nut12.supported,
+            supports_mpp: nuts.nut15.methods.iter().any(|m| &m.unit == unit),
+            supports_websocket: !nuts.nut17.supported.is_empty(),
+            supports_mint_quote_pubkey: nuts.nut20.supported,
+            requires_clear_auth,
+            requires_blind_auth,
+        }
+    }
+
+    /// Bolt12 minting and melting are both supported
+    pub fn supports_bolt12(&self) -> bool {
+        self.supports_bolt12_mint && self.supports_bolt12_melt
+    }
+}
+
+impl Wallet {
+    /// Capabilities of the mint for the wallet's unit
+    ///
+    /// Uses the stored mint info and fetches it from the mint if it is not yet known.
+    #[instrument(skip(self))]
+    pub async fn capabilities(&self) -> Result<MintCapabilities, Error> {
+        let mint_info = match self.localstore.get_mint(self.mint_url.clone()).await? {
+            Some(mint_info) => mint_info,
+            None => self
+                .fetch_mint_info()
+                .await?
+                .ok_or(Error::CouldNotGetMintInfo)?,
+        };
+
+        Ok(MintCapabilities::new(&mint_info, &self.unit))
+    }
+
+    /// Check the mint advertises a capability before calling it
+    ///
+    /// If the mint info cannot be retrieved the check is skipped and the request is left to the mint.
+    pub(crate) async fn ensure_capability<F>(&self, capability: &str, check: F) -> Result<(), Error>
+    where
+        F: FnOnce(&MintCapabilities) -> bool,
+    {
+        match self.capabilities().await {
+            Ok(capabilities) => {
+                ensure_cdk!(
+                    check(&capabilities),
+                    Error::MintDoesNotSupport(capability.to_string())
+                );
+                Ok(())
+            }
+            Err(Error::CouldNotGetMintInfo) => {
+                tracing::debug!(
+                    "Mint info unavailable, skipping {} capability check",
+                    capability
+                );
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nuts::nut04::MintMethodSettings;
+    use crate::nuts::nut05::MeltMethodSettings;
+    use crate::nuts::{nut04, nut05, Nuts};
+
+    fn mint_info(nuts: Nuts) -> MintInfo {
+        MintInfo::new().nuts(nuts)
+    }
+
+    fn bolt11_nuts() -> Nuts {
+        Nuts::new()
+            .nut04(nut04::Settings::new(
+                vec![MintMethodSettings {
+                    method: PaymentMethod::Bolt11,
+                    unit: CurrencyUnit::Sat,
+                    min_amount: None,
+                    max_amount: None,
+                    options: None,
+                }],
+                false,
+            ))
+            .nut05(nut05::Settings::new(
+                vec![MeltMethodSettings {
+                    method: PaymentMethod::Bolt11,
+                    unit: CurrencyUnit::Sat,
+                    min_amount: None,
+                    max_amount: None,
+                    options: None,
+                }],
+                false,
+            ))
+    }
+
+    #[test]
+    fn test_capabilities_from_mint_info() {
+        let nuts = bolt11_nuts()
+            .nut07(true)
+            .nut09(true)
+            .nut10(true)
+            .nut11(true)
+            .nut12(true);
+
+        let capabilities = MintCapabilities::new(&mint_info(nuts), &CurrencyUnit::Sat);
+
+        assert!(capabilities.supports_bolt11_mint);
+        assert!(capabilities.supports_bolt11_melt);
+        assert!(!capabilities.supports_bolt12());
+        assert!(!capabilities.supports_keysend_melt);
+        assert!(capabilities.supports_state_check);
+        assert!(capabilities.supports_restore);
+        assert!(capabilities.supports_p2pk);
+        assert!(!capabilities.supports_htlc);
+        assert!(capabilities.supports_dleq);
+        assert!(!capabilities.supports_websocket);
+    }
+
+    #[test]
+    fn test_capabilities_depend_on_unit() {
+        let capabilities = MintCapabilities::new(&mint_info(bolt11_nuts()), &CurrencyUnit::Usd);
+
+        assert!(!capabilities.supports_bolt11_mint);
+        assert!(!capabilities.supports_bolt11_melt);
+    }
+
+    #[test]
+    fn test_disabled_minting_is_unsupported() {
+        let mut nuts = bolt11_nuts();
+        nuts.nut04.disabled = true;
+
+        let capabilities = MintCapabilities::new(&mint_info(nuts), &CurrencyUnit::Sat);
+
+        assert!(!capabilities.supports_bolt11_mint);
+        assert!(capabilities.supports_bolt11_melt);
+    }
+
+    #[test]
+    fn test_htlc_requires_nut10() {
+        let nuts = bolt11_nuts().nut11(true).nut14(true);
+
+        let capabilities = MintCapabilities::new(&mint_info(nuts), &CurrencyUnit::Sat);
+
+        assert!(!capabilities.supports_p2pk);
+        assert!(!capabilities.supports_htlc);
+    }
+}
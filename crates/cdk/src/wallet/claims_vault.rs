@@ -0,0 +1,334 @@
+//! Claims vault
+//!
+//! Encrypted store of tokens the wallet has sent but the receiver has not yet
+//! claimed, so a lost token can be exported again. Entries are encrypted with a
+//! key derived from the wallet seed and kept in the wallet database key-value store.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use cdk_common::wallet::TransactionId;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use getrandom::getrandom;
+use tracing::instrument;
+
+use crate::nuts::{KeySetInfo, State, Token};
+use crate::util::hex;
+use crate::{ensure_cdk, Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const CLAIMS_VAULT_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for the claims vault
+const CLAIMS_VAULT_SECONDARY_NAMESPACE: &str = "claims_vault";
+/// Domain separation tag for the vault encryption key
+const CLAIMS_VAULT_KEY_TAG: &[u8] = b"cdk_claims_vault";
+/// ChaCha20-Poly1305 nonce length
+const NONCE_LEN: usize = 12;
+
+impl Wallet {
+    /// Cipher keyed with `HMAC-SHA256(seed, "cdk_claims_vault")`
+    fn claims_vault_cipher(&self) -> ChaCha20Poly1305 {
+        let mut engine = hmac::HmacEngine::<sha256::Hash>::new(&self.seed);
+        engine.input(CLAIMS_VAULT_KEY_TAG);
+        let key = hmac::Hmac::<sha256::Hash>::from_engine(engine).to_byte_array();
+
+        ChaCha20Poly1305::new(Key::from_slice(&key))
+    }
+
+    /// Encrypt a token into a vault entry of `nonce || ciphertext`
+    fn claims_vault_encrypt(&self, token: &Token) -> Result<Vec<u8>, Error> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom(&mut nonce).map_err(|e| Error::ClaimsVault(e.to_string()))?;
+
+        let ciphertext = self
+            .claims_vault_cipher()
+            .encrypt(Nonce::from_slice(&nonce), token.to_string().as_bytes())
+            .map_err(|_| Error::ClaimsVault("Could not encrypt token".to_string()))?;
+
+        Ok([nonce.as_slice(), ciphertext.as_slice()].concat())
+    }
+
+    /// Decrypt a vault entry back into a token
+    fn claims_vault_decrypt(&self, entry: &[u8]) -> Result<Token, Error> {
+        ensure_cdk!(
+            entry.len() > NONCE_LEN,
+            Error::ClaimsVault("Entry is too short".to_string())
+        );
+
+        let (nonce, ciphertext) = entry.split_at(NONCE_LEN);
+
+        let plaintext = self
+            .claims_vault_cipher()
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::ClaimsVault("Could not decrypt entry".to_string()))?;
+
+        let token = String::from_utf8(plaintext)
+            .map_err(|_| Error::ClaimsVault("Entry is not a valid token".to_string()))?;
+
+        Ok(Token::from_str(&token)?)
+    }
+
+    /// Store a sent token in the claims vault
+    pub(crate) async fn claims_vault_store(
+        &self,
+        transaction_id: TransactionId,
+        token: &Token,
+    ) -> Result<(), Error> {
+        let entry = self.claims_vault_encrypt(token)?;
+
+        self.localstore
+            .kv_write(
+                CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                CLAIMS_VAULT_SECONDARY_NAMESPACE,
+                &transaction_id.to_string(),
+                &entry,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove a token from the claims vault
+    pub(crate) async fn claims_vault_remove(
+        &self,
+        transaction_id: TransactionId,
+    ) -> Result<(), Error> {
+        self.localstore
+            .kv_remove(
+                CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                CLAIMS_VAULT_SECONDARY_NAMESPACE,
+                &transaction_id.to_string(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sent tokens for this mint and unit that have not been claimed
+    ///
+    /// Checks the state of every vaulted token with the mint and removes the
+    /// tokens whose proofs have all been spent.
+    #[instrument(skip(self))]
+    pub async fn pending_sent_tokens(&self) -> Result<Vec<Token>, Error> {
+        let keysets_info = self.load_mint_keysets().await?;
+        let mut pending = Vec::new();
+
+        for key in self
+            .localstore
+            .kv_list(
+                CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                CLAIMS_VAULT_SECONDARY_NAMESPACE,
+            )
+            .await?
+        {
+            match self.claims_vault_pending_entry(&key, &keysets_info).await {
+                Ok(Some(token)) => pending.push(token),
+                Ok(None) => (),
+                Err(err) => tracing::warn!("Skipping claims vault entry {}: {}", key, err),
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// Token of a vault entry if it was sent from this mint and unit and is not yet claimed
+    ///
+    /// Claimed tokens are removed from the vault.
+    async fn claims_vault_pending_entry(
+        &self,
+        key: &str,
+        keysets_info: &[KeySetInfo],
+    ) -> Result<Option<Token>, Error> {
+        let entry = match self
+            .localstore
+            .kv_read(
+                CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                CLAIMS_VAULT_SECONDARY_NAMESPACE,
+                key,
+            )
+            .await?
+        {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let token = self.claims_vault_decrypt(&entry)?;
+
+        if token.mint_url()? != self.mint_url || token.unit() != Some(self.unit.clone()) {
+            return Ok(None);
+        }
+
+        let proofs = token.proofs(keysets_info)?;
+
+        // Tokens whose state cannot be checked are listed, so they are not lost
+        let all_spent = match self.check_proofs_spent(proofs).await {
+            Ok(states) => states
+                .iter()
+                .all(|proof_state| proof_state.state == State::Spent),
+            Err(err) => {
+                tracing::warn!("Could not check state of vaulted token {}: {}", key, err);
+                false
+            }
+        };
+
+        if !all_spent {
+            return Ok(Some(token));
+        }
+
+        tracing::debug!("Removing claimed token {} from claims vault", key);
+        self.localstore
+            .kv_remove(
+                CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                CLAIMS_VAULT_SECONDARY_NAMESPACE,
+                key,
+            )
+            .await?;
+
+        Ok(None)
+    }
+
+    /// Export the claims vault as a JSON backup
+    ///
+    /// Entries stay encrypted, so the backup can only be imported by a wallet
+    /// with the same seed. The vault is shared by all wallets using the same
+    /// database, so the backup includes tokens sent from every mint.
+    #[instrument(skip(self))]
+    pub async fn export_claims_vault(&self) -> Result<String, Error> {
+        let mut entries = BTreeMap::new();
+
+        for key in self
+            .localstore
+            .kv_list(
+                CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                CLAIMS_VAULT_SECONDARY_NAMESPACE,
+            )
+            .await?
+        {
+            if let Some(entry) = self
+                .localstore
+                .kv_read(
+                    CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                    CLAIMS_VAULT_SECONDARY_NAMESPACE,
+                    &key,
+                )
+                .await?
+            {
+                entries.insert(key, hex::encode(entry));
+            }
+        }
+
+        Ok(serde_json::to_string(&entries)?)
+    }
+
+    /// Import a claims vault backup created by [`Wallet::export_claims_vault`]
+    ///
+    /// Every entry must decrypt with this wallet's seed before anything is
+    /// written. Returns the number of imported entries.
+    #[instrument(skip_all)]
+    pub async fn import_claims_vault(&self, backup: &str) -> Result<usize, Error> {
+        let backup: BTreeMap<String, String> = serde_json::from_str(backup)?;
+
+        let mut entries = Vec::with_capacity(backup.len());
+
+        for (key, entry) in backup {
+            let transaction_id =
+                TransactionId::from_str(&key).map_err(|_| Error::InvalidTransactionId)?;
+            let entry = hex::decode(entry)?;
+
+            self.claims_vault_decrypt(&entry)?;
+
+            entries.push((transaction_id, entry));
+        }
+
+        for (transaction_id, entry) in entries.iter() {
+            self.localstore
+                .kv_write(
+                    CLAIMS_VAULT_PRIMARY_NAMESPACE,
+                    CLAIMS_VAULT_SECONDARY_NAMESPACE,
+                    &transaction_id.to_string(),
+                    entry,
+                )
+                .await?;
+        }
+
+        Ok(entries.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::nuts::nut00::ProofsMethods;
+    use crate::nuts::CurrencyUnit;
+
+    const TOKEN: &str = "cashuAeyJ0b2tlbiI6W3sicHJvb2ZzIjpbeyJhbW91bnQiOjIsInNlY3JldCI6ImI2Zjk1ODIxYmZlNjUyYjYwZGQ2ZjYwMDU4N2UyZjNhOTk4MzVhMGMyNWI4MTQzODNlYWIwY2QzOWFiNDFjNzUiLCJDIjoiMDI1YWU4ZGEyOTY2Y2E5OGVmYjA5ZDcwOGMxM2FiZmEwZDkxNGUwYTk3OTE4MmFjMzQ4MDllMjYxODY5YTBhNDJlIiwiaWQiOiIwMDlhMWYyOTMyNTNlNDFlIn1dLCJtaW50IjoiaHR0cHM6Ly90ZXN0bnV0LmNhc2h1LnNwYWNlIn1dLCJ1bml0Ijoic2F0In0=";
+
+    async fn test_wallet(seed: [u8; 64]) -> Wallet {
+        let localstore = cdk_sqlite::wallet::memory::empty()
+            .await
+            .expect("Failed to create in-memory database");
+
+        Wallet::new(
+            "https://testnut.cashu.space",
+            CurrencyUnit::Sat,
+            Arc::new(localstore),
+            seed,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_claims_vault_encryption_round_trip() {
+        let wallet = test_wallet([1u8; 64]).await;
+        let token = Token::from_str(TOKEN).unwrap();
+
+        let entry = wallet.claims_vault_encrypt(&token).unwrap();
+        assert!(!String::from_utf8_lossy(&entry).contains("testnut"));
+        assert_ne!(entry, wallet.claims_vault_encrypt(&token).unwrap());
+
+        assert_eq!(wallet.claims_vault_decrypt(&entry).unwrap(), token);
+
+        // Entries only decrypt with the seed that encrypted them
+        let other_wallet = test_wallet([2u8; 64]).await;
+        assert!(other_wallet.claims_vault_decrypt(&entry).is_err());
+
+        let mut tampered = entry.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(wallet.claims_vault_decrypt(&tampered).is_err());
+        assert!(wallet.claims_vault_decrypt(&entry[..NONCE_LEN]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_claims_vault_backup_round_trip() {
+        let wallet = test_wallet([1u8; 64]).await;
+        let token = Token::from_str(TOKEN).unwrap();
+        let transaction_id = TransactionId::new(token.proofs(&[]).unwrap().ys().unwrap());
+
+        wallet
+            .claims_vault_store(transaction_id, &token)
+            .await
+            .unwrap();
+        let backup = wallet.export_claims_vault().await.unwrap();
+
+        // A wallet with another seed cannot import the backup
+        let other_wallet = test_wallet([2u8; 64]).await;
+        assert!(other_wallet.import_claims_vault(&backup).await.is_err());
+        assert_eq!(other_wallet.export_claims_vault().await.unwrap(), "{}");
+
+        let restored_wallet = test_wallet([1u8; 64]).await;
+        assert_eq!(
+            restored_wallet.import_claims_vault(&backup).await.unwrap(),
+            1
+        );
+        assert_eq!(restored_wallet.export_claims_vault().await.unwrap(), backup);
+
+        wallet.claims_vault_remove(transaction_id).await.unwrap();
+        assert_eq!(wallet.export_claims_vault().await.unwrap(), "{}");
+    }
+}
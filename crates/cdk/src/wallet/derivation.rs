@@ -0,0 +1,218 @@
+//! Deterministic secret derivation audit
+//!
+//! Reports the NUT-13 counter of every keyset of the wallet's mint and the gaps
+//! found by the last [`Wallet::restore`], so recovery issues can be diagnosed
+//! without reading the wallet database, and tunes how restore scans counters.
+
+use std::ops::Range;
+
+use bitcoin::bip32::DerivationPath;
+use tracing::instrument;
+
+use crate::mint_url::MintUrl;
+use crate::nuts::nut13::counter_derivation_path;
+use crate::nuts::{CurrencyUnit, Id};
+use crate::{Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const RESTORE_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for the highest signed counter found by restore
+const RESTORE_HIGH_WATER_SECONDARY_NAMESPACE: &str = "restore_high_water";
+
+/// How [`Wallet::restore_with_options`] scans counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestoreOptions {
+    /// Counters asked to the mint per request
+    pub batch_size: u32,
+    /// Batches in a row without any signature after which the scan stops
+    pub gap_limit: u32,
+    /// Scan one more batch when a signature is found in the last tenth of a batch
+    pub adaptive: bool,
+    /// Start at the highest signed counter found by the previous restore instead of zero
+    pub incremental: bool,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            gap_limit: 3,
+            adaptive: false,
+            incremental: false,
+        }
+    }
+}
+
+/// Outcome of the last restore of a keyset
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreScan {
+    /// Counter the scan started at
+    pub started_at: u32,
+    /// One past the last counter asked to the mint
+    pub scanned_until: u32,
+    /// One past the highest counter the mint had a signature for, or `started_at`
+    pub used_until: u32,
+    /// Counters without a signature between `started_at` and `used_until`
+    pub gaps: Vec<Range<u32>>,
+}
+
+impl RestoreScan {
+    /// Scan starting at `counter`
+    pub(crate) fn starting_at(counter: u32) -> Self {
+        Self {
+            started_at: counter,
+            scanned_until: counter,
+            used_until: counter,
+            gaps: Vec::new(),
+        }
+    }
+
+    /// Record that the mint had a signature for `counter`
+    ///
+    /// Counters must be recorded in increasing order.
+    pub(crate) fn record_used(&mut self, counter: u32) {
+        if counter > self.used_until {
+            self.gaps.push(self.used_until..counter);
+        }
+
+        self.used_until = counter + 1;
+    }
+}
+
+/// Derivation state of a keyset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeysetDerivation {
+    /// Keyset id
+    pub keyset_id: Id,
+    /// Keyset unit
+    pub unit: CurrencyUnit,
+    /// Whether the mint signs with this keyset
+    pub active: bool,
+    /// Counter the next secret is derived at
+    pub next_counter: u32,
+    /// BIP32 path of the next secret, `None` for keysets deriving secrets with HMAC-SHA256
+    pub next_path: Option<DerivationPath>,
+    /// Counter incremental restores start at
+    pub restore_high_water: u32,
+    /// Last restore of the keyset by this wallet, if any
+    pub restore: Option<RestoreScan>,
+}
+
+impl KeysetDerivation {
+    /// Whether the counter is below secrets the mint already signed
+    ///
+    /// Deriving at such a counter creates outputs the mint rejects as already signed.
+    pub fn counter_behind_restore(&self) -> bool {
+        self.restore
+            .as_ref()
+            .is_some_and(|restore| self.next_counter < restore.used_until)
+    }
+}
+
+/// Derivation state of all the keysets of a mint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationReport {
+    /// Mint url
+    pub mint_url: MintUrl,
+    /// Keysets of the mint
+    pub keysets: Vec<KeysetDerivation>,
+}
+
+impl Wallet {
+    /// Highest signed counter of a keyset found by previous restores, plus one
+    pub(crate) async fn restore_high_water(&self, keyset_id: &Id) -> Result<u32, Error> {
+        let high_water = self
+            .localstore
+            .kv_read(
+                RESTORE_PRIMARY_NAMESPACE,
+                RESTORE_HIGH_WATER_SECONDARY_NAMESPACE,
+                &keyset_id.to_string(),
+            )
+            .await?;
+
+        match high_water.and_then(|bytes| bytes.try_into().ok()) {
+            Some(bytes) => Ok(u32::from_be_bytes(bytes)),
+            None => Ok(0),
+        }
+    }
+
+    /// Persist the highest signed counter found by a restore, it never decreases
+    pub(crate) async fn set_restore_high_water(
+        &self,
+        keyset_id: &Id,
+        used_until: u32,
+    ) -> Result<(), Error> {
+        if used_until <= self.restore_high_water(keyset_id).await? {
+            return Ok(());
+        }
+
+        self.localstore
+            .kv_write(
+                RESTORE_PRIMARY_NAMESPACE,
+                RESTORE_HIGH_WATER_SECONDARY_NAMESPACE,
+                &keyset_id.to_string(),
+                &used_until.to_be_bytes(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Report the counters and restore gaps of the mint's keysets
+    ///
+    /// Restore gaps are only known for keysets restored by this wallet instance.
+    #[instrument(skip(self))]
+    pub async fn derivation_report(&self) -> Result<DerivationReport, Error> {
+        let keysets = self
+            .localstore
+            .get_mint_keysets(self.mint_url.clone())
+            .await?
+            .unwrap_or_default();
+
+        let restore_scans = self.restore_scans.read().await;
+
+        let mut derivations = Vec::with_capacity(keysets.len());
+
+        for keyset in keysets {
+            let next_counter = self.localstore.get_keyset_counter(&keyset.id).await?;
+
+            derivations.push(KeysetDerivation {
+                keyset_id: keyset.id,
+                unit: keyset.unit,
+                active: keyset.active,
+                next_counter,
+                next_path: counter_derivation_path(keyset.id, next_counter)?,
+                restore_high_water: self.restore_high_water(&keyset.id).await?,
+                restore: restore_scans.get(&keyset.id).cloned(),
+            });
+        }
+
+        Ok(DerivationReport {
+            mint_url: self.mint_url.clone(),
+            keysets: derivations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_scan_gaps() {
+        let mut scan = RestoreScan::default();
+
+        for counter in [0, 1, 4, 5, 150] {
+            scan.record_used(counter);
+        }
+
+        assert_eq!(scan.used_until, 151);
+        assert_eq!(scan.gaps, vec![2..4, 6..150]);
+
+        let mut scan = RestoreScan::starting_at(200);
+        scan.record_used(203);
+
+        assert_eq!(scan.used_until, 204);
+        assert_eq!(scan.gaps, vec![200..203]);
+    }
+}
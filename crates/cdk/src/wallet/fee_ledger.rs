@@ -0,0 +1,110 @@
+//! Fee ledger
+//!
+//! Every fee the wallet pays to a mint, the keyset input fees of swaps and
+//! melts and the lightning fees of melts, is recorded in the fee ledger of the
+//! wallet database so the cost of using a mint can be compared with others.
+
+use std::ops::{Bound, RangeBounds};
+use std::sync::Arc;
+
+use cdk_common::database::{self, WalletDatabase};
+use cdk_common::util::unix_time;
+use cdk_common::wallet::{FeeEntry, FeeKind};
+use tracing::instrument;
+
+use crate::mint_url::MintUrl;
+use crate::nuts::CurrencyUnit;
+use crate::{Amount, Error, Wallet};
+
+/// `since` inclusive and `until` exclusive unix timestamps of a range
+fn timestamp_bounds(range: &impl RangeBounds<u64>) -> (Option<u64>, Option<u64>) {
+    let since = match range.start_bound() {
+        Bound::Included(start) => Some(*start),
+        Bound::Excluded(start) => Some(start.saturating_add(1)),
+        Bound::Unbounded => None,
+    };
+    let until = match range.end_bound() {
+        Bound::Included(end) => end.checked_add(1),
+        Bound::Excluded(end) => Some(*end),
+        Bound::Unbounded => None,
+    };
+
+    (since, until)
+}
+
+/// Fee ledger entries of `unit` paid in `range`, optionally to one mint
+pub(crate) async fn fee_entries(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+    mint_url: Option<MintUrl>,
+    unit: &CurrencyUnit,
+    range: &impl RangeBounds<u64>,
+) -> Result<Vec<FeeEntry>, Error> {
+    let (since, until) = timestamp_bounds(range);
+
+    Ok(localstore
+        .list_fee_entries(mint_url, Some(unit.clone()), since, until)
+        .await?)
+}
+
+impl Wallet {
+    /// Record a fee paid to the mint in the fee ledger
+    ///
+    /// The fee is already paid when it is recorded, failing to record it is
+    /// logged rather than failing the operation that paid it.
+    pub(crate) async fn record_fee(&self, kind: FeeKind, amount: Amount, quote_id: Option<String>) {
+        if amount == Amount::ZERO {
+            return;
+        }
+
+        if let Err(err) = self
+            .localstore
+            .add_fee_entry(FeeEntry {
+                mint_url: self.mint_url.clone(),
+                unit: self.unit.clone(),
+                kind,
+                amount,
+                timestamp: unix_time(),
+                quote_id,
+            })
+            .await
+        {
+            tracing::warn!("Could not record {} fee of {}: {}", kind, amount, err);
+        }
+    }
+
+    /// Fees paid to the mint in the unit of this wallet within a range of unix timestamps
+    #[instrument(skip_all)]
+    pub async fn fee_entries(&self, range: impl RangeBounds<u64>) -> Result<Vec<FeeEntry>, Error> {
+        fee_entries(
+            &self.localstore,
+            Some(self.mint_url.clone()),
+            &self.unit,
+            &range,
+        )
+        .await
+    }
+
+    /// Total fees paid to the mint in the unit of this wallet within a range of unix timestamps
+    ///
+    /// `wallet.fees_paid(..)` returns every fee ever paid to the mint.
+    #[instrument(skip_all)]
+    pub async fn fees_paid(&self, range: impl RangeBounds<u64>) -> Result<Amount, Error> {
+        let entries = self.fee_entries(range).await?;
+
+        Amount::try_sum(entries.iter().map(|entry| entry.amount)).map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_bounds() {
+        assert_eq!(timestamp_bounds(&(..)), (None, None));
+        assert_eq!(timestamp_bounds(&(10..20)), (Some(10), Some(20)));
+        assert_eq!(timestamp_bounds(&(10..=20)), (Some(10), Some(21)));
+        assert_eq!(timestamp_bounds(&(10..)), (Some(10), None));
+        assert_eq!(timestamp_bounds(&(..=u64::MAX)), (None, None));
+    }
+}
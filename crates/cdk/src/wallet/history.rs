@@ -0,0 +1,324 @@
+//! History reconstruction
+//!
+//! A wallet restored from seed, or whose database lost its transactions, holds
+//! proofs without the records of how they got there. [`Wallet::rebuild_history`]
+//! adds the records that can be inferred: mint and melt quotes the mint reports
+//! as issued or paid, and a balancing entry for proofs held or spent that no
+//! record accounts for. Rebuilt records carry [`INFERRED_METADATA_KEY`], since
+//! their amounts, fees and times are best guesses.
+
+use std::collections::{HashMap, HashSet};
+
+use cdk_common::util::unix_time;
+use cdk_common::wallet::{
+    MeltQuote, MintQuote, Transaction, TransactionDirection, TransactionStatus,
+};
+use tracing::instrument;
+
+use crate::dhke::hash_to_curve;
+use crate::nuts::{MeltQuoteState, MintQuoteState, PaymentMethod, PublicKey, State};
+use crate::{Amount, Error, Wallet};
+
+/// Metadata key of transactions rebuilt instead of recorded when they happened
+pub const INFERRED_METADATA_KEY: &str = "inferred";
+/// Metadata key of what an inferred transaction was rebuilt from
+const INFERRED_FROM_METADATA_KEY: &str = "inferred_from";
+
+impl Wallet {
+    /// Rebuild missing transaction records from stored quotes and proofs
+    ///
+    /// Mint quotes the mint reports as issued become incoming transactions and
+    /// melt quotes it reports as paid become outgoing ones. Whatever difference
+    /// is left between the recorded history and the proofs held, including
+    /// locked savings, is recorded as a single incoming transaction of the
+    /// unaccounted proofs, or an outgoing one of the unaccounted archived proofs.
+    ///
+    /// Running it again only adds what is still missing. Returns the records added.
+    #[instrument(skip(self))]
+    pub async fn rebuild_history(&self) -> Result<Vec<Transaction>, Error> {
+        let existing = self.list_transactions(None).await?;
+        let known_quotes: HashSet<String> = existing
+            .iter()
+            .filter_map(|tx| tx.quote_id.clone())
+            .collect();
+
+        let mut rebuilt = Vec::new();
+
+        for quote in self.localstore.get_mint_quotes().await? {
+            if quote.mint_url != self.mint_url
+                || quote.unit != self.unit
+                || known_quotes.contains(&quote.id)
+            {
+                continue;
+            }
+
+            if let Some(tx) = self.infer_mint_transaction(&quote).await {
+                rebuilt.push(tx);
+            }
+        }
+
+        // Melt quotes stored before their mint was recorded are skipped, looking them
+        // up would send quote ids of other mints to this one
+        for quote in self.localstore.get_melt_quotes().await? {
+            if quote.mint_url.as_ref() != Some(&self.mint_url)
+                || quote.unit != self.unit
+                || known_quotes.contains(&quote.id)
+            {
+                continue;
+            }
+
+            if let Some(tx) = self.infer_melt_transaction(&quote).await {
+                rebuilt.push(tx);
+            }
+        }
+
+        if let Some(tx) = self
+            .infer_balancing_transaction(existing.iter().chain(rebuilt.iter()))
+            .await?
+        {
+            rebuilt.push(tx);
+        }
+
+        for tx in &rebuilt {
+            self.localstore.add_transaction(tx.clone()).await?;
+        }
+
+        tracing::info!(
+            "Rebuilt {} transactions of {} {}",
+            rebuilt.len(),
+            self.mint_url,
+            self.unit
+        );
+
+        Ok(rebuilt)
+    }
+
+    async fn infer_mint_transaction(&self, quote: &MintQuote) -> Option<Transaction> {
+        let (issued, timestamps) = match quote.payment_method {
+            PaymentMethod::Bolt11 => match self.client.get_mint_quote_status(&quote.id).await {
+                Ok(response) => (
+                    response.state == MintQuoteState::Issued,
+                    response.timestamps,
+                ),
+                Err(err) => {
+                    tracing::debug!("Could not look up mint quote {}: {}", quote.id, err);
+                    (quote.state == MintQuoteState::Issued, None)
+                }
+            },
+            _ => (quote.amount_issued > Amount::ZERO, None),
+        };
+
+        if !issued {
+            return None;
+        }
+
+        let amount = if quote.amount_issued > Amount::ZERO {
+            quote.amount_issued
+        } else {
+            quote.amount?
+        };
+
+        let timestamp = timestamps
+            .and_then(|timestamps| timestamps.issued.or(timestamps.paid))
+            .unwrap_or_else(unix_time);
+
+        Some(self.inferred_transaction(
+            TransactionDirection::Incoming,
+            amount,
+            Amount::ZERO,
+            vec![inferred_y("mint_quote", &quote.id).ok()?],
+            timestamp,
+            Some(quote.id.clone()),
+            "mint_quote",
+        ))
+    }
+
+    async fn infer_melt_transaction(&self, quote: &MeltQuote) -> Option<Transaction> {
+        let response = match self.client.get_melt_quote_status(&quote.id).await {
+            Ok(response) => response,
+            Err(err) => {
+                tracing::debug!("Could not look up melt quote {}: {}", quote.id, err);
+                return None;
+            }
+        };
+
+        if response.state != MeltQuoteState::Paid {
+            return None;
+        }
+
+        let change = Amount::try_sum(
+            response
+                .change
+                .iter()
+                .flatten()
+                .map(|signature| signature.amount),
+        )
+        .ok()?;
+
+        let timestamp = response
+            .timestamps
+            .and_then(|timestamps| timestamps.paid)
+            .unwrap_or_else(unix_time);
+
+        Some(self.inferred_transaction(
+            TransactionDirection::Outgoing,
+            quote.amount,
+            quote.fee_reserve.checked_sub(change).unwrap_or_default(),
+            vec![inferred_y("melt_quote", &quote.id).ok()?],
+            timestamp,
+            Some(quote.id.clone()),
+            "melt_quote",
+        ))
+    }
+
+    /// Transaction accounting for the difference between the history and the proofs held
+    async fn infer_balancing_transaction<'a>(
+        &self,
+        history: impl Iterator<Item = &'a Transaction>,
+    ) -> Result<Option<Transaction>, Error> {
+        let mut recorded: i128 = 0;
+        let mut covered: HashSet<PublicKey> = HashSet::new();
+
+        for tx in history {
+            match tx.direction {
+                TransactionDirection::Incoming => recorded += i128::from(u64::from(tx.amount)),
+                TransactionDirection::Outgoing => {
+                    recorded -= i128::from(u64::from(tx.amount)) + i128::from(u64::from(tx.fee))
+                }
+            }
+            covered.extend(tx.ys.iter().cloned());
+        }
+
+        // Proofs of pending sends are already counted by their outgoing transaction
+        let held = self
+            .localstore
+            .get_proofs(
+                Some(self.mint_url.clone()),
+                Some(self.unit.clone()),
+                Some(vec![State::Unspent, State::Pending, State::Reserved]),
+                None,
+            )
+            .await?;
+
+        let held_amount = Amount::try_sum(held.iter().map(|proof| proof.proof.amount))?
+            .checked_add(self.locked_savings_balance().await?)
+            .ok_or(Error::AmountOverflow)?;
+
+        let difference = i128::from(u64::from(held_amount)) - recorded;
+        let now = unix_time();
+
+        let tx = match difference {
+            0 => return Ok(None),
+            difference if difference > 0 => {
+                let mut ys: Vec<PublicKey> = held
+                    .iter()
+                    .map(|proof| proof.y)
+                    .filter(|y| !covered.contains(y))
+                    .collect();
+                if ys.is_empty() {
+                    ys.push(inferred_y("balance", &now.to_string())?);
+                }
+
+                self.inferred_transaction(
+                    TransactionDirection::Incoming,
+                    Amount::from(u64::try_from(difference).map_err(|_| Error::AmountOverflow)?),
+                    Amount::ZERO,
+                    ys,
+                    now,
+                    None,
+                    "proofs",
+                )
+            }
+            difference => {
+                let archived: Vec<_> = self
+                    .localstore
+                    .get_archived_proofs(Some(self.mint_url.clone()), Some(self.unit.clone()))
+                    .await?
+                    .into_iter()
+                    .filter(|archived| !covered.contains(&archived.y))
+                    .collect();
+
+                let timestamp = archived
+                    .iter()
+                    .map(|archived| archived.spent_time)
+                    .max()
+                    .unwrap_or(now);
+                let mut ys: Vec<PublicKey> = archived.iter().map(|archived| archived.y).collect();
+                if ys.is_empty() {
+                    ys.push(inferred_y("balance", &now.to_string())?);
+                }
+
+                self.inferred_transaction(
+                    TransactionDirection::Outgoing,
+                    Amount::from(u64::try_from(-difference).map_err(|_| Error::AmountOverflow)?),
+                    Amount::ZERO,
+                    ys,
+                    timestamp,
+                    None,
+                    "archived_proofs",
+                )
+            }
+        };
+
+        Ok(Some(tx))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn inferred_transaction(
+        &self,
+        direction: TransactionDirection,
+        amount: Amount,
+        fee: Amount,
+        ys: Vec<PublicKey>,
+        timestamp: u64,
+        quote_id: Option<String>,
+        inferred_from: &str,
+    ) -> Transaction {
+        Transaction {
+            mint_url: self.mint_url.clone(),
+            direction,
+            amount,
+            fee,
+            unit: self.unit.clone(),
+            ys,
+            timestamp,
+            memo: None,
+            metadata: HashMap::from([
+                (INFERRED_METADATA_KEY.to_string(), "true".to_string()),
+                (
+                    INFERRED_FROM_METADATA_KEY.to_string(),
+                    inferred_from.to_string(),
+                ),
+            ]),
+            quote_id,
+            status: TransactionStatus::Confirmed,
+        }
+    }
+}
+
+/// Stand-in `Y` of a transaction rebuilt without its proofs
+///
+/// Transactions are identified by their `Y`s, deriving it from the source keeps
+/// the id of a rebuilt transaction stable and distinct from others.
+fn inferred_y(source: &str, id: &str) -> Result<PublicKey, Error> {
+    Ok(hash_to_curve(
+        format!("cdk_inferred_{source}_{id}").as_bytes(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inferred_y_is_stable() {
+        assert_eq!(
+            inferred_y("mint_quote", "quote").unwrap(),
+            inferred_y("mint_quote", "quote").unwrap()
+        );
+        assert_ne!(
+            inferred_y("mint_quote", "quote").unwrap(),
+            inferred_y("melt_quote", "quote").unwrap()
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use cdk_common::nut04::MintMethodOptions;
-use cdk_common::wallet::{MintQuote, Transaction, TransactionDirection};
+use cdk_common::wallet::{MintQuote, Transaction, TransactionDirection, TransactionStatus};
 use cdk_common::PaymentMethod;
 use tracing::instrument;
 
@@ -123,6 +123,8 @@ impl Wallet {
     }
 
     /// Check status of pending mint quotes
+    ///
+    /// Finalized quotes older than the quote retention are pruned afterwards.
     #[instrument(skip(self))]
     pub async fn check_all_mint_quotes(&self) -> Result<Amount, Error> {
         let mint_quotes = self.localstore.get_mint_quotes().await?;
@@ -140,6 +142,11 @@ impl Wallet {
                 self.localstore.remove_mint_quote(&mint_quote.id).await?;
             }
         }
+
+        if let Err(err) = self.prune_quotes().await {
+            tracing::warn!("Could not prune quotes: {}", err);
+        }
+
         Ok(total_amount)
     }
 
@@ -323,6 +330,7 @@ impl Wallet {
                 memo: None,
                 metadata: HashMap::new(),
                 quote_id: Some(quote_id.to_string()),
+                status: TransactionStatus::Confirmed,
             })
             .await?;
 
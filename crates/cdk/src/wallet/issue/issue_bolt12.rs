@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use cdk_common::nut04::MintMethodOptions;
 use cdk_common::nut25::MintQuoteBolt12Request;
-use cdk_common::wallet::{Transaction, TransactionDirection};
+use cdk_common::wallet::{Transaction, TransactionDirection, TransactionStatus};
 use cdk_common::{Proofs, SecretKey};
 use tracing::instrument;
 
@@ -31,6 +31,9 @@ impl Wallet {
 
         self.refresh_keysets().await?;
 
+        self.ensure_capability("bolt12 minting", |c| c.supports_bolt12_mint)
+            .await?;
+
         // If we have a description, we check that the mint supports it.
         if description.is_some() {
             let mint_method_settings = self
@@ -227,6 +230,7 @@ impl Wallet {
                 memo: None,
                 metadata: HashMap::new(),
                 quote_id: Some(quote_id.to_string()),
+                status: TransactionStatus::Confirmed,
             })
             .await?;
 
@@ -0,0 +1,172 @@
+//! Keyset expiry
+//!
+//! Mints can announce a `final_expiry` for a keyset (NUT-02), after which proofs
+//! of that keyset are no longer accepted. The wallet moves its proofs out of such
+//! keysets before they expire, on request or, when built with
+//! [`WalletBuilder::keyset_expiry_migration`](crate::wallet::WalletBuilder::keyset_expiry_migration),
+//! before sending and receiving.
+
+use std::time::Duration;
+
+use tracing::instrument;
+
+use crate::amount::SplitTarget;
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::{KeySetInfo, State};
+use crate::util::unix_time;
+use crate::{Amount, Error, Wallet};
+
+/// Warn about held proofs of keysets expiring within a week
+pub(crate) const KEYSET_EXPIRY_WARNING: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+impl Wallet {
+    /// Keysets of the wallet's unit that expire within `within` from now
+    ///
+    /// Uses the keysets stored locally, call [`Wallet::refresh_keysets`] first for
+    /// the latest expiry announced by the mint.
+    #[instrument(skip(self))]
+    pub async fn expiring_keysets(&self, within: Duration) -> Result<Vec<KeySetInfo>, Error> {
+        let deadline = unix_time() + within.as_secs();
+
+        Ok(self
+            .localstore
+            .get_mint_keysets(self.mint_url.clone())
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|keyset| keyset.unit == self.unit)
+            .filter(|keyset| {
+                keyset
+                    .final_expiry
+                    .is_some_and(|final_expiry| final_expiry <= deadline)
+            })
+            .collect())
+    }
+
+    /// Swap the unspent proofs of keysets expiring within `within` into the active keyset
+    ///
+    /// Returns the amount of the migrated proofs, before input fees. Proofs are left in
+    /// place, with a warning, when the active keyset expires within `within` as well.
+    #[instrument(skip(self))]
+    pub async fn migrate_expiring_proofs(&self, within: Duration) -> Result<Amount, Error> {
+        let active_keyset = self.fetch_active_keyset().await?;
+        let expiring_keysets = self.expiring_keysets(within).await?;
+
+        if expiring_keysets.is_empty() {
+            return Ok(Amount::ZERO);
+        }
+
+        if expiring_keysets
+            .iter()
+            .any(|keyset| keyset.id == active_keyset.id)
+        {
+            tracing::warn!(
+                "Active keyset {} of mint {} expires too, proofs are not migrated",
+                active_keyset.id,
+                self.mint_url
+            );
+            return Ok(Amount::ZERO);
+        }
+
+        let proofs = self
+            .localstore
+            .get_proofs(
+                Some(self.mint_url.clone()),
+                Some(self.unit.clone()),
+                Some(vec![State::Unspent]),
+                None,
+            )
+            .await?
+            .into_iter()
+            .map(|proof_info| proof_info.proof)
+            .filter(|proof| {
+                expiring_keysets
+                    .iter()
+                    .any(|keyset| keyset.id == proof.keyset_id)
+            })
+            .collect::<Vec<_>>();
+
+        if proofs.is_empty() {
+            return Ok(Amount::ZERO);
+        }
+
+        let amount = proofs.total_amount()?;
+
+        tracing::info!(
+            "Migrating {} proofs worth {} from expiring keysets of mint {}",
+            proofs.len(),
+            amount,
+            self.mint_url
+        );
+
+        self.swap(None, SplitTarget::default(), proofs, None, false)
+            .await?;
+
+        Ok(amount)
+    }
+
+    /// Migrate the proofs of expiring keysets when the wallet is set to do so
+    ///
+    /// Runs before proofs are selected, failures are logged and leave the proofs in place.
+    pub(crate) async fn auto_migrate_expiring_proofs(&self) {
+        let Some(within) = self.keyset_expiry_migration else {
+            return;
+        };
+
+        match self.migrate_expiring_proofs(within).await {
+            Ok(amount) if amount > Amount::ZERO => {
+                tracing::info!(
+                    "Migrated {} from expiring keysets of mint {}",
+                    amount,
+                    self.mint_url
+                );
+            }
+            Ok(_) => (),
+            Err(err) => {
+                tracing::warn!(
+                    "Could not migrate proofs from expiring keysets of mint {}: {}",
+                    self.mint_url,
+                    err
+                );
+            }
+        }
+    }
+
+    /// Warn about keysets the wallet holds proofs of that expire within `within`
+    pub(crate) async fn warn_expiring_keysets(&self, within: Duration) -> Result<(), Error> {
+        let expiring_keysets = self.expiring_keysets(within).await?;
+
+        if expiring_keysets.is_empty() {
+            return Ok(());
+        }
+
+        let proofs = self
+            .localstore
+            .get_proofs(
+                Some(self.mint_url.clone()),
+                Some(self.unit.clone()),
+                Some(vec![State::Unspent]),
+                None,
+            )
+            .await?;
+
+        for keyset in expiring_keysets {
+            let held = proofs
+                .iter()
+                .filter(|proof_info| proof_info.proof.keyset_id == keyset.id)
+                .count();
+
+            if held > 0 {
+                tracing::warn!(
+                    "Keyset {} of mint {} expires at {}, {} proofs should be migrated",
+                    keyset.id,
+                    self.mint_url,
+                    keyset.final_expiry.unwrap_or_default(),
+                    held
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+//! Keyset history
+//!
+//! Mints that rotated their keysets many times would make a restore fetch the
+//! keys of each keyset separately. The keyset history of the mint carries every
+//! keyset with its keys in one response, it is checked against the mint
+//! identity key and cached in the wallet database, where restores reuse it
+//! while it lists every keyset the wallet knows of.
+
+use tracing::instrument;
+
+use crate::keyset_history::KeysetHistory;
+use crate::nuts::{KeySetInfo, MintInfo};
+use crate::{Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const KEYSET_HISTORY_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for cached keyset histories
+const KEYSET_HISTORY_SECONDARY_NAMESPACE: &str = "keyset_history";
+
+impl Wallet {
+    /// Fetch the keyset history of the mint and cache every keyset and its keys
+    ///
+    /// If a mint identity pubkey is pinned the history must be signed by it,
+    /// otherwise a signature is checked against the pubkey of the mint info.
+    /// The keys of every keyset must match its id.
+    #[instrument(skip(self))]
+    pub async fn fetch_keyset_history(&self) -> Result<KeysetHistory, Error> {
+        let mint_info = match self.localstore.get_mint(self.mint_url.clone()).await? {
+            Some(mint_info) => Some(mint_info),
+            None => self.fetch_mint_info().await?,
+        };
+
+        let history = self.client.get_keyset_history().await?;
+
+        self.verify_keyset_history(&history, mint_info.as_ref())
+            .await?;
+        history.verify_keyset_ids()?;
+
+        self.localstore
+            .add_mint_keysets(
+                self.mint_url.clone(),
+                history
+                    .keysets
+                    .iter()
+                    .cloned()
+                    .map(KeySetInfo::from)
+                    .collect(),
+            )
+            .await?;
+
+        for keyset in &history.keysets {
+            if self.localstore.get_keys(&keyset.id).await?.is_none() {
+                self.localstore.add_keys(keyset.clone().into()).await?;
+            }
+        }
+
+        self.localstore
+            .kv_write(
+                KEYSET_HISTORY_PRIMARY_NAMESPACE,
+                KEYSET_HISTORY_SECONDARY_NAMESPACE,
+                &self.mint_identity_key(),
+                &serde_json::to_vec(&history)?,
+            )
+            .await?;
+
+        Ok(history)
+    }
+
+    /// Keyset history of the mint cached by [`Wallet::fetch_keyset_history`]
+    #[instrument(skip(self))]
+    pub async fn cached_keyset_history(&self) -> Result<Option<KeysetHistory>, Error> {
+        let history = self
+            .localstore
+            .kv_read(
+                KEYSET_HISTORY_PRIMARY_NAMESPACE,
+                KEYSET_HISTORY_SECONDARY_NAMESPACE,
+                &self.mint_identity_key(),
+            )
+            .await?;
+
+        Ok(history
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    /// Keyset history listing every keyset of the mint known to the wallet
+    ///
+    /// The cached history is used while it has every keyset, otherwise it is
+    /// fetched again. Mints without the keyset history return `None`.
+    pub(crate) async fn load_keyset_history(&self) -> Result<Option<KeysetHistory>, Error> {
+        let known_keysets = self
+            .localstore
+            .get_mint_keysets(self.mint_url.clone())
+            .await?;
+
+        if let (Some(history), Some(known_keysets)) =
+            (self.cached_keyset_history().await?, known_keysets)
+        {
+            if known_keysets
+                .iter()
+                .all(|keyset| history.keysets.iter().any(|entry| entry.id == keyset.id))
+            {
+                return Ok(Some(history));
+            }
+        }
+
+        match self.fetch_keyset_history().await {
+            Ok(history) => Ok(Some(history)),
+            Err(err) => {
+                tracing::debug!(
+                    "Could not fetch keyset history of mint {}: {}",
+                    self.mint_url,
+                    err
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Verify the keyset history was signed by the mint
+    async fn verify_keyset_history(
+        &self,
+        history: &KeysetHistory,
+        mint_info: Option<&MintInfo>,
+    ) -> Result<(), Error> {
+        if let Some(pinned) = self.pinned_mint_pubkey().await? {
+            if history.verify_signature(&pinned).is_err() {
+                tracing::warn!(
+                    "Mint {} keyset history is not signed by pinned pubkey {}",
+                    self.mint_url,
+                    pinned
+                );
+                return Err(Error::MintIdentityMismatch(pinned.to_hex()));
+            }
+
+            return Ok(());
+        }
+
+        if history.signature.is_some() {
+            match mint_info.and_then(|mint_info| mint_info.pubkey) {
+                Some(pubkey) => history.verify_signature(&pubkey)?,
+                None => tracing::debug!(
+                    "Mint {} has no pubkey to verify its keyset history",
+                    self.mint_url
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
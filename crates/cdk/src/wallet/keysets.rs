@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use cdk_common::nut02::{KeySetInfos, KeySetInfosMethods};
 use tracing::instrument;
 
+use super::keyset_expiry::KEYSET_EXPIRY_WARNING;
 use crate::nuts::{Id, KeySetInfo, Keys};
 use crate::{Error, Wallet};
 
@@ -93,6 +94,10 @@ impl Wallet {
             .add_mint_keysets(self.mint_url.clone(), all_keysets.clone())
             .await?;
 
+        if let Err(err) = self.warn_expiring_keysets(KEYSET_EXPIRY_WARNING).await {
+            tracing::debug!("Could not check for expiring keysets: {}", err);
+        }
+
         // Filter for active keysets matching our unit
         let keysets: KeySetInfos = all_keysets.unit(self.unit.clone()).cloned().collect();
 
@@ -0,0 +1,240 @@
+//! Batch melts
+//!
+//! Payout services pay many invoices from one balance. Melting them one by one
+//! selects and swaps proofs for every invoice, and concurrent melts compete for
+//! the same proofs. [`Wallet::melt_batch`] quotes every invoice, selects and
+//! swaps the proofs for all of them at once, and then pays the invoices
+//! concurrently, each with its own reserved proofs.
+
+use std::collections::HashSet;
+
+use cdk_common::wallet::FeeKind;
+use futures::stream::{self, StreamExt};
+use tracing::instrument;
+
+use crate::amount::SplitTarget;
+use crate::dhke::construct_proofs;
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::{Proofs, PublicKey, State};
+use crate::types::{Melted, ProofInfo};
+use crate::wallet::MeltQuote;
+use crate::{ensure_cdk, Amount, Error, Wallet};
+
+/// Invoices quoted and paid at the same time by [`Wallet::melt_batch`]
+pub const MELT_BATCH_CONCURRENCY: usize = 8;
+
+/// Outcome of one invoice of a batch melt
+#[derive(Debug)]
+pub struct BatchMelt {
+    /// The bolt11 invoice
+    pub request: String,
+    /// Id of the melt quote, if the invoice could be quoted
+    pub quote_id: Option<String>,
+    /// The melt, or why the invoice could not be quoted or paid
+    pub result: Result<Melted, Error>,
+}
+
+impl Wallet {
+    /// Pay several bolt11 invoices from the balance
+    ///
+    /// Every invoice is quoted first. The proofs for all quoted invoices,
+    /// including the fee reserves and input fees, are selected and swapped into
+    /// one reserved set per invoice in a single swap, and the invoices are then
+    /// paid with at most [`MELT_BATCH_CONCURRENCY`] melts in flight.
+    ///
+    /// Returns one [`BatchMelt`] per invoice, in order. Invoices that could not
+    /// be quoted or paid carry their error and their proofs are returned to the
+    /// balance. The call itself only fails when the proofs could not be
+    /// selected or swapped, in which case nothing was paid.
+    #[instrument(skip(self, requests))]
+    pub async fn melt_batch(&self, requests: Vec<String>) -> Result<Vec<BatchMelt>, Error> {
+        let quotes: Vec<Result<MeltQuote, Error>> = stream::iter(requests.iter().cloned())
+            .map(|request| self.melt_quote(request, None))
+            .buffered(MELT_BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut groups = self
+            .reserve_batch_proofs(quotes.iter().filter_map(|quote| quote.as_ref().ok()))
+            .await?
+            .into_iter();
+
+        let melts = requests
+            .into_iter()
+            .zip(quotes)
+            .map(|(request, quote)| {
+                let proofs = quote.as_ref().ok().and_then(|_| groups.next());
+                (request, quote, proofs)
+            })
+            .collect::<Vec<_>>();
+
+        let results = stream::iter(melts)
+            .map(|(request, quote, proofs)| async move {
+                let quote = match quote {
+                    Ok(quote) => quote,
+                    Err(err) => {
+                        return BatchMelt {
+                            request,
+                            quote_id: None,
+                            result: Err(err),
+                        }
+                    }
+                };
+
+                let result = match proofs {
+                    Some(proofs) => self.melt_batch_proofs(&quote.id, proofs).await,
+                    None => Err(Error::Internal),
+                };
+
+                BatchMelt {
+                    request,
+                    quote_id: Some(quote.id),
+                    result,
+                }
+            })
+            .buffered(MELT_BATCH_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        tracing::info!(
+            "Batch melt paid {} of {} invoices",
+            results.iter().filter(|melt| melt.result.is_ok()).count(),
+            results.len()
+        );
+
+        Ok(results)
+    }
+
+    /// Swap unspent proofs into one reserved set of proofs per quote
+    async fn reserve_batch_proofs<'a>(
+        &self,
+        quotes: impl Iterator<Item = &'a MeltQuote>,
+    ) -> Result<Vec<Proofs>, Error> {
+        let active_keyset_id = self.fetch_active_keyset().await?.id;
+        let fee_ppk = self.get_keyset_fees_by_id(active_keyset_id).await?;
+
+        // Denominations of every quote, covering the input fee of melting them
+        let splits = quotes
+            .map(|quote| {
+                quote
+                    .amount
+                    .checked_add(quote.fee_reserve)
+                    .ok_or(Error::AmountOverflow)?
+                    .split_with_fee(fee_ppk)
+                    .map_err(Error::from)
+            })
+            .collect::<Result<Vec<Vec<Amount>>, Error>>()?;
+
+        if splits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let values: Vec<Amount> = splits.iter().flatten().copied().collect();
+        let total = Amount::try_sum(values.iter().copied())?;
+
+        let active_keyset_ids = self
+            .refresh_keysets()
+            .await?
+            .into_iter()
+            .filter(|keyset| keyset.active)
+            .map(|keyset| keyset.id)
+            .collect();
+        let keyset_fees = self.get_keyset_fees().await?;
+        let inputs = Wallet::select_proofs(
+            total,
+            self.get_unspent_proofs().await?,
+            &active_keyset_ids,
+            &keyset_fees,
+            true,
+        )?;
+        let input_ys = inputs.ys()?;
+
+        // Every output is change of the swap, split into the denominations of the quotes
+        let pre_swap = self
+            .create_swap(None, SplitTarget::Values(values), inputs, None, false)
+            .await?;
+
+        let swap_response = match self.client.post_swap(pre_swap.swap_request).await {
+            Ok(swap_response) => swap_response,
+            Err(err) => {
+                self.unreserve_proofs(input_ys).await?;
+                return Err(err);
+            }
+        };
+
+        let active_keys = self
+            .localstore
+            .get_keys(&pre_swap.pre_mint_secrets.keyset_id)
+            .await?
+            .ok_or(Error::NoActiveKeyset)?;
+
+        let mut outputs = construct_proofs(
+            swap_response.signatures,
+            pre_swap.pre_mint_secrets.rs(),
+            pre_swap.pre_mint_secrets.secrets(),
+            &active_keys,
+        )?;
+
+        let mut groups = Vec::with_capacity(splits.len());
+        for split in splits {
+            let mut group = Proofs::with_capacity(split.len());
+            for amount in split {
+                let index = outputs
+                    .iter()
+                    .position(|proof| proof.amount == amount)
+                    .ok_or(Error::Internal)?;
+                group.push(outputs.swap_remove(index));
+            }
+            groups.push(group);
+        }
+
+        let mut proof_infos = Vec::new();
+        for (proofs, state) in groups
+            .iter()
+            .map(|group| (group, State::Reserved))
+            .chain([(&outputs, State::Unspent)])
+        {
+            for proof in proofs {
+                proof_infos.push(ProofInfo::new(
+                    proof.clone(),
+                    self.mint_url.clone(),
+                    state,
+                    self.unit.clone(),
+                )?);
+            }
+        }
+
+        self.replace_spent_proofs(proof_infos, input_ys).await?;
+
+        self.record_fee(FeeKind::Input, pre_swap.fee, None).await;
+
+        Ok(groups)
+    }
+
+    /// Melt a reserved set of proofs, returning them to the balance if the melt fails
+    async fn melt_batch_proofs(&self, quote_id: &str, proofs: Proofs) -> Result<Melted, Error> {
+        let ys: HashSet<PublicKey> = proofs.ys()?.into_iter().collect();
+        ensure_cdk!(!ys.is_empty(), Error::AmountUndefined);
+
+        let err = match self.melt_proofs(quote_id, proofs).await {
+            Ok(melted) => return Ok(melted),
+            Err(err) => err,
+        };
+
+        // Failures before the request was sent leave the proofs reserved, the
+        // melt itself reclaims them once they were sent
+        let still_reserved = self
+            .get_reserved_proofs()
+            .await?
+            .ys()?
+            .into_iter()
+            .filter(|y| ys.contains(y))
+            .collect::<Vec<_>>();
+
+        if !still_reserved.is_empty() {
+            self.unreserve_proofs(still_reserved).await?;
+        }
+
+        Err(err)
+    }
+}
@@ -2,20 +2,21 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use cdk_common::amount::SplitTarget;
-use cdk_common::wallet::{Transaction, TransactionDirection};
+use cdk_common::wallet::{FeeKind, Transaction, TransactionDirection, TransactionStatus};
 use cdk_common::PaymentMethod;
 use lightning_invoice::Bolt11Invoice;
+use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 
 use crate::amount::to_unit;
 use crate::dhke::construct_proofs;
 use crate::nuts::{
-    CurrencyUnit, MeltOptions, MeltQuoteBolt11Request, MeltQuoteBolt11Response, MeltRequest,
-    PreMintSecrets, Proofs, ProofsMethods, State,
+    CurrencyUnit, MeltOptions, MeltQuoteBolt11Request, MeltQuoteBolt11Response, MeltQuoteState,
+    MeltRequest, PreMintSecrets, Proofs, ProofsMethods, State,
 };
 use crate::types::{Melted, ProofInfo};
 use crate::util::unix_time;
-use crate::wallet::MeltQuote;
+use crate::wallet::{MeltProgressState, MeltQuote, SpendKind};
 use crate::{ensure_cdk, Amount, Error, Wallet};
 
 impl Wallet {
@@ -89,9 +90,12 @@ impl Wallet {
             expiry: quote_res.expiry,
             payment_preimage: quote_res.payment_preimage,
             payment_method: PaymentMethod::Bolt11,
+            mint_url: Some(self.mint_url.clone()),
         };
 
         self.localstore.add_melt_quote(quote.clone()).await?;
+        self.record_melt_progress(&quote.id, MeltProgressState::QuoteCreated)
+            .await;
 
         Ok(quote)
     }
@@ -129,6 +133,27 @@ impl Wallet {
     /// Melt specific proofs
     #[instrument(skip(self, proofs))]
     pub async fn melt_proofs(&self, quote_id: &str, proofs: Proofs) -> Result<Melted, Error> {
+        self.melt_proofs_with_cancel(quote_id, proofs, CancellationToken::new())
+            .await
+    }
+
+    /// Melt specific proofs, aborting if `cancel_token` is cancelled
+    ///
+    /// Cancelling before the melt request is sent returns the reserved proofs to
+    /// [`State::Unspent`]. Cancelling while the request is in flight checks the quote at the mint:
+    /// a paid quote settles the melt as if the mint had answered, a pending quote leaves the inputs
+    /// pending for [`Wallet::check_pending_melt_quotes`] and returns
+    /// [`Error::MeltCancelledPending`], otherwise the inputs the mint still reports as unspent are
+    /// reclaimed.
+    #[instrument(skip(self, proofs, cancel_token))]
+    pub async fn melt_proofs_with_cancel(
+        &self,
+        quote_id: &str,
+        proofs: Proofs,
+        cancel_token: CancellationToken,
+    ) -> Result<Melted, Error> {
+        ensure_cdk!(!cancel_token.is_cancelled(), Error::Cancelled);
+
         let quote_info = self
             .localstore
             .get_melt_quote(quote_id)
@@ -145,10 +170,16 @@ impl Wallet {
             return Err(Error::InsufficientFunds);
         }
 
+        let spend_reservation = self
+            .check_spending_limits(SpendKind::Melt, quote_info.amount + quote_info.fee_reserve)
+            .await?;
+
         let ys = proofs.ys()?;
         self.localstore
             .update_proofs_state(ys, State::Pending)
             .await?;
+        self.record_melt_progress(quote_id, MeltProgressState::ProofsReserved)
+            .await;
 
         let active_keyset_id = self.fetch_active_keyset().await?.id;
 
@@ -179,32 +210,98 @@ impl Wallet {
             PreMintSecrets::from_seed_blank(active_keyset_id, count, &self.seed, change_amount)?
         };
 
+        if cancel_token.is_cancelled() {
+            tracing::info!("Melt {} cancelled before request was sent", quote_id);
+            self.unreserve_proofs(proofs.ys()?).await?;
+            return Err(Error::Cancelled);
+        }
+
         let request = MeltRequest::new(
             quote_id.to_string(),
             proofs.clone(),
             Some(premint_secrets.blinded_messages()),
         );
 
-        let melt_response = match quote_info.payment_method {
-            cdk_common::PaymentMethod::Bolt11 => self.client.post_melt(request).await,
-            cdk_common::PaymentMethod::Bolt12 => self.client.post_melt_bolt12(request).await,
+        let melt_request = match quote_info.payment_method {
+            cdk_common::PaymentMethod::Bolt11 => self.client.post_melt(request),
+            cdk_common::PaymentMethod::Bolt12 => self.client.post_melt_bolt12(request),
+            method if method == cdk_common::PaymentMethod::keysend() => {
+                self.client.post_melt_keysend(request)
+            }
             cdk_common::PaymentMethod::Custom(_) => {
                 return Err(Error::UnsupportedPaymentMethod);
             }
         };
 
+        self.record_melt_progress(quote_id, MeltProgressState::PaymentInFlight)
+            .await;
+
+        let melt_response = tokio::select! {
+            melt_response = melt_request => melt_response,
+            _ = cancel_token.cancelled() => {
+                tracing::info!("Melt {} cancelled while in flight, checking quote", quote_id);
+
+                match self.client.get_melt_quote_status(quote_id).await {
+                    // The payment went through, settle the melt with the quote's change
+                    Ok(response) if response.state == MeltQuoteState::Paid => Ok(response),
+                    Ok(response) if response.state == MeltQuoteState::Pending => {
+                        // The pending melt check settles the quote and its inputs
+                        spend_reservation.keep();
+                        let mut quote = quote_info.clone();
+                        quote.state = MeltQuoteState::Pending;
+                        self.localstore.add_melt_quote(quote).await?;
+
+                        return Err(Error::MeltCancelledPending(quote_id.to_string()));
+                    }
+                    _ => {
+                        let progress = match self.reclaim_unspent(proofs).await {
+                            Ok(()) => MeltProgressState::Refunded,
+                            Err(err) => {
+                                tracing::warn!(
+                                    "Could not reclaim proofs of cancelled melt: {}",
+                                    err
+                                );
+                                MeltProgressState::Failed
+                            }
+                        };
+                        self.record_melt_progress(quote_id, progress).await;
+
+                        return Err(Error::Cancelled);
+                    }
+                }
+            }
+        };
+
         let melt_response = match melt_response {
             Ok(melt_response) => melt_response,
             Err(err) => {
                 tracing::error!("Could not melt: {}", err);
                 tracing::info!("Checking status of input proofs.");
 
-                self.reclaim_unspent(proofs).await?;
+                if let Err(reclaim_err) = self.reclaim_unspent(proofs).await {
+                    self.record_melt_progress(quote_id, MeltProgressState::Failed)
+                        .await;
+                    return Err(reclaim_err);
+                }
+
+                // Inputs of a payment still pending at the mint stay reserved
+                match err {
+                    Error::PendingQuote | Error::PaymentPending => {
+                        spend_reservation.keep();
+                    }
+                    _ => {
+                        self.record_melt_progress(quote_id, MeltProgressState::Refunded)
+                            .await
+                    }
+                }
 
-                return Err(err);
+                return Err(self.melt_failure(quote_id, err).await);
             }
         };
 
+        // The mint took the inputs, the spend counts against the daily limit
+        spend_reservation.keep();
+
         let active_keys = self
             .localstore
             .get_keys(&active_keyset_id)
@@ -236,13 +333,16 @@ impl Wallet {
             None => None,
         };
 
+        let input_fee = self.get_proofs_fee(&proofs).await?;
+
         let melted = Melted::from_proofs(
             melt_response.state,
             melt_response.payment_preimage,
             quote_info.amount,
             proofs.clone(),
             change_proofs.clone(),
-        )?;
+        )?
+        .with_fee_breakdown(quote_info.fee_reserve, input_fee);
 
         let change_proof_infos = match change_proofs {
             Some(change_proofs) => {
@@ -269,8 +369,7 @@ impl Wallet {
         self.localstore.remove_melt_quote(&quote_info.id).await?;
 
         let deleted_ys = proofs.ys()?;
-        self.localstore
-            .update_proofs(change_proof_infos, deleted_ys)
+        self.replace_spent_proofs(change_proof_infos, deleted_ys)
             .await?;
 
         // Add transaction to store
@@ -284,11 +383,43 @@ impl Wallet {
                 ys: proofs.ys()?,
                 timestamp: unix_time(),
                 memo: None,
-                metadata: HashMap::new(),
+                metadata: HashMap::from([
+                    (
+                        "lightning_fee".to_string(),
+                        melted.fee_breakdown.lightning_fee.to_string(),
+                    ),
+                    (
+                        "input_fee".to_string(),
+                        melted.fee_breakdown.input_fee.to_string(),
+                    ),
+                    (
+                        "fee_reserve_returned".to_string(),
+                        melted.fee_breakdown.fee_reserve_returned.to_string(),
+                    ),
+                ]),
                 quote_id: Some(quote_id.to_string()),
+                status: TransactionStatus::Confirmed,
             })
             .await?;
 
+        if melted.state == MeltQuoteState::Paid {
+            self.record_fee(
+                FeeKind::Input,
+                melted.fee_breakdown.input_fee,
+                Some(quote_id.to_string()),
+            )
+            .await;
+            self.record_fee(
+                FeeKind::Lightning,
+                melted.fee_breakdown.lightning_fee,
+                Some(quote_id.to_string()),
+            )
+            .await;
+
+            self.record_melt_progress(quote_id, MeltProgressState::Settled)
+                .await;
+        }
+
         Ok(melted)
     }
 
@@ -320,6 +451,21 @@ impl Wallet {
     /// }
     #[instrument(skip(self))]
     pub async fn melt(&self, quote_id: &str) -> Result<Melted, Error> {
+        self.melt_with_cancel(quote_id, CancellationToken::new())
+            .await
+    }
+
+    /// Melt, aborting if `cancel_token` is cancelled
+    ///
+    /// See [`Wallet::melt_proofs_with_cancel`] for how reserved proofs are rolled back.
+    #[instrument(skip(self, cancel_token))]
+    pub async fn melt_with_cancel(
+        &self,
+        quote_id: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<Melted, Error> {
+        ensure_cdk!(!cancel_token.is_cancelled(), Error::Cancelled);
+
         let quote_info = self
             .localstore
             .get_melt_quote(quote_id)
@@ -333,6 +479,8 @@ impl Wallet {
 
         let inputs_needed_amount = quote_info.amount + quote_info.fee_reserve;
 
+        self.auto_unlock_savings().await;
+
         let available_proofs = self.get_unspent_proofs().await?;
 
         let active_keyset_ids = self
@@ -368,6 +516,30 @@ impl Wallet {
             input_proofs.extend_from_slice(&new_proofs);
         }
 
-        self.melt_proofs(quote_id, input_proofs).await
+        self.melt_proofs_with_cancel(quote_id, input_proofs, cancel_token)
+            .await
+    }
+
+    /// Replace a failed melt error with the failure reason the mint recorded on the quote
+    async fn melt_failure(&self, quote_id: &str, err: Error) -> Error {
+        // Errors unrelated to the payment attempt are returned as is
+        if !matches!(
+            err,
+            Error::PaymentFailed
+                | Error::RequestAlreadyPaid
+                | Error::ExpiredQuote(..)
+                | Error::UnknownErrorResponse(_)
+        ) {
+            return err;
+        }
+
+        match self.client.get_melt_quote_status(quote_id).await {
+            Ok(MeltQuoteBolt11Response {
+                state: MeltQuoteState::Unpaid,
+                failure_reason: Some(reason),
+                ..
+            }) => Error::MeltFailed(reason),
+            _ => err,
+        }
     }
 }
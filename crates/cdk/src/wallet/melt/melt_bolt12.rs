@@ -12,6 +12,7 @@ use tracing::instrument;
 
 use crate::amount::to_unit;
 use crate::nuts::{CurrencyUnit, MeltOptions, MeltQuoteBolt11Response, MeltQuoteBolt12Request};
+use crate::wallet::MeltProgressState;
 use crate::{Error, Wallet};
 
 impl Wallet {
@@ -22,6 +23,9 @@ impl Wallet {
         request: String,
         options: Option<MeltOptions>,
     ) -> Result<MeltQuote, Error> {
+        self.ensure_capability("bolt12 melting", |c| c.supports_bolt12_melt)
+            .await?;
+
         let quote_request = MeltQuoteBolt12Request {
             request: request.clone(),
             unit: self.unit.clone(),
@@ -59,9 +63,12 @@ impl Wallet {
             expiry: quote_res.expiry,
             payment_preimage: quote_res.payment_preimage,
             payment_method: PaymentMethod::Bolt12,
+            mint_url: Some(self.mint_url.clone()),
         };
 
         self.localstore.add_melt_quote(quote.clone()).await?;
+        self.record_melt_progress(&quote.id, MeltProgressState::QuoteCreated)
+            .await;
 
         Ok(quote)
     }
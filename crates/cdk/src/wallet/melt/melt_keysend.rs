@@ -0,0 +1,95 @@
+//! Melt keysend
+//!
+//! Implementation of melt functionality for spontaneous keysend payments to a node
+
+use cdk_common::wallet::MeltQuote;
+use cdk_common::PaymentMethod;
+use tracing::instrument;
+
+use crate::amount::to_unit;
+use crate::nuts::{CurrencyUnit, MeltQuoteBolt11Response, MeltQuoteKeysendRequest, PublicKey};
+use crate::wallet::MeltProgressState;
+use crate::{Amount, Error, Wallet};
+
+impl Wallet {
+    /// Melt Quote for a keysend payment of `amount_msat` to the node `pubkey`
+    #[instrument(skip(self))]
+    pub async fn melt_keysend_quote(
+        &self,
+        pubkey: PublicKey,
+        amount_msat: Amount,
+    ) -> Result<MeltQuote, Error> {
+        self.ensure_capability("keysend melting", |c| c.supports_keysend_melt)
+            .await?;
+
+        let quote_request = MeltQuoteKeysendRequest {
+            pubkey,
+            amount: amount_msat,
+            unit: self.unit.clone(),
+        };
+
+        let quote_res = self.client.post_melt_keysend_quote(quote_request).await?;
+
+        if self.unit == CurrencyUnit::Sat || self.unit == CurrencyUnit::Msat {
+            let amount_quote_unit = to_unit(amount_msat, &CurrencyUnit::Msat, &self.unit)?;
+
+            if quote_res.amount != amount_quote_unit {
+                tracing::warn!(
+                    "Mint returned incorrect quote amount. Expected {}, got {}",
+                    amount_quote_unit,
+                    quote_res.amount
+                );
+                return Err(Error::IncorrectQuoteAmount);
+            }
+        }
+
+        let quote = MeltQuote {
+            id: quote_res.quote,
+            amount: quote_res.amount,
+            request: pubkey.to_string(),
+            unit: self.unit.clone(),
+            fee_reserve: quote_res.fee_reserve,
+            state: quote_res.state,
+            expiry: quote_res.expiry,
+            payment_preimage: quote_res.payment_preimage,
+            payment_method: PaymentMethod::keysend(),
+            mint_url: Some(self.mint_url.clone()),
+        };
+
+        self.localstore.add_melt_quote(quote.clone()).await?;
+        self.record_melt_progress(&quote.id, MeltProgressState::QuoteCreated)
+            .await;
+
+        Ok(quote)
+    }
+
+    /// Keysend melt quote status
+    #[instrument(skip(self, quote_id))]
+    pub async fn melt_keysend_quote_status(
+        &self,
+        quote_id: &str,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error> {
+        let response = self.client.get_melt_keysend_quote_status(quote_id).await?;
+
+        match self.localstore.get_melt_quote(quote_id).await? {
+            Some(quote) => {
+                let mut quote = quote;
+
+                if let Err(e) = self
+                    .add_transaction_for_pending_melt(&quote, &response)
+                    .await
+                {
+                    tracing::error!("Failed to add transaction for pending melt: {}", e);
+                }
+
+                quote.state = response.state;
+                self.localstore.add_melt_quote(quote).await?;
+            }
+            None => {
+                tracing::info!("Quote melt {} unknown", quote_id);
+            }
+        }
+
+        Ok(response)
+    }
+}
@@ -1,16 +1,25 @@
 use std::collections::HashMap;
 
 use cdk_common::util::unix_time;
-use cdk_common::wallet::{MeltQuote, Transaction, TransactionDirection};
+use cdk_common::wallet::{
+    FeeKind, MeltQuote, Transaction, TransactionDirection, TransactionStatus,
+};
 use cdk_common::{Error, MeltQuoteBolt11Response, MeltQuoteState, ProofsMethods};
 use tracing::instrument;
 
 use crate::Wallet;
 
+mod melt_batch;
 #[cfg(all(feature = "bip353", not(target_arch = "wasm32")))]
 mod melt_bip353;
 mod melt_bolt11;
 mod melt_bolt12;
+mod melt_keysend;
+mod progress;
+
+pub use melt_batch::{BatchMelt, MELT_BATCH_CONCURRENCY};
+pub(crate) use progress::{prune_melt_progress, MELT_PROGRESS_CAPACITY};
+pub use progress::{MeltProgress, MeltProgressState};
 
 impl Wallet {
     /// Check pending melt quotes
@@ -60,25 +69,56 @@ impl Wallet {
                 let pending_proofs = self.get_pending_proofs().await?;
                 let proofs_total = pending_proofs.total_amount().unwrap_or_default();
                 let change_total = response.change_amount().unwrap_or_default();
+                let fee = proofs_total
+                    .checked_sub(response.amount)
+                    .and_then(|amt| amt.checked_sub(change_total))
+                    .unwrap_or_default();
+                let input_fee = self.get_proofs_fee(&pending_proofs).await?.min(fee);
+
                 self.localstore
                     .add_transaction(Transaction {
                         mint_url: self.mint_url.clone(),
                         direction: TransactionDirection::Outgoing,
                         amount: response.amount,
-                        fee: proofs_total
-                            .checked_sub(response.amount)
-                            .and_then(|amt| amt.checked_sub(change_total))
-                            .unwrap_or_default(),
+                        fee,
                         unit: quote.unit.clone(),
                         ys: pending_proofs.ys()?,
                         timestamp: unix_time(),
                         memo: None,
                         metadata: HashMap::new(),
                         quote_id: Some(quote.id.clone()),
+                        status: TransactionStatus::Confirmed,
                     })
                     .await?;
+
+                self.record_fee(FeeKind::Input, input_fee, Some(quote.id.clone()))
+                    .await;
+                self.record_fee(
+                    FeeKind::Lightning,
+                    fee.checked_sub(input_fee).unwrap_or_default(),
+                    Some(quote.id.clone()),
+                )
+                .await;
+
+                self.record_melt_progress(&quote.id, MeltProgressState::Settled)
+                    .await;
             }
         }
+
+        // The payment of a melt left in flight has since failed, its inputs
+        // are still pending in the wallet and have to be reclaimed explicitly
+        if matches!(
+            response.state,
+            MeltQuoteState::Unpaid | MeltQuoteState::Failed
+        ) && self
+            .melt_progress(&quote.id)
+            .await?
+            .is_some_and(|progress| progress.state == MeltProgressState::PaymentInFlight)
+        {
+            self.record_melt_progress(&quote.id, MeltProgressState::Failed)
+                .await;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,287 @@
+//! Melt progress
+//!
+//! A melt goes through a fixed lifecycle:
+//!
+//! ```text
+//! QuoteCreated -> ProofsReserved -> PaymentInFlight -> Settled
+//!                       |                  |---------> Failed
+//!                       |                  '---------> Refunded
+//!                       '------------------------------> Refunded
+//! ```
+//!
+//! A melt that failed or was refunded can be tried again while its quote is
+//! unpaid, which reserves proofs anew.
+//!
+//! Every step is kept in the wallet database key-value store, so a UI can show
+//! where a melt stands after a restart, and is sent to the receivers of
+//! [`Wallet::subscribe_melt_progress`] as it happens.
+
+use std::fmt;
+use std::sync::Arc;
+
+use cdk_common::database::{self, WalletDatabase};
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::instrument;
+
+use crate::mint_url::MintUrl;
+use crate::{Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const MELT_PROGRESS_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for melt progress
+const MELT_PROGRESS_SECONDARY_NAMESPACE: &str = "melt_progress";
+/// Progress updates buffered for slow receivers before the oldest are dropped
+pub(crate) const MELT_PROGRESS_CAPACITY: usize = 64;
+
+/// Step of the melt lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MeltProgressState {
+    /// Quote received from the mint
+    QuoteCreated,
+    /// Input proofs reserved for the melt
+    ProofsReserved,
+    /// Melt request sent, the mint is paying
+    PaymentInFlight,
+    /// Payment made, inputs spent and change received
+    Settled,
+    /// Melt failed and the inputs could not be reclaimed
+    Failed,
+    /// Melt failed or was cancelled and the inputs were reclaimed
+    Refunded,
+}
+
+impl MeltProgressState {
+    /// Whether the melt can move from this state to `next`
+    pub fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::QuoteCreated, Self::ProofsReserved)
+                | (Self::ProofsReserved, Self::PaymentInFlight | Self::Refunded)
+                | (
+                    Self::PaymentInFlight,
+                    Self::Settled | Self::Failed | Self::Refunded
+                )
+                // Failed melts of quotes that are still unpaid can be tried again
+                | (Self::Failed | Self::Refunded, Self::ProofsReserved)
+        )
+    }
+
+    /// Whether the melt is over
+    pub fn is_final(self) -> bool {
+        matches!(self, Self::Settled | Self::Failed | Self::Refunded)
+    }
+}
+
+impl fmt::Display for MeltProgressState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QuoteCreated => write!(f, "quote created"),
+            Self::ProofsReserved => write!(f, "proofs reserved"),
+            Self::PaymentInFlight => write!(f, "payment in flight"),
+            Self::Settled => write!(f, "settled"),
+            Self::Failed => write!(f, "failed"),
+            Self::Refunded => write!(f, "refunded"),
+        }
+    }
+}
+
+/// Progress of a melt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeltProgress {
+    /// Melt quote id
+    pub quote_id: String,
+    /// Mint of the quote
+    pub mint_url: MintUrl,
+    /// Current step
+    pub state: MeltProgressState,
+    /// Unix time the melt reached the step
+    pub updated_at: u64,
+}
+
+/// Key-value store key of a quote id
+///
+/// Quote ids are UUIDs or base64, base64 is mapped to the url-safe alphabet
+/// accepted by the key-value store.
+fn melt_progress_key(quote_id: &str) -> String {
+    quote_id
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_")
+}
+
+pub(crate) async fn read_melt_progress(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+    quote_id: &str,
+) -> Result<Option<MeltProgress>, Error> {
+    let progress = localstore
+        .kv_read(
+            MELT_PROGRESS_PRIMARY_NAMESPACE,
+            MELT_PROGRESS_SECONDARY_NAMESPACE,
+            &melt_progress_key(quote_id),
+        )
+        .await?;
+
+    Ok(progress
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()?)
+}
+
+pub(crate) async fn list_melt_progress(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+) -> Result<Vec<MeltProgress>, Error> {
+    let keys = localstore
+        .kv_list(
+            MELT_PROGRESS_PRIMARY_NAMESPACE,
+            MELT_PROGRESS_SECONDARY_NAMESPACE,
+        )
+        .await?;
+
+    let mut progress = Vec::with_capacity(keys.len());
+    for key in keys {
+        if let Some(bytes) = localstore
+            .kv_read(
+                MELT_PROGRESS_PRIMARY_NAMESPACE,
+                MELT_PROGRESS_SECONDARY_NAMESPACE,
+                &key,
+            )
+            .await?
+        {
+            progress.push(serde_json::from_slice(&bytes)?);
+        }
+    }
+
+    Ok(progress)
+}
+
+/// Remove the progress of melts that ended before `cutoff`
+pub(crate) async fn prune_melt_progress(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+    cutoff: u64,
+) -> Result<usize, Error> {
+    let mut pruned = 0;
+
+    for progress in list_melt_progress(localstore).await? {
+        if progress.state.is_final() && progress.updated_at < cutoff {
+            localstore
+                .kv_remove(
+                    MELT_PROGRESS_PRIMARY_NAMESPACE,
+                    MELT_PROGRESS_SECONDARY_NAMESPACE,
+                    &melt_progress_key(&progress.quote_id),
+                )
+                .await?;
+            pruned += 1;
+        }
+    }
+
+    Ok(pruned)
+}
+
+impl Wallet {
+    /// Move a melt to its next step, store it and notify the receivers
+    ///
+    /// Progress is informational, so transitions the lifecycle does not allow
+    /// and storage errors are only logged.
+    pub(crate) async fn record_melt_progress(&self, quote_id: &str, state: MeltProgressState) {
+        if let Err(err) = self.try_record_melt_progress(quote_id, state).await {
+            tracing::warn!(
+                "Could not record melt progress {} of quote {}: {}",
+                state,
+                quote_id,
+                err
+            );
+        }
+    }
+
+    async fn try_record_melt_progress(
+        &self,
+        quote_id: &str,
+        state: MeltProgressState,
+    ) -> Result<(), Error> {
+        let current = read_melt_progress(&self.localstore, quote_id).await?;
+
+        if let Some(current) = current {
+            if !current.state.can_transition_to(state) {
+                tracing::debug!(
+                    "Ignoring melt progress {} of quote {} in state {}",
+                    state,
+                    quote_id,
+                    current.state
+                );
+                return Ok(());
+            }
+        }
+
+        let progress = MeltProgress {
+            quote_id: quote_id.to_string(),
+            mint_url: self.mint_url.clone(),
+            state,
+            updated_at: unix_time(),
+        };
+
+        self.localstore
+            .kv_write(
+                MELT_PROGRESS_PRIMARY_NAMESPACE,
+                MELT_PROGRESS_SECONDARY_NAMESPACE,
+                &melt_progress_key(quote_id),
+                &serde_json::to_vec(&progress)?,
+            )
+            .await?;
+
+        // Nobody listening is not an error
+        let _ = self.melt_progress.send(progress);
+
+        Ok(())
+    }
+
+    /// Progress of the melt of a quote
+    #[instrument(skip(self))]
+    pub async fn melt_progress(&self, quote_id: &str) -> Result<Option<MeltProgress>, Error> {
+        read_melt_progress(&self.localstore, quote_id).await
+    }
+
+    /// Progress of every melt stored in the wallet database
+    #[instrument(skip(self))]
+    pub async fn list_melt_progress(&self) -> Result<Vec<MeltProgress>, Error> {
+        list_melt_progress(&self.localstore).await
+    }
+
+    /// Receive the progress of melts made by this wallet as they happen
+    ///
+    /// Receivers falling more than a few dozen updates behind miss the oldest ones.
+    pub fn subscribe_melt_progress(&self) -> broadcast::Receiver<MeltProgress> {
+        self.melt_progress.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_melt_progress_transitions() {
+        use MeltProgressState::*;
+
+        assert!(QuoteCreated.can_transition_to(ProofsReserved));
+        assert!(ProofsReserved.can_transition_to(PaymentInFlight));
+        assert!(PaymentInFlight.can_transition_to(Settled));
+        assert!(PaymentInFlight.can_transition_to(Failed));
+        assert!(PaymentInFlight.can_transition_to(Refunded));
+        assert!(Refunded.can_transition_to(ProofsReserved));
+
+        assert!(!QuoteCreated.can_transition_to(Settled));
+        assert!(!Settled.can_transition_to(ProofsReserved));
+        assert!(!PaymentInFlight.can_transition_to(QuoteCreated));
+        assert!(!Settled.can_transition_to(Refunded));
+    }
+
+    #[test]
+    fn test_melt_progress_key() {
+        assert_eq!(
+            melt_progress_key("5c1e63f4-7d2b-4a43-9a37-0c2f5b0b3e1a"),
+            "5c1e63f4-7d2b-4a43-9a37-0c2f5b0b3e1a"
+        );
+        assert_eq!(melt_progress_key("ab+c/d=="), "ab-c_d");
+    }
+}
@@ -0,0 +1,258 @@
+//! Mint attestations
+//!
+//! Structured reports of a wallet's experience with a mint, published on nostr
+//! as NIP-87 recommendations and aggregated by mint discovery into a
+//! decentralized reputation for the mint.
+//!
+//! An attestation is a kind `38000` event with the tags `k` = `38172` and
+//! `d` = `u` = mint url, a `[rating/5] review` content and the tags:
+//!
+//! - `["uptime", "<percent of successful requests>"]`
+//! - `["melts", "<successful melt count>"]`
+//! - `["fee_ppm", "<average lightning fee in parts per million of the amount melted>"]`
+//! - `["period", "<first unix timestamp>", "<last unix timestamp>"]`
+
+use cdk_common::wallet::TransactionDirection;
+use nostr_sdk::{Event, EventBuilder, Tag};
+use tracing::instrument;
+
+use super::mint_discovery::tag_values;
+use super::nostr_transport::{nostr_keys, relay_client, EventKind, RelayUse};
+use crate::nuts::SecretKey;
+use crate::{ensure_cdk, Error, Wallet};
+
+/// Tag holding the observed uptime
+const UPTIME_TAG: &str = "uptime";
+/// Tag holding the successful melt count
+const MELTS_TAG: &str = "melts";
+/// Tag holding the average lightning fee
+const FEE_PPM_TAG: &str = "fee_ppm";
+/// Tag holding the observation period
+const PERIOD_TAG: &str = "period";
+
+/// Observations of a wallet about a mint
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MintAttestation {
+    /// Requests to the mint that succeeded, in hundredths of a percent
+    pub uptime_basis_points: Option<u16>,
+    /// Number of successful melts
+    pub successful_melts: u64,
+    /// Average lightning fee paid in parts per million of the amount melted
+    pub average_fee_ppm: Option<u64>,
+    /// First and last unix timestamp of the observed transactions
+    pub period: Option<(u64, u64)>,
+}
+
+impl MintAttestation {
+    /// Uptime in hundredths of a percent, `None` when `percent` is not between 0 and 100
+    pub fn uptime_from_percent(percent: f64) -> Option<u16> {
+        (0.0..=100.0)
+            .contains(&percent)
+            .then(|| (percent * 100.0).round() as u16)
+    }
+
+    /// Structured attestation tags
+    fn tags(&self) -> Vec<Vec<String>> {
+        let mut tags = vec![vec![
+            MELTS_TAG.to_string(),
+            self.successful_melts.to_string(),
+        ]];
+
+        if let Some(uptime) = self.uptime_basis_points {
+            tags.push(vec![
+                UPTIME_TAG.to_string(),
+                format!("{}.{:02}", uptime / 100, uptime % 100),
+            ]);
+        }
+
+        if let Some(average_fee_ppm) = self.average_fee_ppm {
+            tags.push(vec![FEE_PPM_TAG.to_string(), average_fee_ppm.to_string()]);
+        }
+
+        if let Some((start, end)) = self.period {
+            tags.push(vec![
+                PERIOD_TAG.to_string(),
+                start.to_string(),
+                end.to_string(),
+            ]);
+        }
+
+        tags
+    }
+
+    /// Parse an attestation from a recommendation event
+    ///
+    /// Returns `None` for plain recommendations without a `melts` tag.
+    pub(super) fn from_event(event: &Event) -> Option<Self> {
+        let successful_melts = tag_values(event, MELTS_TAG).next()?.parse().ok()?;
+
+        let period = event.tags.iter().find_map(|tag| match tag.as_slice() {
+            [name, start, end, ..] if name == PERIOD_TAG => {
+                Some((start.parse().ok()?, end.parse().ok()?))
+            }
+            _ => None,
+        });
+
+        Some(Self {
+            uptime_basis_points: tag_values(event, UPTIME_TAG)
+                .next()
+                .and_then(|v| v.parse().ok())
+                .and_then(Self::uptime_from_percent),
+            successful_melts,
+            average_fee_ppm: tag_values(event, FEE_PPM_TAG)
+                .next()
+                .and_then(|v| v.parse().ok()),
+            period,
+        })
+    }
+}
+
+impl Wallet {
+    /// Attestation of the mint from the wallet's transaction history
+    ///
+    /// Counts the melts recorded for the mint and their lightning fees. The wallet
+    /// does not track uptime, so `uptime_basis_points` is left for the caller to set.
+    #[instrument(skip(self))]
+    pub async fn mint_attestation(&self) -> Result<MintAttestation, Error> {
+        let melts: Vec<_> = self
+            .list_transactions(Some(TransactionDirection::Outgoing))
+            .await?
+            .into_iter()
+            .filter(|tx| tx.quote_id.is_some())
+            .collect();
+
+        let melted: u64 = melts.iter().map(|tx| u64::from(tx.amount)).sum();
+        let lightning_fees: u64 = melts
+            .iter()
+            .map(|tx| {
+                tx.metadata
+                    .get("lightning_fee")
+                    .and_then(|fee| fee.parse().ok())
+                    .unwrap_or(u64::from(tx.fee))
+            })
+            .sum();
+
+        let average_fee_ppm = match melted {
+            0 => None,
+            melted => Some(
+                (u128::from(lightning_fees) * 1_000_000 / u128::from(melted))
+                    .try_into()
+                    .unwrap_or(u64::MAX),
+            ),
+        };
+
+        let period = melts
+            .iter()
+            .map(|tx| tx.timestamp)
+            .min()
+            .zip(melts.iter().map(|tx| tx.timestamp).max());
+
+        Ok(MintAttestation {
+            uptime_basis_points: None,
+            successful_melts: melts.len() as u64,
+            average_fee_ppm,
+            period,
+        })
+    }
+
+    /// Publish an attestation of the mint to nostr
+    ///
+    /// The event is signed with `secret_key`, so attestations from the same key
+    /// build up a reputation for its author. Returns the hex id of the event.
+    #[instrument(skip(self, secret_key, attestation))]
+    pub async fn publish_mint_attestation(
+        &self,
+        secret_key: &SecretKey,
+        relays: Vec<String>,
+        attestation: &MintAttestation,
+        rating: Option<u8>,
+        review: &str,
+    ) -> Result<String, Error> {
+        ensure_cdk!(
+            rating.is_none_or(|rating| rating <= 5),
+            Error::Custom("Rating must be between 0 and 5".to_string())
+        );
+
+        let keys = nostr_keys(secret_key)?;
+        let client = relay_client(&relays, RelayUse::Write).await?;
+
+        let mint_url = self.mint_url.to_string();
+
+        let mut tags = vec![
+            vec![
+                "k".to_string(),
+                EventKind::MintAnnouncement.as_u16().to_string(),
+            ],
+            vec!["d".to_string(), mint_url.clone()],
+            vec!["u".to_string(), mint_url],
+        ];
+        tags.extend(attestation.tags());
+
+        let tags = tags
+            .into_iter()
+            .map(Tag::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::Custom(format!("Invalid attestation tag: {e}")))?;
+
+        let content = match rating {
+            Some(rating) => format!("[{rating}/5] {review}").trim_end().to_string(),
+            None => review.to_string(),
+        };
+
+        let event = EventBuilder::new(EventKind::Recommendation.into(), content)
+            .tags(tags)
+            .sign_with_keys(&keys)
+            .map_err(|e| Error::Custom(format!("Sign Nostr event: {e}")))?;
+
+        let output = client
+            .send_event(&event)
+            .await
+            .map_err(|e| Error::Custom(format!("Publish Nostr event: {e}")))?;
+
+        if !output.failed.is_empty() {
+            tracing::warn!(
+                "Could not publish attestation to {} relays",
+                output.failed.len()
+            );
+        }
+
+        Ok(output.val.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::{Keys, Kind};
+
+    use super::*;
+
+    #[test]
+    fn test_attestation_roundtrip() {
+        let attestation = MintAttestation {
+            uptime_basis_points: Some(9_950),
+            successful_melts: 12,
+            average_fee_ppm: Some(4_000),
+            period: Some((1_700_000_000, 1_700_086_400)),
+        };
+
+        let tags = attestation
+            .tags()
+            .into_iter()
+            .map(|tag| Tag::parse(tag).unwrap())
+            .collect::<Vec<_>>();
+
+        let event = EventBuilder::new(Kind::from(EventKind::Recommendation), "[5/5] Fast melts")
+            .tags(tags)
+            .sign_with_keys(&Keys::generate())
+            .unwrap();
+
+        assert_eq!(MintAttestation::from_event(&event), Some(attestation));
+
+        let recommendation =
+            EventBuilder::new(Kind::from(EventKind::Recommendation), "[4/5] Good mint")
+                .sign_with_keys(&Keys::generate())
+                .unwrap();
+
+        assert_eq!(MintAttestation::from_event(&recommendation), None);
+    }
+}
@@ -3,7 +3,10 @@ use std::collections::HashSet;
 use std::sync::{Arc, RwLock as StdRwLock};
 
 use async_trait::async_trait;
-use cdk_common::{nut19, MeltQuoteBolt12Request, MintQuoteBolt12Request, MintQuoteBolt12Response};
+use cdk_common::{
+    nut19, MeltQuoteBolt12Request, MeltQuoteKeysendRequest, MintQuoteBolt12Request,
+    MintQuoteBolt12Response,
+};
 #[cfg(feature = "auth")]
 use cdk_common::{Method, ProtectedEndpoint, RoutePath};
 use serde::de::DeserializeOwned;
@@ -14,8 +17,10 @@ use tracing::instrument;
 use url::Url;
 use web_time::{Duration, Instant};
 
+use super::rate_limit;
 use super::transport::Transport;
 use super::{Error, MintConnector};
+use crate::keyset_history::KeysetHistory;
 use crate::mint_url::MintUrl;
 #[cfg(feature = "auth")]
 use crate::nuts::nut22::MintAuthRequest;
@@ -107,6 +112,35 @@ where
         })
     }
 
+    /// HTTP Get request, backing off while the mint rate limits requests
+    async fn http_get<R>(&self, url: Url, auth: Option<AuthToken>) -> Result<R, Error>
+    where
+        R: DeserializeOwned,
+    {
+        rate_limit::send(&self.mint_url, || {
+            self.transport.http_get(url.clone(), auth.clone())
+        })
+        .await
+    }
+
+    /// HTTP Post request, backing off while the mint rate limits requests
+    async fn http_post<P, R>(
+        &self,
+        url: Url,
+        auth_token: Option<AuthToken>,
+        payload: &P,
+    ) -> Result<R, Error>
+    where
+        P: Serialize + ?Sized + Send + Sync,
+        R: DeserializeOwned,
+    {
+        rate_limit::send(&self.mint_url, || {
+            self.transport
+                .http_post(url.clone(), auth_token.clone(), payload)
+        })
+        .await
+    }
+
     /// Generic implementation of a retriable http request
     ///
     /// The retry only happens if the mint supports replay through the Caching of NUT-19.
@@ -143,16 +177,13 @@ where
                 nut19::Path::MeltBolt11 => vec!["v1", "melt", "bolt11"],
                 nut19::Path::MintBolt12 => vec!["v1", "mint", "bolt12"],
                 nut19::Path::MeltBolt12 => vec!["v1", "melt", "bolt12"],
+                nut19::Path::MeltKeysend => vec!["v1", "melt", "keysend"],
                 nut19::Path::Swap => vec!["v1", "swap"],
             })?;
 
             let result = match method {
-                nut19::Method::Get => self.transport.http_get(url, auth_token.clone()).await,
-                nut19::Method::Post => {
-                    self.transport
-                        .http_post(url, auth_token.clone(), payload)
-                        .await
-                }
+                nut19::Method::Get => self.http_get(url, auth_token.clone()).await,
+                nut19::Method::Post => self.http_post(url, auth_token.clone(), payload).await,
             };
 
             if result.is_ok() {
@@ -212,7 +243,7 @@ where
             .mint_url
             .join_paths(&["v1", "keys", &keyset_id.to_string()])?;
 
-        let keys_response = self.transport.http_get::<KeysResponse>(url, None).await?;
+        let keys_response = self.http_get::<KeysResponse>(url, None).await?;
 
         Ok(keys_response.keysets.first().unwrap().clone())
     }
@@ -221,7 +252,14 @@ where
     #[instrument(skip(self), fields(mint_url = %self.mint_url))]
     async fn get_mint_keysets(&self) -> Result<KeysetResponse, Error> {
         let url = self.mint_url.join_paths(&["v1", "keysets"])?;
-        self.transport.http_get(url, None).await
+        self.http_get(url, None).await
+    }
+
+    /// Get every keyset of the mint with its keys
+    #[instrument(skip(self), fields(mint_url = %self.mint_url))]
+    async fn get_keyset_history(&self) -> Result<KeysetHistory, Error> {
+        let url = self.mint_url.join_paths(&["v1", "keysets", "history"])?;
+        self.http_get(url, None).await
     }
 
     /// Mint Quote [NUT-04]
@@ -242,7 +280,7 @@ where
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
 
-        self.transport.http_post(url, auth_token, &request).await
+        self.http_post(url, auth_token, &request).await
     }
 
     /// Mint Quote status
@@ -262,7 +300,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_get(url, auth_token).await
+        self.http_get(url, auth_token).await
     }
 
     /// Mint Tokens [NUT-04]
@@ -300,7 +338,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_post(url, auth_token, &request).await
+        self.http_post(url, auth_token, &request).await
     }
 
     /// Melt Quote Status
@@ -320,7 +358,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_get(url, auth_token).await
+        self.http_get(url, auth_token).await
     }
 
     /// Melt [NUT-05]
@@ -368,7 +406,7 @@ where
     /// Helper to get mint info
     async fn get_mint_info(&self) -> Result<MintInfo, Error> {
         let url = self.mint_url.join_paths(&["v1", "info"])?;
-        let info: MintInfo = self.transport.http_get(url, None).await?;
+        let info: MintInfo = self.http_get(url, None).await?;
 
         if let Ok(mut cache_support) = self.cache_support.write() {
             *cache_support = (
@@ -410,7 +448,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_post(url, auth_token, &request).await
+        self.http_post(url, auth_token, &request).await
     }
 
     /// Restore request [NUT-13]
@@ -424,7 +462,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_post(url, auth_token, &request).await
+        self.http_post(url, auth_token, &request).await
     }
 
     /// Mint Quote Bolt12 [NUT-23]
@@ -445,7 +483,7 @@ where
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
 
-        self.transport.http_post(url, auth_token, &request).await
+        self.http_post(url, auth_token, &request).await
     }
 
     /// Mint Quote Bolt12 status
@@ -465,7 +503,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_get(url, auth_token).await
+        self.http_get(url, auth_token).await
     }
 
     /// Melt Quote Bolt12 [NUT-23]
@@ -484,7 +522,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_post(url, auth_token, &request).await
+        self.http_post(url, auth_token, &request).await
     }
 
     /// Melt Quote Bolt12 Status [NUT-23]
@@ -504,7 +542,7 @@ where
 
         #[cfg(not(feature = "auth"))]
         let auth_token = None;
-        self.transport.http_get(url, auth_token).await
+        self.http_get(url, auth_token).await
     }
 
     /// Melt Bolt12 [NUT-23]
@@ -528,6 +566,67 @@ where
         )
         .await
     }
+
+    /// Melt Quote for a keysend payment
+    #[instrument(skip(self, request), fields(mint_url = %self.mint_url))]
+    async fn post_melt_keysend_quote(
+        &self,
+        request: MeltQuoteKeysendRequest,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error> {
+        let url = self
+            .mint_url
+            .join_paths(&["v1", "melt", "quote", "keysend"])?;
+        #[cfg(feature = "auth")]
+        let auth_token = self
+            .get_auth_token(Method::Post, RoutePath::MeltQuoteKeysend)
+            .await?;
+
+        #[cfg(not(feature = "auth"))]
+        let auth_token = None;
+        self.http_post(url, auth_token, &request).await
+    }
+
+    /// Melt Quote Status for a keysend payment
+    #[instrument(skip(self), fields(mint_url = %self.mint_url))]
+    async fn get_melt_keysend_quote_status(
+        &self,
+        quote_id: &str,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error> {
+        let url = self
+            .mint_url
+            .join_paths(&["v1", "melt", "quote", "keysend", quote_id])?;
+
+        #[cfg(feature = "auth")]
+        let auth_token = self
+            .get_auth_token(Method::Get, RoutePath::MeltQuoteKeysend)
+            .await?;
+
+        #[cfg(not(feature = "auth"))]
+        let auth_token = None;
+        self.http_get(url, auth_token).await
+    }
+
+    /// Melt for a keysend payment
+    #[instrument(skip(self, request), fields(mint_url = %self.mint_url))]
+    async fn post_melt_keysend(
+        &self,
+        request: MeltRequest<String>,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error> {
+        #[cfg(feature = "auth")]
+        let auth_token = self
+            .get_auth_token(Method::Post, RoutePath::MeltKeysend)
+            .await?;
+
+        #[cfg(not(feature = "auth"))]
+        let auth_token = None;
+        self.retriable_http_request(
+            nut19::Method::Post,
+            nut19::Path::MeltKeysend,
+            auth_token,
+            &request,
+        )
+        .await
+    }
 }
 
 /// Http Client
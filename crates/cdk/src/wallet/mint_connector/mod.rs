@@ -3,9 +3,13 @@
 use std::fmt::Debug;
 
 use async_trait::async_trait;
-use cdk_common::{MeltQuoteBolt12Request, MintQuoteBolt12Request, MintQuoteBolt12Response};
+use cdk_common::{
+    MeltQuoteBolt12Request, MeltQuoteKeysendRequest, MintQuoteBolt12Request,
+    MintQuoteBolt12Response,
+};
 
 use super::Error;
+use crate::keyset_history::KeysetHistory;
 use crate::nuts::{
     CheckStateRequest, CheckStateResponse, Id, KeySet, KeysetResponse, MeltQuoteBolt11Request,
     MeltQuoteBolt11Response, MeltRequest, MintInfo, MintQuoteBolt11Request,
@@ -16,6 +20,7 @@ use crate::nuts::{
 use crate::wallet::AuthWallet;
 
 pub mod http_client;
+mod rate_limit;
 pub mod transport;
 
 /// Auth HTTP Client with async transport
@@ -38,6 +43,8 @@ pub trait MintConnector: Debug {
     async fn get_mint_keyset(&self, keyset_id: Id) -> Result<KeySet, Error>;
     /// Get Keysets [NUT-02]
     async fn get_mint_keysets(&self) -> Result<KeysetResponse, Error>;
+    /// Get every keyset of the mint with its keys
+    async fn get_keyset_history(&self) -> Result<KeysetHistory, Error>;
     /// Mint Quote [NUT-04]
     async fn post_mint_quote(
         &self,
@@ -110,4 +117,19 @@ pub trait MintConnector: Debug {
         &self,
         request: MeltRequest<String>,
     ) -> Result<MeltQuoteBolt11Response<String>, Error>;
+    /// Melt Quote for a keysend payment
+    async fn post_melt_keysend_quote(
+        &self,
+        request: MeltQuoteKeysendRequest,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error>;
+    /// Melt Quote Status for a keysend payment
+    async fn get_melt_keysend_quote_status(
+        &self,
+        quote_id: &str,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error>;
+    /// Melt for a keysend payment
+    async fn post_melt_keysend(
+        &self,
+        request: MeltRequest<String>,
+    ) -> Result<MeltQuoteBolt11Response<String>, Error>;
 }
@@ -0,0 +1,199 @@
+//! Rate limiting
+//!
+//! Mints answer requests over their rate limit with `429 Too Many Requests`,
+//! usually with a `Retry-After` header saying how long to back off. The backoff
+//! is kept per mint for the whole process, so concurrent wallet tasks talking to
+//! the same mint hold back their requests together instead of each running into
+//! the limit again.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+
+use cdk_common::util::unix_time;
+use web_time::{Duration, Instant};
+
+use super::Error;
+use crate::mint_url::MintUrl;
+
+/// Backoff used when a mint does not say how long to wait
+pub(crate) const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// Longest backoff accepted from a mint
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+/// Longest backoff waited out before a request, longer ones fail with [`Error::RateLimited`]
+const MAX_WAIT: Duration = Duration::from_secs(30);
+/// Times a rate limited request is sent again
+const MAX_RETRIES: usize = 3;
+
+/// Time until which requests to a mint are held back
+type Backoffs = Mutex<HashMap<MintUrl, Instant>>;
+
+static BACKOFFS: OnceLock<Backoffs> = OnceLock::new();
+
+fn backoffs() -> &'static Backoffs {
+    BACKOFFS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Parse a `Retry-After` header value, given in seconds or as an HTTP date
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    let retry_after = match value.parse::<u64>() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => {
+            let date = httpdate::parse_http_date(value)
+                .ok()?
+                .duration_since(std::time::UNIX_EPOCH)
+                .ok()?;
+            Duration::from_secs(date.as_secs().saturating_sub(unix_time()))
+        }
+    };
+
+    Some(retry_after.min(MAX_RETRY_AFTER))
+}
+
+/// Hold back requests to `mint_url` for `retry_after`
+pub(crate) fn back_off(mint_url: &MintUrl, retry_after: Duration) {
+    let now = Instant::now();
+    let until = now + retry_after.min(MAX_RETRY_AFTER);
+
+    let mut backoffs = backoffs().lock().unwrap_or_else(|e| e.into_inner());
+    backoffs.retain(|_, until| *until > now);
+
+    let current = backoffs.entry(mint_url.clone()).or_insert(until);
+    if *current < until {
+        *current = until;
+    }
+}
+
+/// Time left until requests to `mint_url` are sent again
+pub(crate) fn remaining_backoff(mint_url: &MintUrl) -> Option<Duration> {
+    let backoffs = backoffs().lock().unwrap_or_else(|e| e.into_inner());
+
+    backoffs
+        .get(mint_url)
+        .map(|until| until.saturating_duration_since(Instant::now()))
+        .filter(|remaining| !remaining.is_zero())
+}
+
+async fn sleep(duration: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Send `request` to `mint_url`, backing off while the mint rate limits requests
+///
+/// Short backoffs are waited out and the request is sent again a few times,
+/// otherwise [`Error::RateLimited`] is returned with the time left to wait.
+pub(crate) async fn send<R, F, Fut>(mint_url: &MintUrl, request: F) -> Result<R, Error>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<R, Error>>,
+{
+    let mut retries = 0;
+
+    loop {
+        if let Some(retry_after) = remaining_backoff(mint_url) {
+            if retry_after > MAX_WAIT {
+                return Err(Error::RateLimited { retry_after });
+            }
+
+            tracing::debug!(
+                "Waiting {:?} before sending a request to rate limiting mint {}",
+                retry_after,
+                mint_url
+            );
+            sleep(retry_after).await;
+        }
+
+        match request().await {
+            Err(Error::RateLimited { retry_after }) => {
+                back_off(mint_url, retry_after);
+
+                if retries >= MAX_RETRIES || retry_after > MAX_WAIT {
+                    return Err(Error::RateLimited { retry_after });
+                }
+
+                tracing::warn!(
+                    "Mint {} is rate limiting requests, retrying after {:?}",
+                    mint_url,
+                    retry_after
+                );
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            parse_retry_after(&httpdate::fmt_http_date(
+                std::time::UNIX_EPOCH + std::time::Duration::from_secs(unix_time() + 600)
+            ))
+            .map(|retry_after| retry_after.as_secs() > 590),
+            Some(true)
+        );
+        assert_eq!(parse_retry_after("9999999999"), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_backoff_shared_by_requests_to_mint() {
+        let mint_url = MintUrl::from_str("https://rate-limited.example.com").unwrap();
+        let other_mint_url = MintUrl::from_str("https://other.example.com").unwrap();
+        let sent = AtomicUsize::new(0);
+
+        // Short backoffs are waited out and the request is sent again
+        let result = send(&mint_url, || async {
+            match sent.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(Error::RateLimited {
+                    retry_after: Duration::ZERO,
+                }),
+                _ => Ok(()),
+            }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+
+        // Long backoffs fail every request to the mint without sending it
+        let result = send(&mint_url, || async {
+            Err::<(), _>(Error::RateLimited {
+                retry_after: Duration::from_secs(600),
+            })
+        })
+        .await;
+        assert!(matches!(result, Err(Error::RateLimited { .. })));
+
+        let result = send(&mint_url, || async {
+            sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::RateLimited { retry_after }) if retry_after > MAX_WAIT
+        ));
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+
+        // Other mints are not held back
+        assert!(send(&other_mint_url, || async { Ok(()) }).await.is_ok());
+    }
+}
@@ -8,15 +8,20 @@ use hickory_resolver::config::ResolverConfig;
 use hickory_resolver::name_server::TokioConnectionProvider;
 #[cfg(all(feature = "bip353", not(target_arch = "wasm32")))]
 use hickory_resolver::Resolver;
-use reqwest::Client;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use url::Url;
 
+use super::rate_limit::{parse_retry_after, DEFAULT_RETRY_AFTER};
 use super::Error;
 use crate::error::ErrorResponse;
 
 /// Expected HTTP Transport
+///
+/// Responses with status `429 Too Many Requests` are expected to be returned as
+/// [`Error::RateLimited`], so the client backs off from the mint.
 #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 pub trait Transport: Default + Send + Sync + Debug + Clone {
@@ -146,23 +151,23 @@ impl Transport for Async {
             request = request.header(auth.header_key(), auth.to_string());
         }
 
-        let response = request
-            .send()
-            .await
-            .map_err(|e| {
-                Error::HttpError(
-                    e.status().map(|status_code| status_code.as_u16()),
-                    e.to_string(),
-                )
-            })?
-            .text()
-            .await
-            .map_err(|e| {
-                Error::HttpError(
-                    e.status().map(|status_code| status_code.as_u16()),
-                    e.to_string(),
-                )
-            })?;
+        let response = request.send().await.map_err(|e| {
+            Error::HttpError(
+                e.status().map(|status_code| status_code.as_u16()),
+                e.to_string(),
+            )
+        })?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(&response));
+        }
+
+        let response = response.text().await.map_err(|e| {
+            Error::HttpError(
+                e.status().map(|status_code| status_code.as_u16()),
+                e.to_string(),
+            )
+        })?;
 
         serde_json::from_str::<R>(&response).map_err(|err| {
             tracing::warn!("Http Response error: {}", err);
@@ -196,6 +201,10 @@ impl Transport for Async {
             )
         })?;
 
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(&response));
+        }
+
         let response = response.text().await.map_err(|e| {
             Error::HttpError(
                 e.status().map(|status_code| status_code.as_u16()),
@@ -212,3 +221,15 @@ impl Transport for Async {
         })
     }
 }
+
+/// [`Error::RateLimited`] of a `429 Too Many Requests` response
+fn rate_limited(response: &Response) -> Error {
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after)
+        .unwrap_or(DEFAULT_RETRY_AFTER);
+
+    Error::RateLimited { retry_after }
+}
@@ -0,0 +1,402 @@
+//! Mint discovery
+//!
+//! Discovers mints from NIP-87 announcements and recommendations published on
+//! nostr, so a wallet can be onboarded without pasting mint urls.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use futures::{future, stream, StreamExt};
+use nostr_sdk::{Alphabet, Event, Filter, Kind, SingleLetterTag};
+use tracing::instrument;
+
+use super::mint_attestation::MintAttestation;
+use super::nostr_transport::{relay_client, EventKind, RelayUse, RELAY_TIMEOUT};
+use crate::mint_url::MintUrl;
+use crate::nuts::{CurrencyUnit, MintInfo};
+use crate::wallet::{HttpClient, MintConnector};
+use crate::{Error, Wallet};
+
+/// Timeout for fetching the info of a discovered mint
+const MINT_INFO_TIMEOUT: Duration = Duration::from_secs(10);
+/// Mints whose info is requested at the same time
+const MAX_CONCURRENT_MINT_INFO: usize = 8;
+
+/// Review of a mint published in a NIP-87 recommendation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintReview {
+    /// Hex pubkey of the author
+    pub author: String,
+    /// Rating out of 5, if the review starts with `[n/5]`
+    pub rating: Option<u8>,
+    /// Review text
+    pub content: String,
+    /// Unix timestamp of the review
+    pub created_at: u64,
+    /// Structured observations, if the review is an attestation
+    pub attestation: Option<MintAttestation>,
+}
+
+/// Mint found through nostr discovery
+#[derive(Debug, Clone)]
+pub struct DiscoveredMint {
+    /// Mint url
+    pub mint_url: MintUrl,
+    /// Identifier of the mint announcement, usually the mint pubkey
+    pub identifier: Option<String>,
+    /// NUTs listed in the mint announcement
+    pub nuts: Vec<u16>,
+    /// Network listed in the mint announcement
+    pub network: Option<String>,
+    /// Info fetched from the mint
+    pub mint_info: Option<MintInfo>,
+    /// Latest review of each author recommending the mint
+    pub reviews: Vec<MintReview>,
+}
+
+impl DiscoveredMint {
+    fn new(mint_url: MintUrl) -> Self {
+        Self {
+            mint_url,
+            identifier: None,
+            nuts: Vec::new(),
+            network: None,
+            mint_info: None,
+            reviews: Vec::new(),
+        }
+    }
+
+    /// Number of distinct authors recommending the mint
+    pub fn recommendations(&self) -> usize {
+        self.reviews.len()
+    }
+
+    /// Average rating of the reviews that include one
+    pub fn average_rating(&self) -> Option<f64> {
+        let ratings: Vec<u8> = self.reviews.iter().filter_map(|r| r.rating).collect();
+
+        match ratings.is_empty() {
+            true => None,
+            false => {
+                Some(ratings.iter().map(|r| f64::from(*r)).sum::<f64>() / ratings.len() as f64)
+            }
+        }
+    }
+
+    /// Attestations included in the reviews
+    fn attestations(&self) -> impl Iterator<Item = &MintAttestation> {
+        self.reviews.iter().filter_map(|r| r.attestation.as_ref())
+    }
+
+    /// Total successful melts attested by reviewers
+    pub fn attested_melts(&self) -> u64 {
+        self.attestations().map(|a| a.successful_melts).sum()
+    }
+
+    /// Average uptime attested by reviewers
+    pub fn average_uptime(&self) -> Option<f64> {
+        let uptimes: Vec<f64> = self
+            .attestations()
+            .filter_map(|a| a.uptime_basis_points)
+            .map(|uptime| f64::from(uptime) / 100.0)
+            .collect();
+
+        match uptimes.is_empty() {
+            true => None,
+            false => Some(uptimes.iter().sum::<f64>() / uptimes.len() as f64),
+        }
+    }
+
+    /// Average lightning fee attested by reviewers, weighted by their melt count
+    pub fn average_fee_ppm(&self) -> Option<u64> {
+        let (fees, melts) = self
+            .attestations()
+            .filter_map(|a| a.average_fee_ppm.map(|fee| (fee, a.successful_melts)))
+            .fold((0u128, 0u128), |(fees, melts), (fee, count)| {
+                (
+                    fees + u128::from(fee) * u128::from(count),
+                    melts + u128::from(count),
+                )
+            });
+
+        match melts {
+            0 => None,
+            melts => Some((fees / melts).try_into().unwrap_or(u64::MAX)),
+        }
+    }
+
+    /// Add a review, keeping only the latest review of each author
+    fn add_review(&mut self, review: MintReview) {
+        match self.reviews.iter_mut().find(|r| r.author == review.author) {
+            Some(existing) => {
+                if review.created_at > existing.created_at {
+                    *existing = review;
+                }
+            }
+            None => self.reviews.push(review),
+        }
+    }
+}
+
+/// Values of every tag with the given name
+pub(super) fn tag_values<'a>(
+    event: &'a Event,
+    name: &'a str,
+) -> impl Iterator<Item = &'a str> + 'a {
+    event
+        .tags
+        .iter()
+        .filter_map(move |tag| match tag.as_slice() {
+            [tag_name, value, ..] if tag_name == name => Some(value.as_str()),
+            _ => None,
+        })
+}
+
+/// Parse a `[n/5]` rating prefix from a review
+fn parse_rating(content: &str) -> Option<u8> {
+    let rest = content.trim_start().strip_prefix('[')?;
+    let (rating, rest) = rest.split_once('/')?;
+    let (max, _) = rest.split_once(']')?;
+
+    let rating: u8 = rating.trim().parse().ok()?;
+
+    match max.trim() == "5" && rating <= 5 {
+        true => Some(rating),
+        false => None,
+    }
+}
+
+/// Order mints by number of recommendations, then average rating
+fn rank_mints(mints: &mut [DiscoveredMint]) {
+    mints.sort_by(|a, b| {
+        b.recommendations()
+            .cmp(&a.recommendations())
+            .then_with(|| {
+                b.average_rating()
+                    .unwrap_or_default()
+                    .total_cmp(&a.average_rating().unwrap_or_default())
+            })
+            .then_with(|| a.mint_url.cmp(&b.mint_url))
+    });
+}
+
+impl Wallet {
+    /// Discover mints announced and recommended on nostr (NIP-87)
+    ///
+    /// Collects mint announcements and recommendations from `relays`, fetches the
+    /// info of every mint found and returns the reachable mints supporting `unit`,
+    /// ranked by number of recommendations and average rating.
+    #[instrument(skip(relays))]
+    pub async fn discover_mints(
+        relays: Vec<String>,
+        unit: &CurrencyUnit,
+    ) -> Result<Vec<DiscoveredMint>, Error> {
+        let client = relay_client(&relays, RelayUse::Read).await?;
+
+        let announcements = client
+            .fetch_events(
+                Filter::new().kind(Kind::from(EventKind::MintAnnouncement)),
+                RELAY_TIMEOUT,
+            )
+            .await
+            .map_err(|e| Error::Custom(format!("Fetch mint announcements: {e}")))?;
+
+        let recommendations = client
+            .fetch_events(
+                Filter::new()
+                    .kind(Kind::from(EventKind::Recommendation))
+                    .custom_tag(
+                        SingleLetterTag::lowercase(Alphabet::K),
+                        EventKind::MintAnnouncement.as_u16().to_string(),
+                    ),
+                RELAY_TIMEOUT,
+            )
+            .await
+            .map_err(|e| Error::Custom(format!("Fetch mint recommendations: {e}")))?;
+
+        let mut mints: HashMap<MintUrl, DiscoveredMint> = HashMap::new();
+        // Announcement address `38172:<pubkey>:<identifier>` to mint url
+        let mut addresses: HashMap<String, MintUrl> = HashMap::new();
+
+        for event in announcements.iter() {
+            let mint_url = match tag_values(event, "u").find_map(|url| MintUrl::from_str(url).ok())
+            {
+                Some(mint_url) => mint_url,
+                None => continue,
+            };
+
+            let identifier = tag_values(event, "d").next().map(|d| d.to_string());
+
+            if let Some(identifier) = &identifier {
+                addresses.insert(
+                    format!(
+                        "{}:{}:{}",
+                        EventKind::MintAnnouncement.as_u16(),
+                        event.pubkey.to_hex(),
+                        identifier
+                    ),
+                    mint_url.clone(),
+                );
+            }
+
+            let mint = mints
+                .entry(mint_url.clone())
+                .or_insert_with(|| DiscoveredMint::new(mint_url));
+
+            mint.identifier = identifier;
+            mint.nuts = tag_values(event, "nuts")
+                .flat_map(|nuts| nuts.split(','))
+                .filter_map(|nut| nut.trim().parse().ok())
+                .collect();
+            mint.network = tag_values(event, "n").next().map(|n| n.to_string());
+        }
+
+        for event in recommendations.iter() {
+            let mut mint_urls: Vec<MintUrl> = tag_values(event, "u")
+                .filter_map(|url| MintUrl::from_str(url).ok())
+                .collect();
+            mint_urls.extend(
+                tag_values(event, "a").filter_map(|address| addresses.get(address).cloned()),
+            );
+            mint_urls.sort();
+            mint_urls.dedup();
+
+            for mint_url in mint_urls {
+                mints
+                    .entry(mint_url.clone())
+                    .or_insert_with(|| DiscoveredMint::new(mint_url))
+                    .add_review(MintReview {
+                        author: event.pubkey.to_hex(),
+                        rating: parse_rating(&event.content),
+                        content: event.content.clone(),
+                        created_at: event.created_at.as_u64(),
+                        attestation: MintAttestation::from_event(event),
+                    });
+            }
+        }
+
+        tracing::debug!(
+            "Found {} mints from {} announcements and {} recommendations",
+            mints.len(),
+            announcements.len(),
+            recommendations.len()
+        );
+
+        let mut discovered: Vec<DiscoveredMint> =
+            stream::iter(mints.into_values().map(|mut mint| {
+                let unit = unit.clone();
+                async move {
+                    #[cfg(feature = "auth")]
+                    let client = HttpClient::new(mint.mint_url.clone(), None);
+                    #[cfg(not(feature = "auth"))]
+                    let client = HttpClient::new(mint.mint_url.clone());
+
+                    match tokio::time::timeout(MINT_INFO_TIMEOUT, client.get_mint_info()).await {
+                        Ok(Ok(mint_info)) if mint_info.supported_units().contains(&&unit) => {
+                            mint.mint_info = Some(mint_info);
+                            Some(mint)
+                        }
+                        Ok(Ok(_)) => None,
+                        Ok(Err(err)) => {
+                            tracing::debug!("Could not get info of {}: {}", mint.mint_url, err);
+                            None
+                        }
+                        Err(_) => {
+                            tracing::debug!("Timed out getting info of {}", mint.mint_url);
+                            None
+                        }
+                    }
+                }
+            }))
+            .buffer_unordered(MAX_CONCURRENT_MINT_INFO)
+            .filter_map(future::ready)
+            .collect()
+            .await;
+
+        rank_mints(&mut discovered);
+
+        Ok(discovered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(author: &str, rating: Option<u8>, created_at: u64) -> MintReview {
+        MintReview {
+            author: author.to_string(),
+            rating,
+            content: String::new(),
+            created_at,
+            attestation: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_rating() {
+        assert_eq!(parse_rating("[5/5] Great mint"), Some(5));
+        assert_eq!(parse_rating("  [3/5]"), Some(3));
+        assert_eq!(parse_rating("[6/5] Too good"), None);
+        assert_eq!(parse_rating("[4/10] Other scale"), None);
+        assert_eq!(parse_rating("Great mint"), None);
+    }
+
+    #[test]
+    fn test_rank_mints() {
+        let mut popular = DiscoveredMint::new(MintUrl::from_str("https://a.mint").unwrap());
+        popular.add_review(review("alice", Some(3), 1));
+        popular.add_review(review("bob", None, 1));
+
+        let mut rated = DiscoveredMint::new(MintUrl::from_str("https://b.mint").unwrap());
+        rated.add_review(review("alice", Some(5), 1));
+
+        let mut low = DiscoveredMint::new(MintUrl::from_str("https://c.mint").unwrap());
+        low.add_review(review("carol", Some(1), 1));
+        // Only the latest review of an author counts
+        low.add_review(review("carol", Some(2), 2));
+        low.add_review(review("carol", Some(5), 0));
+
+        assert_eq!(low.recommendations(), 1);
+        assert_eq!(low.average_rating(), Some(2.0));
+
+        let mut mints = vec![low, rated, popular];
+        rank_mints(&mut mints);
+
+        let urls: Vec<String> = mints.iter().map(|m| m.mint_url.to_string()).collect();
+        assert_eq!(
+            urls,
+            vec!["https://a.mint", "https://b.mint", "https://c.mint"]
+        );
+    }
+
+    #[test]
+    fn test_attestation_aggregation() {
+        let mut mint = DiscoveredMint::new(MintUrl::from_str("https://a.mint").unwrap());
+        assert_eq!(mint.average_fee_ppm(), None);
+
+        let mut alice = review("alice", Some(5), 1);
+        alice.attestation = Some(MintAttestation {
+            uptime_basis_points: Some(10_000),
+            successful_melts: 3,
+            average_fee_ppm: Some(1_000),
+            period: None,
+        });
+        let mut bob = review("bob", None, 1);
+        bob.attestation = Some(MintAttestation {
+            uptime_basis_points: Some(9_000),
+            successful_melts: 1,
+            average_fee_ppm: Some(5_000),
+            period: None,
+        });
+
+        mint.add_review(alice);
+        mint.add_review(bob);
+        mint.add_review(review("carol", Some(4), 1));
+
+        assert_eq!(mint.attested_melts(), 4);
+        assert_eq!(mint.average_uptime(), Some(95.0));
+        assert_eq!(mint.average_fee_ppm(), Some(2_000));
+    }
+}
@@ -0,0 +1,102 @@
+//! Mint identity
+//!
+//! Verification of signed mint info and pinning of the mint identity pubkey, so
+//! a phishing mint serving copied info under another URL or key is detected.
+
+use bitcoin::hashes::{sha256, Hash};
+use tracing::instrument;
+
+use crate::nuts::{MintInfo, PublicKey};
+use crate::{Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const MINT_IDENTITY_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for pinned mint identity pubkeys
+const MINT_IDENTITY_SECONDARY_NAMESPACE: &str = "mint_identity";
+
+impl Wallet {
+    /// Key-value store key for the wallet's mint
+    ///
+    /// Mint urls contain characters the store does not allow, so they are hashed.
+    pub(crate) fn mint_identity_key(&self) -> String {
+        sha256::Hash::hash(self.mint_url.to_string().as_bytes()).to_string()
+    }
+
+    /// Pin the mint identity pubkey
+    ///
+    /// The pubkey should be obtained out of band, e.g. from the operator's nostr
+    /// profile or a DNS record. Once pinned, mint info is only accepted if it is
+    /// signed by this key.
+    #[instrument(skip(self))]
+    pub async fn pin_mint_pubkey(&self, pubkey: PublicKey) -> Result<(), Error> {
+        self.localstore
+            .kv_write(
+                MINT_IDENTITY_PRIMARY_NAMESPACE,
+                MINT_IDENTITY_SECONDARY_NAMESPACE,
+                &self.mint_identity_key(),
+                &pubkey.to_bytes(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove the pinned mint identity pubkey
+    #[instrument(skip(self))]
+    pub async fn unpin_mint_pubkey(&self) -> Result<(), Error> {
+        self.localstore
+            .kv_remove(
+                MINT_IDENTITY_PRIMARY_NAMESPACE,
+                MINT_IDENTITY_SECONDARY_NAMESPACE,
+                &self.mint_identity_key(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Pinned mint identity pubkey, if any
+    #[instrument(skip(self))]
+    pub async fn pinned_mint_pubkey(&self) -> Result<Option<PublicKey>, Error> {
+        let pinned = self
+            .localstore
+            .kv_read(
+                MINT_IDENTITY_PRIMARY_NAMESPACE,
+                MINT_IDENTITY_SECONDARY_NAMESPACE,
+                &self.mint_identity_key(),
+            )
+            .await?;
+
+        match pinned {
+            Some(bytes) => Ok(Some(PublicKey::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Verify the identity of the mint from its info
+    ///
+    /// Only enforced once a pubkey is pinned: the info must then be signed by the
+    /// pinned pubkey. Without a pinned pubkey an invalid signature is only logged.
+    pub(crate) async fn verify_mint_identity(&self, mint_info: &MintInfo) -> Result<(), Error> {
+        let Some(pinned) = self.pinned_mint_pubkey().await? else {
+            if mint_info.signature.is_some() {
+                if let Err(err) = mint_info.verify_signature() {
+                    tracing::warn!("Mint {} info signature is invalid: {}", self.mint_url, err);
+                }
+            }
+
+            return Ok(());
+        };
+
+        if mint_info.pubkey != Some(pinned) || mint_info.verify_signature().is_err() {
+            tracing::warn!(
+                "Mint {} info is not signed by pinned pubkey {}",
+                self.mint_url,
+                pinned
+            );
+            return Err(Error::MintIdentityMismatch(pinned.to_hex()));
+        }
+
+        Ok(())
+    }
+}
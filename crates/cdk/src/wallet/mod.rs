@@ -3,13 +3,13 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use cdk_common::database::{self, WalletDatabase};
 use cdk_common::subscription::Params;
 use getrandom::getrandom;
 use subscription::{ActiveSubscription, SubscriptionManager};
-#[cfg(feature = "auth")]
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 use tracing::instrument;
 use zeroize::Zeroize;
 
@@ -30,30 +30,70 @@ use crate::Amount;
 #[cfg(feature = "auth")]
 use crate::OidcClient;
 
+mod account;
+mod atomic_swap;
 #[cfg(feature = "auth")]
 mod auth;
+mod backup_import;
 mod balance;
 mod builder;
+mod capabilities;
+mod claims_vault;
+mod derivation;
+mod fee_ledger;
+mod history;
 mod issue;
+mod keyset_expiry;
+mod keyset_history;
 mod keysets;
 mod melt;
+#[cfg(feature = "nostr")]
+mod mint_attestation;
 mod mint_connector;
+#[cfg(feature = "nostr")]
+mod mint_discovery;
+mod mint_identity;
 pub mod multi_mint_wallet;
+#[cfg(feature = "nostr")]
+mod nostr_transport;
+mod offline;
 pub mod payment_request;
+mod payment_stream;
+mod prepared_spend;
+mod proof_import;
 mod proofs;
+mod quote_retention;
 mod receive;
+mod receive_keys;
+mod savings;
 mod send;
+mod spending_limits;
 #[cfg(not(target_arch = "wasm32"))]
 mod streams;
 pub mod subscription;
 mod swap;
+#[cfg(feature = "nostr")]
+mod token_delivery;
+mod token_verification;
 mod transactions;
 pub mod util;
 
+pub use account::{account_derivation_path, account_seed, DEFAULT_ACCOUNT};
+pub use atomic_swap::{
+    AtomicSwap, SwapLeg, SwapMessage, SwapOffer, SwapRole, SwapState, CLAIM_MARGIN,
+    MIN_SWAP_TIMEOUT,
+};
 #[cfg(feature = "auth")]
 pub use auth::{AuthMintConnector, AuthWallet};
+pub use backup_import::WalletBackup;
 pub use builder::WalletBuilder;
+pub use capabilities::MintCapabilities;
 pub use cdk_common::wallet as types;
+pub use derivation::{DerivationReport, KeysetDerivation, RestoreOptions, RestoreScan};
+pub use history::INFERRED_METADATA_KEY;
+pub use melt::{BatchMelt, MeltProgress, MeltProgressState, MELT_BATCH_CONCURRENCY};
+#[cfg(feature = "nostr")]
+pub use mint_attestation::MintAttestation;
 #[cfg(feature = "auth")]
 pub use mint_connector::http_client::AuthHttpClient as BaseAuthHttpClient;
 pub use mint_connector::http_client::HttpClient as BaseHttpClient;
@@ -61,9 +101,29 @@ pub use mint_connector::transport::Transport as HttpTransport;
 #[cfg(feature = "auth")]
 pub use mint_connector::AuthHttpClient;
 pub use mint_connector::{HttpClient, MintConnector};
+#[cfg(feature = "nostr")]
+pub use mint_discovery::{DiscoveredMint, MintReview};
 pub use multi_mint_wallet::{MultiMintReceiveOptions, MultiMintSendOptions, MultiMintWallet};
+pub use offline::OfflineVerification;
+pub use payment_stream::{PaymentStream, PaymentStreamDestination, PaymentStreamState};
+pub use prepared_spend::{PreparedSpend, SigningRequest, SpendSignature};
+pub use proof_import::ProofImport;
+pub use quote_retention::{PrunedQuotes, QuoteStats};
 pub use receive::ReceiveOptions;
+pub use receive_keys::receive_key_derivation_path;
+pub use savings::LockedSavings;
 pub use send::{PreparedSend, SendMemo, SendOptions};
+pub use spending_limits::{
+    ConfirmationHandler, ExceededLimit, SpendConfirmation, SpendKind, SpendingLimits,
+};
+#[cfg(feature = "nostr")]
+pub use token_delivery::{
+    send_token_nostr, BlobStore, BlossomServer, TokenBlobReference, TokenDelivery,
+    MAX_INLINE_TOKEN_LEN, MAX_TOKEN_BLOB_LEN,
+};
+pub use token_verification::{
+    verify_token, verify_token_with_client, TokenVerdict, TokenVerification,
+};
 pub use types::{MeltQuote, MintQuote, SendKind};
 
 use crate::nuts::nut00::ProofsMethods;
@@ -88,6 +148,11 @@ pub struct Wallet {
     seed: [u8; 64],
     client: Arc<dyn MintConnector + Send + Sync>,
     subscription: SubscriptionManager,
+    restore_scans: Arc<RwLock<HashMap<Id, RestoreScan>>>,
+    confirmation_handler: Arc<RwLock<Option<Arc<dyn ConfirmationHandler>>>>,
+    melt_progress: broadcast::Sender<MeltProgress>,
+    archived_proof_retention: Option<Duration>,
+    keyset_expiry_migration: Option<Duration>,
 }
 
 const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
@@ -120,26 +185,18 @@ impl From<WalletSubscription> for Params {
             .collect::<String>();
 
         match val {
-            WalletSubscription::ProofState(filters) => Params {
-                filters,
-                kind: Kind::ProofState,
-                id: id.into(),
-            },
-            WalletSubscription::Bolt11MintQuoteState(filters) => Params {
-                filters,
-                kind: Kind::Bolt11MintQuote,
-                id: id.into(),
-            },
-            WalletSubscription::Bolt11MeltQuoteState(filters) => Params {
-                filters,
-                kind: Kind::Bolt11MeltQuote,
-                id: id.into(),
-            },
-            WalletSubscription::Bolt12MintQuoteState(filters) => Params {
-                filters,
-                kind: Kind::Bolt12MintQuote,
-                id: id.into(),
-            },
+            WalletSubscription::ProofState(filters) => {
+                Params::new(Kind::ProofState, filters, id.into())
+            }
+            WalletSubscription::Bolt11MintQuoteState(filters) => {
+                Params::new(Kind::Bolt11MintQuote, filters, id.into())
+            }
+            WalletSubscription::Bolt11MeltQuoteState(filters) => {
+                Params::new(Kind::Bolt11MeltQuote, filters, id.into())
+            }
+            WalletSubscription::Bolt12MintQuoteState(filters) => {
+                Params::new(Kind::Bolt12MintQuote, filters, id.into())
+            }
         }
     }
 }
@@ -192,7 +249,7 @@ impl Wallet {
     /// Subscribe to events
     pub async fn subscribe<T: Into<Params>>(&self, query: T) -> ActiveSubscription {
         self.subscription
-            .subscribe(self.mint_url.clone(), query.into(), Arc::new(self.clone()))
+            .subscribe(self.mint_url.clone(), query.into())
             .await
     }
 
@@ -259,6 +316,8 @@ impl Wallet {
     pub async fn fetch_mint_info(&self) -> Result<Option<MintInfo>, Error> {
         match self.client.get_mint_info().await {
             Ok(mint_info) => {
+                self.verify_mint_identity(&mint_info).await?;
+
                 // If mint provides time make sure it is accurate
                 if let Some(mint_unix_time) = mint_info.time {
                     let current_unix_time = unix_time();
@@ -379,9 +438,20 @@ impl Wallet {
         Ok(SplitTarget::Values(values))
     }
 
-    /// Restore
+    /// Restore proofs from the wallet seed with [`RestoreOptions::default`]
     #[instrument(skip(self))]
     pub async fn restore(&self) -> Result<Amount, Error> {
+        self.restore_with_options(RestoreOptions::default()).await
+    }
+
+    /// Restore proofs from the wallet seed
+    ///
+    /// Counters are asked to the mint in batches until `gap_limit` batches in a row
+    /// have no signature. Afterwards the keyset counter is moved past the highest
+    /// signed counter, so new secrets never collide with restored ones, and that
+    /// counter is persisted for later incremental restores.
+    #[instrument(skip(self))]
+    pub async fn restore_with_options(&self, options: RestoreOptions) -> Result<Amount, Error> {
         // Check that mint is in store of mints
         if self
             .localstore
@@ -392,27 +462,52 @@ impl Wallet {
             self.fetch_mint_info().await?;
         }
 
+        self.ensure_capability("restore (NUT-09)", |c| c.supports_restore)
+            .await?;
+
+        // Keys of every keyset in one request, mints without the keyset history
+        // have their keysets fetched one by one
+        let history = self.load_keyset_history().await?;
+
         let keysets = self.load_mint_keysets().await?;
+        let batch_size = options.batch_size.max(1);
 
         let mut restored_value = Amount::ZERO;
 
         for keyset in keysets {
-            let keys = self.load_keyset_keys(keyset.id).await?;
+            let keys = match history
+                .as_ref()
+                .and_then(|history| history.keysets.iter().find(|entry| entry.id == keyset.id))
+            {
+                Some(entry) => entry.keys.clone(),
+                None => self.load_keyset_keys(keyset.id).await?,
+            };
+
+            let mut start_counter = match options.incremental {
+                true => self.restore_high_water(&keyset.id).await?,
+                false => 0,
+            };
+            let mut scan = RestoreScan::starting_at(start_counter);
             let mut empty_batch = 0;
-            let mut start_counter = 0;
+            let mut gap_limit = options.gap_limit;
+
+            while empty_batch < gap_limit {
+                let end_counter = start_counter
+                    .checked_add(batch_size - 1)
+                    .ok_or(Error::CounterOverflow)?;
+                let next_counter = end_counter.checked_add(1).ok_or(Error::CounterOverflow)?;
 
-            while empty_batch.lt(&3) {
                 let premint_secrets = PreMintSecrets::restore_batch(
                     keyset.id,
                     &self.seed,
                     start_counter,
-                    start_counter + 100,
+                    end_counter,
                 )?;
 
                 tracing::debug!(
                     "Attempting to restore counter {}-{} for mint {} keyset {}",
                     start_counter,
-                    start_counter + 100,
+                    end_counter,
                     self.mint_url,
                     keyset.id
                 );
@@ -423,16 +518,23 @@ impl Wallet {
 
                 let response = self.client.post_restore(restore_request).await?;
 
+                scan.scanned_until = next_counter;
+
                 if response.signatures.is_empty() {
                     empty_batch += 1;
-                    start_counter += 100;
+                    start_counter = next_counter;
                     continue;
                 }
 
                 let premint_secrets: Vec<_> = premint_secrets
                     .secrets
                     .iter()
-                    .filter(|p| response.outputs.contains(&p.blinded_message))
+                    .zip(start_counter..)
+                    .filter(|(p, _)| response.outputs.contains(&p.blinded_message))
+                    .map(|(p, counter)| {
+                        scan.record_used(counter);
+                        p
+                    })
                     .collect();
 
                 // the response outputs and premint secrets should be the same after filtering
@@ -448,10 +550,6 @@ impl Wallet {
 
                 tracing::debug!("Restored {} proofs", proofs.len());
 
-                self.localstore
-                    .increment_keyset_counter(&keyset.id, proofs.len() as u32)
-                    .await?;
-
                 let states = self.check_proofs_spent(proofs.clone()).await?;
 
                 let unspent_proofs: Vec<Proof> = proofs
@@ -480,9 +578,40 @@ impl Wallet {
                     .update_proofs(unspent_proofs, vec![])
                     .await?;
 
+                // Secrets signed at the end of a batch hint at a busy range, scan one more
+                // empty batch than usual before stopping
+                let near_end = scan.used_until > next_counter - batch_size.div_ceil(10);
+                gap_limit = match options.adaptive && near_end {
+                    true => options.gap_limit + 1,
+                    false => options.gap_limit,
+                };
+
                 empty_batch = 0;
-                start_counter += 100;
+                start_counter = next_counter;
             }
+
+            if !scan.gaps.is_empty() {
+                tracing::warn!(
+                    "Restore of keyset {} found {} gaps in counters below {}",
+                    keyset.id,
+                    scan.gaps.len(),
+                    scan.used_until
+                );
+            }
+
+            // Move the counter past every signed secret, a restore into a wallet in use
+            // must neither lower it nor count restored secrets twice
+            let counter = self.localstore.get_keyset_counter(&keyset.id).await?;
+            if counter < scan.used_until {
+                self.localstore
+                    .increment_keyset_counter(&keyset.id, scan.used_until - counter)
+                    .await?;
+            }
+
+            self.set_restore_high_water(&keyset.id, scan.used_until)
+                .await?;
+
+            self.restore_scans.write().await.insert(keyset.id, scan);
         }
         Ok(restored_value)
     }
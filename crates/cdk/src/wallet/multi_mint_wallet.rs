@@ -4,21 +4,27 @@
 //! pairs
 
 use std::collections::BTreeMap;
+use std::ops::RangeBounds;
 use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::Result;
 use cdk_common::database;
 use cdk_common::database::WalletDatabase;
-use cdk_common::wallet::{Transaction, TransactionDirection};
+use cdk_common::wallet::{FeeEntry, Transaction, TransactionDirection};
 use tokio::sync::RwLock;
 use tracing::instrument;
 use zeroize::Zeroize;
 
 use super::builder::WalletBuilder;
+use super::fee_ledger::fee_entries;
+use super::quote_retention::{
+    prune_quotes, quote_stats, read_quote_retention, write_quote_retention,
+};
 use super::receive::ReceiveOptions;
 use super::send::{PreparedSend, SendOptions};
-use super::Error;
+use super::spending_limits::{read_spending_limits, write_spending_limits};
+use super::{ConfirmationHandler, Error, PrunedQuotes, QuoteStats, SpendKind, SpendingLimits};
 use crate::amount::SplitTarget;
 use crate::mint_url::MintUrl;
 use crate::nuts::nut00::ProofsMethods;
@@ -26,7 +32,7 @@ use crate::nuts::nut23::QuoteState;
 use crate::nuts::{CurrencyUnit, MeltOptions, Proof, Proofs, SpendingConditions, Token};
 use crate::types::Melted;
 use crate::wallet::types::MintQuote;
-use crate::{Amount, Wallet};
+use crate::{ensure_cdk, Amount, Wallet};
 
 // Transfer timeout constants
 /// Total timeout for waiting for Lightning payment confirmation during transfers
@@ -114,6 +120,8 @@ pub struct MultiMintWallet {
     wallets: Arc<RwLock<BTreeMap<MintUrl, Wallet>>>,
     /// Proxy configuration for HTTP clients (optional)
     proxy_config: Option<url::Url>,
+    /// Handler approving spends above the spending limits, shared by the wallets
+    confirmation_handler: Arc<RwLock<Option<Arc<dyn ConfirmationHandler>>>>,
 }
 
 impl MultiMintWallet {
@@ -129,6 +137,7 @@ impl MultiMintWallet {
             unit,
             wallets: Arc::new(RwLock::new(BTreeMap::new())),
             proxy_config: None,
+            confirmation_handler: Arc::new(RwLock::new(None)),
         };
 
         // Automatically load wallets from database for this currency unit
@@ -153,6 +162,7 @@ impl MultiMintWallet {
             unit,
             wallets: Arc::new(RwLock::new(BTreeMap::new())),
             proxy_config: Some(proxy_url),
+            confirmation_handler: Arc::new(RwLock::new(None)),
         };
 
         // Automatically load wallets from database for this currency unit
@@ -207,6 +217,9 @@ impl MultiMintWallet {
 
         wallet.fetch_mint_info().await?;
         wallet.refresh_keysets().await?;
+        wallet
+            .set_confirmation_handler(self.confirmation_handler.read().await.clone())
+            .await;
 
         let mut wallets = self.wallets.write().await;
         wallets.insert(mint_url, wallet);
@@ -214,6 +227,23 @@ impl MultiMintWallet {
         Ok(())
     }
 
+    /// Adds a wallet built elsewhere, e.g. with its own [`MintConnector`](super::MintConnector)
+    ///
+    /// The wallet must use the unit of this [MultiMintWallet] and should share its storage.
+    #[instrument(skip_all)]
+    pub async fn add_wallet(&self, wallet: Wallet) -> Result<(), Error> {
+        ensure_cdk!(wallet.unit == self.unit, Error::UnsupportedUnit);
+
+        wallet
+            .set_confirmation_handler(self.confirmation_handler.read().await.clone())
+            .await;
+
+        let mut wallets = self.wallets.write().await;
+        wallets.insert(wallet.mint_url.clone(), wallet);
+
+        Ok(())
+    }
+
     /// Remove mint from MultiMintWallet
     #[instrument(skip(self))]
     pub async fn remove_mint(&self, mint_url: &MintUrl) {
@@ -279,6 +309,66 @@ impl MultiMintWallet {
         &self.unit
     }
 
+    /// Spending limits of the unit of this wallet
+    #[instrument(skip(self))]
+    pub async fn spending_limits(&self) -> Result<SpendingLimits, Error> {
+        read_spending_limits(&self.localstore, &self.unit).await
+    }
+
+    /// Set the spending limits of the unit of this wallet
+    ///
+    /// The limits apply to the wallets of every mint.
+    #[instrument(skip(self))]
+    pub async fn set_spending_limits(&self, limits: SpendingLimits) -> Result<(), Error> {
+        write_spending_limits(&self.localstore, &self.unit, limits).await
+    }
+
+    /// Days finalized quotes are kept after they expired, `None` to keep them forever
+    #[instrument(skip(self))]
+    pub async fn quote_retention(&self) -> Result<Option<u64>, Error> {
+        read_quote_retention(&self.localstore).await
+    }
+
+    /// Set the days finalized quotes are kept after they expired
+    #[instrument(skip(self))]
+    pub async fn set_quote_retention(&self, days: Option<u64>) -> Result<(), Error> {
+        write_quote_retention(&self.localstore, days).await
+    }
+
+    /// Count the quotes stored in the wallet database
+    #[instrument(skip(self))]
+    pub async fn quote_stats(&self) -> Result<QuoteStats, Error> {
+        quote_stats(&self.localstore).await
+    }
+
+    /// Delete the finalized quotes older than the retention
+    #[instrument(skip(self))]
+    pub async fn prune_quotes(&self) -> Result<PrunedQuotes, Error> {
+        prune_quotes(&self.localstore).await
+    }
+
+    /// Fees paid to every mint in the unit of the wallet within a range of unix timestamps
+    #[instrument(skip_all)]
+    pub async fn fee_entries(&self, range: impl RangeBounds<u64>) -> Result<Vec<FeeEntry>, Error> {
+        fee_entries(&self.localstore, None, &self.unit, &range).await
+    }
+
+    /// Set the handler approving spends above the limits for the wallets of every mint
+    pub async fn set_confirmation_handler(&self, handler: Option<Arc<dyn ConfirmationHandler>>) {
+        *self.confirmation_handler.write().await = handler.clone();
+
+        for wallet in self.wallets.read().await.values() {
+            wallet.set_confirmation_handler(handler.clone()).await;
+        }
+    }
+
+    /// Storage backend shared by the wallets
+    pub(crate) fn localstore(
+        &self,
+    ) -> &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync> {
+        &self.localstore
+    }
+
     /// Get wallet balances for all mints
     #[instrument(skip(self))]
     pub async fn get_balances(&self) -> Result<BTreeMap<MintUrl, Amount>, Error> {
@@ -332,6 +422,18 @@ impl MultiMintWallet {
         Ok(total)
     }
 
+    /// Unlock the savings of all wallets whose unlock time has passed
+    ///
+    /// Returns the amount unlocked into the balance.
+    #[instrument(skip(self))]
+    pub async fn unlock_savings(&self) -> Result<Amount, Error> {
+        let mut unlocked = Amount::ZERO;
+        for (_, wallet) in self.wallets.read().await.iter() {
+            unlocked += wallet.unlock_savings().await?;
+        }
+        Ok(unlocked)
+    }
+
     /// Prepare to send tokens from a specific mint with optional transfer from other mints
     ///
     /// This method ensures that sends always happen from only one mint. If the specified
@@ -1222,6 +1324,8 @@ impl MultiMintWallet {
     }
 
     /// Swap proofs with automatic wallet selection
+    ///
+    /// Swaps to spending conditions are checked against the spending limits.
     #[instrument(skip(self))]
     pub async fn swap(
         &self,
@@ -1237,9 +1341,27 @@ impl MultiMintWallet {
                 // Try to swap with this wallet
                 let proofs = wallet.get_unspent_proofs().await?;
                 if !proofs.is_empty() {
-                    return wallet
+                    let spend_reservation = match conditions {
+                        Some(_) => Some(
+                            wallet
+                                .check_spending_limits(
+                                    SpendKind::Lock,
+                                    amount.unwrap_or(proofs.total_amount()?),
+                                )
+                                .await?,
+                        ),
+                        None => None,
+                    };
+
+                    let swapped = wallet
                         .swap(amount, SplitTarget::default(), proofs, conditions, false)
-                        .await;
+                        .await?;
+
+                    if let Some(spend_reservation) = spend_reservation {
+                        spend_reservation.keep();
+                    }
+
+                    return Ok(swapped);
                 }
             }
         }
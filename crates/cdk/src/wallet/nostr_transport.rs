@@ -0,0 +1,38 @@
+//! Nostr transport
+//!
+//! Wallet features reach nostr relays through the [`RelayPool`] shared by the
+//! process, so features using the same relays share one connection. Errors of
+//! `cdk-nostr` are converted to wallet errors here.
+
+use cdk_nostr::RelayPool;
+pub(crate) use cdk_nostr::{EventKind, RelayUse, RELAY_TIMEOUT};
+use nostr_sdk::{Client as NostrClient, Keys};
+
+use crate::nuts::{PublicKey, SecretKey};
+use crate::Error;
+
+/// Wallet error of a nostr error
+pub(crate) fn nostr_error(err: cdk_nostr::Error) -> Error {
+    Error::Custom(err.to_string())
+}
+
+/// Nostr keys of a wallet secret key
+pub(crate) fn nostr_keys(secret_key: &SecretKey) -> Result<Keys, Error> {
+    cdk_nostr::nostr_keys(secret_key).map_err(nostr_error)
+}
+
+/// Nostr pubkey of a wallet pubkey
+pub(crate) fn nostr_public_key(pubkey: &PublicKey) -> Result<nostr_sdk::PublicKey, Error> {
+    cdk_nostr::nostr_public_key(pubkey).map_err(nostr_error)
+}
+
+/// Client of the shared pool connected to `relays`
+pub(crate) async fn relay_client(
+    relays: &[String],
+    relay_use: RelayUse,
+) -> Result<NostrClient, Error> {
+    RelayPool::global()
+        .client(relays, relay_use)
+        .await
+        .map_err(nostr_error)
+}
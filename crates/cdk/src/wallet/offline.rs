@@ -0,0 +1,164 @@
+//! Offline token verification
+//!
+//! Verification of the DLEQ proofs of a received token without contacting the
+//! mint, using the mint keys cached by the wallet or, with the `keyset-hints`
+//! feature, the keyset hints embedded in V4 tokens by the sender.
+//!
+//! Keys embedded in a token are supplied by the sender and are not authenticated
+//! until they are compared with the mint keys, so a token verified against them
+//! should only be trusted once [`Wallet::confirm_offline_token`] succeeds. Offline
+//! verification cannot detect proofs that were already spent.
+
+use cdk_common::nut02::ShortKeysetId;
+use tracing::instrument;
+
+use super::ReceiveOptions;
+use crate::nuts::nut00::token::TokenV4Token;
+use crate::nuts::{Keys, Token};
+use crate::{ensure_cdk, Amount, Error, Wallet};
+
+/// Keys a token was verified against offline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfflineVerification {
+    /// Mint keys cached by the wallet
+    KnownKeys,
+    /// Keys embedded in the token by the sender, not yet confirmed with the mint
+    EmbeddedKeys,
+}
+
+impl Wallet {
+    /// Verify the DLEQ proofs of a token without contacting the mint
+    ///
+    /// Keys cached by the wallet take precedence over keys embedded in the
+    /// token, and a token whose embedded keys disagree with the cached ones is
+    /// rejected. Every proof must carry a DLEQ proof.
+    #[instrument(skip_all)]
+    pub async fn verify_token_offline(&self, token: &Token) -> Result<OfflineVerification, Error> {
+        ensure_cdk!(self.mint_url == token.mint_url()?, Error::IncorrectMint);
+        ensure_cdk!(
+            token.unit().is_none_or(|unit| unit == self.unit),
+            Error::UnsupportedUnit
+        );
+
+        let keysets = self
+            .localstore
+            .get_mint_keysets(self.mint_url.clone())
+            .await?
+            .unwrap_or_default();
+
+        let token = match token {
+            Token::TokenV3(token) => {
+                for proof in token.proofs(&keysets)? {
+                    let keys = self
+                        .localstore
+                        .get_keys(&proof.keyset_id)
+                        .await?
+                        .ok_or(Error::OfflineKeysUnavailable(proof.keyset_id.to_string()))?;
+                    let key = keys.amount_key(proof.amount).ok_or(Error::AmountKey)?;
+                    proof.verify_dleq(key)?;
+                }
+
+                return Ok(OfflineVerification::KnownKeys);
+            }
+            Token::TokenV4(token) => token,
+        };
+
+        let mut verification = OfflineVerification::KnownKeys;
+
+        for token_keyset in &token.token {
+            let cached_keys = match keysets
+                .iter()
+                .find(|k| ShortKeysetId::from(k.id) == token_keyset.keyset_id)
+            {
+                Some(keyset) => self.localstore.get_keys(&keyset.id).await?,
+                None => None,
+            };
+
+            let keys = match (cached_keys, keyset_hints(token_keyset)) {
+                (Some(cached_keys), Some(hints)) => {
+                    ensure_hints_match(&cached_keys, hints, &token_keyset.keyset_id)?;
+                    cached_keys
+                }
+                (Some(cached_keys), None) => cached_keys,
+                (None, Some(hints)) => {
+                    verification = OfflineVerification::EmbeddedKeys;
+                    hints.clone()
+                }
+                (None, None) => {
+                    return Err(Error::OfflineKeysUnavailable(
+                        token_keyset.keyset_id.to_string(),
+                    ))
+                }
+            };
+
+            for proof in &token_keyset.proofs {
+                let key = keys.amount_key(proof.amount).ok_or(Error::AmountKey)?;
+                proof.verify_dleq(key)?;
+            }
+        }
+
+        Ok(verification)
+    }
+
+    /// Confirm a token verified offline with the mint and receive it
+    ///
+    /// Keys embedded in the token are compared with the keys served by the mint
+    /// before the token is swapped, so a sender forging hints is detected even if
+    /// the swap would fail anyway.
+    #[instrument(skip_all)]
+    pub async fn confirm_offline_token(
+        &self,
+        token: &Token,
+        opts: ReceiveOptions,
+    ) -> Result<Amount, Error> {
+        ensure_cdk!(self.mint_url == token.mint_url()?, Error::IncorrectMint);
+
+        if let Token::TokenV4(token_v4) = token {
+            let keysets = self.refresh_keysets().await?;
+
+            for token_keyset in &token_v4.token {
+                let hints = match keyset_hints(token_keyset) {
+                    Some(hints) => hints,
+                    None => continue,
+                };
+
+                let keyset = keysets
+                    .iter()
+                    .find(|k| ShortKeysetId::from(k.id) == token_keyset.keyset_id)
+                    .ok_or(Error::UnknownKeySet)?;
+
+                let keys = self.load_keyset_keys(keyset.id).await?;
+
+                ensure_hints_match(&keys, hints, &token_keyset.keyset_id)?;
+            }
+        }
+
+        self.receive(&token.to_string(), opts).await
+    }
+}
+
+/// Keys embedded in a V4 token by the sender
+#[cfg(feature = "keyset-hints")]
+fn keyset_hints(token_keyset: &TokenV4Token) -> Option<&Keys> {
+    token_keyset.keys.as_ref()
+}
+
+/// Keys embedded in a V4 token by the sender, never read without the `keyset-hints` feature
+#[cfg(not(feature = "keyset-hints"))]
+fn keyset_hints(_token_keyset: &TokenV4Token) -> Option<&Keys> {
+    None
+}
+
+/// Check that every key embedded in a token matches the mint key for its amount
+fn ensure_hints_match(keys: &Keys, hints: &Keys, keyset_id: &ShortKeysetId) -> Result<(), Error> {
+    match hints
+        .iter()
+        .all(|(amount, key)| keys.amount_key(*amount) == Some(*key))
+    {
+        true => Ok(()),
+        false => {
+            tracing::warn!("Keyset hints for {} do not match mint keys", keyset_id);
+            Err(Error::KeysetHintMismatch(keyset_id.to_string()))
+        }
+    }
+}
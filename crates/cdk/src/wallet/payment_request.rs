@@ -14,7 +14,10 @@ use nostr_sdk::nips::nip19::Nip19Profile;
 #[cfg(feature = "nostr")]
 use nostr_sdk::prelude::*;
 #[cfg(feature = "nostr")]
-use nostr_sdk::{Client as NostrClient, EventBuilder, FromBech32, Keys, ToBech32};
+use nostr_sdk::{EventBuilder, FromBech32, Keys, ToBech32};
+
+#[cfg(feature = "nostr")]
+use super::nostr_transport::{nostr_error, relay_client, RelayUse};
 use reqwest::Client;
 
 use crate::error::Error;
@@ -90,7 +93,6 @@ impl Wallet {
                     #[cfg(feature = "nostr")]
                     {
                         let keys = Keys::generate();
-                        let client = NostrClient::new(keys);
                         let nprofile = Nip19Profile::from_bech32(&transport.target)
                             .map_err(|e| Error::Custom(format!("Invalid nprofile: {e}")))?;
 
@@ -99,20 +101,21 @@ impl Wallet {
                             serde_json::to_string(&payload)
                                 .map_err(|e| Error::Custom(format!("Serialize payload: {e}")))?,
                         )
-                        .build(nprofile.public_key);
-                        let relays = nprofile.relays;
-
-                        for relay in relays.iter() {
-                            client
-                                .add_write_relay(relay)
-                                .await
-                                .map_err(|e| Error::Custom(format!("Add relay {relay}: {e}")))?;
-                        }
+                        .build(keys.public_key());
+                        let relays = nprofile
+                            .relays
+                            .iter()
+                            .map(|relay| relay.to_string())
+                            .collect::<Vec<_>>();
 
-                        client.connect().await;
+                        let client = relay_client(&relays, RelayUse::Write).await?;
+
+                        let event = cdk_nostr::gift_wrap(&keys, &nprofile.public_key, rumor)
+                            .await
+                            .map_err(nostr_error)?;
 
                         let gift_wrap = client
-                            .gift_wrap_to(relays, &nprofile.public_key, rumor, None)
+                            .send_event(&event)
                             .await
                             .map_err(|e| Error::Custom(format!("Publish Nostr event: {e}")))?;
 
@@ -567,29 +570,30 @@ impl MultiMintWallet {
             pubkey,
         } = info;
 
-        let client = nostr_sdk::Client::new(keys);
-
-        for r in &relays {
-            client
-                .add_read_relay(r.clone())
-                .await
-                .map_err(|e| crate::error::Error::Custom(format!("Add relay {r}: {e}")))?;
-        }
-
-        client.connect().await;
+        let client = relay_client(&relays, RelayUse::Read).await?;
 
-        // Subscribe to events addressed to `pubkey`
-        let filter = Filter::new().pubkey(pubkey);
-        client
+        // Subscribe to gift wraps addressed to `pubkey`
+        let filter = Filter::new().pubkey(pubkey).kind(Kind::GiftWrap);
+        let mut notifications = client.notifications();
+        let subscription_id = client
             .subscribe(filter, None)
             .await
-            .map_err(|e| crate::error::Error::Custom(format!("Subscribe: {e}")))?;
+            .map_err(|e| crate::error::Error::Custom(format!("Subscribe: {e}")))?
+            .val;
 
         // Await notifications until we successfully parse a payment payload and receive it
-        let mut notifications = client.notifications();
         while let Ok(notification) = notifications.recv().await {
-            if let RelayPoolNotification::Event { event, .. } = notification {
-                match client.unwrap_gift_wrap(&event).await {
+            if let RelayPoolNotification::Event {
+                subscription_id: event_subscription_id,
+                event,
+                ..
+            } = notification
+            {
+                // The client is shared, events of other subscriptions are skipped
+                if event_subscription_id != subscription_id {
+                    continue;
+                }
+                match cdk_nostr::unwrap_gift_wrap(&keys, &event).await {
                     Ok(unwrapped) => {
                         let rumor = unwrapped.rumor;
                         match serde_json::from_str::<PaymentRequestPayload>(&rumor.content) {
@@ -600,6 +604,7 @@ impl MultiMintWallet {
                                     payload.memo,
                                     payload.unit,
                                 );
+                                client.unsubscribe(&subscription_id).await;
 
                                 let amount = self
                                     .receive(&token.to_string(), MultiMintReceiveOptions::default())
@@ -620,6 +625,7 @@ impl MultiMintWallet {
                 }
             }
         }
+        client.unsubscribe(&subscription_id).await;
 
         Ok(Amount::ZERO)
     }
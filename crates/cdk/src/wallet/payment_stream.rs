@@ -0,0 +1,504 @@
+//! Payment streams
+//!
+//! Streaming payments that melt a fixed amount to a destination on a fixed
+//! interval, until the stream is stopped or its budget is exhausted. Streams are
+//! kept in the wallet database key-value store, so a stream resumes where it
+//! left off after a restart. Intervals missed while the stream was not running
+//! are not caught up.
+//!
+//! Payments go to BOLT12 offers, directly or resolved from a BIP353 address, or
+//! as keysend payments to a node. Only one runner pays a stream at a time, and
+//! every payment re-reads the stored stream before saving it, so a stream
+//! stopped while a payment is in flight stays stopped.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+
+use crate::amount::to_unit;
+use crate::mint_url::MintUrl;
+use crate::nuts::{CurrencyUnit, MeltOptions, PublicKey};
+use crate::{ensure_cdk, Amount, Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const PAYMENT_STREAM_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for payment streams
+const PAYMENT_STREAM_SECONDARY_NAMESPACE: &str = "payment_stream";
+
+/// Destination of a payment stream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentStreamDestination {
+    /// BOLT12 offer
+    Bolt12Offer(String),
+    /// BIP353 human readable address resolving to a BOLT12 offer
+    Bip353Address(String),
+    /// Node paid with keysend payments
+    Keysend(PublicKey),
+}
+
+impl fmt::Display for PaymentStreamDestination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bolt12Offer(offer) => write!(f, "{offer}"),
+            Self::Bip353Address(address) => write!(f, "{address}"),
+            Self::Keysend(pubkey) => write!(f, "{pubkey}"),
+        }
+    }
+}
+
+impl FromStr for PaymentStreamDestination {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let destination = s.trim();
+
+        match destination {
+            d if d.to_lowercase().starts_with("lno") => Ok(Self::Bolt12Offer(d.to_string())),
+            d if d.contains('@') => Ok(Self::Bip353Address(d.trim_start_matches('₿').to_string())),
+            d if d.len() == 66 => PublicKey::from_hex(d).map(Self::Keysend).map_err(|_| {
+                Error::PaymentStream("Keysend destination is not a valid node id".to_string())
+            }),
+            _ => Err(Error::PaymentStream(
+                "Destination must be a BOLT12 offer, a BIP353 address or a node id".to_string(),
+            )),
+        }
+    }
+}
+
+/// State of a payment stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentStreamState {
+    /// Paying on every interval
+    Active,
+    /// Stopped by the user
+    Stopped,
+    /// Budget does not cover another payment
+    Exhausted,
+}
+
+/// Payment stream stored in the wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentStream {
+    /// Stream id
+    pub id: String,
+    /// Mint the stream melts from
+    pub mint_url: MintUrl,
+    /// Destination of the payments
+    pub destination: PaymentStreamDestination,
+    /// Amount paid on every interval in the wallet unit
+    pub amount: Amount,
+    /// Interval between payments in seconds
+    pub interval: u64,
+    /// Maximum total spent by the stream, including fees
+    pub budget: Amount,
+    /// Total spent by the stream, including fees
+    pub spent: Amount,
+    /// Number of payments made
+    pub payments: u64,
+    /// Unix time of the last payment
+    pub last_payment: Option<u64>,
+    /// Error of the last failed payment, cleared by the next successful one
+    pub last_error: Option<String>,
+    /// Stream state
+    pub state: PaymentStreamState,
+    /// Unix time the stream was created
+    pub created_time: u64,
+}
+
+impl PaymentStream {
+    /// Unix time the next payment is due
+    pub fn next_payment(&self) -> u64 {
+        match self.last_payment {
+            Some(last_payment) => last_payment + self.interval,
+            None => self.created_time,
+        }
+    }
+
+    /// Budget left for payments and fees
+    pub fn remaining_budget(&self) -> Amount {
+        self.budget.checked_sub(self.spent).unwrap_or(Amount::ZERO)
+    }
+}
+
+/// Ids of the payment streams being paid in this process
+static RUNNING_STREAMS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+fn running_streams() -> &'static Mutex<HashSet<String>> {
+    RUNNING_STREAMS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Claim on paying a payment stream, released when dropped
+struct PaymentStreamRunner {
+    id: String,
+}
+
+impl PaymentStreamRunner {
+    /// Claim the payment stream `id`, failing if it is already being paid
+    fn claim(id: &str) -> Result<Self, Error> {
+        let mut running = running_streams().lock().unwrap_or_else(|e| e.into_inner());
+
+        ensure_cdk!(
+            running.insert(id.to_string()),
+            Error::PaymentStream("Payment stream is already running".to_string())
+        );
+
+        Ok(Self { id: id.to_string() })
+    }
+}
+
+impl Drop for PaymentStreamRunner {
+    fn drop(&mut self) {
+        running_streams()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+impl Wallet {
+    /// Store a payment stream
+    async fn save_payment_stream(&self, stream: &PaymentStream) -> Result<(), Error> {
+        self.localstore
+            .kv_write(
+                PAYMENT_STREAM_PRIMARY_NAMESPACE,
+                PAYMENT_STREAM_SECONDARY_NAMESPACE,
+                &stream.id,
+                &serde_json::to_vec(stream)?,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Apply `update` to the stored payment stream and store it again
+    ///
+    /// The stream is read again right before saving, so changes made while a
+    /// payment was in flight, like stopping the stream, are kept.
+    async fn update_payment_stream<F>(&self, id: &str, update: F) -> Result<PaymentStream, Error>
+    where
+        F: FnOnce(&mut PaymentStream),
+    {
+        let mut stream = self
+            .get_payment_stream(id)
+            .await?
+            .ok_or(Error::PaymentStream("Unknown payment stream".to_string()))?;
+
+        update(&mut stream);
+        self.save_payment_stream(&stream).await?;
+
+        Ok(stream)
+    }
+
+    /// Create a payment stream paying `amount` to `destination` every `interval` seconds
+    ///
+    /// The first payment is due immediately. Run the stream with
+    /// [`Wallet::run_payment_stream`] or pay due intervals with
+    /// [`Wallet::pay_payment_stream`].
+    #[instrument(skip(self))]
+    pub async fn create_payment_stream(
+        &self,
+        destination: PaymentStreamDestination,
+        amount: Amount,
+        interval: u64,
+        budget: Amount,
+    ) -> Result<PaymentStream, Error> {
+        ensure_cdk!(amount > Amount::ZERO, Error::AmountUndefined);
+        ensure_cdk!(
+            interval > 0,
+            Error::PaymentStream("Interval must be at least one second".to_string())
+        );
+        ensure_cdk!(
+            budget >= amount,
+            Error::PaymentStream("Budget does not cover a single payment".to_string())
+        );
+
+        // Fail early for units that cannot be paid over lightning
+        to_unit(amount, &self.unit, &CurrencyUnit::Msat)?;
+
+        let stream = PaymentStream {
+            id: uuid::Uuid::new_v4().to_string(),
+            mint_url: self.mint_url.clone(),
+            destination,
+            amount,
+            interval,
+            budget,
+            spent: Amount::ZERO,
+            payments: 0,
+            last_payment: None,
+            last_error: None,
+            state: PaymentStreamState::Active,
+            created_time: unix_time(),
+        };
+
+        self.save_payment_stream(&stream).await?;
+
+        Ok(stream)
+    }
+
+    /// Get a payment stream by id
+    #[instrument(skip(self))]
+    pub async fn get_payment_stream(&self, id: &str) -> Result<Option<PaymentStream>, Error> {
+        let stream = self
+            .localstore
+            .kv_read(
+                PAYMENT_STREAM_PRIMARY_NAMESPACE,
+                PAYMENT_STREAM_SECONDARY_NAMESPACE,
+                id,
+            )
+            .await?;
+
+        match stream {
+            Some(stream) => {
+                let stream: PaymentStream = serde_json::from_slice(&stream)?;
+                Ok((stream.mint_url == self.mint_url).then_some(stream))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// List the payment streams of the wallet's mint
+    #[instrument(skip(self))]
+    pub async fn payment_streams(&self) -> Result<Vec<PaymentStream>, Error> {
+        let ids = self
+            .localstore
+            .kv_list(
+                PAYMENT_STREAM_PRIMARY_NAMESPACE,
+                PAYMENT_STREAM_SECONDARY_NAMESPACE,
+            )
+            .await?;
+
+        let mut streams = Vec::new();
+        for id in ids {
+            if let Some(stream) = self.get_payment_stream(&id).await? {
+                streams.push(stream);
+            }
+        }
+
+        streams.sort_by_key(|stream| stream.created_time);
+
+        Ok(streams)
+    }
+
+    /// Stop a payment stream
+    ///
+    /// A running [`Wallet::run_payment_stream`] returns before its next payment.
+    #[instrument(skip(self))]
+    pub async fn stop_payment_stream(&self, id: &str) -> Result<PaymentStream, Error> {
+        self.update_payment_stream(id, |stream| {
+            if stream.state == PaymentStreamState::Active {
+                stream.state = PaymentStreamState::Stopped;
+            }
+        })
+        .await
+    }
+
+    /// Pay the next interval of a payment stream if it is due
+    ///
+    /// The stream is marked exhausted when the remaining budget does not cover
+    /// the amount and fee reserve of the next payment. Fails if the stream is
+    /// already being paid by another call or a [`Wallet::run_payment_stream`].
+    #[instrument(skip(self))]
+    pub async fn pay_payment_stream(&self, id: &str) -> Result<PaymentStream, Error> {
+        let _runner = PaymentStreamRunner::claim(id)?;
+
+        self.pay_due_payment_stream(id).await
+    }
+
+    /// Pay the next interval of a payment stream claimed by the caller
+    async fn pay_due_payment_stream(&self, id: &str) -> Result<PaymentStream, Error> {
+        let stream = self
+            .get_payment_stream(id)
+            .await?
+            .ok_or(Error::PaymentStream("Unknown payment stream".to_string()))?;
+
+        if stream.state != PaymentStreamState::Active || stream.next_payment() > unix_time() {
+            return Ok(stream);
+        }
+
+        let amount_msat = to_unit(stream.amount, &self.unit, &CurrencyUnit::Msat)?;
+
+        let quote = match &stream.destination {
+            PaymentStreamDestination::Bolt12Offer(offer) => {
+                self.melt_bolt12_quote(
+                    offer.clone(),
+                    Some(MeltOptions::new_amountless(amount_msat)),
+                )
+                .await
+            }
+            #[cfg(all(feature = "bip353", not(target_arch = "wasm32")))]
+            PaymentStreamDestination::Bip353Address(address) => {
+                self.melt_bip353_quote(address, amount_msat).await
+            }
+            #[cfg(not(all(feature = "bip353", not(target_arch = "wasm32"))))]
+            PaymentStreamDestination::Bip353Address(_) => Err(Error::PaymentStream(
+                "BIP353 addresses are not supported in this build".to_string(),
+            )),
+            PaymentStreamDestination::Keysend(pubkey) => {
+                self.melt_keysend_quote(*pubkey, amount_msat).await
+            }
+        };
+
+        let quote = match quote {
+            Ok(quote) => quote,
+            Err(err) => {
+                let error = err.to_string();
+                self.update_payment_stream(id, |stream| stream.last_error = Some(error))
+                    .await?;
+                return Err(err);
+            }
+        };
+
+        if quote.amount + quote.fee_reserve > stream.remaining_budget() {
+            tracing::info!("Payment stream {} budget is exhausted", stream.id);
+            return self
+                .update_payment_stream(id, |stream| {
+                    if stream.state == PaymentStreamState::Active {
+                        stream.state = PaymentStreamState::Exhausted;
+                    }
+                })
+                .await;
+        }
+
+        match self.melt(&quote.id).await {
+            Ok(melted) => {
+                tracing::debug!(
+                    "Payment stream {} paid {} with fee {}",
+                    stream.id,
+                    melted.amount,
+                    melted.fee_paid
+                );
+
+                self.update_payment_stream(id, |stream| {
+                    stream.spent += melted.amount + melted.fee_paid;
+                    stream.payments += 1;
+                    stream.last_payment = Some(unix_time());
+                    stream.last_error = None;
+                })
+                .await
+            }
+            Err(err) => {
+                let error = err.to_string();
+                self.update_payment_stream(id, |stream| stream.last_error = Some(error))
+                    .await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Run a payment stream until it is stopped, exhausted or `cancel_token` is cancelled
+    ///
+    /// Failed payments are retried after one interval. Cancelling leaves the
+    /// stream active, so it can be run again later. Fails if the stream is
+    /// already being paid.
+    #[instrument(skip(self, cancel_token))]
+    pub async fn run_payment_stream(
+        &self,
+        id: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<PaymentStream, Error> {
+        let _runner = PaymentStreamRunner::claim(id)?;
+
+        loop {
+            let stream = match self.pay_due_payment_stream(id).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    tracing::warn!("Payment stream {} payment failed: {}", id, err);
+
+                    let stream = self
+                        .get_payment_stream(id)
+                        .await?
+                        .ok_or(Error::PaymentStream("Unknown payment stream".to_string()))?;
+
+                    // Retry after a full interval instead of immediately
+                    PaymentStream {
+                        last_payment: Some(unix_time()),
+                        ..stream
+                    }
+                }
+            };
+
+            if stream.state != PaymentStreamState::Active {
+                return Ok(stream);
+            }
+
+            let wait = stream.next_payment().saturating_sub(unix_time());
+
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(wait)) => (),
+                _ = cancel_token.cancelled() => return Ok(stream),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destination_from_str() {
+        assert_eq!(
+            PaymentStreamDestination::from_str(
+                "lno1qgsqvgnwgcg35z6ee2h3yczraddm72xrfua9uve2rlrm9deu7xyfzrc"
+            )
+            .unwrap(),
+            PaymentStreamDestination::Bolt12Offer(
+                "lno1qgsqvgnwgcg35z6ee2h3yczraddm72xrfua9uve2rlrm9deu7xyfzrc".to_string()
+            )
+        );
+        assert_eq!(
+            PaymentStreamDestination::from_str("₿alice@example.com").unwrap(),
+            PaymentStreamDestination::Bip353Address("alice@example.com".to_string())
+        );
+        assert_eq!(
+            PaymentStreamDestination::from_str(
+                "02a9acc1e48c25eeeb9289b5031cc57da9fe72f3fe2861d264bdc074209b107ba2"
+            )
+            .unwrap(),
+            PaymentStreamDestination::Keysend(
+                PublicKey::from_hex(
+                    "02a9acc1e48c25eeeb9289b5031cc57da9fe72f3fe2861d264bdc074209b107ba2"
+                )
+                .unwrap()
+            )
+        );
+        assert!(PaymentStreamDestination::from_str("lnbc1").is_err());
+        assert!(PaymentStreamDestination::from_str(&"0".repeat(66)).is_err());
+    }
+
+    #[test]
+    fn test_next_payment_and_budget() {
+        let mut stream = PaymentStream {
+            id: "stream".to_string(),
+            mint_url: MintUrl::from_str("https://mint.example.com").unwrap(),
+            destination: PaymentStreamDestination::Bip353Address("alice@example.com".to_string()),
+            amount: Amount::from(10),
+            interval: 60,
+            budget: Amount::from(25),
+            spent: Amount::ZERO,
+            payments: 0,
+            last_payment: None,
+            last_error: None,
+            state: PaymentStreamState::Active,
+            created_time: 1_000,
+        };
+
+        assert_eq!(stream.next_payment(), 1_000);
+
+        stream.last_payment = Some(1_030);
+        stream.spent = Amount::from(21);
+
+        assert_eq!(stream.next_payment(), 1_090);
+        assert_eq!(stream.remaining_budget(), Amount::from(4));
+
+        stream.spent = Amount::from(30);
+        assert_eq!(stream.remaining_budget(), Amount::ZERO);
+    }
+}
@@ -0,0 +1,543 @@
+//! Prepared spends
+//!
+//! A [`PreparedSpend`] holds what a signer needs to sign the inputs of a send: the
+//! swap request with the selected proofs and blinded outputs, and the signatures
+//! the P2PK locked inputs still need. It serializes to JSON, so the signatures can
+//! be made by a hardware or remote signer that never sees the wallet.
+//!
+//! The secrets and blinding factors of the outputs stay in the wallet database,
+//! stored under the id of the spend. The signer only returns [`SpendSignature`]s,
+//! which the wallet checks and adds to its own copy of the swap request when the
+//! spend is finalized, so a signer cannot change the inputs or outputs.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use cdk_common::nut02::KeySetInfosMethods;
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::spending_limits::release_spend;
+use super::SpendKind;
+use crate::amount::SplitTarget;
+use crate::mint_url::MintUrl;
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::nut11::{self, enforce_sig_flag};
+use crate::nuts::{
+    CurrencyUnit, Kind, P2PKWitness, PreMintSecrets, PreSwap, Proofs, PublicKey, SecretKey,
+    SigFlag, SpendingConditions, State, SwapRequest, Witness,
+};
+use crate::secp256k1::schnorr::Signature;
+use crate::{ensure_cdk, Amount, Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const PREPARED_SPEND_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for prepared spends
+const PREPARED_SPEND_SECONDARY_NAMESPACE: &str = "prepared_spends";
+
+/// Message a signer has to sign for a prepared spend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningRequest {
+    /// Index of the input the signature is added to
+    pub input_index: usize,
+    /// Message to sign
+    pub message: String,
+    /// Public keys a signature is accepted from
+    pub pubkeys: Vec<PublicKey>,
+    /// Number of signatures required
+    pub required_signatures: u64,
+}
+
+/// Signature of a signer for an input of a prepared spend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendSignature {
+    /// Index of the input the signature is for
+    pub input_index: usize,
+    /// Key that made the signature
+    pub pubkey: PublicKey,
+    /// Signature of the message of the signing request
+    pub signature: Signature,
+}
+
+/// Swap of the inputs of a send, waiting for signatures
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreparedSpend {
+    /// Id of the spend, used to finalize or cancel it
+    pub id: String,
+    /// Mint of the inputs
+    pub mint_url: MintUrl,
+    /// Unit of the inputs
+    pub unit: CurrencyUnit,
+    /// Amount to send
+    pub amount: Amount,
+    /// Input fee of the swap
+    pub fee: Amount,
+    /// Swap request with the selected inputs and the blinded outputs
+    pub swap_request: SwapRequest,
+}
+
+/// Prepared spend with the output secrets, kept in the wallet database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredSpend {
+    spend: PreparedSpend,
+    spending_conditions: Option<SpendingConditions>,
+    pre_mint_secrets: PreMintSecrets,
+    derived_secret_count: u32,
+    /// Entry of the spend in the spend log, released when the spend is cancelled
+    #[serde(default)]
+    spend_reservation: Option<String>,
+}
+
+impl PreparedSpend {
+    /// Signatures the inputs need before the spend can be finalized
+    ///
+    /// With `SIG_ALL` a single signature over the whole swap is added to the first
+    /// input, otherwise every P2PK locked input signs its own secret.
+    pub fn signing_requests(&self) -> Result<Vec<SigningRequest>, Error> {
+        let inputs = self.swap_request.inputs();
+
+        if enforce_sig_flag(inputs.clone()).sig_flag == SigFlag::SigAll {
+            let first_input = inputs.first().ok_or(nut11::Error::IncorrectSecretKind)?;
+            let conditions = SpendingConditions::try_from(&first_input.secret)?;
+
+            return Ok(vec![SigningRequest {
+                input_index: 0,
+                message: self.swap_request.sig_all_msg_to_sign(),
+                pubkeys: signing_pubkeys(&conditions),
+                required_signatures: conditions.num_sigs().unwrap_or(1),
+            }]);
+        }
+
+        let mut requests = Vec::new();
+
+        for (input_index, proof) in inputs.iter().enumerate() {
+            let Ok(conditions) = SpendingConditions::try_from(&proof.secret) else {
+                continue;
+            };
+
+            requests.push(SigningRequest {
+                input_index,
+                message: proof.secret.to_string(),
+                pubkeys: signing_pubkeys(&conditions),
+                required_signatures: conditions.num_sigs().unwrap_or(1),
+            });
+        }
+
+        Ok(requests)
+    }
+
+    /// Sign every signing request `secret_key` can sign
+    ///
+    /// The signatures are returned to the wallet, which adds them with
+    /// [`Wallet::finalize_spend`].
+    pub fn sign(&self, secret_key: &SecretKey) -> Result<Vec<SpendSignature>, Error> {
+        let pubkey = secret_key.public_key();
+
+        self.signing_requests()?
+            .into_iter()
+            .filter(|request| request.pubkeys.contains(&pubkey))
+            .map(|request| {
+                Ok(SpendSignature {
+                    input_index: request.input_index,
+                    pubkey,
+                    signature: secret_key.sign(request.message.as_bytes())?,
+                })
+            })
+            .collect()
+    }
+
+    /// Add a signature to the witness of its input
+    ///
+    /// The signature is checked against the message of the signing request of the
+    /// input before it is added.
+    fn add_signature(&mut self, signature: &SpendSignature) -> Result<(), Error> {
+        let SpendSignature {
+            input_index,
+            pubkey,
+            signature,
+        } = signature;
+
+        let request = self
+            .signing_requests()?
+            .into_iter()
+            .find(|request| request.input_index == *input_index)
+            .ok_or(Error::Custom(format!(
+                "Input {input_index} does not need a signature"
+            )))?;
+
+        ensure_cdk!(
+            request.pubkeys.contains(pubkey),
+            Error::Custom(format!("{pubkey} cannot sign input {input_index}"))
+        );
+
+        pubkey
+            .verify(request.message.as_bytes(), signature)
+            .map_err(|_| Error::Custom(format!("Invalid signature for input {input_index}")))?;
+
+        let input = &mut self.swap_request.inputs_mut()[*input_index];
+        let witness = input
+            .witness
+            .get_or_insert(Witness::P2PKWitness(P2PKWitness::default()));
+
+        // A key counts once towards the required signatures, so a signature from a
+        // key that already signed the input is not added again
+        let signed = witness
+            .signatures()
+            .unwrap_or_default()
+            .iter()
+            .any(|existing| {
+                Signature::from_str(existing).is_ok_and(|existing| {
+                    pubkey.verify(request.message.as_bytes(), &existing).is_ok()
+                })
+            });
+        if !signed {
+            witness.add_signatures(vec![signature.to_string()]);
+        }
+
+        Ok(())
+    }
+}
+
+/// Keys that may sign for spending conditions, including refund keys after the locktime
+fn signing_pubkeys(conditions: &SpendingConditions) -> Vec<PublicKey> {
+    let mut pubkeys = conditions.pubkeys().unwrap_or_default();
+
+    if conditions
+        .locktime()
+        .is_some_and(|locktime| locktime < unix_time())
+    {
+        pubkeys.extend(conditions.refund_keys().unwrap_or_default());
+    }
+
+    pubkeys
+}
+
+impl Wallet {
+    /// Prepare a send of `amount` whose inputs are signed outside the wallet
+    ///
+    /// The inputs are selected from the unspent proofs and reserved until the
+    /// spend is finalized with [`Wallet::finalize_spend`] or cancelled with
+    /// [`Wallet::cancel_spend`].
+    #[instrument(skip(self))]
+    pub async fn prepare_spend(
+        &self,
+        amount: Amount,
+        spending_conditions: Option<SpendingConditions>,
+    ) -> Result<PreparedSpend, Error> {
+        let spend_reservation = self.check_spending_limits(SpendKind::Send, amount).await?;
+
+        let available_proofs = self
+            .get_unspent_proofs()
+            .await?
+            .into_iter()
+            .filter(|proof| {
+                // HTLC inputs need a preimage a signer cannot provide
+                SpendingConditions::try_from(&proof.secret)
+                    .map(|conditions| conditions.kind() == Kind::P2PK)
+                    .unwrap_or(true)
+            })
+            .collect::<Proofs>();
+
+        ensure_cdk!(
+            available_proofs.total_amount()? >= amount,
+            Error::InsufficientFunds
+        );
+
+        let active_keyset_ids = self
+            .refresh_keysets()
+            .await?
+            .active()
+            .map(|k| k.id)
+            .collect();
+
+        let keyset_fees = self.get_keyset_fees().await?;
+        let proofs = Wallet::select_proofs(
+            amount,
+            available_proofs,
+            &active_keyset_ids,
+            &keyset_fees,
+            true,
+        )?;
+
+        let PreSwap {
+            pre_mint_secrets,
+            swap_request,
+            derived_secret_count,
+            fee,
+        } = self
+            .create_swap(
+                Some(amount),
+                SplitTarget::default(),
+                proofs,
+                spending_conditions.clone(),
+                false,
+            )
+            .await?;
+
+        let spend = PreparedSpend {
+            id: swap_request
+                .inputs()
+                .ys()?
+                .first()
+                .ok_or(Error::InsufficientFunds)?
+                .to_hex(),
+            mint_url: self.mint_url.clone(),
+            unit: self.unit.clone(),
+            amount,
+            fee,
+            swap_request,
+        };
+
+        let stored = StoredSpend {
+            spend: spend.clone(),
+            spending_conditions,
+            pre_mint_secrets,
+            derived_secret_count,
+            spend_reservation: spend_reservation.id().map(str::to_string),
+        };
+
+        let written = async {
+            self.localstore
+                .kv_write(
+                    PREPARED_SPEND_PRIMARY_NAMESPACE,
+                    PREPARED_SPEND_SECONDARY_NAMESPACE,
+                    &spend.id,
+                    &serde_json::to_vec(&stored)?,
+                )
+                .await?;
+
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        // Without the stored spend the reserved inputs could never be cancelled
+        if let Err(err) = written {
+            self.unreserve_proofs(spend.swap_request.inputs().ys()?)
+                .await?;
+            return Err(err);
+        }
+
+        // Stays counted until the spend is cancelled
+        spend_reservation.keep();
+
+        Ok(spend)
+    }
+
+    /// Prepared spend of this wallet stored under `spend_id`
+    async fn stored_spend(&self, spend_id: &str) -> Result<StoredSpend, Error> {
+        let stored = self
+            .localstore
+            .kv_read(
+                PREPARED_SPEND_PRIMARY_NAMESPACE,
+                PREPARED_SPEND_SECONDARY_NAMESPACE,
+                spend_id,
+            )
+            .await?
+            .ok_or(Error::Custom(format!("Unknown prepared spend {spend_id}")))?;
+        let stored: StoredSpend = serde_json::from_slice(&stored)?;
+
+        ensure_cdk!(
+            stored.spend.mint_url == self.mint_url && stored.spend.unit == self.unit,
+            Error::Custom("Prepared spend is for another mint or unit".to_string())
+        );
+
+        Ok(stored)
+    }
+
+    async fn remove_stored_spend(&self, spend_id: &str) -> Result<(), Error> {
+        self.localstore
+            .kv_remove(
+                PREPARED_SPEND_PRIMARY_NAMESPACE,
+                PREPARED_SPEND_SECONDARY_NAMESPACE,
+                spend_id,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Add the `signatures` of a signer to a prepared spend and swap its inputs
+    ///
+    /// Returns the proofs to send, which are stored as reserved like the proofs of
+    /// a confirmed send. The change is stored as unspent.
+    #[instrument(skip(self, signatures))]
+    pub async fn finalize_spend(
+        &self,
+        spend_id: &str,
+        signatures: Vec<SpendSignature>,
+    ) -> Result<Proofs, Error> {
+        let StoredSpend {
+            mut spend,
+            spending_conditions,
+            pre_mint_secrets,
+            derived_secret_count,
+            ..
+        } = self.stored_spend(spend_id).await?;
+
+        for signature in &signatures {
+            spend.add_signature(signature)?;
+        }
+
+        let pre_swap = PreSwap {
+            pre_mint_secrets,
+            swap_request: spend.swap_request,
+            derived_secret_count,
+            fee: spend.fee,
+        };
+
+        let proofs = self
+            .complete_swap(
+                pre_swap,
+                Some(spend.amount),
+                SplitTarget::default(),
+                spending_conditions,
+            )
+            .await?
+            .ok_or(Error::InsufficientFunds)?;
+
+        self.remove_stored_spend(spend_id).await?;
+
+        Ok(proofs)
+    }
+
+    /// Release the inputs of a prepared spend that will not be finalized
+    #[instrument(skip(self))]
+    pub async fn cancel_spend(&self, spend_id: &str) -> Result<(), Error> {
+        let stored = self.stored_spend(spend_id).await?;
+        let input_ys = stored.spend.swap_request.inputs().ys()?;
+
+        // Only inputs still reserved are released, spent inputs were removed
+        let reserved_ys = self
+            .localstore
+            .get_proofs(
+                Some(self.mint_url.clone()),
+                Some(self.unit.clone()),
+                Some(vec![State::Reserved]),
+                None,
+            )
+            .await?
+            .into_iter()
+            .map(|proof_info| proof_info.y)
+            .collect::<HashSet<_>>();
+
+        let ys = input_ys
+            .into_iter()
+            .filter(|y| reserved_ys.contains(y))
+            .collect();
+
+        self.localstore
+            .update_proofs_state(ys, State::Unspent)
+            .await?;
+
+        self.remove_stored_spend(spend_id).await?;
+
+        if let Some(id) = stored.spend_reservation {
+            release_spend(&id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::nuts::{Conditions, Id, Nut10Secret, Proof};
+    use crate::secret::Secret;
+
+    fn locked_proof(conditions: &SpendingConditions) -> Proof {
+        let secret: Secret = Nut10Secret::from(conditions.clone()).try_into().unwrap();
+
+        Proof::new(
+            Amount::from(8),
+            Id::from_str("009a1f293253e41e").unwrap(),
+            secret,
+            SecretKey::generate().public_key(),
+        )
+    }
+
+    fn prepared_spend(inputs: Proofs) -> PreparedSpend {
+        let keyset_id = Id::from_str("009a1f293253e41e").unwrap();
+        let pre_mint_secrets =
+            PreMintSecrets::random(keyset_id, Amount::from(8), &SplitTarget::default()).unwrap();
+
+        PreparedSpend {
+            id: inputs.ys().unwrap()[0].to_hex(),
+            mint_url: MintUrl::from_str("https://mint.example.com").unwrap(),
+            unit: CurrencyUnit::Sat,
+            amount: Amount::from(8),
+            fee: Amount::ZERO,
+            swap_request: SwapRequest::new(inputs, pre_mint_secrets.blinded_messages()),
+        }
+    }
+
+    #[test]
+    fn test_external_signatures() {
+        let signer = SecretKey::generate();
+        let other = SecretKey::generate();
+        let conditions = SpendingConditions::new_p2pk(signer.public_key(), None);
+
+        let spend = prepared_spend(vec![locked_proof(&conditions)]);
+
+        // The spend survives the round trip to the signer
+        let mut spend: PreparedSpend =
+            serde_json::from_str(&serde_json::to_string(&spend).unwrap()).unwrap();
+
+        let requests = spend.signing_requests().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].pubkeys, vec![signer.public_key()]);
+
+        // Signatures of other keys are rejected
+        let forged = SpendSignature {
+            input_index: 0,
+            pubkey: signer.public_key(),
+            signature: other.sign(requests[0].message.as_bytes()).unwrap(),
+        };
+        assert!(spend.add_signature(&forged).is_err());
+        assert!(spend.sign(&other).unwrap().is_empty());
+
+        // Only the signatures travel back from the signer
+        let signatures = spend.sign(&signer).unwrap();
+        let signatures: Vec<SpendSignature> =
+            serde_json::from_str(&serde_json::to_string(&signatures).unwrap()).unwrap();
+        assert_eq!(signatures.len(), 1);
+
+        spend.add_signature(&signatures[0]).unwrap();
+        assert!(spend.swap_request.inputs()[0].verify_p2pk().is_ok());
+
+        // Signing again with the same key does not add a second signature
+        let again = spend.sign(&signer).unwrap();
+        spend.add_signature(&again[0]).unwrap();
+        let witness = spend.swap_request.inputs()[0].witness.as_ref().unwrap();
+        assert_eq!(witness.signatures().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sig_all_signing_request() {
+        let signer = SecretKey::generate();
+        let conditions = SpendingConditions::new_p2pk(
+            signer.public_key(),
+            Some(Conditions {
+                sig_flag: SigFlag::SigAll,
+                ..Default::default()
+            }),
+        );
+
+        let mut spend = prepared_spend(vec![locked_proof(&conditions), locked_proof(&conditions)]);
+
+        let requests = spend.signing_requests().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].message,
+            spend.swap_request.sig_all_msg_to_sign()
+        );
+
+        let signatures = spend.sign(&signer).unwrap();
+        assert_eq!(signatures.len(), 1);
+        spend.add_signature(&signatures[0]).unwrap();
+        assert!(spend.swap_request.verify_sig_all().is_ok());
+    }
+}
@@ -0,0 +1,172 @@
+//! Proof import
+//!
+//! Adds raw proofs, such as proofs exported from another wallet, to the wallet
+//! database after checking them against the mint. Proofs carrying a valid DLEQ
+//! proof are stored as they are, so the source wallet can still spend them. Proofs
+//! without one cannot be shown to be signed by the mint offline and are swapped
+//! for new proofs instead.
+
+use std::collections::HashSet;
+
+use tracing::instrument;
+
+use crate::amount::SplitTarget;
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::{Proofs, State};
+use crate::types::ProofInfo;
+use crate::{Amount, Error, Wallet};
+
+/// Outcome of [`Wallet::import_proofs`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProofImport {
+    /// Number of imported proofs
+    pub imported: usize,
+    /// Value of the imported proofs, after the input fee of swapped proofs
+    pub amount: Amount,
+    /// Imported proofs without a DLEQ proof, swapped for new proofs
+    pub swapped: usize,
+    /// Proofs already in the wallet or repeated in the import
+    pub duplicates: usize,
+    /// Proofs of keysets of another mint or unit
+    pub foreign: usize,
+    /// Proofs the mint reports as spent or pending
+    pub unspendable: usize,
+    /// Proofs with a DLEQ proof that does not match the mint's keys
+    pub invalid: usize,
+}
+
+impl Wallet {
+    /// Import proofs of the wallet's mint and unit as unspent
+    ///
+    /// Proofs already in the wallet, of an unknown keyset, with an invalid DLEQ proof or
+    /// not unspent at the mint are skipped and counted in the returned [`ProofImport`].
+    /// Proofs without a DLEQ proof are swapped before they are stored.
+    #[instrument(skip_all)]
+    pub async fn import_proofs(&self, proofs: Proofs) -> Result<ProofImport, Error> {
+        let keyset_ids = self
+            .refresh_keysets()
+            .await?
+            .into_iter()
+            .map(|keyset| keyset.id)
+            .collect::<HashSet<_>>();
+
+        let mut known_ys = self
+            .localstore
+            .get_proofs(Some(self.mint_url.clone()), None, None, None)
+            .await?
+            .into_iter()
+            .map(|proof_info| proof_info.y)
+            .collect::<HashSet<_>>();
+
+        let mut result = ProofImport::default();
+        let mut candidates = Proofs::new();
+
+        for proof in proofs {
+            if !keyset_ids.contains(&proof.keyset_id) {
+                result.foreign += 1;
+                continue;
+            }
+
+            if !known_ys.insert(proof.y()?) {
+                result.duplicates += 1;
+                continue;
+            }
+
+            if proof.dleq.is_some() {
+                let keys = self.load_keyset_keys(proof.keyset_id).await?;
+                let valid = keys
+                    .amount_key(proof.amount)
+                    .is_some_and(|key| proof.verify_dleq(key).is_ok());
+
+                if !valid {
+                    result.invalid += 1;
+                    continue;
+                }
+            }
+
+            candidates.push(proof);
+        }
+
+        if candidates.is_empty() {
+            return Ok(result);
+        }
+
+        let candidate_count = candidates.len();
+        let states = self.check_proofs_spent(candidates.clone()).await?;
+
+        let unspent = candidates
+            .into_iter()
+            .zip(states)
+            .filter_map(|(proof, state)| match state.state {
+                State::Unspent => Some(proof),
+                _ => None,
+            })
+            .collect::<Proofs>();
+
+        result.unspendable = candidate_count - unspent.len();
+
+        let (verified, unverified): (Proofs, Proofs) =
+            unspent.into_iter().partition(|proof| proof.dleq.is_some());
+
+        if !verified.is_empty() {
+            result.imported += verified.len();
+            result.amount += verified.total_amount()?;
+
+            let proof_infos = verified
+                .into_iter()
+                .map(|proof| {
+                    ProofInfo::new(
+                        proof,
+                        self.mint_url.clone(),
+                        State::Unspent,
+                        self.unit.clone(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            self.localstore.update_proofs(proof_infos, vec![]).await?;
+        }
+
+        if !unverified.is_empty() {
+            result.amount += self.swap_unverified_proofs(unverified.clone()).await?;
+            result.imported += unverified.len();
+            result.swapped = unverified.len();
+        }
+
+        Ok(result)
+    }
+
+    /// Swap proofs whose signature could not be verified for new proofs of the wallet
+    ///
+    /// The proofs are stored as pending while the swap runs and removed if it fails.
+    /// Returns the value of the new proofs.
+    async fn swap_unverified_proofs(&self, proofs: Proofs) -> Result<Amount, Error> {
+        let amount = proofs.total_amount()?;
+        let fee = self.get_proofs_fee(&proofs).await?;
+        let ys = proofs.ys()?;
+
+        let proof_infos = proofs
+            .iter()
+            .cloned()
+            .map(|proof| {
+                ProofInfo::new(
+                    proof,
+                    self.mint_url.clone(),
+                    State::Pending,
+                    self.unit.clone(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.localstore.update_proofs(proof_infos, vec![]).await?;
+
+        if let Err(err) = self
+            .swap(None, SplitTarget::default(), proofs, None, false)
+            .await
+        {
+            self.localstore.update_proofs(vec![], ys).await?;
+            return Err(err);
+        }
+
+        amount.checked_sub(fee).ok_or(Error::AmountOverflow)
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
-use cdk_common::wallet::TransactionId;
+use cdk_common::util::unix_time;
+use cdk_common::wallet::{ArchivedProof, TransactionId};
 use cdk_common::Id;
 use tracing::instrument;
 
@@ -60,6 +62,59 @@ impl Wallet {
             .collect())
     }
 
+    /// Get summaries of this wallet's spent proofs, most recently spent first
+    ///
+    /// Proofs are archived when they are removed from the wallet after being spent,
+    /// archived proofs older than the wallet's retention are pruned first.
+    #[instrument(skip(self))]
+    pub async fn get_archived_proofs(&self) -> Result<Vec<ArchivedProof>, Error> {
+        self.apply_archived_proof_retention().await?;
+
+        Ok(self
+            .localstore
+            .get_archived_proofs(Some(self.mint_url.clone()), Some(self.unit.clone()))
+            .await?)
+    }
+
+    /// Remove archived proofs spent longer ago than `retention`
+    ///
+    /// The archive is shared by every wallet using the same database, so this prunes
+    /// the archived proofs of all mints and units. Returns how many were removed.
+    #[instrument(skip(self))]
+    pub async fn prune_archived_proofs(&self, retention: Duration) -> Result<u64, Error> {
+        let spent_before = unix_time().saturating_sub(retention.as_secs());
+
+        Ok(self.localstore.prune_archived_proofs(spent_before).await?)
+    }
+
+    /// Prune archived proofs older than the retention set with
+    /// [`WalletBuilder::archived_proof_retention`](crate::wallet::WalletBuilder::archived_proof_retention)
+    async fn apply_archived_proof_retention(&self) -> Result<(), Error> {
+        if let Some(retention) = self.archived_proof_retention {
+            let removed = self.prune_archived_proofs(retention).await?;
+            if removed > 0 {
+                tracing::debug!("Pruned {} archived proofs past their retention", removed);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store `added` and remove the proofs of `spent_ys`, marking them spent so
+    /// they are archived
+    pub(crate) async fn replace_spent_proofs(
+        &self,
+        added: Vec<ProofInfo>,
+        spent_ys: Vec<PublicKey>,
+    ) -> Result<(), Error> {
+        self.localstore
+            .update_proofs_state(spent_ys.clone(), State::Spent)
+            .await?;
+        self.localstore.update_proofs(added, spent_ys).await?;
+
+        Ok(())
+    }
+
     /// Return proofs to unspent allowing them to be selected and spent
     #[instrument(skip(self))]
     pub async fn unreserve_proofs(&self, ys: Vec<PublicKey>) -> Result<(), Error> {
@@ -90,8 +145,11 @@ impl Wallet {
             .filter_map(|(p, s)| (s.state == State::Unspent).then_some(p))
             .collect();
 
-        self.swap(None, SplitTarget::default(), unspent, None, false)
-            .await?;
+        // Inputs the mint holds as pending or spent leave nothing to swap
+        if !unspent.is_empty() {
+            self.swap(None, SplitTarget::default(), unspent, None, false)
+                .await?;
+        }
 
         match self.localstore.remove_transaction(transaction_id).await {
             Ok(_) => (),
@@ -120,7 +178,7 @@ impl Wallet {
             })
             .collect();
 
-        self.localstore.update_proofs(vec![], spent_ys).await?;
+        self.replace_spent_proofs(vec![], spent_ys).await?;
 
         Ok(spendable.states)
     }
@@ -164,12 +222,13 @@ impl Wallet {
 
         let amount = Amount::try_sum(pending_proofs.iter().map(|p| p.proof.amount))?;
 
-        self.localstore
-            .update_proofs(
-                vec![],
-                non_pending_proofs.into_iter().map(|p| p.y).collect(),
-            )
-            .await?;
+        self.replace_spent_proofs(
+            vec![],
+            non_pending_proofs.into_iter().map(|p| p.y).collect(),
+        )
+        .await?;
+
+        self.apply_archived_proof_retention().await?;
 
         balance += amount;
 
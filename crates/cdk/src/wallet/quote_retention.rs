@@ -0,0 +1,386 @@
+//! Quote retention
+//!
+//! Mint and melt quotes stay in the wallet database once they are finalized.
+//! With a retention set, finalized quotes that expired more than the retention
+//! ago are deleted by [`Wallet::prune_quotes`], which
+//! [`Wallet::check_all_mint_quotes`] runs after every sync, along with the
+//! progress of melts that ended before the retention. Quotes of pending
+//! transactions are kept. The retention is kept in the wallet database, so it
+//! applies to every wallet sharing it.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use cdk_common::database::{self, WalletDatabase};
+use cdk_common::util::unix_time;
+use cdk_common::wallet::{MeltQuote, MintQuote, TransactionStatus};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::nuts::{MeltQuoteState, MintQuoteState};
+use crate::wallet::melt::prune_melt_progress;
+use crate::{Error, Wallet};
+
+const QUOTE_RETENTION_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+const QUOTE_RETENTION_SECONDARY_NAMESPACE: &str = "quote_retention";
+const QUOTE_RETENTION_KEY: &str = "days";
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Quotes stored in the wallet database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteStats {
+    /// Mint quotes stored
+    pub mint_quotes: usize,
+    /// Mint quotes that are expired and have nothing left to mint
+    pub finalized_mint_quotes: usize,
+    /// Melt quotes stored
+    pub melt_quotes: usize,
+    /// Melt quotes that are expired and not pending
+    pub finalized_melt_quotes: usize,
+}
+
+/// Quotes deleted by [`Wallet::prune_quotes`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrunedQuotes {
+    /// Mint quotes deleted
+    pub mint_quotes: usize,
+    /// Melt quotes deleted
+    pub melt_quotes: usize,
+}
+
+/// Whether a mint quote is expired and has nothing left to mint
+fn mint_quote_finalized(quote: &MintQuote, now: u64) -> bool {
+    quote.is_expired(now)
+        && quote.state != MintQuoteState::Paid
+        && quote.amount_paid <= quote.amount_issued
+}
+
+/// Whether a melt quote is expired and no payment of it can still be in flight
+fn melt_quote_finalized(quote: &MeltQuote, now: u64) -> bool {
+    quote.expiry < now
+        && matches!(
+            quote.state,
+            MeltQuoteState::Paid | MeltQuoteState::Unpaid | MeltQuoteState::Failed
+        )
+}
+
+pub(crate) async fn read_quote_retention(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+) -> Result<Option<u64>, Error> {
+    let days = localstore
+        .kv_read(
+            QUOTE_RETENTION_PRIMARY_NAMESPACE,
+            QUOTE_RETENTION_SECONDARY_NAMESPACE,
+            QUOTE_RETENTION_KEY,
+        )
+        .await?;
+
+    Ok(days
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()?)
+}
+
+pub(crate) async fn write_quote_retention(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+    days: Option<u64>,
+) -> Result<(), Error> {
+    match days {
+        Some(days) => {
+            localstore
+                .kv_write(
+                    QUOTE_RETENTION_PRIMARY_NAMESPACE,
+                    QUOTE_RETENTION_SECONDARY_NAMESPACE,
+                    QUOTE_RETENTION_KEY,
+                    &serde_json::to_vec(&days)?,
+                )
+                .await?;
+        }
+        None => {
+            localstore
+                .kv_remove(
+                    QUOTE_RETENTION_PRIMARY_NAMESPACE,
+                    QUOTE_RETENTION_SECONDARY_NAMESPACE,
+                    QUOTE_RETENTION_KEY,
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn quote_stats(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+) -> Result<QuoteStats, Error> {
+    let now = unix_time();
+    let mint_quotes = localstore.get_mint_quotes().await?;
+    let melt_quotes = localstore.get_melt_quotes().await?;
+
+    Ok(QuoteStats {
+        mint_quotes: mint_quotes.len(),
+        finalized_mint_quotes: mint_quotes
+            .iter()
+            .filter(|quote| mint_quote_finalized(quote, now))
+            .count(),
+        melt_quotes: melt_quotes.len(),
+        finalized_melt_quotes: melt_quotes
+            .iter()
+            .filter(|quote| melt_quote_finalized(quote, now))
+            .count(),
+    })
+}
+
+pub(crate) async fn prune_quotes(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+) -> Result<PrunedQuotes, Error> {
+    let Some(days) = read_quote_retention(localstore).await? else {
+        return Ok(PrunedQuotes::default());
+    };
+
+    // Quotes expired before the cutoff are finalized at the cutoff too
+    let cutoff = unix_time().saturating_sub(days.saturating_mul(DAY_SECS));
+
+    let referenced: HashSet<String> = localstore
+        .list_transactions(None, None, None)
+        .await?
+        .into_iter()
+        .filter(|transaction| transaction.status == TransactionStatus::Pending)
+        .filter_map(|transaction| transaction.quote_id)
+        .collect();
+
+    let mint_quote_ids: Vec<String> = localstore
+        .get_mint_quotes()
+        .await?
+        .into_iter()
+        .filter(|quote| mint_quote_finalized(quote, cutoff) && !referenced.contains(&quote.id))
+        .map(|quote| quote.id)
+        .collect();
+    localstore.remove_mint_quotes(&mint_quote_ids).await?;
+
+    let melt_quote_ids: Vec<String> = localstore
+        .get_melt_quotes()
+        .await?
+        .into_iter()
+        .filter(|quote| melt_quote_finalized(quote, cutoff) && !referenced.contains(&quote.id))
+        .map(|quote| quote.id)
+        .collect();
+    localstore.remove_melt_quotes(&melt_quote_ids).await?;
+
+    prune_melt_progress(localstore, cutoff).await?;
+
+    let pruned = PrunedQuotes {
+        mint_quotes: mint_quote_ids.len(),
+        melt_quotes: melt_quote_ids.len(),
+    };
+
+    if pruned != PrunedQuotes::default() {
+        tracing::info!(
+            "Pruned {} mint and {} melt quotes older than {} days",
+            pruned.mint_quotes,
+            pruned.melt_quotes,
+            days
+        );
+    }
+
+    Ok(pruned)
+}
+
+impl Wallet {
+    /// Days finalized quotes are kept after they expired, `None` to keep them forever
+    #[instrument(skip(self))]
+    pub async fn quote_retention(&self) -> Result<Option<u64>, Error> {
+        read_quote_retention(&self.localstore).await
+    }
+
+    /// Set the days finalized quotes are kept after they expired
+    ///
+    /// The retention applies to every wallet sharing the database.
+    #[instrument(skip(self))]
+    pub async fn set_quote_retention(&self, days: Option<u64>) -> Result<(), Error> {
+        write_quote_retention(&self.localstore, days).await
+    }
+
+    /// Count the quotes stored in the wallet database
+    #[instrument(skip(self))]
+    pub async fn quote_stats(&self) -> Result<QuoteStats, Error> {
+        quote_stats(&self.localstore).await
+    }
+
+    /// Delete the finalized quotes older than the retention
+    ///
+    /// Does nothing without a retention. Quotes of pending transactions are kept.
+    #[instrument(skip(self))]
+    pub async fn prune_quotes(&self) -> Result<PrunedQuotes, Error> {
+        prune_quotes(&self.localstore).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use cdk_common::wallet::{Transaction, TransactionDirection};
+
+    use super::*;
+    use crate::mint_url::MintUrl;
+    use crate::nuts::{CurrencyUnit, PaymentMethod, SecretKey};
+    use crate::Amount;
+
+    fn mint_quote(state: MintQuoteState, paid: u64, issued: u64) -> MintQuote {
+        let mut quote = MintQuote::new(
+            "quote".to_string(),
+            MintUrl::from_str("https://mint.example").unwrap(),
+            PaymentMethod::Bolt11,
+            Some(Amount::from(10)),
+            CurrencyUnit::Sat,
+            String::new(),
+            100,
+            None,
+        );
+        quote.state = state;
+        quote.amount_paid = Amount::from(paid);
+        quote.amount_issued = Amount::from(issued);
+        quote
+    }
+
+    fn melt_quote(id: &str, state: MeltQuoteState, expiry: u64) -> MeltQuote {
+        MeltQuote {
+            id: id.to_string(),
+            unit: CurrencyUnit::Sat,
+            amount: Amount::from(10),
+            request: String::new(),
+            fee_reserve: Amount::from(1),
+            state,
+            expiry,
+            payment_preimage: None,
+            payment_method: PaymentMethod::Bolt11,
+            mint_url: None,
+        }
+    }
+
+    #[test]
+    fn test_mint_quote_finalized() {
+        assert!(mint_quote_finalized(
+            &mint_quote(MintQuoteState::Issued, 10, 10),
+            200
+        ));
+        assert!(mint_quote_finalized(
+            &mint_quote(MintQuoteState::Unpaid, 0, 0),
+            200
+        ));
+        // Not expired yet
+        assert!(!mint_quote_finalized(
+            &mint_quote(MintQuoteState::Issued, 10, 10),
+            50
+        ));
+        // Paid and not minted
+        assert!(!mint_quote_finalized(
+            &mint_quote(MintQuoteState::Paid, 10, 0),
+            200
+        ));
+        // Reusable quote paid again after its last issuance
+        assert!(!mint_quote_finalized(
+            &mint_quote(MintQuoteState::Unpaid, 20, 10),
+            200
+        ));
+    }
+
+    #[test]
+    fn test_melt_quote_finalized() {
+        for state in [
+            MeltQuoteState::Paid,
+            MeltQuoteState::Unpaid,
+            MeltQuoteState::Failed,
+        ] {
+            assert!(melt_quote_finalized(&melt_quote("quote", state, 100), 200));
+        }
+        // Not expired yet
+        assert!(!melt_quote_finalized(
+            &melt_quote("quote", MeltQuoteState::Paid, 100),
+            50
+        ));
+        // A payment may still be in flight
+        for state in [MeltQuoteState::Pending, MeltQuoteState::Unknown] {
+            assert!(!melt_quote_finalized(&melt_quote("quote", state, 100), 200));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_quotes() {
+        let localstore: Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync> = Arc::new(
+            cdk_sqlite::wallet::memory::empty()
+                .await
+                .expect("Failed to create in-memory database"),
+        );
+
+        let mut old_mint_quote = mint_quote(MintQuoteState::Issued, 10, 10);
+        old_mint_quote.id = "old-mint".to_string();
+        let mut recent_mint_quote = mint_quote(MintQuoteState::Issued, 10, 10);
+        recent_mint_quote.id = "recent-mint".to_string();
+        recent_mint_quote.expiry = unix_time();
+        for quote in [old_mint_quote, recent_mint_quote] {
+            localstore.add_mint_quote(quote).await.unwrap();
+        }
+
+        for quote in [
+            melt_quote("old-melt", MeltQuoteState::Paid, 100),
+            melt_quote("pending-melt", MeltQuoteState::Pending, 100),
+            melt_quote("referenced-melt", MeltQuoteState::Paid, 100),
+        ] {
+            localstore.add_melt_quote(quote).await.unwrap();
+        }
+        localstore
+            .add_transaction(Transaction {
+                mint_url: MintUrl::from_str("https://mint.example").unwrap(),
+                direction: TransactionDirection::Outgoing,
+                amount: Amount::from(10),
+                fee: Amount::ZERO,
+                unit: CurrencyUnit::Sat,
+                ys: vec![SecretKey::generate().public_key()],
+                timestamp: 100,
+                memo: None,
+                metadata: Default::default(),
+                quote_id: Some("referenced-melt".to_string()),
+                status: TransactionStatus::Pending,
+            })
+            .await
+            .unwrap();
+
+        // Nothing is pruned without a retention
+        assert_eq!(
+            prune_quotes(&localstore).await.unwrap(),
+            PrunedQuotes::default()
+        );
+
+        write_quote_retention(&localstore, Some(1)).await.unwrap();
+        assert_eq!(
+            prune_quotes(&localstore).await.unwrap(),
+            PrunedQuotes {
+                mint_quotes: 1,
+                melt_quotes: 1,
+            }
+        );
+
+        let mint_quotes: Vec<String> = localstore
+            .get_mint_quotes()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|quote| quote.id)
+            .collect();
+        assert_eq!(mint_quotes, vec!["recent-mint".to_string()]);
+
+        let mut melt_quotes: Vec<String> = localstore
+            .get_melt_quotes()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|quote| quote.id)
+            .collect();
+        melt_quotes.sort();
+        assert_eq!(
+            melt_quotes,
+            vec!["pending-melt".to_string(), "referenced-melt".to_string()]
+        );
+    }
+}
@@ -1,18 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use bitcoin::hashes::sha256::Hash as Sha256Hash;
 use bitcoin::hashes::Hash;
 use bitcoin::XOnlyPublicKey;
 use cdk_common::util::unix_time;
-use cdk_common::wallet::{Transaction, TransactionDirection};
+use cdk_common::wallet::{FeeKind, Transaction, TransactionDirection, TransactionStatus};
+use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 
 use crate::amount::SplitTarget;
 use crate::dhke::construct_proofs;
 use crate::nuts::nut00::ProofsMethods;
 use crate::nuts::nut10::Kind;
-use crate::nuts::{Conditions, Proofs, PublicKey, SecretKey, SigFlag, State, Token};
+use crate::nuts::{
+    Conditions, Proofs, PublicKey, SecretKey, SigFlag, SpendingConditions, State, Token,
+};
 use crate::types::ProofInfo;
 use crate::util::hex;
 use crate::{ensure_cdk, Amount, Error, Wallet, SECP256K1};
@@ -25,11 +28,30 @@ impl Wallet {
         proofs: Proofs,
         opts: ReceiveOptions,
         memo: Option<String>,
+    ) -> Result<Amount, Error> {
+        self.receive_proofs_with_cancel(proofs, opts, memo, CancellationToken::new())
+            .await
+    }
+
+    /// Receive proofs, aborting if `cancel_token` is cancelled
+    ///
+    /// Cancellation is honoured while the proofs are being verified and signed and right before
+    /// the swap is sent. Any proofs already stored as pending are removed again. Once the swap
+    /// request has been sent it is always completed, as dropping it could lose the new proofs.
+    #[instrument(skip_all)]
+    pub async fn receive_proofs_with_cancel(
+        &self,
+        proofs: Proofs,
+        opts: ReceiveOptions,
+        memo: Option<String>,
+        cancel_token: CancellationToken,
     ) -> Result<Amount, Error> {
         let mint_url = &self.mint_url;
 
         self.refresh_keysets().await?;
 
+        self.auto_migrate_expiring_proofs().await;
+
         let active_keyset_id = self.fetch_active_keyset().await?.id;
 
         let keys = self.load_keyset_keys(active_keyset_id).await?;
@@ -57,7 +79,25 @@ impl Wallet {
             .map(|s| (s.x_only_public_key(&SECP256K1).0, s))
             .collect();
 
+        // Keys derived from the seed sign proofs locked to a receive key of this wallet
+        let locking_keys = proofs
+            .iter()
+            .filter_map(|proof| SpendingConditions::try_from(&proof.secret).ok())
+            .flat_map(|conditions| {
+                let mut pubkeys = conditions.pubkeys().unwrap_or_default();
+                pubkeys.extend(conditions.refund_keys().unwrap_or_default());
+                pubkeys
+            })
+            .map(|pubkey| pubkey.x_only_public_key())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let receive_keys = self.receive_signing_keys(&locking_keys).await?;
+        let mut used_receive_keys: HashMap<XOnlyPublicKey, &SecretKey> = HashMap::new();
+
         for proof in &mut proofs {
+            ensure_cdk!(!cancel_token.is_cancelled(), Error::Cancelled);
+
             // Verify that proof DLEQ is valid
             if proof.dleq.is_some() {
                 let keys = self.load_keyset_keys(proof.keyset_id).await?;
@@ -79,6 +119,14 @@ impl Wallet {
                 if let Ok(conditions) = conditions {
                     let mut pubkeys = conditions.pubkeys.unwrap_or_default();
 
+                    // After the locktime the refund keys can spend the proof
+                    if conditions
+                        .locktime
+                        .is_some_and(|locktime| locktime < unix_time())
+                    {
+                        pubkeys.extend(conditions.refund_keys.unwrap_or_default());
+                    }
+
                     match secret.kind() {
                         Kind::P2PK => {
                             let data_key = PublicKey::from_str(secret.secret_data().data())?;
@@ -94,8 +142,13 @@ impl Wallet {
                         }
                     }
                     for pubkey in pubkeys {
-                        if let Some(signing) = p2pk_signing_keys.get(&pubkey.x_only_public_key()) {
+                        let x_only_pubkey = pubkey.x_only_public_key();
+
+                        if let Some(signing) = p2pk_signing_keys.get(&x_only_pubkey) {
                             proof.sign_p2pk(signing.to_owned().clone())?;
+                        } else if let Some(signing) = receive_keys.get(&x_only_pubkey) {
+                            proof.sign_p2pk(signing.clone())?;
+                            used_receive_keys.insert(x_only_pubkey, signing);
                         }
                     }
 
@@ -122,12 +175,19 @@ impl Wallet {
 
         if sig_flag.eq(&SigFlag::SigAll) {
             for blinded_message in pre_swap.swap_request.outputs_mut() {
-                for signing_key in p2pk_signing_keys.values() {
+                for signing_key in p2pk_signing_keys.values().chain(used_receive_keys.values()) {
                     blinded_message.sign_p2pk(signing_key.to_owned().clone())?
                 }
             }
         }
 
+        if cancel_token.is_cancelled() {
+            tracing::info!("Receive cancelled before swap was sent");
+            // The pending token proofs were never spent, so they are dropped without archiving
+            self.localstore.update_proofs(vec![], proofs_ys).await?;
+            return Err(Error::Cancelled);
+        }
+
         let swap_response = self.client.post_swap(pre_swap.swap_request).await?;
 
         // Proof to keep
@@ -148,12 +208,11 @@ impl Wallet {
             .into_iter()
             .map(|proof| ProofInfo::new(proof, mint_url.clone(), State::Unspent, self.unit.clone()))
             .collect::<Result<Vec<ProofInfo>, _>>()?;
-        self.localstore
-            .update_proofs(
-                recv_proof_infos,
-                proofs_info.into_iter().map(|p| p.y).collect(),
-            )
-            .await?;
+        self.replace_spent_proofs(
+            recv_proof_infos,
+            proofs_info.into_iter().map(|p| p.y).collect(),
+        )
+        .await?;
 
         // Add transaction to store
         self.localstore
@@ -168,9 +227,12 @@ impl Wallet {
                 memo,
                 metadata: opts.metadata,
                 quote_id: None, // Receive transactions don't have a quote_id
+                status: TransactionStatus::Confirmed,
             })
             .await?;
 
+        self.record_fee(FeeKind::Input, pre_swap.fee, None).await;
+
         Ok(total_amount)
     }
 
@@ -203,6 +265,20 @@ impl Wallet {
         &self,
         encoded_token: &str,
         opts: ReceiveOptions,
+    ) -> Result<Amount, Error> {
+        self.receive_with_cancel(encoded_token, opts, CancellationToken::new())
+            .await
+    }
+
+    /// Receive, aborting if `cancel_token` is cancelled
+    ///
+    /// See [`Wallet::receive_proofs_with_cancel`] for the cancellation guarantees.
+    #[instrument(skip_all)]
+    pub async fn receive_with_cancel(
+        &self,
+        encoded_token: &str,
+        opts: ReceiveOptions,
+        cancel_token: CancellationToken,
     ) -> Result<Amount, Error> {
         let token = Token::from_str(encoded_token)?;
 
@@ -220,7 +296,7 @@ impl Wallet {
         ensure_cdk!(self.mint_url == token.mint_url()?, Error::IncorrectMint);
 
         let amount = self
-            .receive_proofs(proofs, opts, token.memo().clone())
+            .receive_proofs_with_cancel(proofs, opts, token.memo().clone(), cancel_token)
             .await?;
 
         Ok(amount)
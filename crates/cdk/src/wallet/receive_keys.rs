@@ -0,0 +1,276 @@
+//! Receive keys
+//!
+//! P2PK keys for receiving locked tokens are derived from the wallet seed at
+//! `m/129372'/2'/<index>'`. [`Wallet::next_receive_pubkey`] hands out a new key
+//! on every call, and receiving signs locked proofs with the derived keys on its
+//! own, so users do not have to keep track of raw keys.
+//!
+//! The index of every key handed out is stored under its public key, so
+//! receiving derives only the keys the proofs are locked to.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv};
+use bitcoin::{Network, XOnlyPublicKey};
+use cdk_common::database::{self, WalletDatabase};
+use tracing::instrument;
+
+use super::account::CASHU_PURPOSE;
+use crate::nuts::{PublicKey, SecretKey};
+use crate::{Error, Wallet, SECP256K1};
+
+/// Branch of the receive keys, next to the `0'` branch of the proof secrets
+const RECEIVE_KEY_BRANCH: u32 = 2;
+/// Key-value store primary namespace for wallet data
+const RECEIVE_KEYS_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for receive keys
+const RECEIVE_KEYS_SECONDARY_NAMESPACE: &str = "receive_keys";
+/// Key-value store secondary namespace for the index of each receive key handed out
+const RECEIVE_KEY_INDEXES_SECONDARY_NAMESPACE: &str = "receive_key_indexes";
+/// Key of the index of the next receive key
+const NEXT_INDEX_KEY: &str = "next_index";
+/// Keys past the next index checked when receiving
+///
+/// Covers keys handed out by another wallet restored from the same seed.
+const RECEIVE_KEY_LOOKAHEAD: u32 = 20;
+
+/// Derivation path of a receive key
+pub fn receive_key_derivation_path(index: u32) -> Result<DerivationPath, Error> {
+    Ok(DerivationPath::from(vec![
+        ChildNumber::from_hardened_idx(CASHU_PURPOSE)?,
+        ChildNumber::from_hardened_idx(RECEIVE_KEY_BRANCH)?,
+        ChildNumber::from_hardened_idx(index)?,
+    ]))
+}
+
+/// Locks serializing the updates of the receive keys, per wallet database
+static RECEIVE_KEY_LOCKS: OnceLock<Mutex<HashMap<usize, Arc<tokio::sync::Mutex<()>>>>> =
+    OnceLock::new();
+
+fn receive_key_lock(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+) -> Arc<tokio::sync::Mutex<()>> {
+    RECEIVE_KEY_LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(Arc::as_ptr(localstore) as *const () as usize)
+        .or_default()
+        .clone()
+}
+
+/// Receive keys at `indexes` derived from `seed`
+fn derive_receive_keys(
+    seed: &[u8; 64],
+    indexes: Range<u32>,
+) -> Result<Vec<(u32, SecretKey)>, Error> {
+    let branch = Xpriv::new_master(Network::Bitcoin, seed)?.derive_priv(
+        &SECP256K1,
+        &[
+            ChildNumber::from_hardened_idx(CASHU_PURPOSE)?,
+            ChildNumber::from_hardened_idx(RECEIVE_KEY_BRANCH)?,
+        ],
+    )?;
+
+    indexes
+        .map(|index| {
+            let xpriv =
+                branch.derive_priv(&SECP256K1, &[ChildNumber::from_hardened_idx(index)?])?;
+            Ok((index, SecretKey::from(xpriv.private_key)))
+        })
+        .collect()
+}
+
+impl Wallet {
+    /// Secret key of the receive key at `index`
+    pub fn receive_secret_key(&self, index: u32) -> Result<SecretKey, Error> {
+        let xpriv = Xpriv::new_master(Network::Bitcoin, &self.seed)?
+            .derive_priv(&SECP256K1, &receive_key_derivation_path(index)?)?;
+
+        Ok(SecretKey::from(xpriv.private_key))
+    }
+
+    /// Public key to receive a new locked token to
+    ///
+    /// Every call returns the next key, so a key is not reused between senders.
+    #[instrument(skip(self))]
+    pub async fn next_receive_pubkey(&self) -> Result<PublicKey, Error> {
+        let lock = receive_key_lock(&self.localstore);
+        let _guard = lock.lock().await;
+
+        let index = self.next_receive_key_index().await?;
+        let pubkey = self.receive_secret_key(index)?.public_key();
+
+        self.record_receive_key(&pubkey.x_only_public_key(), index)
+            .await?;
+        self.write_next_receive_key_index(index + 1).await?;
+
+        tracing::debug!("Handing out receive key {}", index);
+
+        Ok(pubkey)
+    }
+
+    async fn write_next_receive_key_index(&self, index: u32) -> Result<(), Error> {
+        self.localstore
+            .kv_write(
+                RECEIVE_KEYS_PRIMARY_NAMESPACE,
+                RECEIVE_KEYS_SECONDARY_NAMESPACE,
+                NEXT_INDEX_KEY,
+                &serde_json::to_vec(&index)?,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Store the index of a receive key handed out
+    async fn record_receive_key(&self, pubkey: &XOnlyPublicKey, index: u32) -> Result<(), Error> {
+        self.localstore
+            .kv_write(
+                RECEIVE_KEYS_PRIMARY_NAMESPACE,
+                RECEIVE_KEY_INDEXES_SECONDARY_NAMESPACE,
+                &pubkey.to_string(),
+                &serde_json::to_vec(&index)?,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Index of a receive key handed out, if it was recorded
+    async fn receive_key_index(&self, pubkey: &XOnlyPublicKey) -> Result<Option<u32>, Error> {
+        let index = self
+            .localstore
+            .kv_read(
+                RECEIVE_KEYS_PRIMARY_NAMESPACE,
+                RECEIVE_KEY_INDEXES_SECONDARY_NAMESPACE,
+                &pubkey.to_string(),
+            )
+            .await?;
+
+        Ok(index
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    /// Index of the next receive key handed out
+    async fn next_receive_key_index(&self) -> Result<u32, Error> {
+        let index = self
+            .localstore
+            .kv_read(
+                RECEIVE_KEYS_PRIMARY_NAMESPACE,
+                RECEIVE_KEYS_SECONDARY_NAMESPACE,
+                NEXT_INDEX_KEY,
+            )
+            .await?;
+
+        Ok(index
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// Secret keys of the `pubkeys` that are receive keys of this wallet
+    ///
+    /// Keys that were not recorded are looked for past the next index, where
+    /// another wallet restored from the same seed hands out keys. Keys found
+    /// there are recorded and not handed out again.
+    pub(crate) async fn receive_signing_keys(
+        &self,
+        pubkeys: &[XOnlyPublicKey],
+    ) -> Result<HashMap<XOnlyPublicKey, SecretKey>, Error> {
+        let mut signing_keys = HashMap::new();
+        let mut unknown = HashSet::new();
+
+        for pubkey in pubkeys {
+            match self.receive_key_index(pubkey).await? {
+                Some(index) => {
+                    signing_keys.insert(*pubkey, self.receive_secret_key(index)?);
+                }
+                None => {
+                    unknown.insert(*pubkey);
+                }
+            }
+        }
+
+        if unknown.is_empty() {
+            return Ok(signing_keys);
+        }
+
+        let lock = receive_key_lock(&self.localstore);
+        let _guard = lock.lock().await;
+
+        let next_index = self.next_receive_key_index().await?;
+
+        // Keys handed out before their index was stored are recorded on the way
+        let recorded = self
+            .localstore
+            .kv_list(
+                RECEIVE_KEYS_PRIMARY_NAMESPACE,
+                RECEIVE_KEY_INDEXES_SECONDARY_NAMESPACE,
+            )
+            .await?
+            .len();
+        let start = match u32::try_from(recorded).is_ok_and(|recorded| recorded >= next_index) {
+            true => next_index,
+            false => 0,
+        };
+
+        let keys = derive_receive_keys(
+            &self.seed,
+            start..next_index.saturating_add(RECEIVE_KEY_LOOKAHEAD),
+        )?
+        .into_iter()
+        .map(|(index, key)| (index, key.x_only_public_key(&SECP256K1).0, key))
+        .collect::<Vec<_>>();
+
+        let new_next_index = keys
+            .iter()
+            .filter(|(_, pubkey, _)| unknown.contains(pubkey))
+            .map(|(index, _, _)| index + 1)
+            .fold(next_index, u32::max);
+
+        for (index, pubkey, key) in keys {
+            if index < new_next_index {
+                self.record_receive_key(&pubkey, index).await?;
+            }
+
+            if unknown.contains(&pubkey) {
+                signing_keys.insert(pubkey, key);
+            }
+        }
+
+        if new_next_index > next_index {
+            tracing::debug!(
+                "Receive keys up to {} were handed out by another wallet",
+                new_next_index
+            );
+            self.write_next_receive_key_index(new_next_index).await?;
+        }
+
+        Ok(signing_keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_receive_keys() {
+        let seed = [7u8; 64];
+        let keys = derive_receive_keys(&seed, 1..4).unwrap();
+
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].0, 1);
+        assert_ne!(keys[0].1, keys[1].1);
+
+        let xpriv = Xpriv::new_master(Network::Bitcoin, &seed)
+            .unwrap()
+            .derive_priv(&SECP256K1, &receive_key_derivation_path(3).unwrap())
+            .unwrap();
+        assert_eq!(keys[2], (3, SecretKey::from(xpriv.private_key)));
+    }
+}
@@ -0,0 +1,223 @@
+//! Locked savings
+//!
+//! Ecash locked away until a date, so it cannot be spent on impulse. The proofs
+//! are swapped into P2PK conditions whose key has been thrown away, with a receive
+//! key of the wallet as refund key after the locktime. Until then neither the
+//! wallet nor anyone else can spend them, and afterwards only the wallet can.
+//!
+//! Locked proofs are kept in the wallet database key-value store instead of with
+//! the other proofs, so they are not counted in the balance or selected for
+//! sends. They are not recovered by a restore from seed, a backup of the database
+//! is needed to keep them.
+//!
+//! Savings past their unlock time are unlocked into the balance before sends and
+//! melts select proofs.
+
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::{Conditions, Proofs, SecretKey, SpendingConditions};
+use crate::wallet::ReceiveOptions;
+use crate::{ensure_cdk, Amount, Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const SAVINGS_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for locked savings
+const SAVINGS_SECONDARY_NAMESPACE: &str = "locked_savings";
+/// Memo of the transaction unlocking savings
+const UNLOCK_MEMO: &str = "Unlocked savings";
+
+/// Ecash locked until a date
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedSavings {
+    /// Id of the lock, the hex `Y` of its first proof
+    pub id: String,
+    /// Amount locked, without the fee of unlocking
+    pub amount: Amount,
+    /// Unix time the lock was created
+    pub created: u64,
+    /// Unix time after which the savings can be unlocked
+    pub unlock_time: u64,
+    /// Locked proofs
+    pub proofs: Proofs,
+}
+
+impl LockedSavings {
+    /// Whether the savings can be unlocked
+    pub fn is_mature(&self) -> bool {
+        unix_time() > self.unlock_time
+    }
+}
+
+impl Wallet {
+    /// Lock `amount` of the balance until `unlock_time`
+    ///
+    /// The fee of unlocking is locked as well, so `amount` is received when the
+    /// savings are unlocked.
+    #[instrument(skip(self))]
+    pub async fn lock_savings(
+        &self,
+        amount: Amount,
+        unlock_time: u64,
+    ) -> Result<LockedSavings, Error> {
+        ensure_cdk!(amount > Amount::ZERO, Error::AmountUndefined);
+
+        let refund_key = self.next_receive_pubkey().await?;
+        // Nobody holds the key of the lock, only the refund key can spend after the locktime
+        let lock_key = SecretKey::generate().public_key();
+
+        let conditions = Conditions::new(
+            Some(unlock_time),
+            None,
+            Some(vec![refund_key]),
+            None,
+            None,
+            None,
+        )?;
+
+        let proofs = self
+            .swap_from_unspent(
+                amount,
+                Some(SpendingConditions::new_p2pk(lock_key, Some(conditions))),
+                true,
+            )
+            .await?;
+
+        let ys = proofs.ys()?;
+        let savings = LockedSavings {
+            id: ys.first().ok_or(Error::InsufficientFunds)?.to_hex(),
+            amount,
+            created: unix_time(),
+            unlock_time,
+            proofs,
+        };
+
+        // Stored before the reserved proofs are removed, so they are never lost
+        self.localstore
+            .kv_write(
+                SAVINGS_PRIMARY_NAMESPACE,
+                SAVINGS_SECONDARY_NAMESPACE,
+                &savings.id,
+                &serde_json::to_vec(&savings)?,
+            )
+            .await?;
+        self.localstore.update_proofs(vec![], ys).await?;
+
+        tracing::info!(
+            "Locked {} {} of savings until {}",
+            amount,
+            self.unit,
+            unlock_time
+        );
+
+        Ok(savings)
+    }
+
+    /// Locked savings of the wallet
+    #[instrument(skip(self))]
+    pub async fn locked_savings(&self) -> Result<Vec<LockedSavings>, Error> {
+        let ids = self
+            .localstore
+            .kv_list(SAVINGS_PRIMARY_NAMESPACE, SAVINGS_SECONDARY_NAMESPACE)
+            .await?;
+
+        let mut savings = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(entry) = self
+                .localstore
+                .kv_read(SAVINGS_PRIMARY_NAMESPACE, SAVINGS_SECONDARY_NAMESPACE, &id)
+                .await?
+            {
+                savings.push(serde_json::from_slice::<LockedSavings>(&entry)?);
+            }
+        }
+
+        savings.sort_by_key(|savings| savings.unlock_time);
+
+        Ok(savings)
+    }
+
+    /// Total amount of locked savings
+    #[instrument(skip(self))]
+    pub async fn locked_savings_balance(&self) -> Result<Amount, Error> {
+        Amount::try_sum(
+            self.locked_savings()
+                .await?
+                .iter()
+                .map(|savings| savings.amount),
+        )
+        .map_err(Error::from)
+    }
+
+    /// Unlock the savings whose unlock time has passed into the balance
+    ///
+    /// Returns the amount received. Savings that fail to unlock stay locked and
+    /// are retried on the next call.
+    #[instrument(skip(self))]
+    pub async fn unlock_savings(&self) -> Result<Amount, Error> {
+        let mut unlocked = Amount::ZERO;
+
+        for savings in self.locked_savings().await? {
+            if !savings.is_mature() {
+                continue;
+            }
+
+            match self
+                .receive_proofs(
+                    savings.proofs.clone(),
+                    ReceiveOptions::default(),
+                    Some(UNLOCK_MEMO.to_string()),
+                )
+                .await
+            {
+                Ok(amount) => {
+                    unlocked += amount;
+                }
+                // Unlocked by another wallet sharing the seed
+                Err(Error::TokenAlreadySpent) => {
+                    tracing::warn!("Savings {} were already unlocked", savings.id);
+                }
+                Err(err) => {
+                    tracing::warn!("Could not unlock savings {}: {}", savings.id, err);
+                    continue;
+                }
+            }
+
+            self.localstore
+                .kv_remove(
+                    SAVINGS_PRIMARY_NAMESPACE,
+                    SAVINGS_SECONDARY_NAMESPACE,
+                    &savings.id,
+                )
+                .await?;
+        }
+
+        Ok(unlocked)
+    }
+
+    /// Unlock the savings whose unlock time has passed
+    ///
+    /// Runs before proofs are selected, failures are logged and leave the savings locked.
+    pub(crate) async fn auto_unlock_savings(&self) {
+        match self.unlock_savings().await {
+            Ok(amount) if amount > Amount::ZERO => {
+                tracing::info!(
+                    "Unlocked {} {} of savings at mint {}",
+                    amount,
+                    self.unit,
+                    self.mint_url
+                );
+            }
+            Ok(_) => (),
+            Err(err) => {
+                tracing::warn!(
+                    "Could not unlock savings at mint {}: {}",
+                    self.mint_url,
+                    err
+                );
+            }
+        }
+    }
+}
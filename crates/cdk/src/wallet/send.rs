@@ -3,10 +3,11 @@ use std::fmt::Debug;
 
 use cdk_common::nut02::KeySetInfosMethods;
 use cdk_common::util::unix_time;
-use cdk_common::wallet::{Transaction, TransactionDirection};
+use cdk_common::wallet::{Transaction, TransactionDirection, TransactionId, TransactionStatus};
 use tracing::instrument;
 
-use super::SendKind;
+use super::spending_limits::SpendReservation;
+use super::{SendKind, SpendKind};
 use crate::amount::SplitTarget;
 use crate::nuts::nut00::ProofsMethods;
 use crate::nuts::{Proofs, SpendingConditions, State, Token};
@@ -31,11 +32,31 @@ impl Wallet {
     ) -> Result<PreparedSend, Error> {
         tracing::info!("Preparing send");
 
+        let spend_reservation = self.check_spending_limits(SpendKind::Send, amount).await?;
+
         // If online send check mint for current keysets fees
         if opts.send_kind.is_online() {
             if let Err(e) = self.refresh_keysets().await {
                 tracing::error!("Error refreshing keysets: {:?}. Using stored keysets", e);
             }
+
+            self.auto_migrate_expiring_proofs().await;
+            self.auto_unlock_savings().await;
+        }
+
+        // Make sure the mint can enforce the requested spending conditions
+        if opts.send_kind.is_online() {
+            match &opts.conditions {
+                Some(SpendingConditions::P2PKConditions { .. }) => {
+                    self.ensure_capability("P2PK (NUT-11)", |c| c.supports_p2pk)
+                        .await?
+                }
+                Some(SpendingConditions::HTLCConditions { .. }) => {
+                    self.ensure_capability("HTLC (NUT-14)", |c| c.supports_htlc)
+                        .await?
+                }
+                None => (),
+            }
         }
 
         // Get keyset fees from localstore
@@ -195,6 +216,7 @@ impl Wallet {
             swap_fee,
             proofs_to_send,
             send_fee,
+            spend_reservation,
         })
     }
 }
@@ -208,6 +230,7 @@ pub struct PreparedSend {
     swap_fee: Amount,
     proofs_to_send: Proofs,
     send_fee: Amount,
+    spend_reservation: SpendReservation,
 }
 
 impl PreparedSend {
@@ -333,6 +356,7 @@ impl PreparedSend {
         let memo = send_memo.and_then(|m| if m.include_memo { Some(m.memo) } else { None });
 
         // Add transaction to store
+        let transaction_id = TransactionId::new(proofs_to_send.ys()?);
         self.wallet
             .localstore
             .add_transaction(Transaction {
@@ -346,16 +370,48 @@ impl PreparedSend {
                 memo: memo.clone(),
                 metadata: self.options.metadata,
                 quote_id: None, // Send transactions don't have a quote_id
+                status: TransactionStatus::Pending,
             })
             .await?;
 
-        // Create and return token
-        Ok(Token::new(
+        // The proofs are handed out, the send counts against the daily limit
+        self.spend_reservation.keep();
+
+        // Keys of the keysets in the token, embedded for offline verification
+        #[cfg(feature = "keyset-hints")]
+        let keyset_hints = match self.options.include_keyset_hints {
+            true => {
+                let mut keyset_hints = HashMap::new();
+                for keyset_id in proofs_to_send.iter().map(|p| p.keyset_id) {
+                    if !keyset_hints.contains_key(&keyset_id) {
+                        let keys = self.wallet.load_keyset_keys(keyset_id).await?;
+                        keyset_hints.insert(keyset_id, keys);
+                    }
+                }
+                Some(keyset_hints)
+            }
+            false => None,
+        };
+
+        // Create token
+        let mut token = Token::new(
             self.wallet.mint_url.clone(),
             proofs_to_send,
             memo,
             self.wallet.unit.clone(),
-        ))
+        );
+
+        #[cfg(feature = "keyset-hints")]
+        if let Some(keyset_hints) = keyset_hints {
+            token = token.with_keyset_hints(&keyset_hints);
+        }
+
+        // Keep an encrypted copy until the receiver claims it
+        if let Err(err) = self.wallet.claims_vault_store(transaction_id, &token).await {
+            tracing::error!("Could not store sent token in claims vault: {}", err);
+        }
+
+        Ok(token)
     }
 
     /// Cancel the prepared send
@@ -429,6 +485,13 @@ pub struct SendOptions {
     pub max_proofs: Option<usize>,
     /// Metadata
     pub metadata: HashMap<String, String>,
+    /// Embed the keys of the token keysets in the token
+    ///
+    /// Lets the receiver verify the token's DLEQ proofs without contacting the mint.
+    /// Only applies to V4 tokens. The hints are not part of NUT-00 and are
+    /// ignored by wallets built without the `keyset-hints` feature.
+    #[cfg(feature = "keyset-hints")]
+    pub include_keyset_hints: bool,
 }
 
 /// Send memo
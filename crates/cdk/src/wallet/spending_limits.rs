@@ -0,0 +1,408 @@
+//! Spending limits
+//!
+//! Sends and melts above a per transaction limit, or taking the outgoing total of
+//! the last 24 hours above a daily limit, need the approval of the
+//! [`ConfirmationHandler`] of the wallet. Bindings implement the handler to ask
+//! for a PIN or biometrics. Limits are kept per unit in the wallet database, so
+//! they apply to every mint of the unit and survive restarts.
+//!
+//! Spends are counted against the daily limit when they are checked, in a log
+//! kept next to the limits. Checks of a unit in a database are serialized, so
+//! concurrent spends cannot both pass the limit. Spends that do not go through
+//! release their entry.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use async_trait::async_trait;
+use cdk_common::database::{self, WalletDatabase};
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::mint_url::MintUrl;
+use crate::nuts::CurrencyUnit;
+use crate::{Amount, Error, Wallet};
+
+/// Key-value store primary namespace for wallet data
+const SPENDING_LIMITS_PRIMARY_NAMESPACE: &str = "cdk_wallet";
+/// Key-value store secondary namespace for spending limits
+const SPENDING_LIMITS_SECONDARY_NAMESPACE: &str = "spending_limits";
+/// Key-value store secondary namespace for the spends counted against the daily limit
+const SPEND_LOG_SECONDARY_NAMESPACE: &str = "spend_log";
+/// Window of the daily limit in seconds
+const DAILY_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+type Localstore = Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>;
+
+/// Locks serializing the spend checks of a unit, per wallet database
+static SPEND_LOCKS: OnceLock<Mutex<HashMap<(usize, CurrencyUnit), Arc<tokio::sync::Mutex<()>>>>> =
+    OnceLock::new();
+/// Logged spends that did not go through, removed from the log on its next check
+static RELEASED_SPENDS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+fn spend_lock(localstore: &Localstore, unit: &CurrencyUnit) -> Arc<tokio::sync::Mutex<()>> {
+    let key = (Arc::as_ptr(localstore) as *const () as usize, unit.clone());
+
+    SPEND_LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(key)
+        .or_default()
+        .clone()
+}
+
+fn released_spends() -> &'static Mutex<HashSet<String>> {
+    RELEASED_SPENDS.get_or_init(Default::default)
+}
+
+/// Release a logged spend that did not go through
+pub(crate) fn release_spend(id: &str) {
+    released_spends()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id.to_string());
+}
+
+/// Spend counted against the daily limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LoggedSpend {
+    id: String,
+    time: u64,
+    amount: Amount,
+}
+
+/// Entry of a checked spend in the spend log
+///
+/// Dropping the reservation releases the entry, spends that went through call
+/// [`SpendReservation::keep`].
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct SpendReservation {
+    id: Option<String>,
+}
+
+impl SpendReservation {
+    /// Id of the entry, `None` if no limits were set
+    pub(crate) fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Keep the spend counted, returns the id to release it with later
+    pub(crate) fn keep(mut self) -> Option<String> {
+        self.id.take()
+    }
+}
+
+impl Drop for SpendReservation {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            release_spend(&id);
+        }
+    }
+}
+
+/// Limits on the amounts a wallet spends without confirmation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendingLimits {
+    /// Largest amount of a single send or melt
+    pub per_transaction: Option<Amount>,
+    /// Largest amount sent and melted in the last 24 hours
+    pub daily: Option<Amount>,
+}
+
+/// Operation spending from the wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpendKind {
+    /// Ecash sent as a token
+    Send,
+    /// Ecash melted to pay a payment request
+    Melt,
+    /// Ecash swapped to proofs locked to spending conditions
+    Lock,
+}
+
+/// Limit a spend exceeds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExceededLimit {
+    /// [`SpendingLimits::per_transaction`]
+    PerTransaction,
+    /// [`SpendingLimits::daily`]
+    Daily,
+}
+
+impl fmt::Display for ExceededLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExceededLimit::PerTransaction => write!(f, "per transaction"),
+            ExceededLimit::Daily => write!(f, "daily"),
+        }
+    }
+}
+
+/// Spend the [`ConfirmationHandler`] is asked to approve
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendConfirmation {
+    /// Mint the ecash is spent from
+    pub mint_url: MintUrl,
+    /// Unit of the amounts
+    pub unit: CurrencyUnit,
+    /// Operation spending the ecash
+    pub kind: SpendKind,
+    /// Amount of the spend, including the fee reserve of a melt
+    pub amount: Amount,
+    /// Amount spent in the last 24 hours, without this spend
+    pub spent_today: Amount,
+    /// Limits of the wallet
+    pub limits: SpendingLimits,
+    /// Limit the spend exceeds
+    pub exceeded: ExceededLimit,
+}
+
+/// Approves spends above the limits of the wallet
+#[async_trait]
+pub trait ConfirmationHandler: Debug + Send + Sync {
+    /// Whether the spend may proceed
+    async fn confirm(&self, spend: &SpendConfirmation) -> Result<bool, Error>;
+}
+
+/// Spending limits of `unit`
+pub(crate) async fn read_spending_limits(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+    unit: &CurrencyUnit,
+) -> Result<SpendingLimits, Error> {
+    let limits = localstore
+        .kv_read(
+            SPENDING_LIMITS_PRIMARY_NAMESPACE,
+            SPENDING_LIMITS_SECONDARY_NAMESPACE,
+            &unit.to_string(),
+        )
+        .await?;
+
+    Ok(limits
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()?
+        .unwrap_or_default())
+}
+
+/// Store the spending limits of `unit`
+pub(crate) async fn write_spending_limits(
+    localstore: &Arc<dyn WalletDatabase<Err = database::Error> + Send + Sync>,
+    unit: &CurrencyUnit,
+    limits: SpendingLimits,
+) -> Result<(), Error> {
+    localstore
+        .kv_write(
+            SPENDING_LIMITS_PRIMARY_NAMESPACE,
+            SPENDING_LIMITS_SECONDARY_NAMESPACE,
+            &unit.to_string(),
+            &serde_json::to_vec(&limits)?,
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Spends of `unit` logged in the last 24 hours that were not released
+async fn read_spend_log(
+    localstore: &Localstore,
+    unit: &CurrencyUnit,
+) -> Result<Vec<LoggedSpend>, Error> {
+    let since = unix_time().saturating_sub(DAILY_WINDOW_SECS);
+
+    let log: Vec<LoggedSpend> = localstore
+        .kv_read(
+            SPENDING_LIMITS_PRIMARY_NAMESPACE,
+            SPEND_LOG_SECONDARY_NAMESPACE,
+            &unit.to_string(),
+        )
+        .await?
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()?
+        .unwrap_or_default();
+
+    let released = released_spends().lock().unwrap_or_else(|e| e.into_inner());
+
+    Ok(log
+        .into_iter()
+        .filter(|spend| spend.time >= since && !released.contains(&spend.id))
+        .collect())
+}
+
+/// Limit a spend of `amount` exceeds after `spent_today`
+fn exceeded_limit(
+    limits: &SpendingLimits,
+    amount: Amount,
+    spent_today: Amount,
+) -> Option<ExceededLimit> {
+    if limits.per_transaction.is_some_and(|limit| amount > limit) {
+        return Some(ExceededLimit::PerTransaction);
+    }
+
+    let daily_total = spent_today
+        .checked_add(amount)
+        .unwrap_or(Amount::from(u64::MAX));
+    if limits.daily.is_some_and(|limit| daily_total > limit) {
+        return Some(ExceededLimit::Daily);
+    }
+
+    None
+}
+
+impl Wallet {
+    /// Spending limits of the unit of this wallet
+    #[instrument(skip(self))]
+    pub async fn spending_limits(&self) -> Result<SpendingLimits, Error> {
+        read_spending_limits(&self.localstore, &self.unit).await
+    }
+
+    /// Set the spending limits of the unit of this wallet
+    ///
+    /// The limits apply to every wallet of the unit sharing the database.
+    #[instrument(skip(self))]
+    pub async fn set_spending_limits(&self, limits: SpendingLimits) -> Result<(), Error> {
+        write_spending_limits(&self.localstore, &self.unit, limits).await
+    }
+
+    /// Set the handler approving spends above the limits
+    ///
+    /// Without a handler spends above the limits are refused.
+    pub async fn set_confirmation_handler(&self, handler: Option<Arc<dyn ConfirmationHandler>>) {
+        *self.confirmation_handler.write().await = handler;
+    }
+
+    /// Amount spent in the unit of this wallet in the last 24 hours
+    ///
+    /// Only spends checked while spending limits were set are counted.
+    #[instrument(skip(self))]
+    pub async fn spent_today(&self) -> Result<Amount, Error> {
+        let log = read_spend_log(&self.localstore, &self.unit).await?;
+
+        Amount::try_sum(log.iter().map(|spend| spend.amount)).map_err(Error::from)
+    }
+
+    /// Check a spend against the spending limits, asking for confirmation above them
+    ///
+    /// The spend is logged against the daily limit until the returned
+    /// reservation is dropped without being kept.
+    pub(crate) async fn check_spending_limits(
+        &self,
+        kind: SpendKind,
+        amount: Amount,
+    ) -> Result<SpendReservation, Error> {
+        let limits = self.spending_limits().await?;
+
+        if limits == SpendingLimits::default() {
+            return Ok(SpendReservation { id: None });
+        }
+
+        // The log is read and written back under the lock of the unit
+        let lock = spend_lock(&self.localstore, &self.unit);
+        let _guard = lock.lock().await;
+
+        let mut log = read_spend_log(&self.localstore, &self.unit).await?;
+        let spent_today = Amount::try_sum(log.iter().map(|spend| spend.amount))?;
+
+        if let Some(exceeded) = exceeded_limit(&limits, amount, spent_today) {
+            self.confirm_spend(kind, amount, spent_today, limits, exceeded)
+                .await?;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        log.push(LoggedSpend {
+            id: id.clone(),
+            time: unix_time(),
+            amount,
+        });
+
+        self.localstore
+            .kv_write(
+                SPENDING_LIMITS_PRIMARY_NAMESPACE,
+                SPEND_LOG_SECONDARY_NAMESPACE,
+                &self.unit.to_string(),
+                &serde_json::to_vec(&log)?,
+            )
+            .await?;
+
+        Ok(SpendReservation { id: Some(id) })
+    }
+
+    /// Ask the confirmation handler to approve a spend above the limits
+    async fn confirm_spend(
+        &self,
+        kind: SpendKind,
+        amount: Amount,
+        spent_today: Amount,
+        limits: SpendingLimits,
+        exceeded: ExceededLimit,
+    ) -> Result<(), Error> {
+        let spend = SpendConfirmation {
+            mint_url: self.mint_url.clone(),
+            unit: self.unit.clone(),
+            kind,
+            amount,
+            spent_today,
+            limits,
+            exceeded,
+        };
+
+        let handler = self.confirmation_handler.read().await.clone();
+
+        let confirmed = match handler {
+            Some(handler) => handler.confirm(&spend).await?,
+            None => false,
+        };
+
+        tracing::info!(
+            "Spend of {} {} exceeds the {} limit, confirmed: {}",
+            amount,
+            self.unit,
+            exceeded,
+            confirmed
+        );
+
+        match confirmed {
+            true => Ok(()),
+            false => Err(Error::SpendingLimitExceeded(amount, exceeded.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exceeded_limit() {
+        let limits = SpendingLimits {
+            per_transaction: Some(Amount::from(100)),
+            daily: Some(Amount::from(500)),
+        };
+
+        assert_eq!(
+            exceeded_limit(&limits, Amount::from(100), Amount::ZERO),
+            None
+        );
+        assert_eq!(
+            exceeded_limit(&limits, Amount::from(101), Amount::ZERO),
+            Some(ExceededLimit::PerTransaction)
+        );
+        assert_eq!(
+            exceeded_limit(&limits, Amount::from(100), Amount::from(401)),
+            Some(ExceededLimit::Daily)
+        );
+        assert_eq!(
+            exceeded_limit(
+                &SpendingLimits::default(),
+                Amount::from(u64::MAX),
+                Amount::from(1)
+            ),
+            None
+        );
+    }
+}
@@ -11,6 +11,7 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use crate::error::Error;
+use crate::wallet::nostr_transport::{relay_client, RelayUse};
 use crate::wallet::streams::RecvFuture;
 
 #[allow(clippy::type_complexity)]
@@ -41,42 +42,44 @@ impl NostrPaymentEventStream {
 
         let init_cancel = cancel.clone();
         let init_fut = Box::pin(async move {
-            let client = nostr_sdk::Client::new(keys);
+            let client = relay_client(&relays, RelayUse::Read).await?;
 
-            for r in &relays {
-                client
-                    .add_read_relay(r.clone())
-                    .await
-                    .map_err(|e| Error::Custom(format!("Add relay {r}: {e}")))?;
-            }
-
-            client.connect().await;
-
-            // Subscribe to events addressed to `pubkey`
-            let filter = nostr_sdk::Filter::new().pubkey(pubkey);
-            client
+            // Subscribe to gift wraps addressed to `pubkey`
+            let filter = nostr_sdk::Filter::new()
+                .pubkey(pubkey)
+                .kind(nostr_sdk::Kind::GiftWrap);
+            let subscription_id = client
                 .subscribe(filter, None)
                 .await
-                .map_err(|e| Error::Custom(format!("Subscribe: {e}")))?;
+                .map_err(|e| Error::Custom(format!("Subscribe: {e}")))?
+                .val;
 
-            let client_for_handler = client.clone();
             // Pump notifications in a background task into the channel until cancelled
             let _bg = tokio::spawn(async move {
                 // Use handle_notifications to avoid manually wiring broadcast receivers
                 let tx_err = tx.clone();
+                let handler_subscription_id = subscription_id.clone();
                 let res = client
                     .handle_notifications(move |notification| {
                         let tx = tx.clone();
-                        let client = client_for_handler.clone();
+                        let keys = keys.clone();
+                        let subscription_id = handler_subscription_id.clone();
                         let cancel = init_cancel.clone();
                         async move {
                             if cancel.is_cancelled() {
                                 return Ok(true);
                             }
-                            if let nostr_sdk::RelayPoolNotification::Event { event, .. } =
-                                notification
+                            // The client is shared, events of other subscriptions are skipped
+                            if let nostr_sdk::RelayPoolNotification::Event {
+                                subscription_id: event_subscription_id,
+                                event,
+                                ..
+                            } = notification
                             {
-                                match client.unwrap_gift_wrap(&event).await {
+                                if event_subscription_id != subscription_id {
+                                    return Ok(false);
+                                }
+                                match cdk_nostr::unwrap_gift_wrap(&keys, &event).await {
                                     Ok(unwrapped) => {
                                         let rumor = unwrapped.rumor;
                                         match serde_json::from_str::<PaymentRequestPayload>(
@@ -111,6 +114,8 @@ impl NostrPaymentEventStream {
                     })
                     .await;
 
+                client.unsubscribe(&subscription_id).await;
+
                 if let Err(e) = res {
                     let _ = tx_err
                         .send(Err(Error::Custom(format!(
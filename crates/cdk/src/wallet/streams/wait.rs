@@ -4,6 +4,7 @@ use cdk_common::{Amount, Error, Proofs, SpendingConditions};
 use futures::future::BoxFuture;
 use futures::StreamExt;
 use tokio::time::{timeout, Duration};
+use tokio_util::sync::CancellationToken;
 
 use super::Wallet;
 
@@ -47,4 +48,22 @@ impl Wallet {
             .map_err(|_| Error::Timeout)?
         })
     }
+
+    /// Same as [`Wallet::wait_for_payment`] but resolves to [`Error::Cancelled`] as soon as
+    /// `cancel_token` is cancelled
+    pub fn wait_for_payment_with_cancel(
+        &self,
+        event: &MintQuote,
+        timeout_duration: Duration,
+        cancel_token: CancellationToken,
+    ) -> BoxFuture<'_, Result<Option<Amount>, Error>> {
+        let wait = self.wait_for_payment(event, timeout_duration);
+
+        Box::pin(async move {
+            tokio::select! {
+                result = wait => result,
+                _ = cancel_token.cancelled() => Err(Error::Cancelled),
+            }
+        })
+    }
 }
@@ -12,7 +12,6 @@ use crate::nuts::nut17::Kind;
 use crate::nuts::{nut01, nut05, nut07, nut23, CheckStateRequest, NotificationPayload};
 use crate::pub_sub::SubId;
 use crate::wallet::MintConnector;
-use crate::Wallet;
 
 #[derive(Debug, Hash, PartialEq, Eq)]
 enum UrlType {
@@ -93,7 +92,6 @@ pub async fn http_main<S: IntoIterator<Item = SubId>>(
     subscriptions: Arc<RwLock<HashMap<SubId, WsSubscriptionBody>>>,
     mut new_subscription_recv: mpsc::Receiver<SubId>,
     mut on_drop: mpsc::Receiver<SubId>,
-    _wallet: Arc<Wallet>,
 ) {
     let mut interval = time::interval(Duration::from_secs(2));
     let mut subscribed_to = SubscribedTo::new();
@@ -125,7 +123,6 @@ pub async fn http_main<S: IntoIterator<Item = SubId>>(
     subscriptions: Arc<RwLock<HashMap<SubId, WsSubscriptionBody>>>,
     mut new_subscription_recv: mpsc::Receiver<SubId>,
     mut on_drop: mpsc::Receiver<SubId>,
-    _wallet: Arc<Wallet>,
 ) {
     let mut subscribed_to = SubscribedTo::new();
 
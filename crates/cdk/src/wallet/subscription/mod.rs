@@ -5,9 +5,13 @@
 //! subscription manager that allows clients to subscribe to notifications from
 //! multiple mint servers using WebSocket or with a poll-based system, using
 //! the HTTP client.
+//!
+//! Wallets built with the default HTTP client share their connections: all
+//! their subscriptions to a mint are multiplexed over one WebSocket, or one
+//! poll loop, which is closed once no wallet or subscription uses it anymore.
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
 
 use cdk_common::subscription::Params;
 use tokio::sync::{mpsc, RwLock};
@@ -16,7 +20,6 @@ use tracing::error;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures;
 
-use super::Wallet;
 use crate::mint_url::MintUrl;
 use crate::pub_sub::SubId;
 use crate::wallet::MintConnector;
@@ -31,6 +34,15 @@ mod ws;
 
 type WsSubscriptionBody = (mpsc::Sender<NotificationPayload>, Params);
 
+/// Connections of the shared subscription managers, by mint and by whether HTTP is preferred
+type SharedConnections = Mutex<HashMap<(MintUrl, bool), Weak<SubscriptionClient>>>;
+
+static SHARED_CONNECTIONS: OnceLock<SharedConnections> = OnceLock::new();
+
+fn shared_connections() -> &'static SharedConnections {
+    SHARED_CONNECTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
 /// Subscription manager
 ///
 /// This structure should be instantiated once per wallet at most. It is
@@ -46,11 +58,15 @@ type WsSubscriptionBody = (mpsc::Sender<NotificationPayload>, Params);
 /// The subscribers have a simple-to-use interface, receiving an
 /// ActiveSubscription struct, which can be used to receive updates and to
 /// unsubscribe from updates automatically on the drop.
+///
+/// Connections are reference counted: they are kept open while the manager or
+/// any of its subscriptions is alive.
 #[derive(Debug, Clone)]
 pub struct SubscriptionManager {
-    all_connections: Arc<RwLock<HashMap<MintUrl, SubscriptionClient>>>,
+    all_connections: Arc<RwLock<HashMap<MintUrl, Arc<SubscriptionClient>>>>,
     http_client: Arc<dyn MintConnector + Send + Sync>,
     prefer_http: bool,
+    shared: bool,
 }
 
 impl SubscriptionManager {
@@ -60,67 +76,115 @@ impl SubscriptionManager {
             all_connections: Arc::new(RwLock::new(HashMap::new())),
             http_client,
             prefer_http,
+            shared: false,
+        }
+    }
+
+    /// Create a subscription manager sharing its connections within the process
+    ///
+    /// Subscriptions of all shared managers to the same mint, with the same
+    /// `prefer_http`, are multiplexed over a single connection. The connection
+    /// uses the `http_client` of the manager that opened it, so only managers
+    /// whose clients reach the mint the same way should be shared.
+    pub fn shared(http_client: Arc<dyn MintConnector + Send + Sync>, prefer_http: bool) -> Self {
+        Self {
+            shared: true,
+            ..Self::new(http_client, prefer_http)
         }
     }
 
     /// Subscribe to updates from a mint server with a given filter
-    pub async fn subscribe(
-        &self,
-        mint_url: MintUrl,
-        filter: Params,
-        wallet: Arc<Wallet>,
-    ) -> ActiveSubscription {
-        let subscription_clients = self.all_connections.read().await;
+    pub async fn subscribe(&self, mint_url: MintUrl, filter: Params) -> ActiveSubscription {
         let id = filter.id.clone();
-        if let Some(subscription_client) = subscription_clients.get(&mint_url) {
-            let (on_drop_notif, receiver) = subscription_client.subscribe(filter).await;
-            ActiveSubscription::new(receiver, id, on_drop_notif)
+        let subscription_client = self.connection(mint_url).await;
+        let (on_drop_notif, receiver) = subscription_client.subscribe(filter).await;
+
+        ActiveSubscription::new(receiver, id, on_drop_notif, subscription_client)
+    }
+
+    /// Connection to the mint, opened or joined on first use
+    async fn connection(&self, mint_url: MintUrl) -> Arc<SubscriptionClient> {
+        if let Some(subscription_client) = self.all_connections.read().await.get(&mint_url) {
+            return subscription_client.clone();
+        }
+
+        let shared_key = (mint_url.clone(), self.prefer_http);
+
+        if self.shared {
+            let shared_client = shared_connections()
+                .lock()
+                .expect("Shared connections lock poisoned")
+                .get(&shared_key)
+                .and_then(Weak::upgrade);
+
+            if let Some(subscription_client) = shared_client {
+                tracing::debug!("Joining the shared connection to {:?}", mint_url);
+                self.all_connections
+                    .write()
+                    .await
+                    .insert(mint_url, subscription_client.clone());
+                return subscription_client;
+            }
+        }
+
+        #[cfg(all(
+            not(feature = "http_subscription"),
+            feature = "mint",
+            not(target_arch = "wasm32")
+        ))]
+        let is_ws_support = self
+            .http_client
+            .get_mint_info()
+            .await
+            .map(|info| !info.nuts.nut17.supported.is_empty())
+            .unwrap_or_default();
+
+        #[cfg(any(
+            feature = "http_subscription",
+            not(feature = "mint"),
+            target_arch = "wasm32"
+        ))]
+        let is_ws_support = false;
+
+        let is_ws_support = if self.prefer_http {
+            false
         } else {
-            drop(subscription_clients);
-
-            #[cfg(all(
-                not(feature = "http_subscription"),
-                feature = "mint",
-                not(target_arch = "wasm32")
-            ))]
-            let is_ws_support = self
-                .http_client
-                .get_mint_info()
-                .await
-                .map(|info| !info.nuts.nut17.supported.is_empty())
-                .unwrap_or_default();
-
-            #[cfg(any(
-                feature = "http_subscription",
-                not(feature = "mint"),
-                target_arch = "wasm32"
-            ))]
-            let is_ws_support = false;
-
-            let is_ws_support = if self.prefer_http {
-                false
-            } else {
-                is_ws_support
-            };
-
-            tracing::debug!(
-                "Connect to {:?} to subscribe. WebSocket is supported ({})",
-                mint_url,
-                is_ws_support
-            );
-
-            let mut subscription_clients = self.all_connections.write().await;
-            let subscription_client = SubscriptionClient::new(
-                mint_url.clone(),
-                self.http_client.clone(),
-                is_ws_support,
-                wallet,
-            );
-            let (on_drop_notif, receiver) = subscription_client.subscribe(filter).await;
-            subscription_clients.insert(mint_url, subscription_client);
-
-            ActiveSubscription::new(receiver, id, on_drop_notif)
+            is_ws_support
+        };
+
+        tracing::debug!(
+            "Connect to {:?} to subscribe. WebSocket is supported ({})",
+            mint_url,
+            is_ws_support
+        );
+
+        let mut subscription_client = Arc::new(SubscriptionClient::new(
+            mint_url.clone(),
+            self.http_client.clone(),
+            is_ws_support,
+        ));
+
+        if self.shared {
+            let mut shared = shared_connections()
+                .lock()
+                .expect("Shared connections lock poisoned");
+            shared.retain(|_, connection| connection.strong_count() > 0);
+
+            // Another manager connected to the mint meanwhile
+            match shared.get(&shared_key).and_then(Weak::upgrade) {
+                Some(connected) => subscription_client = connected,
+                None => {
+                    shared.insert(shared_key, Arc::downgrade(&subscription_client));
+                }
+            }
         }
+
+        self.all_connections
+            .write()
+            .await
+            .entry(mint_url)
+            .or_insert(subscription_client)
+            .clone()
     }
 }
 
@@ -144,6 +208,8 @@ pub struct ActiveSubscription {
     sub_id: Option<SubId>,
     on_drop_notif: mpsc::Sender<SubId>,
     receiver: mpsc::Receiver<NotificationPayload>,
+    /// Keeps the connection open while the subscription is alive
+    _subscription_client: Arc<SubscriptionClient>,
 }
 
 impl ActiveSubscription {
@@ -151,11 +217,13 @@ impl ActiveSubscription {
         receiver: mpsc::Receiver<NotificationPayload>,
         sub_id: SubId,
         on_drop_notif: mpsc::Sender<SubId>,
+        subscription_client: Arc<SubscriptionClient>,
     ) -> Self {
         Self {
             sub_id: Some(sub_id),
             on_drop_notif,
             receiver,
+            _subscription_client: subscription_client,
         }
     }
 
@@ -199,7 +267,6 @@ impl SubscriptionClient {
         url: MintUrl,
         http_client: Arc<dyn MintConnector + Send + Sync>,
         prefer_ws_method: bool,
-        wallet: Arc<Wallet>,
     ) -> Self {
         let subscriptions = Arc::new(RwLock::new(HashMap::new()));
         let (new_subscription_notif, new_subscription_recv) = mpsc::channel(100);
@@ -216,7 +283,6 @@ impl SubscriptionClient {
                 subscriptions,
                 new_subscription_recv,
                 on_drop_recv,
-                wallet,
             ),
         }
     }
@@ -229,7 +295,6 @@ impl SubscriptionClient {
         subscriptions: Arc<RwLock<HashMap<SubId, WsSubscriptionBody>>>,
         new_subscription_recv: mpsc::Receiver<SubId>,
         on_drop_recv: mpsc::Receiver<SubId>,
-        wallet: Arc<Wallet>,
     ) -> Option<JoinHandle<()>> {
         #[cfg(any(
             feature = "http_subscription",
@@ -241,7 +306,6 @@ impl SubscriptionClient {
             subscriptions,
             new_subscription_recv,
             on_drop_recv,
-            wallet,
         );
 
         #[cfg(all(
@@ -256,7 +320,6 @@ impl SubscriptionClient {
                 subscriptions,
                 new_subscription_recv,
                 on_drop_recv,
-                wallet,
             )
         } else {
             Self::http_worker(
@@ -264,7 +327,6 @@ impl SubscriptionClient {
                 subscriptions,
                 new_subscription_recv,
                 on_drop_recv,
-                wallet,
             )
         }
     }
@@ -294,7 +356,6 @@ impl SubscriptionClient {
         subscriptions: Arc<RwLock<HashMap<SubId, WsSubscriptionBody>>>,
         new_subscription_recv: mpsc::Receiver<SubId>,
         on_drop: mpsc::Receiver<SubId>,
-        wallet: Arc<Wallet>,
     ) -> Option<JoinHandle<()>> {
         let http_worker = http::http_main(
             vec![],
@@ -302,7 +363,6 @@ impl SubscriptionClient {
             subscriptions,
             new_subscription_recv,
             on_drop,
-            wallet,
         );
 
         #[cfg(target_arch = "wasm32")]
@@ -332,7 +392,6 @@ impl SubscriptionClient {
         subscriptions: Arc<RwLock<HashMap<SubId, WsSubscriptionBody>>>,
         new_subscription_recv: mpsc::Receiver<SubId>,
         on_drop: mpsc::Receiver<SubId>,
-        wallet: Arc<Wallet>,
     ) -> Option<JoinHandle<()>> {
         Some(tokio::spawn(ws::ws_main(
             http_client,
@@ -340,7 +399,6 @@ impl SubscriptionClient {
             subscriptions,
             new_subscription_recv,
             on_drop,
-            wallet,
         )))
     }
 }
@@ -352,3 +410,63 @@ impl Drop for SubscriptionClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::nuts::nut17::Kind;
+    use crate::wallet::HttpClient;
+
+    fn http_client(mint_url: &MintUrl) -> Arc<dyn MintConnector + Send + Sync> {
+        #[cfg(feature = "auth")]
+        let client = HttpClient::new(mint_url.clone(), None);
+        #[cfg(not(feature = "auth"))]
+        let client = HttpClient::new(mint_url.clone());
+
+        Arc::new(client)
+    }
+
+    #[tokio::test]
+    async fn test_shared_connection_closes_with_last_user() {
+        let mint_url = MintUrl::from_str("https://shared-connection.example").unwrap();
+        let first = SubscriptionManager::shared(http_client(&mint_url), true);
+        let second = SubscriptionManager::shared(http_client(&mint_url), true);
+
+        let first_connection = first.connection(mint_url.clone()).await;
+        let second_connection = second.connection(mint_url.clone()).await;
+        assert!(Arc::ptr_eq(&first_connection, &second_connection));
+
+        let connection = Arc::downgrade(&first_connection);
+        drop(first_connection);
+        drop(second_connection);
+
+        let subscription = second
+            .subscribe(
+                mint_url.clone(),
+                Params::new(Kind::ProofState, vec![], "shared".into()),
+            )
+            .await;
+
+        drop(first);
+        drop(second);
+        // The subscription keeps the connection open after its manager is dropped
+        assert!(connection.upgrade().is_some());
+
+        drop(subscription);
+        assert!(connection.upgrade().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unshared_managers_connect_separately() {
+        let mint_url = MintUrl::from_str("https://unshared-connection.example").unwrap();
+        let first = SubscriptionManager::new(http_client(&mint_url), true);
+        let second = SubscriptionManager::new(http_client(&mint_url), true);
+
+        assert!(!Arc::ptr_eq(
+            &first.connection(mint_url.clone()).await,
+            &second.connection(mint_url).await
+        ));
+    }
+}
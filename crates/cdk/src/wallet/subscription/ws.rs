@@ -3,20 +3,76 @@ use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 
 use cdk_common::subscription::Params;
-use cdk_common::ws::{WsMessageOrResponse, WsMethodRequest, WsRequest, WsUnsubscribeRequest};
+use cdk_common::util::unix_time;
+use cdk_common::ws::{
+    WsEncoding, WsFrame, WsMessageOrResponse, WsMethodRequest, WsRequest, WsUnsubscribeRequest,
+    CBOR_SUBPROTOCOL,
+};
 use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
 use tokio::sync::{mpsc, RwLock};
-use tokio_tungstenite::connect_async;
-use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::error::ProtocolError;
+use tokio_tungstenite::tungstenite::http::header::SEC_WEBSOCKET_PROTOCOL;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 
 use super::http::http_main;
 use super::WsSubscriptionBody;
 use crate::mint_url::MintUrl;
 use crate::pub_sub::SubId;
 use crate::wallet::MintConnector;
-use crate::Wallet;
 
 const MAX_ATTEMPT_FALLBACK_HTTP: usize = 10;
+/// Seconds before the disconnect missed notifications are asked from, as the
+/// clocks of the mint and the wallet may differ
+const REPLAY_CLOCK_MARGIN: u64 = 30;
+
+fn frame_to_message(frame: WsFrame) -> Message {
+    match frame {
+        WsFrame::Text(text) => Message::Text(text.into()),
+        WsFrame::Binary(bytes) => Message::Binary(bytes.into()),
+    }
+}
+
+/// Connect to the websocket of a mint, asking for CBOR messages while `request_cbor` is set
+///
+/// Mints that do not know the CBOR subprotocol fail the handshake, `request_cbor` is cleared
+/// and they are connected to again with JSON messages.
+async fn connect(
+    url: &str,
+    request_cbor: &mut bool,
+) -> Result<(WebSocketStream<MaybeTlsStream<TcpStream>>, WsEncoding), WsError> {
+    loop {
+        let mut request = url.into_client_request()?;
+        if *request_cbor {
+            request.headers_mut().insert(
+                SEC_WEBSOCKET_PROTOCOL,
+                HeaderValue::from_static(CBOR_SUBPROTOCOL),
+            );
+        }
+
+        match connect_async(request).await {
+            Ok((ws_stream, response)) => {
+                let encoding = WsEncoding::from_subprotocol(
+                    response
+                        .headers()
+                        .get(SEC_WEBSOCKET_PROTOCOL)
+                        .and_then(|protocol| protocol.to_str().ok()),
+                );
+                return Ok((ws_stream, encoding));
+            }
+            Err(WsError::Protocol(ProtocolError::SecWebSocketSubProtocolError(_)))
+                if *request_cbor =>
+            {
+                tracing::debug!("{} does not support CBOR messages, using JSON", url);
+                *request_cbor = false;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
 
 #[inline]
 pub async fn ws_main(
@@ -25,7 +81,6 @@ pub async fn ws_main(
     subscriptions: Arc<RwLock<HashMap<SubId, WsSubscriptionBody>>>,
     mut new_subscription_recv: mpsc::Receiver<SubId>,
     mut on_drop: mpsc::Receiver<SubId>,
-    wallet: Arc<Wallet>,
 ) {
     let mut url = mint_url
         .join_paths(&["v1", "ws"])
@@ -41,11 +96,15 @@ pub async fn ws_main(
 
     let mut active_subscriptions = HashMap::<SubId, mpsc::Sender<_>>::new();
     let mut failure_count = 0;
+    // Cleared once the mint turns out not to know the CBOR subprotocol
+    let mut request_cbor = true;
+    // Unix time the last connection was lost
+    let mut disconnected_at: Option<u64> = None;
 
     loop {
         tracing::debug!("Connecting to {}", url);
-        let ws_stream = match connect_async(&url).await {
-            Ok((ws_stream, _)) => ws_stream,
+        let (ws_stream, encoding) = match connect(&url, &mut request_cbor).await {
+            Ok(connection) => connection,
             Err(err) => {
                 failure_count += 1;
                 tracing::error!("Could not connect to server: {:?}", err);
@@ -60,27 +119,26 @@ pub async fn ws_main(
                         subscriptions,
                         new_subscription_recv,
                         on_drop,
-                        wallet,
                     )
                     .await;
                 }
                 continue;
             }
         };
-        tracing::debug!("Connected to {}", url);
+        tracing::debug!("Connected to {} with {:?} messages", url, encoding);
 
         let (mut write, mut read) = ws_stream.split();
         let req_id = AtomicUsize::new(0);
 
-        let get_sub_request = |params: Params| -> Option<(usize, String)> {
+        let get_sub_request = |params: Params| -> Option<(usize, Message)> {
             let request: WsRequest = (
                 WsMethodRequest::Subscribe(params),
                 req_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
             )
                 .into();
 
-            match serde_json::to_string(&request) {
-                Ok(json) => Some((request.id, json)),
+            match encoding.encode(&request) {
+                Ok(frame) => Some((request.id, frame_to_message(frame))),
                 Err(err) => {
                     tracing::error!("Could not serialize subscribe message: {:?}", err);
                     None
@@ -88,15 +146,15 @@ pub async fn ws_main(
             }
         };
 
-        let get_unsub_request = |sub_id: SubId| -> Option<String> {
+        let get_unsub_request = |sub_id: SubId| -> Option<Message> {
             let request: WsRequest = (
                 WsMethodRequest::Unsubscribe(WsUnsubscribeRequest { sub_id }),
                 req_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
             )
                 .into();
 
-            match serde_json::to_string(&request) {
-                Ok(json) => Some(json),
+            match encoding.encode(&request) {
+                Ok(frame) => Some(frame_to_message(frame)),
                 Err(err) => {
                     tracing::error!("Could not serialize unsubscribe message: {:?}", err);
                     None
@@ -104,16 +162,18 @@ pub async fn ws_main(
             }
         };
 
-        // Websocket reconnected, restore all subscriptions
+        // Websocket reconnected, restore all subscriptions and ask for the
+        // notifications missed while disconnected
         let mut subscription_requests = HashSet::new();
+        let since = disconnected_at.map(|at| at.saturating_sub(REPLAY_CLOCK_MARGIN));
 
         let read_subscriptions = subscriptions.read().await;
         for (sub_id, _) in active_subscriptions.iter() {
             if let Some(Some((req_id, req))) = read_subscriptions
                 .get(sub_id)
-                .map(|(_, params)| get_sub_request(params.clone()))
+                .map(|(_, params)| get_sub_request(params.clone().with_since(since)))
             {
-                let _ = write.send(Message::Text(req.into())).await;
+                let _ = write.send(req).await;
                 subscription_requests.insert(req_id);
             }
         }
@@ -126,11 +186,12 @@ pub async fn ws_main(
                         Ok(msg) => msg,
                         Err(_) => break,
                     };
-                    let msg = match msg {
-                        Message::Text(msg) => msg,
+                    let frame = match msg {
+                        Message::Text(msg) => WsFrame::Text(msg.to_string()),
+                        Message::Binary(msg) => WsFrame::Binary(msg.to_vec()),
                         _ => continue,
                     };
-                    let msg = match serde_json::from_str::<WsMessageOrResponse>(&msg) {
+                    let msg = match encoding.decode::<WsMessageOrResponse>(&frame) {
                         Ok(msg) => msg,
                         Err(_) => continue,
                     };
@@ -164,7 +225,6 @@ pub async fn ws_main(
                                         subscriptions,
                                         new_subscription_recv,
                                         on_drop,
-                                        wallet,
                                     )
                                     .await;
                                 }
@@ -184,8 +244,8 @@ pub async fn ws_main(
                     };
                     tracing::debug!("Subscribing to {:?}", sub.1);
                     active_subscriptions.insert(subid, sub.0.clone());
-                    if let Some((req_id, json)) = get_sub_request(sub.1.clone()) {
-                        let _ = write.send(Message::Text(json.into())).await;
+                    if let Some((req_id, message)) = get_sub_request(sub.1.clone()) {
+                        let _ = write.send(message).await;
                         subscription_requests.insert(req_id);
                     }
                 },
@@ -195,11 +255,86 @@ pub async fn ws_main(
                         drop(sub);
                     }
                     tracing::debug!("Unsubscribing from {:?}", subid);
-                    if let Some(json) = get_unsub_request(subid) {
-                        let _ = write.send(Message::Text(json.into())).await;
+                    if let Some(message) = get_unsub_request(subid) {
+                        let _ = write.send(message).await;
                     }
                 }
             }
         }
+
+        disconnected_at = Some(unix_time());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+
+    use super::*;
+
+    /// Serve websocket handshakes, negotiating CBOR if `support_cbor`, and
+    /// report whether each handshake asked for it
+    async fn serve(support_cbor: bool) -> (String, mpsc::UnboundedReceiver<bool>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/v1/ws", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let sender = sender.clone();
+                let _ = tokio_tungstenite::accept_hdr_async(
+                    stream,
+                    move |request: &Request,
+                          mut response: Response|
+                          -> Result<Response, ErrorResponse> {
+                        let asked_cbor = request.headers().get(SEC_WEBSOCKET_PROTOCOL).is_some();
+                        let _ = sender.send(asked_cbor);
+                        if support_cbor && asked_cbor {
+                            response.headers_mut().insert(
+                                SEC_WEBSOCKET_PROTOCOL,
+                                HeaderValue::from_static(CBOR_SUBPROTOCOL),
+                            );
+                        }
+                        Ok(response)
+                    },
+                )
+                .await;
+            }
+        });
+
+        (url, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_connect_negotiates_cbor() {
+        let (url, mut handshakes) = serve(true).await;
+        let mut request_cbor = true;
+
+        let (_, encoding) = connect(&url, &mut request_cbor).await.unwrap();
+
+        assert_eq!(encoding, WsEncoding::Cbor);
+        assert!(request_cbor);
+        assert_eq!(handshakes.recv().await, Some(true));
+        assert!(handshakes.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_falls_back_to_json() {
+        let (url, mut handshakes) = serve(false).await;
+        let mut request_cbor = true;
+
+        let (_, encoding) = connect(&url, &mut request_cbor).await.unwrap();
+
+        assert_eq!(encoding, WsEncoding::Json);
+        assert!(!request_cbor);
+        // The rejected CBOR handshake, then the JSON one
+        assert_eq!(handshakes.recv().await, Some(true));
+        assert_eq!(handshakes.recv().await, Some(false));
+
+        // Later connections go straight to JSON
+        let (_, encoding) = connect(&url, &mut request_cbor).await.unwrap();
+        assert_eq!(encoding, WsEncoding::Json);
+        assert_eq!(handshakes.recv().await, Some(false));
     }
 }
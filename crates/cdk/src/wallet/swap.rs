@@ -1,4 +1,5 @@
 use cdk_common::nut02::KeySetInfosMethods;
+use cdk_common::wallet::FeeKind;
 use tracing::instrument;
 
 use crate::amount::SplitTarget;
@@ -24,19 +25,38 @@ impl Wallet {
         self.refresh_keysets().await?;
 
         tracing::info!("Swapping");
-        let mint_url = &self.mint_url;
-        let unit = &self.unit;
 
         let pre_swap = self
             .create_swap(
                 amount,
                 amount_split_target.clone(),
-                input_proofs.clone(),
+                input_proofs,
                 spending_conditions.clone(),
                 include_fees,
             )
             .await?;
 
+        self.complete_swap(pre_swap, amount, amount_split_target, spending_conditions)
+            .await
+    }
+
+    /// Post a swap created with [`Wallet::create_swap`] and store the resulting proofs
+    ///
+    /// Proofs of `amount` are returned and stored as reserved, the change is stored
+    /// as unspent and the inputs are removed.
+    pub(crate) async fn complete_swap(
+        &self,
+        pre_swap: PreSwap,
+        amount: Option<Amount>,
+        amount_split_target: SplitTarget,
+        spending_conditions: Option<SpendingConditions>,
+    ) -> Result<Option<Proofs>, Error> {
+        let mint_url = &self.mint_url;
+        let unit = &self.unit;
+
+        // Remove spent proofs used as inputs
+        let deleted_ys = pre_swap.swap_request.inputs().ys()?;
+
         let swap_response = self.client.post_swap(pre_swap.swap_request).await?;
 
         let active_keyset_id = pre_swap.pre_mint_secrets.keyset_id;
@@ -124,15 +144,10 @@ impl Wallet {
             .collect::<Result<Vec<ProofInfo>, _>>()?;
         added_proofs.extend(keep_proofs);
 
-        // Remove spent proofs used as inputs
-        let deleted_ys = input_proofs
-            .into_iter()
-            .map(|proof| proof.y())
-            .collect::<Result<Vec<PublicKey>, _>>()?;
+        self.replace_spent_proofs(added_proofs, deleted_ys).await?;
+
+        self.record_fee(FeeKind::Input, pre_swap.fee, None).await;
 
-        self.localstore
-            .update_proofs(added_proofs, deleted_ys)
-            .await?;
         Ok(send_proofs)
     }
 
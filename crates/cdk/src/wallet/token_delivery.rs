@@ -0,0 +1,416 @@
+//! Token delivery over nostr
+//!
+//! Tokens are sent to a nostr pubkey as NIP-17 private direct messages. Relays reject
+//! events above a size limit, which tokens with thousands of proofs exceed, so
+//! such tokens are encrypted and uploaded to a blob server such as a Blossom
+//! server. The message then only carries the blob url and its decryption key.
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use bitcoin::base64::engine::general_purpose;
+use bitcoin::base64::Engine as _;
+use bitcoin::hashes::{sha256, Hash};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use getrandom::getrandom;
+use nostr_sdk::{EventBuilder, JsonUtil, Keys, Kind, Tag, Timestamp};
+use reqwest::header::AUTHORIZATION;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use url::Url;
+
+use super::nostr_transport::{
+    nostr_error, nostr_keys, nostr_public_key, relay_client, EventKind, RelayUse,
+};
+use crate::nuts::{PublicKey, SecretKey, Token};
+use crate::util::hex;
+use crate::wallet::util::token_from_text;
+use crate::{ensure_cdk, Error};
+
+/// Longest token sent inline in a direct message, well below common relay event limits
+pub const MAX_INLINE_TOKEN_LEN: usize = 32 * 1024;
+/// Prefix of direct messages carrying a [`TokenBlobReference`]
+const BLOB_REFERENCE_PREFIX: &str = "ecash-blob:";
+/// Validity of a Blossom authorization event in seconds
+const BLOSSOM_AUTH_EXPIRY: u64 = 300;
+/// ChaCha20-Poly1305 nonce length
+const NONCE_LEN: usize = 12;
+/// Largest token blob fetched, larger responses are cut off instead of buffered
+pub const MAX_TOKEN_BLOB_LEN: usize = 16 * 1024 * 1024;
+
+/// Stores encrypted token blobs
+#[async_trait]
+pub trait BlobStore: Debug + Send + Sync {
+    /// Upload a blob and return the url it can be fetched from
+    async fn upload(&self, blob: Vec<u8>) -> Result<String, Error>;
+}
+
+/// Blossom server used as a [`BlobStore`]
+#[derive(Debug, Clone)]
+pub struct BlossomServer {
+    url: Url,
+    keys: Keys,
+    client: Client,
+}
+
+/// Blob descriptor returned by a Blossom server
+#[derive(Debug, Deserialize)]
+struct BlobDescriptor {
+    url: String,
+}
+
+impl BlossomServer {
+    /// Blossom server at `url`, uploads are authorized with `secret_key`
+    pub fn new(url: Url, secret_key: &SecretKey) -> Result<Self, Error> {
+        Ok(Self {
+            url,
+            keys: nostr_keys(secret_key)?,
+            client: Client::new(),
+        })
+    }
+
+    /// Authorization header value allowing the upload of a blob with the given hash
+    fn upload_authorization(&self, sha256: &str) -> Result<String, Error> {
+        let expiration = Timestamp::now().as_u64() + BLOSSOM_AUTH_EXPIRY;
+
+        let tags = [
+            vec!["t".to_string(), "upload".to_string()],
+            vec!["x".to_string(), sha256.to_string()],
+            vec!["expiration".to_string(), expiration.to_string()],
+        ]
+        .into_iter()
+        .map(Tag::parse)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::Custom(format!("Invalid Blossom tag: {e}")))?;
+
+        let event = EventBuilder::new(Kind::from(EventKind::BlossomAuth), "Upload ecash token")
+            .tags(tags)
+            .sign_with_keys(&self.keys)
+            .map_err(|e| Error::Custom(format!("Sign Blossom authorization: {e}")))?;
+
+        Ok(format!(
+            "Nostr {}",
+            general_purpose::STANDARD.encode(event.as_json())
+        ))
+    }
+}
+
+#[async_trait]
+impl BlobStore for BlossomServer {
+    async fn upload(&self, blob: Vec<u8>) -> Result<String, Error> {
+        let sha256 = sha256::Hash::hash(&blob).to_string();
+
+        let url = self
+            .url
+            .join("upload")
+            .map_err(|e| Error::Custom(format!("Invalid Blossom url: {e}")))?;
+
+        let response = self
+            .client
+            .put(url)
+            .header(AUTHORIZATION, self.upload_authorization(&sha256)?)
+            .body(blob)
+            .send()
+            .await
+            .map_err(|e| Error::HttpError(None, e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::HttpError(
+                Some(status.as_u16()),
+                response.text().await.unwrap_or_default(),
+            ));
+        }
+
+        let descriptor: BlobDescriptor = response
+            .json()
+            .await
+            .map_err(|e| Error::HttpError(None, e.to_string()))?;
+
+        Ok(descriptor.url)
+    }
+}
+
+/// Encrypted token stored as a blob
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBlobReference {
+    /// Url of the blob
+    pub url: String,
+    /// Hex SHA-256 of the blob
+    pub sha256: String,
+    /// Hex ChaCha20-Poly1305 key of the blob
+    pub key: String,
+}
+
+impl TokenBlobReference {
+    /// Fetch the blob, check its hash and decrypt the token
+    ///
+    /// Blobs larger than [`MAX_TOKEN_BLOB_LEN`] are rejected.
+    #[instrument(skip(self))]
+    pub async fn fetch(&self) -> Result<String, Error> {
+        let mut response = Client::new()
+            .get(&self.url)
+            .send()
+            .await
+            .map_err(|e| Error::HttpError(None, e.to_string()))?;
+
+        let status = response.status();
+        ensure_cdk!(
+            status.is_success(),
+            Error::HttpError(Some(status.as_u16()), format!("Fetch blob {}", self.url))
+        );
+
+        let too_large = || Error::Custom(format!("Token blob exceeds {MAX_TOKEN_BLOB_LEN} bytes"));
+
+        ensure_cdk!(
+            response
+                .content_length()
+                .is_none_or(|len| len <= MAX_TOKEN_BLOB_LEN as u64),
+            too_large()
+        );
+
+        let mut blob = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| Error::HttpError(None, e.to_string()))?
+        {
+            ensure_cdk!(blob.len() + chunk.len() <= MAX_TOKEN_BLOB_LEN, too_large());
+            blob.extend_from_slice(&chunk);
+        }
+
+        self.decrypt(&blob)
+    }
+
+    /// Check the hash of a fetched blob and decrypt the token
+    fn decrypt(&self, blob: &[u8]) -> Result<String, Error> {
+        ensure_cdk!(
+            sha256::Hash::hash(&blob).to_string() == self.sha256,
+            Error::Custom("Token blob does not match its hash".to_string())
+        );
+        ensure_cdk!(
+            blob.len() > NONCE_LEN,
+            Error::Custom("Token blob is too short".to_string())
+        );
+
+        let key = hex::decode(&self.key)?;
+        ensure_cdk!(
+            key.len() == 32,
+            Error::Custom("Invalid token blob key".to_string())
+        );
+
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let token = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::Custom("Could not decrypt token blob".to_string()))?;
+
+        String::from_utf8(token).map_err(|_| Error::Custom("Invalid token blob".to_string()))
+    }
+}
+
+/// Content of a direct message delivering a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenDelivery {
+    /// Token sent in the message
+    Inline(String),
+    /// Token uploaded as an encrypted blob
+    Blob(TokenBlobReference),
+}
+
+impl TokenDelivery {
+    /// Deliver `token` inline, or through `blob_store` when it is too large for a message
+    #[instrument(skip_all)]
+    pub async fn new(token: &Token, blob_store: Option<&dyn BlobStore>) -> Result<Self, Error> {
+        let token = token.to_string();
+
+        if token.len() <= MAX_INLINE_TOKEN_LEN {
+            return Ok(Self::Inline(token));
+        }
+
+        let blob_store = blob_store.ok_or(Error::Custom(format!(
+            "Token of {} bytes is too large for a message and no blob server is set",
+            token.len()
+        )))?;
+
+        let mut key = [0u8; 32];
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom(&mut key).map_err(|e| Error::Custom(e.to_string()))?;
+        getrandom(&mut nonce).map_err(|e| Error::Custom(e.to_string()))?;
+
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), token.as_bytes())
+            .map_err(|_| Error::Custom("Could not encrypt token".to_string()))?;
+
+        let mut blob = nonce.to_vec();
+        blob.extend(ciphertext);
+
+        let sha256 = sha256::Hash::hash(&blob).to_string();
+        let url = blob_store.upload(blob).await?;
+
+        Ok(Self::Blob(TokenBlobReference {
+            url,
+            sha256,
+            key: hex::encode(key),
+        }))
+    }
+
+    /// Message text carrying the delivery
+    pub fn to_message(&self) -> Result<String, Error> {
+        match self {
+            Self::Inline(token) => Ok(token.clone()),
+            Self::Blob(reference) => Ok(format!(
+                "{BLOB_REFERENCE_PREFIX}{}",
+                serde_json::to_string(reference)?
+            )),
+        }
+    }
+
+    /// Parse the delivery carried by a message, if any
+    pub fn from_message(message: &str) -> Option<Self> {
+        match message.trim().strip_prefix(BLOB_REFERENCE_PREFIX) {
+            Some(reference) => serde_json::from_str(reference).ok().map(Self::Blob),
+            None => token_from_text(message).map(|token| Self::Inline(token.to_string())),
+        }
+    }
+
+    /// Token of the delivery, fetching it if it was uploaded
+    pub async fn token(&self) -> Result<String, Error> {
+        match self {
+            Self::Inline(token) => Ok(token.clone()),
+            Self::Blob(reference) => reference.fetch().await,
+        }
+    }
+}
+
+/// Send `token` to `receiver` as a NIP-17 private direct message, returning the
+/// hex id of its gift wrap
+///
+/// Tokens longer than [`MAX_INLINE_TOKEN_LEN`] are uploaded to `blob_store`.
+#[instrument(skip(token, sender, blob_store))]
+pub async fn send_token_nostr(
+    token: &Token,
+    sender: &SecretKey,
+    receiver: &PublicKey,
+    relays: Vec<String>,
+    blob_store: Option<&dyn BlobStore>,
+) -> Result<String, Error> {
+    ensure_cdk!(
+        !relays.is_empty(),
+        Error::Custom("No relays provided".to_string())
+    );
+
+    let message = TokenDelivery::new(token, blob_store).await?.to_message()?;
+
+    let keys = nostr_keys(sender)?;
+    let receiver = nostr_public_key(receiver)?;
+
+    let client = relay_client(&relays, RelayUse::Write).await?;
+
+    let event_id = cdk_nostr::send_private_message(&client, &keys, &receiver, &message)
+        .await
+        .map_err(nostr_error)?;
+
+    Ok(event_id.to_hex())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::mint_url::MintUrl;
+    use crate::nuts::{CurrencyUnit, Id, Proof};
+    use crate::secret::Secret;
+    use crate::Amount;
+
+    /// Keeps uploaded blobs in memory
+    #[derive(Debug, Default)]
+    struct MemoryBlobStore {
+        blobs: Mutex<Vec<Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl BlobStore for MemoryBlobStore {
+        async fn upload(&self, blob: Vec<u8>) -> Result<String, Error> {
+            let mut blobs = self.blobs.lock().unwrap();
+            blobs.push(blob);
+            Ok(format!("https://blossom.example.com/{}", blobs.len()))
+        }
+    }
+
+    fn large_token() -> Token {
+        let keyset_id = Id::from_str("009a1f293253e41e").unwrap();
+        let proofs = (0..2000)
+            .map(|_| {
+                Proof::new(
+                    Amount::from(1),
+                    keyset_id,
+                    Secret::generate(),
+                    SecretKey::generate().public_key(),
+                )
+            })
+            .collect();
+
+        Token::new(
+            MintUrl::from_str("https://mint.example.com").unwrap(),
+            proofs,
+            None,
+            CurrencyUnit::Sat,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_token_blob_roundtrip() {
+        let token = large_token();
+        let blob_store = MemoryBlobStore::default();
+
+        let TokenDelivery::Blob(reference) =
+            TokenDelivery::new(&token, Some(&blob_store)).await.unwrap()
+        else {
+            panic!("Large token is delivered as a blob");
+        };
+
+        let blob = blob_store.blobs.lock().unwrap().pop().unwrap();
+        assert_eq!(reference.decrypt(&blob).unwrap(), token.to_string());
+
+        // A tampered blob no longer matches its hash
+        let mut tampered = blob.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(reference.decrypt(&tampered).is_err());
+
+        // A blob decrypted with another key fails authentication
+        let wrong_key = TokenBlobReference {
+            key: "00".repeat(32),
+            ..reference
+        };
+        assert!(wrong_key.decrypt(&blob).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_large_token_needs_blob_store() {
+        assert!(TokenDelivery::new(&large_token(), None).await.is_err());
+    }
+
+    #[test]
+    fn test_delivery_message_roundtrip() {
+        let reference = TokenBlobReference {
+            url: "https://blossom.example.com/abcd".to_string(),
+            sha256: "abcd".to_string(),
+            key: "00".repeat(32),
+        };
+
+        let delivery = TokenDelivery::Blob(reference);
+        let message = delivery.to_message().unwrap();
+
+        assert_eq!(TokenDelivery::from_message(&message), Some(delivery));
+        assert_eq!(
+            TokenDelivery::from_message("here you go cashuBabc thanks"),
+            Some(TokenDelivery::Inline("cashuBabc".to_string()))
+        );
+        assert_eq!(TokenDelivery::from_message("hello"), None);
+    }
+}
@@ -0,0 +1,161 @@
+//! Token verification without receiving
+//!
+//! Merchants checking a token shown by a customer before releasing goods need
+//! to know whether it is genuine and still unspent without swapping it.
+//! [`verify_token`] needs no wallet: it fetches the keys of the token's mint,
+//! verifies the DLEQ proofs of the token and asks the mint for the state of its
+//! proofs.
+//!
+//! Signatures of proofs without a DLEQ proof cannot be checked without spending
+//! them, unspent tokens with such proofs are [`TokenVerdict::Unverified`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::mint_url::MintUrl;
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::{CheckStateRequest, CurrencyUnit, Id, Keys, State, Token};
+use crate::wallet::{HttpClient, MintConnector};
+use crate::{Amount, Error};
+
+/// Verdict on a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenVerdict {
+    /// Every signature is valid and no proof is spent
+    Unspent,
+    /// No proof is spent, but some proofs carry no DLEQ proof so their
+    /// signatures could not be checked
+    Unverified,
+    /// Some proofs are being spent
+    Pending,
+    /// Some proofs are spent
+    Spent,
+    /// The mint could not be reached or does not know the keysets of the token
+    UnknownMint,
+    /// Some DLEQ proofs do not match the mint keys
+    InvalidSignatures,
+}
+
+/// Outcome of [`verify_token`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenVerification {
+    /// Mint of the token
+    pub mint_url: MintUrl,
+    /// Unit of the token
+    pub unit: Option<CurrencyUnit>,
+    /// Value of the token
+    pub amount: Amount,
+    /// Verdict on the token
+    pub verdict: TokenVerdict,
+    /// Whether every proof carried a DLEQ proof that was verified
+    pub signatures_verified: bool,
+}
+
+/// Verify a token with its mint without receiving it
+///
+/// Returns an error only for malformed tokens, every other outcome is a
+/// [`TokenVerdict`].
+#[instrument(skip_all)]
+pub async fn verify_token(token: &Token) -> Result<TokenVerification, Error> {
+    let mint_url = token.mint_url()?;
+
+    #[cfg(feature = "auth")]
+    let client = HttpClient::new(mint_url, None);
+    #[cfg(not(feature = "auth"))]
+    let client = HttpClient::new(mint_url);
+
+    verify_token_with_client(token, Arc::new(client)).await
+}
+
+/// Verify a token without receiving it, reaching its mint through `client`
+#[instrument(skip_all)]
+pub async fn verify_token_with_client(
+    token: &Token,
+    client: Arc<dyn MintConnector + Send + Sync>,
+) -> Result<TokenVerification, Error> {
+    let mut verification = TokenVerification {
+        mint_url: token.mint_url()?,
+        unit: token.unit(),
+        amount: token.value()?,
+        verdict: TokenVerdict::UnknownMint,
+        signatures_verified: false,
+    };
+
+    let keysets = match client.get_mint_keysets().await {
+        Ok(response) => response.keysets,
+        Err(err) => {
+            tracing::debug!(
+                "Could not get keysets of {}: {}",
+                verification.mint_url,
+                err
+            );
+            return Ok(verification);
+        }
+    };
+
+    let proofs = match token.proofs(&keysets) {
+        Ok(proofs) => proofs,
+        Err(err) => {
+            tracing::debug!("Token keysets are unknown to the mint: {}", err);
+            return Ok(verification);
+        }
+    };
+
+    let mut keys: HashMap<Id, Keys> = HashMap::new();
+    let mut signatures_verified = true;
+
+    for proof in &proofs {
+        if proof.dleq.is_none() {
+            signatures_verified = false;
+            continue;
+        }
+
+        if !keys.contains_key(&proof.keyset_id) {
+            match client.get_mint_keyset(proof.keyset_id).await {
+                Ok(keyset) => {
+                    keys.insert(proof.keyset_id, keyset.keys);
+                }
+                Err(err) => {
+                    tracing::debug!("Could not get keyset {}: {}", proof.keyset_id, err);
+                    return Ok(verification);
+                }
+            }
+        }
+
+        let valid = keys
+            .get(&proof.keyset_id)
+            .and_then(|keys| keys.amount_key(proof.amount))
+            .is_some_and(|key| proof.verify_dleq(key).is_ok());
+
+        if !valid {
+            verification.verdict = TokenVerdict::InvalidSignatures;
+            return Ok(verification);
+        }
+    }
+
+    let states = match client
+        .post_check_state(CheckStateRequest { ys: proofs.ys()? })
+        .await
+    {
+        Ok(response) => response.states,
+        Err(err) => {
+            tracing::debug!("Could not check proof states: {}", err);
+            return Ok(verification);
+        }
+    };
+
+    verification.signatures_verified = signatures_verified;
+    verification.verdict = if states.iter().any(|state| state.state == State::Spent) {
+        TokenVerdict::Spent
+    } else if states.iter().any(|state| state.state == State::Pending) {
+        TokenVerdict::Pending
+    } else if !signatures_verified {
+        TokenVerdict::Unverified
+    } else {
+        TokenVerdict::Unspent
+    };
+
+    Ok(verification)
+}
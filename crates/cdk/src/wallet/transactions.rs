@@ -1,6 +1,14 @@
-use cdk_common::wallet::{Transaction, TransactionDirection, TransactionId};
+use std::collections::HashSet;
+use std::time::Duration;
 
-use crate::{Error, Wallet};
+use cdk_common::wallet::{Transaction, TransactionDirection, TransactionId, TransactionStatus};
+use futures::future::{select, Either};
+use futures::pin_mut;
+use tracing::instrument;
+
+use crate::nuts::nut00::ProofsMethods;
+use crate::nuts::{NotificationPayload, ProofState, PublicKey, State, Token};
+use crate::{ensure_cdk, Error, Wallet, WalletSubscription};
 
 impl Wallet {
     /// List transactions
@@ -54,4 +62,94 @@ impl Wallet {
         self.reclaim_unspent(pending_spent_proofs).await?;
         Ok(())
     }
+
+    /// Track a sent token until the receiver claims it
+    ///
+    /// Resolves once every proof in the token is spent at the mint, marking the
+    /// outgoing transaction for the token as [`TransactionStatus::Confirmed`].
+    /// Returns the updated transaction if one was recorded for the token.
+    ///
+    /// Fails with [`Error::Timeout`] if the token is not claimed within
+    /// `timeout_duration`, leaving the transaction pending so it can be tracked again.
+    #[instrument(skip(self, token))]
+    pub async fn track_sent_token(
+        &self,
+        token: &Token,
+        timeout_duration: Duration,
+    ) -> Result<Option<Transaction>, Error> {
+        ensure_cdk!(self.mint_url == token.mint_url()?, Error::IncorrectMint);
+
+        let keysets_info = self.load_mint_keysets().await?;
+        let proofs = token.proofs(&keysets_info)?;
+        let ys = proofs.ys()?;
+
+        let mut unclaimed: HashSet<PublicKey> = ys.iter().cloned().collect();
+
+        // Subscribe before checking the current state so no transition is missed
+        let mut subscription = self
+            .subscribe(WalletSubscription::ProofState(
+                ys.iter().map(|y| y.to_string()).collect(),
+            ))
+            .await;
+
+        let claimed = async {
+            for proof_state in self.check_proofs_spent(proofs).await? {
+                if proof_state.state == State::Spent {
+                    unclaimed.remove(&proof_state.y);
+                }
+            }
+
+            while !unclaimed.is_empty() {
+                match subscription.recv().await {
+                    Some(NotificationPayload::ProofState(ProofState {
+                        y,
+                        state: State::Spent,
+                        ..
+                    })) => {
+                        unclaimed.remove(&y);
+                    }
+                    Some(_) => (),
+                    None => return Err(Error::Internal),
+                }
+            }
+
+            Ok::<(), Error>(())
+        };
+
+        let expired = async {
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::time::sleep(timeout_duration).await;
+            #[cfg(target_arch = "wasm32")]
+            gloo_timers::future::sleep(timeout_duration).await;
+        };
+
+        pin_mut!(claimed, expired);
+        match select(claimed, expired).await {
+            Either::Left((claimed, _)) => claimed?,
+            Either::Right(_) => {
+                tracing::debug!("Sent token was not claimed within {:?}", timeout_duration);
+                return Err(Error::Timeout);
+            }
+        }
+
+        tracing::debug!("Sent token has been claimed");
+
+        self.localstore.update_proofs(vec![], ys.clone()).await?;
+
+        let transaction_id = TransactionId::new(ys);
+
+        self.claims_vault_remove(transaction_id).await?;
+
+        match self.localstore.get_transaction(transaction_id).await? {
+            Some(mut transaction) => {
+                transaction.status = TransactionStatus::Confirmed;
+                self.localstore.add_transaction(transaction.clone()).await?;
+                Ok(Some(transaction))
+            }
+            None => {
+                tracing::debug!("No transaction recorded for tracked token");
+                Ok(None)
+            }
+        }
+    }
 }
@@ -0,0 +1,88 @@
+//! Embedded mint example
+//!
+//! Runs a complete Cashu mint in a single process:
+//! 1. An in-memory SQLite database stores quotes, proofs and keysets
+//! 2. A fake lightning backend settles every invoice, so no node is needed
+//! 3. The mint is served over HTTP by the `cdk-axum` router
+//!
+//! State is lost when the process exits. Run `wallet-app` against it to see a wallet
+//! go through minting, sending, receiving, melting and restoring.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use bip39::Mnemonic;
+use cdk::mint::{MintBuilder, MintMeltLimits};
+use cdk::nuts::{CurrencyUnit, PaymentMethod};
+use cdk::types::{FeeReserve, QuoteTTL};
+use cdk_fake_wallet::FakeWallet;
+use tracing_subscriber::EnvFilter;
+
+/// Address the mint listens on
+const LISTEN_ADDR: &str = "127.0.0.1:8085";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let env_filter = EnvFilter::new("info,tower_http=warn,hyper=warn");
+    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+
+    // Quotes, proofs and keysets live in memory
+    let localstore = Arc::new(cdk_sqlite::mint::memory::empty().await?);
+
+    // The fake backend pays invoices and reports incoming payments after a short delay
+    let fee_reserve = FeeReserve {
+        min_fee_reserve: 1.into(),
+        percent_fee_reserve: 0.02,
+    };
+    let fake_wallet = FakeWallet::new(
+        fee_reserve,
+        HashMap::default(),
+        HashSet::default(),
+        2,
+        CurrencyUnit::Sat,
+    );
+
+    let mut mint_builder = MintBuilder::new(localstore.clone())
+        .with_name("embedded mint".to_string())
+        .with_description("CDK mint embedded with a fake lightning backend".to_string())
+        .with_urls(vec![format!("http://{LISTEN_ADDR}")]);
+
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::Bolt11,
+            MintMeltLimits::new(1, 100_000),
+            Arc::new(fake_wallet),
+        )
+        .await?;
+
+    // Keys are derived from this seed, a real mint must persist it
+    let mnemonic = Mnemonic::generate(12)?;
+    let mint = mint_builder
+        .build_with_seed(localstore, &mnemonic.to_seed_normalized(""))
+        .await?;
+
+    mint.set_quote_ttl(QuoteTTL::new(600, 120)).await?;
+
+    let mint = Arc::new(mint);
+
+    // Background tasks watch the payment backend for paid mint quotes
+    mint.start().await?;
+
+    let router = cdk_axum::create_mint_router(Arc::clone(&mint), false).await?;
+
+    let listener = tokio::net::TcpListener::bind(LISTEN_ADDR).await?;
+    tracing::info!("Mint listening on http://{}", listener.local_addr()?);
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            tracing::info!("Shutdown signal received");
+        })
+        .await?;
+
+    mint.stop().await?;
+
+    Ok(())
+}
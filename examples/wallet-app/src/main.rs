@@ -0,0 +1,104 @@
+//! Wallet lifecycle example
+//!
+//! Drives wallets through every step of their life against a running mint, by default
+//! the `mint-embedded` example on <http://127.0.0.1:8085>:
+//! 1. Alice mints ecash by paying a lightning invoice
+//! 2. Alice sends part of it to Bob as a token
+//! 3. Bob receives the token
+//! 4. Bob melts ecash to pay a lightning invoice
+//! 5. Alice restores her remaining ecash from her seed into a new wallet
+//!
+//! Pass another mint URL as the first argument to run against a different fake wallet mint.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{ensure, Result};
+use cdk::nuts::nut00::ProofsMethods;
+use cdk::nuts::CurrencyUnit;
+use cdk::wallet::{ReceiveOptions, SendOptions, Wallet};
+use cdk::Amount;
+use cdk_fake_wallet::create_fake_invoice;
+use cdk_sqlite::wallet::memory;
+use rand::random;
+use tracing_subscriber::EnvFilter;
+
+/// Mint used when no URL is given, where `mint-embedded` listens
+const DEFAULT_MINT_URL: &str = "http://127.0.0.1:8085";
+
+/// Create a wallet for `mint_url` with its own in-memory database
+async fn create_wallet(mint_url: &str, seed: [u8; 64]) -> Result<Wallet> {
+    let localstore = Arc::new(memory::empty().await?);
+    Ok(Wallet::new(
+        mint_url,
+        CurrencyUnit::Sat,
+        localstore,
+        seed,
+        None,
+    )?)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let env_filter = EnvFilter::new("warn");
+    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+
+    let mint_url = std::env::args()
+        .nth(1)
+        .unwrap_or(DEFAULT_MINT_URL.to_string());
+
+    let alice_seed = random::<[u8; 64]>();
+    let alice = create_wallet(&mint_url, alice_seed).await?;
+    let bob = create_wallet(&mint_url, random::<[u8; 64]>()).await?;
+
+    // 1. Mint: request a quote, the fake backend pays its invoice
+    let quote = alice.mint_quote(Amount::from(100), None).await?;
+    println!("Alice pays invoice {}", quote.request);
+
+    let proofs = alice
+        .wait_and_mint_quote(
+            quote,
+            Default::default(),
+            Default::default(),
+            Duration::from_secs(30),
+        )
+        .await?;
+    println!("Alice minted {}", proofs.total_amount()?);
+
+    // 2. Send: select proofs worth the amount and encode them as a token
+    let prepared_send = alice
+        .prepare_send(Amount::from(40), SendOptions::default())
+        .await?;
+    let token = prepared_send.confirm(None).await?;
+    println!("Alice sends token {token}");
+
+    // 3. Receive: swap the token's proofs for new ones only Bob knows
+    let received = bob
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await?;
+    println!("Bob received {received}");
+
+    // 4. Melt: pay a lightning invoice with ecash
+    let invoice = create_fake_invoice(20_000, "wallet-app melt".to_string());
+    let melt_quote = bob.melt_quote(invoice.to_string(), None).await?;
+    let melted = bob.melt(&melt_quote.id).await?;
+    println!(
+        "Bob paid {} with {} in fees, {} left",
+        melted.amount,
+        melted.fee_paid,
+        bob.total_balance().await?
+    );
+
+    // 5. Restore: a fresh wallet with Alice's seed finds her unspent proofs at the mint
+    let alice_balance = alice.total_balance().await?;
+    let restored_alice = create_wallet(&mint_url, alice_seed).await?;
+    let restored = restored_alice.restore().await?;
+    println!("Restored {restored} for Alice, who had {alice_balance}");
+
+    ensure!(
+        restored == alice_balance,
+        "Restored {restored} but Alice had {alice_balance}"
+    );
+
+    Ok(())
+}